@@ -9,6 +9,7 @@ DEBIAN_FRONTEND=noninteractive TZ=Etc/UTC apt-get -y install \
     unzip \
     sleuthkit \
     cabextract \
+    qemu-utils \
     curl \
     wget \
     git \
@@ -2,12 +2,14 @@
 
 use aho_corasick::AhoCorasick;
 use log::{debug, error, info, warn};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 #[cfg(windows)]
@@ -16,10 +18,16 @@ use std::os::windows;
 #[cfg(unix)]
 use std::os::unix;
 
-use crate::common::{is_offset_safe, read_file};
+use crate::common::{InputData, crc32, is_offset_safe, read_input};
+use crate::encoded;
 use crate::extractors;
+use crate::index_table;
 use crate::magic;
+use crate::padding;
+use crate::profile;
 use crate::signatures;
+use crate::strings;
+use crate::warnings::{Warning, WarningCode};
 
 /// Returned on initialization error
 #[derive(Debug, Default, Clone)]
@@ -43,8 +51,29 @@ pub struct AnalysisResults {
     /// File signature results, as returned by Binwalk::scan
     pub file_map: Vec<signatures::SignatureResult>,
     /// File extraction results, as returned by Binwalk::extract.
-    /// HashMap key is the corresponding SignatureResult.id value in `file_map`.
+    /// HashMap key is the corresponding SignatureResult.id value in `file_map`; serialized in key order.
+    #[serde(serialize_with = "serialize_sorted")]
     pub extractions: HashMap<String, extractors::ExtractionResult>,
+    /// Printable strings found in regions of the file not identified by any signature, see Binwalk::strings_min_length
+    pub strings: Vec<strings::StringResult>,
+    /// Padding found in regions of the file not identified by any signature, see Binwalk::padding_min_length
+    pub padding: Vec<padding::PaddingResult>,
+    /// Base64 / hex encoded data containing signatures, found in regions of the file not identified by any signature;
+    /// see Binwalk::encoded_min_length
+    pub encoded: Vec<encoded::EncodedResult>,
+    /// Index table of a multi-device firmware image, if one was found; see Binwalk::index_table_layouts
+    pub index_table: Option<index_table::IndexTable>,
+    /// Non-fatal problems encountered during analysis (e.g., a bad checksum or a failed extraction), in addition to
+    /// being logged
+    pub warnings: Vec<Warning>,
+}
+
+/// Serializes a HashMap in key order, so that serialized output does not vary from run to run
+fn serialize_sorted<S: Serializer, V: Serialize>(
+    map: &HashMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
 }
 
 /// Analyze files / memory for file signatures
@@ -83,6 +112,35 @@ pub struct Binwalk {
     pub pattern_signature_table: HashMap<usize, signatures::Signature>,
     /// Maps signatures to their corresponding extractors
     pub extractor_lookup_table: HashMap<String, Option<extractors::Extractor>>,
+    /// Maps external extraction utility names to user-specified paths, see Binwalk::set_extractor_path
+    pub extractor_paths: HashMap<String, PathBuf>,
+    /// If true, Binwalk::analyze_buf verifies signature results with a test extraction, see Binwalk::verify
+    pub verify_signatures: bool,
+    /// If set, Binwalk::analyze_buf reports strings of at least this many characters found in unknown regions of the file
+    pub strings_min_length: Option<usize>,
+    /// If set, Binwalk::analyze_buf reports runs of at least this many identical bytes found in unknown regions of the file
+    pub padding_min_length: Option<usize>,
+    /// If set, Binwalk::analyze_buf decodes base64 / hex encoded data of at least this many characters found in unknown
+    /// regions of the file, and scans the decoded data for signatures
+    pub encoded_min_length: Option<usize>,
+    /// Signature results with a confidence below this level are discarded during scanning; default is CONFIDENCE_LOW
+    pub min_confidence: u8,
+    /// If true, signature result IDs are derived from each result's offset and name rather than randomly generated,
+    /// so that repeated scans of the same data produce identical results
+    pub reproducible: bool,
+    /// If set, time spent validating and extracting each signature type is accumulated here; shared by all clones
+    pub profiler: Option<Arc<profile::Profiler>>,
+    /// If true, a base target file that is a gzip, xz or zstd stream is decompressed before analysis, see
+    /// common::read_input; offsets in its analysis results are then offsets into the decompressed data
+    pub decompress_input: bool,
+    /// If set, Binwalk::scan stops scanning once this many valid signatures have been found, leaving the rest of the
+    /// data unscanned; useful for quick triage
+    pub max_results: Option<usize>,
+    /// Binwalk::analyze_buf looks for an index table of sub-images with any of these layouts (e.g.,
+    /// index_table::default_layouts); no index table is looked for if empty
+    pub index_table_layouts: Vec<index_table::IndexTableLayout>,
+    /// If set, external extractors are killed, and their extraction fails, if they run for longer than this
+    pub extractor_timeout: Option<Duration>,
 }
 
 impl Binwalk {
@@ -240,6 +298,48 @@ impl Binwalk {
         Ok(new_instance)
     }
 
+    /// Override the path to an external extraction utility.
+    ///
+    /// Any external extractor that would run the `name` command will instead run `path`.
+    /// If `path` is a bare file name, it is looked up in the directories listed in `$PATH`.
+    /// Returns an error if `path` does not resolve to an existing file.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use binwalk_ng::Binwalk;
+    ///
+    /// let mut binwalker = Binwalk::new();
+    /// # let temp_dir = tempfile::tempdir().unwrap();
+    /// # let unsquashfs4 = temp_dir.path().join("unsquashfs4");
+    /// # std::fs::write(&unsquashfs4, b"").unwrap();
+    ///
+    /// assert!(binwalker.set_extractor_path("unsquashfs", &unsquashfs4).is_ok());
+    /// assert!(binwalker.set_extractor_path("unsquashfs", "/i/do/not/exist").is_err());
+    /// ```
+    pub fn set_extractor_path(
+        &mut self,
+        name: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<(), BinwalkError> {
+        let path = path.as_ref();
+
+        match resolve_executable(path) {
+            None => Err(BinwalkError::new(&format!(
+                "Extractor path '{}' for '{name}' does not exist",
+                path.display()
+            ))),
+            Some(resolved_path) => {
+                debug!(
+                    "Using {} for external extractor '{name}'",
+                    resolved_path.display()
+                );
+                self.extractor_paths.insert(name.to_string(), resolved_path);
+                Ok(())
+            }
+        }
+    }
+
     /// Scan a file for magic signatures.
     /// Returns a list of validated magic signatures representing the known contents of the file.
     ///
@@ -262,9 +362,15 @@ impl Binwalk {
     /// assert!(signature_results.len() > 0);
     /// ```
     pub fn scan(&self, file_data: &[u8]) -> Vec<signatures::SignatureResult> {
+        let (file_map, _) = self.scan_with_limit(file_data);
+        file_map
+    }
+
+    /// Scans a file for magic signatures, reporting at most Binwalk::max_results of them.
+    /// Returns the validated signatures, and true if more signatures were found than were reported.
+    fn scan_with_limit(&self, file_data: &[u8]) -> (Vec<signatures::SignatureResult>, bool) {
         const FILE_START_OFFSET: usize = 0;
 
-        let mut index_adjustment: usize = 0;
         let mut next_valid_offset: usize = 0;
         let mut previous_valid_offset = None;
 
@@ -291,9 +397,18 @@ impl Binwalk {
                         signature.description, magic_start
                     );
 
-                    if let Ok(mut signature_result) = (signature.parser)(file_data, magic_start) {
+                    if let Ok(mut signature_result) =
+                        self.run_parser(signature, file_data, magic_start)
+                        && signature_result.confidence >= self.min_confidence
+                    {
                         // Auto populate some signature result fields
-                        signature_result_auto_populate(&mut signature_result, signature);
+                        signature_result_auto_populate(
+                            &mut signature_result,
+                            signature,
+                            self.reproducible,
+                        );
+                        signature_result.extractor =
+                            self.extractor_name(&signature_result, signature);
 
                         // Add this signature to the file map
                         file_map.push(signature_result.clone());
@@ -334,7 +449,13 @@ impl Binwalk {
          *  1) next_valid_offset exceeds available_data
          *  2) previous_valid_offset <= next_valid_offset
          */
-        while is_offset_safe(available_data, next_valid_offset, previous_valid_offset) {
+        'scan: while is_offset_safe(available_data, next_valid_offset, previous_valid_offset) {
+            // Stop early if enough signatures have already been found
+            if self.scan_satisfied(&file_map, available_data) {
+                debug!("Found {} signatures, stopping scan", file_map.len());
+                break;
+            }
+
             // Update the previous valid offset in praparation for the next loop iteration
             previous_valid_offset = Some(next_valid_offset);
 
@@ -367,11 +488,26 @@ impl Binwalk {
                     signature.description, magic_offset
                 );
 
+                // Signatures that only start on a block boundary need not be validated anywhere else
+                if let Some(alignment) = signature.magic_alignment
+                    && !magic_offset
+                        .checked_sub(signature.magic_offset)
+                        .is_some_and(|start| start.is_multiple_of(alignment))
+                {
+                    debug!(
+                        "{} magic match at offset {:#X} is not {alignment}-byte aligned; skipping",
+                        signature.description, magic_offset
+                    );
+                    continue;
+                }
+
                 /*
                  * Invoke the signature parser to parse and validate the signature.
                  * An error indicates a false positive match for the signature type.
                  */
-                if let Ok(mut signature_result) = (signature.parser)(file_data, magic_offset) {
+                if let Ok(mut signature_result) =
+                    self.run_parser(&signature, file_data, magic_offset)
+                {
                     // Calculate the end of this signature's data
                     let signature_end_offset = signature_result.offset + signature_result.size;
 
@@ -382,8 +518,22 @@ impl Binwalk {
                         continue;
                     }
 
+                    // Drop signatures that the user has asked not to see
+                    if signature_result.confidence < self.min_confidence {
+                        info!(
+                            "Signature {} at offset {:#X} is below the minimum confidence; ignoring",
+                            signature.name, signature_result.offset
+                        );
+                        continue;
+                    }
+
                     // Auto populate some signature result fields
-                    signature_result_auto_populate(&mut signature_result, &signature);
+                    signature_result_auto_populate(
+                        &mut signature_result,
+                        &signature,
+                        self.reproducible,
+                    );
+                    signature_result.extractor = self.extractor_name(&signature_result, &signature);
 
                     // Add this signature to the file map
                     file_map.push(signature_result.clone());
@@ -393,6 +543,11 @@ impl Binwalk {
                         signature_result.name, signature_result.offset
                     );
 
+                    if self.scan_satisfied(&file_map, available_data) {
+                        debug!("Found {} signatures, stopping scan", file_map.len());
+                        break 'scan;
+                    }
+
                     // Only update the next_valid_offset if confidence is at least medium
                     if signature_result.confidence >= signatures::CONFIDENCE_MEDIUM {
                         // Only update the next_valid offset if the end of the signature reported the size of its contents
@@ -415,94 +570,79 @@ impl Binwalk {
 
         debug!("Aho-Corasick scan found {} magic matches", file_map.len());
 
-        /*
-         * A file's magic bytes do not always start at the beginning of a file, meaning that it is possible
-         * that the order in which the signatures were found in the file data is not the order in which we
-         * want to process/validate the signatures. Each signature's parser function will report the correct
-         * starting offset for the signature, so sort the file_map by the SignatureResult.offset value.
-         */
-        file_map.sort_by_key(|e| e.offset);
-        next_valid_offset = 0;
+        remove_conflicting_signatures(&mut file_map, available_data);
 
         /*
-         * Now that signatures are in the correct order, identify and any overlapping signatures
-         * (such as gzip files identified within a tarball archive), signatures with the same reported offset,
-         * and any signatures with an invalid reported size (i.e., the size extends beyond the end of available file_data).
+         * Many devices store multiple full firmware images back-to-back (e.g., A/B dual-bank layouts). Since the scan
+         * resumes precisely at the end of each bounded image, any high confidence firmware image or file system that
+         * starts exactly where a previous one of the same type ends is reported as a sibling of the first image in that
+         * run; other back-to-back signatures (e.g., a JPEG followed by a PNG) are not banks. Only sizes reported by the
+         * signature parsers are considered; inferred sizes (below) would make every signature look back-to-back.
          */
-        for mut i in 0..file_map.len() {
-            // Some entries may have been removed from the file_map list in previous loop iterations; adjust the index accordingly
-            i -= index_adjustment;
-
-            // Make sure the file map index is valid
-            if file_map.is_empty() || i >= file_map.len() {
-                break;
-            }
-
-            let this_signature = file_map[i].clone();
-            let remaining_available_size = file_data.len() - this_signature.offset;
-
-            // Check if the previous file map entry had the same reported starting offset as this one
-            if i > 0 && this_signature.offset == file_map[i - 1].offset {
-                // Get the previous signature in the file map
-                let previous_signature = file_map[i - 1].clone();
-
-                // If this file map entry and the conflicting entry do not have the same confidence level, default to the one with highest confidence
-                if this_signature.confidence != previous_signature.confidence {
-                    debug!(
-                        "Conflicting signatures at offset {:#X}; defaulting to the signature with highest confidence",
-                        this_signature.offset
-                    );
-
-                    // If this signature is higher confidence, invalidate the previous signature
-                    if this_signature.confidence > previous_signature.confidence {
-                        file_map.remove(i - 1);
-                        index_adjustment += 1;
-
-                    // Else, this signature has a lower confidence; invalidate this signature and continue to the next signature in the list
-                    } else {
-                        file_map.remove(i);
-                        index_adjustment += 1;
-                        continue;
-                    }
-
-                // Conflicting signatures have identical confidence levels; defer to the previously vetted signature
-                } else {
-                    debug!(
-                        "Conflicting signatures at offset {:#X} with the same confidence; first come, first served",
-                        this_signature.offset
-                    );
-                    file_map.remove(i);
-                    index_adjustment += 1;
-                    continue;
-                }
-
-            // Else, if the offsets don't conflict, make sure this signature doesn't fall inside a previously identified signature's data
-            } else if this_signature.offset < next_valid_offset {
-                debug!(
-                    "Signature {} at offset {:#X} contains conflicting data; ignoring",
-                    this_signature.name, this_signature.offset
-                );
-                file_map.remove(i);
-                index_adjustment += 1;
-                continue;
-            }
-
-            // If we've made it this far, make sure this signature's data doesn't extend beyond EOF and that the file data doesn't wrap around
-            if this_signature.size > remaining_available_size
-                || ((this_signature.offset + this_signature.size) as isize) < 0
+        const SIBLING_IMAGE_SIGNATURES: &[&str] = &[
+            "android_bootimg",
+            "apfs",
+            "arcadyan",
+            "autel",
+            "binhdr",
+            "btrfs",
+            "chk",
+            "cramfs",
+            "csman",
+            "dlink_fw",
+            "dlink_tlv",
+            "dlob",
+            "dms",
+            "ext",
+            "fat",
+            "iso9660",
+            "jboot_arm",
+            "jboot_sch2",
+            "jboot_stag",
+            "jffs2",
+            "logfs",
+            "matter_ota",
+            "mh01",
+            "ntfs",
+            "packimg",
+            "program_store",
+            "qnx_ifs",
+            "romfs",
+            "rtk",
+            "seama",
+            "shrs",
+            "squashfs",
+            "tplink",
+            "tplink_rtos",
+            "trx",
+            "ubi",
+            "ubifs",
+            "uefi_capsule",
+            "uimage",
+            "wince",
+            "yaffs",
+        ];
+
+        for i in 1..file_map.len() {
+            let previous_signature = &file_map[i - 1];
+
+            if previous_signature.size > 0
+                && previous_signature.name == file_map[i].name
+                && SIBLING_IMAGE_SIGNATURES.contains(&file_map[i].name.as_str())
+                && previous_signature.confidence >= signatures::CONFIDENCE_HIGH
+                && file_map[i].confidence >= signatures::CONFIDENCE_HIGH
+                && previous_signature.offset + previous_signature.size == file_map[i].offset
             {
-                debug!(
-                    "Signature {} at offset {:#X} claims its size extends beyond EOF; ignoring",
-                    this_signature.name, this_signature.offset
+                let first_image_offset = previous_signature
+                    .sibling_offset
+                    .unwrap_or(previous_signature.offset);
+
+                info!(
+                    "Signature {} at offset {:#X} is a sibling image of offset {:#X}",
+                    file_map[i].name, file_map[i].offset, first_image_offset
                 );
-                file_map.remove(i);
-                index_adjustment += 1;
-                continue;
-            }
 
-            // This signature looks OK, update the next_valid_offset to be the end of this signature's data, only if we're fairly confident in the signature
-            if this_signature.confidence >= signatures::CONFIDENCE_MEDIUM {
-                next_valid_offset = this_signature.offset + this_signature.size;
+                file_map[i].sibling_offset = Some(first_image_offset);
             }
         }
 
@@ -556,7 +696,28 @@ impl Binwalk {
 
         debug!("Found {} valid signatures", file_map.len());
 
-        file_map
+        // The scan goes one signature past max_results, to tell whether there was anything more to report
+        let truncated = self
+            .max_results
+            .is_some_and(|max_results| file_map.len() > max_results);
+
+        if let Some(max_results) = self.max_results {
+            file_map.truncate(max_results);
+        }
+
+        (file_map, truncated)
+    }
+
+    /// Returns true if more signatures have been found than will be reported, so that scanning can stop; see
+    /// Binwalk::max_results. Only signatures that survive conflict resolution are counted.
+    fn scan_satisfied(&self, file_map: &[signatures::SignatureResult], file_size: usize) -> bool {
+        self.max_results.is_some_and(|max_results| {
+            file_map.len() > max_results && {
+                let mut reported = file_map.to_vec();
+                remove_conflicting_signatures(&mut reported, file_size);
+                reported.len() > max_results
+            }
+        })
     }
 
     /// Extract all extractable signatures found in a file.
@@ -613,15 +774,34 @@ impl Binwalk {
                 continue;
             }
 
-            // Get the extractor for this signature
-            let extractor = self.extractor_lookup_table[&signature.name].clone();
+            // Get the extractor for this signature; extractors that defer picking the utility to run do so now
+            let mut extractor = self.extractor_lookup_table[&signature.name]
+                .as_ref()
+                .map(|default_extractor| default_extractor.resolve(file_data, signature));
+
+            let mut signature = signature.clone();
+            signature.preferred_extractor = signature
+                .preferred_extractor
+                .as_ref()
+                .map(|preferred_extractor| preferred_extractor.resolve(file_data, &signature));
+
+            // Apply any user-specified external extractor paths to both the default and preferred extractors
+            if let Some(default_extractor) = extractor.as_mut() {
+                self.apply_extractor_path(default_extractor);
+                self.apply_extractor_timeout(default_extractor);
+            }
+            if let Some(preferred_extractor) = signature.preferred_extractor.as_mut() {
+                self.apply_extractor_path(preferred_extractor);
+                self.apply_extractor_timeout(preferred_extractor);
+            }
+            let signature = &signature;
 
             match &extractor {
                 None => continue,
                 Some(_) => {
                     // Run an extraction for this signature
                     let mut extraction_result =
-                        extractors::execute(file_data, file_path, signature, &extractor);
+                        self.run_extractor(file_data, file_path, signature, &extractor);
 
                     if !extraction_result.success {
                         debug!(
@@ -650,7 +830,7 @@ impl Binwalk {
                             );
 
                             // Re-run the extraction
-                            extraction_result = extractors::execute(
+                            extraction_result = self.run_extractor(
                                 file_data,
                                 file_path,
                                 &new_signature,
@@ -668,6 +848,128 @@ impl Binwalk {
         extraction_results
     }
 
+    /// Invokes a signature's parser, timing it if profiling is enabled
+    fn run_parser(
+        &self,
+        signature: &signatures::Signature,
+        file_data: &[u8],
+        offset: usize,
+    ) -> Result<signatures::SignatureResult, signatures::SignatureError> {
+        self.profiler.as_ref().map_or_else(
+            || (signature.parser)(file_data, offset),
+            |profiler| {
+                profiler.time_validation(&signature.name, || (signature.parser)(file_data, offset))
+            },
+        )
+    }
+
+    /// Runs an extractor for a signature result, timing it if profiling is enabled
+    fn run_extractor(
+        &self,
+        file_data: &[u8],
+        file_path: &Path,
+        signature: &signatures::SignatureResult,
+        extractor: &Option<extractors::Extractor>,
+    ) -> extractors::ExtractionResult {
+        self.profiler.as_ref().map_or_else(
+            || extractors::execute(file_data, file_path, signature, extractor),
+            |profiler| {
+                profiler.time_extraction(&signature.name, || {
+                    extractors::execute(file_data, file_path, signature, extractor)
+                })
+            },
+        )
+    }
+
+    /// Verify signature results by running their internal extractors in dry-run mode (no output is written to disk).
+    ///
+    /// Signature results that fail verification are downgraded to low confidence; those that pass have their size
+    /// updated to the size reported by the extractor. Signatures with external extractors, or none at all, are left as-is.
+    /// Returns a warning for each signature that failed verification.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use binwalk_ng::Binwalk;
+    /// use binwalk_ng::signatures::CONFIDENCE_HIGH;
+    ///
+    /// let file_data = std::fs::read("tests/inputs/gzip.bin").expect("Unable to read file");
+    ///
+    /// let binwalker = Binwalk::new();
+    /// let mut file_map = binwalker.scan(&file_data);
+    ///
+    /// let warnings = binwalker.verify(&file_data, &mut file_map);
+    ///
+    /// assert!(warnings.is_empty());
+    /// assert_eq!(file_map.len(), 1);
+    /// assert_eq!(file_map[0].confidence, CONFIDENCE_HIGH);
+    /// ```
+    pub fn verify(
+        &self,
+        file_data: &[u8],
+        file_map: &mut [signatures::SignatureResult],
+    ) -> Vec<Warning> {
+        let mut warnings: Vec<Warning> = Vec::new();
+
+        for signature in file_map.iter_mut() {
+            if signature.extraction_declined {
+                continue;
+            }
+
+            // Signatures may specify a preferred extractor, which overrides the default extractor
+            let extractor = signature
+                .preferred_extractor
+                .clone()
+                .or_else(|| {
+                    self.extractor_lookup_table
+                        .get(&signature.name)
+                        .cloned()
+                        .flatten()
+                })
+                .map(|extractor| extractor.resolve(file_data, signature));
+
+            // Only internal extractors support dry runs
+            let Some(extractors::Extractor {
+                utility: extractors::ExtractorType::Internal(extractor_function),
+                ..
+            }) = extractor
+            else {
+                continue;
+            };
+
+            let dry_run = extractor_function(file_data, signature.offset, None);
+
+            if !dry_run.success {
+                warn!(
+                    "Signature {} at offset {:#X} failed verification",
+                    signature.name, signature.offset
+                );
+                signature.confidence = signatures::CONFIDENCE_LOW;
+                signature.description = format!("{}, verification failed", signature.description);
+                warnings.push(Warning::for_signature(
+                    WarningCode::VerificationFailed,
+                    signature,
+                    format!(
+                        "Signature {} at offset {:#X} failed verification",
+                        signature.name, signature.offset
+                    ),
+                ));
+            } else if let Some(verified_size) = dry_run.size
+                && verified_size > 0
+                && verified_size != signature.size
+                && signature.offset + verified_size <= file_data.len()
+            {
+                info!(
+                    "Signature {} at offset {:#X} verified; size adjusted from {:#X} to {:#X} bytes",
+                    signature.name, signature.offset, signature.size, verified_size
+                );
+                signature.size = verified_size;
+            }
+        }
+
+        warnings
+    }
+
     /// Analyze a data buffer and optionally extract the file contents.
     ///
     /// ## Example
@@ -721,7 +1023,103 @@ impl Binwalk {
 
         // Scan file data for signatures
         debug!("Analysis start: {}", file_path.display());
-        results.file_map = self.scan(file_data);
+        let (file_map, truncated) = self.scan_with_limit(file_data);
+        results.file_map = file_map;
+
+        // Let the user know that there is more to find
+        if truncated {
+            let message = format!(
+                "Scan of {} stopped after {} signature results; the remaining data was not scanned",
+                file_path.display(),
+                results.file_map.len()
+            );
+            info!("{message}");
+            results
+                .warnings
+                .push(Warning::new(WarningCode::ResultsTruncated, message));
+        }
+
+        // Report signatures whose data is intact but whose trailer is not (e.g., a truncated stream or bad checksum), and
+        // signatures whose header declares more data than is available
+        for signature in &results.file_map {
+            if let Some(trailer_error) = &signature.trailer_error {
+                let message = format!(
+                    "Signature {} at offset {:#X} has an invalid trailer: {trailer_error}",
+                    signature.name, signature.offset
+                );
+                warn!("{message}");
+                results.warnings.push(Warning::for_signature(
+                    WarningCode::InvalidTrailer,
+                    signature,
+                    message,
+                ));
+            }
+
+            if let Some(declared_size) = signature.declared_size {
+                let message = format!(
+                    "Signature {} at offset {:#X} is truncated: declared {declared_size} bytes, available {} bytes",
+                    signature.name, signature.offset, signature.size
+                );
+                warn!("{message}");
+                results.warnings.push(Warning::for_signature(
+                    WarningCode::Truncated,
+                    signature,
+                    message,
+                ));
+            }
+        }
+
+        // Confirm signature results with a test extraction, if requested
+        if self.verify_signatures {
+            let warnings = self.verify(file_data, &mut results.file_map);
+            results.warnings.extend(warnings);
+        }
+
+        // Identify padding in any data that no signature accounted for, if requested
+        if let Some(min_length) = self.padding_min_length {
+            results.padding = padding::find_padding(file_data, &results.file_map, min_length);
+        }
+
+        // Pull printable strings out of any data that no signature accounted for, if requested
+        if let Some(min_length) = self.strings_min_length {
+            results.strings = strings::find_strings(file_data, &results.file_map, min_length);
+
+            if results.strings.len() >= strings::MAX_STRINGS {
+                let message = format!(
+                    "Reporting only the first {} strings found in {}",
+                    strings::MAX_STRINGS,
+                    file_path.display()
+                );
+                warn!("{message}");
+                results
+                    .warnings
+                    .push(Warning::new(WarningCode::ResultsTruncated, message));
+            }
+        }
+
+        // Look for an index table of sub-images, if requested
+        if !self.index_table_layouts.is_empty() {
+            results.index_table =
+                index_table::find_index_table(file_data, &self.index_table_layouts);
+        }
+
+        // Scan any encoded data that no signature accounted for, if requested
+        if let Some(min_length) = self.encoded_min_length {
+            results.encoded = encoded::find_encoded_blobs(file_data, &results.file_map, min_length)
+                .into_iter()
+                .filter_map(|blob| {
+                    let decoded_file_map = self.scan(&blob.decoded);
+
+                    (!decoded_file_map.is_empty()).then_some(encoded::EncodedResult {
+                        offset: blob.offset,
+                        size: blob.size,
+                        encoding: blob.encoding,
+                        decoded_size: blob.decoded.len(),
+                        file_map: decoded_file_map,
+                    })
+                })
+                .collect();
+        }
 
         // Only extract if told to, and if there were some signatures found in this file
         if do_extraction && !results.file_map.is_empty() {
@@ -731,6 +1129,25 @@ impl Binwalk {
                 results.file_map.len()
             );
             results.extractions = self.extract(file_data, file_path, &results.file_map);
+
+            for signature in &results.file_map {
+                if results
+                    .extractions
+                    .get(&signature.id)
+                    .is_some_and(|extraction| !extraction.success)
+                {
+                    let message = format!(
+                        "Extraction of {} at offset {:#X} failed",
+                        signature.name, signature.offset
+                    );
+                    warn!("{message}");
+                    results.warnings.push(Warning::for_signature(
+                        WarningCode::ExtractionFailed,
+                        signature,
+                        message,
+                    ));
+                }
+            }
         }
 
         debug!("Analysis end: {}", file_path.display());
@@ -776,13 +1193,79 @@ impl Binwalk {
     pub fn analyze(&self, target_file: impl AsRef<Path>, do_extraction: bool) -> AnalysisResults {
         let file_path = target_file.as_ref();
 
-        let file_data = read_file(file_path).unwrap_or_else(|_| {
+        let decompress = self.decompress_input && file_path == self.base_target_file;
+        let file_data = read_input(file_path, decompress).unwrap_or_else(|_| {
             error!("Failed to read data from {}", file_path.display());
-            b"".to_vec()
+            InputData::default()
         });
 
         self.analyze_buf(&file_data, file_path, do_extraction)
     }
+
+    /// Returns the name of the extractor that Binwalk::extract would run for a signature result, if any.
+    fn extractor_name(
+        &self,
+        signature_result: &signatures::SignatureResult,
+        signature: &signatures::Signature,
+    ) -> Option<String> {
+        if signature_result.extraction_declined {
+            return None;
+        }
+
+        // Preferred extractors are only used if the signature defines a default extractor, see extractors::execute
+        signature.extractor.as_ref()?;
+
+        let mut extractor = signature_result
+            .preferred_extractor
+            .clone()
+            .or_else(|| signature.extractor.clone())?;
+        self.apply_extractor_path(&mut extractor);
+
+        match extractor.utility {
+            extractors::ExtractorType::None => None,
+            extractors::ExtractorType::IdentifyOnly => {
+                Some(extractors::IDENTIFY_ONLY_EXTRACTOR.to_string())
+            }
+            extractors::ExtractorType::Internal(_) | extractors::ExtractorType::Carve(_) => {
+                Some(format!("{}_built_in", signature.name))
+            }
+            extractors::ExtractorType::External(command) => Some(command),
+        }
+    }
+
+    /// Replaces an external extractor's command with the user-specified path for that command, if any.
+    fn apply_extractor_path(&self, extractor: &mut extractors::Extractor) {
+        if let extractors::ExtractorType::External(command) = &extractor.utility
+            && let Some(path) = self.extractor_paths.get(command)
+        {
+            extractor.utility = extractors::ExtractorType::External(path.display().to_string());
+        }
+    }
+
+    /// Limits an extractor's run time to Binwalk.extractor_timeout, if set; an extractor's own, shorter, timeout is kept.
+    fn apply_extractor_timeout(&self, extractor: &mut extractors::Extractor) {
+        if let Some(timeout) = self.extractor_timeout {
+            extractor.timeout = Some(extractor.timeout.map_or(timeout, |own| own.min(timeout)));
+        }
+    }
+}
+
+/// Resolves the path to an executable file; bare file names are searched for in `$PATH`.
+fn resolve_executable(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return path::absolute(path).ok();
+    }
+
+    // Only bare file names (no directory components) are looked up in $PATH
+    if path.components().count() != 1 {
+        return None;
+    }
+
+    std::env::var_os("PATH").and_then(|search_paths| {
+        std::env::split_paths(&search_paths)
+            .map(|directory| directory.join(path))
+            .find(|candidate| candidate.is_file())
+    })
 }
 
 /// Initializes the extraction output directory
@@ -887,12 +1370,138 @@ fn include_signature(
     true
 }
 
+/// Sorts the file map by offset, and removes overlapping signatures, signatures with the same offset as a higher (or
+/// equal) confidence signature, and signatures that extend beyond the end of the file.
+fn remove_conflicting_signatures(
+    file_map: &mut Vec<signatures::SignatureResult>,
+    file_size: usize,
+) {
+    /*
+     * A file's magic bytes do not always start at the beginning of a file, meaning that it is possible
+     * that the order in which the signatures were found in the file data is not the order in which we
+     * want to process/validate the signatures. Each signature's parser function will report the correct
+     * starting offset for the signature, so sort the file_map by the SignatureResult.offset value.
+     */
+    file_map.sort_by_key(|e| e.offset);
+
+    let mut next_valid_offset: usize = 0;
+    let mut index_adjustment: usize = 0;
+
+    /*
+     * Now that signatures are in the correct order, identify and any overlapping signatures
+     * (such as gzip files identified within a tarball archive), signatures with the same reported offset,
+     * and any signatures with an invalid reported size (i.e., the size extends beyond the end of available file_data).
+     */
+    for mut i in 0..file_map.len() {
+        // Some entries may have been removed from the file_map list in previous loop iterations; adjust the index accordingly
+        i -= index_adjustment;
+
+        // Make sure the file map index is valid
+        if file_map.is_empty() || i >= file_map.len() {
+            break;
+        }
+
+        let this_signature = file_map[i].clone();
+        let remaining_available_size = file_size - this_signature.offset;
+
+        // Check if the previous file map entry had the same reported starting offset as this one
+        if i > 0 && this_signature.offset == file_map[i - 1].offset {
+            // Get the previous signature in the file map
+            let previous_signature = file_map[i - 1].clone();
+
+            // If this file map entry and the conflicting entry do not have the same confidence level, default to the one with highest confidence
+            if this_signature.confidence != previous_signature.confidence {
+                debug!(
+                    "Conflicting signatures at offset {:#X}; defaulting to the signature with highest confidence",
+                    this_signature.offset
+                );
+
+                // If this signature is higher confidence, invalidate the previous signature
+                if this_signature.confidence > previous_signature.confidence {
+                    file_map.remove(i - 1);
+                    index_adjustment += 1;
+
+                // Else, this signature has a lower confidence; invalidate this signature and continue to the next signature in the list
+                } else {
+                    file_map.remove(i);
+                    index_adjustment += 1;
+                    continue;
+                }
+
+            // Conflicting signatures have identical confidence levels; defer to the previously vetted signature
+            } else {
+                debug!(
+                    "Conflicting signatures at offset {:#X} with the same confidence; first come, first served",
+                    this_signature.offset
+                );
+                file_map.remove(i);
+                index_adjustment += 1;
+                continue;
+            }
+
+        // Else, if the offsets don't conflict, make sure this signature doesn't fall inside a previously identified signature's data
+        } else if this_signature.offset < next_valid_offset {
+            debug!(
+                "Signature {} at offset {:#X} contains conflicting data; ignoring",
+                this_signature.name, this_signature.offset
+            );
+            file_map.remove(i);
+            index_adjustment += 1;
+            continue;
+        }
+
+        // If we've made it this far, make sure this signature's data doesn't extend beyond EOF and that the file data doesn't wrap around
+        if this_signature.size > remaining_available_size
+            || ((this_signature.offset + this_signature.size) as isize) < 0
+        {
+            debug!(
+                "Signature {} at offset {:#X} claims its size extends beyond EOF; ignoring",
+                this_signature.name, this_signature.offset
+            );
+            file_map.remove(i);
+            index_adjustment += 1;
+            continue;
+        }
+
+        // This signature looks OK, update the next_valid_offset to be the end of this signature's data, only if we're fairly confident in the signature
+        if this_signature.confidence >= signatures::CONFIDENCE_MEDIUM {
+            next_valid_offset = this_signature.offset + this_signature.size;
+        }
+    }
+}
+
 /// Some SignatureResult fields need to be auto-populated.
 fn signature_result_auto_populate(
     signature_result: &mut signatures::SignatureResult,
     signature: &signatures::Signature,
+    reproducible: bool,
 ) {
-    signature_result.id = Uuid::new_v4().to_string();
+    signature_result.id =
+        signature_result_id(signature_result.offset, &signature.name, reproducible);
     signature_result.name = signature.name.clone();
     signature_result.always_display = signature.always_display;
+
+    if signature_result.description.is_empty() {
+        signature_result.description = signature.description.clone();
+    }
+
+    populate_children(signature_result, reproducible);
+}
+
+/// Assigns IDs to a signature result's children (and theirs), linking each child back to its parent
+fn populate_children(parent: &mut signatures::SignatureResult, reproducible: bool) {
+    for child in parent.children.iter_mut() {
+        child.id = signature_result_id(child.offset, &child.name, reproducible);
+        child.parent_id = Some(parent.id.clone());
+        populate_children(child, reproducible);
+    }
+}
+
+/// Generates a signature result ID; reproducible IDs are derived from the result's offset and name
+fn signature_result_id(offset: usize, name: &str, reproducible: bool) -> String {
+    if reproducible {
+        Uuid::from_u64_pair(offset as u64, crc32(name.as_bytes()) as u64).to_string()
+    } else {
+        Uuid::new_v4().to_string()
+    }
 }
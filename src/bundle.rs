@@ -0,0 +1,112 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+
+/// Extension appended to the bundle file name for its index file
+const INDEX_EXTENSION: &str = "index.json";
+
+/// Describes a single region of data in a carve bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleEntry {
+    /// Offset of the region's data in the bundle file
+    pub bundle_offset: u64,
+    /// Size of the region, in bytes
+    pub size: usize,
+    /// The file the region was carved from
+    pub source_file: PathBuf,
+    /// Offset of the region in the source file
+    pub source_offset: usize,
+    /// The signature name of the region's data, or "unknown" / "padding" for unidentified data
+    pub name: String,
+    /// Signature description; empty for unidentified data
+    pub description: String,
+}
+
+/// Carved data regions, concatenated into a single file, plus an index of where each region came from
+#[derive(Debug)]
+pub struct CarveBundle {
+    bundle_file: PathBuf,
+    writer: io::BufWriter<fs::File>,
+    index: Vec<BundleEntry>,
+}
+
+impl CarveBundle {
+    /// Creates (or truncates) the bundle file
+    pub fn create(bundle_file: impl AsRef<Path>) -> io::Result<Self> {
+        let bundle_file = bundle_file.as_ref().to_path_buf();
+        let writer = io::BufWriter::new(fs::File::create(&bundle_file)?);
+
+        Ok(Self {
+            bundle_file,
+            writer,
+            index: Vec::new(),
+        })
+    }
+
+    /// Appends a region of file data to the end of the bundle
+    pub fn append(
+        &mut self,
+        source_file: impl AsRef<Path>,
+        file_data: &[u8],
+        offset: usize,
+        size: usize,
+        name: &str,
+        description: &str,
+    ) -> io::Result<()> {
+        let data = offset
+            .checked_add(size)
+            .and_then(|end| file_data.get(offset..end))
+            .ok_or(io::ErrorKind::UnexpectedEof)?;
+
+        // A failed append may have partially written its data, so the bundle offset is taken from the bundle itself
+        let bundle_offset = self.writer.stream_position()?;
+
+        self.writer.write_all(data)?;
+
+        self.index.push(BundleEntry {
+            bundle_offset,
+            size,
+            source_file: source_file.as_ref().to_path_buf(),
+            source_offset: offset,
+            name: name.to_string(),
+            description: description.to_string(),
+        });
+
+        Ok(())
+    }
+
+    /// Flushes the bundle data to disk and writes the index to `<bundle file>.index.json`; index entries are sorted by
+    /// source file, then by source offset
+    pub fn close(&mut self) {
+        let mut index_file = self.bundle_file.clone().into_os_string();
+        index_file.push(".");
+        index_file.push(INDEX_EXTENSION);
+
+        if let Err(e) = self.writer.flush() {
+            error!(
+                "Failed to write carve bundle '{}': {e}",
+                self.bundle_file.display()
+            );
+        }
+
+        // Files are analyzed, and their regions appended, in no particular order
+        self.index.sort_by(|a, b| {
+            (&a.source_file, a.source_offset).cmp(&(&b.source_file, b.source_offset))
+        });
+
+        match serde_json::to_string_pretty(&self.index) {
+            Err(e) => error!("Failed to convert carve bundle index to JSON: {e}"),
+            Ok(json) => {
+                if let Err(e) = fs::write(&index_file, json) {
+                    error!(
+                        "Failed to write carve bundle index '{}': {e}",
+                        PathBuf::from(index_file).display()
+                    );
+                }
+            }
+        }
+    }
+}
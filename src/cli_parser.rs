@@ -2,6 +2,8 @@ use std::path::PathBuf;
 
 use clap::Parser;
 
+use binwalk_ng::signatures::{CONFIDENCE_HIGH, CONFIDENCE_LOW, CONFIDENCE_MEDIUM};
+
 #[derive(Parser, Debug)]
 #[command(
     version,
@@ -27,6 +29,11 @@ pub struct CliArgs {
     #[arg(short, long)]
     pub quiet: bool,
 
+    /// Disable colorized output
+    /// (also disabled when stdout is not a terminal or the NO_COLOR environment variable is set)
+    #[arg(long)]
+    pub no_color: bool,
+
     /// During recursive extraction display *all* results
     #[arg(short, long)]
     pub verbose: bool,
@@ -36,13 +43,104 @@ pub struct CliArgs {
     pub extract: bool,
 
     /// Carve both known and unknown file contents to disk
+    /// (no extractors are run unless --extract is also specified)
     #[arg(short, long)]
     pub carve: bool,
 
+    /// When carving, leave long runs of zero bytes as holes in the carved files
+    #[arg(long, requires = "carve")]
+    pub sparse: bool,
+
+    /// Carve both known and unknown file contents into a single FILE, indexed in FILE.index.json
+    /// (index entries give each region's offset in FILE along with its original file and offset)
+    #[arg(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath)]
+    pub carve_bundle: Option<PathBuf>,
+
+    /// Never run extractors for known file types, even if --extract is specified
+    #[arg(long)]
+    pub no_extract_known: bool,
+
+    /// Confirm identified file types with a test extraction, discarding the output
+    #[arg(long)]
+    pub verify: bool,
+
+    /// Report ASCII and UTF-16LE strings of at least MINLEN characters found in unidentified data
+    #[arg(
+        long,
+        value_name = "MINLEN",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "4",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub strings: Option<usize>,
+
+    /// Report runs of at least MINLEN identical bytes (e.g. 0xFF erased flash) in unidentified data as padding
+    #[arg(
+        long,
+        value_name = "MINLEN",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "4096",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub padding: Option<usize>,
+
+    /// Decode base64 or hex encoded runs of at least MINLEN characters in unidentified data, and scan the decoded data
+    #[arg(
+        long,
+        value_name = "MINLEN",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "64",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub decode: Option<usize>,
+
+    /// If FILE is a gzip, xz or zstd stream, decompress it in memory and analyze the decompressed data
+    /// (reported offsets are then offsets into the decompressed data, not FILE)
+    #[arg(long)]
+    pub decompress_input: bool,
+
+    /// Look for an index table of per-device sub-images (offset, size and model name) at the start of the file
+    /// (with --carve, each sub-image is also carved to disk)
+    #[arg(long)]
+    pub index_table: bool,
+
+    /// Only report signatures of at least this confidence: low, medium or high
+    /// (low-confidence results include partially damaged data, e.g. compressed streams with a bad trailer)
+    #[arg(long, value_name = "LEVEL", default_value = "low", value_parser = parse_confidence)]
+    pub min_confidence: u8,
+
+    /// Make output identical across runs: pin extracted file timestamps to SOURCE_DATE_EPOCH
+    /// (or the Unix epoch if unset) and sort all logged results (implied by SOURCE_DATE_EPOCH)
+    #[arg(long)]
+    pub reproducible: bool,
+
+    /// Report the time spent validating and extracting each signature type (also logged to JSON)
+    #[arg(long)]
+    pub profile: bool,
+
+    /// Stop scanning each file after the first valid signature is found
+    #[arg(long, conflicts_with = "max_results")]
+    pub first_match: bool,
+
+    /// Stop scanning each file after N valid signatures are found
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub max_results: Option<usize>,
+
     /// Recursively scan extracted files
     #[arg(short = 'M', long)]
     pub matryoshka: bool,
 
+    /// With --matryoshka, stop scanning extracted files N levels below the target file [default: unlimited]
+    #[arg(long, value_name = "N", requires = "matryoshka")]
+    pub max_depth: Option<usize>,
+
     /// Search for all signatures at all offsets
     #[arg(short = 'a', long)]
     pub search_all: bool,
@@ -59,8 +157,14 @@ pub struct CliArgs {
     #[arg(short, long, value_name = "LOG_FILE", value_hint = clap::ValueHint::FilePath)]
     pub log: Option<PathBuf>,
 
-    /// Manually specify the number of threads to use
-    #[arg(short, long, value_name = "INT", value_parser = clap::value_parser!(u64).range(1..))]
+    /// Number of worker threads used to analyze and extract files
+    /// [default: number of available CPU cores; larger values are clamped to 4x that]
+    #[arg(
+        short,
+        long,
+        value_name = "INT",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
     pub threads: Option<usize>,
 
     /// Do not scan for these signatures
@@ -87,4 +191,40 @@ pub struct CliArgs {
     /// Extract files/folders to a custom directory
     #[arg(short, long, default_value = "extractions", value_hint = clap::ValueHint::DirPath)]
     pub directory: PathBuf,
+
+    /// Load additional signature definitions from a JSON magic file (may be specified more than once)
+    #[arg(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath)]
+    pub magic: Vec<PathBuf>,
+
+    /// Override the path to an external extraction utility (e.g. unsquashfs=/opt/bin/unsquashfs4)
+    #[arg(long, value_name = "NAME=PATH", value_parser = parse_extractor_path)]
+    pub extractor_path: Vec<(String, PathBuf)>,
+
+    /// Kill external extraction utilities that run for longer than SECS seconds, failing their extraction
+    #[arg(
+        long,
+        value_name = "SECS",
+        value_parser = clap::builder::RangedU64ValueParser::<u64>::new().range(1..)
+    )]
+    pub extractor_timeout: Option<u64>,
+}
+
+/// Parses a NAME=PATH extractor path override
+pub fn parse_extractor_path(arg: &str) -> Result<(String, PathBuf), String> {
+    match arg.split_once('=') {
+        Some((name, path)) if !name.is_empty() && !path.is_empty() => {
+            Ok((name.to_string(), PathBuf::from(path)))
+        }
+        _ => Err(format!("expected NAME=PATH, got '{arg}'")),
+    }
+}
+
+/// Parses a signature confidence level name
+pub fn parse_confidence(arg: &str) -> Result<u8, String> {
+    match arg.to_lowercase().as_str() {
+        "low" => Ok(CONFIDENCE_LOW),
+        "medium" => Ok(CONFIDENCE_MEDIUM),
+        "high" => Ok(CONFIDENCE_HIGH),
+        _ => Err(format!("expected low, medium or high, got '{arg}'")),
+    }
 }
@@ -1,7 +1,13 @@
 //! Common Functions
-use log::{debug, error};
+use log::{debug, error, info, warn};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Deref;
 use std::path::Path;
 
+/// Maximum size of a decompressed input file, see read_input
+pub const MAX_DECOMPRESSED_INPUT_SIZE: usize = 1024 * 1024 * 1024;
+
 /// Read a file data into memory and return its contents.
 ///
 /// ## Example
@@ -30,6 +36,220 @@ pub fn read_file(file: impl AsRef<Path>) -> Result<Vec<u8>, std::io::Error> {
     Ok(file_data)
 }
 
+/// Read up to `len` bytes of a file's data starting at `offset`, or all data from `offset` to EOF if `len` is None.
+/// Offsets at or beyond EOF return no data.
+///
+/// ## Example
+///
+/// ```
+/// # fn main() { #[allow(non_snake_case)] fn _doctest_main_src_common_rs_read_file_range() -> Result<(), Box<dyn std::error::Error>> {
+/// use binwalk_ng::common::read_file_range;
+///
+/// let file = tempfile::NamedTempFile::new()?;
+/// std::fs::write(file.path(), b"0123456789")?;
+///
+/// assert_eq!(read_file_range(file.path(), 2, Some(4))?, b"2345");
+/// assert_eq!(read_file_range(file.path(), 8, Some(4))?, b"89");
+/// assert_eq!(read_file_range(file.path(), 6, None)?, b"6789");
+/// assert!(read_file_range(file.path(), 10, None)?.is_empty());
+/// assert!(read_file_range(file.path(), 100, Some(4))?.is_empty());
+/// # Ok(())
+/// # } _doctest_main_src_common_rs_read_file_range(); }
+/// ```
+pub fn read_file_range(
+    file: impl AsRef<Path>,
+    offset: u64,
+    len: Option<usize>,
+) -> Result<Vec<u8>, std::io::Error> {
+    let file_path = file.as_ref();
+    let mut file_data: Vec<u8> = Vec::new();
+
+    let mut file = File::open(file_path)
+        .inspect_err(|e| error!("Failed to open file {}: {e}", file_path.display()))?;
+    file.seek(SeekFrom::Start(offset))?;
+
+    match len {
+        Some(len) => file.take(len as u64).read_to_end(&mut file_data),
+        None => file.read_to_end(&mut file_data),
+    }
+    .inspect_err(|e| {
+        error!(
+            "Failed to read file {} at offset {offset:#X}: {e}",
+            file_path.display()
+        )
+    })?;
+
+    debug!(
+        "Loaded {} bytes from {} at offset {offset:#X}",
+        file_data.len(),
+        file_path.display()
+    );
+    Ok(file_data)
+}
+
+/// File data returned by read_input; either the file itself, memory mapped, or data read or decompressed into memory
+#[derive(Debug)]
+pub enum InputData {
+    Mapped(memmap2::Mmap),
+    Owned(Vec<u8>),
+}
+
+impl Default for InputData {
+    fn default() -> Self {
+        Self::Owned(Vec::new())
+    }
+}
+
+impl Deref for InputData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Mapped(mapped_data) => mapped_data,
+            Self::Owned(owned_data) => owned_data,
+        }
+    }
+}
+
+/// Memory maps a file, so that large files can be analyzed without first reading them into memory.
+///
+/// ## Notes
+///
+/// The file must not be modified while it is mapped. Empty files can't be mapped, and are returned as empty owned data;
+/// anything other than a regular file is read into memory rather than mapped.
+///
+/// ## Example
+///
+/// ```
+/// # fn main() { #[allow(non_snake_case)] fn _doctest_main_src_common_rs_read_input_mmap() -> Result<(), Box<dyn std::error::Error>> {
+/// use binwalk_ng::common::read_input_mmap;
+///
+/// let file_data = read_input_mmap("/etc/passwd")?;
+/// assert_eq!(*file_data, std::fs::read("/etc/passwd")?);
+/// # Ok(())
+/// # } _doctest_main_src_common_rs_read_input_mmap(); }
+/// ```
+pub fn read_input_mmap(file: impl AsRef<Path>) -> Result<InputData, std::io::Error> {
+    let file_path = file.as_ref();
+
+    let file = File::open(file_path)
+        .inspect_err(|e| error!("Failed to open file {}: {e}", file_path.display()))?;
+
+    let metadata = file.metadata()?;
+
+    // Block devices, FIFOs, /proc files, etc. report a size of 0 and/or can't be mapped; read them into memory instead
+    if !metadata.is_file() {
+        return read_file(file_path).map(InputData::Owned);
+    }
+
+    if metadata.len() == 0 {
+        return Ok(InputData::default());
+    }
+
+    // SAFETY: the mapped file is only ever read, and is not expected to be modified during analysis
+    let mapped_data = unsafe { memmap2::Mmap::map(&file) }
+        .inspect_err(|e| error!("Failed to memory map file {}: {e}", file_path.display()))?;
+
+    debug!(
+        "Mapped {} bytes from {}",
+        mapped_data.len(),
+        file_path.display()
+    );
+    Ok(InputData::Mapped(mapped_data))
+}
+
+/// Memory map a file's data (see read_input_mmap); if `decompress` is true and the file is a gzip, xz or zstd stream, the
+/// file data is decompressed in memory and the decompressed data is returned instead.
+///
+/// ## Notes
+///
+/// Offsets into the returned data are offsets into the decompressed data, not the file. If the file is not a supported
+/// compressed stream, fails to decompress, or decompresses to more than MAX_DECOMPRESSED_INPUT_SIZE bytes, the file
+/// data is returned as-is.
+pub fn read_input(file: impl AsRef<Path>, decompress: bool) -> Result<InputData, std::io::Error> {
+    let file_path = file.as_ref();
+    let file_data = read_input_mmap(file_path)?;
+
+    if !decompress {
+        return Ok(file_data);
+    }
+
+    Ok(
+        decompress_input(&file_data, MAX_DECOMPRESSED_INPUT_SIZE).map_or(
+            file_data,
+            |decompressed_data| {
+                info!(
+                    "Decompressed {} into {} bytes",
+                    file_path.display(),
+                    decompressed_data.len()
+                );
+                InputData::Owned(decompressed_data)
+            },
+        ),
+    )
+}
+
+/// Decompresses data that is a gzip, xz or zstd stream, as identified by its magic bytes. Returns None if the data is
+/// not compressed, fails to decompress, or decompresses to more than `max_size` bytes.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::common::decompress_input;
+/// use std::io::Write;
+///
+/// let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+/// encoder.write_all(b"firmware image").unwrap();
+/// let compressed = encoder.finish().unwrap();
+///
+/// assert_eq!(decompress_input(&compressed, 1024).unwrap(), b"firmware image");
+/// assert_eq!(decompress_input(&compressed, 4), None);
+/// assert_eq!(decompress_input(b"firmware image", 1024), None);
+/// ```
+pub fn decompress_input(data: &[u8], max_size: usize) -> Option<Vec<u8>> {
+    const GZIP_MAGIC: &[u8] = b"\x1F\x8B\x08";
+    const XZ_MAGIC: &[u8] = b"\xFD\x37\x7A\x58\x5A\x00";
+    const ZSTD_MAGIC: &[u8] = b"\x28\xB5\x2F\xFD";
+
+    let (compression, decoder): (&str, Box<dyn Read + '_>) = if data.starts_with(GZIP_MAGIC) {
+        ("gzip", Box::new(flate2::read::MultiGzDecoder::new(data)))
+    } else if data.starts_with(XZ_MAGIC) {
+        (
+            "xz",
+            Box::new(liblzma::read::XzDecoder::new_multi_decoder(data)),
+        )
+    } else if data.starts_with(ZSTD_MAGIC) {
+        (
+            "zstd",
+            Box::new(zstd::stream::read::Decoder::new(data).ok()?),
+        )
+    } else {
+        return None;
+    };
+
+    // Read one byte past the limit to detect data that decompresses to more than max_size bytes
+    let mut decompressed_data: Vec<u8> = Vec::new();
+    if let Err(e) = decoder
+        .take(max_size as u64 + 1)
+        .read_to_end(&mut decompressed_data)
+    {
+        warn!("Failed to decompress {compression} input data: {e}");
+        return None;
+    }
+
+    if decompressed_data.len() > max_size {
+        warn!("Decompressed {compression} input data exceeds {max_size} bytes, not decompressing");
+        return None;
+    }
+
+    debug!(
+        "Decompressed {} bytes of {compression} input data into {} bytes",
+        data.len(),
+        decompressed_data.len()
+    );
+    Some(decompressed_data)
+}
+
 /// Calculates the CRC32 checksum of the given data.
 ///
 /// ## Notes
@@ -48,7 +268,139 @@ pub fn read_file(file: impl AsRef<Path>) -> Result<Vec<u8>, std::io::Error> {
 /// assert_eq!(my_data_crc, 0xDB1720A5);
 /// ```
 pub fn crc32(data: &[u8]) -> u32 {
-    crc32fast::hash(data)
+    crc32_seed(0, data)
+}
+
+/// Calculates the CRC32 checksum of the given data, using the specified initial CRC value.
+///
+/// Passing the CRC32 of all previous chunks of data as the initial value continues the calculation over the next
+/// chunk, which allows large or non-contiguous data to be checksummed without first copying it into one buffer.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::common::{crc32, crc32_seed};
+///
+/// assert_eq!(crc32_seed(0, b"ABCD"), 0xDB1720A5);
+/// assert_eq!(crc32_seed(0x12345678, b"ABCD"), 0x553E786B);
+/// assert_eq!(crc32_seed(crc32(b"AB"), b"CD"), crc32(b"ABCD"));
+/// ```
+pub fn crc32_seed(init: u32, data: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new_with_initial(init);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// Calculates the CRC-16/CCITT-FALSE checksum of the given data (polynomial 0x1021, initial value 0xFFFF).
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::common::crc16_ccitt;
+///
+/// assert_eq!(crc16_ccitt(b"123456789"), 0x29B1);
+/// ```
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+
+    for &byte in data {
+        crc ^= u16::from(byte) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+
+    crc
+}
+
+/// Calculates the CRC-16/MODBUS checksum of the given data (reflected polynomial 0xA001, initial value 0xFFFF).
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::common::crc16_modbus;
+///
+/// assert_eq!(crc16_modbus(b"123456789"), 0x4B37);
+/// ```
+pub fn crc16_modbus(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+
+    for &byte in data {
+        crc ^= u16::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xA001
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    crc
+}
+
+/// Calculates the Adler-32 checksum of the given data, as used by zlib.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::common::adler32;
+///
+/// assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+/// ```
+pub fn adler32(data: &[u8]) -> u32 {
+    let mut checksum = adler2::Adler32::new();
+    checksum.write_slice(data);
+    checksum.checksum()
+}
+
+/// Calculates the Shannon entropy of the given data, in bits per byte (0.0 - 8.0).
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::common::shannon_entropy;
+///
+/// assert_eq!(shannon_entropy(&[0; 4096]), 0.0);
+/// assert_eq!(shannon_entropy(b""), 0.0);
+///
+/// // Every byte value, equally often
+/// let uniform_data: Vec<u8> = (0..=255).cycle().take(4096).collect();
+/// assert_eq!(shannon_entropy(&uniform_data), 8.0);
+///
+/// // Pseudo-random data
+/// let mut state: u32 = 0x12345678;
+/// let random_data: Vec<u8> = (0..65536)
+///     .map(|_| {
+///         state ^= state << 13;
+///         state ^= state >> 17;
+///         state ^= state << 5;
+///         state as u8
+///     })
+///     .collect();
+/// assert!(shannon_entropy(&random_data) > 7.99);
+/// ```
+pub fn shannon_entropy(data: &[u8]) -> f64 {
+    let mut byte_counts = [0_usize; 256];
+
+    for &byte in data {
+        byte_counts[byte as usize] += 1;
+    }
+
+    let data_size = data.len() as f64;
+
+    byte_counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let probability = *count as f64 / data_size;
+            -probability * probability.log2()
+        })
+        .sum()
 }
 
 /// Converts an epoch time to a formatted time string.
@@ -83,29 +435,83 @@ pub fn epoch_to_string(epoch_timestamp: impl Into<i64>) -> String {
 /// assert_eq!(string, "this_is_a_c_string");
 /// ```
 pub fn get_cstring(raw_data: &[u8]) -> String {
-    let first_zero = raw_data
+    get_cstring_bounded(raw_data, usize::MAX)
+}
+
+/// Get a C-style NULL-terminated string from the provided array of u8 bytes, reading no more than `max_len` bytes.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::common::get_cstring_bounded;
+///
+/// let raw_data: &[u8] = b"this_is_a_c_string\x00";
+///
+/// assert_eq!(get_cstring_bounded(raw_data, 64), "this_is_a_c_string");
+/// assert_eq!(get_cstring_bounded(raw_data, 4), "this");
+/// ```
+pub fn get_cstring_bounded(raw_data: &[u8], max_len: usize) -> String {
+    let bounded_data = &raw_data[..raw_data.len().min(max_len)];
+    let first_zero = bounded_data
         .iter()
         .position(|&r| r == 0)
-        .unwrap_or(raw_data.len());
-    let raw_bytes = &raw_data[..first_zero];
+        .unwrap_or(bounded_data.len());
+    let raw_bytes = &bounded_data[..first_zero];
     String::from_utf8_lossy(raw_bytes).into_owned()
 }
 
-/// Returns true if the provided byte is a printable ASCII character
+/// Get a NULL-terminated UTF-16 string from the provided array of u8 bytes. Invalid UTF-16 sequences are replaced with
+/// U+FFFD, and a trailing odd byte is ignored.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::common::get_utf16_string;
+///
+/// assert_eq!(get_utf16_string(b"A\x00B\x00\x00\x00C\x00", true), "AB");
+/// assert_eq!(get_utf16_string(b"\x00A\x00B\x00", false), "AB");
+/// ```
+pub fn get_utf16_string(raw_data: &[u8], little_endian: bool) -> String {
+    let code_units: Vec<u16> = raw_data
+        .chunks_exact(2)
+        .map(|code_unit| {
+            let code_unit = [code_unit[0], code_unit[1]];
+
+            if little_endian {
+                u16::from_le_bytes(code_unit)
+            } else {
+                u16::from_be_bytes(code_unit)
+            }
+        })
+        .take_while(|code_unit| *code_unit != 0)
+        .collect();
+
+    String::from_utf16_lossy(&code_units)
+}
+
+/// Returns true if the provided byte is a printable ASCII character (0x20 - 0x7E); if `allow_whitespace` is true, tabs,
+/// carriage returns, and newlines are also considered printable. Other control characters never are.
 ///
 /// ## Example
 ///
 /// ```
 /// use binwalk_ng::common::is_printable_ascii;
 ///
-/// assert!(is_printable_ascii(0x41));
-/// assert!(!is_printable_ascii(0xFE));
+/// assert!(is_printable_ascii(0x41, false));
+/// assert!(is_printable_ascii(b' ', false));
+/// assert!(is_printable_ascii(b'\n', true));
+/// assert!(!is_printable_ascii(b'\n', false));
+/// assert!(!is_printable_ascii(0x0B, true));
+/// assert!(!is_printable_ascii(0x0C, true));
+/// assert!(!is_printable_ascii(0x1B, true));
+/// assert!(!is_printable_ascii(0x7F, true));
+/// assert!(!is_printable_ascii(0xFE, true));
 /// ```
-pub fn is_printable_ascii(b: u8) -> bool {
-    const ASCII_MIN: u8 = 0x0A;
+pub fn is_printable_ascii(b: u8, allow_whitespace: bool) -> bool {
+    const ASCII_MIN: u8 = 0x20;
     const ASCII_MAX: u8 = 0x7E;
 
-    (ASCII_MIN..=ASCII_MAX).contains(&b)
+    (ASCII_MIN..=ASCII_MAX).contains(&b) || (allow_whitespace && matches!(b, b'\t' | b'\n' | b'\r'))
 }
 
 /// Validates data offsets to prevent out-of-bounds access and infinite loops while parsing file formats.
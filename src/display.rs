@@ -1,25 +1,55 @@
 use binwalk_ng::AnalysisResults;
+use binwalk_ng::encoded;
 use binwalk_ng::extractors;
+use binwalk_ng::index_table;
+use binwalk_ng::padding;
+use binwalk_ng::profile;
 use binwalk_ng::signatures;
+use binwalk_ng::strings;
 use colored::ColoredString;
 use colored::Colorize;
 use log::error;
 use std::collections::HashMap;
 use std::io;
+use std::io::IsTerminal;
 use std::io::Write;
 use std::time;
 use terminal_size::Width;
 
 const DELIM_CHARACTER: &str = "-";
 const DEFAULT_TERMINAL_WIDTH: u16 = 200;
+const TRUNCATION_MARKER: &str = "...";
 
 const COLUMN1_WIDTH: usize = 35;
 const COLUMN2_WIDTH: usize = 35;
 
+// Offset columns used when the terminal is too narrow to fit the full width columns and a readable description
+const COMPACT_COLUMN1_WIDTH: usize = 12;
+const COMPACT_COLUMN2_WIDTH: usize = 14;
+const MIN_DESCRIPTION_WIDTH: usize = 40;
+
+/// Disables colorized output if requested, if stdout is not a terminal, or if the NO_COLOR environment variable is set
+pub fn configure_color(no_color: bool) {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+    if no_color || no_color_env || !io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+}
+
 fn terminal_width() -> usize {
     terminal_size::terminal_size().map_or(DEFAULT_TERMINAL_WIDTH, |(Width(w), _)| w) as usize
 }
 
+/// Returns the widths of the decimal and hexadecimal offset columns, narrowing them to fit the terminal if necessary
+fn column_widths() -> (usize, usize) {
+    if terminal_width() >= COLUMN1_WIDTH + COLUMN2_WIDTH + MIN_DESCRIPTION_WIDTH {
+        (COLUMN1_WIDTH, COLUMN2_WIDTH)
+    } else {
+        (COMPACT_COLUMN1_WIDTH, COMPACT_COLUMN2_WIDTH)
+    }
+}
+
 fn line_delimiter() -> String {
     DELIM_CHARACTER.repeat(terminal_width())
 }
@@ -34,20 +64,39 @@ fn pad_to_length(text: &str, len: usize) -> String {
     format!("{text:width$}", width = len)
 }
 
+/// Truncates text longer than max_size characters, marking where it was cut
+fn truncate(text: &str, max_size: usize) -> String {
+    if text.chars().count() <= max_size {
+        return text.to_string();
+    }
+
+    let truncated: String = text
+        .chars()
+        .take(max_size.saturating_sub(TRUNCATION_MARKER.len()))
+        .collect();
+
+    truncated + TRUNCATION_MARKER
+}
+
 fn line_wrap(text: &str, prefix_size: usize) -> String {
     let mut this_line = "".to_string();
     let mut formatted_string = "".to_string();
-    let max_line_size: usize = terminal_width() - prefix_size;
+    let max_line_size: usize = terminal_width()
+        .saturating_sub(prefix_size)
+        .max(MIN_DESCRIPTION_WIDTH);
 
     for word in text.split_whitespace() {
-        if (this_line.len() + word.len()) < max_line_size {
-            this_line = this_line + word + " ";
+        // Words that can't fit on a line by themselves (e.g., long file paths) are truncated
+        let word = truncate(word, max_line_size - 1);
+
+        if (this_line.chars().count() + word.chars().count()) < max_line_size {
+            this_line = this_line + &word + " ";
         } else {
             formatted_string = formatted_string + &this_line + "\n";
             for _i in 0..prefix_size {
                 formatted_string += " ";
             }
-            this_line = word.to_string() + " ";
+            this_line = word + " ";
         }
     }
 
@@ -56,11 +105,48 @@ fn line_wrap(text: &str, prefix_size: usize) -> String {
     formatted_string.trim().to_string()
 }
 
+fn format_offset_columns(offset: usize) -> String {
+    let (column1_width, column2_width) = column_widths();
+
+    format!(
+        "{}{}",
+        pad_to_length(&format!("{offset}"), column1_width),
+        pad_to_length(&format!("{offset:#X}"), column2_width)
+    )
+}
+
+/// Formats an offset and its description as an aligned table row, wrapping the description to fit the terminal
+fn format_row(offset: usize, description: &str) -> String {
+    let (column1_width, column2_width) = column_widths();
+
+    format!(
+        "{}{}",
+        format_offset_columns(offset),
+        line_wrap(description, column1_width + column2_width)
+    )
+}
+
+/// Formats an offset and its description as an aligned table row, truncating the description to fit the terminal
+fn format_truncated_row(offset: usize, description: &str) -> String {
+    let (column1_width, column2_width) = column_widths();
+    let max_description_size = terminal_width()
+        .saturating_sub(column1_width + column2_width)
+        .max(MIN_DESCRIPTION_WIDTH);
+
+    format!(
+        "{}{}",
+        format_offset_columns(offset),
+        truncate(description, max_description_size)
+    )
+}
+
 fn print_column_headers(col1: &str, col2: &str, col3: &str) {
+    let (column1_width, column2_width) = column_widths();
+
     let header_string = format!(
         "{}{}{}",
-        pad_to_length(col1, COLUMN1_WIDTH),
-        pad_to_length(col2, COLUMN2_WIDTH),
+        pad_to_length(col1, column1_width),
+        pad_to_length(col2, column2_width),
         col3
     );
 
@@ -84,16 +170,26 @@ fn print_footer() {
     println!();
 }
 
-fn print_signature(signature: &signatures::SignatureResult) {
-    let decimal_string = format!("{}", signature.offset);
-    let hexadecimal_string = format!("{:#X}", signature.offset);
-    let display_string = format!(
-        "{}{}{}",
-        pad_to_length(&decimal_string, COLUMN1_WIDTH),
-        pad_to_length(&hexadecimal_string, COLUMN2_WIDTH),
-        line_wrap(&signature.description, COLUMN1_WIDTH + COLUMN2_WIDTH)
+fn print_signature(signature: &signatures::SignatureResult, show_extractor: bool) {
+    // Point out back-to-back images (e.g., A/B firmware banks) so analysts don't have to do the offset math
+    let description = signature.sibling_offset.map_or_else(
+        || signature.description.clone(),
+        |sibling_offset| {
+            format!(
+                "{} (sibling image of offset {sibling_offset:#X})",
+                signature.description
+            )
+        },
     );
 
+    // When not extracting, let the user know what could have extracted this signature
+    let description = match &signature.extractor {
+        Some(extractor) if show_extractor => format!("{description} [extractor: {extractor}]"),
+        _ => description,
+    };
+
+    let display_string = format_row(signature.offset, &description);
+
     if signature.confidence >= signatures::CONFIDENCE_HIGH {
         println!("{}", display_string.green());
     } else if signature.confidence >= signatures::CONFIDENCE_MEDIUM {
@@ -103,15 +199,133 @@ fn print_signature(signature: &signatures::SignatureResult) {
     }
 }
 
-fn print_signatures(signatures: &Vec<signatures::SignatureResult>) {
+fn print_signatures(signatures: &Vec<signatures::SignatureResult>, show_extractors: bool) {
     for signature in signatures {
-        print_signature(signature);
+        print_signature(signature, show_extractors);
+        print_children(signature, 1);
+    }
+}
+
+/// Prints the sub-images of a container signature, indented beneath it
+fn print_children(parent: &signatures::SignatureResult, depth: usize) {
+    const INDENT: &str = "  ";
+
+    for child in &parent.children {
+        let indented_child = signatures::SignatureResult {
+            description: format!("{}└─ {}", INDENT.repeat(depth - 1), child.description),
+            extractor: None,
+            ..child.clone()
+        };
+
+        print_signature(&indented_child, false);
+        print_children(child, depth + 1);
+    }
+}
+
+fn print_padding(padding: &[padding::PaddingResult]) {
+    if padding.is_empty() {
+        return;
+    }
+
+    print_delimiter();
+
+    for region in padding {
+        let description = format!(
+            "Padding, fill byte: {:#04X}, size: {} bytes",
+            region.fill_byte, region.size
+        );
+
+        println!("{}", format_row(region.offset, &description));
+    }
+}
+
+fn print_index_table(index_table: Option<&index_table::IndexTable>) {
+    let Some(index_table) = index_table else {
+        return;
+    };
+
+    print_delimiter();
+
+    let description = format!(
+        "Index table, layout: {}, entries: {}",
+        index_table.layout,
+        index_table.entries.len()
+    );
+
+    println!("{}", format_row(index_table.offset, &description));
+
+    for entry in &index_table.entries {
+        let description = format!(
+            "Indexed sub-image, model: \"{}\", size: {} bytes",
+            entry.model.escape_debug(),
+            entry.size
+        );
+
+        println!("{}", format_row(entry.offset, &description));
+    }
+}
+
+fn print_encoded(encoded: &[encoded::EncodedResult]) {
+    if encoded.is_empty() {
+        return;
+    }
+
+    print_delimiter();
+
+    for blob in encoded {
+        let encoding = match blob.encoding {
+            encoded::BlobEncoding::Base64 => "Base64",
+            encoded::BlobEncoding::Hex => "Hex",
+        };
+
+        let description = format!(
+            "{encoding} encoded data, encoded size: {} bytes, decoded size: {} bytes",
+            blob.size, blob.decoded_size
+        );
+
+        println!("{}", format_row(blob.offset, &description));
+
+        // Signatures in the decoded data are listed beneath the encoded data they were found in
+        for signature in &blob.file_map {
+            let decoded_signature = signatures::SignatureResult {
+                offset: blob.offset,
+                description: format!(
+                    "└─ decoded offset {:#X}: {}",
+                    signature.offset, signature.description
+                ),
+                extractor: None,
+                ..signature.clone()
+            };
+
+            print_signature(&decoded_signature, false);
+        }
+    }
+}
+
+fn print_strings(strings: &[strings::StringResult]) {
+    if strings.is_empty() {
+        return;
+    }
+
+    print_delimiter();
+
+    for string in strings {
+        let encoding = match string.encoding {
+            strings::StringEncoding::Ascii => "ASCII",
+            strings::StringEncoding::Utf16Le => "UTF-16LE",
+        };
+
+        // Wrapping would mangle whitespace in the string, so long strings are truncated instead
+        let description = format!("{encoding} string: \"{}\"", string.value.escape_debug());
+
+        println!("{}", format_truncated_row(string.offset, &description));
     }
 }
 
 fn print_extraction(
     signature: &signatures::SignatureResult,
     extraction: Option<&extractors::ExtractionResult>,
+    verbose: bool,
 ) {
     let extraction_message: ColoredString;
 
@@ -125,7 +339,14 @@ fn print_extraction(
             .yellow();
         }
         Some(extraction_result) => {
-            if extraction_result.success {
+            if extraction_result.identified_only {
+                extraction_message = format!(
+                    "[*] {} data at offset {:#X} identified ({} bytes), not extracted",
+                    signature.name, signature.offset, signature.size
+                )
+                .bold()
+                .cyan();
+            } else if extraction_result.success {
                 extraction_message = format!(
                     "[+] Extraction of {} data at offset {:#X} completed successfully",
                     signature.name, signature.offset
@@ -144,11 +365,22 @@ fn print_extraction(
     }
 
     println!("{extraction_message}");
+
+    // In verbose mode, show what a failed external extractor had to say for itself
+    if verbose
+        && let Some(extractor_output) =
+            extraction.and_then(|extraction_result| extraction_result.extractor_output.as_ref())
+    {
+        for line in extractor_output.lines() {
+            println!("    {}", line.dimmed());
+        }
+    }
 }
 
 fn print_extractions(
     signatures: &Vec<signatures::SignatureResult>,
     extraction_results: &HashMap<String, extractors::ExtractionResult>,
+    verbose: bool,
 ) {
     let mut delimiter_printed = false;
 
@@ -170,25 +402,37 @@ fn print_extractions(
                 print_delimiter();
                 delimiter_printed = true;
             }
-            print_extraction(signature, extraction_result);
+            print_extraction(signature, extraction_result, verbose);
         }
     }
 }
 
-pub fn print_analysis_results(quiet: bool, extraction_attempted: bool, results: &AnalysisResults) {
+/// Prints analysis results; in verbose mode, the output of failed external extractors is included
+pub fn print_analysis_results(
+    quiet: bool,
+    verbose: bool,
+    extraction_attempted: bool,
+    results: &AnalysisResults,
+) {
     if quiet {
         return;
     }
 
     // Print signature results
     print_header(&results.file_path.display().to_string());
-    print_signatures(&results.file_map);
+    print_signatures(&results.file_map, !extraction_attempted);
 
     // If extraction was attempted, print extraction results
     if extraction_attempted {
-        print_extractions(&results.file_map, &results.extractions);
+        print_extractions(&results.file_map, &results.extractions, verbose);
     }
 
+    // Print any index table, and any padding, encoded data and strings found in unknown data
+    print_index_table(results.index_table.as_ref());
+    print_padding(&results.padding);
+    print_encoded(&results.encoded);
+    print_strings(&results.strings);
+
     // Print the footer text
     print_footer();
 }
@@ -246,9 +490,14 @@ pub fn print_signature_list(quiet: bool, signatures: &Vec<signatures::Signature>
                     extractors::ExtractorType::External(command) => {
                         signature_info.extractor = command.to_string();
                     }
-                    extractors::ExtractorType::Internal(_) => {
+                    extractors::ExtractorType::Internal(_)
+                    | extractors::ExtractorType::Carve(_) => {
                         signature_info.extractor = "Built-in".to_string();
                     }
+                    extractors::ExtractorType::IdentifyOnly => {
+                        signature_info.has_extractor = false;
+                        signature_info.extractor = "Identify only".to_string();
+                    }
                     extractors::ExtractorType::None => error!(
                         "An invalid extractor type exists for the '{}' signature",
                         signature.description
@@ -343,6 +592,34 @@ pub fn print_stats(
     );
 }
 
+pub fn print_profile(quiet: bool, report: &[profile::SignatureProfile]) {
+    const US_IN_A_MS: f64 = 1000.0;
+
+    if quiet || report.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("{}", center_text("Signature Profile").bold().magenta());
+    print_delimiter();
+    print_column_headers("SIGNATURE", "", "TIME (SLOWEST FIRST)");
+    print_delimiter();
+
+    for signature in report {
+        println!(
+            "{}total: {:.1} ms, validation: {:.1} ms ({} calls), extraction: {:.1} ms ({} calls)",
+            pad_to_length(&signature.name, COLUMN1_WIDTH + COLUMN2_WIDTH),
+            signature.total_time_us() as f64 / US_IN_A_MS,
+            signature.validation_time_us as f64 / US_IN_A_MS,
+            signature.validations,
+            signature.extraction_time_us as f64 / US_IN_A_MS,
+            signature.extractions
+        );
+    }
+
+    print_footer();
+}
+
 pub fn print_plain(quiet: bool, msg: &str) {
     if !quiet {
         print!("{msg}");
@@ -0,0 +1,174 @@
+//! Detection of base64 and hex encoded data (e.g., firmware or certificates stored in configuration files) in
+//! unidentified data, so that the decoded data can be scanned for signatures.
+
+use crate::signatures::SignatureResult;
+use crate::strings::unknown_regions;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// Line breaks are allowed inside encoded data, as base64 is commonly wrapped at 64 or 76 characters
+const LINE_BREAKS: [u8; 2] = [b'\r', b'\n'];
+
+/// Base64 padding character; at most two may end a base64 string
+const BASE64_PADDING: u8 = b'=';
+const MAX_BASE64_PADDING: usize = 2;
+
+/// Encoding of an encoded blob
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlobEncoding {
+    Base64,
+    Hex,
+}
+
+/// A run of base64 or hex encoded data found in the file data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedBlob {
+    /// File/data offset where the encoded data starts
+    pub offset: usize,
+    /// Size of the encoded data in the file, including any line breaks and padding
+    pub size: usize,
+    pub encoding: BlobEncoding,
+    /// The decoded data
+    pub decoded: Vec<u8>,
+}
+
+/// Describes an encoded blob whose decoded data contains identified signatures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncodedResult {
+    /// File/data offset where the encoded data starts
+    pub offset: usize,
+    /// Size of the encoded data in the file, including any line breaks and padding
+    pub size: usize,
+    pub encoding: BlobEncoding,
+    /// Size of the decoded data
+    pub decoded_size: usize,
+    /// Signatures identified in the decoded data; offsets are relative to the start of the decoded data
+    pub file_map: Vec<SignatureResult>,
+}
+
+/// Find all runs of at least `min_length` base64 or hex characters in the unknown regions of the file data, and decode
+/// them. Results are sorted by offset.
+///
+/// To limit false positives, runs that are not strictly valid (an odd number of hex digits, a base64 length that is not
+/// a multiple of 4, misplaced padding) are ignored. Runs made up only of hex digits are decoded as hex.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::encoded::{BlobEncoding, find_encoded_blobs};
+///
+/// let file_data = b"key=SGVsbG8sIHdvcmxkIQ==;id=48656c6c6f;";
+///
+/// let blobs = find_encoded_blobs(file_data, &[], 10);
+///
+/// assert_eq!(blobs.len(), 2);
+/// assert_eq!(blobs[0].offset, 4);
+/// assert_eq!(blobs[0].encoding, BlobEncoding::Base64);
+/// assert_eq!(blobs[0].decoded, b"Hello, world!");
+/// assert_eq!(blobs[1].offset, 28);
+/// assert_eq!(blobs[1].encoding, BlobEncoding::Hex);
+/// assert_eq!(blobs[1].decoded, b"Hello");
+/// ```
+pub fn find_encoded_blobs(
+    file_data: &[u8],
+    file_map: &[SignatureResult],
+    min_length: usize,
+) -> Vec<EncodedBlob> {
+    let mut blobs: Vec<EncodedBlob> = Vec::new();
+
+    for (region_offset, region_size) in unknown_regions(file_map, file_data.len()) {
+        let Some(region) = file_data.get(region_offset..region_offset + region_size) else {
+            continue;
+        };
+
+        let mut next_offset = 0;
+
+        while next_offset < region.len() {
+            let run_size = encoded_run_size(&region[next_offset..]);
+
+            if run_size == 0 {
+                next_offset += 1;
+                continue;
+            }
+
+            let run = &region[next_offset..next_offset + run_size];
+
+            // Line breaks may wrap the encoded data, but do not start or end it
+            let leading = run.iter().take_while(|b| LINE_BREAKS.contains(b)).count();
+            let trailing = run
+                .iter()
+                .rev()
+                .take_while(|b| LINE_BREAKS.contains(b))
+                .count();
+
+            if leading < run.len()
+                && let Some((encoding, decoded)) =
+                    decode(&run[leading..run.len() - trailing], min_length)
+            {
+                blobs.push(EncodedBlob {
+                    offset: region_offset + next_offset + leading,
+                    size: run.len() - leading - trailing,
+                    encoding,
+                    decoded,
+                });
+            }
+
+            next_offset += run_size;
+        }
+    }
+
+    blobs
+}
+
+/// Returns the length of the run of base64 characters and line breaks at the start of `data`, including any padding
+fn encoded_run_size(data: &[u8]) -> usize {
+    let characters = data
+        .iter()
+        .take_while(|b| is_base64_character(**b) || LINE_BREAKS.contains(b))
+        .count();
+
+    let padding = data[characters..]
+        .iter()
+        .take_while(|b| **b == BASE64_PADDING)
+        .count();
+
+    characters + padding
+}
+
+/// Decodes a run of encoded characters; returns None if the run is too short, or is not strictly valid hex or base64
+fn decode(run: &[u8], min_length: usize) -> Option<(BlobEncoding, Vec<u8>)> {
+    let encoded: Vec<u8> = run
+        .iter()
+        .copied()
+        .filter(|b| !LINE_BREAKS.contains(b))
+        .collect();
+
+    if encoded.len() < min_length {
+        return None;
+    }
+
+    if encoded.iter().all(u8::is_ascii_hexdigit) && encoded.len().is_multiple_of(2) {
+        return hex::decode(&encoded)
+            .ok()
+            .map(|decoded| (BlobEncoding::Hex, decoded));
+    }
+
+    let padding = encoded
+        .iter()
+        .rev()
+        .take_while(|b| **b == BASE64_PADDING)
+        .count();
+
+    if padding > MAX_BASE64_PADDING || !encoded.len().is_multiple_of(4) {
+        return None;
+    }
+
+    base64::engine::general_purpose::STANDARD
+        .decode(&encoded)
+        .ok()
+        .map(|decoded| (BlobEncoding::Base64, decoded))
+}
+
+const fn is_base64_character(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'+' || byte == b'/'
+}
@@ -27,8 +27,9 @@ fn blocks(data: &[u8]) -> Vec<BlockEntropy> {
 
     let mut offset: usize = 0;
 
+    // Empty data yields no blocks, but chunks() requires a non-zero block size
     let block_size = if data.len() < BLOCK_COUNT {
-        data.len()
+        data.len().max(1)
     } else {
         data.len() / BLOCK_COUNT
     };
@@ -13,7 +13,7 @@
 //! - The name of a command-line utility to run
 //! - What arguments to pass to it
 //! - What file extension the utility expects
-//! - Which exit codes are considered successful (the default is exit code `0`)
+//! - Which exit codes are considered successful (the default is exit code `0`, see also `Extractor.allow_nonzero_exit`)
 //!
 //! ### Example
 //!
@@ -142,9 +142,10 @@
 use crate::signatures::SignatureResult;
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::ffi::OsString;
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
 #[cfg(unix)]
 use std::os::unix::fs as unix_fs;
 #[cfg(unix)]
@@ -154,11 +155,32 @@ use std::os::windows;
 use std::path::Path;
 use std::path::{self, Component, PathBuf};
 use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
 
 /// This constants in command line arguments will be replaced with the path to the input file
 pub const SOURCE_FILE_PLACEHOLDER: &str = "%e";
 
+/// Compression ratios above this value are suspicious (decompression bombs, misidentified data)
+pub const SUSPICIOUS_COMPRESSION_RATIO: f64 = 1000.0;
+
+/// Runs of zero bytes at least this long are left as holes by Chroot::carve_sparse_file
+pub const SPARSE_THRESHOLD: usize = 64 * 1024;
+
+/// How often an external extractor with a timeout is checked for completion
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Number of trailing lines of a failed external extractor's stdout, and of its stderr, kept for diagnostics
+pub const EXTRACTOR_OUTPUT_LINES: usize = 20;
+
+/// Lines of external extractor output longer than this many bytes are truncated
+pub const EXTRACTOR_OUTPUT_LINE_SIZE: usize = 1024;
+
+/// How long to wait for an exited external extractor's output to be read; background processes left running by the
+/// extractor may hold its stdout / stderr open indefinitely
+const OUTPUT_READER_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Return value of InternalExtractor upon error
 #[derive(Debug, Clone)]
 pub struct ExtractionError;
@@ -167,11 +189,27 @@ pub struct ExtractionError;
 /// Arguments: file_data, offset, output_directory.
 pub type InternalExtractor = fn(&[u8], usize, Option<&Path>) -> ExtractionResult;
 
+/// Internal extractors that produce a single stream of data may also provide a function conforming to this definition,
+/// which returns the extracted data in ExtractionResult.data instead of writing it to disk; see execute_in_memory.
+/// Arguments: file_data, offset.
+pub type InMemoryExtractor = fn(&[u8], usize) -> ExtractionResult;
+
+/// Picks the extractor to run for a signature at extraction time, see Extractor.selector.
+/// Arguments: file_data, signature. Returns None to run the extractor as is.
+pub type ExtractorSelector = fn(&[u8], &SignatureResult) -> Option<Extractor>;
+
+/// Name reported in ExtractionResult.extractor for identify-only extractors
+pub const IDENTIFY_ONLY_EXTRACTOR: &str = "identify_only";
+
 /// Enum to define either an Internal or External extractor type
 #[derive(Debug, Default, Clone)]
 pub enum ExtractorType {
     External(String),
     Internal(InternalExtractor),
+    /// The data is deliberately not extracted, only identified and sized; see identify::identify_only_extractor
+    IdentifyOnly,
+    /// The signature's data is carved to a file of this name in the output directory; see carve::carve_extractor
+    Carve(String),
     #[default]
     None,
 }
@@ -187,8 +225,55 @@ pub struct Extractor {
     pub arguments: Vec<String>,
     /// A list of successful exit codes for the external command
     pub exit_codes: Vec<i32>,
+    /// Set to true if the external command exits nonzero on benign warnings, to consider any exit code successful;
+    /// the command must still exit normally (i.e., not be killed by a signal)
+    pub allow_nonzero_exit: bool,
     /// Set to true to disable recursion into this extractor's extracted files
     pub do_not_recurse: bool,
+    /// The external command is killed, and the extraction fails, if it runs for longer than this; None for no limit
+    pub timeout: Option<Duration>,
+    /// Picks the extractor that is actually run, for choices that are too expensive to make while scanning (e.g.,
+    /// probing the capabilities of installed utilities); see Extractor::resolve
+    pub selector: Option<ExtractorSelector>,
+    /// Set for internal extractors that support extraction in memory; see execute_in_memory
+    pub in_memory: Option<InMemoryExtractor>,
+}
+
+impl Extractor {
+    /// Returns the extractor to run for the signature: the one picked by Extractor.selector, if any, else this one.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use binwalk_ng::extractors::carve::carve_extractor;
+    /// use binwalk_ng::extractors::{Extractor, ExtractorType};
+    /// use binwalk_ng::signatures::SignatureResult;
+    ///
+    /// fn select_carver(_file_data: &[u8], _signature: &SignatureResult) -> Option<Extractor> {
+    ///     Some(carve_extractor("selected.bin"))
+    /// }
+    ///
+    /// let extractor = Extractor {
+    ///     selector: Some(select_carver),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let resolved = extractor.resolve(b"", &SignatureResult::default());
+    ///
+    /// assert!(matches!(resolved.utility, ExtractorType::Carve(file_name) if file_name == "selected.bin"));
+    /// assert!(resolved.selector.is_none());
+    /// ```
+    pub fn resolve(&self, file_data: &[u8], signature: &SignatureResult) -> Extractor {
+        let selected = self
+            .selector
+            .and_then(|selector| selector(file_data, signature))
+            .unwrap_or_else(|| self.clone());
+
+        Extractor {
+            selector: None,
+            ..selected
+        }
+    }
 }
 
 /// Stores information about a completed extraction
@@ -205,6 +290,41 @@ pub struct ExtractionResult {
     pub do_not_recurse: bool,
     /// The output directory where the extractor dropped its files, automatically populated by extractors::execute
     pub output_directory: PathBuf,
+    /// Ratio of decompressed size to compressed size; populated by internal decompressors, see ExtractionResult::set_compression_ratio
+    pub compression_ratio: Option<f64>,
+    /// Set to true if the signature's data was only identified and sized, not extracted, see ExtractorType::IdentifyOnly
+    pub identified_only: bool,
+    /// The regular, non-empty files that the extractor wrote to its output directory; automatically populated by
+    /// extractors::execute on success
+    pub extracted_files: Vec<PathBuf>,
+    /// The extracted data, for extractors run in memory; see extractors::execute_in_memory
+    #[serde(skip)]
+    pub data: Option<Vec<u8>>,
+    /// The last EXTRACTOR_OUTPUT_LINES lines of a failed external extractor's stdout, followed by those of its stderr
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extractor_output: Option<String>,
+}
+
+impl ExtractionResult {
+    /// Records the compression ratio (decompressed size / compressed size) of a successful decompression.
+    ///
+    /// The ratio is left unset if the compressed size is zero.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use binwalk_ng::extractors::ExtractionResult;
+    ///
+    /// let mut result = ExtractionResult::default();
+    ///
+    /// result.set_compression_ratio(100, 250);
+    /// assert_eq!(result.compression_ratio, Some(2.5));
+    /// ```
+    pub fn set_compression_ratio(&mut self, compressed_size: usize, decompressed_size: usize) {
+        if compressed_size > 0 {
+            self.compression_ratio = Some(decompressed_size as f64 / compressed_size as f64);
+        }
+    }
 }
 
 /// Stores information about external extractor processes. For internal use only.
@@ -212,7 +332,9 @@ pub struct ExtractionResult {
 pub struct ProcInfo {
     pub child: process::Child,
     pub exit_codes: Vec<i32>,
+    pub allow_nonzero_exit: bool,
     pub carved_file: String,
+    pub timeout: Option<Duration>,
 }
 
 /// Provides chroot-like functionality for internal extractors.
@@ -227,6 +349,17 @@ pub struct ProcInfo {
 /// where the attacker controls archive contents but not concurrent filesystem activity —
 /// but `Chroot` is not safe to use against an actively adversarial, concurrently-mutated
 /// filesystem.
+///
+/// Special files are never created for real, since that requires privileges; each is
+/// represented by a placeholder regular file at its path, whose contents record what it
+/// would have been:
+///
+/// | Special file     | Placeholder contents  |
+/// |------------------|-----------------------|
+/// | Character device | `c <major> <minor>`   |
+/// | Block device     | `b <major> <minor>`   |
+/// | FIFO             | `fifo`                |
+/// | Socket           | `socket`              |
 #[derive(Debug, Clone)]
 pub struct Chroot {
     /// The chroot directory passed to Chroot::new
@@ -572,6 +705,98 @@ impl Chroot {
         }
     }
 
+    /// Carve data and write it to a new sparse file.
+    ///
+    /// Runs of at least SPARSE_THRESHOLD zero bytes are seeked over rather than written, leaving holes in the file on
+    /// filesystems that support them. The file contents are identical to those written by Chroot::carve_file.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// # fn main() { #[allow(non_snake_case)] fn _doctest_main_src_extractors_common_rs_290_0() -> Result<(), Box<dyn std::error::Error>> {
+    /// use binwalk_ng::extractors::{Chroot, SPARSE_THRESHOLD};
+    ///
+    /// let mut data = b"foo".to_vec();
+    /// data.extend(vec![0; SPARSE_THRESHOLD * 2]);
+    /// data.extend(b"bar");
+    /// data.extend(vec![0; SPARSE_THRESHOLD]);
+    ///
+    /// let file_name = "sparse_file.bin";
+    ///
+    /// let chroot_dir = std::path::Path::new("tests").join("binwalk_unit_tests");
+    /// # let temp_dir = tempfile::tempdir().unwrap();
+    /// # let chroot_dir = temp_dir.path();
+    ///
+    /// let chroot = Chroot::new(&chroot_dir);
+    ///
+    /// assert_eq!(chroot.carve_sparse_file(file_name, &data, 0, data.len()), true);
+    /// assert_eq!(std::fs::read(std::path::Path::new(&chroot_dir).join(file_name))?, data);
+    ///
+    /// // Offsets and sizes taken from a header may be out of range
+    /// assert_eq!(chroot.carve_sparse_file(file_name, &data, 3, usize::MAX), false);
+    /// # Ok(())
+    /// } _doctest_main_src_extractors_common_rs_290_0(); }
+    /// ```
+    pub fn carve_sparse_file(
+        &self,
+        file_path: impl AsRef<Path>,
+        data: &[u8],
+        start: usize,
+        size: usize,
+    ) -> bool {
+        // Zero runs are detected at this granularity
+        const BLOCK_SIZE: usize = 4096;
+
+        let Some(file_data) = start.checked_add(size).and_then(|end| data.get(start..end)) else {
+            error!(
+                "Failed to create file {}: data offset/size are invalid",
+                file_path.as_ref().display()
+            );
+            return false;
+        };
+
+        let Some(mut file) = self.create_file_writer(&file_path) else {
+            return false;
+        };
+
+        let mut write_blocks = || -> std::io::Result<()> {
+            // Length of the run of zero blocks not yet written to disk
+            let mut zero_run: usize = 0;
+
+            for block in file_data.chunks(BLOCK_SIZE) {
+                if block.len() == BLOCK_SIZE && block.iter().all(|b| *b == 0) {
+                    zero_run += block.len();
+                    continue;
+                }
+
+                // Short runs of zeros are not worth a hole; write them out
+                if zero_run >= SPARSE_THRESHOLD {
+                    file.seek(SeekFrom::Current(zero_run as i64))?;
+                } else {
+                    file.write_all(&vec![0; zero_run])?;
+                }
+                zero_run = 0;
+
+                file.write_all(block)?;
+            }
+
+            // Extend the file to its full size, in case it ends in a run of zeros
+            file.set_len(size as u64)
+        };
+
+        let result = write_blocks();
+
+        if let Err(e) = result {
+            error!(
+                "Failed to write data to {}: {e}",
+                file_path.as_ref().display()
+            );
+            return false;
+        }
+
+        true
+    }
+
     /// Creates a device file in the chroot directory.
     ///
     /// Note that this does *not* create a real device file, just a regular file containing the device file info.
@@ -1044,7 +1269,9 @@ impl Chroot {
     ///
     /// The link target is rewritten to a path relative to the symlink that always
     /// resolves *within* the chroot directory, so neither the symlink nor anything that
-    /// follows it can escape the chroot — even when `target_path` is absolute.
+    /// follows it can escape the chroot — even when `target_path` is absolute. Absolute
+    /// targets are taken to be relative to the chroot directory; relative targets whose
+    /// `..` components climb above the chroot directory are refused.
     ///
     /// ## Example
     ///
@@ -1089,6 +1316,25 @@ impl Chroot {
             }
         };
 
+        // Number of ".." needed to climb from the symlink's directory back to the
+        // chroot root: total components, minus the leading root, minus the symlink
+        // file itself.
+        let depth = self
+            .strip_chroot_prefix(&safe_symlink)
+            .components()
+            .count()
+            .saturating_sub(2);
+
+        // A relative target that climbs above the chroot root is a traversal attempt
+        if !target.is_absolute() && climbs_above_root(depth, target) {
+            error!(
+                "Refusing to create symlink {} -> {}: target escapes the chroot",
+                symlink.display(),
+                target.display()
+            );
+            return false;
+        }
+
         let safe_target_base = if target.is_absolute() {
             self.chrooted_path(target)
         } else {
@@ -1097,7 +1343,6 @@ impl Chroot {
             self.safe_path_join(parent, target)
         };
 
-        let symlink_inside = self.strip_chroot_prefix(&safe_symlink);
         let target_inside = self.strip_chroot_prefix(&safe_target_base);
 
         // Build a relative path from the symlink's location to the target so the link
@@ -1105,10 +1350,6 @@ impl Chroot {
         // (even when the archive's target is absolute, e.g. "/etc/passwd").
         let mut relative_target = PathBuf::new();
 
-        // Number of ".." needed to climb from the symlink's directory back to the
-        // chroot root: total components, minus the leading root, minus the symlink
-        // file itself.
-        let depth = symlink_inside.components().count().saturating_sub(2);
         for _ in 0..depth {
             relative_target.push("..");
         }
@@ -1339,7 +1580,6 @@ impl Default for Chroot {
 }
 
 /// Recursively walks a given directory and returns a list of regular non-zero size files in the given directory path.
-#[allow(dead_code)]
 pub fn get_extracted_files(directory: impl AsRef<Path>) -> Vec<PathBuf> {
     let mut regular_files: Vec<PathBuf> = vec![];
 
@@ -1374,10 +1614,35 @@ pub fn execute(
 ) -> ExtractionResult {
     let mut result = ExtractionResult::default();
 
+    // If the signature result specified a preferred extractor, use that instead of the default signature extractor
+    let extractor_definition = extractor.as_ref().map(|default_extractor| {
+        signature
+            .preferred_extractor
+            .as_ref()
+            .unwrap_or(default_extractor)
+            .resolve(file_data, signature)
+    });
+
+    // Identify-only extractors just record the size of the signature's data, no output directory is needed
+    if let Some(extractor_definition) = &extractor_definition
+        && matches!(extractor_definition.utility, ExtractorType::IdentifyOnly)
+    {
+        debug!(
+            "Signature {} at offset {:#X} is identified only, not extracting",
+            signature.name, signature.offset
+        );
+        result.size = Some(signature.size);
+        result.success = true;
+        result.identified_only = true;
+        result.extractor = IDENTIFY_ONLY_EXTRACTOR.to_string();
+        result.do_not_recurse = true;
+        return result;
+    }
+
     // Create an output directory for the extraction
     if let Ok(output_directory) = create_output_directory(&file_path, signature.offset) {
         // Make sure a default extractor was actually defined (this function should not be called if signature.extractor is None)
-        match &extractor {
+        match extractor_definition {
             None => {
                 error!(
                     "Attempted to extract {} data, but no extractor is defined!",
@@ -1385,13 +1650,7 @@ pub fn execute(
                 );
             }
 
-            Some(default_extractor) => {
-                // If the signature result specified a preferred extractor, use that instead of the default signature extractor
-                let extractor_definition = signature.preferred_extractor.as_ref().map_or_else(
-                    || default_extractor.clone(),
-                    |preferred_extractor| preferred_extractor.clone(),
-                );
-
+            Some(extractor_definition) => {
                 // Decide how to execute the extractor depending on the extractor type
                 match &extractor_definition.utility {
                     ExtractorType::None => {
@@ -1401,12 +1660,39 @@ pub fn execute(
                         );
                     }
 
+                    // Handled above, before the output directory was created
+                    ExtractorType::IdentifyOnly => (),
+
+                    ExtractorType::Carve(file_name) => {
+                        debug!("Carving {} data to {file_name}", signature.name);
+                        let chroot = Chroot::new(&output_directory);
+                        result.success = chroot.carve_file(
+                            file_name,
+                            file_data,
+                            signature.offset,
+                            signature.size,
+                        );
+                        result.size = Some(signature.size);
+                        // Carving is built in, so name it like an internal extractor
+                        result.extractor = format!("{}_built_in", signature.name);
+                    }
+
                     ExtractorType::Internal(func) => {
                         debug!("Executing internal {} extractor", signature.name);
                         // Run the internal extractor function
                         result = func(file_data, signature.offset, Some(&output_directory));
                         // Set the extractor name to "<signature name>_built_in"
                         result.extractor = format!("{}_built_in", signature.name);
+
+                        // Absurd compression ratios are a sign of a decompression bomb or misidentified data
+                        if let Some(ratio) = result.compression_ratio
+                            && ratio > SUSPICIOUS_COMPRESSION_RATIO
+                        {
+                            warn!(
+                                "Signature {} at offset {:#X} has a suspicious compression ratio of {ratio:.1}:1",
+                                signature.name, signature.offset
+                            );
+                        }
                     }
 
                     ExtractorType::External(cmd) => {
@@ -1451,6 +1737,10 @@ pub fn execute(
                     result.success = false;
                     warn!("Extractor exited successfully, but no data was extracted");
                 }
+
+                if result.success {
+                    result.extracted_files = get_extracted_files(&result.output_directory);
+                }
             }
         }
 
@@ -1468,6 +1758,96 @@ pub fn execute(
     result
 }
 
+/// Executes an extractor for the provided SignatureResult without writing anything to disk; the extracted data is
+/// returned in ExtractionResult.data.
+///
+/// Only extractors that produce a single stream of data support this: carve extractors, and internal extractors that
+/// define an Extractor.in_memory function. For any other extractor, data is None.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::extractors;
+/// use binwalk_ng::extractors::carve::carve_extractor;
+/// use binwalk_ng::signatures::SignatureResult;
+///
+/// let file_data = b"\x00\x00\x00\x00BLOBDATA\xFF\xFF";
+///
+/// let signature = SignatureResult {
+///     name: "blob".to_string(),
+///     offset: 4,
+///     size: 8,
+///     ..Default::default()
+/// };
+///
+/// let result = extractors::execute_in_memory(file_data, &signature, &Some(carve_extractor("blob.bin")));
+///
+/// assert!(result.success);
+/// assert_eq!(result.data, Some(b"BLOBDATA".to_vec()));
+/// ```
+pub fn execute_in_memory(
+    file_data: &[u8],
+    signature: &SignatureResult,
+    extractor: &Option<Extractor>,
+) -> ExtractionResult {
+    let mut result = ExtractionResult::default();
+
+    // As with execute, a preferred extractor is only used if the signature defines a default extractor
+    let Some(extractor_definition) = extractor.as_ref().map(|default_extractor| {
+        signature
+            .preferred_extractor
+            .as_ref()
+            .unwrap_or(default_extractor)
+            .resolve(file_data, signature)
+    }) else {
+        error!(
+            "Attempted to extract {} data, but no extractor is defined!",
+            signature.name
+        );
+        return result;
+    };
+
+    match &extractor_definition.utility {
+        ExtractorType::Internal(_) => match extractor_definition.in_memory {
+            Some(func) => {
+                debug!("Executing internal {} extractor in memory", signature.name);
+                result = func(file_data, signature.offset);
+                result.extractor = format!("{}_built_in", signature.name);
+            }
+            None => {
+                warn!(
+                    "Signature {}: the internal extractor does not support extraction in memory",
+                    signature.name
+                );
+            }
+        },
+
+        ExtractorType::Carve(_) => {
+            if let Some(data) = signature
+                .offset
+                .checked_add(signature.size)
+                .and_then(|end| file_data.get(signature.offset..end))
+            {
+                result.data = Some(data.to_vec());
+                result.size = Some(signature.size);
+                result.success = true;
+            }
+            result.extractor = format!("{}_built_in", signature.name);
+        }
+
+        ExtractorType::External(_) | ExtractorType::IdentifyOnly | ExtractorType::None => {
+            warn!(
+                "Signature {}: only carve and internal extractors can be run in memory",
+                signature.name
+            );
+        }
+    }
+
+    result.do_not_recurse = extractor_definition.do_not_recurse;
+
+    result
+}
+
 /// Spawn an external extractor process.
 fn spawn(
     file_data: &[u8],
@@ -1494,6 +1874,18 @@ fn spawn(
                 "invalid external command of type None",
             ));
         }
+        ExtractorType::IdentifyOnly => {
+            error!("Tried to run an identify-only extractor as an external command!");
+            return Err(std::io::Error::other(
+                "attempt to execute an identify-only extractor as an external command",
+            ));
+        }
+        ExtractorType::Carve(_) => {
+            error!("Tried to run a carve extractor as an external command!");
+            return Err(std::io::Error::other(
+                "attempt to execute a carve extractor as an external command",
+            ));
+        }
     };
 
     // Carved file path will be <output directory>/<signature.name>_<hex offset>.<extractor.extension>
@@ -1537,8 +1929,8 @@ fn spawn(
     info!("Spawning process {} {:?}", command, extractor.arguments);
     match process::Command::new(&command)
         .args(&extractor.arguments)
-        .stdout(process::Stdio::null())
-        .stderr(process::Stdio::null())
+        .stdout(process::Stdio::piped())
+        .stderr(process::Stdio::piped())
         .current_dir(output_directory)
         .spawn()
     {
@@ -1555,7 +1947,9 @@ fn spawn(
             let proc_info = ProcInfo {
                 child,
                 exit_codes: extractor.exit_codes,
+                allow_nonzero_exit: extractor.allow_nonzero_exit,
                 carved_file,
+                timeout: extractor.timeout,
             };
             Ok(proc_info)
         }
@@ -1565,11 +1959,12 @@ fn spawn(
 /// Waits for an extraction process to complete.
 /// Returns ExtractionError if the extractor was prematurely terminated, else returns an ExtractionResult.
 fn proc_wait(mut worker_info: ProcInfo) -> Result<ExtractionResult, ExtractionError> {
-    // The standard exit success value is 0
-    const EXIT_SUCCESS: i32 = 0;
+    // Drain the child's output as it runs, so that it never blocks on a full pipe
+    let stdout_reader = worker_info.child.stdout.take().map(spawn_output_reader);
+    let stderr_reader = worker_info.child.stderr.take().map(spawn_output_reader);
 
     // Block until child process has terminated
-    match worker_info.child.wait() {
+    match wait_with_timeout(&mut worker_info.child, worker_info.timeout) {
         // Child was terminated from an external signal, status unknown, assume failure but do nothing else
         Err(e) => {
             error!("Failed to retreive child process status: {e}");
@@ -1593,12 +1988,16 @@ fn proc_wait(mut worker_info: ProcInfo) -> Result<ExtractionResult, ExtractionEr
             // Check the extractor's exit status
             match status.code() {
                 None => {
+                    warn!("Child process was terminated by a signal");
                     extraction_success = false;
                 }
 
                 Some(code) => {
                     // Make sure the extractor's exit code is an expected one
-                    if code == EXIT_SUCCESS || worker_info.exit_codes.contains(&code) {
+                    if status.success()
+                        || worker_info.allow_nonzero_exit
+                        || worker_info.exit_codes.contains(&code)
+                    {
                         extraction_success = true;
                     } else {
                         warn!("Child process exited with unexpected code: {code}");
@@ -1606,15 +2005,110 @@ fn proc_wait(mut worker_info: ProcInfo) -> Result<ExtractionResult, ExtractionEr
                 }
             }
 
+            // Keep the tail end of a failed extractor's output, which usually says what went wrong
+            let mut extractor_output: Option<String> = None;
+            if !extraction_success {
+                let deadline = Instant::now() + OUTPUT_READER_TIMEOUT;
+                let output_lines: Vec<String> = [stdout_reader, stderr_reader]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|reader| join_with_deadline(reader, deadline))
+                    .flatten()
+                    .collect();
+
+                if !output_lines.is_empty() {
+                    let output = output_lines.join("\n");
+                    info!("External extractor output:\n{output}");
+                    extractor_output = Some(output);
+                }
+            }
+
             // Return an ExtractionResult with the appropriate success status
             Ok(ExtractionResult {
                 success: extraction_success,
+                extractor_output,
                 ..Default::default()
             })
         }
     }
 }
 
+/// Spawns a thread that reads a child process's output until EOF, and returns the last EXTRACTOR_OUTPUT_LINES lines,
+/// each truncated to EXTRACTOR_OUTPUT_LINE_SIZE bytes.
+fn spawn_output_reader(output: impl Read + Send + 'static) -> thread::JoinHandle<VecDeque<String>> {
+    thread::spawn(move || {
+        let mut last_lines: VecDeque<String> = VecDeque::with_capacity(EXTRACTOR_OUTPUT_LINES);
+        let mut reader = BufReader::new(output);
+        let mut line: Vec<u8> = Vec::new();
+
+        // Output may not be UTF-8, so read raw lines and convert them lossily
+        while let Ok(n) = (&mut reader)
+            .take(EXTRACTOR_OUTPUT_LINE_SIZE as u64)
+            .read_until(b'\n', &mut line)
+            && n > 0
+        {
+            // Don't buffer the rest of an overly long line (e.g., a progress bar that only uses carriage returns)
+            if !line.ends_with(b"\n") && reader.skip_until(b'\n').is_err() {
+                break;
+            }
+
+            if last_lines.len() == EXTRACTOR_OUTPUT_LINES {
+                last_lines.pop_front();
+            }
+            last_lines.push_back(String::from_utf8_lossy(&line).trim_end().to_string());
+            line.clear();
+        }
+
+        last_lines
+    })
+}
+
+/// Waits for a thread to finish until the deadline; if it has not finished by then, it is detached and None is returned.
+fn join_with_deadline<T>(handle: thread::JoinHandle<T>, deadline: Instant) -> Option<T> {
+    while !handle.is_finished() {
+        if Instant::now() >= deadline {
+            debug!("Gave up waiting for external extractor output");
+            return None;
+        }
+        thread::sleep(TIMEOUT_POLL_INTERVAL);
+    }
+
+    handle.join().ok()
+}
+
+/// Waits for a child process to exit, killing it if it is still running once the timeout, if any, has elapsed.
+/// The child is always reaped before returning.
+fn wait_with_timeout(
+    child: &mut process::Child,
+    timeout: Option<Duration>,
+) -> Result<process::ExitStatus, std::io::Error> {
+    let Some(timeout) = timeout else {
+        return child.wait();
+    };
+
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+
+        if Instant::now() >= deadline {
+            warn!(
+                "External extractor timed out after {:.1} seconds, killing it",
+                timeout.as_secs_f64()
+            );
+            // The child may have exited in the meantime, in which case there is nothing to kill
+            if let Err(e) = child.kill() {
+                debug!("Failed to kill timed out child process: {e}");
+            }
+            return child.wait();
+        }
+
+        thread::sleep(TIMEOUT_POLL_INTERVAL);
+    }
+}
+
 // Create an output directory in which to place extraction results
 fn create_output_directory(
     file_path: impl AsRef<Path>,
@@ -1678,8 +2172,30 @@ fn was_something_extracted(output_directory: impl AsRef<Path>) -> bool {
     false
 }
 
+/// Returns true if following the relative symlink target `target` from a directory `depth` levels below the chroot
+/// root would climb above the chroot root.
+fn climbs_above_root(depth: usize, target: &Path) -> bool {
+    let mut depth = depth;
+
+    for component in target.components() {
+        match component {
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(parent_depth) => depth = parent_depth,
+                None => return true,
+            },
+            Component::Normal(_) => depth += 1,
+            _ => (),
+        }
+    }
+
+    false
+}
+
+pub mod carve;
 pub mod dumpifs;
+pub mod identify;
 pub mod inflate;
+pub mod squashfs_reader;
 pub mod swapped;
 pub mod tsk;
 
@@ -1924,6 +2440,23 @@ mod chroot_security_tests {
         assert_eq!(fs::read(dir.path().join("link")).unwrap(), b"hi");
     }
 
+    /// A relative symlink target that climbs above the chroot root via `..` is refused,
+    /// and no link is created.
+    #[test]
+    fn relative_symlink_traversal_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let chroot = Chroot::new(dir.path());
+
+        assert!(chroot.create_directory("a"));
+        assert!(!chroot.create_symlink("a/evil", "../../etc/passwd"));
+        assert!(fs::symlink_metadata(dir.path().join("a/evil")).is_err());
+
+        // Climbing up to, but not above, the chroot root is fine
+        assert!(chroot.create_file("hello.txt", b"hi"));
+        assert!(chroot.create_symlink("a/link", "../b/../hello.txt"));
+        assert_eq!(fs::read(dir.path().join("a/link")).unwrap(), b"hi");
+    }
+
     /// A nested symlink (deeper than the chroot root) also resolves within the chroot.
     #[test]
     fn nested_symlink_resolves_within_chroot() {
@@ -0,0 +1,40 @@
+use crate::extractors;
+
+/// Describes an extractor that writes the signature's data (offset..offset+size, as reported by the signature parser)
+/// to a file named `file_name` in the output directory.
+///
+/// Useful for formats that need no processing beyond being carved out of the surrounding data (e.g., images).
+///
+/// ```
+/// use binwalk_ng::extractors::{self, ExtractorType};
+/// use binwalk_ng::extractors::carve::carve_extractor;
+/// use binwalk_ng::signatures::SignatureResult;
+///
+/// # let temp_dir = tempfile::tempdir().unwrap();
+/// # let file_path = temp_dir.path().join("firmware.bin");
+/// let file_data = b"\x00\x00\x00\x00BLOBDATA\xFF\xFF";
+///
+/// let signature = SignatureResult {
+///     name: "blob".to_string(),
+///     offset: 4,
+///     size: 8,
+///     ..Default::default()
+/// };
+///
+/// let extractor = carve_extractor("blob.bin");
+/// assert!(matches!(&extractor.utility, ExtractorType::Carve(file_name) if file_name == "blob.bin"));
+///
+/// let result = extractors::execute(file_data, &file_path, &signature, &Some(extractor));
+///
+/// assert!(result.success);
+/// assert_eq!(result.size, Some(8));
+/// assert_eq!(result.extractor, "blob_built_in");
+/// assert_eq!(result.extracted_files, [result.output_directory.join("blob.bin")]);
+/// assert_eq!(std::fs::read(&result.extracted_files[0]).unwrap(), b"BLOBDATA");
+/// ```
+pub fn carve_extractor(file_name: &str) -> extractors::Extractor {
+    extractors::Extractor {
+        utility: extractors::ExtractorType::Carve(file_name.to_string()),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,20 @@
+use crate::extractors;
+
+/// Describes an extractor for data that can be identified and sized, but not extracted (e.g., encrypted volumes).
+///
+/// Running this extractor never writes anything to disk; it only records the signature's data region, see
+/// ExtractionResult.identified_only. Use --carve to save the raw data.
+///
+/// ```
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::extractors::identify::identify_only_extractor;
+///
+/// assert!(matches!(identify_only_extractor().utility, ExtractorType::IdentifyOnly));
+/// ```
+pub fn identify_only_extractor() -> extractors::Extractor {
+    extractors::Extractor {
+        utility: extractors::ExtractorType::IdentifyOnly,
+        do_not_recurse: true,
+        ..Default::default()
+    }
+}
@@ -6,7 +6,9 @@ use std::{io::Read, path::Path};
 #[derive(Debug, Default, Clone)]
 pub struct DeflateResult {
     pub size: usize,
+    pub decompressed_size: usize,
     pub adler32: u32,
+    pub crc32: u32,
     pub success: bool,
 }
 
@@ -25,8 +27,13 @@ pub fn inflate_decompressor(
     let mut result = DeflateResult::default();
 
     let mut adler32_checksum = Adler32::new();
+    let mut crc32_checksum = crc32fast::Hasher::new();
     let mut decompressed_buffer = [0; BLOCK_SIZE];
-    let mut decompressor = DeflateDecoder::new(&file_data[offset..]);
+    let Some(compressed_data) = file_data.get(offset..) else {
+        return result;
+    };
+
+    let mut decompressor = DeflateDecoder::new(compressed_data);
 
     /*
      * Loop through all compressed data and decompress it.
@@ -49,6 +56,7 @@ pub fn inflate_decompressor(
                 // Decompressed a block of data, update checksum and if extraction was requested write the decompressed block to the output file
                 if n > 0 {
                     adler32_checksum.write_slice(&decompressed_buffer[0..n]);
+                    crc32_checksum.update(&decompressed_buffer[0..n]);
 
                     if let Some(output_directory) = output_directory {
                         let chroot = Chroot::new(output_directory);
@@ -65,7 +73,9 @@ pub fn inflate_decompressor(
                     if decompressor.total_out() > 0 {
                         result.success = true;
                         result.adler32 = adler32_checksum.checksum();
+                        result.crc32 = crc32_checksum.clone().finalize();
                         result.size = decompressor.total_in() as usize;
+                        result.decompressed_size = decompressor.total_out() as usize;
                     }
 
                     // Nothing else to do, break
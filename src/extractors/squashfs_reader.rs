@@ -0,0 +1,714 @@
+//! Read-only access to SquashFS v4 images, for recovering file names and small files without an external utility.
+//!
+//! Only little endian SquashFS v4 images compressed with gzip or xz are supported.
+
+use crate::structures::StructureError;
+use flate2::read::ZlibDecoder;
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
+
+/// SquashFS v4 (little endian) magic bytes
+const SQUASHFS_MAGIC: &[u8] = b"hsqs";
+
+/// Maximum size of a decompressed metadata block
+const METADATA_BLOCK_SIZE: usize = 8192;
+
+/// Set in a metadata block header if the block is stored uncompressed
+const METADATA_UNCOMPRESSED: u16 = 0x8000;
+
+/// Set in a data block / fragment size if the block is stored uncompressed
+const DATA_UNCOMPRESSED: u32 = 0x0100_0000;
+
+/// Fragment index of files that do not end in a fragment
+const NO_FRAGMENT: u32 = 0xFFFF_FFFF;
+
+/// Size of an entry in the fragment table
+const FRAGMENT_ENTRY_SIZE: usize = 16;
+
+/// Directories are only followed this deep, to guard against maliciously looped directory trees
+const MAX_DIRECTORY_DEPTH: usize = 64;
+
+/// Valid data block sizes are powers of two within this range
+const MIN_BLOCK_SIZE: usize = 4 * 1024;
+const MAX_BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Compression algorithms supported by the reader
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SquashFSCompression {
+    Gzip,
+    Xz,
+}
+
+/// The type of a directory entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SquashFSEntryType {
+    Directory,
+    File,
+    Symlink,
+    /// Device files, FIFOs and sockets
+    Other,
+}
+
+/// Describes a single file or directory in the image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SquashFSEntry {
+    /// Full path of the entry, relative to the root of the file system
+    pub path: String,
+    pub entry_type: SquashFSEntryType,
+    /// Size of the file's contents; 0 for anything other than regular files
+    pub size: usize,
+    /// Reference to the entry's inode: the metadata block offset in the inode table << 16 | offset within that block
+    pub inode: u64,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct SuperBlockBytes {
+    magic: [u8; 4],
+    inode_count: zerocopy::U32<LE>,
+    modification_time: zerocopy::U32<LE>,
+    block_size: zerocopy::U32<LE>,
+    fragment_count: zerocopy::U32<LE>,
+    compression_id: zerocopy::U16<LE>,
+    block_log: zerocopy::U16<LE>,
+    flags: zerocopy::U16<LE>,
+    id_count: zerocopy::U16<LE>,
+    major_version: zerocopy::U16<LE>,
+    minor_version: zerocopy::U16<LE>,
+    root_inode: zerocopy::U64<LE>,
+    bytes_used: zerocopy::U64<LE>,
+    id_table_start: zerocopy::U64<LE>,
+    xattr_id_table_start: zerocopy::U64<LE>,
+    inode_table_start: zerocopy::U64<LE>,
+    directory_table_start: zerocopy::U64<LE>,
+    fragment_table_start: zerocopy::U64<LE>,
+    export_table_start: zerocopy::U64<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct InodeHeaderBytes {
+    inode_type: zerocopy::U16<LE>,
+    permissions: zerocopy::U16<LE>,
+    uid_index: zerocopy::U16<LE>,
+    gid_index: zerocopy::U16<LE>,
+    modification_time: zerocopy::U32<LE>,
+    inode_number: zerocopy::U32<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct BasicDirectoryInodeBytes {
+    header: InodeHeaderBytes,
+    block_index: zerocopy::U32<LE>,
+    link_count: zerocopy::U32<LE>,
+    file_size: zerocopy::U16<LE>,
+    block_offset: zerocopy::U16<LE>,
+    parent_inode: zerocopy::U32<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct ExtendedDirectoryInodeBytes {
+    header: InodeHeaderBytes,
+    link_count: zerocopy::U32<LE>,
+    file_size: zerocopy::U32<LE>,
+    block_index: zerocopy::U32<LE>,
+    parent_inode: zerocopy::U32<LE>,
+    index_count: zerocopy::U16<LE>,
+    block_offset: zerocopy::U16<LE>,
+    xattr_index: zerocopy::U32<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct BasicFileInodeBytes {
+    header: InodeHeaderBytes,
+    blocks_start: zerocopy::U32<LE>,
+    fragment_index: zerocopy::U32<LE>,
+    fragment_offset: zerocopy::U32<LE>,
+    file_size: zerocopy::U32<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct ExtendedFileInodeBytes {
+    header: InodeHeaderBytes,
+    blocks_start: zerocopy::U64<LE>,
+    file_size: zerocopy::U64<LE>,
+    sparse: zerocopy::U64<LE>,
+    link_count: zerocopy::U32<LE>,
+    fragment_index: zerocopy::U32<LE>,
+    fragment_offset: zerocopy::U32<LE>,
+    xattr_index: zerocopy::U32<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct DirectoryHeaderBytes {
+    count: zerocopy::U32<LE>,
+    start: zerocopy::U32<LE>,
+    inode_number: zerocopy::U32<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct DirectoryEntryBytes {
+    offset: zerocopy::U16<LE>,
+    inode_offset: zerocopy::I16<LE>,
+    entry_type: zerocopy::U16<LE>,
+    name_size: zerocopy::U16<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct FragmentEntryBytes {
+    start: zerocopy::U64<LE>,
+    size: zerocopy::U32<LE>,
+    unused: zerocopy::U32<LE>,
+}
+
+/// Location of a regular file's data
+struct FileLayout {
+    blocks_start: usize,
+    file_size: usize,
+    fragment_index: u32,
+    fragment_offset: usize,
+    block_sizes: Vec<u32>,
+}
+
+/// A parsed inode
+enum Inode {
+    Directory {
+        block_index: usize,
+        block_offset: usize,
+        listing_size: usize,
+    },
+    File(FileLayout),
+    Symlink,
+    Other,
+}
+
+/// Read-only SquashFS v4 image reader
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::extractors::squashfs_reader::{SquashFSEntryType, SquashFSReader};
+///
+/// let image = std::fs::read("tests/inputs/squashfs.bin").unwrap();
+/// let reader = SquashFSReader::new(&image).unwrap();
+///
+/// for entry in reader.list().unwrap() {
+///     if entry.entry_type == SquashFSEntryType::File {
+///         assert_eq!(reader.read_file(&entry).unwrap().len(), entry.size);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SquashFSReader<'a> {
+    /// The image data, starting at the superblock
+    image: &'a [u8],
+    pub compression: SquashFSCompression,
+    pub block_size: usize,
+    root_inode: u64,
+    inode_table_start: usize,
+    directory_table_start: usize,
+    fragment_table_start: usize,
+    fragment_count: usize,
+}
+
+impl<'a> SquashFSReader<'a> {
+    /// Parses the superblock at the start of `image`.
+    /// Fails if the image is not a little endian SquashFS v4 image, or is compressed with an unsupported algorithm.
+    pub fn new(image: &'a [u8]) -> Result<Self, StructureError> {
+        const SQUASHFS_V4: u16 = 4;
+        const GZIP: u16 = 1;
+        const XZ: u16 = 4;
+
+        let (superblock, _) =
+            SuperBlockBytes::ref_from_prefix(image).map_err(|_| StructureError)?;
+
+        if superblock.magic != SQUASHFS_MAGIC || superblock.major_version.get() != SQUASHFS_V4 {
+            return Err(StructureError);
+        }
+
+        let compression = match superblock.compression_id.get() {
+            GZIP => SquashFSCompression::Gzip,
+            XZ => SquashFSCompression::Xz,
+            _ => return Err(StructureError),
+        };
+
+        // Data blocks are sized (and decompressed) according to the block size, so don't trust an invalid one
+        let block_size = superblock.block_size.get() as usize;
+
+        if !block_size.is_power_of_two() || !(MIN_BLOCK_SIZE..=MAX_BLOCK_SIZE).contains(&block_size)
+        {
+            return Err(StructureError);
+        }
+
+        // Everything the reader needs lives within bytes_used
+        let image = image
+            .get(..superblock.bytes_used.get() as usize)
+            .ok_or(StructureError)?;
+
+        Ok(Self {
+            image,
+            compression,
+            block_size,
+            root_inode: superblock.root_inode.get(),
+            inode_table_start: superblock.inode_table_start.get() as usize,
+            directory_table_start: superblock.directory_table_start.get() as usize,
+            fragment_table_start: superblock.fragment_table_start.get() as usize,
+            fragment_count: superblock.fragment_count.get() as usize,
+        })
+    }
+
+    /// Decompresses a single data or metadata block, producing at most `max_size` bytes
+    pub fn decompress_block(
+        &self,
+        block_data: &[u8],
+        max_size: usize,
+    ) -> Result<Vec<u8>, StructureError> {
+        let mut decompressed: Vec<u8> = Vec::new();
+
+        let result = match self.compression {
+            SquashFSCompression::Gzip => ZlibDecoder::new(block_data)
+                .take(max_size as u64)
+                .read_to_end(&mut decompressed),
+            SquashFSCompression::Xz => liblzma::read::XzDecoder::new(block_data)
+                .take(max_size as u64)
+                .read_to_end(&mut decompressed),
+        };
+
+        result.map_err(|_| StructureError)?;
+        Ok(decompressed)
+    }
+
+    /// Reads the metadata block at `offset`, returning its decompressed contents and the offset of the next block
+    fn read_metadata_block(&self, offset: usize) -> Result<(Vec<u8>, usize), StructureError> {
+        let header_bytes = self.image.get(offset..offset + 2).ok_or(StructureError)?;
+        let header = u16::from_le_bytes(header_bytes.try_into().unwrap());
+        let block_size = (header & !METADATA_UNCOMPRESSED) as usize;
+
+        let block_start = offset + 2;
+        let block_data = self
+            .image
+            .get(block_start..block_start + block_size)
+            .ok_or(StructureError)?;
+
+        let data = if header & METADATA_UNCOMPRESSED != 0 {
+            block_data.to_vec()
+        } else {
+            self.decompress_block(block_data, METADATA_BLOCK_SIZE)?
+        };
+
+        Ok((data, block_start + block_size))
+    }
+
+    /// Reads `length` bytes of metadata, which may span multiple metadata blocks, starting `block_offset` bytes into
+    /// the (decompressed) metadata block at `table_start + block_index`
+    fn read_metadata(
+        &self,
+        table_start: usize,
+        block_index: usize,
+        block_offset: usize,
+        length: usize,
+    ) -> Result<Vec<u8>, StructureError> {
+        let mut metadata: Vec<u8> = Vec::new();
+        let mut next_block = table_start + block_index;
+
+        while metadata.len() < block_offset + length {
+            let (data, next) = self.read_metadata_block(next_block)?;
+
+            if data.is_empty() {
+                return Err(StructureError);
+            }
+
+            metadata.extend(data);
+            next_block = next;
+        }
+
+        Ok(metadata[block_offset..block_offset + length].to_vec())
+    }
+
+    /// Reads and parses the inode referenced by `inode_ref`
+    fn read_inode(&self, inode_ref: u64) -> Result<Inode, StructureError> {
+        const BASIC_DIRECTORY: u16 = 1;
+        const BASIC_FILE: u16 = 2;
+        const BASIC_SYMLINK: u16 = 3;
+        const EXTENDED_DIRECTORY: u16 = 8;
+        const EXTENDED_FILE: u16 = 9;
+        const EXTENDED_SYMLINK: u16 = 10;
+
+        let block_index = (inode_ref >> 16) as usize;
+        let block_offset = (inode_ref & 0xFFFF) as usize;
+        let read = |length: usize| {
+            self.read_metadata(self.inode_table_start, block_index, block_offset, length)
+        };
+
+        let header_data = read(std::mem::size_of::<InodeHeaderBytes>())?;
+        let (header, _) =
+            InodeHeaderBytes::ref_from_prefix(&header_data).map_err(|_| StructureError)?;
+
+        match header.inode_type.get() {
+            BASIC_DIRECTORY => {
+                let data = read(std::mem::size_of::<BasicDirectoryInodeBytes>())?;
+                let (inode, _) =
+                    BasicDirectoryInodeBytes::ref_from_prefix(&data).map_err(|_| StructureError)?;
+
+                Ok(Inode::Directory {
+                    block_index: inode.block_index.get() as usize,
+                    block_offset: inode.block_offset.get() as usize,
+                    listing_size: inode.file_size.get() as usize,
+                })
+            }
+            EXTENDED_DIRECTORY => {
+                let data = read(std::mem::size_of::<ExtendedDirectoryInodeBytes>())?;
+                let (inode, _) = ExtendedDirectoryInodeBytes::ref_from_prefix(&data)
+                    .map_err(|_| StructureError)?;
+
+                Ok(Inode::Directory {
+                    block_index: inode.block_index.get() as usize,
+                    block_offset: inode.block_offset.get() as usize,
+                    listing_size: inode.file_size.get() as usize,
+                })
+            }
+            BASIC_FILE => {
+                let inode_size = std::mem::size_of::<BasicFileInodeBytes>();
+                let data = read(inode_size)?;
+                let (inode, _) =
+                    BasicFileInodeBytes::ref_from_prefix(&data).map_err(|_| StructureError)?;
+
+                let mut layout = FileLayout {
+                    blocks_start: inode.blocks_start.get() as usize,
+                    file_size: inode.file_size.get() as usize,
+                    fragment_index: inode.fragment_index.get(),
+                    fragment_offset: inode.fragment_offset.get() as usize,
+                    block_sizes: vec![],
+                };
+                layout.block_sizes = self.read_block_sizes(&layout, &read, inode_size)?;
+
+                Ok(Inode::File(layout))
+            }
+            EXTENDED_FILE => {
+                let inode_size = std::mem::size_of::<ExtendedFileInodeBytes>();
+                let data = read(inode_size)?;
+                let (inode, _) =
+                    ExtendedFileInodeBytes::ref_from_prefix(&data).map_err(|_| StructureError)?;
+
+                let mut layout = FileLayout {
+                    blocks_start: inode.blocks_start.get() as usize,
+                    file_size: inode.file_size.get() as usize,
+                    fragment_index: inode.fragment_index.get(),
+                    fragment_offset: inode.fragment_offset.get() as usize,
+                    block_sizes: vec![],
+                };
+                layout.block_sizes = self.read_block_sizes(&layout, &read, inode_size)?;
+
+                Ok(Inode::File(layout))
+            }
+            BASIC_SYMLINK | EXTENDED_SYMLINK => Ok(Inode::Symlink),
+            _ => Ok(Inode::Other),
+        }
+    }
+
+    /// Reads the list of data block sizes that follows a file inode
+    fn read_block_sizes(
+        &self,
+        layout: &FileLayout,
+        read: &dyn Fn(usize) -> Result<Vec<u8>, StructureError>,
+        inode_size: usize,
+    ) -> Result<Vec<u32>, StructureError> {
+        // The tail end of the file is stored in a fragment, if it has one
+        let block_count = if layout.fragment_index == NO_FRAGMENT {
+            layout.file_size.div_ceil(self.block_size)
+        } else {
+            layout.file_size / self.block_size
+        };
+
+        // Block sizes can not add up to more data than the image holds
+        if block_count > self.image.len() {
+            return Err(StructureError);
+        }
+
+        let data = read(inode_size + (block_count * 4))?;
+
+        Ok(data[inode_size..]
+            .chunks_exact(4)
+            .map(|size| u32::from_le_bytes(size.try_into().unwrap()))
+            .collect())
+    }
+
+    /// Lists all files and directories in the image, in directory order
+    pub fn list(&self) -> Result<Vec<SquashFSEntry>, StructureError> {
+        let mut entries: Vec<SquashFSEntry> = Vec::new();
+        let mut visited: HashSet<u64> = HashSet::new();
+        self.list_directory(self.root_inode, "", 0, &mut visited, &mut entries)?;
+        Ok(entries)
+    }
+
+    /// Recursively lists the contents of the directory referenced by `inode_ref`; directories that have already been
+    /// listed are not listed again
+    fn list_directory(
+        &self,
+        inode_ref: u64,
+        path: &str,
+        depth: usize,
+        visited: &mut HashSet<u64>,
+        entries: &mut Vec<SquashFSEntry>,
+    ) -> Result<(), StructureError> {
+        const DIRECTORY_TYPE: u16 = 1;
+        const FILE_TYPE: u16 = 2;
+        const SYMLINK_TYPE: u16 = 3;
+        // The listing size includes the implicit "." and ".." entries, which are not actually stored
+        const IMPLICIT_ENTRIES_SIZE: usize = 3;
+
+        if depth > MAX_DIRECTORY_DEPTH {
+            return Err(StructureError);
+        }
+
+        if !visited.insert(inode_ref) {
+            return Ok(());
+        }
+
+        let Inode::Directory {
+            block_index,
+            block_offset,
+            listing_size,
+        } = self.read_inode(inode_ref)?
+        else {
+            return Err(StructureError);
+        };
+
+        let listing_size = listing_size.saturating_sub(IMPLICIT_ENTRIES_SIZE);
+        let listing = self.read_metadata(
+            self.directory_table_start,
+            block_index,
+            block_offset,
+            listing_size,
+        )?;
+
+        let mut next_header = 0;
+
+        while next_header < listing.len() {
+            let (header, _) = DirectoryHeaderBytes::ref_from_prefix(&listing[next_header..])
+                .map_err(|_| StructureError)?;
+            let mut next_entry = next_header + std::mem::size_of::<DirectoryHeaderBytes>();
+
+            // The count field is one less than the number of entries that follow
+            for _ in 0..=header.count.get() {
+                let (entry, _) = DirectoryEntryBytes::ref_from_prefix(
+                    listing.get(next_entry..).ok_or(StructureError)?,
+                )
+                .map_err(|_| StructureError)?;
+
+                let name_start = next_entry + std::mem::size_of::<DirectoryEntryBytes>();
+                // The name_size field is one less than the length of the name
+                let name_end = name_start + entry.name_size.get() as usize + 1;
+                let name = String::from_utf8_lossy(
+                    listing.get(name_start..name_end).ok_or(StructureError)?,
+                );
+                next_entry = name_end;
+
+                let entry_path = format!("{path}/{name}");
+                let entry_inode = ((header.start.get() as u64) << 16) | entry.offset.get() as u64;
+
+                let entry_type = match entry.entry_type.get() {
+                    DIRECTORY_TYPE => SquashFSEntryType::Directory,
+                    FILE_TYPE => SquashFSEntryType::File,
+                    SYMLINK_TYPE => SquashFSEntryType::Symlink,
+                    _ => SquashFSEntryType::Other,
+                };
+
+                let size = match entry_type {
+                    SquashFSEntryType::File => match self.read_inode(entry_inode)? {
+                        Inode::File(layout) => layout.file_size,
+                        _ => return Err(StructureError),
+                    },
+                    _ => 0,
+                };
+
+                entries.push(SquashFSEntry {
+                    path: entry_path.clone(),
+                    entry_type,
+                    size,
+                    inode: entry_inode,
+                });
+
+                if entry_type == SquashFSEntryType::Directory {
+                    self.list_directory(entry_inode, &entry_path, depth + 1, visited, entries)?;
+                }
+            }
+
+            next_header = next_entry;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the contents of a regular file
+    pub fn read_file(&self, entry: &SquashFSEntry) -> Result<Vec<u8>, StructureError> {
+        let mut contents: Vec<u8> = Vec::new();
+        self.write_file(entry, &mut contents)?;
+        Ok(contents)
+    }
+
+    /// Writes the contents of a regular file to `output`, one block at a time; returns the number of bytes written
+    pub fn write_file(
+        &self,
+        entry: &SquashFSEntry,
+        output: &mut impl Write,
+    ) -> Result<usize, StructureError> {
+        let Inode::File(layout) = self.read_inode(entry.inode)? else {
+            return Err(StructureError);
+        };
+
+        // Each data block holds at most one block's worth of data, as does the fragment
+        let max_file_size = layout
+            .block_sizes
+            .len()
+            .saturating_add(1)
+            .saturating_mul(self.block_size);
+
+        if layout.file_size > max_file_size {
+            return Err(StructureError);
+        }
+
+        let mut written: usize = 0;
+        let mut block_start = layout.blocks_start;
+
+        for block_size in &layout.block_sizes {
+            let remaining = layout.file_size - written;
+            let expected_size = remaining.min(self.block_size);
+            let stored_size = (block_size & !DATA_UNCOMPRESSED) as usize;
+
+            let block = if stored_size == 0 {
+                // Sparse block
+                vec![0; expected_size]
+            } else {
+                let block_end = block_start.checked_add(stored_size).ok_or(StructureError)?;
+                let block_data = self
+                    .image
+                    .get(block_start..block_end)
+                    .ok_or(StructureError)?;
+                block_start = block_end;
+
+                if block_size & DATA_UNCOMPRESSED != 0 {
+                    block_data[..expected_size.min(block_data.len())].to_vec()
+                } else {
+                    self.decompress_block(block_data, expected_size)?
+                }
+            };
+
+            output.write_all(&block).map_err(|_| StructureError)?;
+            written += block.len();
+        }
+
+        // Whatever data is left is in a fragment block
+        if layout.fragment_index != NO_FRAGMENT && written < layout.file_size {
+            let remaining = layout.file_size - written;
+            let fragment = self.read_fragment(layout.fragment_index as usize)?;
+
+            let fragment_data = layout
+                .fragment_offset
+                .checked_add(remaining)
+                .and_then(|fragment_end| fragment.get(layout.fragment_offset..fragment_end))
+                .ok_or(StructureError)?;
+
+            output
+                .write_all(fragment_data)
+                .map_err(|_| StructureError)?;
+            written += fragment_data.len();
+        }
+
+        if written != layout.file_size {
+            return Err(StructureError);
+        }
+
+        Ok(written)
+    }
+
+    /// Reads and decompresses the fragment block at `fragment_index` in the fragment table
+    fn read_fragment(&self, fragment_index: usize) -> Result<Vec<u8>, StructureError> {
+        const ENTRIES_PER_BLOCK: usize = METADATA_BLOCK_SIZE / FRAGMENT_ENTRY_SIZE;
+
+        if fragment_index >= self.fragment_count {
+            return Err(StructureError);
+        }
+
+        // The fragment table is located via a list of pointers to the metadata blocks that hold the fragment entries
+        let pointer_offset = self.fragment_table_start + ((fragment_index / ENTRIES_PER_BLOCK) * 8);
+        let pointer_bytes = self
+            .image
+            .get(pointer_offset..pointer_offset + 8)
+            .ok_or(StructureError)?;
+        let metadata_block = u64::from_le_bytes(pointer_bytes.try_into().unwrap()) as usize;
+
+        let entry_data = self.read_metadata(
+            metadata_block,
+            0,
+            (fragment_index % ENTRIES_PER_BLOCK) * FRAGMENT_ENTRY_SIZE,
+            FRAGMENT_ENTRY_SIZE,
+        )?;
+        let (entry, _) =
+            FragmentEntryBytes::ref_from_prefix(&entry_data).map_err(|_| StructureError)?;
+
+        let fragment_start = entry.start.get() as usize;
+        let stored_size = (entry.size.get() & !DATA_UNCOMPRESSED) as usize;
+        let fragment_data = fragment_start
+            .checked_add(stored_size)
+            .and_then(|fragment_end| self.image.get(fragment_start..fragment_end))
+            .ok_or(StructureError)?;
+
+        if entry.size.get() & DATA_UNCOMPRESSED != 0 {
+            Ok(fragment_data.to_vec())
+        } else {
+            self.decompress_block(fragment_data, self.block_size)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_and_reads_gzip_image() {
+        let image = std::fs::read("tests/inputs/squashfs.bin").unwrap();
+        let reader = SquashFSReader::new(&image).unwrap();
+        assert_eq!(reader.compression, SquashFSCompression::Gzip);
+
+        let entries = reader.list().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "/lsb-release");
+        assert_eq!(entries[0].entry_type, SquashFSEntryType::File);
+
+        // The file is small enough to be stored entirely in a fragment
+        let contents = reader.read_file(&entries[0]).unwrap();
+        assert_eq!(contents.len(), 104);
+        assert!(contents.starts_with(b"DISTRIB_ID=Ubuntu\n"));
+    }
+
+    #[test]
+    fn rejects_unsupported_images() {
+        let mut image = std::fs::read("tests/inputs/squashfs.bin").unwrap();
+
+        // LZO compression
+        image[20] = 3;
+        assert!(SquashFSReader::new(&image).is_err());
+
+        // Truncated image
+        image[20] = 1;
+        assert!(SquashFSReader::new(&image[..0x100]).is_err());
+
+        // Block size that is not a power of two
+        image[12..16].copy_from_slice(&0x0001_2000_u32.to_le_bytes());
+        assert!(SquashFSReader::new(&image).is_err());
+
+        // Block size that is too large
+        image[12..16].copy_from_slice(&0x0200_0000_u32.to_le_bytes());
+        assert!(SquashFSReader::new(&image).is_err());
+    }
+}
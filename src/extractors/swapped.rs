@@ -12,6 +12,8 @@ use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
 ///
 /// match swapped_extractor_u16().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -41,6 +41,7 @@ pub mod gif;
 pub mod gpg;
 pub mod gzip;
 pub mod hashes;
+pub mod ihex;
 pub mod iso9660;
 pub mod jboot;
 pub mod jffs2;
@@ -50,12 +51,15 @@ pub mod logfs;
 pub mod luks;
 pub mod lz4;
 pub mod lzfse;
+pub mod lzip;
 pub mod lzma;
 pub mod lzop;
+pub mod macho;
 pub mod matter_ota;
 pub mod mbr;
 pub mod mh01;
 pub mod ntfs;
+pub mod olecf;
 pub mod openssl;
 pub mod packimg;
 pub mod pcap;
@@ -77,6 +81,7 @@ pub mod rtk;
 pub mod seama;
 pub mod sevenzip;
 pub mod shrs;
+pub mod sqlite;
 pub mod squashfs;
 pub mod srec;
 pub mod svg;
@@ -85,8 +90,10 @@ pub mod tplink;
 pub mod trx;
 pub mod ubi;
 pub mod uboot;
+pub mod uboot_env;
 pub mod uefi;
 pub mod uimage;
+pub mod vmdk;
 pub mod vxworks;
 pub mod wince;
 pub mod xz;
@@ -1,5 +1,7 @@
+use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
 use crate::signatures::{CONFIDENCE_LOW, CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
 use crate::structures::StructureError;
+use std::path::Path;
 use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
 
 /// Human readable description
@@ -23,55 +25,478 @@ pub fn android_bootimg_parser(
         ..Default::default()
     };
 
-    if let Ok(bootimg_header) = parse_android_bootimg_header(&file_data[offset..]) {
-        if offset == 0 {
-            result.confidence = CONFIDENCE_MEDIUM;
-        }
+    let bootimg_data = file_data.get(offset..).ok_or(SignatureError)?;
+    let bootimg_header = parse_android_bootimg_header(bootimg_data).map_err(|_| SignatureError)?;
+
+    if offset == 0 {
+        result.confidence = CONFIDENCE_MEDIUM;
+    }
+
+    result.description = format!(
+        "{}, header version: {}, page size: {}, kernel size: {} bytes",
+        result.description,
+        bootimg_header.header_version,
+        bootimg_header.page_size,
+        bootimg_header.kernel_size,
+    );
 
+    // Load addresses were dropped from the header in version 3
+    if bootimg_header.header_version < 3 {
         result.description = format!(
-            "{}, kernel size: {} bytes, kernel load address: {:#X}, ramdisk size: {} bytes, ramdisk load address: {:#X}",
+            "{}, kernel load address: {:#X}, ramdisk size: {} bytes, ramdisk load address: {:#X}",
             result.description,
-            bootimg_header.kernel_size,
             bootimg_header.kernel_load_address,
             bootimg_header.ramdisk_size,
             bootimg_header.ramdisk_load_address,
         );
+    } else {
+        result.description = format!(
+            "{}, ramdisk size: {} bytes",
+            result.description, bootimg_header.ramdisk_size,
+        );
+    }
+
+    if let Some((version, patch_level)) = bootimg_header.os_version() {
+        result.description = format!(
+            "{}, OS version: {}, patch level: {}",
+            result.description, version, patch_level
+        );
+    }
+
+    let total_size = bootimg_header.total_size();
+
+    // If all the sections are there, report the full size of the boot image
+    if total_size <= bootimg_data.len() {
+        result.size = total_size;
         return Ok(result);
     }
 
-    Err(SignatureError)
+    // Otherwise the header is valid, but the image is cut short
+    result.extraction_declined = true;
+    result.set_truncated(total_size, bootimg_data.len());
+    Ok(result)
+}
+
+/// A section of an Android boot image (kernel, ramdisk, etc)
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AndroidBootImageSection {
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
 }
 
 /// Struct to store Android boot image header info
+///
+/// Fields that are not present in a given header version are left as 0.
 #[derive(Debug, Default, Clone)]
 pub struct AndroidBootImageHeader {
-    pub kernel_size: u32,
-    pub ramdisk_size: u32,
+    pub header_version: u32,
+    pub page_size: usize,
+    pub os_version: u32,
+    pub kernel_size: usize,
+    pub ramdisk_size: usize,
+    pub second_size: usize,
+    pub recovery_dtbo_size: usize,
+    pub dtb_size: usize,
+    pub signature_size: usize,
     pub kernel_load_address: u32,
     pub ramdisk_load_address: u32,
 }
 
+impl AndroidBootImageHeader {
+    /// Returns the boot image sections that follow the header, in the order they appear in the image.
+    /// Each section starts on a page boundary; empty sections are omitted.
+    pub fn sections(&self) -> Vec<AndroidBootImageSection> {
+        let section_sizes = [
+            ("kernel", self.kernel_size),
+            ("ramdisk", self.ramdisk_size),
+            ("second", self.second_size),
+            ("recovery_dtbo", self.recovery_dtbo_size),
+            ("dtb", self.dtb_size),
+            ("boot_signature", self.signature_size),
+        ];
+
+        // The header always occupies the first page
+        let mut next_offset = self.page_size;
+        let mut sections = Vec::new();
+
+        for (name, size) in section_sizes {
+            if size > 0 {
+                sections.push(AndroidBootImageSection {
+                    name,
+                    offset: next_offset,
+                    size,
+                });
+                next_offset += size.next_multiple_of(self.page_size);
+            }
+        }
+
+        sections
+    }
+
+    /// Returns the total size of the boot image, including padding
+    pub fn total_size(&self) -> usize {
+        self.sections().last().map_or(self.page_size, |section| {
+            section.offset + section.size.next_multiple_of(self.page_size)
+        })
+    }
+
+    /// Decodes the os_version field into an Android version string (e.g., "11.0.0") and a security patch level
+    /// string (e.g., "2021-03"); returns None if the field is not set
+    pub fn os_version(&self) -> Option<(String, String)> {
+        if self.os_version == 0 {
+            return None;
+        }
+
+        let version = self.os_version >> 11;
+        let patch_level = self.os_version & 0x7FF;
+
+        Some((
+            format!(
+                "{}.{}.{}",
+                (version >> 14) & 0x7F,
+                (version >> 7) & 0x7F,
+                version & 0x7F
+            ),
+            format!("{}-{:02}", (patch_level >> 4) + 2000, patch_level & 0xF),
+        ))
+    }
+}
+
+/// Header layout for versions 0, 1 and 2; versions 1 and 2 append additional fields
 #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
 #[repr(C, packed)]
-struct AndroidBootImageHeaderBytes {
-    magic: zerocopy::U64<LE>,
+struct AndroidBootImageHeaderV0Bytes {
+    magic: [u8; 8],
     kernel_size: zerocopy::U32<LE>,
     kernel_load_addr: zerocopy::U32<LE>,
     ramdisk_size: zerocopy::U32<LE>,
     ramdisk_load_addr: zerocopy::U32<LE>,
+    second_size: zerocopy::U32<LE>,
+    second_load_addr: zerocopy::U32<LE>,
+    tags_addr: zerocopy::U32<LE>,
+    page_size: zerocopy::U32<LE>,
+    header_version: zerocopy::U32<LE>,
+    os_version: zerocopy::U32<LE>,
+    name: [u8; 16],
+    cmdline: [u8; 512],
+    id: [u8; 32],
+    extra_cmdline: [u8; 1024],
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct AndroidBootImageHeaderV1Bytes {
+    recovery_dtbo_size: zerocopy::U32<LE>,
+    recovery_dtbo_offset: zerocopy::U64<LE>,
+    header_size: zerocopy::U32<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct AndroidBootImageHeaderV2Bytes {
+    dtb_size: zerocopy::U32<LE>,
+    dtb_load_addr: zerocopy::U64<LE>,
+}
+
+/// Header layout for versions 3 and 4; version 4 appends the boot signature size
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct AndroidBootImageHeaderV3Bytes {
+    magic: [u8; 8],
+    kernel_size: zerocopy::U32<LE>,
+    ramdisk_size: zerocopy::U32<LE>,
+    os_version: zerocopy::U32<LE>,
+    header_size: zerocopy::U32<LE>,
+    reserved: [u8; 16],
+    header_version: zerocopy::U32<LE>,
+    cmdline: [u8; 1536],
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct AndroidBootImageHeaderV4Bytes {
+    signature_size: zerocopy::U32<LE>,
 }
 
 /// Parses an Android boot image header
 pub fn parse_android_bootimg_header(
     bootimg_data: &[u8],
 ) -> Result<AndroidBootImageHeader, StructureError> {
-    let (bootimg_header, _) =
-        AndroidBootImageHeaderBytes::ref_from_prefix(bootimg_data).map_err(|_| StructureError)?;
-
-    Ok(AndroidBootImageHeader {
-        kernel_size: bootimg_header.kernel_size.get(),
-        kernel_load_address: bootimg_header.kernel_load_addr.get(),
-        ramdisk_size: bootimg_header.ramdisk_size.get(),
-        ramdisk_load_address: bootimg_header.ramdisk_load_addr.get(),
-    })
+    // The header_version field is at the same offset in all header versions
+    const HEADER_VERSION_OFFSET: usize = 40;
+
+    let header_version = u32::from_le_bytes(
+        bootimg_data
+            .get(HEADER_VERSION_OFFSET..HEADER_VERSION_OFFSET + 4)
+            .ok_or(StructureError)?
+            .try_into()
+            .unwrap(),
+    );
+
+    let bootimg_header = match header_version {
+        0..=2 => parse_android_bootimg_header_v0(bootimg_data, header_version)?,
+        3 | 4 => parse_android_bootimg_header_v3(bootimg_data, header_version)?,
+        // Before header versions existed, this field was unused, or held the device tree size on Qualcomm devices
+        _ => parse_android_bootimg_header_v0(bootimg_data, 0)?,
+    };
+
+    // There must be a kernel
+    if bootimg_header.kernel_size == 0 {
+        return Err(StructureError);
+    }
+
+    Ok(bootimg_header)
+}
+
+/// Parses version 0, 1 and 2 headers, which specify their own page size
+fn parse_android_bootimg_header_v0(
+    bootimg_data: &[u8],
+    header_version: u32,
+) -> Result<AndroidBootImageHeader, StructureError> {
+    const VALID_PAGE_SIZES: [usize; 4] = [2048, 4096, 8192, 16384];
+
+    let (header_v0, rest) =
+        AndroidBootImageHeaderV0Bytes::ref_from_prefix(bootimg_data).map_err(|_| StructureError)?;
+
+    let mut bootimg_header = AndroidBootImageHeader {
+        header_version,
+        page_size: header_v0.page_size.get() as usize,
+        os_version: header_v0.os_version.get(),
+        kernel_size: header_v0.kernel_size.get() as usize,
+        ramdisk_size: header_v0.ramdisk_size.get() as usize,
+        second_size: header_v0.second_size.get() as usize,
+        kernel_load_address: header_v0.kernel_load_addr.get(),
+        ramdisk_load_address: header_v0.ramdisk_load_addr.get(),
+        ..Default::default()
+    };
+
+    if !VALID_PAGE_SIZES.contains(&bootimg_header.page_size) {
+        return Err(StructureError);
+    }
+
+    if header_version >= 1 {
+        let (header_v1, rest) =
+            AndroidBootImageHeaderV1Bytes::ref_from_prefix(rest).map_err(|_| StructureError)?;
+
+        let mut expected_header_size = std::mem::size_of::<AndroidBootImageHeaderV0Bytes>()
+            + std::mem::size_of::<AndroidBootImageHeaderV1Bytes>();
+
+        bootimg_header.recovery_dtbo_size = header_v1.recovery_dtbo_size.get() as usize;
+
+        if header_version >= 2 {
+            let (header_v2, _) =
+                AndroidBootImageHeaderV2Bytes::ref_from_prefix(rest).map_err(|_| StructureError)?;
+
+            expected_header_size += std::mem::size_of::<AndroidBootImageHeaderV2Bytes>();
+            bootimg_header.dtb_size = header_v2.dtb_size.get() as usize;
+        }
+
+        // Versions 1 and later report the size of their own header
+        if header_v1.header_size.get() as usize != expected_header_size {
+            return Err(StructureError);
+        }
+    }
+
+    Ok(bootimg_header)
+}
+
+/// Parses version 3 and 4 headers, which always use a 4096 byte page size
+fn parse_android_bootimg_header_v3(
+    bootimg_data: &[u8],
+    header_version: u32,
+) -> Result<AndroidBootImageHeader, StructureError> {
+    const PAGE_SIZE: usize = 4096;
+
+    let (header_v3, rest) =
+        AndroidBootImageHeaderV3Bytes::ref_from_prefix(bootimg_data).map_err(|_| StructureError)?;
+
+    let mut expected_header_size = std::mem::size_of::<AndroidBootImageHeaderV3Bytes>();
+
+    let mut bootimg_header = AndroidBootImageHeader {
+        header_version,
+        page_size: PAGE_SIZE,
+        os_version: header_v3.os_version.get(),
+        kernel_size: header_v3.kernel_size.get() as usize,
+        ramdisk_size: header_v3.ramdisk_size.get() as usize,
+        ..Default::default()
+    };
+
+    if header_version >= 4 {
+        let (header_v4, _) =
+            AndroidBootImageHeaderV4Bytes::ref_from_prefix(rest).map_err(|_| StructureError)?;
+
+        expected_header_size += std::mem::size_of::<AndroidBootImageHeaderV4Bytes>();
+        bootimg_header.signature_size = header_v4.signature_size.get() as usize;
+    }
+
+    if header_v3.header_size.get() as usize != expected_header_size {
+        return Err(StructureError);
+    }
+
+    Ok(bootimg_header)
+}
+
+/// Defines the internal extractor function for splitting Android boot images into their sections
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::android_bootimg::android_bootimg_extractor;
+///
+/// match android_bootimg_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn android_bootimg_extractor() -> Extractor {
+    Extractor {
+        utility: ExtractorType::Internal(extract_android_bootimg),
+        ..Default::default()
+    }
+}
+
+/// Carves each section of an Android boot image (kernel, ramdisk, second, recovery_dtbo, dtb, boot_signature) to
+/// a file of the same name
+pub fn extract_android_bootimg(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> ExtractionResult {
+    let mut result = ExtractionResult::default();
+
+    let bootimg_data = file_data.get(offset..).unwrap_or_default();
+
+    if let Ok(bootimg_header) = parse_android_bootimg_header(bootimg_data) {
+        let total_size = bootimg_header.total_size();
+
+        // All sections must be present
+        if total_size <= bootimg_data.len() {
+            result.size = Some(total_size);
+            result.success = true;
+
+            if let Some(output_directory) = output_directory {
+                let chroot = Chroot::new(output_directory);
+
+                for section in bootimg_header.sections() {
+                    if !chroot.carve_file(section.name, bootimg_data, section.offset, section.size)
+                    {
+                        result.success = false;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A version 2 boot image with 2048 byte pages, a kernel, a ramdisk and a DTB (see tests/inputs/gen_android_bootimg.sh)
+    static VALID_V2: &[u8] = include_bytes!("../../tests/inputs/android_bootimg.bin");
+
+    /// A version 3 boot image with a kernel and a ramdisk
+    static VALID_V3: &[u8] = include_bytes!("../../tests/inputs/android_bootimg_v3.bin");
+
+    /// Extracts a boot image and returns the contents of each extracted section, sorted by name
+    fn extract_sections(image: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let output_directory = tempfile::tempdir().unwrap();
+
+        let result = extract_android_bootimg(image, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(image.len()));
+
+        let mut sections: Vec<(String, Vec<u8>)> = std::fs::read_dir(output_directory.path())
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                (
+                    path.file_name().unwrap().to_string_lossy().to_string(),
+                    std::fs::read(&path).unwrap(),
+                )
+            })
+            .collect();
+        sections.sort();
+        sections
+    }
+
+    #[test]
+    fn v2_header() {
+        let header = parse_android_bootimg_header(VALID_V2).unwrap();
+
+        assert_eq!(header.header_version, 2);
+        assert_eq!(header.page_size, 2048);
+        assert_eq!(header.kernel_size, 100);
+        assert_eq!(header.kernel_load_address, 0x10008000);
+        assert_eq!(header.ramdisk_size, 50);
+        assert_eq!(header.second_size, 0);
+        assert_eq!(header.recovery_dtbo_size, 0);
+        assert_eq!(header.dtb_size, 30);
+        assert_eq!(
+            header.os_version(),
+            Some(("11.0.0".to_string(), "2021-03".to_string()))
+        );
+        assert_eq!(header.total_size(), VALID_V2.len());
+
+        assert_eq!(
+            extract_sections(VALID_V2),
+            [
+                ("dtb".to_string(), vec![b'D'; 30]),
+                ("kernel".to_string(), vec![b'K'; 100]),
+                ("ramdisk".to_string(), vec![b'R'; 50]),
+            ]
+        );
+
+        // The header size must match the header version
+        let mut bad_header_size = VALID_V2.to_vec();
+        bad_header_size[1644..1648].copy_from_slice(&1648u32.to_le_bytes());
+        assert!(parse_android_bootimg_header(&bad_header_size).is_err());
+    }
+
+    #[test]
+    fn v3_header() {
+        let header = parse_android_bootimg_header(VALID_V3).unwrap();
+
+        assert_eq!(header.header_version, 3);
+        assert_eq!(header.page_size, 4096);
+        assert_eq!(header.kernel_size, 5000);
+        assert_eq!(header.kernel_load_address, 0);
+        assert_eq!(header.ramdisk_size, 200);
+        assert_eq!(header.dtb_size, 0);
+        assert_eq!(header.total_size(), VALID_V3.len());
+
+        assert_eq!(
+            extract_sections(VALID_V3),
+            [
+                ("kernel".to_string(), vec![b'K'; 5000]),
+                ("ramdisk".to_string(), vec![b'R'; 200]),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_image() {
+        // Truncated images are reported, but not extracted
+        let signature = android_bootimg_parser(&VALID_V3[..3 * 4096], 0).unwrap();
+        assert!(signature.extraction_declined);
+        assert_eq!(signature.declared_size, Some(VALID_V3.len()));
+
+        assert!(!extract_android_bootimg(&VALID_V3[..3 * 4096], 0, None).success);
+    }
 }
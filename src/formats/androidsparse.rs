@@ -208,6 +208,8 @@ pub fn parse_android_sparse_chunk_header(
 ///
 /// match android_sparse_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -243,7 +245,9 @@ pub fn extract_android_sparse(
     let mut result = ExtractionResult::default();
 
     // Parse the sparse file header
-    if let Ok(sparse_header) = parse_android_sparse_header(&file_data[offset..]) {
+    if let Ok(sparse_header) =
+        parse_android_sparse_header(file_data.get(offset..).unwrap_or_default())
+    {
         match sparse_header
             .block_count
             .checked_mul(sparse_header.block_size)
@@ -55,6 +55,8 @@ pub fn obfuscated_lzma_parser(
 ///
 /// match obfuscated_lzma_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -84,12 +86,12 @@ pub fn extract_obfuscated_lzma(
     const MIN_DATA_SIZE: usize = 0x100;
     const MAX_DATA_SIZE: usize = 0x1B0000;
 
-    let available_data: usize = file_data.len() - offset;
+    let obfuscated_data = file_data.get(offset..).unwrap_or_default();
 
     // Sanity check data size
-    if available_data <= MAX_DATA_SIZE && available_data > MIN_DATA_SIZE {
+    if obfuscated_data.len() <= MAX_DATA_SIZE && obfuscated_data.len() > MIN_DATA_SIZE {
         // De-obfuscate the LZMA data
-        let deobfuscated_data = arcadyan_deobfuscator(&file_data[offset..]);
+        let deobfuscated_data = arcadyan_deobfuscator(obfuscated_data);
 
         // Do a decompression on the LZMA data (actual LZMA data starts 4 bytes into the deobfuscated data)
         return lzma_decompress(&deobfuscated_data, LZMA_DATA_OFFSET, output_directory);
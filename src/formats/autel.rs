@@ -2,6 +2,7 @@ use crate::common::get_cstring;
 use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
 use crate::signatures::{CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
 use crate::structures::StructureError;
+use std::io::{self, BufWriter, Write};
 use std::path::Path;
 use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
 
@@ -68,10 +69,16 @@ pub fn parse_autel_header(autel_data: &[u8]) -> Result<AutelECCHeader, Structure
 
         // Sanity check the copyright string value
         if copyright_string == EXPECTED_COPYRIGHT_STRING {
-            return Ok(AutelECCHeader {
-                data_size: autel_header.data_size.get() as usize,
-                header_size: autel_header.header_size.get() as usize,
-            });
+            let header_size = autel_header.header_size.get() as usize;
+            let data_size = autel_header.data_size.get() as usize;
+
+            // The encoded data must fit in the available data following the header
+            if data_size <= autel_data.len().saturating_sub(header_size) {
+                return Ok(AutelECCHeader {
+                    data_size,
+                    header_size,
+                });
+            }
         }
     }
 
@@ -90,6 +97,8 @@ const BLOCK_SIZE: usize = 256;
 ///
 /// match autel_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -105,6 +114,7 @@ const BLOCK_SIZE: usize = 256;
 pub fn autel_extractor() -> Extractor {
     Extractor {
         utility: ExtractorType::Internal(autel_deobfuscate),
+        in_memory: Some(autel_deobfuscate_in_memory),
         ..Default::default()
     }
 }
@@ -118,9 +128,36 @@ pub fn autel_deobfuscate(
 ) -> ExtractionResult {
     const OUTPUT_FILE_NAME: &str = "autel.decoded";
 
+    match output_directory {
+        // Dry run, the decoded data is discarded
+        None => autel_decode(file_data, offset, &mut io::sink()),
+        Some(output_directory) => {
+            match Chroot::new(output_directory).create_file_writer(OUTPUT_FILE_NAME) {
+                None => ExtractionResult::default(),
+                Some(file) => autel_decode(file_data, offset, &mut BufWriter::new(file)),
+            }
+        }
+    }
+}
+
+/// Internal extractor for obfuscated Autel firmware that returns the decoded data in ExtractionResult.data
+pub fn autel_deobfuscate_in_memory(file_data: &[u8], offset: usize) -> ExtractionResult {
+    let mut decoded_data: Vec<u8> = Vec::new();
+
+    let mut result = autel_decode(file_data, offset, &mut decoded_data);
+
+    if result.success {
+        result.data = Some(decoded_data);
+    }
+
+    result
+}
+
+/// Decodes obfuscated Autel firmware, writing the decoded data to `output`
+fn autel_decode(file_data: &[u8], offset: usize, output: &mut impl Write) -> ExtractionResult {
     let mut result = ExtractionResult::default();
 
-    let data = &file_data[offset..];
+    let data = file_data.get(offset..).unwrap_or_default();
     let Ok(autel_header) = parse_autel_header(data) else {
         return result;
     };
@@ -134,18 +171,18 @@ pub fn autel_deobfuscate(
     let Some(autel_data) = autel_data.get(..autel_header.data_size) else {
         return result;
     };
+
     // Iterate through each block of the encoded data
     for chunk in autel_data.chunks(BLOCK_SIZE) {
-        let decoded_block = decode_autel_block(chunk);
-
-        // Write to file, if requested
-        if let Some(output_directory) = output_directory {
-            let chroot = Chroot::new(output_directory);
-            if !chroot.append_to_file(OUTPUT_FILE_NAME, &decoded_block) {
-                return result;
-            }
+        if output.write_all(&decode_autel_block(chunk)).is_err() {
+            return result;
         }
     }
+
+    if output.flush().is_err() {
+        return result;
+    }
+
     result.size = Some(autel_header.data_size);
     result.success = true;
     result
@@ -200,3 +237,34 @@ fn decode_autel_block(block_data: &[u8]) -> Vec<u8> {
 
     decoded_block
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An Autel header followed by 300 bytes of encoded data (see tests/inputs/gen_autel.sh)
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/autel.bin");
+
+    /// Returns the fixture with its header's data size set to `data_size`
+    fn with_data_size(data_size: u32) -> Vec<u8> {
+        let mut image = VALID.to_vec();
+        image[8..12].copy_from_slice(&data_size.to_le_bytes());
+        image
+    }
+
+    #[test]
+    fn data_size_must_fit_in_available_data() {
+        let header = parse_autel_header(VALID).unwrap();
+        assert_eq!(header.header_size, 0x20);
+        assert_eq!(header.data_size, 300);
+        assert_eq!(autel_parser(VALID, 0).unwrap().size, VALID.len());
+
+        // One byte more than is available
+        assert!(parse_autel_header(&with_data_size(301)).is_err());
+
+        // A huge data size
+        let image = with_data_size(0xFFFFFFFF);
+        assert!(parse_autel_header(&image).is_err());
+        assert!(autel_parser(&image, 0).is_err());
+    }
+}
@@ -1,7 +1,7 @@
-use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
+use crate::extractors::Extractor;
+use crate::extractors::carve::carve_extractor;
 use crate::signatures::{CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
 use crate::structures::StructureError;
-use std::path::Path;
 use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
 
 /// Human readable description
@@ -25,20 +25,22 @@ pub fn bmp_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
         ..Default::default()
     };
 
-    // Extraction dry-run to validate the image
-    let dry_run = extract_bmp_image(file_data, offset, None);
-
-    // If it was successful, inform the user
-    if dry_run.success {
-        // Retrieve total file size and report it to the user
-        if let Some(total_size) = dry_run.size {
-            result.description = format!("BMP image, total size: {total_size}");
-            result.size = total_size;
-            return Ok(result);
-        }
+    let bmp_data = file_data.get(offset..).ok_or(SignatureError)?;
+
+    // The headers report the total file size if they are valid, even if the image data is truncated
+    let total_size = get_bmp_size(bmp_data).map_err(|_| SignatureError)?;
+    result.description = format!("BMP image, total size: {total_size}");
+
+    // If all the image data is there, inform the user
+    if total_size <= bmp_data.len() {
+        result.size = total_size;
+        return Ok(result);
     }
 
-    Err(SignatureError)
+    // Otherwise the image is valid, but cut short
+    result.extraction_declined = true;
+    result.set_truncated(total_size, bmp_data.len());
+    Ok(result)
 }
 
 #[derive(Debug, Default, Clone)]
@@ -58,6 +60,7 @@ struct RawHeader {
     bf_off_bits: zerocopy::U32<LE>,
 }
 
+/// Parses a BMP file header; the declared file size may be larger than bmp_data if the image is truncated
 pub fn parse_bmp_file_header(bmp_data: &[u8]) -> Result<BMPFileHeader, StructureError> {
     let (raw_header, _rest) = RawHeader::ref_from_prefix(bmp_data).map_err(|_| StructureError)?;
     let bmp_data_size = bmp_data.len();
@@ -65,11 +68,6 @@ pub fn parse_bmp_file_header(bmp_data: &[u8]) -> Result<BMPFileHeader, Structure
     let bf_size = raw_header.bf_size.get() as usize;
     let bf_off_bits = raw_header.bf_off_bits.get() as usize;
 
-    // The BMP file size cannot be bigger than bmp_data
-    if bmp_data_size < bf_size {
-        return Err(StructureError);
-    }
-
     // The file size cannot be 0
     if bf_size == 0 {
         return Err(StructureError);
@@ -85,6 +83,11 @@ pub fn parse_bmp_file_header(bmp_data: &[u8]) -> Result<BMPFileHeader, Structure
         return Err(StructureError);
     }
 
+    // The image data must start within the declared file size
+    if bf_off_bits >= bf_size {
+        return Err(StructureError);
+    }
+
     // If everything is Ok so far, return a BMPFileHeader
     Ok(BMPFileHeader {
         size: bf_size,
@@ -102,16 +105,43 @@ pub fn get_dib_header_size(bmp_data: &[u8]) -> Result<usize, StructureError> {
         124,
     ];
 
-    let header_size = u32::from_le_bytes(bmp_data[..4].try_into().unwrap());
+    let header_size =
+        u32::from_le_bytes(bmp_data.get(..4).ok_or(StructureError)?.try_into().unwrap());
 
     if !valid_header_sizes.contains(&header_size) {
         return Err(StructureError);
     }
 
+    // The header can't extend past the end of the data
+    if header_size as usize > bmp_data.len() {
+        return Err(StructureError);
+    }
+
     Ok(header_size as usize)
 }
 
-/// Defines the internal extractor function for carving out GIF images
+/// Validates a BMP's file header and the DIB header that follows it, and returns the total size of the BMP file.
+/// The size is as declared in the file header, and may be larger than bmp_data if the image is truncated.
+pub fn get_bmp_size(bmp_data: &[u8]) -> Result<usize, StructureError> {
+    // https://learn.microsoft.com/en-us/windows/win32/api/wingdi/ns-wingdi-bitmapfileheader
+    // The size of the BMP file header
+    const BMP_FILE_HEADER_SIZE: usize = 14;
+
+    let bmp_file_header = parse_bmp_file_header(bmp_data)?;
+
+    // Retrieve the size of the header following the BMP file header
+    let dib_header_data = bmp_data.get(BMP_FILE_HEADER_SIZE..).ok_or(StructureError)?;
+    let dib_header_size = get_dib_header_size(dib_header_data)?;
+
+    // The offset that points to the image data cannot point into the second header
+    if bmp_file_header.bitmap_bits_offset < BMP_FILE_HEADER_SIZE + dib_header_size {
+        return Err(StructureError);
+    }
+
+    Ok(bmp_file_header.size)
+}
+
+/// Defines the extractor for carving out BMP images
 ///
 /// ```
 /// use std::io::ErrorKind;
@@ -121,6 +151,8 @@ pub fn get_dib_header_size(bmp_data: &[u8]) -> Result<usize, StructureError> {
 ///
 /// match bmp_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(file_name) => println!("Carve extractor OK: {}", file_name),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -136,44 +168,57 @@ pub fn get_dib_header_size(bmp_data: &[u8]) -> Result<usize, StructureError> {
 pub fn bmp_extractor() -> Extractor {
     Extractor {
         do_not_recurse: true,
-        utility: ExtractorType::Internal(extract_bmp_image),
-        ..Default::default()
+        ..carve_extractor("image.bmp")
     }
 }
 
-pub fn extract_bmp_image(
-    file_data: &[u8],
-    offset: usize,
-    output_directory: Option<&Path>,
-) -> ExtractionResult {
-    const OUTFILE_NAME: &str = "image.bmp";
-
-    let mut result = ExtractionResult::default();
-
-    // Parse the bmp_file_header
-    if let Ok(bmp_file_header) = parse_bmp_file_header(&file_data[offset..]) {
-        // https://learn.microsoft.com/en-us/windows/win32/api/wingdi/ns-wingdi-bitmapfileheader
-        // The size of the BMP file header
-        const BMP_FILE_HEADER_SIZE: usize = 14;
-
-        // Retrieve the size of the header following the BMP file header
-        if let Ok(bmp_header_size) =
-            get_dib_header_size(&file_data[(offset + BMP_FILE_HEADER_SIZE)..])
-        {
-            // The offset that points to the image data cannot point into the second header
-            if bmp_file_header.bitmap_bits_offset >= (BMP_FILE_HEADER_SIZE + bmp_header_size) {
-                // If it was parsed successfully, get the file size
-                result.size = Some(bmp_file_header.size);
-                result.success = true;
-
-                if let Some(output_directory) = output_directory {
-                    let chroot = Chroot::new(output_directory);
-                    result.success =
-                        chroot.carve_file(OUTFILE_NAME, file_data, offset, bmp_file_header.size);
-                }
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Firmware image with two embedded BMP images (see tests/bmp.rs)
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/bmp.bin");
+    /// Offset of the first BMP image, a 300x300 24-bit image with a BITMAPINFOHEADER
+    const BMP_OFFSET: usize = 0xB7F94;
+    const BMP_FILE_HEADER_SIZE: usize = 14;
+
+    #[test]
+    fn dib_header_size_bounds() {
+        let dib_header = &VALID[BMP_OFFSET + BMP_FILE_HEADER_SIZE..];
+
+        // Too short to hold the header size field
+        assert!(get_dib_header_size(&dib_header[..2]).is_err());
+
+        // A BITMAPINFOHEADER that is cut short
+        assert_eq!(get_dib_header_size(&dib_header[..40]).unwrap(), 40);
+        assert!(get_dib_header_size(&dib_header[..20]).is_err());
     }
 
-    result
+    #[test]
+    fn carved_image_round_trip() {
+        let signature = bmp_parser(VALID, BMP_OFFSET).unwrap();
+        assert_eq!(signature.size, 270056);
+        assert!(!signature.extraction_declined);
+
+        let bmp_image = &VALID[BMP_OFFSET..BMP_OFFSET + signature.size];
+
+        let work_directory = tempfile::tempdir().unwrap();
+        let file_path = work_directory.path().join("bmp.bin");
+        std::fs::write(&file_path, VALID).unwrap();
+
+        let result =
+            crate::extractors::execute(VALID, &file_path, &signature, &Some(bmp_extractor()));
+        assert!(result.success);
+        assert!(result.do_not_recurse);
+        assert_eq!(result.extracted_files.len(), 1);
+        assert_eq!(
+            std::fs::read(&result.extracted_files[0]).unwrap(),
+            bmp_image
+        );
+
+        // The image data offset must fall within the declared file size
+        let mut bad_offset = bmp_image.to_vec();
+        bad_offset[10..14].copy_from_slice(&(bmp_image.len() as u32).to_le_bytes());
+        assert!(bmp_parser(&bad_offset, 0).is_err());
+    }
 }
@@ -1,5 +1,6 @@
 use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
 use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
+use crate::structures::StructureError;
 use bzip2::read::BzDecoder;
 use std::io::{Read, copy};
 use std::path::Path;
@@ -38,6 +39,10 @@ pub fn bzip2_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult,
         && let Some(bzip2_size) = dry_run.size
     {
         result.size = bzip2_size;
+        result.trailer_offset = file_data
+            .get(offset..offset + bzip2_size)
+            .and_then(bzip2_eos_offset)
+            .map(|eos_offset| offset + eos_offset);
         result.description = format!("{}, total size: {} bytes", result.description, result.size);
         return Ok(result);
     }
@@ -45,6 +50,95 @@ pub fn bzip2_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult,
     Err(SignatureError)
 }
 
+/// Stores info about a bzip2 stream header
+#[derive(Debug, Default, Clone)]
+pub struct Bzip2Header {
+    /// Size of the uncompressed data blocks, in bytes
+    pub block_size: usize,
+    /// True if the stream contains no data blocks, just an end-of-stream marker
+    pub empty: bool,
+}
+
+/// Parses a bzip2 stream header: the magic bytes, the block size digit, and the magic number that starts the first
+/// block (or, for empty streams, the end-of-stream marker).
+///
+/// ```
+/// use binwalk_ng::formats::bzip2::parse_bzip2_header;
+///
+/// let header = parse_bzip2_header(b"BZh91AY&SY").unwrap();
+/// assert_eq!(header.block_size, 900_000);
+/// assert!(!header.empty);
+///
+/// assert!(parse_bzip2_header(b"BZh01AY&SY").is_err());
+/// ```
+pub fn parse_bzip2_header(bzip2_data: &[u8]) -> Result<Bzip2Header, StructureError> {
+    const MAGIC: &[u8] = b"BZh";
+    const BLOCK_SIZE_UNIT: usize = 100_000;
+    const BLOCK_MAGIC: &[u8] = b"\x31\x41\x59\x26\x53\x59";
+    const EOS_MAGIC: &[u8] = b"\x17\x72\x45\x38\x50\x90";
+    const BLOCK_MAGIC_START: usize = 4;
+    const BLOCK_MAGIC_END: usize = 10;
+
+    if bzip2_data.get(..MAGIC.len()) != Some(MAGIC) {
+        return Err(StructureError);
+    }
+
+    // Block size is an ASCII digit, in units of 100k
+    let block_size_digit = *bzip2_data.get(MAGIC.len()).ok_or(StructureError)?;
+    if !(b'1'..=b'9').contains(&block_size_digit) {
+        return Err(StructureError);
+    }
+
+    let empty = match bzip2_data.get(BLOCK_MAGIC_START..BLOCK_MAGIC_END) {
+        Some(BLOCK_MAGIC) => false,
+        Some(EOS_MAGIC) => true,
+        _ => return Err(StructureError),
+    };
+
+    Ok(Bzip2Header {
+        block_size: (block_size_digit - b'0') as usize * BLOCK_SIZE_UNIT,
+        empty,
+    })
+}
+
+/// Locates the end-of-stream marker at the end of a bzip2 stream.
+/// Returns the offset, relative to the start of the stream, of the byte containing the first bit of the marker.
+///
+/// The marker is a 48-bit magic number followed by a 32-bit combined CRC, and is not byte-aligned;
+/// only the last byte of the stream is padded, so the marker must start 80 to 87 bits before the end of the stream.
+///
+/// ```
+/// use binwalk_ng::formats::bzip2::bzip2_eos_offset;
+///
+/// // Empty bzip2 stream: header, end-of-stream marker, CRC
+/// let bzip2_data = b"BZh9\x17\x72\x45\x38\x50\x90\x00\x00\x00\x00";
+///
+/// assert_eq!(bzip2_eos_offset(bzip2_data), Some(4));
+/// assert_eq!(bzip2_eos_offset(&bzip2_data[..8]), None);
+/// ```
+pub fn bzip2_eos_offset(bzip2_data: &[u8]) -> Option<usize> {
+    const EOS_MAGIC: u64 = 0x1772_4538_5090;
+    const EOS_MAGIC_BITS: usize = 48;
+    const CRC_BITS: usize = 32;
+    const MAX_PADDING_BITS: usize = 7;
+
+    let total_bits = bzip2_data.len() * 8;
+
+    // Returns the 48 bits starting at the specified bit offset
+    let bits_at = |bit_offset: usize| -> u64 {
+        (0..EOS_MAGIC_BITS).fold(0, |value, i| {
+            let bit = bit_offset + i;
+            let bit_value = (bzip2_data[bit / 8] >> (7 - (bit % 8))) & 1;
+            (value << 1) | u64::from(bit_value)
+        })
+    };
+
+    (0..=MAX_PADDING_BITS)
+        .filter_map(|padding| total_bits.checked_sub(EOS_MAGIC_BITS + CRC_BITS + padding))
+        .find(|&bit_offset| bits_at(bit_offset) == EOS_MAGIC)
+        .map(|bit_offset| bit_offset / 8)
+}
+
 /// Defines the internal extractor function for decompressing BZIP2 files
 ///
 /// ```
@@ -55,6 +149,8 @@ pub fn bzip2_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult,
 ///
 /// match bzip2_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -75,6 +171,9 @@ pub fn bzip2_extractor() -> Extractor {
 }
 
 /// Internal extractor for decompressing BZIP2 data
+///
+/// Only a single bzip2 stream is decompressed; the reported size is that of the compressed stream, up to and
+/// including the end-of-stream marker, so any trailing data is not consumed.
 pub fn bzip2_decompressor(
     file_data: &[u8],
     offset: usize,
@@ -86,35 +185,89 @@ pub fn bzip2_decompressor(
     let mut result = ExtractionResult::default();
 
     // Slice the data starting from the provided offset
-    let bzip2_data = &file_data[offset..];
+    let Some(bzip2_data) = file_data.get(offset..) else {
+        return result;
+    };
+
+    // Don't bother decompressing data without a valid header
+    if parse_bzip2_header(bzip2_data).is_err() {
+        return result;
+    }
 
     let mut decoder = BzDecoder::new(bzip2_data);
 
-    if let Some(output_directory) = output_directory {
-        // If extraction is requested, we write directly to the chroot file
+    let decompressed_size = if let Some(output_directory) = output_directory {
+        // If extraction is requested, decompress to memory, then write the chroot file
         let chroot = Chroot::new(output_directory);
-
-        // We need a writer target. Assuming append_to_file doesn't expose a raw writer,
-        // we can decompress into a local vector or file, then append it.
         let mut decompressed_output = Vec::new();
 
-        if decoder.read_to_end(&mut decompressed_output).is_ok()
-            && chroot.create_file(OUTPUT_FILE_NAME, &decompressed_output)
+        if decoder.read_to_end(&mut decompressed_output).is_err()
+            || !chroot.create_file(OUTPUT_FILE_NAME, &decompressed_output)
         {
-            result.success = true;
-            // total_in() tells us exactly how many compressed bytes were read from file_data
-            result.size = Some(decoder.total_in() as usize);
+            return result;
         }
+
+        decompressed_output.len()
     } else {
         // If no output directory is provided, we just drain the decoder into a sink (null device)
         // to validate the stream and calculate its total compressed size.
-        let mut sink = std::io::sink();
-
-        if copy(&mut decoder, &mut sink).is_ok() {
-            result.success = true;
-            result.size = Some(decoder.total_in() as usize);
+        match copy(&mut decoder, &mut std::io::sink()) {
+            Ok(decompressed_size) => decompressed_size as usize,
+            Err(_) => return result,
         }
+    };
+
+    // total_in() tells us exactly how many compressed bytes were read from file_data
+    let compressed_size = decoder.total_in() as usize;
+
+    // The decoder stops after the end-of-stream marker, which must be at the end of the consumed data
+    if bzip2_data
+        .get(..compressed_size)
+        .and_then(bzip2_eos_offset)
+        .is_some()
+    {
+        result.success = true;
+        result.size = Some(compressed_size);
+        result.set_compression_ratio(compressed_size, decompressed_size);
     }
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A bzip2 stream followed by trailing junk that starts out looking like another bzip2 stream (see
+    /// tests/inputs/gen_bzip2_trailing_junk.sh).
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/bzip2_trailing_junk.bin");
+
+    /// Size of the trailing junk at the end of VALID
+    const JUNK_SIZE: usize = 18;
+
+    #[test]
+    fn trailing_junk() {
+        let stream_size = VALID.len() - JUNK_SIZE;
+
+        let header = parse_bzip2_header(VALID).unwrap();
+        assert_eq!(header.block_size, 100_000);
+        assert!(!header.empty);
+
+        let signature = bzip2_parser(VALID, 0).unwrap();
+        assert_eq!(signature.size, stream_size);
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = bzip2_decompressor(VALID, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(stream_size));
+
+        let decompressed = std::fs::read(output_directory.path().join("decompressed.bin")).unwrap();
+        assert_eq!(
+            result.compression_ratio,
+            Some(decompressed.len() as f64 / stream_size as f64)
+        );
+
+        // A stream cut short before its end-of-stream marker is not valid
+        assert!(!bzip2_decompressor(&VALID[..stream_size - 20], 0, None).success);
+    }
+}
@@ -14,7 +14,7 @@ pub fn cab_magic() -> Vec<Vec<u8>> {
 /// Parses and cabinet file signature
 pub fn cab_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
     // Parse the CAB header
-    if let Ok(cab_header) = parse_cab_header(&file_data[offset..]) {
+    if let Ok(cab_header) = parse_cab_header(file_data.get(offset..).unwrap_or_default()) {
         let available_data = file_data.len() - offset;
 
         // Sanity check the reported CAB file size
@@ -22,8 +22,9 @@ pub fn cab_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
             // Return success
             return Ok(SignatureResult {
                 description: format!(
-                    "{}, file count: {}, folder count: {}, header size: {}, total size: {} bytes",
+                    "{}, compression: {}, file count: {}, folder count: {}, header size: {}, total size: {} bytes",
                     DESCRIPTION,
+                    cab_header.compression_description(),
                     cab_header.file_count,
                     cab_header.folder_count,
                     cab_header.header_size,
@@ -40,6 +41,36 @@ pub fn cab_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
     Err(SignatureError)
 }
 
+/// CAB folder compression types
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum CabCompression {
+    #[default]
+    None,
+    Mszip,
+    Quantum,
+    Lzx,
+}
+
+impl std::fmt::Display for CabCompression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CabCompression::None => write!(f, "none"),
+            CabCompression::Mszip => write!(f, "MSZIP"),
+            CabCompression::Quantum => write!(f, "Quantum"),
+            CabCompression::Lzx => write!(f, "LZX"),
+        }
+    }
+}
+
+/// Stores CAB folder info
+#[derive(Debug, Default, Clone)]
+pub struct CabinetFolder {
+    /// Offset of the folder's first data block, relative to the start of the cabinet
+    pub data_offset: usize,
+    pub data_block_count: usize,
+    pub compression: CabCompression,
+}
+
 /// Stores CAB header info
 #[derive(Debug, Default, Clone)]
 pub struct CabinetHeader {
@@ -47,6 +78,26 @@ pub struct CabinetHeader {
     pub header_size: usize,
     pub file_count: usize,
     pub folder_count: usize,
+    /// Offset of the first file entry, relative to the start of the cabinet
+    pub first_file_offset: usize,
+    pub folders: Vec<CabinetFolder>,
+}
+
+impl CabinetHeader {
+    /// Describes the compression type(s) used by the cabinet's folders
+    pub fn compression_description(&self) -> String {
+        let mut compression_types: Vec<String> = Vec::new();
+
+        for folder in &self.folders {
+            let compression = folder.compression.to_string();
+
+            if !compression_types.contains(&compression) {
+                compression_types.push(compression);
+            }
+        }
+
+        compression_types.join("/")
+    }
 }
 
 #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
@@ -65,6 +116,12 @@ struct CabHeaderBytes {
     flags: zerocopy::U16<LE>,
     id: zerocopy::U16<LE>,
     set_number: zerocopy::U16<LE>,
+}
+
+// Present only if the reserve flag is set in the CAB header
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct CabReserveBytes {
     extra_header_size: zerocopy::U16<LE>,
     cb_cf_folder: u8,
     cb_cf_data: u8,
@@ -80,66 +137,151 @@ struct CabExtraBytes {
     unknown3: zerocopy::U32<LE>,
 }
 
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct CabFolderBytes {
+    data_offset: zerocopy::U32<LE>,
+    data_block_count: zerocopy::U16<LE>,
+    compression_type: zerocopy::U16<LE>,
+}
+
 /// Parse a CAB file header
 pub fn parse_cab_header(header_data: &[u8]) -> Result<CabinetHeader, StructureError> {
     // CAB files must be version 1.3
     const MAJOR_VERSION: u8 = 1;
     const MINOR_VERSION: u8 = 3;
 
-    const CAB_STRUCT_SIZE: usize = 40;
+    const CAB_STRUCT_SIZE: usize = 36;
+    const CAB_RESERVE_STRUCT_SIZE: usize = 4;
     const CAB_EXTRA_STRUCT_SIZE: u16 = 20;
+    const CAB_FOLDER_STRUCT_SIZE: usize = 8;
+
+    const FLAG_PREV_CABINET: u16 = 1;
+    const FLAG_NEXT_CABINET: u16 = 2;
     const FLAG_EXTRA_DATA_PRESENT: u16 = 4;
 
+    // Parse the CAB header
+    let (cab_header, _) =
+        CabHeaderBytes::ref_from_prefix(header_data).map_err(|_| StructureError)?;
+
+    // All reserved fields must be 0, and version must be 1.3
+    if cab_header.reserved1 != 0
+        || cab_header.reserved2 != 0
+        || cab_header.reserved3 != 0
+        || cab_header.major_version != MAJOR_VERSION
+        || cab_header.minor_version != MINOR_VERSION
+    {
+        return Err(StructureError);
+    }
+
     let mut header_info = CabinetHeader {
         header_size: CAB_STRUCT_SIZE,
+        total_size: cab_header.size.get() as usize,
+        file_count: cab_header.file_count.get() as usize,
+        folder_count: cab_header.folder_count.get() as usize,
+        first_file_offset: cab_header.first_file_offset.get() as usize,
         ..Default::default()
     };
 
-    // Parse the CAB header
+    let cabinet_size = cab_header.size.get() as usize;
+    let flags = cab_header.flags.get();
+    let mut folder_reserve_size: usize = 0;
 
-    let (cab_header, _) =
-        CabHeaderBytes::ref_from_prefix(header_data).map_err(|_| StructureError)?;
+    // If the extra data flag was set, the header, folder and data entries have reserved space
+    if (flags & FLAG_EXTRA_DATA_PRESENT) != 0 {
+        let (cab_reserve, _) = CabReserveBytes::ref_from_prefix(
+            header_data.get(CAB_STRUCT_SIZE..).unwrap_or_default(),
+        )
+        .map_err(|_| StructureError)?;
 
-    // All reserved fields must be 0
-    if cab_header.reserved1 == 0 && cab_header.reserved2 == 0 && cab_header.reserved3 == 0 {
-        // Version must be 1.3
-        if cab_header.major_version == MAJOR_VERSION && cab_header.minor_version == MINOR_VERSION {
-            // Update the CabinetHeader fields
-            header_info.total_size = cab_header.size.get() as usize;
-            header_info.file_count = cab_header.file_count.get() as usize;
-            header_info.folder_count = cab_header.folder_count.get() as usize;
-
-            // If the extra data flag was set, we need to parse the extra data header to get the size of the extra data
-            if (cab_header.flags.get() & FLAG_EXTRA_DATA_PRESENT) != 0
-                && cab_header.extra_header_size.get() == CAB_EXTRA_STRUCT_SIZE
-            {
-                // Calclate the start and end of the extra header
-                let extra_header_start: usize = CAB_STRUCT_SIZE;
-                let extra_header_end: usize = extra_header_start + CAB_EXTRA_STRUCT_SIZE as usize;
-
-                // Get the extra header raw data
-                if let Some(extra_header_data) =
-                    header_data.get(extra_header_start..extra_header_end)
-                {
-                    // Parse the extra header
-
-                    let (extra_header, _) = CabExtraBytes::ref_from_prefix(extra_header_data)
-                        .map_err(|_| StructureError)?;
-
-                    // The extra data is expected to come immediately after the data specified in the main CAB header
-                    if extra_header.data_offset == cab_header.size {
-                        // Update the CAB file size to include the extra data
-                        header_info.total_size += extra_header.data_size.get() as usize;
-                        return Ok(header_info);
-                    }
-                }
-            } else {
-                return Ok(header_info);
+        let extra_header_size = cab_reserve.extra_header_size.get();
+        folder_reserve_size = cab_reserve.cb_cf_folder as usize;
+
+        // Calclate the start and end of the extra header
+        let extra_header_start: usize = CAB_STRUCT_SIZE + CAB_RESERVE_STRUCT_SIZE;
+        let extra_header_end: usize = extra_header_start + extra_header_size as usize;
+
+        header_info.header_size = extra_header_end;
+
+        // Signed cabinets have a 20 byte extra header, which describes the size of the signature data
+        if extra_header_size == CAB_EXTRA_STRUCT_SIZE {
+            // Get the extra header raw data
+            let extra_header_data = header_data
+                .get(extra_header_start..extra_header_end)
+                .ok_or(StructureError)?;
+
+            // Parse the extra header
+            let (extra_header, _) =
+                CabExtraBytes::ref_from_prefix(extra_header_data).map_err(|_| StructureError)?;
+
+            // The extra data is expected to come immediately after the data specified in the main CAB header
+            if extra_header.data_offset != cab_header.size {
+                return Err(StructureError);
             }
+
+            // Update the CAB file size to include the extra data
+            header_info.total_size += extra_header.data_size.get() as usize;
         }
     }
 
-    Err(StructureError)
+    // Previous and next cabinet names and disk names are NULL-terminated strings that precede the folder entries
+    let mut folder_offset = header_info.header_size;
+    let cabinet_link_count = [FLAG_PREV_CABINET, FLAG_NEXT_CABINET]
+        .iter()
+        .filter(|&&flag| (flags & flag) != 0)
+        .count();
+
+    for _ in 0..(cabinet_link_count * 2) {
+        let string_size = header_data
+            .get(folder_offset..)
+            .and_then(|string_data| string_data.iter().position(|&b| b == 0))
+            .ok_or(StructureError)?;
+
+        folder_offset += string_size + 1;
+    }
+
+    // Parse the folder entries
+    for _ in 0..header_info.folder_count {
+        let (cab_folder, _) =
+            CabFolderBytes::ref_from_prefix(header_data.get(folder_offset..).unwrap_or_default())
+                .map_err(|_| StructureError)?;
+
+        header_info.folders.push(CabinetFolder {
+            data_offset: cab_folder.data_offset.get() as usize,
+            data_block_count: cab_folder.data_block_count.get() as usize,
+            compression: cab_compression_type(cab_folder.compression_type.get())?,
+        });
+
+        folder_offset += CAB_FOLDER_STRUCT_SIZE + folder_reserve_size;
+    }
+
+    // There must be at least one file and one folder; file entries follow the folder entries, data follows the file entries
+    if header_info.folders.is_empty()
+        || header_info.file_count == 0
+        || header_info.first_file_offset < folder_offset
+        || header_info.first_file_offset >= cabinet_size
+        || header_info.folders.iter().any(|folder| {
+            folder.data_offset <= header_info.first_file_offset
+                || folder.data_offset >= cabinet_size
+        })
+    {
+        return Err(StructureError);
+    }
+
+    Ok(header_info)
+}
+
+/// Returns the compression type specified in the low 4 bits of a folder's compression type field
+fn cab_compression_type(compression_type: u16) -> Result<CabCompression, StructureError> {
+    const COMPRESSION_MASK: u16 = 0x000F;
+
+    match compression_type & COMPRESSION_MASK {
+        0 => Ok(CabCompression::None),
+        1 => Ok(CabCompression::Mszip),
+        2 => Ok(CabCompression::Quantum),
+        3 => Ok(CabCompression::Lzx),
+        _ => Err(StructureError),
+    }
 }
 
 /// Describes how to run the cabextract utility to extract MS CAB archives
@@ -152,6 +294,8 @@ pub fn parse_cab_header(header_data: &[u8]) -> Result<CabinetHeader, StructureEr
 ///
 /// match cab_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -173,3 +317,59 @@ pub fn cab_extractor() -> extractors::Extractor {
         ..Default::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single-file cabinet with one uncompressed folder (see tests/inputs/gen_cab.sh).
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/cab.bin");
+
+    /// Offset of the folder's compression type field in VALID
+    const COMPRESSION_TYPE_OFFSET: usize = 42;
+
+    /// Returns a copy of VALID, with the folder's compression type changed
+    fn with_compression_type(compression_type: u16) -> Vec<u8> {
+        let mut cab = VALID.to_vec();
+        cab[COMPRESSION_TYPE_OFFSET..COMPRESSION_TYPE_OFFSET + 2]
+            .copy_from_slice(&compression_type.to_le_bytes());
+        cab
+    }
+
+    #[test]
+    fn valid_header() {
+        let header = parse_cab_header(VALID).unwrap();
+        assert_eq!(header.total_size, VALID.len());
+        assert_eq!(header.header_size, 36);
+        assert_eq!(header.file_count, 1);
+        assert_eq!(header.folder_count, 1);
+        assert_eq!(header.first_file_offset, 44);
+        assert_eq!(header.folders[0].data_offset, 66);
+        assert_eq!(header.folders[0].compression, CabCompression::None);
+
+        let signature = cab_parser(VALID, 0).unwrap();
+        assert_eq!(signature.size, VALID.len());
+        assert!(signature.description.contains("compression: none"));
+    }
+
+    #[test]
+    fn compression_types() {
+        // The upper bits of the compression type field are compression parameters, e.g. the LZX window size
+        let lzx = parse_cab_header(&with_compression_type(0x1503)).unwrap();
+        assert_eq!(lzx.folders[0].compression, CabCompression::Lzx);
+
+        let mszip = cab_parser(&with_compression_type(1), 0).unwrap();
+        assert!(mszip.description.contains("compression: MSZIP"));
+
+        assert!(parse_cab_header(&with_compression_type(4)).is_err());
+    }
+
+    #[test]
+    fn truncated_cabinet() {
+        let cab = &VALID[..VALID.len() - 1];
+
+        // The header is valid, but the cabinet size exceeds the available data
+        assert!(parse_cab_header(cab).is_ok());
+        assert!(cab_parser(cab, 0).is_err());
+    }
+}
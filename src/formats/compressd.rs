@@ -1,29 +1,492 @@
+use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
 use crate::signatures::{CONFIDENCE_LOW, CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
+use crate::structures::StructureError;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 
 /// Human readable description
 pub const DESCRIPTION: &str = "compress'd data";
 
-/// Compress'd files always start with these bytes
+/// Size of the compress'd header: two magic bytes, plus the flags byte
+const COMPRESSD_HEADER_SIZE: usize = 3;
+
+/// Initial LZW code size, in bits
+const INIT_BITS: usize = 9;
+
+/// Clear code, which resets the code table when block mode is enabled
+const CLEAR_CODE: usize = 256;
+
+/// Compress'd files always start with these bytes: the magic bytes, and a flags byte specifying
+/// block mode and a maximum code size of 9 to 16 bits
 pub fn compressd_magic() -> Vec<Vec<u8>> {
-    vec![b"\x1F\x9D\x90".to_vec()]
+    const BLOCK_MODE_FLAG: u8 = 0x80;
+
+    let mut magics: Vec<Vec<u8>> = Vec::new();
+
+    for max_bits in INIT_BITS as u8..=16 {
+        magics.push(vec![0x1F, 0x9D, BLOCK_MODE_FLAG | max_bits]);
+        magics.push(vec![0x1F, 0x9D, max_bits]);
+    }
+
+    magics
 }
 
-/// "Validate" the compress'd header
+/// Validate the compress'd header, and the LZW data that follows it
 pub fn compressd_parser(
-    _file_data: &[u8],
+    file_data: &[u8],
     offset: usize,
 ) -> Result<SignatureResult, SignatureError> {
-    // Successful return value; confidence is medium since this only matches magic bytes at the beginning of a file
+    // Number of LZW codes to decode when validating the data; enough to weed out false positives without decompressing
+    // the entire stream
+    const MAX_VALIDATION_CODES: usize = 4096;
+
+    let compressd_data = file_data.get(offset..).unwrap_or_default();
+    let header = parse_compressd_header(compressd_data).map_err(|_| SignatureError)?;
+
+    let lzw = lzw_decompress(
+        &compressd_data[COMPRESSD_HEADER_SIZE..],
+        &header,
+        None,
+        Some(MAX_VALIDATION_CODES),
+    )
+    .ok_or(SignatureError)?;
+
+    if lzw.decompressed_size == 0 {
+        return Err(SignatureError);
+    }
+
+    // Successful return value; there is no checksum or end marker to validate, so confidence is only raised
+    // to medium if the data starts at the beginning of the file
     let mut result = SignatureResult {
         offset,
-        description: DESCRIPTION.to_string(),
         confidence: CONFIDENCE_LOW,
         ..Default::default()
     };
 
+    result.description = format!(
+        "{}, max code size: {} bits, block mode: {}",
+        DESCRIPTION, header.max_bits, header.block_mode
+    );
+
+    // The compressed data runs until EOF, or until an invalid code is encountered; if that wasn't found within the
+    // validated codes, the size is unknown
+    if lzw.complete {
+        result.size = COMPRESSD_HEADER_SIZE + lzw.compressed_size;
+        result.description = format!("{}, total size: {} bytes", result.description, result.size);
+    }
+
     if offset == 0 {
         result.confidence = CONFIDENCE_MEDIUM;
     }
 
     Ok(result)
 }
+
+/// Stores info about a compress'd header
+#[derive(Debug, Default, Clone)]
+pub struct CompressdHeader {
+    /// Maximum LZW code size, in bits
+    pub max_bits: usize,
+    /// If true, a clear code may be used to reset the code table
+    pub block_mode: bool,
+}
+
+/// Parses a compress'd header
+///
+/// ```
+/// use binwalk_ng::formats::compressd::parse_compressd_header;
+///
+/// let header = parse_compressd_header(b"\x1F\x9D\x90").unwrap();
+/// assert_eq!(header.max_bits, 16);
+/// assert!(header.block_mode);
+///
+/// assert!(parse_compressd_header(b"\x1F\x9D\x91").is_err());
+/// assert!(parse_compressd_header(b"\x1F\x9D\xC8").is_err());
+/// ```
+pub fn parse_compressd_header(compressd_data: &[u8]) -> Result<CompressdHeader, StructureError> {
+    const MAGIC: &[u8; 2] = b"\x1F\x9D";
+    const MAX_BITS: usize = 16;
+    const FLAG_BLOCK_MODE: u8 = 0b1000_0000;
+    const FLAGS_RESERVED_MASK: u8 = 0b0110_0000;
+    const MAX_BITS_MASK: u8 = 0b0001_1111;
+
+    let header = compressd_data
+        .get(..COMPRESSD_HEADER_SIZE)
+        .ok_or(StructureError)?;
+
+    let flags = header[2];
+    let max_bits = (flags & MAX_BITS_MASK) as usize;
+
+    if header[..2] != *MAGIC
+        || (flags & FLAGS_RESERVED_MASK) != 0
+        || !(INIT_BITS..=MAX_BITS).contains(&max_bits)
+    {
+        return Err(StructureError);
+    }
+
+    Ok(CompressdHeader {
+        max_bits,
+        block_mode: (flags & FLAG_BLOCK_MODE) != 0,
+    })
+}
+
+/// Defines the internal extractor function for decompressing compress'd data
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::compressd::compressd_extractor;
+///
+/// match compressd_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn compressd_extractor() -> Extractor {
+    Extractor {
+        utility: ExtractorType::Internal(compressd_decompress),
+        ..Default::default()
+    }
+}
+
+/// Internal extractor for compress'd (LZW) data
+///
+/// There is no end marker in compress'd data, so decompression continues until EOF, or until an
+/// invalid code is found; the reported size is that of the data up to the last valid code.
+pub fn compressd_decompress(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> ExtractionResult {
+    // Output file for decompressed data
+    const OUTPUT_FILE_NAME: &str = "decompressed.bin";
+
+    let mut result = ExtractionResult::default();
+
+    let Some(compressd_data) = file_data.get(offset..) else {
+        return result;
+    };
+
+    let Ok(header) = parse_compressd_header(compressd_data) else {
+        return result;
+    };
+
+    let mut output_file = match output_directory {
+        None => None,
+        Some(output_directory) => {
+            match Chroot::new(output_directory).create_file_writer(OUTPUT_FILE_NAME) {
+                None => return result,
+                Some(file) => Some(BufWriter::new(file)),
+            }
+        }
+    };
+
+    let Some(lzw) = lzw_decompress(
+        &compressd_data[COMPRESSD_HEADER_SIZE..],
+        &header,
+        output_file.as_mut().map(|file| file as &mut dyn Write),
+        None,
+    ) else {
+        return result;
+    };
+
+    if lzw.decompressed_size == 0 {
+        return result;
+    }
+
+    if let Some(mut output_file) = output_file
+        && output_file.flush().is_err()
+    {
+        return result;
+    }
+
+    let compressed_size = COMPRESSD_HEADER_SIZE + lzw.compressed_size;
+
+    result.success = true;
+    result.size = Some(compressed_size);
+    result.set_compression_ratio(compressed_size, lzw.decompressed_size);
+
+    result
+}
+
+/// Reads LZW codes from the compress'd data stream
+struct CodeReader<'a> {
+    data: &'a [u8],
+    /// Current position in the data, in bits
+    bit_position: usize,
+    /// Bit position at which the current code size took effect
+    group_start: usize,
+}
+
+impl CodeReader<'_> {
+    /// Returns the next code, or None if there is not enough data left for a full code
+    fn read(&mut self, n_bits: usize) -> Option<usize> {
+        if self.bit_position + n_bits > self.data.len() * 8 {
+            return None;
+        }
+
+        let mut code: usize = 0;
+
+        for i in 0..n_bits {
+            let bit = self.bit_position + i;
+            let bit_value = (self.data[bit / 8] >> (bit % 8)) & 1;
+            code |= (bit_value as usize) << i;
+        }
+
+        self.bit_position += n_bits;
+
+        Some(code)
+    }
+
+    /// Codes are written in groups of n_bits bytes; when the code size changes, or the code table is
+    /// cleared, the remainder of the current group is padding.
+    fn skip_group_padding(&mut self, n_bits: usize) {
+        let group_bits = n_bits * 8;
+        let used_bits = self.bit_position - self.group_start;
+
+        self.bit_position = self.group_start + used_bits.next_multiple_of(group_bits);
+        self.group_start = self.bit_position;
+    }
+
+    /// Returns the number of bytes containing the codes read so far
+    fn consumed_bytes(&self) -> usize {
+        self.bit_position.div_ceil(8).min(self.data.len())
+    }
+}
+
+/// Describes the result of decompressing LZW data
+struct LzwStatus {
+    /// Number of bytes of decompressed data
+    decompressed_size: usize,
+    /// Number of compressed bytes processed
+    compressed_size: usize,
+    /// False if decompression stopped because the maximum number of codes was reached
+    complete: bool,
+}
+
+/// Decompresses LZW data, writing the decompressed data to `output`, if specified, and stopping after `max_codes`
+/// codes, if specified. Returns None if the data decompresses to more than MAX_DECOMPRESSED_SIZE bytes, or if the
+/// decompressed data could not be written.
+fn lzw_decompress(
+    lzw_data: &[u8],
+    header: &CompressdHeader,
+    mut output: Option<&mut dyn Write>,
+    max_codes: Option<usize>,
+) -> Option<LzwStatus> {
+    // Code table entries for literal byte values
+    const LITERAL_CODES: usize = 256;
+    // LZW can expand data by several orders of magnitude; don't allow a small input to decompress to an absurd size
+    const MAX_DECOMPRESSED_SIZE: usize = 4 * 1024 * 1024 * 1024;
+
+    let max_max_code: usize = 1 << header.max_bits;
+    let first_free_code: usize = if header.block_mode {
+        CLEAR_CODE + 1
+    } else {
+        LITERAL_CODES
+    };
+
+    // Each code table entry is a prefix code plus a suffix byte; literal codes have no prefix.
+    // The length and first byte of each entry's string are tracked so that the decompressed size
+    // can be counted without walking the prefix chain.
+    let mut prefixes: Vec<usize> = vec![0; max_max_code];
+    let mut suffixes: Vec<u8> = (0..max_max_code).map(|code| code as u8).collect();
+    let mut first_bytes: Vec<u8> = suffixes.clone();
+    let mut lengths: Vec<usize> = vec![1; max_max_code];
+
+    let mut reader = CodeReader {
+        data: lzw_data,
+        bit_position: 0,
+        group_start: 0,
+    };
+
+    let mut string: Vec<u8> = Vec::new();
+
+    let mut n_bits = INIT_BITS;
+    let mut max_code = (1 << n_bits) - 1;
+    let mut free_code = first_free_code;
+    let mut previous_code: Option<usize> = None;
+    let mut code_count: usize = 0;
+
+    let mut status = LzwStatus {
+        decompressed_size: 0,
+        compressed_size: 0,
+        complete: true,
+    };
+
+    loop {
+        if max_codes.is_some_and(|max_codes| code_count >= max_codes) {
+            status.complete = false;
+            break;
+        }
+
+        // If the next code table entry won't fit in the current code size, increase the code size
+        if free_code > max_code && n_bits < header.max_bits {
+            reader.skip_group_padding(n_bits);
+            n_bits += 1;
+            max_code = if n_bits == header.max_bits {
+                max_max_code
+            } else {
+                (1 << n_bits) - 1
+            };
+        }
+
+        let Some(code) = reader.read(n_bits) else {
+            break;
+        };
+
+        code_count += 1;
+
+        if header.block_mode && code == CLEAR_CODE {
+            reader.skip_group_padding(n_bits);
+            n_bits = INIT_BITS;
+            max_code = (1 << n_bits) - 1;
+            free_code = first_free_code;
+            previous_code = None;
+            status.compressed_size = reader.consumed_bytes();
+            continue;
+        }
+
+        // The code, and the length and first byte of its string
+        let (entry_code, string_length, first_byte) = match previous_code {
+            // The first code (after a clear code) must be a literal
+            None if code < LITERAL_CODES => (code, 1, code as u8),
+            None => break,
+            Some(previous_code) => {
+                // The code may refer to the entry that is about to be added, but nothing beyond that;
+                // once the table is full, no new entries are added
+                let (entry_code, string_length) = if code < free_code {
+                    (code, lengths[code])
+                } else if code == free_code && free_code < max_max_code {
+                    // The special case, where the code is the entry about to be added
+                    (previous_code, lengths[previous_code] + 1)
+                } else {
+                    break;
+                };
+
+                let first_byte = first_bytes[entry_code];
+
+                if free_code < max_max_code {
+                    prefixes[free_code] = previous_code;
+                    suffixes[free_code] = first_byte;
+                    first_bytes[free_code] = first_bytes[previous_code];
+                    lengths[free_code] = lengths[previous_code] + 1;
+                    free_code += 1;
+                }
+
+                (entry_code, string_length, first_byte)
+            }
+        };
+
+        status.decompressed_size += string_length;
+
+        if status.decompressed_size > MAX_DECOMPRESSED_SIZE {
+            return None;
+        }
+
+        if let Some(output) = &mut output {
+            // Walk the prefix chain to build the string, in reverse
+            string.clear();
+
+            let mut next_code = entry_code;
+            while next_code >= LITERAL_CODES {
+                string.push(suffixes[next_code]);
+                next_code = prefixes[next_code];
+            }
+            string.push(next_code as u8);
+            string.reverse();
+
+            if string.len() < string_length {
+                string.push(first_byte);
+            }
+
+            output.write_all(&string).ok()?;
+        }
+
+        previous_code = Some(code);
+        status.compressed_size = reader.consumed_bytes();
+    }
+
+    Some(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 16-bit maximum code size (see tests/inputs/gen_compressd.sh).
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/compressd.bin");
+
+    /// 10-bit maximum code size, with a clear code each time the code table fills up (see
+    /// tests/inputs/gen_compressd.sh).
+    static VALID_CLEAR: &[u8] = include_bytes!("../../tests/inputs/compressd_clear.bin");
+
+    /// The data compressed in both fixtures
+    fn expected_data() -> Vec<u8> {
+        (0..200)
+            .map(|n| {
+                format!(
+                    "Line {n}: the quick brown fox jumps over the lazy dog {}\n",
+                    n * n
+                )
+            })
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    fn decompress(file_data: &[u8]) -> Vec<u8> {
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = compressd_decompress(file_data, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(file_data.len()));
+        std::fs::read(output_directory.path().join("decompressed.bin")).unwrap()
+    }
+
+    #[test]
+    fn variable_code_size() {
+        let signature = compressd_parser(VALID, 0).unwrap();
+        assert_eq!(signature.size, VALID.len());
+        assert!(signature.description.contains("max code size: 16 bits"));
+
+        assert_eq!(decompress(VALID), expected_data());
+    }
+
+    #[test]
+    fn clear_code() {
+        // The code table fills up several times, and is reset with a clear code each time
+        let signature = compressd_parser(VALID_CLEAR, 0).unwrap();
+        assert_eq!(signature.size, VALID_CLEAR.len());
+        assert!(signature.description.contains("max code size: 10 bits"));
+
+        assert_eq!(decompress(VALID_CLEAR), expected_data());
+    }
+
+    #[test]
+    fn invalid_codes() {
+        // Max code size must be between 9 and 16 bits
+        assert!(compressd_parser(b"\x1F\x9D\x88\x41\x00", 0).is_err());
+        assert!(compressd_parser(b"\x1F\x9D\x91\x41\x00", 0).is_err());
+
+        // The first code must be a literal: 0x1FF is a 9-bit code that is not in the code table
+        assert!(!compressd_decompress(b"\x1F\x9D\x90\xFF\x01", 0, None).success);
+
+        // Data ends at the first code that is not yet in the code table
+        let mut file_data = VALID[..64].to_vec();
+        file_data.extend([0xFF; 32]);
+
+        let result = compressd_decompress(&file_data, 0, None);
+        assert!(result.success);
+        assert!(result.size.unwrap() < file_data.len());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::common::get_cstring;
+use crate::common::get_cstring_bounded;
 use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
 
 /// Human readable description
@@ -21,6 +21,9 @@ pub fn copyright_parser(
     // Size of "copright" string
     const MAGIC_SIZE: usize = 9;
 
+    // Copyright text longer than this is reported as this size
+    const MAX_COPYRIGHT_SIZE: usize = 1024;
+
     // Successful return value
     let mut result = SignatureResult {
         offset,
@@ -30,7 +33,7 @@ pub fn copyright_parser(
     };
 
     // Get a NULL terminated string, starting at the "copright" text
-    let copyright_string = get_cstring(&file_data[offset..]);
+    let copyright_string = get_cstring_bounded(&file_data[offset..], MAX_COPYRIGHT_SIZE);
 
     // Make sure we got more than just the "copyright" string
     if copyright_string.len() > MAGIC_SIZE {
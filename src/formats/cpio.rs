@@ -1,6 +1,9 @@
 use crate::common::is_offset_safe;
+use crate::extractors::{self, Chroot, ExtractionResult};
 use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
 use crate::structures::StructureError;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// Human readable description
 pub const DESCRIPTION: &str = "CPIO ASCII archive";
@@ -12,9 +15,6 @@ pub fn cpio_magic() -> Vec<Vec<u8>> {
 
 /// Parse and validate CPIO archives
 pub fn cpio_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
-    // The last CPIO entry will have this file name
-    const EOF_MARKER: &str = "TRAILER!!!";
-
     let mut header_count: usize = 0;
     let mut result = SignatureResult {
         description: DESCRIPTION.to_string(),
@@ -82,78 +82,399 @@ pub fn cpio_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, S
     Err(SignatureError)
 }
 
+/// The last CPIO entry will have this file name
+const EOF_MARKER: &str = "TRAILER!!!";
+
 /// Expected minimum size of a CPIO entry header
 pub const CPIO_HEADER_SIZE: usize = 110;
 
+/// File type bits of the CPIO entry mode
+const S_IFMT: usize = 0o170000;
+const S_IFSOCK: usize = 0o140000;
+const S_IFLNK: usize = 0o120000;
+const S_IFREG: usize = 0o100000;
+const S_IFBLK: usize = 0o060000;
+const S_IFDIR: usize = 0o040000;
+const S_IFCHR: usize = 0o020000;
+const S_IFIFO: usize = 0o010000;
+
 /// Storage struct for CPIO entry header info
 #[derive(Debug, Clone, Default)]
 pub struct CPIOEntryHeader {
     pub magic: Vec<u8>,
+    pub inode: usize,
+    pub mode: usize,
+    pub uid: usize,
+    pub gid: usize,
+    pub link_count: usize,
+    /// Size of the file data, without padding
+    pub file_size: usize,
+    pub dev_major: usize,
+    pub dev_minor: usize,
+    pub rdev_major: usize,
+    pub rdev_minor: usize,
+    /// Size of the file data, including padding
     pub data_size: usize,
     pub file_name: String,
+    /// Size of the header and file name, including padding
     pub header_size: usize,
 }
 
+impl CPIOEntryHeader {
+    /// Returns the file type bits of the entry's mode
+    pub fn file_type(&self) -> usize {
+        self.mode & S_IFMT
+    }
+
+    /// Returns the permission bits (including setuid/setgid/sticky) of the entry's mode
+    pub fn permissions(&self) -> u32 {
+        (self.mode & 0o7777) as u32
+    }
+}
+
 /// Parses a CPIO entry header
 pub fn parse_cpio_entry_header(cpio_data: &[u8]) -> Result<CPIOEntryHeader, StructureError> {
     // Some expected constants
     const NULL_BYTE_SIZE: usize = 1;
     const CPIO_MAGIC_START: usize = 0;
     const CPIO_MAGIC_END: usize = 6;
-    const FILE_SIZE_START: usize = 54;
-    const FILE_SIZE_END: usize = 62;
-    const FILE_NAME_SIZE_START: usize = 94;
-    const FILE_NAME_SIZE_END: usize = 102;
+    const INODE_OFFSET: usize = 6;
+    const MODE_OFFSET: usize = 14;
+    const UID_OFFSET: usize = 22;
+    const GID_OFFSET: usize = 30;
+    const LINK_COUNT_OFFSET: usize = 38;
+    const FILE_SIZE_OFFSET: usize = 54;
+    const DEV_MAJOR_OFFSET: usize = 62;
+    const DEV_MINOR_OFFSET: usize = 70;
+    const RDEV_MAJOR_OFFSET: usize = 78;
+    const RDEV_MINOR_OFFSET: usize = 86;
+    const FILE_NAME_SIZE_OFFSET: usize = 94;
+
+    if cpio_data.len() <= CPIO_HEADER_SIZE {
+        return Err(StructureError);
+    }
 
-    let available_data: usize = cpio_data.len();
+    let file_size = cpio_hex_field(cpio_data, FILE_SIZE_OFFSET)?;
+    let file_name_size = cpio_hex_field(cpio_data, FILE_NAME_SIZE_OFFSET)?;
 
-    // TODO: If file mode parsing is added, internal extractor would be pretty easy to implement...
-    if available_data > CPIO_HEADER_SIZE {
-        // Grab the CPIO header magic bytes
-        let header_magic = cpio_data[CPIO_MAGIC_START..CPIO_MAGIC_END].to_vec();
+    // The file name immediately follows the fixed-length header data, and includes a NULL terminator
+    let file_name_start: usize = CPIO_HEADER_SIZE;
+    let file_name_end: usize = file_name_start
+        + file_name_size
+            .checked_sub(NULL_BYTE_SIZE)
+            .ok_or(StructureError)?;
 
-        // Get the ASCII hex string representing the file's data size
-        if let Ok(file_data_size_str) =
-            String::from_utf8(cpio_data[FILE_SIZE_START..FILE_SIZE_END].to_vec())
-        {
-            // Convert the file data size from ASCII hex to an integer
-            if let Ok(file_data_size) = usize::from_str_radix(&file_data_size_str, 16) {
-                // Get the ASCII hex string representing the file name's size
-                if let Ok(file_name_size_str) =
-                    String::from_utf8(cpio_data[FILE_NAME_SIZE_START..FILE_NAME_SIZE_END].to_vec())
-                {
-                    // Convert the file name size from ASCII hex to an integer
-                    if let Ok(file_name_size) = usize::from_str_radix(&file_name_size_str, 16) {
-                        // The file name immediately follows the fixed-length header data.
-                        let file_name_start: usize = CPIO_HEADER_SIZE;
-                        let file_name_end: usize =
-                            file_name_start + file_name_size - NULL_BYTE_SIZE;
-
-                        // Get the file name
-                        if let Some(file_name_raw_bytes) =
-                            cpio_data.get(file_name_start..file_name_end)
-                            && let Ok(file_name) = String::from_utf8(file_name_raw_bytes.to_vec())
-                        {
-                            let header_total_size = CPIO_HEADER_SIZE + file_name_size;
-
-                            return Ok(CPIOEntryHeader {
-                                magic: header_magic,
-                                file_name,
-                                data_size: file_data_size + byte_padding(file_data_size),
-                                header_size: header_total_size + byte_padding(header_total_size),
-                            });
+    let file_name = String::from_utf8(
+        cpio_data
+            .get(file_name_start..file_name_end)
+            .ok_or(StructureError)?
+            .to_vec(),
+    )
+    .map_err(|_| StructureError)?;
+
+    let header_total_size = CPIO_HEADER_SIZE + file_name_size;
+
+    Ok(CPIOEntryHeader {
+        magic: cpio_data[CPIO_MAGIC_START..CPIO_MAGIC_END].to_vec(),
+        inode: cpio_hex_field(cpio_data, INODE_OFFSET)?,
+        mode: cpio_hex_field(cpio_data, MODE_OFFSET)?,
+        uid: cpio_hex_field(cpio_data, UID_OFFSET)?,
+        gid: cpio_hex_field(cpio_data, GID_OFFSET)?,
+        link_count: cpio_hex_field(cpio_data, LINK_COUNT_OFFSET)?,
+        file_size,
+        dev_major: cpio_hex_field(cpio_data, DEV_MAJOR_OFFSET)?,
+        dev_minor: cpio_hex_field(cpio_data, DEV_MINOR_OFFSET)?,
+        rdev_major: cpio_hex_field(cpio_data, RDEV_MAJOR_OFFSET)?,
+        rdev_minor: cpio_hex_field(cpio_data, RDEV_MINOR_OFFSET)?,
+        data_size: file_size + byte_padding(file_size),
+        file_name,
+        header_size: header_total_size + byte_padding(header_total_size),
+    })
+}
+
+/// Converts an 8-character ASCII hex header field at the specified offset to an integer
+fn cpio_hex_field(cpio_data: &[u8], offset: usize) -> Result<usize, StructureError> {
+    const FIELD_SIZE: usize = 8;
+
+    let field = cpio_data
+        .get(offset..offset + FIELD_SIZE)
+        .ok_or(StructureError)?;
+
+    // from_str_radix would otherwise accept a leading '+'
+    if !field.iter().all(u8::is_ascii_hexdigit) {
+        return Err(StructureError);
+    }
+
+    // All hex digits are ASCII, so this is valid UTF-8
+    usize::from_str_radix(std::str::from_utf8(field).map_err(|_| StructureError)?, 16)
+        .map_err(|_| StructureError)
+}
+
+/// File data and CPIO headers are padded to 4-byte boundaries
+const fn byte_padding(n: usize) -> usize {
+    let modulus: usize = n % 4;
+    if modulus == 0 { 0 } else { 4 - modulus }
+}
+
+/// Describes the internal CPIO extractor
+///
+/// ```
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::cpio::cpio_extractor;
+///
+/// match cpio_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => panic!("Unexpected external extractor '{}'", cmd),
+/// }
+/// ```
+pub fn cpio_extractor() -> extractors::Extractor {
+    extractors::Extractor {
+        utility: extractors::ExtractorType::Internal(extract_cpio),
+        ..Default::default()
+    }
+}
+
+/// Internal extractor for newc CPIO archives.
+///
+/// Special files are extracted as placeholder files, and hard links as symlinks to the
+/// entry that carries the file data (see Chroot). When `output_directory` is `None`,
+/// the archive is only validated.
+pub fn extract_cpio(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> ExtractionResult {
+    // Identifies the file that a hard link refers to: device major/minor and inode number
+    type InodeKey = (usize, usize, usize);
+
+    let mut result = ExtractionResult::default();
+
+    let chroot = output_directory.map(Chroot::new);
+    let available_data = file_data.len();
+    let mut next_header_offset = offset;
+    let mut previous_header_offset = None;
+    let mut eof = false;
+    let mut extracted_something = false;
+
+    // Hard links whose data has not been seen yet, and the paths of hard linked files that have been written
+    let mut pending_links: HashMap<InodeKey, Vec<PathBuf>> = HashMap::new();
+    let mut linked_files: HashMap<InodeKey, PathBuf> = HashMap::new();
+
+    // Directory modes are applied last, so that read-only directories can still be populated
+    let mut directory_modes: Vec<(PathBuf, u32)> = Vec::new();
+
+    while is_offset_safe(available_data, next_header_offset, previous_header_offset) {
+        let Ok(cpio_header) = parse_cpio_entry_header(&file_data[next_header_offset..]) else {
+            break;
+        };
+
+        if !cpio_magic().contains(&cpio_header.magic) {
+            break;
+        }
+
+        let data_start = next_header_offset + cpio_header.header_size;
+        let Some(entry_data) = file_data.get(data_start..data_start + cpio_header.file_size) else {
+            break;
+        };
+
+        previous_header_offset = Some(next_header_offset);
+        next_header_offset = data_start + cpio_header.data_size;
+
+        if cpio_header.file_name == EOF_MARKER {
+            eof = true;
+            break;
+        }
+
+        let Some(chroot) = &chroot else {
+            continue;
+        };
+
+        let path = PathBuf::from(&cpio_header.file_name);
+
+        // The archive root itself has nothing to extract
+        if path.as_os_str().is_empty() || path == Path::new(".") {
+            continue;
+        }
+
+        if let Some(parent) = path.parent() {
+            chroot.create_directory(parent);
+        }
+
+        let entry_extracted = match cpio_header.file_type() {
+            S_IFDIR => {
+                let created = chroot.create_directory(&path);
+                if created {
+                    directory_modes.push((path, cpio_header.permissions()));
+                }
+                created
+            }
+            S_IFLNK => match String::from_utf8(entry_data.to_vec()) {
+                Ok(target) => chroot.create_symlink(&path, target),
+                Err(_) => false,
+            },
+            S_IFCHR => chroot.create_character_device(
+                &path,
+                cpio_header.rdev_major,
+                cpio_header.rdev_minor,
+            ),
+            S_IFBLK => {
+                chroot.create_block_device(&path, cpio_header.rdev_major, cpio_header.rdev_minor)
+            }
+            S_IFIFO => chroot.create_fifo(&path),
+            S_IFSOCK => chroot.create_socket(&path),
+            S_IFREG if cpio_header.link_count > 1 => {
+                let inode_key = (
+                    cpio_header.dev_major,
+                    cpio_header.dev_minor,
+                    cpio_header.inode,
+                );
+
+                if let Some(linked_file) = linked_files.get(&inode_key) {
+                    // The data for this hard link has already been written
+                    chroot.create_symlink(&path, Path::new("/").join(linked_file))
+                } else if entry_data.is_empty() {
+                    // Only one of the hard links, usually the last, carries the file data
+                    pending_links.entry(inode_key).or_default().push(path);
+                    true
+                } else {
+                    let created = chroot.create_file(&path, entry_data);
+                    if created {
+                        apply_file_metadata(chroot, &path, &cpio_header);
+
+                        for link in pending_links.remove(&inode_key).unwrap_or_default() {
+                            chroot.create_symlink(&link, Path::new("/").join(&path));
                         }
+
+                        linked_files.insert(inode_key, path);
                     }
+                    created
+                }
+            }
+            S_IFREG => {
+                let created = chroot.create_file(&path, entry_data);
+                if created {
+                    apply_file_metadata(chroot, &path, &cpio_header);
                 }
+                created
             }
+            _ => false,
+        };
+
+        extracted_something |= entry_extracted;
+    }
+
+    if let Some(chroot) = &chroot {
+        // Hard links whose data was never seen are empty files
+        for link in pending_links.into_values().flatten() {
+            chroot.create_file(&link, &[]);
+        }
+
+        for (directory, mode) in directory_modes.iter().rev() {
+            chroot.set_mode(directory, *mode);
         }
     }
 
-    Err(StructureError)
+    // A dry run only needs to find the end of the archive
+    if eof && (chroot.is_none() || extracted_something) {
+        result.success = true;
+        result.size = Some(next_header_offset - offset);
+    }
+
+    result
 }
 
-/// File data and CPIO headers are padded to 4-byte boundaries
-const fn byte_padding(n: usize) -> usize {
-    let modulus: usize = n % 4;
-    if modulus == 0 { 0 } else { 4 - modulus }
+/// Restores a file's ownership (best effort; needs privileges) and then its mode; ownership is
+/// set first because changing it can clear the setuid/setgid bits on some systems.
+fn apply_file_metadata(chroot: &Chroot, path: &Path, cpio_header: &CPIOEntryHeader) {
+    chroot.set_ownership(path, cpio_header.uid as u32, cpio_header.gid as u32);
+    chroot.set_mode(path, cpio_header.permissions());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// newc archive of a directory, a regular file, a symlink to it, and a pair of hard links (see
+    /// tests/inputs/gen_cpio.sh).
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/cpio.bin");
+
+    #[test]
+    fn entry_header() {
+        let header = parse_cpio_entry_header(&VALID[116..]).unwrap();
+        assert_eq!(header.file_name, "etc/hostname");
+        assert_eq!(header.inode, 2);
+        assert_eq!(header.mode, 0o100644);
+        assert_eq!(header.permissions(), 0o644);
+        assert_eq!(header.file_size, 7);
+        assert_eq!(header.data_size, 8);
+        assert_eq!(header.header_size, 124);
+
+        // Header fields must be ASCII hex
+        let mut bad_field = VALID.to_vec();
+        bad_field[54] = b'+';
+        assert!(parse_cpio_entry_header(&bad_field).is_err());
+    }
+
+    #[test]
+    fn extract_regular_file_and_symlink() {
+        // Trailing data is not part of the archive
+        let mut archive = VALID.to_vec();
+        archive.extend([0xFF; 64]);
+
+        let signature = cpio_parser(&archive, 0).unwrap();
+        assert_eq!(signature.size, VALID.len());
+        assert_eq!(signature.description, "CPIO ASCII archive, file count: 5");
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = extract_cpio(&archive, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(VALID.len()));
+
+        let root = output_directory.path();
+        assert_eq!(
+            std::fs::read(root.join("etc/hostname")).unwrap(),
+            b"router\n"
+        );
+
+        let symlink = root.join("hostname");
+        assert!(
+            std::fs::symlink_metadata(&symlink)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(std::fs::read(&symlink).unwrap(), b"router\n");
+
+        // Hard links are extracted as symlinks to the file that carries the data
+        assert_eq!(std::fs::read(root.join("bin/busybox")).unwrap(), b"\x7FELF");
+        assert!(
+            std::fs::symlink_metadata(root.join("bin/sh"))
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(std::fs::read(root.join("bin/sh")).unwrap(), b"\x7FELF");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = std::fs::metadata(root.join("bin/busybox"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+    }
+
+    #[test]
+    fn archive_without_trailer_is_rejected() {
+        // Cut the archive off before the TRAILER!!! entry
+        let truncated = &VALID[..VALID.len() - 124];
+        assert!(cpio_parser(truncated, 0).is_err());
+        assert!(!extract_cpio(truncated, 0, None).success);
+    }
 }
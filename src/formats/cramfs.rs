@@ -18,8 +18,12 @@ pub fn cramfs_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult,
     const CRC_START_OFFSET: usize = 32;
     const CRC_END_OFFSET: usize = 36;
 
+    let Some(start_offset) = offset.checked_sub(SIGNATURE_OFFSET) else {
+        return Err(SignatureError);
+    };
+
     let mut result = SignatureResult {
-        offset: offset - SIGNATURE_OFFSET,
+        offset: start_offset,
         description: DESCRIPTION.to_string(),
         confidence: CONFIDENCE_HIGH,
         ..Default::default()
@@ -34,21 +38,24 @@ pub fn cramfs_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult,
             if let Some(cramfs_image_data) =
                 file_data.get(result.offset..result.offset + result.size)
             {
-                /*
-                 * Create a copy of the cramfs image; we have to NULL out the checksum field to calculate the CRC.
-                 * This typically shouldn't be too bad on performance, CramFS images are usually relatively small.
-                 */
-                let mut cramfs_image = cramfs_image_data.to_vec();
-
-                // Null out the checksum field
-                cramfs_image[CRC_START_OFFSET..CRC_END_OFFSET].fill(0);
+                // The checksum is calculated with the checksum field NULL'd out
+                let crc = common::crc32_seed(
+                    common::crc32_seed(
+                        common::crc32(&cramfs_image_data[..CRC_START_OFFSET]),
+                        &[0; CRC_END_OFFSET - CRC_START_OFFSET],
+                    ),
+                    &cramfs_image_data[CRC_END_OFFSET..],
+                );
 
                 // For displaying an error message in the description
                 let mut error_message = "";
 
-                // On CRC error, lower confidence and report the checksum error
-                // (have seen partially corrupted images that still extract Ok)
-                if common::crc32(&cramfs_image) != cramfs_header.checksum {
+                if cramfs_header.flags & CRAMFS_FLAG_FSID_VERSION_2 == 0 {
+                    // Older images have no checksum to validate
+                    result.confidence = CONFIDENCE_MEDIUM;
+                } else if crc != cramfs_header.checksum {
+                    // On CRC error, lower confidence and report the checksum error
+                    // (have seen partially corrupted images that still extract Ok)
                     error_message = " (checksum error)";
                     result.confidence = CONFIDENCE_MEDIUM;
                 }
@@ -70,10 +77,16 @@ pub fn cramfs_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult,
 }
 
 /// Struct to store info about a CramFS header
+/// Set in the header flags of images that have a valid fsid (checksum, edition, block and file counts)
+pub const CRAMFS_FLAG_FSID_VERSION_2: u32 = 1;
+
 #[derive(Debug, Clone)]
 pub struct CramFSHeader {
     pub size: usize,
+    pub flags: u32,
     pub checksum: u32,
+    pub edition: u32,
+    pub block_count: usize,
     pub file_count: usize,
     pub endianness: Endianness,
 }
@@ -97,6 +110,7 @@ pub fn parse_cramfs_header(cramfs_data: &[u8]) -> Result<CramFSHeader, Structure
     const MAGIC: u32 = 0x28CD3D45;
     const LITTLE_ENDIAN_MAGIC: dyn_endian::U32 = dyn_endian::U32::new(MAGIC, Endianness::Little);
     const BIG_ENDIAN_MAGIC: dyn_endian::U32 = dyn_endian::U32::new(MAGIC, Endianness::Big);
+    const SIGNATURE: &[u8; 16] = b"Compressed ROMFS";
 
     let cramfs_structure_size = std::mem::size_of::<CramFSHeaderBytes>();
 
@@ -110,10 +124,15 @@ pub fn parse_cramfs_header(cramfs_data: &[u8]) -> Result<CramFSHeader, Structure
     };
 
     // Reported image size must be larger than the header structure
-    if cramfs_header.size.get(endianness) as usize > cramfs_structure_size {
+    if cramfs_header.size.get(endianness) as usize > cramfs_structure_size
+        && cramfs_header.signature == *SIGNATURE
+    {
         return Ok(CramFSHeader {
             size: cramfs_header.size.get(endianness) as usize,
+            flags: cramfs_header.flags.get(endianness),
             checksum: cramfs_header.checksum.get(endianness),
+            edition: cramfs_header.edition.get(endianness),
+            block_count: cramfs_header.block_count.get(endianness) as usize,
             file_count: cramfs_header.file_count.get(endianness) as usize,
             endianness,
         });
@@ -121,3 +140,70 @@ pub fn parse_cramfs_header(cramfs_data: &[u8]) -> Result<CramFSHeader, Structure
 
     Err(StructureError)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A little endian CramFS image, with the fsid flag set and a valid checksum
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/cramfs.bin");
+
+    /// Offsets of the 32-bit header fields: magic, size, flags, future, checksum, edition, block count and file count
+    const HEADER_FIELD_OFFSETS: [usize; 8] = [0, 4, 8, 12, 32, 36, 40, 44];
+
+    /// Sets the image checksum, which is calculated with the checksum field zeroed
+    fn update_checksum(image: &mut [u8], to_bytes: fn(u32) -> [u8; 4]) {
+        image[32..36].fill(0);
+        let crc = common::crc32(image);
+        image[32..36].copy_from_slice(&to_bytes(crc));
+    }
+
+    /// Returns a copy of the fixture with its header fields converted to big endian, and an updated checksum
+    fn big_endian_fixture() -> Vec<u8> {
+        let mut image = VALID.to_vec();
+        for field_offset in HEADER_FIELD_OFFSETS {
+            image[field_offset..field_offset + 4].reverse();
+        }
+        update_checksum(&mut image, u32::to_be_bytes);
+        image
+    }
+
+    #[test]
+    fn both_endiannesses() {
+        for (image, to_bytes, endianness) in [
+            (
+                VALID.to_vec(),
+                u32::to_le_bytes as fn(u32) -> [u8; 4],
+                Endianness::Little,
+            ),
+            (big_endian_fixture(), u32::to_be_bytes, Endianness::Big),
+        ] {
+            let header = parse_cramfs_header(&image).unwrap();
+            assert_eq!(header.endianness, endianness);
+            assert_eq!(header.size, image.len());
+            assert_eq!(header.flags, 3);
+            assert_eq!(header.block_count, 1);
+            assert_eq!(header.file_count, 2);
+
+            // The parser is called with the offset of the signature string
+            let signature = cramfs_parser(&image, 16).unwrap();
+            assert_eq!(signature.offset, 0);
+            assert_eq!(signature.size, image.len());
+            assert_eq!(signature.confidence, CONFIDENCE_HIGH);
+            assert!(!signature.description.contains("checksum error"));
+
+            // A corrupted image fails checksum validation
+            let mut corrupt_image = image.clone();
+            corrupt_image[0x800] ^= 0xFF;
+            let signature = cramfs_parser(&corrupt_image, 16).unwrap();
+            assert_eq!(signature.confidence, CONFIDENCE_MEDIUM);
+            assert!(signature.description.ends_with("(checksum error)"));
+
+            // Images without the fsid flag set have no checksum to validate
+            corrupt_image[8..12].copy_from_slice(&to_bytes(2));
+            let signature = cramfs_parser(&corrupt_image, 16).unwrap();
+            assert_eq!(signature.confidence, CONFIDENCE_MEDIUM);
+            assert!(!signature.description.contains("checksum error"));
+        }
+    }
+}
@@ -1,7 +1,7 @@
-use crate::common::get_cstring;
 use crate::signatures::{CONFIDENCE_HIGH, CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
+use crate::structures::fixed_string::FixedString;
 use crate::structures::{Endianness, StructureError, dyn_endian};
-use zerocopy::FromBytes;
+use zerocopy::{FromBytes, Immutable, KnownLayout, Unaligned};
 
 /// Human readable description
 pub const DESCRIPTION: &str = "DKBS firmware header";
@@ -72,55 +72,46 @@ pub struct DKBSHeader {
     pub endianness: Endianness,
 }
 
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct DKBSHeaderBytes {
+    board_id: FixedString<0x20>,
+    magic: [u8; 8],
+    version: FixedString<0x20>,
+    unknown1: [u8; 0x20],
+    data_size: dyn_endian::U32,
+    unknown2: [u8; 4],
+    boot_device: FixedString<0x20>,
+    unknown3: [u8; 0x10],
+}
+
 /// Parses a DKBS header
 pub fn parse_dkbs_header(dkbs_data: &[u8]) -> Result<DKBSHeader, StructureError> {
-    // Header is a fixed size
-    const HEADER_SIZE: usize = 0xA0;
-
-    // Constant offsets for strings and known header fields
-    const BOARD_ID_START: usize = 0;
-    const BOARD_ID_END: usize = 0x20;
-    const VERSION_START: usize = 0x28;
-    const VERSION_END: usize = 0x48;
-    const BOOT_DEVICE_START: usize = 0x70;
-    const BOOT_DEVICE_END: usize = 0x90;
-    const DATA_SIZE_START: usize = 0x68;
-    const DATA_SIZE_END: usize = DATA_SIZE_START + 4;
-
-    // Available data should be at least big enough for the header to fit
-    if dkbs_data.len() >= HEADER_SIZE {
-        // Parse the version, board ID, and boot device strings
-        let version = get_cstring(&dkbs_data[VERSION_START..VERSION_END]);
-        let board_id = get_cstring(&dkbs_data[BOARD_ID_START..BOARD_ID_END]);
-        let boot_device = get_cstring(&dkbs_data[BOOT_DEVICE_START..BOOT_DEVICE_END]);
-
-        // Sanity check to make sure the strings were retrieved
-        if !version.is_empty()
-            && !board_id.is_empty()
-            && !boot_device.is_empty()
-            && let Some(data_size_bytes) = dkbs_data.get(DATA_SIZE_START..DATA_SIZE_END)
-        {
-            // Parse the payload size field
-            let data_size =
-                dyn_endian::U32::ref_from_bytes(data_size_bytes).map_err(|_| StructureError)?;
-
-            let endianness = match data_size.get(Endianness::Big) & 0xFF000000 {
-                0 => Endianness::Big,
-                _ => Endianness::Little,
-            };
-            let data_size = data_size.get(endianness) as usize;
-
-            if data_size != 0 {
-                // return Ok(header);
-                return Ok(DKBSHeader {
-                    data_size,
-                    header_size: HEADER_SIZE,
-                    board_id,
-                    version,
-                    boot_device,
-                    endianness,
-                });
-            }
+    let (dkbs_header, _) =
+        DKBSHeaderBytes::ref_from_prefix(dkbs_data).map_err(|_| StructureError)?;
+
+    // Parse the version, board ID, and boot device strings
+    let version = dkbs_header.version.get();
+    let board_id = dkbs_header.board_id.get();
+    let boot_device = dkbs_header.boot_device.get();
+
+    // Sanity check to make sure the strings were retrieved
+    if !version.is_empty() && !board_id.is_empty() && !boot_device.is_empty() {
+        let endianness = match dkbs_header.data_size.get(Endianness::Big) & 0xFF000000 {
+            0 => Endianness::Big,
+            _ => Endianness::Little,
+        };
+        let data_size = dkbs_header.data_size.get(endianness) as usize;
+
+        if data_size != 0 {
+            return Ok(DKBSHeader {
+                data_size,
+                header_size: size_of::<DKBSHeaderBytes>(),
+                board_id,
+                version,
+                boot_device,
+                endianness,
+            });
         }
     }
 
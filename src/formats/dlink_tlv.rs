@@ -77,6 +77,17 @@ pub struct DlinkTLVHeader {
     pub data_checksum: String,
 }
 
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct DlinkTLVHeaderBytes {
+    magic: [u8; 4],
+    model_name: [u8; 32],
+    board_id: [u8; 32],
+    unknown: [u8; 8],
+    md5_hash: [u8; 32],
+    data_tlv: TLVBytes,
+}
+
 #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
 #[repr(C, packed)]
 struct TLVBytes {
@@ -87,41 +98,27 @@ struct TLVBytes {
 
 /// Parses a DLink TLV firmware header
 pub fn parse_dlink_tlv_header(tlv_data: &[u8]) -> Result<DlinkTLVHeader, StructureError> {
-    const MAX_STRING_LENGTH: usize = 0x20;
+    const EXPECTED_DATA_TYPE: u32 = 1;
 
-    const MODEL_NAME_OFFSET: usize = 4;
-    const BOARD_ID_OFFSET: usize = 0x24;
-    const MD5_HASH_OFFSET: usize = 0x4C;
-    const DATA_TLV_OFFSET: usize = 0x6C;
+    let (header_bytes, _) =
+        DlinkTLVHeaderBytes::ref_from_prefix(tlv_data).map_err(|_| StructureError)?;
 
-    const HEADER_SIZE: usize = 0x74;
-    const EXPECTED_DATA_TYPE: u32 = 1;
+    // Get the strings from the header
+    let header = DlinkTLVHeader {
+        model_name: get_cstring(&header_bytes.model_name),
+        board_id: get_cstring(&header_bytes.board_id),
+        data_checksum: get_cstring(&header_bytes.md5_hash),
+        header_size: size_of::<DlinkTLVHeaderBytes>(),
+        data_size: header_bytes.data_tlv.chunk_length.get() as usize,
+    };
 
-    let mut header = DlinkTLVHeader::default();
-
-    // Get the header data
-    if let Some(header_data) = tlv_data.get(0..HEADER_SIZE) {
-        // Get the strings from the header
-        header.board_id =
-            get_cstring(&header_data[BOARD_ID_OFFSET..BOARD_ID_OFFSET + MAX_STRING_LENGTH]);
-        header.model_name =
-            get_cstring(&header_data[MODEL_NAME_OFFSET..MODEL_NAME_OFFSET + MAX_STRING_LENGTH]);
-        header.data_checksum =
-            get_cstring(&header_data[MD5_HASH_OFFSET..MD5_HASH_OFFSET + MAX_STRING_LENGTH]);
-
-        // Make sure we got the expected strings OK (checksum is not always included)
-        if !header.model_name.is_empty() && !header.board_id.is_empty() {
-            // Parse the type and length values that describe the data the follows the header
-            let (data_tlv, _) = TLVBytes::ref_from_prefix(&header_data[DATA_TLV_OFFSET..])
-                .map_err(|_| StructureError)?;
-
-            // Sanity check the reported type (should be 1)
-            if data_tlv.chunk_type == EXPECTED_DATA_TYPE {
-                header.data_size = data_tlv.chunk_length.get() as usize;
-                header.header_size = HEADER_SIZE;
-                return Ok(header);
-            }
-        }
+    // Make sure we got the expected strings OK (checksum is not always included), and sanity check the type of the
+    // data that follows the header (should be 1)
+    if !header.model_name.is_empty()
+        && !header.board_id.is_empty()
+        && header_bytes.data_tlv.chunk_type == EXPECTED_DATA_TYPE
+    {
+        return Ok(header);
     }
 
     Err(StructureError)
@@ -50,6 +50,7 @@ pub fn dmg_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
                     // Report the result
                     result.size = offset + dmg_footer.footer_size;
                     result.offset = xml_offset - dmg_footer.data_length;
+                    result.trailer_offset = Some(offset);
                     result.description =
                         format!("{}, total size: {} bytes", result.description, result.size);
                     return Ok(result);
@@ -151,6 +152,8 @@ pub fn parse_dmg_footer(dmg_data: &[u8]) -> Result<DMGFooter, StructureError> {
 ///
 /// match dmg_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
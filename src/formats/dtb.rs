@@ -9,6 +9,9 @@ use zerocopy::{BE, FromBytes, Immutable, KnownLayout, Unaligned};
 /// Human readable description
 pub const DESCRIPTION: &str = "Device tree blob (DTB)";
 
+/// Name of the file that the entire DTB is carved to during extraction
+pub const DTB_FILE_NAME: &str = "device-tree.dtb";
+
 /// DTB files start with these magic bytes
 pub fn dtb_magic() -> Vec<Vec<u8>> {
     vec![b"\xD0\x0D\xFE\xED".to_vec()]
@@ -24,24 +27,55 @@ pub fn dtb_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
         ..Default::default()
     };
 
-    // Parse the DTB header
-    if let Ok(dtb_header) = parse_dtb_header(&file_data[offset..]) {
-        // Calculate the offsets of where the dt_struct and dt_strings end
-        let dt_struct_end: usize = offset + dtb_header.struct_offset + dtb_header.struct_size;
-        let dt_strings_end: usize = offset + dtb_header.strings_offset + dtb_header.strings_size;
-
-        // Sanity check the dt_struct and dt_strings offsets
-        if file_data.len() >= dt_struct_end && file_data.len() >= dt_strings_end {
-            result.size = dtb_header.total_size;
-            result.description = format!(
-                "{}, version: {}, CPU ID: {}, total size: {} bytes",
-                result.description, dtb_header.version, dtb_header.cpu_id, result.size
-            );
-            return Ok(result);
-        }
+    let dtb_data = file_data.get(offset..).ok_or(SignatureError)?;
+
+    // Parse the DTB header; the header validates that all structures lie within the reported total size
+    let dtb_header = parse_dtb_header(dtb_data).map_err(|_| SignatureError)?;
+
+    result.description = format!(
+        "{}, version: {}, CPU ID: {}, total size: {} bytes",
+        result.description, dtb_header.version, dtb_header.cpu_id, dtb_header.total_size
+    );
+
+    // The header is valid, but the rest of the DTB is cut short
+    if dtb_header.total_size > dtb_data.len() {
+        result.extraction_declined = true;
+        result.set_truncated(dtb_header.total_size, dtb_data.len());
+        return Ok(result);
+    }
+
+    result.size = dtb_header.total_size;
+
+    // Flattened Image Tree (FIT) images are DTBs that describe the kernel, ramdisk, etc. images they contain
+    let fit_images: Vec<FITImage> =
+        parse_fit_images(&dtb_header, &dtb_data[..dtb_header.total_size])
+            .into_iter()
+            .filter(|image| offset + image.offset + image.size <= file_data.len())
+            .collect();
+
+    if !fit_images.is_empty() {
+        result.description = format!(
+            "{}, flattened image tree (FIT), images: {}",
+            result.description,
+            fit_images.len()
+        );
+        result.children = fit_images
+            .into_iter()
+            .map(|image| SignatureResult {
+                offset: offset + image.offset,
+                size: image.size,
+                name: "fit_image".to_string(),
+                confidence: CONFIDENCE_MEDIUM,
+                description: format!(
+                    "FIT image \"{}\", type: {}, compression: {}, size: {} bytes",
+                    image.name, image.image_type, image.compression, image.size
+                ),
+                ..Default::default()
+            })
+            .collect();
     }
 
-    Err(SignatureError)
+    Ok(result)
 }
 
 /// Struct to store DTB info
@@ -49,7 +83,9 @@ pub fn dtb_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
 pub struct DTBHeader {
     pub total_size: usize,
     pub version: u32,
+    pub min_compatible_version: u32,
     pub cpu_id: u32,
+    pub mem_reservation_offset: usize,
     pub struct_offset: usize,
     pub strings_offset: usize,
     pub struct_size: usize,
@@ -73,6 +109,8 @@ struct DTBHeaderBytes {
 
 /// Parse  DTB header
 pub fn parse_dtb_header(dtb_data: &[u8]) -> Result<DTBHeader, StructureError> {
+    const MAGIC: u32 = 0xD00DFEED;
+
     // Expected version numbers
     const EXPECTED_VERSION: u32 = 17;
     const EXPECTED_COMPAT_VERSION: u32 = 16;
@@ -84,39 +122,60 @@ pub fn parse_dtb_header(dtb_data: &[u8]) -> Result<DTBHeader, StructureError> {
 
     // Parse the header
     let (dtb_header, _) = DTBHeaderBytes::ref_from_prefix(dtb_data).map_err(|_| StructureError)?;
+
+    if dtb_header.magic.get() != MAGIC {
+        return Err(StructureError);
+    }
+
     // Check the reported versioning
-    if dtb_header.version.get() == EXPECTED_VERSION
-        && dtb_header.min_compatible_version.get() == EXPECTED_COMPAT_VERSION
+    if dtb_header.version.get() != EXPECTED_VERSION
+        || dtb_header.min_compatible_version.get() != EXPECTED_COMPAT_VERSION
     {
-        // Check required byte alignments for the specified offsets
-        if dtb_header
-            .dt_struct_offset
+        return Err(StructureError);
+    }
+
+    // Check required byte alignments for the specified offsets
+    if !dtb_header
+        .dt_struct_offset
+        .get()
+        .is_multiple_of(STRUCT_ALIGNMENT)
+        || !dtb_header
+            .mem_reservation_block_offset
             .get()
-            .is_multiple_of(STRUCT_ALIGNMENT)
-            && dtb_header
-                .mem_reservation_block_offset
-                .get()
-                .is_multiple_of(MEM_RESERVATION_ALIGNMENT)
-        {
-            // All offsets must start after the header structure
-            if dtb_header.dt_struct_offset.get() as usize >= dtb_structure_size
-                && dtb_header.dt_strings_offset.get() as usize >= dtb_structure_size
-                && dtb_header.mem_reservation_block_offset.get() as usize >= dtb_structure_size
-            {
-                return Ok(DTBHeader {
-                    total_size: dtb_header.total_size.get() as usize,
-                    version: dtb_header.version.get(),
-                    cpu_id: dtb_header.cpu_id.get(),
-                    struct_offset: dtb_header.dt_struct_offset.get() as usize,
-                    strings_offset: dtb_header.dt_strings_offset.get() as usize,
-                    struct_size: dtb_header.dt_struct_size.get() as usize,
-                    strings_size: dtb_header.dt_strings_size.get() as usize,
-                });
-            }
-        }
+            .is_multiple_of(MEM_RESERVATION_ALIGNMENT)
+    {
+        return Err(StructureError);
+    }
+
+    let header = DTBHeader {
+        total_size: dtb_header.total_size.get() as usize,
+        version: dtb_header.version.get(),
+        min_compatible_version: dtb_header.min_compatible_version.get(),
+        cpu_id: dtb_header.cpu_id.get(),
+        mem_reservation_offset: dtb_header.mem_reservation_block_offset.get() as usize,
+        struct_offset: dtb_header.dt_struct_offset.get() as usize,
+        strings_offset: dtb_header.dt_strings_offset.get() as usize,
+        struct_size: dtb_header.dt_struct_size.get() as usize,
+        strings_size: dtb_header.dt_strings_size.get() as usize,
+    };
+
+    // All offsets must start after the header structure
+    if header.struct_offset < dtb_structure_size
+        || header.strings_offset < dtb_structure_size
+        || header.mem_reservation_offset < dtb_structure_size
+    {
+        return Err(StructureError);
     }
 
-    Err(StructureError)
+    // The memory reservation block, structure block and strings block must all lie within the reported total size
+    if header.mem_reservation_offset >= header.total_size
+        || header.struct_offset + header.struct_size > header.total_size
+        || header.strings_offset + header.strings_size > header.total_size
+    {
+        return Err(StructureError);
+    }
+
+    Ok(header)
 }
 
 /// Describes a DTB node entry
@@ -212,6 +271,110 @@ pub fn parse_dtb_node(
     Ok(node)
 }
 
+/// Describes an image inside a Flattened Image Tree (FIT)
+#[derive(Debug, Default, Clone)]
+pub struct FITImage {
+    /// Name of the image node, e.g. "kernel-1"
+    pub name: String,
+    /// Offset of the image data, relative to the start of the DTB
+    pub offset: usize,
+    pub size: usize,
+    pub image_type: String,
+    pub compression: String,
+}
+
+/// Walks the DTB structure and returns the images listed under the /images node of a FIT image.
+///
+/// Images may be embedded in a `data` property, or stored after the DTB (`data-offset` / `data-position`).
+/// Returns an empty list if this is not a FIT image, or the DTB structure is invalid.
+pub fn parse_fit_images(dtb_header: &DTBHeader, dtb_data: &[u8]) -> Vec<FITImage> {
+    const IMAGES_NODE_PATH: [&str; 2] = ["", "images"];
+    const UNKNOWN: &str = "unknown";
+
+    let mut images: Vec<FITImage> = Vec::new();
+    let mut hierarchy: Vec<String> = Vec::new();
+    let mut image: Option<FITImage> = None;
+    // External data offsets and sizes; resolved once the image node ends
+    let mut external_offset: Option<usize> = None;
+    let mut external_size: Option<usize> = None;
+
+    let mut entry_offset = dtb_header.struct_offset;
+    let mut previous_entry_offset = None;
+
+    while is_offset_safe(dtb_data.len(), entry_offset, previous_entry_offset) {
+        let Ok(node) = parse_dtb_node(dtb_header, dtb_data, entry_offset) else {
+            return vec![];
+        };
+
+        if node.begin {
+            hierarchy.push(node.name.clone());
+
+            // Each child of the /images node is an image
+            if hierarchy.len() == IMAGES_NODE_PATH.len() + 1 && hierarchy[..2] == IMAGES_NODE_PATH {
+                image = Some(FITImage {
+                    name: node.name,
+                    image_type: UNKNOWN.to_string(),
+                    compression: UNKNOWN.to_string(),
+                    ..Default::default()
+                });
+                external_offset = None;
+                external_size = None;
+            }
+        } else if node.end {
+            // End of an image node
+            if hierarchy.len() == IMAGES_NODE_PATH.len() + 1
+                && let Some(mut fit_image) = image.take()
+            {
+                if let (Some(data_offset), Some(data_size)) = (external_offset, external_size) {
+                    fit_image.offset = data_offset;
+                    fit_image.size = data_size;
+                }
+
+                if fit_image.size > 0 {
+                    images.push(fit_image);
+                }
+            }
+
+            hierarchy.pop();
+        } else if node.eof {
+            return images;
+        } else if node.property
+            && hierarchy.len() == IMAGES_NODE_PATH.len() + 1
+            && let Some(fit_image) = image.as_mut()
+        {
+            let cell = node
+                .data
+                .get(0..4)
+                .map(|cell| u32::from_be_bytes(cell.try_into().unwrap()) as usize);
+
+            match node.name.as_str() {
+                // Embedded image data immediately follows the property token and structure
+                "data" => {
+                    fit_image.offset =
+                        entry_offset + node.total_size - dtb_aligned(node.data.len());
+                    fit_image.size = node.data.len();
+                }
+                "type" => fit_image.image_type = get_cstring(&node.data),
+                "compression" => fit_image.compression = get_cstring(&node.data),
+                "data-size" => external_size = cell,
+                // External data offsets are relative to the end of the (4-byte aligned) DTB
+                "data-offset" => {
+                    external_offset = cell.map(|cell| dtb_aligned(dtb_header.total_size) + cell)
+                }
+                "data-position" => external_offset = cell,
+                _ => (),
+            }
+        } else if !node.begin && !node.end && !node.nop && !node.property {
+            return vec![];
+        }
+
+        previous_entry_offset = Some(entry_offset);
+        entry_offset += node.total_size;
+    }
+
+    vec![]
+}
+
 /// DTB entries must be aligned to 4-byte boundaries
 const fn dtb_aligned(len: usize) -> usize {
     const ALIGNMENT: usize = 4;
@@ -232,6 +395,8 @@ const fn dtb_aligned(len: usize) -> usize {
 ///
 /// match dtb_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -262,7 +427,7 @@ pub fn extract_dtb(
     let mut result = ExtractionResult::default();
 
     // Parse the DTB file header
-    if let Ok(dtb_header) = parse_dtb_header(&file_data[offset..]) {
+    if let Ok(dtb_header) = parse_dtb_header(file_data.get(offset..).unwrap_or_default()) {
         // Get all the DTB data
         if let Some(dtb_data) = file_data.get(offset..offset + dtb_header.total_size) {
             // DTB node entries start at the structure offset specified in the DTB header
@@ -288,6 +453,13 @@ pub fn extract_dtb(
                     } else if node.eof {
                         result.success = true;
                         result.size = Some(available_data);
+
+                        // Also carve out the DTB itself, so that it can be decompiled with standard tools
+                        if let Some(output_directory) = output_directory {
+                            let chroot = Chroot::new(output_directory);
+                            result.success =
+                                chroot.carve_file(DTB_FILE_NAME, file_data, offset, available_data);
+                        }
                         break;
                     // DTB property, extract it to disk
                     } else if node.property {
@@ -320,3 +492,67 @@ pub fn extract_dtb(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// DTB with a "model" property and a "chosen" node; see tests/inputs/gen_dtb.sh
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/dtb.bin");
+
+    #[test]
+    fn fixture_header() {
+        let header = parse_dtb_header(VALID).unwrap();
+
+        assert_eq!(header.total_size, VALID.len());
+        assert_eq!(header.version, 17);
+        assert_eq!(header.min_compatible_version, 16);
+        assert_eq!(header.mem_reservation_offset, 40);
+        assert_eq!(header.struct_offset, 56);
+        assert_eq!(header.struct_size, 88);
+
+        // The blob's size comes from the header, not the amount of available data
+        let mut file_data = b"JUNK".to_vec();
+        file_data.extend(VALID);
+        file_data.extend([0xFF; 64]);
+
+        let signature = dtb_parser(&file_data, 4).unwrap();
+        assert_eq!(signature.offset, 4);
+        assert_eq!(signature.size, VALID.len());
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = extract_dtb(&file_data, 4, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(VALID.len()));
+        assert_eq!(
+            std::fs::read(output_directory.path().join(DTB_FILE_NAME)).unwrap(),
+            VALID
+        );
+        assert_eq!(
+            std::fs::read(output_directory.path().join("model")).unwrap(),
+            b"binwalk-test\0"
+        );
+        assert_eq!(
+            std::fs::read(output_directory.path().join("chosen").join("bootargs")).unwrap(),
+            b"console=ttyS0\0"
+        );
+    }
+
+    #[test]
+    fn invalid_headers() {
+        // The strings block must lie within the reported total size
+        let mut bad_strings_size = VALID.to_vec();
+        bad_strings_size[32..36].copy_from_slice(&64u32.to_be_bytes());
+        assert!(parse_dtb_header(&bad_strings_size).is_err());
+
+        // As must the structure block
+        let mut bad_struct_offset = VALID.to_vec();
+        bad_struct_offset[8..12].copy_from_slice(&(VALID.len() as u32).to_be_bytes());
+        assert!(parse_dtb_header(&bad_struct_offset).is_err());
+
+        // Truncated DTBs are reported, but not extracted
+        let signature = dtb_parser(&VALID[..VALID.len() - 8], 0).unwrap();
+        assert!(signature.extraction_declined);
+        assert_eq!(signature.declared_size, Some(VALID.len()));
+    }
+}
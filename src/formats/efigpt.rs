@@ -1,60 +1,198 @@
-use crate::common::{crc32, is_offset_safe};
+use crate::common::{crc32, crc32_seed, get_utf16_string, is_offset_safe};
+use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
 use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
 use crate::structures::StructureError;
+use std::path::Path;
 use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
 
 /// Human readable description
 pub const DESCRIPTION: &str = "EFI Global Partition Table";
 
-/// EFI GPT always contains these bytes
+/// Offset of magic bytes from the start of the MBR, for disks with 512 byte sectors
+pub const MAGIC_OFFSET: usize = 0x01FE;
+
+/// Offset of magic bytes from the start of the MBR, for disks with 4096 byte sectors
+pub const MAGIC_OFFSET_4K: usize = 4096;
+
+/// With 512 byte sectors, the GPT header immediately follows the protective MBR's boot signature
 pub fn efigpt_magic() -> Vec<Vec<u8>> {
     vec![b"\x55\xAAEFI PART".to_vec()]
 }
 
-/// Validates the EFI GPT header
+/// With 4096 byte sectors, the GPT header does not immediately follow the MBR; match on the GPT signature and revision
+pub fn efigpt_4k_magic() -> Vec<Vec<u8>> {
+    vec![b"EFI PART\x00\x00\x01\x00".to_vec()]
+}
+
+/// Validates the EFI GPT header of disks with 512 byte sectors
 pub fn efigpt_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
-    // Offset of magic bytes from the start of the MBR
-    const MAGIC_OFFSET: usize = 0x01FE;
+    // MBR actually starts this may bytes before the magic bytes
+    let start_offset = offset.checked_sub(MAGIC_OFFSET).ok_or(SignatureError)?;
+    parse_efigpt_signature(file_data, start_offset)
+}
+
+/// Validates the EFI GPT header of disks with 4096 byte sectors
+pub fn efigpt_4k_parser(
+    file_data: &[u8],
+    offset: usize,
+) -> Result<SignatureResult, SignatureError> {
+    const MBR_SIGNATURE_OFFSET: usize = 0x01FE;
+
+    let start_offset = offset.checked_sub(MAGIC_OFFSET_4K).ok_or(SignatureError)?;
 
+    // Make sure there is a protective MBR
+    if file_data.get(start_offset + MBR_SIGNATURE_OFFSET..start_offset + MBR_SIGNATURE_OFFSET + 2)
+        != Some(b"\x55\xAA")
+    {
+        return Err(SignatureError);
+    }
+
+    parse_efigpt_signature(file_data, start_offset)
+}
+
+/// Validates the EFI GPT image starting at start_offset, which is the start of the protective MBR
+fn parse_efigpt_signature(
+    file_data: &[u8],
+    start_offset: usize,
+) -> Result<SignatureResult, SignatureError> {
     // Successful return value
     let mut result = SignatureResult {
+        offset: start_offset,
         description: DESCRIPTION.to_string(),
         confidence: CONFIDENCE_HIGH,
         ..Default::default()
     };
 
-    let available_data = file_data.len() - offset;
-
-    if offset >= MAGIC_OFFSET {
-        // MBR actually starts this may bytes before the magic bytes
-        result.offset = offset - MAGIC_OFFSET;
-
-        // Get the EFI data, including the MBR block
-        if let Some(efi_data) = file_data.get(result.offset..) {
-            // Parse the EFI data; this also validates CRC so if this succeeds, confidence is high
-            if let Ok(efi_header) = parse_efigpt_header(efi_data) {
-                // Some EFI images have been observed to define partitions that extend beyond EOF.
-                // If that is the case, assume the EFI image extends to EOF.
-                if efi_header.total_size > available_data {
-                    result.size = available_data;
-                } else {
-                    result.size = efi_header.total_size;
+    // Get the EFI data, including the MBR block
+    let efi_data = file_data.get(start_offset..).ok_or(SignatureError)?;
+
+    // Parse the EFI data; this also validates CRCs so if this succeeds, confidence is high
+    let efi_header = parse_efigpt_header(efi_data).map_err(|_| SignatureError)?;
+
+    // Some EFI images have been observed to define partitions that extend beyond EOF.
+    // If that is the case, assume the EFI image extends to EOF.
+    result.size = efi_header.total_size.min(efi_data.len());
+
+    let partitions: Vec<String> = efi_header
+        .partitions
+        .iter()
+        .map(|partition| {
+            format!(
+                "\"{}\" (LBA {}-{})",
+                partition.name, partition.start_lba, partition.end_lba
+            )
+        })
+        .collect();
+
+    result.description = format!(
+        "{}, sector size: {}, total size: {}, partitions: {}",
+        result.description,
+        efi_header.sector_size,
+        result.size,
+        partitions.join(", ")
+    );
+
+    Ok(result)
+}
+
+/// Default sector size
+const BLOCK_SIZE: usize = 512;
+
+/// Supported sector sizes
+const SECTOR_SIZES: [usize; 2] = [BLOCK_SIZE, 4096];
+
+/// Defines the internal extractor function for EFI GPT partitions
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::efigpt::efigpt_extractor;
+///
+/// match efigpt_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn efigpt_extractor() -> Extractor {
+    Extractor {
+        utility: ExtractorType::Internal(extract_efigpt_partitions),
+        ..Default::default()
+    }
+}
+
+/// Validate and carve each partition out of an EFI GPT image, so that recursive extraction can scan each partition independently
+pub fn extract_efigpt_partitions(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> ExtractionResult {
+    let mut result = ExtractionResult::default();
+
+    if let Some(efi_data) = file_data.get(offset..)
+        && let Ok(efi_header) = parse_efigpt_header(efi_data)
+    {
+        // Same as the signature parser, partitions extending beyond EOF are assumed to end at EOF
+        result.size = Some(efi_header.total_size.min(efi_data.len()));
+        result.success = true;
+
+        if let Some(output_directory) = output_directory {
+            let chroot = Chroot::new(output_directory);
+
+            for (partition_count, partition) in efi_header.partitions.iter().enumerate() {
+                // Partition offsets are relative to the GPT image, not the file data
+                let partition_start = offset + partition.start;
+                let partition_size = partition
+                    .size
+                    .min(file_data.len().saturating_sub(partition_start));
+
+                result.success = chroot.carve_file(
+                    format!("gpt_partition.{partition_count}"),
+                    file_data,
+                    partition_start,
+                    partition_size,
+                );
+
+                // If partition extraction failed, quit and report a failure
+                if !result.success {
+                    break;
                 }
-                result.description = format!("{}, total size: {}", result.description, result.size);
-                return Ok(result);
             }
         }
     }
 
-    Err(SignatureError)
+    result
 }
 
-const BLOCK_SIZE: usize = 512;
+/// Struct to store EFI GPT partition info; offsets are relative to the start of the GPT image
+#[derive(Debug, Default, Clone)]
+pub struct GPTPartition {
+    pub start: usize,
+    pub size: usize,
+    pub start_lba: u64,
+    /// The ending LBA is inclusive
+    pub end_lba: u64,
+    pub type_guid: String,
+    pub name: String,
+}
 
 /// Struct to store EFI GPT header info
 #[derive(Debug, Default, Clone)]
 pub struct EFIGPTHeader {
+    pub sector_size: usize,
     pub total_size: usize,
+    pub partitions: Vec<GPTPartition>,
 }
 
 // https://uefi.org/sites/default/files/resources/UEFI_Spec_2_10_Aug29.pdf, p.116
@@ -78,106 +216,292 @@ struct EFIGPTHeaderBytes {
     partition_entries_crc: zerocopy::U32<LE>,
 }
 
-/// Parses an EFI GPT header
+/// Parses an EFI GPT header; efi_data must start at the beginning of the protective MBR
 pub fn parse_efigpt_header(efi_data: &[u8]) -> Result<EFIGPTHeader, StructureError> {
     const EXPECTED_REVISION: u32 = 0x00010000;
+    const GPT_SIGNATURE: &[u8] = b"EFI PART";
+    const HEADER_CRC_START: usize = 16;
+    const HEADER_CRC_END: usize = 20;
 
-    let mut result = EFIGPTHeader::default();
-
-    // EFI GPT structure starts at the second block (first block is MBR)
-    if let Some(gpt_data) = efi_data.get(BLOCK_SIZE..) {
-        // Parse the EFI GPT structure
-        let (gpt_header, _) =
-            EFIGPTHeaderBytes::ref_from_prefix(gpt_data).map_err(|_| StructureError)?;
-
-        // Make sure the reserved field is NULL
-        if gpt_header.reserved == 0 {
-            // Make sure the revision field is the expected valid
-            if gpt_header.revision == EXPECTED_REVISION {
-                // Calculate the start and end offsets of the partition entries
-                let partition_entries_start =
-                    lba_to_offset(gpt_header.partition_entry_lba.get() as usize);
-                let partition_entries_end = partition_entries_start
-                    + (gpt_header.partition_entry_count.get()
-                        * gpt_header.partition_entry_size.get()) as usize;
-
-                // Get the partition entires
-                if let Some(partition_entries_data) =
-                    efi_data.get(partition_entries_start..partition_entries_end)
-                {
-                    // Validate the partition entries' CRC
-                    if gpt_header.partition_entries_crc == crc32(partition_entries_data) {
-                        let mut next_partition_offset = 0;
-                        let mut previous_partition_offset = None;
-                        let available_data = partition_entries_data.len();
-
-                        // Loop through all partition entries
-                        while is_offset_safe(
-                            available_data,
-                            next_partition_offset,
-                            previous_partition_offset,
-                        ) {
-                            if let Some(partition) = parse_gpt_partition_entry(
-                                &partition_entries_data[next_partition_offset..],
-                            ) {
-                                // EOF is the end of the farthest away partition
-                                let total_size = result.total_size;
-                                if (total_size < partition.end_offset)
-                                    && (partition.start_offset < partition.end_offset)
-                                {
-                                    result.total_size = partition.end_offset;
-                                }
-                            }
-
-                            previous_partition_offset = Some(next_partition_offset);
-                            next_partition_offset += gpt_header.partition_entry_size.get() as usize;
-                        }
-
-                        if result.total_size > 0 {
-                            return Ok(result);
-                        }
-                    }
-                }
-            }
+    // EFI GPT structure starts at the second sector (first sector is MBR)
+    let sector_size = SECTOR_SIZES
+        .into_iter()
+        .find(|sector_size| {
+            efi_data
+                .get(*sector_size..)
+                .is_some_and(|gpt_data| gpt_data.starts_with(GPT_SIGNATURE))
+        })
+        .ok_or(StructureError)?;
+
+    let mut result = EFIGPTHeader {
+        sector_size,
+        ..Default::default()
+    };
+
+    let gpt_data = &efi_data[sector_size..];
+
+    // Parse the EFI GPT structure
+    let (gpt_header, _) =
+        EFIGPTHeaderBytes::ref_from_prefix(gpt_data).map_err(|_| StructureError)?;
+
+    // Make sure the reserved field is NULL, and the revision field is the expected value
+    if gpt_header.reserved != 0 || gpt_header.revision != EXPECTED_REVISION {
+        return Err(StructureError);
+    }
+
+    // The header CRC is calculated over header_size bytes, with the CRC field NULL'd out
+    let header_size = gpt_header.header_size.get() as usize;
+    if header_size < std::mem::size_of::<EFIGPTHeaderBytes>() || header_size > sector_size {
+        return Err(StructureError);
+    }
+
+    let header_data = gpt_data.get(..header_size).ok_or(StructureError)?;
+    let header_crc = crc32_seed(
+        crc32_seed(
+            crc32(&header_data[..HEADER_CRC_START]),
+            &[0; HEADER_CRC_END - HEADER_CRC_START],
+        ),
+        &header_data[HEADER_CRC_END..],
+    );
+
+    if header_crc != gpt_header.header_crc.get() {
+        return Err(StructureError);
+    }
+
+    // Calculate the start and end offsets of the partition entries
+    let partition_entries_start =
+        lba_to_offset(gpt_header.partition_entry_lba.get(), sector_size).ok_or(StructureError)?;
+    let partition_entries_end = (gpt_header.partition_entry_count.get() as usize)
+        .checked_mul(gpt_header.partition_entry_size.get() as usize)
+        .and_then(|entries_size| partition_entries_start.checked_add(entries_size))
+        .ok_or(StructureError)?;
+
+    // Get the partition entires
+    let partition_entries_data = efi_data
+        .get(partition_entries_start..partition_entries_end)
+        .ok_or(StructureError)?;
+
+    // Validate the partition entries' CRC
+    if gpt_header.partition_entries_crc != crc32(partition_entries_data) {
+        return Err(StructureError);
+    }
+
+    let mut next_partition_offset = 0;
+    let mut previous_partition_offset = None;
+    let available_data = partition_entries_data.len();
+
+    // Loop through all partition entries
+    while is_offset_safe(
+        available_data,
+        next_partition_offset,
+        previous_partition_offset,
+    ) {
+        if let Some(partition) = parse_gpt_partition_entry(
+            &partition_entries_data[next_partition_offset..],
+            sector_size,
+        ) && partition.start < partition.end_offset()
+        {
+            // EOF is the end of the farthest away partition
+            result.total_size = result.total_size.max(partition.end_offset());
+            result.partitions.push(partition);
         }
+
+        previous_partition_offset = Some(next_partition_offset);
+        next_partition_offset += gpt_header.partition_entry_size.get() as usize;
+    }
+
+    if result.total_size > 0 {
+        return Ok(result);
     }
 
     Err(StructureError)
 }
 
-#[derive(Debug, Default, Clone)]
-struct GPTPartitionEntry {
-    pub end_offset: usize,
-    pub start_offset: usize,
+impl GPTPartition {
+    /// Offset of the end of the partition, relative to the start of the GPT image
+    fn end_offset(&self) -> usize {
+        self.start + self.size
+    }
 }
 
 #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
 #[repr(C, packed)]
 struct GPTEntry {
-    type_guid_p1: zerocopy::U64<LE>,
-    type_guid_p2: zerocopy::U64<LE>,
-    partition_guid_p1: zerocopy::U64<LE>,
-    partition_guid_p2: zerocopy::U64<LE>,
+    type_guid: [u8; 16],
+    partition_guid: [u8; 16],
     starting_lba: zerocopy::U64<LE>,
     ending_lba: zerocopy::U64<LE>,
     attributes: zerocopy::U64<LE>,
+    name: [u8; 72],
 }
 
-fn parse_gpt_partition_entry(entry_data: &[u8]) -> Option<GPTPartitionEntry> {
+fn parse_gpt_partition_entry(entry_data: &[u8], sector_size: usize) -> Option<GPTPartition> {
     let (entry_header, _) = GPTEntry::ref_from_prefix(entry_data).ok()?;
 
     // GUID types of NULL can be ignored
-    if entry_header.type_guid_p1 == 0 || entry_header.type_guid_p2 == 0 {
+    if entry_header.type_guid[..8] == [0; 8] || entry_header.type_guid[8..] == [0; 8] {
         return None;
     }
 
-    Some(GPTPartitionEntry {
-        start_offset: lba_to_offset(entry_header.starting_lba.get() as usize),
-        end_offset: lba_to_offset(entry_header.ending_lba.get() as usize),
+    let start_lba = entry_header.starting_lba.get();
+    let end_lba = entry_header.ending_lba.get();
+
+    // The ending LBA is inclusive
+    let start = lba_to_offset(start_lba, sector_size)?;
+    let end_offset = lba_to_offset(end_lba.checked_add(1)?, sector_size)?;
+
+    Some(GPTPartition {
+        start,
+        size: end_offset.checked_sub(start)?,
+        start_lba,
+        end_lba,
+        type_guid: format_guid(&entry_header.type_guid),
+        name: get_utf16_string(&entry_header.name, true),
     })
 }
 
-// Convert LBA to offset
-const fn lba_to_offset(lba: usize) -> usize {
-    lba * BLOCK_SIZE
+/// Formats a GUID as a string; the first three fields are little endian
+pub(crate) fn format_guid(guid: &[u8; 16]) -> String {
+    format!(
+        "{:08X}-{:04X}-{:04X}-{}-{}",
+        u32::from_le_bytes(guid[0..4].try_into().unwrap()),
+        u16::from_le_bytes(guid[4..6].try_into().unwrap()),
+        u16::from_le_bytes(guid[6..8].try_into().unwrap()),
+        hex::encode_upper(&guid[8..10]),
+        hex::encode_upper(&guid[10..16]),
+    )
+}
+
+// Convert a 64-bit LBA to an offset; returns None if the offset can't be represented
+fn lba_to_offset(lba: u64, sector_size: usize) -> Option<usize> {
+    usize::try_from(lba).ok()?.checked_mul(sector_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An eight sector GPT disk image with 512 byte sectors, and two partitions: "part0" (LBAs 4-5, filled with
+    /// 0xAA) and "part1" (LBAs 6-7, filled with 0xBB); see tests/inputs/gen_efigpt.sh
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/efigpt.bin");
+
+    /// The same disk image, with 4096 byte sectors
+    static VALID_4K: &[u8] = include_bytes!("../../tests/inputs/efigpt_4k.bin");
+
+    const PARTITION_ENTRY_SIZE: usize = 128;
+    const PARTITION_ENTRIES_SIZE: usize = PARTITION_ENTRY_SIZE * 4;
+
+    /// Sets the inclusive start and end LBAs of the specified partition entry in a 512 byte sector image, then
+    /// updates the partition entries and header CRCs; unused entries are given the type GUID of the first partition
+    fn set_partition(image: &mut [u8], index: usize, start_lba: u64, end_lba: u64) {
+        const HEADER_SIZE: usize = 92;
+
+        let entries_offset = BLOCK_SIZE * 2;
+        let entry_offset = entries_offset + index * PARTITION_ENTRY_SIZE;
+
+        image.copy_within(entries_offset..entries_offset + 16, entry_offset);
+        image[entry_offset + 32..entry_offset + 40].copy_from_slice(&start_lba.to_le_bytes());
+        image[entry_offset + 40..entry_offset + 48].copy_from_slice(&end_lba.to_le_bytes());
+
+        let entries_crc = crc32(&image[entries_offset..entries_offset + PARTITION_ENTRIES_SIZE]);
+        image[BLOCK_SIZE + 88..BLOCK_SIZE + 92].copy_from_slice(&entries_crc.to_le_bytes());
+
+        image[BLOCK_SIZE + 16..BLOCK_SIZE + 20].fill(0);
+        let header_crc = crc32(&image[BLOCK_SIZE..BLOCK_SIZE + HEADER_SIZE]);
+        image[BLOCK_SIZE + 16..BLOCK_SIZE + 20].copy_from_slice(&header_crc.to_le_bytes());
+    }
+
+    #[test]
+    fn carves_partitions_at_absolute_offsets() {
+        // Embed the disk image in some leading data
+        let mut file_data = vec![0; 0x100];
+        file_data.extend(VALID);
+
+        let signature = efigpt_parser(&file_data, 0x100 + MAGIC_OFFSET).unwrap();
+        assert_eq!(signature.offset, 0x100);
+        assert_eq!(signature.size, VALID.len());
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let extraction =
+            extract_efigpt_partitions(&file_data, signature.offset, Some(output_dir.path()));
+        assert!(extraction.success);
+        assert_eq!(extraction.size, Some(VALID.len()));
+
+        let partition = std::fs::read(output_dir.path().join("gpt_partition.0")).unwrap();
+        assert_eq!(partition, vec![0xAA; BLOCK_SIZE * 2]);
+        let partition = std::fs::read(output_dir.path().join("gpt_partition.1")).unwrap();
+        assert_eq!(partition, vec![0xBB; BLOCK_SIZE * 2]);
+    }
+
+    #[test]
+    fn lbas_above_u32_max_are_not_truncated() {
+        const HIGH_LBA: u64 = 0x1_0000_0000;
+
+        // The last partition's end offset can't be represented, and is ignored
+        let mut image = VALID.to_vec();
+        set_partition(&mut image, 1, HIGH_LBA, HIGH_LBA + 7);
+        set_partition(&mut image, 2, 8, u64::MAX);
+
+        let header = parse_efigpt_header(&image).unwrap();
+        assert_eq!(header.partitions.len(), 2);
+        assert_eq!(header.partitions[1].start, HIGH_LBA as usize * BLOCK_SIZE);
+        assert_eq!(header.partitions[1].size, BLOCK_SIZE * 8);
+        assert_eq!(header.total_size, (HIGH_LBA as usize + 8) * BLOCK_SIZE);
+
+        // The partition beyond EOF is assumed to end at EOF
+        let signature = efigpt_parser(&image, MAGIC_OFFSET).unwrap();
+        assert_eq!(signature.size, image.len());
+    }
+
+    #[test]
+    fn scan_finds_unaligned_images() {
+        let binwalker =
+            crate::Binwalk::configure(None, None, vec!["efigpt".to_string()], vec![], None, false)
+                .unwrap();
+
+        // Disk images embedded in firmware need not start on a sector boundary
+        for leading_bytes in [BLOCK_SIZE, 0x100] {
+            let mut file_data = vec![0; leading_bytes];
+            file_data.extend(VALID);
+
+            let results = binwalker.scan(&file_data);
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].offset, leading_bytes);
+        }
+    }
+
+    #[test]
+    fn enumerates_partitions() {
+        for (image, sector_size) in [(VALID, BLOCK_SIZE), (VALID_4K, 4096)] {
+            let header = parse_efigpt_header(image).unwrap();
+            assert_eq!(header.sector_size, sector_size);
+            assert_eq!(header.total_size, sector_size * 8);
+            assert_eq!(header.partitions.len(), 2);
+
+            for (i, partition) in header.partitions.iter().enumerate() {
+                assert_eq!(partition.name, format!("part{i}"));
+                assert_eq!(partition.type_guid, "0FC63DAF-8483-4772-8E79-3D69D8477DE4");
+                assert_eq!(partition.start_lba, 4 + 2 * i as u64);
+                assert_eq!(partition.end_lba, 5 + 2 * i as u64);
+                assert_eq!(partition.start, sector_size * (4 + 2 * i));
+                assert_eq!(partition.size, sector_size * 2);
+            }
+        }
+
+        // 4096 byte sector images are found by their own signature
+        let signature = efigpt_4k_parser(VALID_4K, MAGIC_OFFSET_4K).unwrap();
+        assert_eq!(signature.offset, 0);
+        assert_eq!(signature.size, VALID_4K.len());
+        assert!(signature.description.contains("\"part1\" (LBA 6-7)"));
+    }
+
+    #[test]
+    fn rejects_corrupt_header() {
+        assert!(parse_efigpt_header(VALID).is_ok());
+
+        // Corrupt the disk GUID, which is covered by the header CRC
+        let mut image = VALID.to_vec();
+        image[BLOCK_SIZE + 56] ^= 1;
+        assert!(parse_efigpt_header(&image).is_err());
+    }
 }
@@ -32,6 +32,21 @@ pub fn elf_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
             elf_header.osabi,
             elf_header.endianness
         );
+
+        // If the section/program header tables can be parsed, report the real size of the ELF
+        if let Ok(elf_size) = get_elf_size(&file_data[offset..]) {
+            let available_size = file_data.len() - offset;
+
+            if elf_size <= available_size {
+                result.size = elf_size;
+                result.description =
+                    format!("{}, total size: {} bytes", result.description, elf_size);
+            } else {
+                result.extraction_declined = true;
+                result.set_truncated(elf_size, available_size);
+            }
+        }
+
         return Ok(result);
     }
 
@@ -125,6 +140,260 @@ pub fn parse_elf_header(elf_data: &[u8]) -> Result<ELFHeader, StructureError> {
     Err(StructureError)
 }
 
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct Elf32TableInfo {
+    entry: dyn_endian::U32,
+    phoff: dyn_endian::U32,
+    shoff: dyn_endian::U32,
+    flags: dyn_endian::U32,
+    ehsize: dyn_endian::U16,
+    phentsize: dyn_endian::U16,
+    phnum: dyn_endian::U16,
+    shentsize: dyn_endian::U16,
+    shnum: dyn_endian::U16,
+    shstrndx: dyn_endian::U16,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct Elf64TableInfo {
+    entry: dyn_endian::U64,
+    phoff: dyn_endian::U64,
+    shoff: dyn_endian::U64,
+    flags: dyn_endian::U32,
+    ehsize: dyn_endian::U16,
+    phentsize: dyn_endian::U16,
+    phnum: dyn_endian::U16,
+    shentsize: dyn_endian::U16,
+    shnum: dyn_endian::U16,
+    shstrndx: dyn_endian::U16,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct Elf32SectionHeader {
+    name: dyn_endian::U32,
+    section_type: dyn_endian::U32,
+    flags: dyn_endian::U32,
+    addr: dyn_endian::U32,
+    offset: dyn_endian::U32,
+    size: dyn_endian::U32,
+    link: dyn_endian::U32,
+    info: dyn_endian::U32,
+    addralign: dyn_endian::U32,
+    entsize: dyn_endian::U32,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct Elf64SectionHeader {
+    name: dyn_endian::U32,
+    section_type: dyn_endian::U32,
+    flags: dyn_endian::U64,
+    addr: dyn_endian::U64,
+    offset: dyn_endian::U64,
+    size: dyn_endian::U64,
+    link: dyn_endian::U32,
+    info: dyn_endian::U32,
+    addralign: dyn_endian::U64,
+    entsize: dyn_endian::U64,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct Elf32ProgramHeader {
+    segment_type: dyn_endian::U32,
+    offset: dyn_endian::U32,
+    vaddr: dyn_endian::U32,
+    paddr: dyn_endian::U32,
+    filesz: dyn_endian::U32,
+    memsz: dyn_endian::U32,
+    flags: dyn_endian::U32,
+    align: dyn_endian::U32,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct Elf64ProgramHeader {
+    segment_type: dyn_endian::U32,
+    flags: dyn_endian::U32,
+    offset: dyn_endian::U64,
+    vaddr: dyn_endian::U64,
+    paddr: dyn_endian::U64,
+    filesz: dyn_endian::U64,
+    memsz: dyn_endian::U64,
+    align: dyn_endian::U64,
+}
+
+/// Offset, entry count and entry size of an ELF header table
+#[derive(Debug, Default, Clone, Copy)]
+struct ElfTable {
+    offset: usize,
+    count: usize,
+    entry_size: usize,
+}
+
+impl ElfTable {
+    /// Returns true if the ELF has this table
+    fn is_present(&self) -> bool {
+        self.offset != 0 && self.count > 0
+    }
+
+    /// Returns the offset of the end of the table; the table must contain entries of the expected size
+    fn end(&self, expected_entry_size: usize) -> Result<usize, StructureError> {
+        if !self.is_present() {
+            return Ok(0);
+        }
+
+        if self.entry_size != expected_entry_size {
+            return Err(StructureError);
+        }
+
+        self.count
+            .checked_mul(self.entry_size)
+            .and_then(|table_size| self.offset.checked_add(table_size))
+            .ok_or(StructureError)
+    }
+
+    /// Returns the raw data of each table entry
+    fn entries<'a>(&self, elf_data: &'a [u8]) -> Result<Vec<&'a [u8]>, StructureError> {
+        (0..self.count)
+            .map(|i| {
+                let entry_start = self.offset + (i * self.entry_size);
+                elf_data
+                    .get(entry_start..entry_start + self.entry_size)
+                    .ok_or(StructureError)
+            })
+            .collect()
+    }
+}
+
+/// Returns the total size of an ELF file.
+///
+/// This is the end of the farthest section (or segment, if there is no section header table), the end of the header
+/// tables themselves, or the end of the ELF header, whichever is greatest.
+pub fn get_elf_size(elf_data: &[u8]) -> Result<usize, StructureError> {
+    const TABLE_INFO_OFFSET: usize = 24;
+
+    // Sections of this type occupy no space in the file
+    const SHT_NOBITS: u32 = 8;
+
+    let elf_header = parse_elf_header(elf_data)?;
+    let endianness = elf_header.endianness;
+    let is_64_bit = elf_header.class == "64";
+
+    let table_info_data = elf_data.get(TABLE_INFO_OFFSET..).ok_or(StructureError)?;
+
+    let (header_size, program_headers, section_headers) = if is_64_bit {
+        let (info, _) =
+            Elf64TableInfo::ref_from_prefix(table_info_data).map_err(|_| StructureError)?;
+        (
+            info.ehsize.get(endianness) as usize,
+            ElfTable {
+                offset: info.phoff.get(endianness) as usize,
+                count: info.phnum.get(endianness) as usize,
+                entry_size: info.phentsize.get(endianness) as usize,
+            },
+            ElfTable {
+                offset: info.shoff.get(endianness) as usize,
+                count: info.shnum.get(endianness) as usize,
+                entry_size: info.shentsize.get(endianness) as usize,
+            },
+        )
+    } else {
+        let (info, _) =
+            Elf32TableInfo::ref_from_prefix(table_info_data).map_err(|_| StructureError)?;
+        (
+            info.ehsize.get(endianness) as usize,
+            ElfTable {
+                offset: info.phoff.get(endianness) as usize,
+                count: info.phnum.get(endianness) as usize,
+                entry_size: info.phentsize.get(endianness) as usize,
+            },
+            ElfTable {
+                offset: info.shoff.get(endianness) as usize,
+                count: info.shnum.get(endianness) as usize,
+                entry_size: info.shentsize.get(endianness) as usize,
+            },
+        )
+    };
+
+    let (program_header_size, section_header_size) = if is_64_bit {
+        (
+            std::mem::size_of::<Elf64ProgramHeader>(),
+            std::mem::size_of::<Elf64SectionHeader>(),
+        )
+    } else {
+        (
+            std::mem::size_of::<Elf32ProgramHeader>(),
+            std::mem::size_of::<Elf32SectionHeader>(),
+        )
+    };
+
+    let mut elf_size = header_size
+        .max(program_headers.end(program_header_size)?)
+        .max(section_headers.end(section_header_size)?);
+
+    if section_headers.is_present() {
+        // The end of the farthest section that has data in the file
+        for entry in section_headers.entries(elf_data)? {
+            let (section_type, section_offset, section_size) = if is_64_bit {
+                let section =
+                    Elf64SectionHeader::ref_from_bytes(entry).map_err(|_| StructureError)?;
+                (
+                    section.section_type.get(endianness),
+                    section.offset.get(endianness) as usize,
+                    section.size.get(endianness) as usize,
+                )
+            } else {
+                let section =
+                    Elf32SectionHeader::ref_from_bytes(entry).map_err(|_| StructureError)?;
+                (
+                    section.section_type.get(endianness),
+                    section.offset.get(endianness) as usize,
+                    section.size.get(endianness) as usize,
+                )
+            };
+
+            if section_type != SHT_NOBITS {
+                elf_size = elf_size.max(
+                    section_offset
+                        .checked_add(section_size)
+                        .ok_or(StructureError)?,
+                );
+            }
+        }
+    } else {
+        // No section header table (e.g., a stripped firmware image), fall back to the end of the farthest segment
+        for entry in program_headers.entries(elf_data)? {
+            let (segment_offset, segment_size) = if is_64_bit {
+                let segment =
+                    Elf64ProgramHeader::ref_from_bytes(entry).map_err(|_| StructureError)?;
+                (
+                    segment.offset.get(endianness) as usize,
+                    segment.filesz.get(endianness) as usize,
+                )
+            } else {
+                let segment =
+                    Elf32ProgramHeader::ref_from_bytes(entry).map_err(|_| StructureError)?;
+                (
+                    segment.offset.get(endianness) as usize,
+                    segment.filesz.get(endianness) as usize,
+                )
+            };
+
+            elf_size = elf_size.max(
+                segment_offset
+                    .checked_add(segment_size)
+                    .ok_or(StructureError)?,
+            );
+        }
+    }
+
+    Ok(elf_size)
+}
+
 const fn parse_elf_machine(elf_machine: u16) -> &'static str {
     match elf_machine {
         0 => "no machine",
@@ -368,3 +637,46 @@ const fn parse_elf_type(elf_type: u16) -> Option<&'static str> {
         _ => return None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 64-bit little endian ELF executable, embedded 0x20 bytes into a blob of padding; see tests/inputs/gen_elf.sh
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/elf.bin");
+
+    const ELF_OFFSET: usize = 0x20;
+    const ELF_SIZE: usize = 0x240;
+    const SECTION_HEADERS_OFFSET: usize = 0x140;
+
+    /// Returns a copy of the ELF in the fixture, without the surrounding padding
+    fn fixture_elf() -> Vec<u8> {
+        VALID[ELF_OFFSET..ELF_OFFSET + ELF_SIZE].to_vec()
+    }
+
+    #[test]
+    fn elf64_le_size_from_sections() {
+        let elf = fixture_elf();
+        assert_eq!(get_elf_size(&elf).unwrap(), ELF_SIZE);
+
+        // An ELF embedded in a larger blob is sized by its headers, not the surrounding data
+        let signature = elf_parser(VALID, ELF_OFFSET).unwrap();
+        assert_eq!(signature.offset, ELF_OFFSET);
+        assert_eq!(signature.size, ELF_SIZE);
+
+        // Truncated ELFs are reported as such
+        let signature = elf_parser(&elf[..ELF_SIZE - 1], 0).unwrap();
+        assert_eq!(signature.declared_size, Some(ELF_SIZE));
+    }
+
+    #[test]
+    fn elf64_le_size_from_segments() {
+        // Without a section header table, the program headers determine the size
+        let mut elf = fixture_elf();
+        elf[0x28..0x30].fill(0);
+        elf.truncate(SECTION_HEADERS_OFFSET);
+
+        assert_eq!(get_elf_size(&elf).unwrap(), 0x120);
+        assert_eq!(elf_parser(&elf, 0).unwrap().size, 0x120);
+    }
+}
@@ -75,6 +75,8 @@ pub fn encfw_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult,
 ///
 /// match encfw_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -103,7 +105,7 @@ pub fn encfw_decrypt(
     const OUTPUT_FILE_NAME: &str = "decrypted.bin";
 
     let mut result = ExtractionResult::default();
-    if let Ok(decrypted_data) = delink::decrypt(&file_data[offset..]) {
+    if let Ok(decrypted_data) = delink::decrypt(file_data.get(offset..).unwrap_or_default()) {
         result.success = true;
 
         // Write to file, if requested
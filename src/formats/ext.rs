@@ -1,10 +1,14 @@
 use crate::signatures::{CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
 use crate::structures::StructureError;
+use crate::structures::fixed_string::FixedString;
 use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
 
 /// Human readable description
 pub const DESCRIPTION: &str = "EXT filesystem";
 
+/// Offset inside the EXT image where the magic bytes reside (the superblock starts at 1024)
+pub const MAGIC_OFFSET: usize = 1080;
+
 /// EXT magic bytes
 pub fn ext_magic() -> Vec<Vec<u8>> {
     /*
@@ -25,12 +29,13 @@ pub fn ext_magic() -> Vec<Vec<u8>> {
 
 /// Parse the EXT signature
 pub fn ext_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
-    // Offset inside the EXT image where the magic bytes reside
-    const MAGIC_OFFSET: usize = 1080;
+    let Some(start_offset) = offset.checked_sub(MAGIC_OFFSET) else {
+        return Err(SignatureError);
+    };
 
     let mut result = SignatureResult {
         description: DESCRIPTION.to_string(),
-        offset: offset - MAGIC_OFFSET,
+        offset: start_offset,
         size: 0,
         confidence: CONFIDENCE_MEDIUM,
         ..Default::default()
@@ -41,14 +46,16 @@ pub fn ext_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
     {
         result.size = ext_header.image_size;
         result.description = format!(
-            "{} for {}, inodes: {}, block size: {}, block count: {}, free blocks: {}, reserved blocks: {}, total size: {} bytes",
+            "{}, {} for {}, volume name: \"{}\", inodes: {}, block size: {}, block count: {}, free blocks: {}, reserved blocks: {}, total size: {} bytes",
             result.description,
+            ext_header.version,
             ext_header.os,
+            ext_header.volume_name,
             ext_header.inodes_count,
             ext_header.block_size,
+            ext_header.blocks_count,
             ext_header.free_blocks_count,
             ext_header.reserved_blocks_count,
-            ext_header.blocks_count,
             result.size
         );
         return Ok(result);
@@ -67,6 +74,10 @@ pub const SUPERBLOCK_OFFSET: usize = 1024;
 #[derive(Debug, Default, Clone)]
 pub struct EXTHeader {
     pub os: String,
+    /// "ext2", "ext3" or "ext4", as determined by the feature flags
+    pub version: String,
+    pub volume_name: String,
+    pub feature_incompat: u32,
     pub block_size: usize,
     pub image_size: usize,
     pub blocks_count: usize,
@@ -104,8 +115,19 @@ struct EXTSuprtBlockBytes {
     s_rev_level: zerocopy::U32<LE>,
     resuid: zerocopy::U16<LE>,
     resgid: zerocopy::U16<LE>,
+    first_inode: zerocopy::U32<LE>,
+    inode_size: zerocopy::U16<LE>,
+    block_group_number: zerocopy::U16<LE>,
+    feature_compat: zerocopy::U32<LE>,
+    feature_incompat: zerocopy::U32<LE>,
+    feature_ro_compat: zerocopy::U32<LE>,
+    uuid: [u8; 16],
+    volume_name: FixedString<16>,
 }
 
+/// Offset of the high 32 bits of the block count, relative to the start of the superblock
+const BLOCKS_COUNT_HI_OFFSET: usize = 0x150;
+
 /// Partially parses an EXT superblock structure
 pub fn parse_ext_header(ext_data: &[u8]) -> Result<EXTHeader, StructureError> {
     // Max value of the EXT log block size
@@ -114,12 +136,24 @@ pub fn parse_ext_header(ext_data: &[u8]) -> Result<EXTHeader, StructureError> {
     const ALLOWED_REV_LEVELS: [u32; 2] = [0, 1];
     const ALLOWED_FIRST_DATA_BLOCKS: [u32; 2] = [0, 1];
 
+    const EXT_MAGIC: u16 = 0xEF53;
+
+    // Feature flags used to distinguish between ext2, ext3 and ext4
+    const COMPAT_HAS_JOURNAL: u32 = 0x4;
+    const INCOMPAT_EXTENTS: u32 = 0x40;
+    const INCOMPAT_64BIT: u32 = 0x80;
+    const INCOMPAT_FLEX_BG: u32 = 0x200;
+
     // Sanity check the available data
     if ext_data.len() >= (SUPERBLOCK_OFFSET + SUPERBLOCK_SIZE) {
         // Parse the EXT superblock structure
+        let superblock_data = &ext_data[SUPERBLOCK_OFFSET..];
         let (ext_superblock, _) =
-            EXTSuprtBlockBytes::ref_from_prefix(&ext_data[SUPERBLOCK_OFFSET..])
-                .map_err(|_| StructureError)?;
+            EXTSuprtBlockBytes::ref_from_prefix(superblock_data).map_err(|_| StructureError)?;
+
+        if ext_superblock.magic.get() != EXT_MAGIC {
+            return Err(StructureError);
+        }
 
         // Sanity check the reported OS this EXT image was created on
         let creator_os = match ext_superblock.creator_os.get() {
@@ -137,11 +171,38 @@ pub fn parse_ext_header(ext_data: &[u8]) -> Result<EXTHeader, StructureError> {
                 // Santiy check the log_block_size
                 if ext_superblock.log_block_size.get() <= MAX_BLOCK_LOG {
                     let block_size = 1024 << ext_superblock.log_block_size.get();
+                    let feature_incompat = ext_superblock.feature_incompat.get();
+
+                    // 64-bit file systems store the high 32 bits of the block count elsewhere in the superblock
+                    let mut blocks_count = ext_superblock.blocks_count.get() as usize;
+                    if feature_incompat & INCOMPAT_64BIT != 0 {
+                        let blocks_count_hi = superblock_data
+                            .get(BLOCKS_COUNT_HI_OFFSET..BLOCKS_COUNT_HI_OFFSET + 4)
+                            .ok_or(StructureError)?;
+                        blocks_count |= (u32::from_le_bytes(blocks_count_hi.try_into().unwrap())
+                            as usize)
+                            << 32;
+                    }
+
+                    let version = if feature_incompat
+                        & (INCOMPAT_EXTENTS | INCOMPAT_64BIT | INCOMPAT_FLEX_BG)
+                        != 0
+                    {
+                        "ext4"
+                    } else if ext_superblock.feature_compat.get() & COMPAT_HAS_JOURNAL != 0 {
+                        "ext3"
+                    } else {
+                        "ext2"
+                    };
+
                     return Ok(EXTHeader {
                         os: creator_os.to_string(),
+                        version: version.to_string(),
+                        volume_name: ext_superblock.volume_name.get(),
+                        feature_incompat,
                         block_size,
-                        image_size: block_size * (ext_superblock.blocks_count.get() as usize),
-                        blocks_count: ext_superblock.blocks_count.get() as usize,
+                        image_size: block_size * blocks_count,
+                        blocks_count,
                         inodes_count: ext_superblock.inodes_count.get() as usize,
                         free_blocks_count: ext_superblock.free_blocks_count.get() as usize,
                         reserved_blocks_count: ext_superblock.reserved_blocks_count.get() as usize,
@@ -153,3 +214,61 @@ pub fn parse_ext_header(ext_data: &[u8]) -> Result<EXTHeader, StructureError> {
 
     Err(StructureError)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An ext2 file system with 64 blocks of 1KiB (see tests/inputs/gen_ext.sh)
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/ext.bin");
+
+    /// File offsets of the compatible and incompatible feature flags
+    const FEATURE_COMPAT_OFFSET: usize = SUPERBLOCK_OFFSET + 92;
+    const FEATURE_INCOMPAT_OFFSET: usize = SUPERBLOCK_OFFSET + 96;
+
+    /// Returns a copy of the fixture with the specified feature flags set
+    fn with_features(feature_compat: u32, feature_incompat: u32) -> Vec<u8> {
+        let mut image = VALID.to_vec();
+        for (offset, features) in [
+            (FEATURE_COMPAT_OFFSET, feature_compat),
+            (FEATURE_INCOMPAT_OFFSET, feature_incompat),
+        ] {
+            let flags = u32::from_le_bytes(image[offset..offset + 4].try_into().unwrap());
+            image[offset..offset + 4].copy_from_slice(&(flags | features).to_le_bytes());
+        }
+        image
+    }
+
+    #[test]
+    fn superblock() {
+        let header = parse_ext_header(VALID).unwrap();
+        assert_eq!(header.version, "ext2");
+        assert_eq!(header.os, "Linux");
+        assert_eq!(header.volume_name, "rootfs");
+        assert_eq!(header.block_size, 1024);
+        assert_eq!(header.blocks_count, 64);
+        assert_eq!(header.image_size, VALID.len());
+
+        // The signature matches on the superblock magic, but the reported offset is the start of the file system
+        let mut file_data = vec![0xFF; 512];
+        file_data.extend(VALID);
+        let signature = ext_parser(&file_data, 512 + MAGIC_OFFSET).unwrap();
+        assert_eq!(signature.offset, 512);
+        assert_eq!(signature.size, VALID.len());
+
+        // ext3 has a journal, ext4 uses extents
+        assert_eq!(
+            parse_ext_header(&with_features(0x4, 0)).unwrap().version,
+            "ext3"
+        );
+        assert_eq!(
+            parse_ext_header(&with_features(0x4, 0x40)).unwrap().version,
+            "ext4"
+        );
+
+        // Bad magic
+        let mut bad_magic = VALID.to_vec();
+        bad_magic[MAGIC_OFFSET] = 0;
+        assert!(parse_ext_header(&bad_magic).is_err());
+    }
+}
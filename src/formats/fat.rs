@@ -1,5 +1,11 @@
+use crate::common::get_utf16_string;
+use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
 use crate::signatures::{CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
 use crate::structures::StructureError;
+use log::warn;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
 use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
 
 /// Human readable description
@@ -8,6 +14,12 @@ pub const DESCRIPTION: &str = "FAT file system";
 /// Offset of magic bytes from the start of the FAT
 pub const MAGIC_OFFSET: usize = 0x01FE;
 
+/// Size of a FAT directory entry
+const DIRECTORY_ENTRY_SIZE: usize = 32;
+
+/// First valid data cluster number
+const FIRST_DATA_CLUSTER: usize = 2;
+
 /// FAT always contains these bytes
 pub fn fat_magic() -> Vec<Vec<u8>> {
     vec![b"\x55\xAA".to_vec()]
@@ -34,16 +46,13 @@ pub fn fat_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
             // Report the total size of the FAT image
             result.size = fat_header.total_size;
 
-            // Include FAT type in the description
-            let fat_type_desc = if fat_header.is_fat32 {
-                "FAT32"
-            } else {
-                "FAT12/16"
-            };
-
             result.description = format!(
-                "{}, type: {}, total size: {} bytes",
-                result.description, fat_type_desc, result.size
+                "{}, type: {}, cluster size: {} bytes, cluster count: {}, total size: {} bytes",
+                result.description,
+                fat_header.fat_type,
+                fat_header.cluster_size,
+                fat_header.cluster_count,
+                result.size
             );
             return Ok(result);
         }
@@ -52,11 +61,47 @@ pub fn fat_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
     Err(SignatureError)
 }
 
+/// FAT type, as determined by the number of data clusters
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum FATType {
+    #[default]
+    Fat12,
+    Fat16,
+    Fat32,
+}
+
+impl std::fmt::Display for FATType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FATType::Fat12 => write!(f, "FAT12"),
+            FATType::Fat16 => write!(f, "FAT16"),
+            FATType::Fat32 => write!(f, "FAT32"),
+        }
+    }
+}
+
 /// Struct to store FAT header info
 #[derive(Debug, Default, Clone)]
 pub struct FATHeader {
-    pub is_fat32: bool,
+    pub fat_type: FATType,
     pub total_size: usize,
+    pub bytes_per_sector: usize,
+    pub cluster_size: usize,
+    pub fat_count: usize,
+    /// Number of entries in the FAT12/16 root directory; 0 for FAT32
+    pub root_entry_count: usize,
+    /// Number of data clusters
+    pub cluster_count: usize,
+    /// First cluster of the FAT32 root directory; 0 for FAT12/16
+    pub root_cluster: usize,
+    /// Offset of the first FAT, relative to the start of the file system
+    pub fat_offset: usize,
+    /// Size of each FAT, in bytes
+    pub fat_size: usize,
+    /// Offset of the FAT12/16 root directory, relative to the start of the file system
+    pub root_directory_offset: usize,
+    /// Offset of the first data cluster, relative to the start of the file system
+    pub data_offset: usize,
 }
 
 // http://elm-chan.org/docs/fat_e.html
@@ -81,54 +126,663 @@ struct FATBootSectorBytes {
     total_sectors_32: zerocopy::U32<LE>,
 }
 
+// FAT32 extended boot sector fields, which immediately follow FATBootSectorBytes
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct FAT32BootSectorBytes {
+    fat_size_32: zerocopy::U32<LE>,
+    ext_flags: zerocopy::U16<LE>,
+    fs_version: zerocopy::U16<LE>,
+    root_cluster: zerocopy::U32<LE>,
+}
+
 /// Parses a FAT header
 pub fn parse_fat_header(fat_data: &[u8]) -> Result<FATHeader, StructureError> {
     // Number of FATs could technically be 1 or greater, but *should* be 2
     const EXPECTED_FAT_COUNT: u8 = 2;
+    // Boot sector signature
+    const BOOT_SIGNATURE: &[u8] = b"\x55\xAA";
+    // FAT12 and FAT16 have fewer than this many clusters, respectively
+    const MAX_FAT12_CLUSTERS: usize = 4085;
+    const MAX_FAT16_CLUSTERS: usize = 65525;
 
     let valid_opcode1 = [0xEB, 0xE9];
     let valid_sector_sizes = [512, 1024, 2048, 4096];
     let valid_sectors_per_cluster = [1, 2, 4, 8, 16, 32, 64, 128];
     let valid_media_types = [0xF0, 0xF8, 0xF9, 0xFA, 0xFB, 0xFC, 0xFD, 0xFE];
 
-    // Return value
-    let mut result = FATHeader::default();
-
     // Parse the boot sector header
-
-    let (bs_header, _) =
+    let (bs_header, extended_data) =
         FATBootSectorBytes::ref_from_prefix(fat_data).map_err(|_| StructureError)?;
-    // Sanity check the first opcode, reported sector size, reported sectors per cluster
-    if valid_opcode1.contains(&bs_header.opcode1)
-        && valid_sector_sizes.contains(&bs_header.bytes_per_sector.get())
-        && valid_sectors_per_cluster.contains(&bs_header.sectors_per_cluster)
+
+    // Sanity check the boot sector signature, first opcode, reported sector size, reported sectors per cluster
+    if fat_data.get(MAGIC_OFFSET..MAGIC_OFFSET + BOOT_SIGNATURE.len()) != Some(BOOT_SIGNATURE)
+        || !valid_opcode1.contains(&bs_header.opcode1)
+        || !valid_sector_sizes.contains(&bs_header.bytes_per_sector.get())
+        || !valid_sectors_per_cluster.contains(&bs_header.sectors_per_cluster)
+    {
+        return Err(StructureError);
+    }
+
+    // Reserved sectors must be at least 1; sanity check the reported number of FATs, reported media type
+    if bs_header.reserved_sectors == 0
+        || bs_header.fat_count != EXPECTED_FAT_COUNT
+        || !valid_media_types.contains(&bs_header.media_type)
     {
-        // Reserved sectors must be at least 1
-        if bs_header.reserved_sectors > 0 {
-            // Sanity check the reported number of FATs, reported media type
-            if bs_header.fat_count == EXPECTED_FAT_COUNT
-                && valid_media_types.contains(&bs_header.media_type)
-            {
-                // This field is set to 0 for FAT32, but populated by FAT12/16
-                result.is_fat32 = bs_header.fat_size_16 == 0;
-
-                // total_sectors_16 is used for FAT12/16 that have less than 0x10000 sectors
-                if bs_header.total_sectors_16 != 0 {
-                    result.total_size = (bs_header.total_sectors_16.get() as usize)
-                        * (bs_header.bytes_per_sector.get() as usize);
-                // Else, total_sectors_32 is used to define the number of sectors
+        return Err(StructureError);
+    }
+
+    let bytes_per_sector = bs_header.bytes_per_sector.get() as usize;
+
+    // total_sectors_16 is used for FAT12/16 that have less than 0x10000 sectors, else total_sectors_32 is used
+    let total_sectors = if bs_header.total_sectors_16 != 0 {
+        bs_header.total_sectors_16.get() as usize
+    } else {
+        bs_header.total_sectors_32.get() as usize
+    };
+
+    // fat_size_16 is 0 for FAT32, which stores the FAT size and root directory cluster in the extended boot sector
+    let (fat_sectors, root_cluster) = if bs_header.fat_size_16 == 0 {
+        let (fat32_header, _) =
+            FAT32BootSectorBytes::ref_from_prefix(extended_data).map_err(|_| StructureError)?;
+
+        (
+            fat32_header.fat_size_32.get() as usize,
+            fat32_header.root_cluster.get() as usize,
+        )
+    } else {
+        (bs_header.fat_size_16.get() as usize, 0)
+    };
+
+    let root_entry_count = bs_header.root_entries_count_16.get() as usize;
+    let root_directory_sectors =
+        (root_entry_count * DIRECTORY_ENTRY_SIZE).div_ceil(bytes_per_sector);
+
+    let fat_start_sector = bs_header.reserved_sectors.get() as usize;
+    let root_directory_sector = fat_start_sector + (bs_header.fat_count as usize * fat_sectors);
+    let data_start_sector = root_directory_sector + root_directory_sectors;
+
+    // If both total_sectors_32 and total_sectors_16 is 0, or there's no room for data, this is not a valid FAT
+    if fat_sectors == 0 || total_sectors <= data_start_sector {
+        return Err(StructureError);
+    }
+
+    let cluster_count =
+        (total_sectors - data_start_sector) / bs_header.sectors_per_cluster as usize;
+
+    // The FAT type is determined by the number of clusters, though only FAT32 can have no FAT12/16 FAT size
+    let fat_type = if bs_header.fat_size_16 == 0 {
+        FATType::Fat32
+    } else if cluster_count < MAX_FAT12_CLUSTERS {
+        FATType::Fat12
+    } else if cluster_count < MAX_FAT16_CLUSTERS {
+        FATType::Fat16
+    } else {
+        return Err(StructureError);
+    };
+
+    // FAT32 has no fixed root directory
+    if fat_type == FATType::Fat32 && (root_entry_count != 0 || root_cluster < FIRST_DATA_CLUSTER) {
+        return Err(StructureError);
+    }
+
+    Ok(FATHeader {
+        fat_type,
+        total_size: total_sectors * bytes_per_sector,
+        bytes_per_sector,
+        cluster_size: bs_header.sectors_per_cluster as usize * bytes_per_sector,
+        fat_count: bs_header.fat_count as usize,
+        root_entry_count,
+        cluster_count,
+        root_cluster,
+        fat_offset: fat_start_sector * bytes_per_sector,
+        fat_size: fat_sectors * bytes_per_sector,
+        root_directory_offset: root_directory_sector * bytes_per_sector,
+        data_offset: data_start_sector * bytes_per_sector,
+    })
+}
+
+/// Describes a file or directory in a FAT file system
+#[derive(Debug, Default, Clone)]
+pub struct FATEntry {
+    /// Long file name, if there is one, else the 8.3 short name
+    pub name: String,
+    pub directory: bool,
+    pub first_cluster: usize,
+    pub size: usize,
+    pub children: Vec<FATEntry>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct FATDirectoryEntryBytes {
+    name: [u8; 11],
+    attributes: u8,
+    nt_reserved: u8,
+    creation_time_tenths: u8,
+    creation_time: zerocopy::U16<LE>,
+    creation_date: zerocopy::U16<LE>,
+    access_date: zerocopy::U16<LE>,
+    first_cluster_high: zerocopy::U16<LE>,
+    write_time: zerocopy::U16<LE>,
+    write_date: zerocopy::U16<LE>,
+    first_cluster_low: zerocopy::U16<LE>,
+    file_size: zerocopy::U32<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct FATLongNameEntryBytes {
+    order: u8,
+    name1: [u8; 10],
+    attributes: u8,
+    entry_type: u8,
+    checksum: u8,
+    name2: [u8; 12],
+    first_cluster_low: zerocopy::U16<LE>,
+    name3: [u8; 4],
+}
+
+/// Accumulates long file name entries, which precede the short name entry they belong to, in reverse order
+#[derive(Debug, Default, Clone)]
+struct FATLongName {
+    /// Name parts, indexed by their order number
+    parts: Vec<Option<Vec<u8>>>,
+    checksum: u8,
+}
+
+impl FATLongName {
+    /// Returns the long name, if it is complete and belongs to the short name entry
+    fn name(&self, short_name: &[u8; 11]) -> Option<String> {
+        if self.parts.is_empty() || self.checksum != short_name_checksum(short_name) {
+            return None;
+        }
+
+        let mut name_data: Vec<u8> = Vec::new();
+
+        for part in &self.parts {
+            name_data.extend(part.as_ref()?);
+        }
+
+        // Long names are NULL terminated, unless they fill the last entry
+        Some(get_utf16_string(&name_data, true))
+    }
+}
+
+/// Checksum of the short name, which is stored in each of its long name entries
+fn short_name_checksum(short_name: &[u8; 11]) -> u8 {
+    short_name
+        .iter()
+        .fold(0u8, |sum, byte| sum.rotate_right(1).wrapping_add(*byte))
+}
+
+/// Converts an 8.3 short name to a file name
+fn short_name_to_string(short_name: &[u8; 11], nt_reserved: u8) -> String {
+    // Windows NT flags specifying lower case base names and extensions
+    const LOWER_CASE_BASE: u8 = 0x08;
+    const LOWER_CASE_EXTENSION: u8 = 0x10;
+    // A leading 0x05 stands in for 0xE5, which marks deleted entries
+    const ESCAPED_E5: u8 = 0x05;
+
+    let mut short_name = *short_name;
+
+    if short_name[0] == ESCAPED_E5 {
+        short_name[0] = 0xE5;
+    }
+
+    let mut base = String::from_utf8_lossy(&short_name[..8])
+        .trim_end()
+        .to_string();
+    let mut extension = String::from_utf8_lossy(&short_name[8..])
+        .trim_end()
+        .to_string();
+
+    if (nt_reserved & LOWER_CASE_BASE) != 0 {
+        base = base.to_lowercase();
+    }
+
+    if (nt_reserved & LOWER_CASE_EXTENSION) != 0 {
+        extension = extension.to_lowercase();
+    }
+
+    if extension.is_empty() {
+        base
+    } else {
+        format!("{base}.{extension}")
+    }
+}
+
+/// Parses the FAT directory tree
+pub fn parse_fat_directory_tree(
+    fat_data: &[u8],
+    fat_header: &FATHeader,
+) -> Result<Vec<FATEntry>, StructureError> {
+    let mut tree = FATTree {
+        fat_data,
+        header: fat_header,
+        visited_directories: HashSet::new(),
+    };
+
+    let root_directory_data = if fat_header.fat_type == FATType::Fat32 {
+        tree.visited_directories.insert(fat_header.root_cluster);
+        tree.cluster_chain_data(fat_header.root_cluster, None)?
+    } else {
+        let root_directory_end =
+            fat_header.root_directory_offset + (fat_header.root_entry_count * DIRECTORY_ENTRY_SIZE);
+        fat_data
+            .get(fat_header.root_directory_offset..root_directory_end)
+            .ok_or(StructureError)?
+            .to_vec()
+    };
+
+    tree.read_directory(&root_directory_data, 0)
+}
+
+/// Walks the FAT directory tree
+struct FATTree<'a> {
+    fat_data: &'a [u8],
+    header: &'a FATHeader,
+    /// First clusters of directories that have already been read, to prevent infinite recursion
+    visited_directories: HashSet<usize>,
+}
+
+impl FATTree<'_> {
+    /// Returns the next cluster in a cluster chain, or None at the end of the chain
+    fn next_cluster(&self, cluster: usize) -> Result<Option<usize>, StructureError> {
+        const FAT12_END_OF_CHAIN: usize = 0xFF8;
+        const FAT16_END_OF_CHAIN: usize = 0xFFF8;
+        const FAT32_END_OF_CHAIN: usize = 0x0FFFFFF8;
+        // The upper 4 bits of FAT32 entries are reserved
+        const FAT32_ENTRY_MASK: u32 = 0x0FFFFFFF;
+
+        let fat = self
+            .fat_data
+            .get(self.header.fat_offset..self.header.fat_offset + self.header.fat_size)
+            .ok_or(StructureError)?;
+
+        let (next_cluster, end_of_chain) = match self.header.fat_type {
+            FATType::Fat12 => {
+                let entry_offset = cluster + (cluster / 2);
+                let entry_bytes = fat
+                    .get(entry_offset..entry_offset + 2)
+                    .ok_or(StructureError)?;
+                let entry = u16::from_le_bytes([entry_bytes[0], entry_bytes[1]]) as usize;
+
+                // Odd clusters are in the upper 12 bits, even clusters in the lower 12 bits
+                if cluster % 2 == 1 {
+                    (entry >> 4, FAT12_END_OF_CHAIN)
                 } else {
-                    result.total_size = (bs_header.total_sectors_32.get() as usize)
-                        * (bs_header.bytes_per_sector.get() as usize);
+                    (entry & 0xFFF, FAT12_END_OF_CHAIN)
+                }
+            }
+            FATType::Fat16 => {
+                let entry_offset = cluster * 2;
+                let entry_bytes = fat
+                    .get(entry_offset..entry_offset + 2)
+                    .ok_or(StructureError)?;
+
+                (
+                    u16::from_le_bytes([entry_bytes[0], entry_bytes[1]]) as usize,
+                    FAT16_END_OF_CHAIN,
+                )
+            }
+            FATType::Fat32 => {
+                let entry_offset = cluster * 4;
+                let (entry, _) = zerocopy::U32::<LE>::read_from_prefix(
+                    fat.get(entry_offset..).ok_or(StructureError)?,
+                )
+                .map_err(|_| StructureError)?;
+
+                (
+                    (entry.get() & FAT32_ENTRY_MASK) as usize,
+                    FAT32_END_OF_CHAIN,
+                )
+            }
+        };
+
+        if next_cluster >= end_of_chain {
+            return Ok(None);
+        }
+
+        // Free, reserved, and bad clusters have no place in a cluster chain
+        if !self.is_valid_cluster(next_cluster) {
+            return Err(StructureError);
+        }
+
+        Ok(Some(next_cluster))
+    }
+
+    /// Returns true if the cluster number refers to a data cluster
+    fn is_valid_cluster(&self, cluster: usize) -> bool {
+        (FIRST_DATA_CLUSTER..FIRST_DATA_CLUSTER + self.header.cluster_count).contains(&cluster)
+    }
+
+    /// Returns the data in each cluster of a cluster chain, up to max_size bytes
+    fn cluster_chain(
+        &self,
+        first_cluster: usize,
+        max_size: Option<usize>,
+    ) -> Result<Vec<&[u8]>, StructureError> {
+        let mut clusters: Vec<&[u8]> = Vec::new();
+        let mut chain_size: usize = 0;
+        let mut cluster = Some(first_cluster);
+
+        if !self.is_valid_cluster(first_cluster) {
+            return Err(StructureError);
+        }
+
+        while let Some(this_cluster) = cluster {
+            if max_size.is_some_and(|max_size| chain_size >= max_size) {
+                break;
+            }
+
+            // A chain can't be longer than the number of clusters, else it has a loop
+            if clusters.len() >= self.header.cluster_count {
+                return Err(StructureError);
+            }
+
+            let cluster_offset = self.header.data_offset
+                + ((this_cluster - FIRST_DATA_CLUSTER) * self.header.cluster_size);
+            let cluster_data = self
+                .fat_data
+                .get(cluster_offset..cluster_offset + self.header.cluster_size)
+                .ok_or(StructureError)?;
+
+            clusters.push(cluster_data);
+            chain_size += cluster_data.len();
+            cluster = self.next_cluster(this_cluster)?;
+        }
+
+        Ok(clusters)
+    }
+
+    /// Returns the data in a cluster chain, as one contiguous buffer
+    fn cluster_chain_data(
+        &self,
+        first_cluster: usize,
+        max_size: Option<usize>,
+    ) -> Result<Vec<u8>, StructureError> {
+        Ok(self.cluster_chain(first_cluster, max_size)?.concat())
+    }
+
+    /// Recursively reads a directory's entries, and all of its children
+    fn read_directory(
+        &mut self,
+        directory_data: &[u8],
+        depth: usize,
+    ) -> Result<Vec<FATEntry>, StructureError> {
+        // Don't recurse indefinitely
+        const MAX_DEPTH: usize = 64;
+        // First name byte of the last directory entry, and of deleted entries
+        const END_OF_DIRECTORY: u8 = 0;
+        const DELETED_ENTRY: u8 = 0xE5;
+        // Entry attributes
+        const ATTRIBUTE_VOLUME_LABEL: u8 = 0x08;
+        const ATTRIBUTE_DIRECTORY: u8 = 0x10;
+        const ATTRIBUTE_LONG_NAME: u8 = 0x0F;
+        const ATTRIBUTE_LONG_NAME_MASK: u8 = 0x3F;
+        // Long name entry order flags
+        const LAST_LONG_NAME_ENTRY: u8 = 0x40;
+        const LONG_NAME_ORDER_MASK: u8 = 0x1F;
+
+        if depth > MAX_DEPTH {
+            return Err(StructureError);
+        }
+
+        let mut entries: Vec<FATEntry> = Vec::new();
+        let mut long_name = FATLongName::default();
+
+        for entry_data in directory_data.chunks_exact(DIRECTORY_ENTRY_SIZE) {
+            let (entry, _) =
+                FATDirectoryEntryBytes::ref_from_prefix(entry_data).map_err(|_| StructureError)?;
+
+            if entry.name[0] == END_OF_DIRECTORY {
+                break;
+            }
+
+            if entry.name[0] == DELETED_ENTRY {
+                long_name = FATLongName::default();
+                continue;
+            }
+
+            // Long file name entries are stored in reverse order, immediately before their short name entry
+            if (entry.attributes & ATTRIBUTE_LONG_NAME_MASK) == ATTRIBUTE_LONG_NAME {
+                let (lfn_entry, _) = FATLongNameEntryBytes::ref_from_prefix(entry_data)
+                    .map_err(|_| StructureError)?;
+                let order = (lfn_entry.order & LONG_NAME_ORDER_MASK) as usize;
+
+                if (lfn_entry.order & LAST_LONG_NAME_ENTRY) != 0 {
+                    long_name = FATLongName {
+                        parts: vec![None; order],
+                        checksum: lfn_entry.checksum,
+                    };
+                }
+
+                if order > 0
+                    && order <= long_name.parts.len()
+                    && lfn_entry.checksum == long_name.checksum
+                {
+                    long_name.parts[order - 1] =
+                        Some([&lfn_entry.name1[..], &lfn_entry.name2, &lfn_entry.name3].concat());
                 }
 
-                // If both total_sectors_32 and total_sectors_16 is 0, this is not a valid FAT
-                if result.total_size > 0 {
-                    return Ok(result);
+                continue;
+            }
+
+            let long_name_string = std::mem::take(&mut long_name).name(&entry.name);
+
+            if (entry.attributes & ATTRIBUTE_VOLUME_LABEL) != 0 {
+                continue;
+            }
+
+            let short_name = short_name_to_string(&entry.name, entry.nt_reserved);
+
+            // Skip the "." and ".." entries
+            if short_name == "." || short_name == ".." {
+                continue;
+            }
+
+            let mut fat_entry = FATEntry {
+                name: long_name_string.unwrap_or(short_name),
+                directory: (entry.attributes & ATTRIBUTE_DIRECTORY) != 0,
+                first_cluster: ((entry.first_cluster_high.get() as usize) << 16)
+                    | entry.first_cluster_low.get() as usize,
+                size: entry.file_size.get() as usize,
+                ..Default::default()
+            };
+
+            if fat_entry.directory {
+                if !self.visited_directories.insert(fat_entry.first_cluster) {
+                    return Err(StructureError);
+                }
+
+                let child_directory_data =
+                    self.cluster_chain_data(fat_entry.first_cluster, None)?;
+                fat_entry.children = self.read_directory(&child_directory_data, depth + 1)?;
+            }
+
+            entries.push(fat_entry);
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Defines the internal extractor for FAT file systems
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::fat::fat_extractor;
+///
+/// match fat_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn fat_extractor() -> Extractor {
+    Extractor {
+        utility: ExtractorType::Internal(extract_fat),
+        ..Default::default()
+    }
+}
+
+/// Internal extractor for FAT file systems
+pub fn extract_fat(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> ExtractionResult {
+    let mut result = ExtractionResult::default();
+
+    let Ok(fat_header) = parse_fat_header(file_data.get(offset..).unwrap_or_default()) else {
+        return result;
+    };
+
+    let Some(fat_data) = file_data.get(offset..offset + fat_header.total_size) else {
+        return result;
+    };
+
+    let Ok(entries) = parse_fat_directory_tree(fat_data, &fat_header) else {
+        return result;
+    };
+
+    result.success = true;
+    result.size = Some(fat_header.total_size);
+
+    if let Some(output_directory) = output_directory {
+        let chroot = Chroot::new(output_directory);
+        let tree = FATTree {
+            fat_data,
+            header: &fat_header,
+            visited_directories: HashSet::new(),
+        };
+
+        result.success = extract_fat_entries(&chroot, &tree, &entries, Path::new(""));
+    }
+
+    result
+}
+
+/// Recursively extracts FAT entries, returns false if any entry failed to extract
+fn extract_fat_entries(
+    chroot: &Chroot,
+    tree: &FATTree<'_>,
+    entries: &[FATEntry],
+    parent_directory: &Path,
+) -> bool {
+    let mut success = true;
+
+    for entry in entries {
+        let file_path = chroot.safe_path_join(parent_directory, &entry.name);
+
+        if entry.directory {
+            success &= chroot.create_directory(&file_path)
+                && extract_fat_entries(chroot, tree, &entry.children, &file_path);
+            continue;
+        }
+
+        // Empty files have no clusters
+        let clusters = if entry.size == 0 {
+            Vec::new()
+        } else {
+            match tree.cluster_chain(entry.first_cluster, Some(entry.size)) {
+                Ok(clusters) => clusters,
+                Err(_) => {
+                    warn!("Invalid cluster chain for FAT file {}", file_path.display());
+                    success = false;
+                    continue;
                 }
             }
+        };
+
+        let Some(mut file) = chroot.create_file_writer(&file_path) else {
+            success = false;
+            continue;
+        };
+
+        // The last cluster is only partially used
+        let mut remaining_size = entry.size;
+
+        for cluster_data in clusters {
+            let write_size = remaining_size.min(cluster_data.len());
+
+            if file.write_all(&cluster_data[..write_size]).is_err() {
+                success = false;
+                break;
+            }
+
+            remaining_size -= write_size;
+        }
+
+        if remaining_size > 0 {
+            warn!("FAT file {} is truncated", file_path.display());
+            success = false;
         }
     }
 
-    Err(StructureError)
+    success
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A FAT16 image with a long-named file whose clusters are not contiguous, and a file in a sub-directory (see
+    /// tests/inputs/gen_fat16.sh).
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/fat16.bin");
+
+    /// Offset of the first FAT in VALID: one reserved sector
+    const FAT_OFFSET: usize = 512;
+
+    #[test]
+    fn fat16_extraction() {
+        let header = parse_fat_header(VALID).unwrap();
+        assert_eq!(header.fat_type, FATType::Fat16);
+        assert_eq!(header.cluster_count, 4133);
+
+        let signature = fat_parser(VALID, MAGIC_OFFSET).unwrap();
+        assert_eq!(signature.offset, 0);
+        assert_eq!(signature.size, VALID.len());
+        assert!(signature.description.contains("type: FAT16"));
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = extract_fat(VALID, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(VALID.len()));
+
+        let file_data: Vec<u8> = (0..700).map(|n| (n % 251) as u8).collect();
+        assert_eq!(
+            std::fs::read(output_directory.path().join("A long file name.txt")).unwrap(),
+            file_data
+        );
+        assert_eq!(
+            std::fs::read(output_directory.path().join("SUBDIR").join("INNER.TXT")).unwrap(),
+            b"inner\n"
+        );
+    }
+
+    #[test]
+    fn fat16_cluster_loop() {
+        let mut image = VALID.to_vec();
+
+        // Point the sub-directory back at itself
+        let fat_entry_offset = FAT_OFFSET + 2 * 2;
+        image[fat_entry_offset..fat_entry_offset + 2].copy_from_slice(&2u16.to_le_bytes());
+
+        assert!(fat_parser(&image, MAGIC_OFFSET).is_ok());
+        assert!(!extract_fat(&image, 0, None).success);
+    }
 }
@@ -216,6 +216,8 @@ pub fn parse_gif_extension(extension_data: &[u8]) -> Result<usize, StructureErro
 ///
 /// match gif_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -247,7 +249,7 @@ pub fn extract_gif_image(
     let mut result = ExtractionResult::default();
 
     // Parse the GIF header
-    if let Ok(gif_header) = parse_gif_header(&file_data[offset..]) {
+    if let Ok(gif_header) = parse_gif_header(file_data.get(offset..).unwrap_or_default()) {
         // GIF data follows the gif header
         if let Some(gif_image_data) = file_data.get(offset + gif_header.size..) {
             // Determine the size of the GIF image data
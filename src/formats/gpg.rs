@@ -52,6 +52,8 @@ pub fn gpg_signed_parser(
 ///
 /// match gpg_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -90,6 +92,7 @@ pub fn gpg_decompress(
     if inflate_result.success {
         exresult.success = true;
         exresult.size = Some(HEADER_SIZE + inflate_result.size);
+        exresult.set_compression_ratio(inflate_result.size, inflate_result.decompressed_size);
     }
 
     exresult
@@ -2,7 +2,7 @@ use crate::common;
 use crate::common::get_cstring;
 use crate::extractors::inflate;
 use crate::extractors::{ExtractionResult, Extractor, ExtractorType};
-use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
+use crate::signatures::{CONFIDENCE_HIGH, CONFIDENCE_LOW, SignatureError, SignatureResult};
 use crate::structures::StructureError;
 use std::path::Path;
 use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
@@ -16,51 +16,139 @@ pub fn gzip_magic() -> Vec<Vec<u8>> {
 }
 
 /// Validates gzip signatures
+///
+/// Concatenated gzip members are treated as a single gzip file, as gunzip does; see gzip_decompress.
+/// Gzip data whose header and deflate stream are valid, but whose trailing CRC or ISIZE field is missing or wrong (e.g.,
+/// a truncated stream), is reported with low confidence and the reason recorded in SignatureResult.trailer_error.
 pub fn gzip_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
+    // Do a dry-run decompression of the first member; if successful, this is almost certainly a valid gzip file
+    let first_member = gzip_member(file_data, offset, None).ok_or(SignatureError)?;
+
+    let mut result = SignatureResult {
+        offset,
+        confidence: CONFIDENCE_HIGH,
+        trailer_error: first_member.trailer_error,
+        ..Default::default()
+    };
+
+    // Validate any members that follow the first one
+    let mut member_end = first_member.end;
+
+    while member_end < file_data.len()
+        && let Some(member) = gzip_member(file_data, member_end, None)
+    {
+        if result.trailer_error.is_none() {
+            result.trailer_error = member.trailer_error;
+        }
+        member_end = member.end;
+    }
+
+    result.size = member_end - offset;
+
+    // Original file name is optional
+    let original_file_name_text: String = if first_member.header.original_name.is_empty() {
+        "".to_string()
+    } else {
+        format!(
+            " original file name: \"{}\",",
+            first_member.header.original_name
+        )
+    };
+
+    result.description = format!(
+        "{},{} operating system: {}, timestamp: {}, total size: {} bytes",
+        DESCRIPTION,
+        original_file_name_text,
+        first_member.header.os,
+        common::epoch_to_string(first_member.header.timestamp),
+        result.size,
+    );
+
+    if let Some(trailer_error) = &result.trailer_error {
+        result.confidence = CONFIDENCE_LOW;
+        result.description = format!("{}, invalid trailer: {}", result.description, trailer_error);
+    }
+
+    Ok(result)
+}
+
+/// Describes a single member of a gzip file
+struct GzipMember {
+    header: GzipHeader,
+    /// Offset of the end of the member, including its trailer; EOF if the trailer is missing
+    end: usize,
+    decompressed_size: usize,
+    /// Why the member's CRC and ISIZE fields are invalid, if they are
+    trailer_error: Option<String>,
+}
+
+/// Decompresses the gzip member at member_start into the output directory, or does a dry run if there is none.
+/// Returns None if there is no valid gzip member at member_start.
+fn gzip_member(
+    file_data: &[u8],
+    member_start: usize,
+    output_directory: Option<&Path>,
+) -> Option<GzipMember> {
     // Length of the GZIP CRC located at the end of the deflate data stream
     const GZIP_CRC_SIZE: usize = 4;
     // Length of the ISIZE field located after the CRC field
     const GZIP_ISIZE_SIZE: usize = 4;
 
-    // Do a dry-run decompression
-    let dry_run = gzip_decompress(file_data, offset, None);
-
-    // If dry-run was successful, this is almost certainly a valid gzip file
-    if dry_run.success {
-        // Get the size of the deflate data stream
-        if let Some(deflate_data_size) = dry_run.size {
-            // The dry run has already validated the header, but we want some header info to display to the user
-            if let Ok(gzip_header) = parse_gzip_header(&file_data[offset..]) {
-                // Original file name is optional
-                let original_file_name_text: String = if gzip_header.original_name.is_empty() {
-                    "".to_string()
-                } else {
-                    format!(" original file name: \"{}\",", gzip_header.original_name)
-                };
-
-                // Total size of the gzip file is the size of the header, plus the size of the compressed data, plus the trailing CRC and ISIZE fields
-                let total_size =
-                    gzip_header.size + deflate_data_size + GZIP_CRC_SIZE + GZIP_ISIZE_SIZE;
-
-                return Ok(SignatureResult {
-                    offset,
-                    size: total_size,
-                    confidence: CONFIDENCE_HIGH,
-                    description: format!(
-                        "{},{} operating system: {}, timestamp: {}, total size: {} bytes",
-                        DESCRIPTION,
-                        original_file_name_text,
-                        gzip_header.os,
-                        common::epoch_to_string(gzip_header.timestamp),
-                        total_size,
-                    ),
-                    ..Default::default()
-                });
-            }
-        }
+    let header = parse_gzip_header(file_data.get(member_start..)?).ok()?;
+
+    // Deflate compressed data starts at the end of the gzip header
+    let deflate_data_start = member_start + header.size;
+
+    if file_data.len() <= deflate_data_start {
+        return None;
     }
 
-    Err(SignatureError)
+    let inflate_result =
+        inflate::inflate_decompressor(file_data, deflate_data_start, output_directory);
+
+    if !inflate_result.success {
+        return None;
+    }
+
+    // The CRC and ISIZE fields immediately follow the deflate data
+    let trailer_start = deflate_data_start + inflate_result.size;
+    let trailer_end = trailer_start + GZIP_CRC_SIZE + GZIP_ISIZE_SIZE;
+
+    let (end, trailer_error) = match file_data.get(trailer_start..trailer_end) {
+        // Truncated stream; the gzip data ends at EOF
+        None => (
+            file_data.len(),
+            Some("CRC and ISIZE fields are missing".to_string()),
+        ),
+        Some(trailer) => {
+            let crc = u32::from_le_bytes(trailer[0..GZIP_CRC_SIZE].try_into().unwrap());
+            let isize = u32::from_le_bytes(trailer[GZIP_CRC_SIZE..].try_into().unwrap());
+
+            // ISIZE is the size of the uncompressed data, modulo 2^32
+            let trailer_error = if crc != inflate_result.crc32 {
+                Some(format!(
+                    "CRC mismatch (expected {:#010X}, found {:#010X})",
+                    inflate_result.crc32, crc
+                ))
+            } else if isize != inflate_result.decompressed_size as u32 {
+                Some(format!(
+                    "ISIZE mismatch (expected {}, found {})",
+                    inflate_result.decompressed_size as u32, isize
+                ))
+            } else {
+                None
+            };
+
+            (trailer_end, trailer_error)
+        }
+    };
+
+    Some(GzipMember {
+        header,
+        end,
+        decompressed_size: inflate_result.decompressed_size,
+        trailer_error,
+    })
 }
 
 /// Struct to store useful Gzip header info
@@ -105,7 +193,7 @@ pub fn parse_gzip_header(header_data: &[u8]) -> Result<GzipHeader, StructureErro
     const FLAG_RESERVED: u8 = 0b1110_0000;
 
     // Parse the gzip header
-    let (gzip_header, _) =
+    let (gzip_header, optional_header_data) =
         GzipHeaderBytes::ref_from_prefix(header_data).map_err(|_| StructureError)?;
 
     // Sanity check; compression type should be deflate, reserved flag bits should not be set, OS ID should be a known value
@@ -139,24 +227,11 @@ pub fn parse_gzip_header(header_data: &[u8]) -> Result<GzipHeader, StructureErro
 
         // Check if the optional "extra" data follows the standard Gzip header
         if (gzip_header.flags & FLAG_EXTRA) != 0 {
-            // File offsets and sizes for parsing the extra header
-            let extra_header_size = std::mem::size_of::<GzipHeaderExtraBytes>();
-            let extra_header_start: usize = header_info.size;
-            let extra_header_end: usize = extra_header_start + extra_header_size;
-
-            match header_data.get(extra_header_start..extra_header_end) {
-                None => {
-                    return Err(StructureError);
-                }
-                Some(extra_header_data) => {
-                    // Parse the extra header and update the header_info.size to include this data
-                    let (extra_header, _) =
-                        GzipHeaderExtraBytes::ref_from_prefix(extra_header_data)
-                            .map_err(|_| StructureError)?;
-                    header_info.size +=
-                        extra_header_size + extra_header.extra_data_len.get() as usize;
-                }
-            }
+            // Parse the extra header and update the header_info.size to include this data
+            let (extra_header, _) = GzipHeaderExtraBytes::ref_from_prefix(optional_header_data)
+                .map_err(|_| StructureError)?;
+            header_info.size += std::mem::size_of::<GzipHeaderExtraBytes>()
+                + extra_header.extra_data_len.get() as usize;
         }
 
         // If the NULL-terminated original file name is included, it will be next
@@ -211,6 +286,8 @@ pub fn parse_gzip_header(header_data: &[u8]) -> Result<GzipHeader, StructureErro
 ///
 /// match gzip_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -231,6 +308,9 @@ pub fn gzip_extractor() -> Extractor {
 }
 
 /// Internal extractor for gzip compressed data
+///
+/// Like gunzip, concatenated gzip members are decompressed into the same output file; the reported size
+/// includes all members, up to the first data that is not a valid gzip member.
 pub fn gzip_decompress(
     file_data: &[u8],
     offset: usize,
@@ -238,20 +318,99 @@ pub fn gzip_decompress(
 ) -> ExtractionResult {
     let mut exresult = ExtractionResult::default();
 
-    // Parse the gzip header
-    if let Ok(gzip_header) = parse_gzip_header(&file_data[offset..]) {
-        // Deflate compressed data starts at the end of the gzip header
-        let deflate_data_start: usize = offset + gzip_header.size;
-
-        if file_data.len() > deflate_data_start {
-            let inflate_result =
-                inflate::inflate_decompressor(file_data, deflate_data_start, output_directory);
-            if inflate_result.success {
-                exresult.success = true;
-                exresult.size = Some(inflate_result.size);
-            }
+    let mut member_start = offset;
+    let mut decompressed_size: usize = 0;
+
+    while member_start < file_data.len() {
+        // Don't write partial data from a corrupt trailing member to the output file
+        let is_first_member = member_start == offset;
+        if !is_first_member
+            && output_directory.is_some()
+            && gzip_member(file_data, member_start, None).is_none()
+        {
+            break;
         }
+
+        let Some(member) = gzip_member(file_data, member_start, output_directory) else {
+            break;
+        };
+
+        exresult.success = true;
+        decompressed_size += member.decompressed_size;
+        member_start = member.end;
+    }
+
+    if exresult.success {
+        exresult.size = Some(member_start - offset);
+        exresult.set_compression_ratio(member_start - offset, decompressed_size);
     }
 
     exresult
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two concatenated gzip members (see tests/inputs/gen_gzip_multi_member.sh).
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/gzip_multi_member.bin");
+    static TRUNCATED: &[u8] = include_bytes!("../../tests/inputs/gzip_truncated.bin");
+    static BAD_CRC: &[u8] = include_bytes!("../../tests/inputs/gzip_bad_crc.bin");
+
+    #[test]
+    fn truncated_stream() {
+        let signature = gzip_parser(TRUNCATED, 0).unwrap();
+        assert_eq!(signature.size, TRUNCATED.len());
+        assert_eq!(signature.confidence, CONFIDENCE_LOW);
+        assert_eq!(
+            signature.trailer_error.as_deref(),
+            Some("CRC and ISIZE fields are missing")
+        );
+
+        // The deflate data is intact, so it is still decompressed
+        let result = gzip_decompress(TRUNCATED, 0, None);
+        assert!(result.success);
+        assert_eq!(result.size, Some(TRUNCATED.len()));
+    }
+
+    #[test]
+    fn bad_crc() {
+        let signature = gzip_parser(BAD_CRC, 0).unwrap();
+        assert_eq!(signature.size, BAD_CRC.len());
+        assert_eq!(signature.confidence, CONFIDENCE_LOW);
+        assert_eq!(
+            signature.trailer_error.as_deref(),
+            Some("CRC mismatch (expected 0xA04E36EA, found 0xA04E3615)")
+        );
+
+        // Repairing the CRC restores a high confidence match
+        let mut repaired = BAD_CRC.to_vec();
+        let crc_offset = repaired.len() - 8;
+        repaired[crc_offset] ^= 0xFF;
+        let signature = gzip_parser(&repaired, 0).unwrap();
+        assert_eq!(signature.confidence, CONFIDENCE_HIGH);
+        assert_eq!(signature.trailer_error, None);
+    }
+
+    #[test]
+    fn multi_member() {
+        // Followed by some non-gzip data
+        let file_data = [VALID, b"\x1f\x8bjunk"].concat();
+
+        let signature = gzip_parser(&file_data, 0).unwrap();
+        assert_eq!(signature.size, VALID.len());
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = gzip_decompress(&file_data, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(VALID.len()));
+        assert_eq!(
+            std::fs::read(output_directory.path().join("decompressed.bin")).unwrap(),
+            b"first member\nsecond member\n"
+        );
+
+        // A dry run reports the same combined size
+        let dry_run = gzip_decompress(&file_data, 0, None);
+        assert_eq!(dry_run.size, Some(VALID.len()));
+    }
+}
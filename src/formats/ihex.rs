@@ -0,0 +1,360 @@
+use crate::extractors::{self, Chroot, ExtractionResult};
+use crate::signatures::{CONFIDENCE_HIGH, CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
+use crate::structures::StructureError;
+use std::path::Path;
+
+/// Human readable description
+pub const DESCRIPTION: &str = "Intel HEX";
+
+/// Intel HEX files typically start with an extended address record, or a data record at address 0
+pub fn ihex_magic() -> Vec<Vec<u8>> {
+    vec![
+        // Extended linear address record
+        b":020000040".to_vec(),
+        // Extended segment address record
+        b":020000020".to_vec(),
+        // 16-byte data record at address 0
+        b":1000000000".to_vec(),
+    ]
+}
+
+/// Validates an Intel HEX signature
+pub fn ihex_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
+    let ihex =
+        parse_ihex(file_data.get(offset..).ok_or(SignatureError)?).map_err(|_| SignatureError)?;
+
+    let mut result = SignatureResult {
+        offset,
+        size: ihex.size,
+        description: format!(
+            "{}, records: {}, base address: {:#X}, data size: {} bytes, total size: {} bytes",
+            DESCRIPTION,
+            ihex.record_count,
+            ihex.base_address(),
+            ihex.image_size(),
+            ihex.size
+        ),
+        confidence: CONFIDENCE_HIGH,
+        ..Default::default()
+    };
+
+    // Without an EOF record, the data was cut short or corrupted somewhere along the way
+    if !ihex.eof {
+        result.confidence = CONFIDENCE_MEDIUM;
+        result.description = format!("{}, missing EOF record", result.description);
+    }
+
+    Ok(result)
+}
+
+/// Describes a block of data decoded from an Intel HEX data record
+#[derive(Debug, Default, Clone)]
+pub struct IHexDataBlock {
+    pub address: usize,
+    pub data: Vec<u8>,
+}
+
+/// Stores the data decoded from Intel HEX records
+#[derive(Debug, Default, Clone)]
+pub struct IHexData {
+    /// Size of the Intel HEX text, in bytes
+    pub size: usize,
+    pub record_count: usize,
+    pub eof: bool,
+    pub blocks: Vec<IHexDataBlock>,
+}
+
+impl IHexData {
+    /// Returns the lowest address written to by the data records
+    pub fn base_address(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| block.address)
+            .min()
+            .unwrap_or_default()
+    }
+
+    /// Returns the size of the binary image described by the data records, including any gaps between them
+    pub fn image_size(&self) -> usize {
+        self.blocks
+            .iter()
+            .map(|block| block.address + block.data.len())
+            .max()
+            .unwrap_or_default()
+            - self.base_address()
+    }
+
+    /// Reconstructs the binary image, relative to the base address; gaps between records are filled with 0xFF
+    pub fn image(&self) -> Vec<u8> {
+        const GAP_FILL: u8 = 0xFF;
+
+        let base_address = self.base_address();
+        let mut image = vec![GAP_FILL; self.image_size()];
+
+        for block in &self.blocks {
+            let start = block.address - base_address;
+            image[start..start + block.data.len()].copy_from_slice(&block.data);
+        }
+
+        image
+    }
+}
+
+/// Parses Intel HEX records up to the EOF record, or the first invalid record
+pub fn parse_ihex(ihex_data: &[u8]) -> Result<IHexData, StructureError> {
+    const DATA_RECORD: u8 = 0;
+    const EOF_RECORD: u8 = 1;
+    const EXTENDED_SEGMENT_ADDRESS_RECORD: u8 = 2;
+    const START_SEGMENT_ADDRESS_RECORD: u8 = 3;
+    const EXTENDED_LINEAR_ADDRESS_RECORD: u8 = 4;
+    const START_LINEAR_ADDRESS_RECORD: u8 = 5;
+
+    // Don't allocate absurdly large images for sparse address ranges
+    const MAX_IMAGE_SIZE: usize = 256 * 1024 * 1024;
+
+    let mut ihex = IHexData::default();
+    let mut upper_address: usize = 0;
+
+    while let Some(record_data) = ihex_data.get(ihex.size..) {
+        let Ok(record) = parse_ihex_record(record_data) else {
+            break;
+        };
+
+        match record.record_type {
+            DATA_RECORD => ihex.blocks.push(IHexDataBlock {
+                address: upper_address + record.address,
+                data: record.data,
+            }),
+            EXTENDED_SEGMENT_ADDRESS_RECORD | EXTENDED_LINEAR_ADDRESS_RECORD => {
+                let Ok(address_bytes) = <[u8; 2]>::try_from(record.data.as_slice()) else {
+                    break;
+                };
+                let address = u16::from_be_bytes(address_bytes) as usize;
+
+                upper_address = if record.record_type == EXTENDED_SEGMENT_ADDRESS_RECORD {
+                    address << 4
+                } else {
+                    address << 16
+                };
+            }
+            // Execution start addresses have no bearing on the binary image
+            START_SEGMENT_ADDRESS_RECORD | START_LINEAR_ADDRESS_RECORD => (),
+            EOF_RECORD => ihex.eof = true,
+            _ => break,
+        }
+
+        ihex.size += record.size;
+        ihex.record_count += 1;
+
+        if ihex.eof {
+            break;
+        }
+    }
+
+    // There must be some data, and not too much of it
+    if ihex.blocks.is_empty() || ihex.image_size() > MAX_IMAGE_SIZE {
+        return Err(StructureError);
+    }
+
+    Ok(ihex)
+}
+
+/// Describes a single Intel HEX record
+#[derive(Debug, Default, Clone)]
+struct IHexRecord {
+    record_type: u8,
+    address: usize,
+    data: Vec<u8>,
+    /// Size of the record text, including line endings
+    size: usize,
+}
+
+/// Parses and validates a single Intel HEX record; records are terminated by a line ending, or the end of the data
+fn parse_ihex_record(record_data: &[u8]) -> Result<IHexRecord, StructureError> {
+    const START_CODE: u8 = b':';
+    // Byte count, address, record type, and checksum
+    const FIXED_FIELDS_SIZE: usize = 5;
+
+    if record_data.first() != Some(&START_CODE) {
+        return Err(StructureError);
+    }
+
+    let byte_count = decode_hex_bytes(record_data.get(1..3).ok_or(StructureError)?)?[0] as usize;
+    let record_end = 1 + ((FIXED_FIELDS_SIZE + byte_count) * 2);
+
+    let record_bytes = decode_hex_bytes(record_data.get(1..record_end).ok_or(StructureError)?)?;
+
+    // All bytes in the record, including the checksum, must sum to 0
+    if record_bytes
+        .iter()
+        .fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+        != 0
+    {
+        return Err(StructureError);
+    }
+
+    // Skip over line endings (and blank lines); there must be at least one, unless this is the end of the data
+    let line_ending_size = record_data[record_end..]
+        .iter()
+        .take_while(|byte| matches!(byte, b'\r' | b'\n'))
+        .count();
+
+    if line_ending_size == 0 && record_end < record_data.len() {
+        return Err(StructureError);
+    }
+
+    Ok(IHexRecord {
+        record_type: record_bytes[3],
+        address: u16::from_be_bytes([record_bytes[1], record_bytes[2]]) as usize,
+        data: record_bytes[4..4 + byte_count].to_vec(),
+        size: record_end + line_ending_size,
+    })
+}
+
+/// Decodes ASCII hex digits into bytes
+fn decode_hex_bytes(hex_data: &[u8]) -> Result<Vec<u8>, StructureError> {
+    hex::decode(hex_data).map_err(|_| StructureError)
+}
+
+/// Describes the internal extractor used to convert Intel HEX to binary
+///
+/// ```
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::ihex::ihex_extractor;
+///
+/// match ihex_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => panic!("Unexpected external extractor '{}'", cmd),
+/// }
+/// ```
+pub fn ihex_extractor() -> extractors::Extractor {
+    extractors::Extractor {
+        utility: extractors::ExtractorType::Internal(extract_ihex),
+        ..Default::default()
+    }
+}
+
+/// Internal extractor for Intel HEX. Decodes the data records into a binary blob, relative to the lowest address.
+pub fn extract_ihex(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> ExtractionResult {
+    const OUTPUT_FILE_NAME: &str = "firmware.bin";
+
+    let mut result = ExtractionResult::default();
+
+    if let Some(ihex_data) = file_data.get(offset..)
+        && let Ok(ihex) = parse_ihex(ihex_data)
+    {
+        result.success = true;
+        result.size = Some(ihex.size);
+
+        if let Some(output_directory) = output_directory {
+            let chroot = Chroot::new(output_directory);
+            result.success = chroot.create_file(OUTPUT_FILE_NAME, &ihex.image());
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Intel HEX file with an extended linear address, a gap between data records, and a start address
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/ihex.hex");
+
+    fn decode_ihex(data: &[u8]) -> Vec<u8> {
+        let dir = tempfile::tempdir().unwrap();
+        let res = extract_ihex(data, 0, Some(dir.path()));
+        assert!(res.success);
+        std::fs::read(dir.path().join("firmware.bin")).unwrap()
+    }
+
+    #[test]
+    fn decode_ihex_fixture() {
+        let parsed = parse_ihex(VALID).unwrap();
+        assert_eq!(parsed.size, VALID.len());
+        assert_eq!(parsed.record_count, 8);
+        assert!(parsed.eof);
+        assert_eq!(parsed.base_address(), 0x08000000);
+
+        // Records are decoded relative to the base address, with the gap before the last data record filled with 0xFF
+        let mut expected: Vec<u8> = (0x10..0x50).collect();
+        expected.extend([0xFF; 16]);
+        expected.extend(b"\xDE\xAD\xBE\xEF\xCA\xFE\xBA\xBE");
+        assert_eq!(decode_ihex(VALID), expected);
+    }
+
+    #[test]
+    fn decode_ihex_with_address_gap() {
+        // Extended linear address 0x0800xxxx, two data records with a 4-byte gap, a start address and EOF
+        let ihex = b":020000040800F2\r\n\
+                     :0400000000010203F6\r\n\
+                     :0400080004050607DE\r\n\
+                     :0400000508000000EF\r\n\
+                     :00000001FF\r\n";
+
+        let parsed = parse_ihex(ihex).unwrap();
+        assert_eq!(parsed.size, ihex.len());
+        assert_eq!(parsed.record_count, 5);
+        assert!(parsed.eof);
+        assert_eq!(parsed.base_address(), 0x08000000);
+
+        assert_eq!(
+            decode_ihex(ihex),
+            b"\x00\x01\x02\x03\xFF\xFF\xFF\xFF\x04\x05\x06\x07"
+        );
+
+        let signature = ihex_parser(ihex, 0).unwrap();
+        assert_eq!(signature.size, ihex.len());
+        assert_eq!(signature.confidence, CONFIDENCE_HIGH);
+    }
+
+    #[test]
+    fn decode_ihex_extended_segment_address() {
+        // Segment 0x1000 places the second record at 0x10000, 0x10000 bytes after the first
+        let ihex = b":0200000000AA54\n\
+                     :020000021000EC\n\
+                     :0200000000BB43\n\
+                     :00000001FF";
+
+        let parsed = parse_ihex(ihex).unwrap();
+        assert_eq!(parsed.size, ihex.len());
+        assert_eq!(parsed.image_size(), 0x10002);
+
+        let image = parsed.image();
+        assert_eq!(image[..2], [0x00, 0xAA]);
+        assert_eq!(image[0x10000..], [0x00, 0xBB]);
+    }
+
+    #[test]
+    fn decode_ihex_stops_at_bad_checksum() {
+        // Correct checksum for the second record is 0xDE; 0xDF is wrong
+        let ihex = b":0400000000010203F6\n\
+                     :0400080004050607DF\n\
+                     :00000001FF\n";
+
+        let parsed = parse_ihex(ihex).unwrap();
+        assert_eq!(parsed.size, 20);
+        assert_eq!(parsed.record_count, 1);
+        assert!(!parsed.eof);
+        assert_eq!(decode_ihex(ihex), b"\x00\x01\x02\x03");
+
+        // Only the valid records are reported
+        let signature = ihex_parser(ihex, 0).unwrap();
+        assert_eq!(signature.size, 20);
+        assert_eq!(signature.confidence, CONFIDENCE_MEDIUM);
+    }
+
+    #[test]
+    fn decode_ihex_requires_data() {
+        assert!(parse_ihex(b":00000001FF\n").is_err());
+        assert!(parse_ihex(b":040000000001020\n").is_err());
+    }
+}
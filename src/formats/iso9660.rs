@@ -1,12 +1,24 @@
-use crate::extractors;
-use crate::formats::sevenzip::sevenzip_extractor;
+use crate::common::get_utf16_string;
+use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
 use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
 use crate::structures::StructureError;
+use log::warn;
+use std::collections::HashSet;
+use std::path::Path;
 use zerocopy::{BE, FromBytes, Immutable, KnownLayout, LE, Unaligned};
 
 /// Human readable description
 pub const DESCRIPTION: &str = "ISO9660 primary volume";
 
+/// Offset from the beginning of the ISO image to the magic bytes (the primary volume descriptor in sector 16)
+pub const MAGIC_OFFSET: usize = 32768;
+
+/// Size of each volume descriptor; volume descriptors are always in 2048 byte sectors, regardless of the logical block size
+const VOLUME_DESCRIPTOR_SIZE: usize = 2048;
+
+/// Size of a directory record, not including the file identifier and system use fields
+const DIRECTORY_RECORD_SIZE: usize = 33;
+
 /// ISOs start with these magic bytes
 pub fn iso_magic() -> Vec<Vec<u8>> {
     vec![b"\x01CD001\x01\x00".to_vec()]
@@ -14,25 +26,29 @@ pub fn iso_magic() -> Vec<Vec<u8>> {
 
 /// Validate ISO signatures
 pub fn iso_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
-    // Offset from the beginning of the ISO image to the magic bytes
-    const ISO_MAGIC_OFFSET: usize = 32768;
-
     let mut result = SignatureResult {
         description: DESCRIPTION.to_string(),
         confidence: CONFIDENCE_HIGH,
         ..Default::default()
     };
 
-    // We need at least ISO_MAGIC_OFFSET bytes to exist before the magic match offset
-    if offset >= ISO_MAGIC_OFFSET {
+    // We need at least MAGIC_OFFSET bytes to exist before the magic match offset
+    if offset >= MAGIC_OFFSET {
         // Calculate the actual starting offset of the ISO
-        result.offset = offset - ISO_MAGIC_OFFSET;
+        result.offset = offset - MAGIC_OFFSET;
 
         // Parse the header, if parsing succeeds assume it's valid
         if let Ok(iso_header) = parse_iso_header(&file_data[result.offset..]) {
             result.size = iso_header.image_size;
-            result.description =
-                format!("{}, total size: {} bytes", result.description, result.size);
+            result.description = format!(
+                "{}, volume name: \"{}\", block size: {}, total size: {} bytes",
+                result.description, iso_header.volume_name, iso_header.block_size, result.size
+            );
+
+            if iso_header.joliet_root_directory.is_some() {
+                result.description = format!("{}, Joliet extensions", result.description);
+            }
+
             return Ok(result);
         }
     }
@@ -43,16 +59,31 @@ pub fn iso_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
 /// Struct to store useful ISO info
 #[derive(Debug, Default, Clone)]
 pub struct ISOHeader {
+    /// Total size of the ISO image: the volume space size multiplied by the logical block size
     pub image_size: usize,
+    /// Number of logical blocks in the volume
+    pub volume_size: usize,
+    pub block_size: usize,
+    pub volume_name: String,
+    pub root_directory: ISODirectoryRecord,
+    /// Root directory of the Joliet supplementary volume, if any
+    pub joliet_root_directory: Option<ISODirectoryRecord>,
 }
 
-// Partial ISO header structure, enough to reasonably validate that this is not a false positive and to calculate the total ISO size
+// Volume descriptor structure, up to and including the root directory record
 #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
 #[repr(C, packed)]
-struct ISOHeaderBytes {
+struct ISOVolumeDescriptorBytes {
+    descriptor_type: u8,
+    identifier: [u8; 5],
+    version: u8,
+    unused0: u8,
+    system_identifier: [u8; 32],
+    volume_identifier: [u8; 32],
     unused1: [u8; 8],
     volume_size_1: zerocopy::U32<LE>,
     volume_size_2: zerocopy::U32<BE>,
+    // Unused in the primary volume descriptor; Joliet escape sequences in supplementary volume descriptors
     unused2: [u8; 32],
     set_size_1: zerocopy::U16<LE>,
     set_size_2: zerocopy::U16<BE>,
@@ -62,44 +93,561 @@ struct ISOHeaderBytes {
     block_size_2: zerocopy::U16<BE>,
     path_table_size_1: zerocopy::U32<LE>,
     path_table_size_2: zerocopy::U32<BE>,
+    path_table_locations: [u8; 16],
+    root_directory_record: [u8; 34],
 }
 
-/// Partially parses an ISO header
+impl ISOVolumeDescriptorBytes {
+    /// Make sure all the identical, but byte-swapped, fields agree
+    fn is_consistent(&self) -> bool {
+        self.set_size_1 == self.set_size_2.get()
+            && self.block_size_1 == self.block_size_2.get()
+            && self.volume_size_1 == self.volume_size_2.get()
+            && self.sequence_number_1 == self.sequence_number_2.get()
+            && self.path_table_size_1 == self.path_table_size_2.get()
+    }
+}
+
+/// Parses the ISO volume descriptors, starting with the primary volume descriptor at sector 16
 pub fn parse_iso_header(iso_data: &[u8]) -> Result<ISOHeader, StructureError> {
-    // Offset from the beginning of the ISO image to the start of iso_structure
-    const ISO_STRUCT_START: usize = 32840;
+    const IDENTIFIER: &[u8; 5] = b"CD001";
+    const PRIMARY_VOLUME_DESCRIPTOR: u8 = 1;
+    const SUPPLEMENTARY_VOLUME_DESCRIPTOR: u8 = 2;
+    const VOLUME_DESCRIPTOR_SET_TERMINATOR: u8 = 255;
+    // Don't look at an unreasonable number of volume descriptors
+    const MAX_VOLUME_DESCRIPTORS: usize = 32;
+    // Logical block sizes are powers of 2, no smaller than 512 and no larger than the sector size
+    const MIN_BLOCK_SIZE: usize = 512;
 
-    if let Some(iso_header_data) = iso_data.get(ISO_STRUCT_START..) {
-        // Parse the ISO header
-        let (iso_header, _) =
-            ISOHeaderBytes::ref_from_prefix(iso_header_data).map_err(|_| StructureError)?;
+    let (iso_header, _) = ISOVolumeDescriptorBytes::ref_from_prefix(
+        iso_data.get(MAGIC_OFFSET..).ok_or(StructureError)?,
+    )
+    .map_err(|_| StructureError)?;
 
-        // Make sure all the unused fields are, in fact, unused
-        if iso_header
+    // Make sure all the unused fields are, in fact, unused
+    if iso_header.descriptor_type != PRIMARY_VOLUME_DESCRIPTOR
+        || iso_header.identifier != *IDENTIFIER
+        || !iso_header
             .unused1
             .iter()
             .chain(&iso_header.unused2)
             .all(|&b| b == 0)
+        || !iso_header.is_consistent()
+    {
+        return Err(StructureError);
+    }
+
+    let block_size = iso_header.block_size_1.get() as usize;
+
+    if !block_size.is_power_of_two()
+        || !(MIN_BLOCK_SIZE..=VOLUME_DESCRIPTOR_SIZE).contains(&block_size)
+    {
+        return Err(StructureError);
+    }
+
+    let volume_size = iso_header.volume_size_1.get() as usize;
+
+    let mut header = ISOHeader {
+        image_size: volume_size * block_size,
+        volume_size,
+        block_size,
+        volume_name: String::from_utf8_lossy(&iso_header.volume_identifier)
+            .trim_end()
+            .to_string(),
+        root_directory: parse_iso_directory_record(&iso_header.root_directory_record)?,
+        joliet_root_directory: None,
+    };
+
+    // The root directory record must describe a directory
+    if !header.root_directory.directory {
+        return Err(StructureError);
+    }
+
+    // Look for a Joliet supplementary volume descriptor in the rest of the volume descriptor set
+    for descriptor_index in 1..MAX_VOLUME_DESCRIPTORS {
+        let descriptor_offset = MAGIC_OFFSET + (descriptor_index * VOLUME_DESCRIPTOR_SIZE);
+
+        let Some(Ok((descriptor, _))) = iso_data
+            .get(descriptor_offset..)
+            .map(ISOVolumeDescriptorBytes::ref_from_prefix)
+        else {
+            break;
+        };
+
+        if descriptor.identifier != *IDENTIFIER
+            || descriptor.descriptor_type == VOLUME_DESCRIPTOR_SET_TERMINATOR
+        {
+            break;
+        }
+
+        if descriptor.descriptor_type == SUPPLEMENTARY_VOLUME_DESCRIPTOR
+            && is_joliet_escape_sequence(&descriptor.unused2)
+            && descriptor.is_consistent()
+            && descriptor.block_size_1.get() as usize == block_size
+            && let Ok(joliet_root) = parse_iso_directory_record(&descriptor.root_directory_record)
+            && joliet_root.directory
         {
-            // Make sure all the identical, but byte-swapped, fields agree.
-            if iso_header.set_size_1 == iso_header.set_size_2.get()
-                && iso_header.block_size_1 == iso_header.block_size_2.get()
-                && iso_header.volume_size_1 == iso_header.volume_size_2.get()
-                && iso_header.sequence_number_1 == iso_header.sequence_number_2.get()
-                && iso_header.path_table_size_1 == iso_header.path_table_size_2.get()
+            header.joliet_root_directory = Some(joliet_root);
+            break;
+        }
+    }
+
+    Ok(header)
+}
+
+/// Joliet volumes are identified by one of three UCS-2 escape sequences, for UCS-2 levels 1, 2 and 3
+fn is_joliet_escape_sequence(escape_sequences: &[u8]) -> bool {
+    matches!(escape_sequences, [b'%', b'/', b'@' | b'C' | b'E', ..])
+}
+
+/// Describes an ISO directory record
+#[derive(Debug, Default, Clone)]
+pub struct ISODirectoryRecord {
+    /// Size of the directory record
+    pub size: usize,
+    /// Logical block number of the file data, past any extended attribute record
+    pub extent: usize,
+    pub data_size: usize,
+    pub directory: bool,
+    /// If true, the file's data continues in the extent described by the next directory record
+    pub multi_extent: bool,
+    pub interleaved: bool,
+    pub identifier: Vec<u8>,
+    pub system_use: Vec<u8>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct ISODirectoryRecordBytes {
+    record_size: u8,
+    extended_attribute_size: u8,
+    extent_1: zerocopy::U32<LE>,
+    extent_2: zerocopy::U32<BE>,
+    data_size_1: zerocopy::U32<LE>,
+    data_size_2: zerocopy::U32<BE>,
+    timestamp: [u8; 7],
+    flags: u8,
+    unit_size: u8,
+    gap_size: u8,
+    sequence_number_1: zerocopy::U16<LE>,
+    sequence_number_2: zerocopy::U16<BE>,
+    identifier_size: u8,
+}
+
+/// Parses an ISO directory record
+///
+/// ```
+/// use binwalk_ng::formats::iso9660::parse_iso_directory_record;
+///
+/// let record = b"\x22\x00\x12\x00\x00\x00\x00\x00\x00\x12\x00\x08\x00\x00\x00\x00\x08\x00\
+///                \x7A\x01\x01\x00\x00\x00\x00\x02\x00\x00\x01\x00\x00\x01\x01\x00";
+///
+/// let directory = parse_iso_directory_record(record).unwrap();
+/// assert_eq!(directory.extent, 18);
+/// assert_eq!(directory.data_size, 2048);
+/// assert!(directory.directory);
+/// assert_eq!(directory.identifier, b"\x00");
+///
+/// assert!(parse_iso_directory_record(&record[..33]).is_err());
+/// ```
+pub fn parse_iso_directory_record(
+    record_data: &[u8],
+) -> Result<ISODirectoryRecord, StructureError> {
+    const FLAG_DIRECTORY: u8 = 0x02;
+    const FLAG_MULTI_EXTENT: u8 = 0x80;
+
+    let (record, _) =
+        ISODirectoryRecordBytes::ref_from_prefix(record_data).map_err(|_| StructureError)?;
+
+    let record_size = record.record_size as usize;
+    let identifier_size = record.identifier_size as usize;
+    let identifier_end = DIRECTORY_RECORD_SIZE + identifier_size;
+
+    // The system use field starts on an even offset
+    let system_use_start = identifier_end + (identifier_end % 2);
+
+    if identifier_size == 0
+        || record_size < identifier_end
+        || record.extent_1 != record.extent_2.get()
+        || record.data_size_1 != record.data_size_2.get()
+        || record.sequence_number_1 != record.sequence_number_2.get()
+    {
+        return Err(StructureError);
+    }
+
+    let record_data = record_data.get(..record_size).ok_or(StructureError)?;
+
+    Ok(ISODirectoryRecord {
+        size: record_size,
+        extent: record.extent_1.get() as usize + record.extended_attribute_size as usize,
+        data_size: record.data_size_1.get() as usize,
+        directory: (record.flags & FLAG_DIRECTORY) != 0,
+        multi_extent: (record.flags & FLAG_MULTI_EXTENT) != 0,
+        interleaved: record.unit_size != 0 || record.gap_size != 0,
+        identifier: record_data[DIRECTORY_RECORD_SIZE..identifier_end].to_vec(),
+        system_use: record_data
+            .get(system_use_start..)
+            .unwrap_or_default()
+            .to_vec(),
+    })
+}
+
+/// Describes a file, directory or symlink in an ISO image
+#[derive(Debug, Default, Clone)]
+pub struct ISOEntry {
+    pub name: String,
+    pub directory: bool,
+    /// Offsets and sizes of the file data, relative to the beginning of the ISO image
+    pub extents: Vec<(usize, usize)>,
+    pub symlink_target: Option<String>,
+    /// File permissions, if specified by Rock Ridge
+    pub mode: Option<u32>,
+    pub children: Vec<ISOEntry>,
+}
+
+/// Which set of directory records and file names to use when walking the directory tree
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ISONames {
+    Iso9660,
+    Joliet,
+    /// Rock Ridge entries follow the specified number of bytes in each system use field
+    RockRidge(usize),
+}
+
+/// Parses the ISO directory tree, preferring Rock Ridge names, then Joliet names, over plain ISO9660 names
+pub fn parse_iso_directory_tree(
+    iso_data: &[u8],
+    iso_header: &ISOHeader,
+) -> Result<Vec<ISOEntry>, StructureError> {
+    let mut tree = ISOTree {
+        iso_data,
+        block_size: iso_header.block_size,
+        names: ISONames::Iso9660,
+        visited_directories: HashSet::new(),
+    };
+
+    // Rock Ridge is identified by a SUSP "SP" entry in the system use field of the root directory's "." record
+    let root_records = tree.directory_records(&iso_header.root_directory)?;
+
+    if let Some(skip_size) = root_records
+        .first()
+        .and_then(|dot_record| susp_skip_size(&dot_record.system_use))
+    {
+        tree.names = ISONames::RockRidge(skip_size);
+    } else if let Some(joliet_root_directory) = &iso_header.joliet_root_directory {
+        tree.names = ISONames::Joliet;
+        return tree.read_directory(joliet_root_directory, 0);
+    }
+
+    tree.read_directory(&iso_header.root_directory, 0)
+}
+
+/// Walks the ISO directory tree
+struct ISOTree<'a> {
+    iso_data: &'a [u8],
+    block_size: usize,
+    names: ISONames,
+    /// Extents of directories that have already been read, to prevent infinite recursion
+    visited_directories: HashSet<usize>,
+}
+
+/// Information from a directory record's Rock Ridge entries
+#[derive(Debug, Default, Clone)]
+struct RockRidgeInfo {
+    name: Option<String>,
+    symlink_target: Option<String>,
+    mode: Option<u32>,
+}
+
+impl ISOTree<'_> {
+    /// Returns the ISO data described by a directory record
+    fn extent_data(&self, record: &ISODirectoryRecord) -> Result<&[u8], StructureError> {
+        let start = record.extent * self.block_size;
+
+        self.iso_data
+            .get(start..start + record.data_size)
+            .ok_or(StructureError)
+    }
+
+    /// Returns all directory records in a directory
+    fn directory_records(
+        &self,
+        directory: &ISODirectoryRecord,
+    ) -> Result<Vec<ISODirectoryRecord>, StructureError> {
+        let directory_data = self.extent_data(directory)?;
+        let mut records: Vec<ISODirectoryRecord> = Vec::new();
+        let mut record_offset: usize = 0;
+
+        while record_offset < directory_data.len() {
+            // Directory records don't span logical blocks; the rest of the block is zero-padded
+            if directory_data[record_offset] == 0 {
+                record_offset = (record_offset + 1).next_multiple_of(self.block_size);
+                continue;
+            }
+
+            let record = parse_iso_directory_record(&directory_data[record_offset..])?;
+            record_offset += record.size;
+            records.push(record);
+        }
+
+        Ok(records)
+    }
+
+    /// Recursively reads a directory, and all of its children
+    fn read_directory(
+        &mut self,
+        directory: &ISODirectoryRecord,
+        depth: usize,
+    ) -> Result<Vec<ISOEntry>, StructureError> {
+        // ISO9660 limits directory depth to 8, but Rock Ridge allows deeper trees
+        const MAX_DEPTH: usize = 64;
+        // The "." and ".." records have these identifiers
+        const CURRENT_DIRECTORY: &[u8] = b"\x00";
+        const PARENT_DIRECTORY: &[u8] = b"\x01";
+
+        if depth > MAX_DEPTH || !self.visited_directories.insert(directory.extent) {
+            return Err(StructureError);
+        }
+
+        let mut entries: Vec<ISOEntry> = Vec::new();
+        let mut continues_previous_entry = false;
+
+        for record in self.directory_records(directory)? {
+            if record.identifier == CURRENT_DIRECTORY || record.identifier == PARENT_DIRECTORY {
+                continue;
+            }
+
+            // Multi-extent files are described by consecutive directory records; append this extent to the previous entry
+            if std::mem::take(&mut continues_previous_entry)
+                && !record.directory
+                && let Some(previous_entry) = entries.last_mut()
+            {
+                previous_entry.extents.push(self.file_extent(&record)?);
+                continues_previous_entry = record.multi_extent;
+                continue;
+            }
+
+            let rock_ridge = match self.names {
+                ISONames::RockRidge(skip_size) => self.rock_ridge_info(&record, skip_size),
+                _ => RockRidgeInfo::default(),
+            };
+
+            let name = match rock_ridge.name {
+                Some(name) => name,
+                None => self.record_name(&record),
+            };
+
+            if name.is_empty() || name == "." || name == ".." {
+                warn!("Skipping ISO directory record with an invalid name");
+                continue;
+            }
+
+            if record.interleaved {
+                warn!("Skipping interleaved ISO file {name}");
+                continue;
+            }
+
+            let mut entry = ISOEntry {
+                name,
+                directory: record.directory,
+                symlink_target: rock_ridge.symlink_target,
+                mode: rock_ridge.mode,
+                ..Default::default()
+            };
+
+            if entry.directory {
+                entry.children = self.read_directory(&record, depth + 1)?;
+            } else if entry.symlink_target.is_none() {
+                entry.extents.push(self.file_extent(&record)?);
+                continues_previous_entry = record.multi_extent;
+            }
+
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// Returns the offset and size of a file's data, relative to the beginning of the ISO image
+    fn file_extent(&self, record: &ISODirectoryRecord) -> Result<(usize, usize), StructureError> {
+        // Validates that the extent is within the ISO image
+        self.extent_data(record)?;
+
+        Ok((record.extent * self.block_size, record.data_size))
+    }
+
+    /// Returns the ISO9660 or Joliet name for a directory record, sans the file version number
+    fn record_name(&self, record: &ISODirectoryRecord) -> String {
+        const VERSION_SEPARATOR: char = ';';
+
+        let mut name = if self.names == ISONames::Joliet {
+            get_utf16_string(&record.identifier, false)
+        } else {
+            String::from_utf8_lossy(&record.identifier).to_string()
+        };
+
+        if let Some(version_start) = name.rfind(VERSION_SEPARATOR) {
+            name.truncate(version_start);
+        }
+
+        // File names without an extension still have the '.' separator
+        if !record.directory && name.ends_with('.') {
+            name.pop();
+        }
+
+        name
+    }
+
+    /// Processes the Rock Ridge entries in a directory record's system use field, and any continuation areas
+    fn rock_ridge_info(&self, record: &ISODirectoryRecord, skip_size: usize) -> RockRidgeInfo {
+        // Don't follow an unreasonable number of continuation areas
+        const MAX_CONTINUATION_AREAS: usize = 16;
+        // Size of the SUSP entry header: signature, size, and version
+        const SUSP_ENTRY_HEADER_SIZE: usize = 4;
+
+        const NM_FLAG_CONTINUE: u8 = 0x01;
+
+        let mut info = RockRidgeInfo::default();
+        let mut name: Vec<u8> = Vec::new();
+        let mut has_name = false;
+        let mut symlink_components: Vec<u8> = Vec::new();
+        let mut has_symlink = false;
+
+        let mut area: &[u8] = record.system_use.get(skip_size..).unwrap_or_default();
+
+        for _ in 0..MAX_CONTINUATION_AREAS {
+            let mut continuation_area: Option<&[u8]> = None;
+            let mut entry_offset: usize = 0;
+
+            while let Some(entry_header) =
+                area.get(entry_offset..entry_offset + SUSP_ENTRY_HEADER_SIZE)
             {
-                return Ok(ISOHeader {
-                    image_size: iso_header.volume_size_1.get() as usize
-                        * iso_header.block_size_1.get() as usize,
-                });
+                let entry_size = entry_header[2] as usize;
+
+                if entry_size < SUSP_ENTRY_HEADER_SIZE {
+                    break;
+                }
+
+                let Some(entry_data) = area.get(entry_offset..entry_offset + entry_size) else {
+                    break;
+                };
+
+                let entry_body = &entry_data[SUSP_ENTRY_HEADER_SIZE..];
+
+                match &entry_data[..2] {
+                    // Alternate name; names that don't fit in a single entry are continued in the next NM entry
+                    b"NM" => {
+                        if let Some((&flags, name_data)) = entry_body.split_first()
+                            && (flags & !NM_FLAG_CONTINUE) == 0
+                        {
+                            name.extend_from_slice(name_data);
+                            has_name = true;
+                        }
+                    }
+                    // Symbolic link; component records may span multiple SL entries
+                    b"SL" => {
+                        if let Some((_, component_data)) = entry_body.split_first() {
+                            symlink_components.extend_from_slice(component_data);
+                            has_symlink = true;
+                        }
+                    }
+                    // POSIX file attributes
+                    b"PX" => {
+                        if let Some(mode_bytes) = entry_body.get(..4) {
+                            info.mode = Some(u32::from_le_bytes(mode_bytes.try_into().unwrap()));
+                        }
+                    }
+                    // Continuation area: block, offset and size, each as both-endian 32-bit values
+                    b"CE" => {
+                        if let Some(ce_data) = entry_body.get(..24) {
+                            let block = u32::from_le_bytes(ce_data[0..4].try_into().unwrap());
+                            let offset = u32::from_le_bytes(ce_data[8..12].try_into().unwrap());
+                            let size = u32::from_le_bytes(ce_data[16..20].try_into().unwrap());
+                            let start = (block as usize * self.block_size) + offset as usize;
+
+                            continuation_area = self.iso_data.get(start..start + size as usize);
+                        }
+                    }
+                    // Terminates the system use entries
+                    b"ST" => break,
+                    _ => (),
+                }
+
+                entry_offset += entry_size;
+            }
+
+            match continuation_area {
+                Some(next_area) => area = next_area,
+                None => break,
             }
         }
+
+        if has_name {
+            info.name = Some(String::from_utf8_lossy(&name).to_string());
+        }
+
+        if has_symlink {
+            info.symlink_target = Some(rock_ridge_symlink_target(&symlink_components));
+        }
+
+        info
     }
+}
+
+/// Builds a symlink target path from Rock Ridge SL component records
+fn rock_ridge_symlink_target(component_records: &[u8]) -> String {
+    const COMPONENT_CONTINUE: u8 = 0x01;
+    const COMPONENT_CURRENT: u8 = 0x02;
+    const COMPONENT_PARENT: u8 = 0x04;
+    const COMPONENT_ROOT: u8 = 0x08;
+
+    let mut target = String::new();
+    let mut needs_separator = false;
+    let mut record_offset: usize = 0;
+
+    while let Some(&[flags, size]) = component_records.get(record_offset..record_offset + 2) {
+        let Some(component) =
+            component_records.get(record_offset + 2..record_offset + 2 + size as usize)
+        else {
+            break;
+        };
 
-    Err(StructureError)
+        record_offset += 2 + size as usize;
+
+        if (flags & COMPONENT_ROOT) != 0 {
+            target.push('/');
+            needs_separator = false;
+            continue;
+        }
+
+        if needs_separator {
+            target.push('/');
+        }
+
+        if (flags & COMPONENT_CURRENT) != 0 {
+            target.push('.');
+        } else if (flags & COMPONENT_PARENT) != 0 {
+            target.push_str("..");
+        } else {
+            target.push_str(&String::from_utf8_lossy(component));
+        }
+
+        // A component that continues in the next component record has no separator
+        needs_separator = (flags & COMPONENT_CONTINUE) == 0;
+    }
+
+    target
+}
+
+/// Checks for a SUSP "SP" entry, and returns the number of bytes to skip in each system use field
+fn susp_skip_size(system_use: &[u8]) -> Option<usize> {
+    match system_use {
+        [b'S', b'P', 7, 1, 0xBE, 0xEF, skip_size, ..] => Some(*skip_size as usize),
+        _ => None,
+    }
 }
 
-/// Describes how to run the 7z utility to extract ISO images
+/// Defines the internal extractor for ISO images
 ///
 /// ```
 /// use std::io::ErrorKind;
@@ -109,6 +657,8 @@ pub fn parse_iso_header(iso_data: &[u8]) -> Result<ISOHeader, StructureError> {
 ///
 /// match iso9660_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -121,10 +671,166 @@ pub fn parse_iso_header(iso_data: &[u8]) -> Result<ISOHeader, StructureError> {
 ///     }
 /// }
 /// ```
-pub fn iso9660_extractor() -> extractors::Extractor {
-    // Same as the normal 7z extractor, but give the carved file an ISO file extension.
-    // The file extension matters, and 7z doesn't handle some ISO sub-formats correctly if the file extension is not '.iso'.
-    let mut extractor = sevenzip_extractor();
-    extractor.extension = "iso".to_string();
-    extractor
+pub fn iso9660_extractor() -> Extractor {
+    Extractor {
+        utility: ExtractorType::Internal(extract_iso9660),
+        ..Default::default()
+    }
+}
+
+/// Internal extractor for ISO images; the offset is that of the start of the image, not of the primary volume descriptor
+pub fn extract_iso9660(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> ExtractionResult {
+    let mut result = ExtractionResult::default();
+
+    let Ok(iso_header) = parse_iso_header(file_data.get(offset..).unwrap_or_default()) else {
+        return result;
+    };
+
+    let Some(iso_data) = file_data.get(offset..offset + iso_header.image_size) else {
+        return result;
+    };
+
+    let Ok(entries) = parse_iso_directory_tree(iso_data, &iso_header) else {
+        return result;
+    };
+
+    result.success = true;
+    result.size = Some(iso_header.image_size);
+
+    if let Some(output_directory) = output_directory {
+        let chroot = Chroot::new(output_directory);
+        result.success = extract_iso_entries(&chroot, iso_data, &entries, Path::new(""));
+    }
+
+    result
+}
+
+/// Recursively extracts ISO entries, returns false if any entry failed to extract
+fn extract_iso_entries(
+    chroot: &Chroot,
+    iso_data: &[u8],
+    entries: &[ISOEntry],
+    parent_directory: &Path,
+) -> bool {
+    // Permission bits, including setuid/setgid/sticky
+    const PERMISSIONS_MASK: u32 = 0o7777;
+
+    let mut success = true;
+
+    for entry in entries {
+        let file_path = chroot.safe_path_join(parent_directory, &entry.name);
+
+        if entry.directory {
+            success &= chroot.create_directory(&file_path)
+                && extract_iso_entries(chroot, iso_data, &entry.children, &file_path);
+        } else if let Some(symlink_target) = &entry.symlink_target {
+            success &= chroot.create_symlink(&file_path, symlink_target);
+        } else {
+            // File extents were validated when the directory tree was parsed
+            let mut file_success = chroot.create_file(&file_path, &[]);
+
+            for &(extent_offset, extent_size) in &entry.extents {
+                file_success = file_success
+                    && chroot.append_to_file(
+                        &file_path,
+                        &iso_data[extent_offset..extent_offset + extent_size],
+                    );
+            }
+
+            if file_success && let Some(mode) = entry.mode {
+                file_success = chroot.set_mode(&file_path, mode & PERMISSIONS_MASK);
+            }
+
+            success &= file_success;
+        }
+    }
+
+    success
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An image containing a single file, with no extensions (see tests/inputs/gen_iso9660.sh).
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/iso9660.bin");
+
+    /// As VALID, with a Rock Ridge name for the file.
+    static VALID_ROCK_RIDGE: &[u8] = include_bytes!("../../tests/inputs/iso9660_rock_ridge.bin");
+
+    /// As VALID, with a Joliet supplementary volume.
+    static VALID_JOLIET: &[u8] = include_bytes!("../../tests/inputs/iso9660_joliet.bin");
+
+    /// Logical block size of each fixture
+    const BLOCK_SIZE: usize = 2048;
+
+    /// Size of each fixture, in bytes
+    const IMAGE_SIZE: usize = 22 * BLOCK_SIZE;
+
+    /// Block holding the file data in each fixture
+    const FILE_BLOCK: usize = 21;
+
+    /// Extracts the ISO and returns the name of the one file in it
+    fn extract_one_file(iso: &[u8]) -> String {
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = extract_iso9660(iso, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(IMAGE_SIZE));
+
+        let files: Vec<_> = std::fs::read_dir(output_directory.path())
+            .unwrap()
+            .map(|entry| entry.unwrap())
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            std::fs::read(files[0].path()).unwrap(),
+            b"ISO9660 test file contents\n"
+        );
+
+        files[0].file_name().into_string().unwrap()
+    }
+
+    #[test]
+    fn iso9660_names() {
+        let signature = iso_parser(VALID, MAGIC_OFFSET).unwrap();
+        assert_eq!(signature.offset, 0);
+        assert_eq!(signature.size, IMAGE_SIZE);
+        assert!(
+            signature
+                .description
+                .contains("volume name: \"TEST_VOLUME\"")
+        );
+
+        // The file version number is not part of the extracted file name
+        assert_eq!(extract_one_file(VALID), "README.TXT");
+    }
+
+    #[test]
+    fn rock_ridge_names() {
+        assert_eq!(
+            extract_one_file(VALID_ROCK_RIDGE),
+            "readme with a long name.txt"
+        );
+    }
+
+    #[test]
+    fn joliet_names() {
+        let signature = iso_parser(VALID_JOLIET, MAGIC_OFFSET).unwrap();
+        assert!(signature.description.contains("Joliet extensions"));
+
+        assert_eq!(extract_one_file(VALID_JOLIET), "Joliet README.txt");
+    }
+
+    #[test]
+    fn truncated_image() {
+        let iso = &VALID[..FILE_BLOCK * BLOCK_SIZE];
+
+        // The header is intact, but the image is not
+        assert!(iso_parser(iso, MAGIC_OFFSET).is_ok());
+        assert!(!extract_iso9660(iso, 0, None).success);
+    }
 }
@@ -48,7 +48,9 @@ pub fn jboot_arm_parser(
     };
 
     // Actual header starts MAGIC_OFFSET bytes before the magic bytes
-    let header_start = offset - MAGIC_OFFSET;
+    let Some(header_start) = offset.checked_sub(MAGIC_OFFSET) else {
+        return Err(SignatureError);
+    };
 
     if let Some(jboot_data) = file_data.get(header_start..)
         && let Ok(arm_header) = parse_jboot_arm_header(jboot_data)
@@ -364,6 +366,8 @@ fn sch2_header_crc(sch2_header_bytes: &[u8]) -> Result<u32, StructureError> {
 ///
 /// match sch2_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -1,7 +1,7 @@
+use crate::common::is_offset_safe;
 use crate::extractors;
 use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
 use crate::structures::{Endianness, StructureError, dyn_endian};
-use aho_corasick::AhoCorasick;
 use crc32fast::Hasher;
 use zerocopy::{FromBytes, Immutable, KnownLayout, Unaligned};
 
@@ -27,11 +27,8 @@ pub fn jffs2_magic() -> Vec<Vec<u8>> {
 
 /// Parse and validate a JFFS2 image
 pub fn jffs2_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
-    // Useful constants
-    const MAX_PAGE_SIZE: usize = 0x20000;
+    // Require at least a few valid nodes to reduce false positives
     const MIN_VALID_NODE_COUNT: usize = 2;
-    const JFFS2_BIG_ENDIAN_MAGIC: &[u8; 2] = b"\x19\x85";
-    const JFFS2_LITTLE_ENDIAN_MAGIC: &[u8; 2] = b"\x85\x19";
 
     let mut result = SignatureResult {
         size: 0,
@@ -41,80 +38,95 @@ pub fn jffs2_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult,
         ..Default::default()
     };
 
-    // Parse this first JFFS2 node header to ensure correctness
-    if let Ok(first_node_header) = parse_jffs2_node_header(&file_data[offset..]) {
-        // The known end of JFFS2 data in the raw file data. This will be updated as we find more nodes.
-        let mut jffs2_eof: usize = offset + roundup(first_node_header.size);
-
-        // Make sure that jffs2_eof is sane
-        if jffs2_eof < file_data.len() {
-            // Start searching for subsequent JFFS2 nodes at the end of this node's data
-            let grep_offset: usize = jffs2_eof;
-
-            // Keep a count of how many valid nodes were found
-            let mut node_count: usize = 1;
-
-            // Determine which node magic bytes to search for based on the first node's endianness
-            let node_magic = match first_node_header.endianness {
-                Endianness::Big => JFFS2_BIG_ENDIAN_MAGIC,
-                Endianness::Little => JFFS2_LITTLE_ENDIAN_MAGIC,
-            };
-
-            // Need to grep for all JFFS2 nodes to figure out how big this file system really is
-            let grep = AhoCorasick::new(vec![node_magic]).unwrap();
-
-            // Find all matching JFFS2 node magic bytes
-            for magic_match in grep.find_overlapping_iter(&file_data[grep_offset..].to_vec()) {
-                // Calculate the start and end of the node header inside the file data
-                let header_start: usize = grep_offset + magic_match.start();
-                let header_end: usize = header_start + JFFS2_NODE_STRUCT_SIZE;
-
-                // This is a false positive that is inside the node data of a previously validated node
-                if header_start < jffs2_eof {
-                    continue;
-                }
-
-                // If we haven't found a valid header within MAX_PAGE_SIZE bytes, quit
-                if (header_start - jffs2_eof) > MAX_PAGE_SIZE {
-                    break;
-                }
-
-                // Get the node header's raw bytes
-                match file_data.get(header_start..header_end) {
-                    None => {
-                        break;
-                    }
-                    Some(node_header_data) => {
-                        // Parse this node's header
-                        if let Ok(this_node_header) = parse_jffs2_node_header(node_header_data) {
-                            node_count += 1;
-                            jffs2_eof = header_start + roundup(this_node_header.size);
-                        }
-                    }
-                }
-            }
-
-            // Make sure we've processed at least a few JFFS2 nodes
-            if node_count > MIN_VALID_NODE_COUNT {
-                result.size = jffs2_eof - result.offset;
-                result.description = format!(
-                    "{}, {}, nodes: {}, total size: {} bytes",
-                    result.description, first_node_header.endianness, node_count, result.size
-                );
-                return Ok(result);
-            }
-        }
+    let jffs2_data = file_data.get(offset..).ok_or(SignatureError)?;
+    let jffs2_header = parse_jffs2_header(jffs2_data).map_err(|_| SignatureError)?;
+
+    // Make sure we've processed at least a few JFFS2 nodes
+    if jffs2_header.node_count > MIN_VALID_NODE_COUNT {
+        result.size = jffs2_header.size;
+        result.description = format!(
+            "{}, {}, nodes: {}, total size: {} bytes",
+            result.description, jffs2_header.endianness, jffs2_header.node_count, result.size
+        );
+        return Ok(result);
     }
 
     Err(SignatureError)
 }
 
 /// JFFS2 nodes are padded to a 4 byte boundary
-fn roundup(num: usize) -> usize {
-    let base: f64 = 4.0;
-    let number: f64 = num as f64;
-    let div: f64 = number / base;
-    (base * div.ceil()) as usize
+const JFFS2_NODE_ALIGNMENT: usize = 4;
+
+/// Structure for storing info about a JFFS2 image
+#[derive(Debug, Clone)]
+pub struct JFFS2Header {
+    pub size: usize,
+    pub node_count: usize,
+    pub endianness: Endianness,
+}
+
+/// Walks the JFFS2 nodes at the start of jffs2_data, following each node's length field, to determine the size of
+/// the JFFS2 image.
+///
+/// Erased (0xFF) or zero-filled flash between nodes, such as at the end of an erase block, is skipped over; the walk
+/// stops at the first invalid node, or at the first node whose endianness does not match that of the first node.
+pub fn parse_jffs2_header(jffs2_data: &[u8]) -> Result<JFFS2Header, StructureError> {
+    // Max number of fill bytes between valid nodes, typically the size of an erase block
+    const MAX_FILL_SIZE: usize = 0x20000;
+
+    let first_node = parse_jffs2_node_header(jffs2_data)?;
+
+    let mut jffs2_header = JFFS2Header {
+        size: 0,
+        node_count: 0,
+        endianness: first_node.endianness,
+    };
+
+    let mut next_offset: usize = 0;
+    let mut previous_offset: Option<usize> = None;
+
+    while is_offset_safe(jffs2_data.len(), next_offset, previous_offset) {
+        // Skip over any fill bytes following the end of the previous node
+        let node_offset = match skip_fill(&jffs2_data[next_offset..], MAX_FILL_SIZE) {
+            Some(fill_size) => next_offset + fill_size,
+            None => break,
+        };
+
+        let Ok(node) = parse_jffs2_node_header(&jffs2_data[node_offset..]) else {
+            break;
+        };
+
+        // All nodes are the same endianness; the node must also fit within the available data
+        if node.endianness != jffs2_header.endianness
+            || node.size < JFFS2_NODE_STRUCT_SIZE
+            || jffs2_data
+                .get(node_offset..node_offset + node.size)
+                .is_none()
+        {
+            break;
+        }
+
+        jffs2_header.node_count += 1;
+        jffs2_header.size = node_offset + node.size;
+
+        previous_offset = Some(node_offset);
+        next_offset = node_offset + node.size.next_multiple_of(JFFS2_NODE_ALIGNMENT);
+    }
+
+    if jffs2_header.node_count == 0 {
+        return Err(StructureError);
+    }
+
+    Ok(jffs2_header)
+}
+
+/// Returns the number of 0xFF or 0x00 fill bytes at the start of data, in multiples of the node alignment.
+/// Returns None if there are more than max_fill_size fill bytes, or if the data ends before any non-fill bytes.
+fn skip_fill(data: &[u8], max_fill_size: usize) -> Option<usize> {
+    data.chunks_exact(JFFS2_NODE_ALIGNMENT)
+        .take(max_fill_size / JFFS2_NODE_ALIGNMENT + 1)
+        .position(|word| !word.iter().all(|b| *b == 0xFF) && !word.iter().all(|b| *b == 0))
+        .map(|word_count| word_count * JFFS2_NODE_ALIGNMENT)
 }
 
 /// JFFS2 node header size
@@ -124,6 +136,7 @@ pub const JFFS2_NODE_STRUCT_SIZE: usize = 12;
 #[derive(Debug, Clone)]
 pub struct JFFS2Node {
     pub size: usize,
+    pub node_type: u16,
     pub endianness: Endianness,
 }
 
@@ -146,6 +159,12 @@ pub fn parse_jffs2_node_header(node_data: &[u8]) -> Result<JFFS2Node, StructureE
     // Number of header bytes over which the header CRC is calculated
     const JFFS2_HEADER_CRC_SIZE: usize = 8;
 
+    // Set in the node type of all nodes that have not been obsoleted
+    const JFFS2_NODE_ACCURATE: u16 = 0x2000;
+
+    // Directory entry, inode, clean marker, padding, summary, xattr, and xattr reference nodes
+    const KNOWN_NODE_TYPES: [u16; 7] = [0xE001, 0xE002, 0x2003, 0x2004, 0x2006, 0xE008, 0xE009];
+
     // Parse the node header
     let (node_header, _) =
         JFFS2NodeBytes::ref_from_prefix(node_data).map_err(|_| StructureError)?;
@@ -156,6 +175,12 @@ pub fn parse_jffs2_node_header(node_data: &[u8]) -> Result<JFFS2Node, StructureE
         _ => return Err(StructureError),
     };
 
+    // Obsoleted nodes have the ACCURATE bit cleared, but are otherwise intact
+    let node_type = node_header.node_type.get(endianness);
+    if !KNOWN_NODE_TYPES.contains(&(node_type | JFFS2_NODE_ACCURATE)) {
+        return Err(StructureError);
+    }
+
     // Calculate the node header CRC
     let node_calculated_crc = jffs2_node_crc(&node_data[0..JFFS2_HEADER_CRC_SIZE]);
 
@@ -163,6 +188,7 @@ pub fn parse_jffs2_node_header(node_data: &[u8]) -> Result<JFFS2Node, StructureE
     if node_calculated_crc == node_header.crc.get(endianness) {
         return Ok(JFFS2Node {
             size: node_header.size.get(endianness) as usize,
+            node_type,
             endianness,
         });
     }
@@ -187,6 +213,8 @@ fn jffs2_node_crc(file_data: &[u8]) -> u32 {
 ///
 /// match jffs2_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -213,3 +241,70 @@ pub fn jffs2_extractor() -> extractors::Extractor {
         ..Default::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A big endian JFFS2 image with two erase blocks, the first of which ends in erased flash
+    /// (see tests/inputs/gen_jffs2.sh)
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/jffs2.bin");
+
+    /// Offsets of the directory entry and inode nodes in the second erase block
+    const SECOND_DIRENT_OFFSET: usize = 0x40C;
+    const SECOND_INODE_OFFSET: usize = 0x440;
+
+    /// Size of the image, up to the end of the last node
+    const IMAGE_SIZE: usize = 0x48D;
+
+    /// Rewrites the node header at node_offset with the specified type and size, and updates its CRC
+    fn rewrite_node_header(image: &mut [u8], node_offset: usize, node_type: u16, size: u32) {
+        image[node_offset + 2..node_offset + 4].copy_from_slice(&node_type.to_be_bytes());
+        image[node_offset + 4..node_offset + 8].copy_from_slice(&size.to_be_bytes());
+        let crc = jffs2_node_crc(&image[node_offset..node_offset + 8]);
+        image[node_offset + 8..node_offset + 12].copy_from_slice(&crc.to_be_bytes());
+    }
+
+    #[test]
+    fn node_walk() {
+        // Nodes following erased flash are part of the image
+        let header = parse_jffs2_header(VALID).unwrap();
+        assert_eq!(header.endianness, Endianness::Big);
+        assert_eq!(header.node_count, 6);
+        assert_eq!(header.size, IMAGE_SIZE);
+
+        // Data that is not a JFFS2 node is not
+        let mut image = VALID.to_vec();
+        image.extend(b"not a JFFS2 node");
+        assert_eq!(parse_jffs2_header(&image).unwrap().size, IMAGE_SIZE);
+        assert_eq!(jffs2_parser(&image, 0).unwrap().size, IMAGE_SIZE);
+
+        // Obsoleted nodes are intact, and part of the image
+        let mut image = VALID.to_vec();
+        rewrite_node_header(&mut image, SECOND_INODE_OFFSET, 0xC002, 0x4D);
+        assert_eq!(parse_jffs2_header(&image).unwrap().node_count, 6);
+    }
+
+    #[test]
+    fn invalid_nodes() {
+        // Unknown node type
+        let mut image = VALID.to_vec();
+        rewrite_node_header(&mut image, 0, 0xE00F, 12);
+        assert!(parse_jffs2_header(&image).is_err());
+
+        // A node too short to hold its own header is not followed
+        let mut image = VALID.to_vec();
+        rewrite_node_header(&mut image, SECOND_DIRENT_OFFSET, 0xE001, 4);
+        let header = parse_jffs2_header(&image).unwrap();
+        assert_eq!(header.node_count, 4);
+        assert_eq!(header.size, 0x40C);
+
+        // A node that extends past the end of the data
+        assert_eq!(
+            parse_jffs2_header(&VALID[..IMAGE_SIZE - 1])
+                .unwrap()
+                .node_count,
+            5
+        );
+    }
+}
@@ -58,6 +58,8 @@ pub fn jpeg_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, S
 ///
 /// match jpeg_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -89,7 +91,7 @@ pub fn extract_jpeg_image(
     let mut result = ExtractionResult::default();
 
     // Find the JPEG EOF to identify the total JPEG size
-    if let Some(jpeg_data_size) = get_jpeg_data_size(&file_data[offset..]) {
+    if let Some(jpeg_data_size) = get_jpeg_data_size(file_data.get(offset..).unwrap_or_default()) {
         result.size = Some(jpeg_data_size);
         result.success = true;
 
@@ -1,4 +1,4 @@
-use crate::common::get_cstring;
+use crate::common::get_cstring_bounded;
 use crate::extractors;
 use crate::signatures::{CONFIDENCE_LOW, CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
 use crate::structures::{Endianness, StructureError};
@@ -138,6 +138,7 @@ pub fn linux_kernel_version_parser(
     const PERIOD_OFFSET_3: usize = 18;
     const MIN_FILE_SIZE: usize = 100 * 1024;
     const MIN_VERSION_STRING_LENGTH: usize = 75;
+    const MAX_VERSION_STRING_LENGTH: usize = 1024;
     const GCC_VERSION_STRING: &str = "gcc ";
 
     let mut result = SignatureResult {
@@ -151,7 +152,8 @@ pub fn linux_kernel_version_parser(
     // Sanity check the size of the file; this automatically eliminates small text files that might match the magic bytes
     if file_size > MIN_FILE_SIZE {
         // Get the kernel version string
-        let kernel_version_string = get_cstring(&file_data[offset..]);
+        let kernel_version_string =
+            get_cstring_bounded(&file_data[offset..], MAX_VERSION_STRING_LENGTH);
 
         // Sanity check the length of the version string
         if kernel_version_string.len() > MIN_VERSION_STRING_LENGTH {
@@ -332,6 +334,8 @@ pub fn parse_linux_arm64_boot_image_header(
 ///
 /// match linux_kernel_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -356,5 +360,6 @@ pub fn linux_kernel_extractor() -> extractors::Extractor {
             "linux_kernel.elf".to_string(),
         ],
         exit_codes: vec![0],
+        ..Default::default()
     }
 }
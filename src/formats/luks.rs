@@ -1,11 +1,15 @@
 use crate::common::get_cstring;
 use crate::signatures::{CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
 use crate::structures::StructureError;
+use serde_json::Value;
 use zerocopy::{BE, FromBytes, Immutable, KnownLayout, Unaligned};
 
 /// Human readable description
 pub const DESCRIPTION: &str = "LUKS header";
 
+/// Size of the LUKS2 binary header, which is followed by the JSON metadata area
+const LUKS2_BINARY_HEADER_SIZE: usize = 4096;
+
 /// LUKS Headers start with these bytes
 pub fn luks_magic() -> Vec<Vec<u8>> {
     vec![b"LUKS\xBA\xBE".to_vec()]
@@ -23,41 +27,60 @@ pub fn luks_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, S
     };
 
     // If the header is parsed successfully, consider it valid
-    if let Ok(luks_header) = parse_luks_header(&file_data[offset..]) {
-        // Version 1 and version 2 have different header fields
-        if luks_header.version == 1 {
-            result.description = format!(
-                "{}, version: {}, cipher algorithm: {}, cipher mode: {}, hash fn: {}",
-                result.description,
-                luks_header.version,
-                luks_header.cipher_algorithm,
-                luks_header.cipher_mode,
-                luks_header.hashfn
-            );
+    let luks_data = file_data.get(offset..).unwrap_or_default();
+    let luks_header = parse_luks_header(luks_data).map_err(|_| SignatureError)?;
+
+    result.description = format!(
+        "{}, version: {}, cipher algorithm: {}, cipher mode: {}, key size: {} bits, hash fn: {}",
+        result.description,
+        luks_header.version,
+        luks_header.cipher_algorithm,
+        luks_header.cipher_mode,
+        luks_header.key_size,
+        luks_header.hashfn
+    );
+
+    // Version 2 headers include a JSON metadata area
+    if luks_header.version == 2 {
+        result.description = format!(
+            "{}, header size: {} bytes, JSON metadata size: {} bytes",
+            result.description,
+            luks_header.header_size,
+            luks_header.header_size - LUKS2_BINARY_HEADER_SIZE
+        );
+    }
+
+    // The header region includes the header(s) and key material, and is followed by the encrypted payload
+    if luks_header.payload_offset > 0 {
+        result.description = format!(
+            "{}, payload offset: {:#X}",
+            result.description, luks_header.payload_offset
+        );
+
+        if luks_header.payload_offset > luks_data.len() {
+            result.set_truncated(luks_header.payload_offset, luks_data.len());
         } else {
-            result.description = format!(
-                "{}, version: {}, header size: {} bytes, hash fn: {}",
-                result.description,
-                luks_header.version,
-                luks_header.header_size,
-                luks_header.hashfn
-            );
+            result.size = luks_header.payload_offset;
         }
-
-        return Ok(result);
     }
 
-    Err(SignatureError)
+    Ok(result)
 }
 
 /// Struct to store some useful LUKS info
 #[derive(Debug, Default, Clone)]
 pub struct LUKSHeader {
     pub version: u16,
+    /// Size of the LUKS2 binary header and JSON metadata area; not used by LUKS1
     pub header_size: usize,
+    /// Hash function for LUKS1, header checksum algorithm for LUKS2
     pub hashfn: String,
     pub cipher_mode: String,
     pub cipher_algorithm: String,
+    /// Size of the volume key, in bits
+    pub key_size: usize,
+    /// Offset of the encrypted payload, relative to the start of the header; 0 if unspecified (detached headers)
+    pub payload_offset: usize,
 }
 
 // https://en.wikipedia.org/wiki/Linux_Unified_Key_Setup
@@ -67,70 +90,235 @@ pub struct LUKSHeader {
 struct LUKSHeaderBytes {
     magic: [u8; 6],
     version: zerocopy::U16<BE>,
-    header_size: zerocopy::U64<BE>, // Only available in LUKS2
 }
 
-/// Partially parses a LUKS header
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct LUKS1HeaderBytes {
+    magic: [u8; 6],
+    version: zerocopy::U16<BE>,
+    cipher_name: [u8; 32],
+    cipher_mode: [u8; 32],
+    hash_spec: [u8; 32],
+    payload_offset: zerocopy::U32<BE>,
+    key_bytes: zerocopy::U32<BE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct LUKS2HeaderBytes {
+    magic: [u8; 6],
+    version: zerocopy::U16<BE>,
+    header_size: zerocopy::U64<BE>,
+    sequence_id: zerocopy::U64<BE>,
+    label: [u8; 48],
+    checksum_algorithm: [u8; 32],
+    salt: [u8; 64],
+    uuid: [u8; 40],
+    subsystem: [u8; 48],
+    header_offset: zerocopy::U64<BE>,
+}
+
+/// Parses a LUKS1 or LUKS2 header
 pub fn parse_luks_header(luks_data: &[u8]) -> Result<LUKSHeader, StructureError> {
-    // Start and end offsets of the cipher algorithm string
-    const CIPHER_ALGO_START: usize = 8;
-    const CIPHER_ALGO_END: usize = 40;
+    let (luks_base, _) = LUKSHeaderBytes::ref_from_prefix(luks_data).map_err(|_| StructureError)?;
 
-    // Start and end offsets of the cipher mode string
-    const CIPHER_MODE_START: usize = 40;
-    const CIPHER_MODE_END: usize = 72;
+    // Need to process v1 and v2 headers differently
+    match luks_base.version.get() {
+        1 => parse_luks1_header(luks_data),
+        2 => parse_luks2_header(luks_data),
+        _ => Err(StructureError),
+    }
+}
 
-    // Start and end offsets of the hash function string
-    const HASHFN_START: usize = 72;
-    const HASHFN_END: usize = 104;
+/// Parses a LUKS1 header
+fn parse_luks1_header(luks_data: &[u8]) -> Result<LUKSHeader, StructureError> {
+    // Payload offset is specified in 512 byte sectors
+    const SECTOR_SIZE: usize = 512;
+    // Volume keys are no larger than 512 bits
+    const MAX_KEY_BYTES: usize = 64;
 
-    // Minimum LUKS2 header size (assuming no JSON data)
-    const LUKS2_MIN_HEADER_SIZE: usize = 4032;
+    let (luks_header, _) =
+        LUKS1HeaderBytes::ref_from_prefix(luks_data).map_err(|_| StructureError)?;
 
-    let mut luks_hdr_info = LUKSHeader::default();
+    let luks_hdr_info = LUKSHeader {
+        version: luks_header.version.get(),
+        hashfn: get_cstring(&luks_header.hash_spec),
+        cipher_algorithm: get_cstring(&luks_header.cipher_name),
+        cipher_mode: get_cstring(&luks_header.cipher_mode),
+        key_size: luks_header.key_bytes.get() as usize * 8,
+        payload_offset: luks_header.payload_offset.get() as usize * SECTOR_SIZE,
+        ..Default::default()
+    };
 
-    let (luks_base, _) = LUKSHeaderBytes::ref_from_prefix(luks_data).map_err(|_| StructureError)?;
-    luks_hdr_info.version = luks_base.version.get();
-
-    // Both v1 and v2 include the hash function string at the same offset
-    if let Some(hashfn_bytes) = luks_data.get(HASHFN_START..HASHFN_END) {
-        luks_hdr_info.hashfn = get_cstring(hashfn_bytes);
-
-        // Make sure there was actually a string at the expected hash function offset
-        if !luks_hdr_info.hashfn.is_empty() {
-            // Need to process v1 and v2 headers differently
-            if luks_hdr_info.version == 1 {
-                // Get the cipher algorithm string
-                if let Some(cipher_algo_bytes) = luks_data.get(CIPHER_ALGO_START..CIPHER_ALGO_END) {
-                    luks_hdr_info.cipher_algorithm = get_cstring(cipher_algo_bytes);
-
-                    // Get the cipher mode string
-                    if let Some(cipher_mode_bytes) =
-                        luks_data.get(CIPHER_MODE_START..CIPHER_MODE_END)
-                    {
-                        luks_hdr_info.cipher_mode = get_cstring(cipher_mode_bytes);
-
-                        // Make sure there were valid strings specified for both cipher algo and cipher mode
-                        if !luks_hdr_info.cipher_mode.is_empty()
-                            && !luks_hdr_info.cipher_algorithm.is_empty()
-                        {
-                            return Ok(luks_hdr_info);
-                        }
-                    }
-                }
-            } else if luks_hdr_info.version == 2 {
-                // v2 doesn't have the same string entries, but does include a header size
-                luks_hdr_info.header_size = luks_base.header_size.get() as usize;
-
-                // Sanity check the header size
-                if luks_hdr_info.header_size > LUKS2_MIN_HEADER_SIZE
-                    && luks_hdr_info.header_size < luks_data.len()
-                {
-                    return Ok(luks_hdr_info);
-                }
-            }
-        }
+    // Make sure there were valid strings specified for the hash function, cipher algorithm and cipher mode
+    if luks_hdr_info.hashfn.is_empty()
+        || luks_hdr_info.cipher_algorithm.is_empty()
+        || luks_hdr_info.cipher_mode.is_empty()
+        || luks_hdr_info.key_size == 0
+        || luks_hdr_info.key_size > MAX_KEY_BYTES * 8
+    {
+        return Err(StructureError);
     }
 
-    Err(StructureError)
+    Ok(luks_hdr_info)
+}
+
+/// Parses a LUKS2 binary header, and the data segment and key slot descriptions in its JSON metadata
+fn parse_luks2_header(luks_data: &[u8]) -> Result<LUKSHeader, StructureError> {
+    // The binary header and JSON metadata area are one of these sizes
+    const VALID_HEADER_SIZES_KB: [usize; 9] = [16, 32, 64, 128, 256, 512, 1024, 2048, 4096];
+
+    let (luks_header, _) =
+        LUKS2HeaderBytes::ref_from_prefix(luks_data).map_err(|_| StructureError)?;
+
+    let mut luks_hdr_info = LUKSHeader {
+        version: luks_header.version.get(),
+        header_size: usize::try_from(luks_header.header_size.get()).map_err(|_| StructureError)?,
+        hashfn: get_cstring(&luks_header.checksum_algorithm),
+        ..Default::default()
+    };
+
+    // The primary header is at offset 0; the secondary header has different magic bytes
+    if luks_hdr_info.hashfn.is_empty()
+        || luks_header.header_offset.get() != 0
+        || !VALID_HEADER_SIZES_KB
+            .iter()
+            .any(|size| size * 1024 == luks_hdr_info.header_size)
+    {
+        return Err(StructureError);
+    }
+
+    // The JSON metadata is a NULL-terminated string, padded out to the end of the header
+    let json_data = luks_data
+        .get(LUKS2_BINARY_HEADER_SIZE..luks_hdr_info.header_size)
+        .ok_or(StructureError)?;
+    let metadata: Value =
+        serde_json::from_str(&get_cstring(json_data)).map_err(|_| StructureError)?;
+
+    // The first data segment describes the payload location and cipher
+    let segment = metadata
+        .get("segments")
+        .and_then(Value::as_object)
+        .and_then(|segments| segments.values().next())
+        .ok_or(StructureError)?;
+
+    let encryption = segment
+        .get("encryption")
+        .and_then(Value::as_str)
+        .ok_or(StructureError)?;
+
+    // Ciphers are specified as "<algorithm>-<mode>", e.g. "aes-xts-plain64"
+    let (cipher_algorithm, cipher_mode) = encryption.split_once('-').unwrap_or((encryption, ""));
+    luks_hdr_info.cipher_algorithm = cipher_algorithm.to_string();
+    luks_hdr_info.cipher_mode = cipher_mode.to_string();
+
+    // Offsets and sizes are stored as strings, since they may not fit in a JSON number
+    luks_hdr_info.payload_offset = segment
+        .get("offset")
+        .and_then(Value::as_str)
+        .and_then(|offset| offset.parse().ok())
+        .ok_or(StructureError)?;
+
+    // Key slots store the size of the volume key, in bytes
+    luks_hdr_info.key_size = metadata
+        .get("keyslots")
+        .and_then(Value::as_object)
+        .and_then(|keyslots| keyslots.values().next())
+        .and_then(|keyslot| keyslot.get("key_size"))
+        .and_then(Value::as_u64)
+        .unwrap_or_default() as usize
+        * 8;
+
+    Ok(luks_hdr_info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// LUKS1 volume with a 512-bit aes-xts-plain64 key and its payload at sector 4096 (see tests/inputs/gen_luks.sh)
+    static VALID_LUKS1: &[u8] = include_bytes!("../../tests/inputs/luks1.bin");
+    /// Primary and secondary LUKS2 headers of a volume whose payload is at 16 MiB (see tests/inputs/gen_luks.sh)
+    static VALID_LUKS2: &[u8] = include_bytes!("../../tests/inputs/luks2.bin");
+
+    /// Returns the primary LUKS2 header with its JSON metadata replaced
+    fn with_luks2_json(json: &str) -> Vec<u8> {
+        let mut header = VALID_LUKS2[..LUKS2_BINARY_HEADER_SIZE].to_vec();
+        header.extend(json.as_bytes());
+        header.resize(0x4000, 0);
+        header
+    }
+
+    #[test]
+    fn luks1() {
+        let header = parse_luks_header(VALID_LUKS1).unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!(header.cipher_algorithm, "aes");
+        assert_eq!(header.cipher_mode, "xts-plain64");
+        assert_eq!(header.hashfn, "sha256");
+        assert_eq!(header.key_size, 512);
+        assert_eq!(header.payload_offset, 4096 * 512);
+    }
+
+    #[test]
+    fn luks1_embedded() {
+        let mut file_data = vec![0; 0x200];
+        file_data.extend_from_slice(VALID_LUKS1);
+
+        let signature = luks_parser(&file_data, 0x200).unwrap();
+        assert_eq!(signature.offset, 0x200);
+        assert_eq!(signature.size, 4096 * 512);
+        assert!(signature.description.contains("key size: 512 bits"));
+    }
+
+    #[test]
+    fn luks1_invalid_key_size() {
+        let mut header = VALID_LUKS1.to_vec();
+        header[108..112].copy_from_slice(&0_u32.to_be_bytes());
+        assert!(parse_luks_header(&header).is_err());
+    }
+
+    #[test]
+    fn luks2() {
+        let header = parse_luks_header(VALID_LUKS2).unwrap();
+        assert_eq!(header.version, 2);
+        assert_eq!(header.header_size, 0x4000);
+        assert_eq!(header.cipher_algorithm, "aes");
+        assert_eq!(header.cipher_mode, "xts-plain64");
+        assert_eq!(header.hashfn, "sha256");
+        assert_eq!(header.key_size, 512);
+        assert_eq!(header.payload_offset, 0x1000000);
+    }
+
+    #[test]
+    fn luks2_truncated() {
+        let signature = luks_parser(&VALID_LUKS2[..0x4000], 0).unwrap();
+        assert_eq!(signature.size, 0x4000);
+        assert_eq!(signature.declared_size, Some(0x1000000));
+        assert!(
+            signature
+                .description
+                .contains("header size: 16384 bytes, JSON metadata size: 12288 bytes")
+        );
+    }
+
+    #[test]
+    fn luks2_invalid_header_size() {
+        let mut header = VALID_LUKS2.to_vec();
+        header[8..16].copy_from_slice(&0x5000_u64.to_be_bytes());
+        assert!(parse_luks_header(&header).is_err());
+    }
+
+    #[test]
+    fn luks2_invalid_json() {
+        assert!(parse_luks_header(&with_luks2_json(r#"{"segments":{}}"#)).is_err());
+        assert!(parse_luks_header(&with_luks2_json("not json")).is_err());
+    }
+
+    #[test]
+    fn luks2_secondary_header() {
+        // The secondary header records its own offset, which is not 0
+        assert!(parse_luks_header(&VALID_LUKS2[0x4000..]).is_err());
+    }
 }
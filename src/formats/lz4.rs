@@ -31,16 +31,31 @@ pub fn lz4_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
     };
 
     // Sanity check the size of available data
-    if let Ok(lz4_file_header) = parse_lz4_file_header(&file_data[offset..]) {
+    if let Ok(lz4_file_header) = parse_lz4_file_header(file_data.get(offset..).unwrap_or_default())
+    {
         // LZ4 data starts immediately after the LZ4 header
         if let Some(lz4_data) = file_data.get(offset + lz4_file_header.header_size..) {
             // Determine the size of the actual LZ4 data by processing the data blocks that immediately follow the file header
-            if let Ok(lz4_data_size) =
-                get_lz4_data_size(lz4_data, lz4_file_header.block_checksum_present)
-            {
+            if let Ok(lz4_data_size) = get_lz4_data_size(
+                lz4_data,
+                lz4_file_header.block_checksum_present,
+                lz4_file_header.max_block_size,
+            ) {
                 // Set the size of the header and the LZ4 data
                 result.size = lz4_file_header.header_size + lz4_data_size;
 
+                if let Some(content_size) = lz4_file_header.content_size {
+                    result.description = format!(
+                        "{}, decompressed size: {} bytes",
+                        result.description, content_size
+                    );
+                }
+
+                // Blocks which reference data from previous blocks
+                if !lz4_file_header.block_independence {
+                    result.description = format!("{}, linked blocks", result.description);
+                }
+
                 // If this flag is set, an additional 4-byte checksum will be present at the end of the LZ4 data
                 if lz4_file_header.content_checksum_present {
                     result.size += CONTENT_CHECKSUM_LEN;
@@ -59,7 +74,11 @@ pub fn lz4_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
 }
 
 /// Processes the LZ4 data blocks and returns the size of the raw LZ4 data
-fn get_lz4_data_size(lz4_data: &[u8], checksum_present: bool) -> Result<usize, SignatureError> {
+fn get_lz4_data_size(
+    lz4_data: &[u8],
+    checksum_present: bool,
+    max_block_size: usize,
+) -> Result<usize, SignatureError> {
     let mut lz4_data_size: usize = 0;
     let mut last_lz4_data_size = None;
     let available_data = lz4_data.len();
@@ -77,6 +96,10 @@ fn get_lz4_data_size(lz4_data: &[u8], checksum_present: bool) -> Result<usize, S
                     Err(_) => {
                         break;
                     }
+                    // Blocks, compressed or not, can't be larger than the maximum block size
+                    Ok(block_header) if block_header.data_size > max_block_size => {
+                        break;
+                    }
                     Ok(block_header) => {
                         // Update offsets
                         last_lz4_data_size = Some(lz4_data_size);
@@ -101,8 +124,13 @@ fn get_lz4_data_size(lz4_data: &[u8], checksum_present: bool) -> Result<usize, S
 #[derive(Debug, Default, Clone)]
 pub struct LZ4FileHeader {
     pub header_size: usize,
+    /// If false, blocks may reference data decompressed from previous blocks
+    pub block_independence: bool,
     pub block_checksum_present: bool,
     pub content_checksum_present: bool,
+    pub max_block_size: usize,
+    pub content_size: Option<u64>,
+    pub dictionary_id: Option<u32>,
 }
 
 #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
@@ -120,12 +148,17 @@ pub fn parse_lz4_file_header(lz4_data: &[u8]) -> Result<LZ4FileHeader, Structure
     const LZ4_STRUCT_SIZE: usize = 6;
 
     const BD_RESERVED_MASK: u8 = 0b10001111;
+    const BD_BLOCK_SIZE_MASK: u8 = 0b01110000;
+    const BD_BLOCK_SIZE_SHIFT: u8 = 4;
     const FLAGS_RESERVED_MASK: u8 = 0b00000010;
+    const FLAGS_VERSION_MASK: u8 = 0b11000000;
+    const SUPPORTED_VERSION: u8 = 0b01000000;
 
     const FLAG_DICTIONARY_PRESENT: u8 = 0b00000001;
     const FLAG_CONTENT_SIZE_PRESENT: u8 = 0b00001000;
     const FLAG_BLOCK_CHECKSUM_PRESENT: u8 = 0b00010000;
     const FLAG_CONTENT_CHECKSUM_PRESENT: u8 = 0b00000100;
+    const FLAG_BLOCK_INDEPENDENCE: u8 = 0b00100000;
 
     const DICTIONARY_LEN: usize = 4;
     const CONTENT_SIZE_LEN: usize = 8;
@@ -135,10 +168,24 @@ pub fn parse_lz4_file_header(lz4_data: &[u8]) -> Result<LZ4FileHeader, Structure
     // Parse the header
     let (lz4_header, _) = LZ4HeaderBytes::ref_from_prefix(lz4_data).map_err(|_| StructureError)?;
 
-    // Make sure the reserved bits aren't set
-    if (lz4_header.flags & FLAGS_RESERVED_MASK) != 0 || (lz4_header.bd & BD_RESERVED_MASK) != 0 {
+    // Make sure the reserved bits aren't set, and that this is a known frame version
+    if (lz4_header.flags & FLAGS_RESERVED_MASK) != 0
+        || (lz4_header.bd & BD_RESERVED_MASK) != 0
+        || (lz4_header.flags & FLAGS_VERSION_MASK) != SUPPORTED_VERSION
+    {
         return Err(StructureError);
     }
+
+    // Block maximum sizes are 64KB, 256KB, 1MB, or 4MB; other values are invalid
+    lz4_hdr_info.max_block_size = match (lz4_header.bd & BD_BLOCK_SIZE_MASK) >> BD_BLOCK_SIZE_SHIFT
+    {
+        4 => 64 * 1024,
+        5 => 256 * 1024,
+        6 => 1024 * 1024,
+        7 => 4 * 1024 * 1024,
+        _ => return Err(StructureError),
+    };
+
     /*
      * Calculate the start and end of data used to calculate the header CRC.
      * CRC is calculated over the entire descriptor frame, including optional fields,
@@ -172,6 +219,24 @@ pub fn parse_lz4_file_header(lz4_data: &[u8]) -> Result<LZ4FileHeader, Structure
                     (lz4_header.flags & FLAG_BLOCK_CHECKSUM_PRESENT) != 0;
                 lz4_hdr_info.content_checksum_present =
                     (lz4_header.flags & FLAG_CONTENT_CHECKSUM_PRESENT) != 0;
+                lz4_hdr_info.block_independence = (lz4_header.flags & FLAG_BLOCK_INDEPENDENCE) != 0;
+
+                // Optional fields immediately follow the FLG and BD bytes: content size, then dictionary ID
+                let mut optional_fields = &crc_data[LZ4_STRUCT_SIZE - MAGIC_SIZE..];
+
+                if (lz4_header.flags & FLAG_CONTENT_SIZE_PRESENT) != 0 {
+                    let (content_size, remaining_fields) =
+                        zerocopy::U64::<LE>::read_from_prefix(optional_fields)
+                            .map_err(|_| StructureError)?;
+                    lz4_hdr_info.content_size = Some(content_size.get());
+                    optional_fields = remaining_fields;
+                }
+
+                if (lz4_header.flags & FLAG_DICTIONARY_PRESENT) != 0 {
+                    let (dictionary_id, _) = zerocopy::U32::<LE>::read_from_prefix(optional_fields)
+                        .map_err(|_| StructureError)?;
+                    lz4_hdr_info.dictionary_id = Some(dictionary_id.get());
+                }
 
                 return Ok(lz4_hdr_info);
             }
@@ -234,6 +299,8 @@ pub fn parse_lz4_block_header(
 ///
 /// match lz4_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -281,6 +348,7 @@ fn lz4_decompress(
             result.success = true;
             let remaining = decoder.into_inner();
             result.size = Some(data.len() - remaining.len());
+            result.set_compression_ratio(data.len() - remaining.len(), decompressed.len());
             if let Some(output_directory) = output_directory {
                 let chroot = Chroot::new(output_directory);
                 result.success = chroot.create_file(OUTPUT_FILE_NAME, &decompressed);
@@ -291,3 +359,65 @@ fn lz4_decompress(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An LZ4 frame of several linked 64KB blocks, with a content size and content checksum (see
+    /// tests/inputs/gen_lz4_linked.sh).
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/lz4_linked.bin");
+
+    /// The data compressed in VALID
+    fn payload() -> Vec<u8> {
+        (0..300_000u32)
+            .map(|n| (n % 97) as u8 ^ (n / 1000) as u8)
+            .collect()
+    }
+
+    #[test]
+    fn frame_header() {
+        let header = parse_lz4_file_header(VALID).unwrap();
+        assert_eq!(header.header_size, 15);
+        assert_eq!(header.content_size, Some(300_000));
+        assert_eq!(header.max_block_size, 64 * 1024);
+        assert!(!header.block_independence);
+        assert!(!header.block_checksum_present);
+        assert!(header.content_checksum_present);
+
+        // A bad header checksum is rejected
+        let mut bad_checksum = VALID.to_vec();
+        bad_checksum[14] ^= 0xFF;
+        assert!(parse_lz4_file_header(&bad_checksum).is_err());
+
+        // Blocks larger than the maximum block size are rejected
+        let mut oversized_block = VALID.to_vec();
+        oversized_block[17] = 0x01;
+        assert!(lz4_parser(&oversized_block, 0).is_err());
+    }
+
+    #[test]
+    fn linked_blocks() {
+        let mut file_data = VALID.to_vec();
+        file_data.extend([0xFF; 16]);
+
+        let signature = lz4_parser(&file_data, 0).unwrap();
+        assert_eq!(signature.size, VALID.len());
+        assert_eq!(
+            signature.description,
+            format!(
+                "LZ4 compressed data, decompressed size: 300000 bytes, linked blocks, total size: {} bytes",
+                VALID.len()
+            )
+        );
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = lz4_decompress(&file_data, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(VALID.len()));
+        assert_eq!(
+            std::fs::read(output_directory.path().join("decompressed.bin")).unwrap(),
+            payload()
+        );
+    }
+}
@@ -3,6 +3,7 @@ use crate::extractors::Chroot;
 use crate::extractors::{ExtractionResult, Extractor, ExtractorType};
 use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
 use crate::structures::StructureError;
+use std::io::{self, BufWriter, Write};
 use std::path::Path;
 use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
 
@@ -153,8 +154,8 @@ fn parse_compressedv1_block_header(lzfse_data: &[u8]) -> Result<LZFSEBlock, Stru
     let (header, _) = BlockV1Header::ref_from_prefix(lzfse_data).map_err(|_| StructureError)?;
     Ok(LZFSEBlock {
         eof: false,
-        data_size: (header.n_literal_payload_bytes.get() + header.n_lmd_payload_bytes.get())
-            as usize,
+        data_size: header.n_literal_payload_bytes.get() as usize
+            + header.n_lmd_payload_bytes.get() as usize,
         header_size: HEADER_SIZE,
         uncompressed_size: header.n_raw_bytes.get() as usize,
     })
@@ -181,6 +182,12 @@ fn parse_compressedv2_block_header(lzfse_data: &[u8]) -> Result<LZFSEBlock, Stru
     let (block_header, _) =
         BlockV2Header::ref_from_prefix(lzfse_data).map_err(|_| StructureError)?;
 
+    // The reported header size includes the fixed size header fields
+    let header_size = block_header.header_size.get() as usize;
+    if header_size < std::mem::size_of::<BlockV2Header>() {
+        return Err(StructureError);
+    }
+
     let n_lmd_payload_bytes =
         (block_header.packed_field_2.get() >> LMD_PAYLOAD_SHIFT) & PAYLOAD_MASK;
     let n_literal_payload_bytes =
@@ -189,7 +196,7 @@ fn parse_compressedv2_block_header(lzfse_data: &[u8]) -> Result<LZFSEBlock, Stru
     Ok(LZFSEBlock {
         eof: false,
         data_size: (n_lmd_payload_bytes + n_literal_payload_bytes) as usize,
-        header_size: block_header.header_size.get() as usize,
+        header_size,
         uncompressed_size: block_header.uncompressed_size.get() as usize,
     })
 }
@@ -224,6 +231,8 @@ fn parse_compressedlzvn_block_header(lzfse_data: &[u8]) -> Result<LZFSEBlock, St
 ///
 /// match lzfse_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -239,6 +248,7 @@ fn parse_compressedlzvn_block_header(lzfse_data: &[u8]) -> Result<LZFSEBlock, St
 pub fn lzfse_extractor() -> Extractor {
     Extractor {
         utility: ExtractorType::Internal(lzfse_decompress),
+        in_memory: Some(lzfse_decompress_in_memory),
         ..Default::default()
     }
 }
@@ -250,25 +260,69 @@ fn lzfse_decompress(
 ) -> ExtractionResult {
     const OUTPUT_FILE_NAME: &str = "decompressed.bin";
 
+    match output_directory {
+        // Dry run, the decompressed data is discarded
+        None => lzfse_decode(file_data, offset, &mut io::sink()),
+        Some(output_directory) => {
+            match Chroot::new(output_directory).create_file_writer(OUTPUT_FILE_NAME) {
+                None => ExtractionResult::default(),
+                Some(file) => lzfse_decode(file_data, offset, &mut BufWriter::new(file)),
+            }
+        }
+    }
+}
+
+fn lzfse_decompress_in_memory(file_data: &[u8], offset: usize) -> ExtractionResult {
+    let mut decompressed_data: Vec<u8> = Vec::new();
+
+    let mut exresult = lzfse_decode(file_data, offset, &mut decompressed_data);
+
+    if exresult.success {
+        exresult.data = Some(decompressed_data);
+    }
+
+    exresult
+}
+
+/// Decompresses an LZFSE stream, writing the decompressed data to `output`
+fn lzfse_decode(file_data: &[u8], offset: usize, output: &mut impl Write) -> ExtractionResult {
+    // The whole stream is decompressed into a single buffer, so put an upper limit on its size
+    const MAX_DECOMPRESSED_SIZE: usize = 1024 * 1024 * 1024;
+
     let mut exresult = ExtractionResult::default();
 
-    let data = &file_data[offset..];
+    let data = file_data.get(offset..).unwrap_or_default();
     let mut dst_size = 0;
+    let mut eof_found = false;
     let src_size = {
         let mut remaining_data = data;
         while let Ok(lzfse_block) = parse_lzfse_block_header(remaining_data) {
             let block_size = lzfse_block.header_size + lzfse_block.data_size;
-            dst_size += lzfse_block.uncompressed_size;
-            remaining_data = &remaining_data[block_size..];
+
+            // Block sizes come from the (untrusted) block header; the block must fit in the remaining data
+            let Some(next_block_data) = remaining_data.get(block_size..) else {
+                return exresult;
+            };
+
+            // So are uncompressed sizes; cap their total before allocating the output buffer
+            match dst_size.checked_add(lzfse_block.uncompressed_size) {
+                Some(total_size) if total_size <= MAX_DECOMPRESSED_SIZE => dst_size = total_size,
+                _ => return exresult,
+            }
+            remaining_data = next_block_data;
             if lzfse_block.eof {
+                eof_found = true;
                 break;
             }
-            // We'll never return a header with zero size, but if we did, this would be an infinite loop
-            assert!(block_size > 0);
         }
         data.len() - remaining_data.len()
     };
 
+    // The stream must be terminated by an end-of-stream block
+    if !eof_found {
+        return exresult;
+    }
+
     // The LZFSE API can't differentiate between decompressing exactly the right amount of data and
     // truncation (see https://github.com/lzfse/lzfse/issues/5#issuecomment-237134992), so
     // give it an extra byte so we can differentiate.
@@ -276,13 +330,65 @@ fn lzfse_decompress(
     if let Ok(actual_len) = lzfse::decode_buffer(&data[..src_size], &mut dst)
         && actual_len == dst_size
     {
-        exresult.success = true;
         exresult.size = Some(dst_size);
-        if let Some(output_directory) = output_directory {
-            let chroot = Chroot::new(output_directory);
-            exresult.success = chroot.create_file(OUTPUT_FILE_NAME, &dst[..dst_size]);
-        }
+        exresult.set_compression_ratio(src_size, dst_size);
+        exresult.success = output.write_all(&dst[..dst_size]).is_ok() && output.flush().is_ok();
     }
 
     exresult
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// LZVN compressed text (see tests/inputs/gen_lzfse.sh)
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/lzfse.bin");
+
+    #[test]
+    fn in_memory_extraction() {
+        let signature = lzfse_parser(VALID, 0).unwrap();
+
+        let result =
+            crate::extractors::execute_in_memory(VALID, &signature, &Some(lzfse_extractor()));
+
+        let mut expected = vec![b' '; 1000];
+        for _ in 0..100 {
+            expected.extend(b"Testing, 1, 2, 3...\n");
+        }
+
+        assert!(result.success);
+        assert_eq!(result.data, Some(expected));
+    }
+
+    #[test]
+    fn absurd_block_sizes_are_rejected() {
+        // An uncompressed block claiming far more data than there is, followed by an end-of-stream block
+        let mut uncompressed_block = b"bvx-".to_vec();
+        uncompressed_block.extend(0xFFFFFFF0_u32.to_le_bytes());
+        uncompressed_block.extend(b"data");
+        uncompressed_block.extend(b"bvx$");
+
+        // A version 2 compressed block whose header size is too small to be valid, followed by an end-of-stream block
+        let mut compressed_v2_block = b"bvx2".to_vec();
+        compressed_v2_block.extend([0; 24]);
+        compressed_v2_block.extend(0_u32.to_le_bytes());
+        compressed_v2_block.extend([0; 4]);
+        compressed_v2_block.extend(b"bvx$");
+
+        for file_data in [uncompressed_block, compressed_v2_block] {
+            assert!(lzfse_parser(&file_data, 0).is_err());
+            assert!(!lzfse_decompress(&file_data, 0, None).success);
+        }
+    }
+
+    #[test]
+    fn absurd_uncompressed_sizes_are_rejected() {
+        // An LZVN block claiming to decompress to 4GB, more than the output buffer is allowed to be
+        let mut file_data = VALID.to_vec();
+        file_data[4..8].copy_from_slice(&0xFFFFFFFF_u32.to_le_bytes());
+
+        assert!(lzfse_parser(&file_data, 0).is_ok());
+        assert!(!lzfse_decompress(&file_data, 0, None).success);
+    }
+}
@@ -0,0 +1,382 @@
+use crate::common::{crc32_seed, is_offset_safe};
+use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
+use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
+use crate::structures::StructureError;
+use liblzma::stream::Stream;
+use std::io::{Read, Write};
+use std::path::Path;
+use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
+
+/// Human readable description
+pub const DESCRIPTION: &str = "lzip compressed data";
+
+/// Size of the lzip member header
+const LZIP_HEADER_SIZE: usize = 6;
+
+/// Size of the lzip member footer
+const LZIP_FOOTER_SIZE: usize = 20;
+
+/// lzip magic bytes, plus the version number (always 1)
+pub fn lzip_magic() -> Vec<Vec<u8>> {
+    vec![b"LZIP\x01".to_vec()]
+}
+
+/// Validates lzip signatures
+pub fn lzip_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
+    let members = parse_lzip_members(file_data.get(offset..).unwrap_or_default())
+        .map_err(|_| SignatureError)?;
+
+    let total_size: usize = members.iter().map(|member| member.member_size).sum();
+    let decompressed_size: u64 = members.iter().map(|member| member.data_size).sum();
+
+    Ok(SignatureResult {
+        offset,
+        size: total_size,
+        description: format!(
+            "{}, dictionary size: {} bytes, member count: {}, decompressed size: {} bytes, total size: {} bytes",
+            DESCRIPTION,
+            members[0].dictionary_size,
+            members.len(),
+            decompressed_size,
+            total_size
+        ),
+        confidence: CONFIDENCE_HIGH,
+        ..Default::default()
+    })
+}
+
+/// Stores info about an lzip member
+#[derive(Debug, Default, Clone)]
+pub struct LzipMember {
+    pub dictionary_size: usize,
+    /// CRC32 of the decompressed data
+    pub crc: u32,
+    /// Size of the decompressed data
+    pub data_size: u64,
+    /// Size of the member, including the header and footer
+    pub member_size: usize,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct LzipHeaderBytes {
+    magic: [u8; 4],
+    version: u8,
+    coded_dictionary_size: u8,
+}
+
+/// Parses an lzip member header, returns the dictionary size
+pub fn parse_lzip_header(lzip_data: &[u8]) -> Result<usize, StructureError> {
+    const MAGIC: &[u8; 4] = b"LZIP";
+    const VERSION: u8 = 1;
+    const MIN_DICTIONARY_LOG: u8 = 12;
+    const MAX_DICTIONARY_LOG: u8 = 29;
+    const DICTIONARY_LOG_MASK: u8 = 0b11111;
+    const DICTIONARY_FRACTION_SHIFT: u8 = 5;
+
+    let (lzip_header, _) =
+        LzipHeaderBytes::ref_from_prefix(lzip_data).map_err(|_| StructureError)?;
+
+    if lzip_header.magic != *MAGIC || lzip_header.version != VERSION {
+        return Err(StructureError);
+    }
+
+    // The dictionary size is a power of 2, minus 0 to 7 sixteenths of that power of 2
+    let dictionary_log = lzip_header.coded_dictionary_size & DICTIONARY_LOG_MASK;
+    let dictionary_fraction =
+        (lzip_header.coded_dictionary_size >> DICTIONARY_FRACTION_SHIFT) as usize;
+
+    if !(MIN_DICTIONARY_LOG..=MAX_DICTIONARY_LOG).contains(&dictionary_log) {
+        return Err(StructureError);
+    }
+
+    let dictionary_size: usize = 1 << dictionary_log;
+    let dictionary_size = dictionary_size - ((dictionary_size / 16) * dictionary_fraction);
+
+    // Sizes less than the minimum are invalid
+    if dictionary_size < (1 << MIN_DICTIONARY_LOG) {
+        return Err(StructureError);
+    }
+
+    Ok(dictionary_size)
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct LzipFooterBytes {
+    crc: zerocopy::U32<LE>,
+    data_size: zerocopy::U64<LE>,
+    member_size: zerocopy::U64<LE>,
+}
+
+/// Parses an lzip member footer, returns the CRC32 of the decompressed data, the size of the decompressed data,
+/// and the size of the member.
+///
+/// ```
+/// use binwalk_ng::formats::lzip::parse_lzip_footer;
+///
+/// let footer = b"\x78\x56\x34\x12\x00\x01\x00\x00\x00\x00\x00\x00\x40\x00\x00\x00\x00\x00\x00\x00";
+///
+/// assert_eq!(parse_lzip_footer(footer).unwrap(), (0x12345678, 0x100, 0x40));
+/// assert!(parse_lzip_footer(&footer[..19]).is_err());
+/// ```
+pub fn parse_lzip_footer(footer_data: &[u8]) -> Result<(u32, u64, usize), StructureError> {
+    let (lzip_footer, _) =
+        LzipFooterBytes::ref_from_prefix(footer_data).map_err(|_| StructureError)?;
+
+    Ok((
+        lzip_footer.crc.get(),
+        lzip_footer.data_size.get(),
+        usize::try_from(lzip_footer.member_size.get()).map_err(|_| StructureError)?,
+    ))
+}
+
+/// Parses an lzip member, locating its footer to determine the member size
+///
+/// The LZMA stream is not sized anywhere in the member header, but the footer records the size of the entire member;
+/// the footer is the first one whose member size field matches its own offset, relative to the member header.
+pub fn parse_lzip_member(lzip_data: &[u8]) -> Result<LzipMember, StructureError> {
+    // The LZMA range coder's first byte is always 0
+    const LZMA_FIRST_BYTE: u8 = 0;
+    // Header, a minimal LZMA stream containing only an end of stream marker, and footer
+    const MIN_MEMBER_SIZE: usize = 36;
+
+    let dictionary_size = parse_lzip_header(lzip_data)?;
+
+    if lzip_data.get(LZIP_HEADER_SIZE) != Some(&LZMA_FIRST_BYTE) {
+        return Err(StructureError);
+    }
+
+    let mut footer_start = MIN_MEMBER_SIZE - LZIP_FOOTER_SIZE;
+
+    while let Some(footer_data) = lzip_data.get(footer_start..footer_start + LZIP_FOOTER_SIZE) {
+        if let Ok((crc, data_size, member_size)) = parse_lzip_footer(footer_data)
+            && member_size == footer_start + LZIP_FOOTER_SIZE
+        {
+            return Ok(LzipMember {
+                dictionary_size,
+                crc,
+                data_size,
+                member_size,
+            });
+        }
+
+        footer_start += 1;
+    }
+
+    Err(StructureError)
+}
+
+/// Parses all consecutive lzip members
+pub fn parse_lzip_members(lzip_data: &[u8]) -> Result<Vec<LzipMember>, StructureError> {
+    let mut members: Vec<LzipMember> = Vec::new();
+    let mut next_member_start = 0;
+    let mut previous_member_start = None;
+    let available_data = lzip_data.len();
+
+    while is_offset_safe(available_data, next_member_start, previous_member_start) {
+        let Ok(member) = parse_lzip_member(&lzip_data[next_member_start..]) else {
+            break;
+        };
+
+        previous_member_start = Some(next_member_start);
+        next_member_start += member.member_size;
+        members.push(member);
+    }
+
+    if members.is_empty() {
+        return Err(StructureError);
+    }
+
+    Ok(members)
+}
+
+/// Defines the internal extractor function for decompressing lzip data
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::lzip::lzip_extractor;
+///
+/// match lzip_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn lzip_extractor() -> Extractor {
+    Extractor {
+        utility: ExtractorType::Internal(lzip_decompress),
+        ..Default::default()
+    }
+}
+
+/// Internal extractor for lzip compressed data; all members are decompressed into the same output file
+pub fn lzip_decompress(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> ExtractionResult {
+    // Output file name
+    const OUTPUT_FILE_NAME: &str = "decompressed.bin";
+    // Size of decompression buffer
+    const BLOCK_SIZE: usize = 8192;
+    // Maximum memory limit: 4GB
+    const MEM_LIMIT: u64 = 4 * 1024 * 1024 * 1024;
+
+    let mut result = ExtractionResult::default();
+
+    let Some(lzip_data) = file_data.get(offset..) else {
+        return result;
+    };
+
+    let Ok(members) = parse_lzip_members(lzip_data) else {
+        return result;
+    };
+
+    let mut output_file = match output_directory {
+        None => None,
+        Some(output_directory) => {
+            match Chroot::new(output_directory).create_file_writer(OUTPUT_FILE_NAME) {
+                None => return result,
+                Some(file) => Some(file),
+            }
+        }
+    };
+
+    let mut decompressed_buffer = [0; BLOCK_SIZE];
+    let mut member_start: usize = 0;
+    let mut decompressed_size: usize = 0;
+
+    for member in members {
+        let member_data = &lzip_data[member_start..member_start + member.member_size];
+
+        // The auto-detecting decoder handles the lzip member format
+        let Ok(stream) = Stream::new_auto_decoder(MEM_LIMIT, 0) else {
+            break;
+        };
+        let mut decoder = liblzma::bufread::XzDecoder::new_stream(member_data, stream);
+
+        let mut crc: u32 = 0;
+        let mut member_decompressed_size: usize = 0;
+
+        loop {
+            match decoder.read(&mut decompressed_buffer) {
+                Err(_) => return result,
+                Ok(0) => break,
+                Ok(n) => {
+                    crc = crc32_seed(crc, &decompressed_buffer[..n]);
+                    member_decompressed_size += n;
+
+                    if let Some(output_file) = &mut output_file
+                        && output_file.write_all(&decompressed_buffer[..n]).is_err()
+                    {
+                        return result;
+                    }
+                }
+            }
+        }
+
+        // Validate the decompressed data against the footer
+        if crc != member.crc || member_decompressed_size as u64 != member.data_size {
+            return result;
+        }
+
+        member_start += member.member_size;
+        decompressed_size += member_decompressed_size;
+    }
+
+    result.success = true;
+    result.size = Some(member_start);
+    result.set_compression_ratio(member_start, decompressed_size);
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::crc32;
+
+    /// Two lzip members with a 64KB dictionary (see tests/inputs/gen_lzip.sh).
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/lzip.bin");
+
+    /// Size of the first member in VALID
+    const FIRST_MEMBER_SIZE: usize = 50;
+
+    #[test]
+    fn footer_parsing() {
+        let member = &VALID[..FIRST_MEMBER_SIZE];
+
+        let (crc, data_size, member_size) =
+            parse_lzip_footer(&member[member.len() - LZIP_FOOTER_SIZE..]).unwrap();
+        assert_eq!(crc, crc32(b"first member\n"));
+        assert_eq!(data_size, 13);
+        assert_eq!(member_size, member.len());
+
+        // Trailing data does not affect the member size, as it is read from the footer
+        let mut file_data = member.to_vec();
+        file_data.extend([0xFF; 64]);
+
+        let parsed = parse_lzip_member(&file_data).unwrap();
+        assert_eq!(parsed.dictionary_size, 1 << 16);
+        assert_eq!(parsed.crc, crc32(b"first member\n"));
+        assert_eq!(parsed.data_size, 13);
+        assert_eq!(parsed.member_size, member.len());
+
+        // Without a footer whose member size matches its location, the member is invalid
+        let mut bad_member_size = member.to_vec();
+        let member_size_offset = bad_member_size.len() - 8;
+        bad_member_size[member_size_offset] ^= 1;
+        assert!(parse_lzip_member(&bad_member_size).is_err());
+    }
+
+    #[test]
+    fn coded_dictionary_size() {
+        // 2^20 bytes
+        assert_eq!(parse_lzip_header(b"LZIP\x01\x14").unwrap(), 1 << 20);
+        // 2^20 bytes, minus 3/16ths
+        assert_eq!(
+            parse_lzip_header(b"LZIP\x01\x74").unwrap(),
+            (1 << 20) - 3 * (1 << 16)
+        );
+        // 2^30 bytes is too large
+        assert!(parse_lzip_header(b"LZIP\x01\x1E").is_err());
+        // Only version 1 is supported
+        assert!(parse_lzip_header(b"LZIP\x00\x14").is_err());
+    }
+
+    #[test]
+    fn multi_member() {
+        let file_data = [VALID, b"trailing junk"].concat();
+
+        let signature = lzip_parser(&file_data, 0).unwrap();
+        assert_eq!(signature.size, VALID.len());
+        assert!(signature.description.contains("member count: 2"));
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = lzip_decompress(&file_data, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(VALID.len()));
+        assert_eq!(
+            std::fs::read(output_directory.path().join("decompressed.bin")).unwrap(),
+            b"first member\nsecond member\n"
+        );
+
+        // The decompressed data must match the CRC32 in the footer
+        let mut bad_crc = file_data.clone();
+        bad_crc[FIRST_MEMBER_SIZE - LZIP_FOOTER_SIZE] ^= 0xFF;
+        assert!(!lzip_decompress(&bad_crc, 0, None).success);
+    }
+}
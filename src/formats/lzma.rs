@@ -115,12 +115,22 @@ pub fn parse_lzma_header(lzma_data: &[u8]) -> Result<LZMAHeader, StructureError>
     const MIN_SUPPORTED_DECOMPRESSED_SIZE: u64 = 256;
     const MAX_SUPPORTED_DECOMPRESSED_SIZE: u64 = 0xFFFFFFFF;
 
+    // The properties byte encodes (pb * 5 + lp) * 9 + lc, where lc <= 8, lp <= 4 and pb <= 4
+    const MAX_PROPERTIES: u8 = (4 * 5 + 4) * 9 + 8;
+
     let mut lzma_hdr_info = LZMAHeader::default();
 
     // Parse the lzma header
     let (lzma_header, _) =
         LZMAHeaderBytes::ref_from_prefix(lzma_data).map_err(|_| StructureError)?;
 
+    // Make sure the properties byte and dictionary size are valid
+    if lzma_header.properties > MAX_PROPERTIES
+        || !is_valid_dictionary_size(lzma_header.dictionary_size.get())
+    {
+        return Err(StructureError);
+    }
+
     // Make sure the expected NULL byte is NULL
     if lzma_header.null_byte == 0 {
         // Sanity check the reported decompressed size
@@ -140,6 +150,26 @@ pub fn parse_lzma_header(lzma_data: &[u8]) -> Result<LZMAHeader, StructureError>
     Err(StructureError)
 }
 
+/// Encoders produce dictionary sizes of 2^n or 2^n + 2^(n-1) bytes, or (when the size is specified in MiB) a multiple
+/// of 1 MiB from 2 MiB upward, between 4 KiB and 1.5 GiB
+fn is_valid_dictionary_size(dictionary_size: u32) -> bool {
+    const MIN_DICTIONARY_SIZE: u32 = 4096;
+    const MAX_DICTIONARY_SIZE: u32 = 0x6000_0000;
+    const MIB: u32 = 1024 * 1024;
+
+    if !(MIN_DICTIONARY_SIZE..=MAX_DICTIONARY_SIZE).contains(&dictionary_size) {
+        return false;
+    }
+
+    if dictionary_size >= 2 * MIB && dictionary_size.is_multiple_of(MIB) {
+        return true;
+    }
+
+    // Clear the most significant bit; what remains must be either nothing, or the next most significant bit
+    let remainder = dictionary_size & !(1 << dictionary_size.ilog2());
+    remainder == 0 || remainder == 1 << (dictionary_size.ilog2() - 1)
+}
+
 /// Defines the internal extractor function for decompressing LZMA/XZ data
 ///
 /// ```
@@ -150,6 +180,8 @@ pub fn parse_lzma_header(lzma_data: &[u8]) -> Result<LZMAHeader, StructureError>
 ///
 /// match lzma_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -183,31 +215,30 @@ pub fn lzma_decompress(
     let mut result = ExtractionResult::default();
 
     // Input compression stream
-    let lzma_stream = &file_data[offset..];
+    let lzma_stream = file_data.get(offset..).unwrap_or_default();
 
     // Instantiate a new decoder, auto-detect LZMA or XZ
     if let Ok(stream) = Stream::new_auto_decoder(MEM_LIMIT, 0) {
         let mut decoder = liblzma::bufread::XzDecoder::new_stream(lzma_stream, stream);
-        match output_directory {
+        let decompressed_size = match output_directory {
             Some(output_directory) => {
                 let Some(mut file) =
                     Chroot::new(output_directory).create_file_writer(OUTPUT_FILE_NAME)
                 else {
                     return result;
                 };
-                if io::copy(&mut decoder, &mut file).is_err() {
-                    return result;
-                }
-            }
-            None => {
-                if io::copy(&mut decoder, &mut io::sink()).is_err() {
-                    return result;
-                }
+                io::copy(&mut decoder, &mut file)
             }
+            None => io::copy(&mut decoder, &mut io::sink()),
+        };
+
+        let Ok(decompressed_size) = decompressed_size else {
+            return result;
         };
 
         result.success = true;
         result.size = Some(decoder.total_in() as usize);
+        result.set_compression_ratio(decoder.total_in() as usize, decompressed_size as usize);
     }
 
     result
@@ -259,4 +290,49 @@ mod tests {
             .unwrap();
         assert_eq!(extracted, payload);
     }
+
+    /// An LZMA stream with properties 0x5D and an 8MiB dictionary (see tests/inputs/gen_lzma.sh)
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/lzma.bin");
+
+    /// Returns the fixture's header with the given properties byte and dictionary size
+    fn lzma_header(properties: u8, dictionary_size: u32) -> Vec<u8> {
+        let mut header = VALID[..13].to_vec();
+        header[0] = properties;
+        header[1..5].copy_from_slice(&dictionary_size.to_le_bytes());
+        header
+    }
+
+    #[test]
+    fn fixture_header() {
+        let header = parse_lzma_header(VALID).unwrap();
+        assert_eq!(header.properties, 0x5D);
+        assert_eq!(header.dictionary_size, 0x80_0000);
+        assert_eq!(header.decompressed_size, u64::MAX);
+    }
+
+    #[test]
+    fn rejects_invalid_properties() {
+        assert!(parse_lzma_header(&lzma_header(0x5D, 0x80_0000)).is_ok());
+        assert!(parse_lzma_header(&lzma_header(224, 0x80_0000)).is_ok());
+        assert!(parse_lzma_header(&lzma_header(225, 0x80_0000)).is_err());
+        assert!(parse_lzma_header(&lzma_header(0xFF, 0x80_0000)).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_dictionary_sizes() {
+        // 2^n and 2^n + 2^(n-1) sizes are valid
+        assert!(parse_lzma_header(&lzma_header(0x5D, 0x1000)).is_ok());
+        assert!(parse_lzma_header(&lzma_header(0x5D, 0xC0_0000)).is_ok());
+        assert!(parse_lzma_header(&lzma_header(0x5D, 0x6000_0000)).is_ok());
+
+        // As are multiples of 1 MiB, from 2 MiB upward
+        assert!(parse_lzma_header(&lzma_header(0x5D, 0x50_0000)).is_ok());
+        assert!(parse_lzma_header(&lzma_header(0x5D, 0xA0_0000)).is_ok());
+
+        // Too small, too large, or not a size an encoder would produce
+        assert!(parse_lzma_header(&lzma_header(0x5D, 0x800)).is_err());
+        assert!(parse_lzma_header(&lzma_header(0x5D, 0x8000_0000)).is_err());
+        assert!(parse_lzma_header(&lzma_header(0x5D, 0x14_0000)).is_err());
+        assert!(parse_lzma_header(&lzma_header(0x5D, 0x12_3456)).is_err());
+    }
 }
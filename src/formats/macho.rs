@@ -0,0 +1,519 @@
+use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
+use crate::signatures::{CONFIDENCE_HIGH, CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
+use crate::structures::{Endianness, StructureError, dyn_endian};
+use std::path::Path;
+use zerocopy::{BE, FromBytes, Immutable, KnownLayout, Unaligned};
+
+/// Human readable descriptions
+pub const DESCRIPTION: &str = "Mach-O binary";
+pub const FAT_DESCRIPTION: &str = "Mach-O universal (fat) binary";
+
+/// Thin Mach-O files start with these magic bytes, in either endianness
+pub fn macho_magic() -> Vec<Vec<u8>> {
+    vec![
+        b"\xFE\xED\xFA\xCE".to_vec(),
+        b"\xCE\xFA\xED\xFE".to_vec(),
+        b"\xFE\xED\xFA\xCF".to_vec(),
+        b"\xCF\xFA\xED\xFE".to_vec(),
+    ]
+}
+
+/// Fat Mach-O files start with these (always big endian) magic bytes
+pub fn macho_fat_magic() -> Vec<Vec<u8>> {
+    vec![b"\xCA\xFE\xBA\xBE".to_vec(), b"\xCA\xFE\xBA\xBF".to_vec()]
+}
+
+/// Parse and validate a thin Mach-O header
+pub fn macho_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
+    // Successful result
+    let mut result = SignatureResult {
+        offset,
+        description: DESCRIPTION.to_string(),
+        confidence: CONFIDENCE_MEDIUM,
+        ..Default::default()
+    };
+
+    let macho_header = parse_macho_header(file_data.get(offset..).ok_or(SignatureError)?)
+        .map_err(|_| SignatureError)?;
+
+    result.description = format!(
+        "{}, {}-bit {} for {}, load commands: {}, {}",
+        result.description,
+        macho_header.bits,
+        macho_header.file_type,
+        macho_header.cpu,
+        macho_header.load_command_count,
+        macho_header.endianness
+    );
+
+    Ok(result)
+}
+
+/// Parse and validate a fat Mach-O header
+pub fn macho_fat_parser(
+    file_data: &[u8],
+    offset: usize,
+) -> Result<SignatureResult, SignatureError> {
+    // Successful result
+    let mut result = SignatureResult {
+        offset,
+        description: FAT_DESCRIPTION.to_string(),
+        confidence: CONFIDENCE_HIGH,
+        ..Default::default()
+    };
+
+    let fat_data = file_data.get(offset..).ok_or(SignatureError)?;
+    let fat_header = parse_macho_fat_header(fat_data).map_err(|_| SignatureError)?;
+
+    let architectures: Vec<&str> = fat_header
+        .slices
+        .iter()
+        .map(|slice| slice.cpu.as_str())
+        .collect();
+    result.description = format!(
+        "{}, architectures: {}",
+        result.description,
+        architectures.join(", ")
+    );
+
+    // The header is valid, but one or more slices are cut short
+    if fat_header.total_size > fat_data.len() {
+        result.extraction_declined = true;
+        result.set_truncated(fat_header.total_size, fat_data.len());
+        return Ok(result);
+    }
+
+    // Each slice must be a thin Mach-O binary for the architecture listed in the fat header
+    for slice in &fat_header.slices {
+        let slice_data = slice
+            .offset
+            .checked_add(slice.size)
+            .and_then(|slice_end| fat_data.get(slice.offset..slice_end))
+            .ok_or(SignatureError)?;
+        let slice_header = parse_macho_header(slice_data).map_err(|_| SignatureError)?;
+
+        if slice_header.cpu_type != slice.cpu_type {
+            return Err(SignatureError);
+        }
+    }
+
+    result.size = fat_header.total_size;
+    result.children = fat_header
+        .slices
+        .iter()
+        .map(|slice| SignatureResult {
+            offset: offset + slice.offset,
+            size: slice.size,
+            name: "macho_slice".to_string(),
+            confidence: CONFIDENCE_HIGH,
+            description: format!(
+                "Mach-O slice, CPU: {}, size: {} bytes",
+                slice.cpu, slice.size
+            ),
+            ..Default::default()
+        })
+        .collect();
+    result.description = format!(
+        "{}, total size: {} bytes",
+        result.description, fat_header.total_size
+    );
+
+    Ok(result)
+}
+
+/// Struct to store some useful Mach-O header info
+#[derive(Debug, Clone)]
+pub struct MachOHeader {
+    pub bits: usize,
+    pub endianness: Endianness,
+    pub cpu_type: u32,
+    pub cpu: String,
+    pub file_type: String,
+    pub load_command_count: usize,
+    pub load_commands_size: usize,
+    pub header_size: usize,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct MachOHeaderBytes {
+    magic: [u8; 4],
+    cpu_type: dyn_endian::U32,
+    cpu_subtype: dyn_endian::U32,
+    file_type: dyn_endian::U32,
+    load_command_count: dyn_endian::U32,
+    load_commands_size: dyn_endian::U32,
+    flags: dyn_endian::U32,
+}
+
+/// Parses a thin Mach-O header
+pub fn parse_macho_header(macho_data: &[u8]) -> Result<MachOHeader, StructureError> {
+    // Every load command is at least 8 bytes (command and command size)
+    const MIN_LOAD_COMMAND_SIZE: usize = 8;
+    const MAX_LOAD_COMMANDS_SIZE: usize = 16 * 1024 * 1024;
+
+    // 64-bit headers have an additional reserved field
+    const HEADER_SIZE_32: usize = 28;
+    const HEADER_SIZE_64: usize = 32;
+
+    let (macho_header, _) =
+        MachOHeaderBytes::ref_from_prefix(macho_data).map_err(|_| StructureError)?;
+
+    let (bits, endianness, header_size) = match macho_header.magic {
+        [0xFE, 0xED, 0xFA, 0xCE] => (32, Endianness::Big, HEADER_SIZE_32),
+        [0xCE, 0xFA, 0xED, 0xFE] => (32, Endianness::Little, HEADER_SIZE_32),
+        [0xFE, 0xED, 0xFA, 0xCF] => (64, Endianness::Big, HEADER_SIZE_64),
+        [0xCF, 0xFA, 0xED, 0xFE] => (64, Endianness::Little, HEADER_SIZE_64),
+        _ => return Err(StructureError),
+    };
+
+    let cpu_type = macho_header.cpu_type.get(endianness);
+    let cpu = macho_cpu_name(cpu_type).ok_or(StructureError)?;
+    let file_type =
+        macho_file_type_name(macho_header.file_type.get(endianness)).ok_or(StructureError)?;
+
+    let load_command_count = macho_header.load_command_count.get(endianness) as usize;
+    let load_commands_size = macho_header.load_commands_size.get(endianness) as usize;
+
+    // Sanity check the load commands
+    if load_command_count == 0
+        || load_commands_size > MAX_LOAD_COMMANDS_SIZE
+        || load_command_count * MIN_LOAD_COMMAND_SIZE > load_commands_size
+    {
+        return Err(StructureError);
+    }
+
+    Ok(MachOHeader {
+        bits,
+        endianness,
+        cpu_type,
+        cpu: cpu.to_string(),
+        file_type: file_type.to_string(),
+        load_command_count,
+        load_commands_size,
+        header_size,
+    })
+}
+
+/// Describes a single architecture's slice in a fat Mach-O binary
+#[derive(Debug, Default, Clone)]
+pub struct MachOFatSlice {
+    pub cpu_type: u32,
+    pub cpu: String,
+    /// Offset of the slice, relative to the start of the fat header
+    pub offset: usize,
+    pub size: usize,
+}
+
+/// Struct to store fat Mach-O header info
+#[derive(Debug, Default, Clone)]
+pub struct MachOFatHeader {
+    pub slices: Vec<MachOFatSlice>,
+    /// Offset of the end of the last slice, relative to the start of the fat header
+    pub total_size: usize,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct FatHeaderBytes {
+    magic: zerocopy::U32<BE>,
+    arch_count: zerocopy::U32<BE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct FatArchBytes {
+    cpu_type: zerocopy::U32<BE>,
+    cpu_subtype: zerocopy::U32<BE>,
+    offset: zerocopy::U32<BE>,
+    size: zerocopy::U32<BE>,
+    align: zerocopy::U32<BE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct FatArch64Bytes {
+    cpu_type: zerocopy::U32<BE>,
+    cpu_subtype: zerocopy::U32<BE>,
+    offset: zerocopy::U64<BE>,
+    size: zerocopy::U64<BE>,
+    align: zerocopy::U32<BE>,
+    reserved: zerocopy::U32<BE>,
+}
+
+/// Parses a fat Mach-O header and its architecture table
+pub fn parse_macho_fat_header(fat_data: &[u8]) -> Result<MachOFatHeader, StructureError> {
+    const FAT_MAGIC: u32 = 0xCAFEBABE;
+    const FAT_MAGIC_64: u32 = 0xCAFEBABF;
+
+    // Java class files share the fat magic bytes, but are followed by their major version number (45 or greater)
+    // where the architecture count would be; real fat binaries only ever contain a handful of architectures
+    const MAX_ARCH_COUNT: usize = 16;
+
+    // Slice alignments are specified as a power of 2
+    const MAX_ALIGNMENT: u32 = 16;
+
+    let (fat_header, arch_table) =
+        FatHeaderBytes::ref_from_prefix(fat_data).map_err(|_| StructureError)?;
+
+    let arch_count = fat_header.arch_count.get() as usize;
+
+    if arch_count == 0 || arch_count > MAX_ARCH_COUNT {
+        return Err(StructureError);
+    }
+
+    // (cpu type, offset, size, alignment) of each architecture
+    let archs: Vec<(u32, usize, usize, u32)> = match fat_header.magic.get() {
+        FAT_MAGIC => <[FatArchBytes]>::ref_from_prefix_with_elems(arch_table, arch_count)
+            .map_err(|_| StructureError)?
+            .0
+            .iter()
+            .map(|arch| {
+                (
+                    arch.cpu_type.get(),
+                    arch.offset.get() as usize,
+                    arch.size.get() as usize,
+                    arch.align.get(),
+                )
+            })
+            .collect(),
+        FAT_MAGIC_64 => <[FatArch64Bytes]>::ref_from_prefix_with_elems(arch_table, arch_count)
+            .map_err(|_| StructureError)?
+            .0
+            .iter()
+            .map(|arch| {
+                (
+                    arch.cpu_type.get(),
+                    arch.offset.get() as usize,
+                    arch.size.get() as usize,
+                    arch.align.get(),
+                )
+            })
+            .collect(),
+        _ => return Err(StructureError),
+    };
+
+    let arch_table_size = if fat_header.magic.get() == FAT_MAGIC {
+        std::mem::size_of::<FatArchBytes>()
+    } else {
+        std::mem::size_of::<FatArch64Bytes>()
+    } * arch_count;
+
+    let mut fat = MachOFatHeader {
+        total_size: std::mem::size_of::<FatHeaderBytes>() + arch_table_size,
+        ..Default::default()
+    };
+
+    for (cpu_type, offset, size, align) in archs {
+        let cpu = macho_cpu_name(cpu_type).ok_or(StructureError)?;
+
+        // Slices must be aligned, non-empty, and must not overlap the header or each other
+        if align > MAX_ALIGNMENT
+            || !offset.is_multiple_of(1 << align)
+            || size == 0
+            || offset < fat.total_size
+        {
+            return Err(StructureError);
+        }
+
+        fat.slices.push(MachOFatSlice {
+            cpu_type,
+            cpu: cpu.to_string(),
+            offset,
+            size,
+        });
+
+        fat.total_size = offset.checked_add(size).ok_or(StructureError)?;
+    }
+
+    Ok(fat)
+}
+
+/// Returns the name of a Mach-O CPU type, or None if the CPU type is unknown
+const fn macho_cpu_name(cpu_type: u32) -> Option<&'static str> {
+    Some(match cpu_type {
+        1 => "VAX",
+        6 => "MC680x0",
+        7 => "x86",
+        0x01000007 => "x86_64",
+        10 => "MC98000",
+        11 => "HPPA",
+        12 => "ARM",
+        0x0100000C => "ARM64",
+        0x0200000C => "ARM64_32",
+        13 => "MC88000",
+        14 => "SPARC",
+        15 => "i860",
+        18 => "PowerPC",
+        0x01000012 => "PowerPC64",
+        _ => return None,
+    })
+}
+
+/// Returns the description of a Mach-O file type, or None if the file type is unknown
+const fn macho_file_type_name(file_type: u32) -> Option<&'static str> {
+    Some(match file_type {
+        1 => "object file",
+        2 => "executable",
+        3 => "fixed VM shared library",
+        4 => "core file",
+        5 => "preloaded executable",
+        6 => "dynamic library",
+        7 => "dynamic linker",
+        8 => "bundle",
+        9 => "dynamic library stub",
+        10 => "debug symbols",
+        11 => "kernel extension",
+        12 => "file set",
+        _ => return None,
+    })
+}
+
+/// Defines the internal extractor function for carving out fat Mach-O slices
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::macho::macho_fat_extractor;
+///
+/// match macho_fat_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn macho_fat_extractor() -> Extractor {
+    Extractor {
+        utility: ExtractorType::Internal(extract_macho_fat_slices),
+        ..Default::default()
+    }
+}
+
+/// Internal extractor for carving each slice out of a fat Mach-O binary
+pub fn extract_macho_fat_slices(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> ExtractionResult {
+    let mut result = ExtractionResult::default();
+
+    if let Some(fat_data) = file_data.get(offset..)
+        && let Ok(fat_header) = parse_macho_fat_header(fat_data)
+        && fat_header.total_size <= fat_data.len()
+    {
+        result.success = true;
+        result.size = Some(fat_header.total_size);
+
+        if let Some(output_directory) = output_directory {
+            let chroot = Chroot::new(output_directory);
+
+            for (i, slice) in fat_header.slices.iter().enumerate() {
+                // The same CPU type may appear more than once (e.g., arm64 and arm64e), so include the slice index
+                let slice_file_name = format!("slice_{i}_{}.macho", slice.cpu);
+
+                result.success = chroot.carve_file(
+                    slice_file_name,
+                    file_data,
+                    offset + slice.offset,
+                    slice.size,
+                );
+
+                if !result.success {
+                    break;
+                }
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fat binary with x86_64 and ARM64 slices at 0x1000 and 0x2000; see tests/inputs/gen_macho_fat.sh
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/macho_fat.bin");
+
+    const X86_64_SLICE: std::ops::Range<usize> = 0x1000..0x1038;
+    const ARM64_SLICE: std::ops::Range<usize> = 0x2000..0x2038;
+
+    #[test]
+    fn thin_64_bit_header() {
+        let macho = &VALID[ARM64_SLICE];
+        let header = parse_macho_header(macho).unwrap();
+
+        assert_eq!(header.bits, 64);
+        assert_eq!(header.endianness, Endianness::Little);
+        assert_eq!(header.cpu, "ARM64");
+        assert_eq!(header.file_type, "executable");
+        assert_eq!(header.load_command_count, 1);
+        assert_eq!(header.header_size, 32);
+
+        let signature = macho_parser(macho, 0).unwrap();
+        assert_eq!(
+            signature.description,
+            "Mach-O binary, 64-bit executable for ARM64, load commands: 1, Little Endian"
+        );
+    }
+
+    #[test]
+    fn two_architecture_fat_header() {
+        let header = parse_macho_fat_header(VALID).unwrap();
+
+        let slices: Vec<(&str, usize, usize)> = header
+            .slices
+            .iter()
+            .map(|slice| (slice.cpu.as_str(), slice.offset, slice.size))
+            .collect();
+        assert_eq!(slices, [("x86_64", 0x1000, 56), ("ARM64", 0x2000, 56)]);
+        assert_eq!(header.total_size, VALID.len());
+
+        let signature = macho_fat_parser(VALID, 0).unwrap();
+        assert_eq!(signature.size, VALID.len());
+        assert_eq!(signature.children.len(), 2);
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = extract_macho_fat_slices(VALID, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(
+            std::fs::read(output_directory.path().join("slice_0_x86_64.macho")).unwrap(),
+            &VALID[X86_64_SLICE]
+        );
+        assert_eq!(
+            std::fs::read(output_directory.path().join("slice_1_ARM64.macho")).unwrap(),
+            &VALID[ARM64_SLICE]
+        );
+    }
+
+    #[test]
+    fn mismatched_slice_architecture() {
+        // The first slice is listed as ARM64, but contains an x86_64 binary
+        let mut fat = VALID.to_vec();
+        fat[8..12].copy_from_slice(&0x0100000Cu32.to_be_bytes());
+
+        assert!(parse_macho_fat_header(&fat).is_ok());
+        assert!(macho_fat_parser(&fat, 0).is_err());
+    }
+
+    #[test]
+    fn java_class_files_are_not_fat_binaries() {
+        // Java 8 class file: magic, minor version 0, major version 52, followed by the constant pool count
+        let mut class_file = b"\xCA\xFE\xBA\xBE\x00\x00\x00\x34\x00\x1D".to_vec();
+        class_file.resize(4096, 0);
+
+        assert!(parse_macho_fat_header(&class_file).is_err());
+        assert!(macho_fat_parser(&class_file, 0).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::common::is_offset_safe;
 use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
 use crate::signatures::{CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
 use crate::structures::StructureError;
@@ -72,6 +73,10 @@ pub struct MBRPartition {
     pub start: usize,
     pub size: usize,
     pub name: String,
+    pub os_type: u8,
+    pub start_lba: usize,
+    pub sector_count: usize,
+    pub logical: bool,
 }
 
 /// Struct to store MBR info
@@ -92,98 +97,188 @@ struct PartitionEntryBytes {
     lba_size: zerocopy::U32<LE>,
 }
 
-/// Parse a Master Boot Record image
-pub fn parse_mbr_image(mbr_data: &[u8]) -> Result<MBRHeader, StructureError> {
-    const BLOCK_SIZE: usize = 512;
-    const MIN_IMAGE_SIZE: usize = BLOCK_SIZE * 2;
+const BLOCK_SIZE: usize = 512;
+const PARTITION_COUNT: usize = 4;
+
+/// Partition types that describe an extended partition, which contains a chain of extended boot records
+const EXTENDED_PARTITION_TYPES: [u8; 3] = [0x05, 0x0F, 0x85];
+
+/// Returns the human readable name of an MBR partition type
+fn partition_type_name(os_type: u8) -> &'static str {
+    match os_type {
+        0x05 => "Extended",
+        0x07 => "NTFS_IFS_HPFS_exFAT",
+        0x0B => "FAT32",
+        0x0C => "FAT32",
+        0x0F => "Extended",
+        0x43 => "Linux",
+        0x4D => "QNX Primary Volume",
+        0x4E => "QNX Secondary Volume",
+        0x81 => "Minix",
+        0x83 => "Linux",
+        0x85 => "Linux Extended",
+        0x8E => "Linux LVM",
+        0x96 => "ISO-9660",
+        0xB1 => "QNXv6 File System",
+        0xB2 => "QNXv6 File System",
+        0xB3 => "QNXv6 File System",
+        0xEE => "EFI GPT Protective",
+        0xEF => "EFI System Partition",
+        _ => "Unknown",
+    }
+}
 
-    const PARTITION_COUNT: usize = 4;
+/// Parses the partition table of an MBR or extended boot record sector, and returns its entries.
+/// Unused entries are returned as None.
+fn parse_partition_table(
+    sector_data: &[u8],
+) -> Result<[Option<&PartitionEntryBytes>; PARTITION_COUNT], StructureError> {
     const PARTITION_TABLE_OFFSET: usize = 446;
+    const BOOT_SIGNATURE_OFFSET: usize = 510;
+    const BOOT_SIGNATURE: &[u8] = b"\x55\xAA";
     const ALLOWED_STATUS_VALUES: [u8; 2] = [0, 0x80];
 
+    // Both the MBR and extended boot records end with the boot signature
+    if sector_data.get(BOOT_SIGNATURE_OFFSET..BLOCK_SIZE) != Some(BOOT_SIGNATURE) {
+        return Err(StructureError);
+    }
+
     let partition_structure_size = std::mem::size_of::<PartitionEntryBytes>();
+    let mut entries = [None; PARTITION_COUNT];
+
+    for (i, entry) in entries.iter_mut().enumerate() {
+        // Offset in the sector for this entry
+        let partition_entry_start = PARTITION_TABLE_OFFSET + (i * partition_structure_size);
+
+        // Parse this partition table entry
+        let (partition_entry, _) =
+            PartitionEntryBytes::ref_from_prefix(&sector_data[partition_entry_start..])
+                .map_err(|_| StructureError)?;
+
+        // OS type of zero or LBA size of 0 can be ignored, as can entries with an invalid status value
+        if (partition_entry.os_type != 0 || partition_entry.lba_size.get() != 0)
+            && ALLOWED_STATUS_VALUES.contains(&partition_entry.status)
+        {
+            *entry = Some(partition_entry);
+        }
+    }
+
+    Ok(entries)
+}
 
-    let partition_table_start = PARTITION_TABLE_OFFSET;
-    let partition_table_end = partition_table_start + (partition_structure_size * PARTITION_COUNT);
+/// Parse a Master Boot Record image
+pub fn parse_mbr_image(mbr_data: &[u8]) -> Result<MBRHeader, StructureError> {
+    const MIN_IMAGE_SIZE: usize = BLOCK_SIZE * 2;
 
     let mut mbr_header = MBRHeader::default();
 
-    // Get the partition table raw bytes
-    if let Some(partition_table) = mbr_data.get(partition_table_start..partition_table_end) {
-        // Parse each partition table entry
-        for i in 0..PARTITION_COUNT {
-            // Offset in the partition table for this entry
-            let partition_entry_start: usize = i * partition_structure_size;
-
-            // Parse this partition table entry
-            let (partition_entry, _) =
-                PartitionEntryBytes::ref_from_prefix(&partition_table[partition_entry_start..])
-                    .map_err(|_| StructureError)?;
-
-            // OS type of zero or LBA size of 0 can be ignored
-            if partition_entry.os_type != 0 || partition_entry.lba_size.get() != 0 {
-                // Validate the reported MBR status value
-                if ALLOWED_STATUS_VALUES.contains(&partition_entry.status) {
-                    // Default to unknown partition type
-                    let this_partition_name = match partition_entry.os_type {
-                        0x07 => "NTFS_IFS_HPFS_exFAT",
-                        0x0B => "FAT32",
-                        0x0C => "FAT32",
-                        0x43 => "Linux",
-                        0x4D => "QNX Primary Volume",
-                        0x4E => "QNX Secondary Volume",
-                        0x81 => "Minix",
-                        0x83 => "Linux",
-                        0x8E => "Linux LVM",
-                        0x96 => "ISO-9660",
-                        0xB1 => "QNXv6 File System",
-                        0xB2 => "QNXv6 File System",
-                        0xB3 => "QNXv6 File System",
-                        0xEE => "EFI GPT Protective",
-                        0xEF => "EFI System Partition",
-                        _ => "Unknown",
-                    };
-
-                    // Create an MBRPartition structure for this entry
-                    let this_partition = MBRPartition {
-                        start: partition_entry.lba_start.get() as usize * BLOCK_SIZE,
-                        size: partition_entry.lba_size.get() as usize * BLOCK_SIZE,
-                        name: this_partition_name.to_string(),
-                    };
-
-                    // Calculate where this partition ends
-                    let this_partition_end_offset = this_partition.start + this_partition.size;
-
-                    // Some valid MBRs have partitions that start/end out of bounds WRT the disk image.
-                    // Not sure why? At any rate, don't include them in the reported partitions.
-                    if this_partition_end_offset <= mbr_data.len() {
-                        // Don't report the partition where the MBR header resides
-                        if this_partition.start != 0 {
-                            // Add it to the list of partitions
-                            mbr_header.partitions.push(this_partition.clone());
-                        }
+    // Parse each primary partition table entry
+    for partition_entry in parse_partition_table(mbr_data)?.into_iter().flatten() {
+        let start_lba = partition_entry.lba_start.get() as usize;
 
-                        // Image size is the end of the farthest away partition
-                        if this_partition_end_offset > mbr_header.image_size {
-                            mbr_header.image_size = this_partition_end_offset;
-                        }
-                    }
-                }
-            }
+        if EXTENDED_PARTITION_TYPES.contains(&partition_entry.os_type) {
+            // The extended partition itself is just a container; report the logical partitions inside it
+            parse_extended_partitions(mbr_data, start_lba, &mut mbr_header);
         }
 
-        // There should be at least one valid partition
-        if !mbr_header.partitions.is_empty() {
-            // Total size should be greater than minimum size
-            if mbr_header.image_size > MIN_IMAGE_SIZE {
-                return Ok(mbr_header);
-            }
+        add_partition(mbr_data, partition_entry, start_lba, false, &mut mbr_header);
+    }
+
+    // Logical partitions are only reported alongside their extended partition, which itself is not carved
+    mbr_header
+        .partitions
+        .retain(|partition| !EXTENDED_PARTITION_TYPES.contains(&partition.os_type));
+
+    // There should be at least one valid partition
+    if !mbr_header.partitions.is_empty() {
+        // Total size should be greater than minimum size
+        if mbr_header.image_size > MIN_IMAGE_SIZE {
+            return Ok(mbr_header);
         }
     }
 
     Err(StructureError)
 }
 
+/// Adds a partition entry to the MBR header; start_lba is the absolute LBA of the partition
+fn add_partition(
+    mbr_data: &[u8],
+    partition_entry: &PartitionEntryBytes,
+    start_lba: usize,
+    logical: bool,
+    mbr_header: &mut MBRHeader,
+) {
+    let sector_count = partition_entry.lba_size.get() as usize;
+
+    // Create an MBRPartition structure for this entry
+    let this_partition = MBRPartition {
+        start: start_lba * BLOCK_SIZE,
+        size: sector_count * BLOCK_SIZE,
+        name: partition_type_name(partition_entry.os_type).to_string(),
+        os_type: partition_entry.os_type,
+        start_lba,
+        sector_count,
+        logical,
+    };
+
+    // Calculate where this partition ends
+    let this_partition_end_offset = this_partition.start + this_partition.size;
+
+    // Some valid MBRs have partitions that start/end out of bounds WRT the disk image.
+    // Not sure why? At any rate, don't include them in the reported partitions.
+    if this_partition_end_offset <= mbr_data.len() {
+        // Image size is the end of the farthest away partition
+        if this_partition_end_offset > mbr_header.image_size {
+            mbr_header.image_size = this_partition_end_offset;
+        }
+
+        // Don't report the partition where the MBR header resides
+        if this_partition.start != 0 {
+            // Add it to the list of partitions
+            mbr_header.partitions.push(this_partition);
+        }
+    }
+}
+
+/// Walks the chain of extended boot records in an extended partition, adding each logical partition to the MBR header
+fn parse_extended_partitions(
+    mbr_data: &[u8],
+    extended_start_lba: usize,
+    mbr_header: &mut MBRHeader,
+) {
+    let mut previous_ebr_offset: Option<usize> = None;
+    let mut ebr_lba = extended_start_lba;
+
+    loop {
+        let ebr_offset = ebr_lba * BLOCK_SIZE;
+
+        // Each EBR must come after the previous one, which also prevents infinite loops
+        if !is_offset_safe(mbr_data.len(), ebr_offset, previous_ebr_offset) {
+            break;
+        }
+
+        let Ok(entries) = parse_partition_table(&mbr_data[ebr_offset..]) else {
+            break;
+        };
+
+        // The first entry describes the logical partition, relative to this EBR
+        if let Some(logical_entry) = entries[0] {
+            let start_lba = ebr_lba + logical_entry.lba_start.get() as usize;
+            add_partition(mbr_data, logical_entry, start_lba, true, mbr_header);
+        }
+
+        // The second entry points to the next EBR, relative to the start of the extended partition
+        match entries[1] {
+            Some(next_entry) if EXTENDED_PARTITION_TYPES.contains(&next_entry.os_type) => {
+                ebr_lba = extended_start_lba + next_entry.lba_start.get() as usize;
+            }
+            _ => break,
+        }
+
+        previous_ebr_offset = Some(ebr_offset);
+    }
+}
+
 /// Defines the internal extractor function for MBR partitions
 ///
 /// ```
@@ -194,6 +289,8 @@ pub fn parse_mbr_image(mbr_data: &[u8]) -> Result<MBRHeader, StructureError> {
 ///
 /// match mbr_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -245,11 +342,11 @@ pub fn extract_mbr_partitions(
                         let partition_name =
                             format!("{}_partition.{}", partition.name, partition_count);
 
-                        // Carve out the partition
+                        // Carve out the partition; partition offsets are relative to the MBR, not the file data
                         result.success = chroot.carve_file(
                             partition_name,
                             file_data,
-                            partition.start,
+                            offset + partition.start,
                             partition.size,
                         );
 
@@ -265,3 +362,83 @@ pub fn extract_mbr_partitions(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MBR with a single Linux partition
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/mbr.bin");
+
+    /// MBR with a primary partition and an extended partition holding two logical partitions; see tests/inputs/gen_mbr_extended.sh
+    static VALID_EXTENDED: &[u8] = include_bytes!("../../tests/inputs/mbr_extended.bin");
+
+    /// Returns the (type, start LBA, sector count, offset, size) of each partition in the image
+    fn partitions(image: &[u8]) -> Vec<(u8, usize, usize, usize, usize)> {
+        parse_mbr_image(image)
+            .unwrap()
+            .partitions
+            .iter()
+            .map(|partition| {
+                (
+                    partition.os_type,
+                    partition.start_lba,
+                    partition.sector_count,
+                    partition.start,
+                    partition.size,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn primary_partitions() {
+        assert_eq!(partitions(VALID), [(0x83, 1, 9, 512, 4608)]);
+        assert_eq!(parse_mbr_image(VALID).unwrap().image_size, VALID.len());
+
+        // The boot signature is required
+        let mut image = VALID.to_vec();
+        image[511] = 0;
+        assert!(parse_mbr_image(&image).is_err());
+    }
+
+    #[test]
+    fn extended_partition_chain() {
+        let expected = [
+            (0x83, 2, 2, 1024, 1024),
+            (0x83, 5, 2, 2560, 1024),
+            (0x0B, 8, 4, 4096, 2048),
+        ];
+        assert_eq!(partitions(VALID_EXTENDED), expected);
+
+        let mbr_header = parse_mbr_image(VALID_EXTENDED).unwrap();
+        assert_eq!(mbr_header.image_size, VALID_EXTENDED.len());
+        assert!(!mbr_header.partitions[0].logical);
+        assert!(mbr_header.partitions[1].logical);
+        assert!(mbr_header.partitions[2].logical);
+
+        // A link in the second EBR back to the first ends the chain rather than looping forever
+        let mut image = VALID_EXTENDED.to_vec();
+        let link_offset = (7 * BLOCK_SIZE) + 446 + 16;
+        image[link_offset + 4] = 0x05;
+        image[link_offset + 12..link_offset + 16].copy_from_slice(&8u32.to_le_bytes());
+        assert_eq!(partitions(&image), expected);
+    }
+
+    #[test]
+    fn partition_extraction() {
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = extract_mbr_partitions(VALID_EXTENDED, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(VALID_EXTENDED.len()));
+
+        for (name, start, size) in [
+            ("Linux_partition.0", 1024, 1024),
+            ("Linux_partition.1", 2560, 1024),
+            ("FAT32_partition.2", 4096, 2048),
+        ] {
+            let carved = std::fs::read(output_directory.path().join(name)).unwrap();
+            assert_eq!(carved, &VALID_EXTENDED[start..start + size]);
+        }
+    }
+}
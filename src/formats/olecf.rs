@@ -0,0 +1,400 @@
+use crate::extractors::Extractor;
+use crate::extractors::carve::carve_extractor;
+use crate::formats::efigpt::format_guid;
+use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
+use crate::structures::StructureError;
+use std::collections::HashSet;
+use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
+
+/// Human readable description
+pub const DESCRIPTION: &str = "OLE2 compound file";
+
+/// Size of the compound file header
+const OLECF_HEADER_SIZE: usize = 512;
+
+/// Number of FAT sector locations stored in the header
+const HEADER_DIFAT_COUNT: usize = 109;
+
+/// Size of each FAT entry
+const FAT_ENTRY_SIZE: usize = 4;
+
+/// FAT entry values for unused sectors, and for sectors that store the FAT itself
+const FREE_SECTOR: u32 = 0xFFFFFFFF;
+const FAT_SECTOR: u32 = 0xFFFFFFFD;
+
+/// Compound files always start with these bytes
+pub fn olecf_magic() -> Vec<Vec<u8>> {
+    vec![b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1".to_vec()]
+}
+
+/// Validates the compound file header and FAT
+pub fn olecf_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
+    let olecf_data = file_data.get(offset..).unwrap_or_default();
+    let olecf = parse_olecf(olecf_data).map_err(|_| SignatureError)?;
+
+    let mut result = SignatureResult {
+        offset,
+        description: format!(
+            "{}, version: {}, sector size: {} bytes, FAT sectors: {}",
+            DESCRIPTION,
+            olecf.header.major_version,
+            olecf.header.sector_size,
+            olecf.fat_sectors.len()
+        ),
+        confidence: CONFIDENCE_HIGH,
+        ..Default::default()
+    };
+
+    if let Some(root_clsid) = &olecf.root_clsid {
+        result.description = format!("{}, root CLSID: {}", result.description, root_clsid);
+    }
+
+    result.description = format!("{}, total size: {} bytes", result.description, olecf.size);
+
+    // Sectors beyond the end of the data are still in use according to the FAT
+    if olecf.size > olecf_data.len() {
+        result.extraction_declined = true;
+        result.set_truncated(olecf.size, olecf_data.len());
+        return Ok(result);
+    }
+
+    // If this file is just a compound file, no need to carve it out to yet another file on disk
+    if offset == 0 && olecf.size == file_data.len() {
+        result.extraction_declined = true;
+    }
+
+    result.size = olecf.size;
+
+    Ok(result)
+}
+
+/// Stores info about a compound file header
+#[derive(Debug, Default, Clone)]
+pub struct OLECFHeader {
+    pub major_version: u16,
+    pub sector_size: usize,
+    pub fat_sector_count: usize,
+    pub directory_start_sector: u32,
+    /// FAT sector locations stored in the header
+    pub difat: Vec<u32>,
+    pub first_difat_sector: u32,
+    pub difat_sector_count: usize,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct OLECFHeaderBytes {
+    magic: [u8; 8],
+    clsid: [u8; 16],
+    minor_version: zerocopy::U16<LE>,
+    major_version: zerocopy::U16<LE>,
+    byte_order: zerocopy::U16<LE>,
+    sector_shift: zerocopy::U16<LE>,
+    mini_sector_shift: zerocopy::U16<LE>,
+    reserved: [u8; 6],
+    directory_sector_count: zerocopy::U32<LE>,
+    fat_sector_count: zerocopy::U32<LE>,
+    directory_start_sector: zerocopy::U32<LE>,
+    transaction_signature: zerocopy::U32<LE>,
+    mini_stream_cutoff: zerocopy::U32<LE>,
+    mini_fat_start_sector: zerocopy::U32<LE>,
+    mini_fat_sector_count: zerocopy::U32<LE>,
+    difat_start_sector: zerocopy::U32<LE>,
+    difat_sector_count: zerocopy::U32<LE>,
+    difat: [zerocopy::U32<LE>; HEADER_DIFAT_COUNT],
+}
+
+/// Parses a compound file header
+pub fn parse_olecf_header(olecf_data: &[u8]) -> Result<OLECFHeader, StructureError> {
+    const MAGIC: &[u8; 8] = b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1";
+    const LITTLE_ENDIAN: u16 = 0xFFFE;
+    // Version 3 files have 512 byte sectors, version 4 files have 4096 byte sectors
+    const VERSION_3: u16 = 3;
+    const VERSION_3_SECTOR_SHIFT: u16 = 9;
+    const VERSION_4: u16 = 4;
+    const VERSION_4_SECTOR_SHIFT: u16 = 12;
+    const MINI_SECTOR_SHIFT: u16 = 6;
+    const MINI_STREAM_CUTOFF: u32 = 4096;
+
+    let (olecf_header, _) =
+        OLECFHeaderBytes::ref_from_prefix(olecf_data).map_err(|_| StructureError)?;
+
+    let major_version = olecf_header.major_version.get();
+    let sector_shift = olecf_header.sector_shift.get();
+
+    let valid_sector_shift = match major_version {
+        // Version 3 files have no directory sector count
+        VERSION_3 => {
+            sector_shift == VERSION_3_SECTOR_SHIFT && olecf_header.directory_sector_count == 0
+        }
+        VERSION_4 => sector_shift == VERSION_4_SECTOR_SHIFT,
+        _ => false,
+    };
+
+    if olecf_header.magic != *MAGIC
+        || olecf_header.clsid.iter().any(|&b| b != 0)
+        || olecf_header.reserved.iter().any(|&b| b != 0)
+        || olecf_header.byte_order != LITTLE_ENDIAN
+        || olecf_header.mini_sector_shift != MINI_SECTOR_SHIFT
+        || olecf_header.mini_stream_cutoff != MINI_STREAM_CUTOFF
+        || olecf_header.fat_sector_count == 0
+        || !valid_sector_shift
+    {
+        return Err(StructureError);
+    }
+
+    let fat_sector_count = olecf_header.fat_sector_count.get() as usize;
+
+    Ok(OLECFHeader {
+        major_version,
+        sector_size: 1 << sector_shift,
+        fat_sector_count,
+        directory_start_sector: olecf_header.directory_start_sector.get(),
+        difat: olecf_header
+            .difat
+            .iter()
+            .take(fat_sector_count)
+            .map(|sector| sector.get())
+            .collect(),
+        first_difat_sector: olecf_header.difat_start_sector.get(),
+        difat_sector_count: olecf_header.difat_sector_count.get() as usize,
+    })
+}
+
+/// Describes a compound file
+#[derive(Debug, Default, Clone)]
+pub struct OLECFContainer {
+    pub header: OLECFHeader,
+    /// Locations of the FAT sectors
+    pub fat_sectors: Vec<u32>,
+    /// Total size of the compound file, including the header, up to the last sector in use
+    pub size: usize,
+    /// CLSID of the root storage object, if it has one
+    pub root_clsid: Option<String>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct OLECFDirectoryEntryBytes {
+    name: [u8; 64],
+    name_size: zerocopy::U16<LE>,
+    object_type: u8,
+    color: u8,
+    left_sibling: zerocopy::U32<LE>,
+    right_sibling: zerocopy::U32<LE>,
+    child: zerocopy::U32<LE>,
+    clsid: [u8; 16],
+}
+
+/// Parses a compound file's header, FAT and root directory entry to determine the size of the compound file
+pub fn parse_olecf(olecf_data: &[u8]) -> Result<OLECFContainer, StructureError> {
+    // The first directory entry is always the root storage object
+    const ROOT_STORAGE_OBJECT: u8 = 5;
+
+    let header = parse_olecf_header(olecf_data)?;
+    let fat_sectors = olecf_fat_sectors(olecf_data, &header)?;
+
+    let entries_per_sector = header.sector_size / FAT_ENTRY_SIZE;
+    let mut last_used_sector: Option<usize> = None;
+
+    for (fat_index, &fat_sector) in fat_sectors.iter().enumerate() {
+        let fat_data = olecf_sector(olecf_data, &header, fat_sector)?;
+
+        for (entry_index, entry) in fat_data.chunks_exact(FAT_ENTRY_SIZE).enumerate() {
+            let entry = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+
+            if entry != FREE_SECTOR {
+                last_used_sector = Some((fat_index * entries_per_sector) + entry_index);
+            }
+        }
+
+        // Each FAT sector must be marked as such in the FAT
+        if olecf_fat_entry(olecf_data, &header, &fat_sectors, fat_sector)? != FAT_SECTOR {
+            return Err(StructureError);
+        }
+    }
+
+    // The FAT always has at least one entry in use, for the FAT itself; sector numbers start after the header
+    let size = (last_used_sector.ok_or(StructureError)? + 2) * header.sector_size;
+
+    let directory_data = olecf_sector(olecf_data, &header, header.directory_start_sector)?;
+    let (root_entry, _) =
+        OLECFDirectoryEntryBytes::ref_from_prefix(directory_data).map_err(|_| StructureError)?;
+
+    if root_entry.object_type != ROOT_STORAGE_OBJECT {
+        return Err(StructureError);
+    }
+
+    let root_clsid = root_entry
+        .clsid
+        .iter()
+        .any(|&b| b != 0)
+        .then(|| format_guid(&root_entry.clsid));
+
+    Ok(OLECFContainer {
+        header,
+        fat_sectors,
+        size,
+        root_clsid,
+    })
+}
+
+/// Returns the data in the specified sector; sector numbers start after the header
+fn olecf_sector<'a>(
+    olecf_data: &'a [u8],
+    header: &OLECFHeader,
+    sector: u32,
+) -> Result<&'a [u8], StructureError> {
+    let sector_offset = (sector as usize + 1) * header.sector_size;
+
+    olecf_data
+        .get(sector_offset..sector_offset + header.sector_size)
+        .ok_or(StructureError)
+}
+
+/// Returns the FAT entry for the specified sector
+fn olecf_fat_entry(
+    olecf_data: &[u8],
+    header: &OLECFHeader,
+    fat_sectors: &[u32],
+    sector: u32,
+) -> Result<u32, StructureError> {
+    let entries_per_sector = header.sector_size / FAT_ENTRY_SIZE;
+    let fat_sector = fat_sectors
+        .get(sector as usize / entries_per_sector)
+        .ok_or(StructureError)?;
+    let fat_data = olecf_sector(olecf_data, header, *fat_sector)?;
+    let entry_offset = (sector as usize % entries_per_sector) * FAT_ENTRY_SIZE;
+    let entry = &fat_data[entry_offset..entry_offset + FAT_ENTRY_SIZE];
+
+    Ok(u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]))
+}
+
+/// Returns the locations of all FAT sectors, from the header and the DIFAT sector chain
+fn olecf_fat_sectors(olecf_data: &[u8], header: &OLECFHeader) -> Result<Vec<u32>, StructureError> {
+    // Every FAT sector must lie within the available data
+    if header.fat_sector_count > olecf_data.len() / header.sector_size {
+        return Err(StructureError);
+    }
+
+    let mut fat_sectors = header.difat.clone();
+    let mut difat_sector = header.first_difat_sector;
+    let mut visited_difat_sectors: HashSet<u32> = HashSet::new();
+
+    // Each DIFAT sector lists FAT sector locations, followed by the location of the next DIFAT sector
+    for _ in 0..header.difat_sector_count {
+        if fat_sectors.len() >= header.fat_sector_count {
+            break;
+        }
+
+        // A DIFAT chain that loops back on itself is corrupt
+        if !visited_difat_sectors.insert(difat_sector) {
+            return Err(StructureError);
+        }
+
+        let difat_data = olecf_sector(olecf_data, header, difat_sector)?;
+        let (fat_sector_data, next_difat_sector) =
+            difat_data.split_at(header.sector_size - FAT_ENTRY_SIZE);
+
+        for entry in fat_sector_data.chunks_exact(FAT_ENTRY_SIZE) {
+            if fat_sectors.len() < header.fat_sector_count {
+                fat_sectors.push(u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]));
+            }
+        }
+
+        difat_sector = u32::from_le_bytes([
+            next_difat_sector[0],
+            next_difat_sector[1],
+            next_difat_sector[2],
+            next_difat_sector[3],
+        ]);
+    }
+
+    if fat_sectors.len() != header.fat_sector_count {
+        return Err(StructureError);
+    }
+
+    Ok(fat_sectors)
+}
+
+/// Defines the extractor for carving out compound files
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::olecf::olecf_extractor;
+///
+/// match olecf_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(file_name) => println!("Carve extractor OK: {}", file_name),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn olecf_extractor() -> Extractor {
+    Extractor {
+        do_not_recurse: true,
+        ..carve_extractor("compound_file.ole")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal version 3 compound file with an MSI root CLSID, followed by trailing data (see tests/inputs/gen_olecf.sh)
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/olecf.bin");
+    const SECTOR_SIZE: usize = 512;
+    const OLECF_SIZE: usize = 3 * SECTOR_SIZE;
+
+    #[test]
+    fn minimal_header() {
+        let header = parse_olecf_header(VALID).unwrap();
+        assert_eq!(header.major_version, 3);
+        assert_eq!(header.sector_size, SECTOR_SIZE);
+        assert_eq!(header.fat_sector_count, 1);
+        assert_eq!(header.directory_start_sector, 1);
+        assert_eq!(header.difat, vec![0]);
+
+        let container = parse_olecf(VALID).unwrap();
+        assert_eq!(container.size, OLECF_SIZE);
+        assert_eq!(
+            container.root_clsid.as_deref(),
+            Some("000C1084-0000-0000-C000-000000000046")
+        );
+    }
+
+    #[test]
+    fn embedded_container() {
+        let mut file_data = vec![0; 0x200];
+        file_data.extend_from_slice(VALID);
+
+        let signature = olecf_parser(&file_data, 0x200).unwrap();
+        assert_eq!(signature.offset, 0x200);
+        assert_eq!(signature.size, OLECF_SIZE);
+        assert!(!signature.extraction_declined);
+    }
+
+    #[test]
+    fn invalid_fat() {
+        // The FAT sector must be marked as a FAT sector in the FAT
+        let mut olecf = VALID.to_vec();
+        olecf[SECTOR_SIZE..SECTOR_SIZE + FAT_ENTRY_SIZE].copy_from_slice(b"\xFE\xFF\xFF\xFF");
+        assert!(parse_olecf(&olecf).is_err());
+
+        // Version 3 files have 512 byte sectors
+        let mut olecf = VALID.to_vec();
+        olecf[30] = 12;
+        assert!(parse_olecf_header(&olecf).is_err());
+    }
+}
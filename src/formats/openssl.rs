@@ -1,4 +1,3 @@
-use crate::common::is_printable_ascii;
 use crate::signatures::{CONFIDENCE_LOW, CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
 use crate::structures::StructureError;
 use zerocopy::{BE, FromBytes, Immutable, KnownLayout, Unaligned};
@@ -45,10 +44,13 @@ pub fn openssl_crypt_parser(
 // Returns true if the salt is entirely comprised of NULL and/or ASCII bytes
 fn is_salt_invalid(salt: u64) -> bool {
     const SALT_LEN: usize = std::mem::size_of::<u64>();
+    // Text, including the control characters that may appear in it
+    const ASCII_MIN: u8 = 0x0A;
+    const ASCII_MAX: u8 = 0x7E;
 
     (0..SALT_LEN).all(|i| {
         let byte = ((salt >> (8 * i)) & 0xFF) as u8;
-        byte == 0 || is_printable_ascii(byte)
+        byte == 0 || (ASCII_MIN..=ASCII_MAX).contains(&byte)
     })
 }
 
@@ -1,69 +1,306 @@
-use crate::signatures::{SignatureError, SignatureResult};
+use crate::extractors::Extractor;
+use crate::extractors::carve::carve_extractor;
+use crate::signatures::{CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
+use crate::structures::StructureError;
 
 /// Human readable description
 pub const DESCRIPTION: &str = "PDF document";
 
+/// All PDF documents start with these bytes
+const PDF_MAGIC: &[u8; 5] = b"%PDF-";
+
 /// PDF magic bytes
 pub fn pdf_magic() -> Vec<Vec<u8>> {
-    // This assumes a major version of 1
-    vec![b"%PDF-1.".to_vec()]
+    // This assumes a major version of 1 or 2
+    vec![b"%PDF-1.".to_vec(), b"%PDF-2.".to_vec()]
 }
 
 /// Validate a PDF signature
 pub fn pdf_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
+    let mut result = SignatureResult {
+        description: DESCRIPTION.to_string(),
+        offset,
+        size: 0,
+        ..Default::default()
+    };
+
+    let pdf_data = file_data.get(offset..).unwrap_or_default();
+    let pdf_header = parse_pdf_header(pdf_data).map_err(|_| SignatureError)?;
+
+    // Update the result description to include the version number
+    result.description = format!(
+        "{}, version {}.{}",
+        result.description, pdf_header.version_major, pdf_header.version_minor
+    );
+
+    // Without a trailer, there's no telling where the document ends
+    let Ok(pdf_trailer) = find_pdf_eof(pdf_data) else {
+        result.extraction_declined = true;
+        return Ok(result);
+    };
+
+    result.size = pdf_trailer.size;
+    result.trailer_offset = Some(offset + pdf_trailer.eof_offset);
+    result.confidence = CONFIDENCE_MEDIUM;
+    result.description = format!("{}, total size: {} bytes", result.description, result.size);
+
+    // If this file is just a PDF, no need to carve it out to yet another file on disk
+    if offset == 0 && result.size == file_data.len() {
+        result.extraction_declined = true;
+    }
+
+    Ok(result)
+}
+
+/// Defines the extractor for carving PDF documents out of the surrounding data
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::pdf::pdf_extractor;
+///
+/// match pdf_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(file_name) => println!("Carve extractor OK: {}", file_name),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn pdf_extractor() -> Extractor {
+    Extractor {
+        do_not_recurse: true,
+        ..carve_extractor("document.pdf")
+    }
+}
+
+/// Stores info about a PDF header
+#[derive(Debug, Default, Clone)]
+pub struct PDFHeader {
+    pub version_major: u8,
+    pub version_minor: u8,
+}
+
+/// Parses a PDF header
+///
+/// ```
+/// use binwalk_ng::formats::pdf::parse_pdf_header;
+///
+/// let header = parse_pdf_header(b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n1 0 obj").unwrap();
+/// assert_eq!(header.version_major, 1);
+/// assert_eq!(header.version_minor, 7);
+///
+/// assert!(parse_pdf_header(b"%PDF-1.7 is not a header").is_err());
+/// ```
+pub fn parse_pdf_header(pdf_data: &[u8]) -> Result<PDFHeader, StructureError> {
     // More than enough data for our needs
     const MIN_PDF_SIZE: usize = 16;
 
     const NEWLINE_OFFSET: usize = 8;
+    const MAJOR_NUMBER_OFFSET: usize = 5;
     const MINOR_NUMBER_OFFSET: usize = 7;
 
     const ASCII_ZERO: u8 = 0x30;
-    const ASCII_NINE: u8 = 0x39;
     const ASCII_NEWLINE: u8 = 0x0A;
     const ASCII_PERCENT: u8 = 0x25;
     const ASCII_CARRIGE_RETURN: u8 = 0x0D;
 
-    let mut result = SignatureResult {
-        description: DESCRIPTION.to_string(),
-        offset,
-        size: 0,
-        ..Default::default()
-    };
-
     let newline_characters: Vec<u8> = vec![ASCII_NEWLINE, ASCII_CARRIGE_RETURN];
 
-    let pdf_header_start = offset;
-    let pdf_header_end = pdf_header_start + MIN_PDF_SIZE;
-
     // PDF header is expected to start with something like: %PDF-1.7\n%
-    if let Some(pdf_header) = file_data.get(pdf_header_start..pdf_header_end) {
-        // Get the minor version number at the expected offset
-        let version_minor: u8 = pdf_header[MINOR_NUMBER_OFFSET];
-
-        // Sanity check the minor version number
-        if (ASCII_ZERO..=ASCII_NINE).contains(&version_minor) {
-            // Update the result description to include the version number
-            result.description = format!(
-                "{}, version 1.{}",
-                result.description,
-                version_minor - ASCII_ZERO
-            );
-
-            // Search the remaining bytes for new line characters followed by a percent character
-            for byte in pdf_header[NEWLINE_OFFSET..].iter().copied() {
-                // Any new line or carrige return byte is OK, just keep going
-                if newline_characters.contains(&byte) {
-                    continue;
-                // There should be a percent character
-                } else if byte == ASCII_PERCENT {
-                    return Ok(result);
-                // Anything else is invalid
-                } else {
-                    break;
-                }
-            }
+    let pdf_header = pdf_data.get(..MIN_PDF_SIZE).ok_or(StructureError)?;
+
+    if !pdf_header.starts_with(PDF_MAGIC) {
+        return Err(StructureError);
+    }
+
+    // Get the major and minor version numbers at the expected offsets
+    let version_major: u8 = pdf_header[MAJOR_NUMBER_OFFSET];
+    let version_minor: u8 = pdf_header[MINOR_NUMBER_OFFSET];
+
+    // Sanity check the version numbers
+    if !(b'1'..=b'2').contains(&version_major) || !version_minor.is_ascii_digit() {
+        return Err(StructureError);
+    }
+
+    // Search the remaining bytes for new line characters followed by a percent character
+    for byte in pdf_header[NEWLINE_OFFSET..].iter().copied() {
+        // Any new line or carrige return byte is OK, just keep going
+        if newline_characters.contains(&byte) {
+            continue;
+        // There should be a percent character
+        } else if byte == ASCII_PERCENT {
+            return Ok(PDFHeader {
+                version_major: version_major - ASCII_ZERO,
+                version_minor: version_minor - ASCII_ZERO,
+            });
+        // Anything else is invalid
+        } else {
+            break;
         }
     }
 
-    Err(SignatureError)
+    Err(StructureError)
+}
+
+/// Describes the end of a PDF document
+#[derive(Debug, Default, Clone)]
+pub struct PDFTrailer {
+    /// Offset of the last valid %%EOF marker, relative to the start of the PDF
+    pub eof_offset: usize,
+    /// Offset of the last cross-reference section, as specified by the startxref keyword preceding the last %%EOF marker
+    pub startxref: usize,
+    /// Number of valid %%EOF markers; incrementally updated documents (and linearized documents) have more than one
+    pub eof_count: usize,
+    /// Total size of the PDF document, including the end-of-line following the last %%EOF marker
+    pub size: usize,
+}
+
+/// Locates the end of a PDF document: each incremental update appends a new cross-reference section, trailer and
+/// %%EOF marker to the document, so the document ends at the last %%EOF marker
+///
+/// ```
+/// use binwalk_ng::formats::pdf::find_pdf_eof;
+///
+/// let pdf_data = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\nxref\n0 1\n0000000000 65535 f \ntrailer\n<</Size 1>>\nstartxref\n15\n%%EOF\n%%EOF";
+///
+/// let trailer = find_pdf_eof(pdf_data).unwrap();
+/// assert_eq!(trailer.startxref, 15);
+/// assert_eq!(trailer.eof_count, 1);
+/// assert_eq!(trailer.size, pdf_data.len() - 5);
+/// ```
+pub fn find_pdf_eof(pdf_data: &[u8]) -> Result<PDFTrailer, StructureError> {
+    const EOF_MARKER: &[u8] = b"%%EOF";
+
+    // Don't go looking for %%EOF markers in whatever other PDF follows this one
+    let search_end = pdf_data
+        .get(PDF_MAGIC.len()..)
+        .and_then(|pdf_body| memchr::memmem::find(pdf_body, PDF_MAGIC))
+        .map_or(pdf_data.len(), |next_pdf| PDF_MAGIC.len() + next_pdf);
+
+    let search_data = &pdf_data[..search_end];
+
+    let mut trailer: Option<PDFTrailer> = None;
+    let mut eof_count: usize = 0;
+
+    for eof_offset in memchr::memmem::find_iter(search_data, EOF_MARKER) {
+        // A valid %%EOF marker is preceded by a startxref keyword and the offset of a cross-reference section
+        let Some(startxref) = parse_startxref(&search_data[..eof_offset]) else {
+            continue;
+        };
+
+        if startxref >= eof_offset {
+            continue;
+        }
+
+        eof_count += 1;
+
+        let marker_end = eof_offset + EOF_MARKER.len();
+
+        trailer = Some(PDFTrailer {
+            eof_offset,
+            startxref,
+            eof_count,
+            size: marker_end + end_of_line_size(&search_data[marker_end..]),
+        });
+    }
+
+    trailer.ok_or(StructureError)
+}
+
+/// Parses the cross-reference offset from the end of `trailer_data`, which is expected to end with something like
+/// "startxref\r\n1234\r\n"
+fn parse_startxref(trailer_data: &[u8]) -> Option<usize> {
+    const STARTXREF: &[u8] = b"startxref";
+
+    let trailer_data = trailer_data.trim_ascii_end();
+
+    let digits_start = trailer_data
+        .iter()
+        .rposition(|byte| !byte.is_ascii_digit())
+        .map_or(0, |position| position + 1);
+
+    let startxref = std::str::from_utf8(&trailer_data[digits_start..])
+        .ok()?
+        .parse()
+        .ok()?;
+
+    trailer_data[..digits_start]
+        .trim_ascii_end()
+        .ends_with(STARTXREF)
+        .then_some(startxref)
+}
+
+/// Returns the size of the end-of-line sequence at the start of `data`: CR, LF, CRLF, or nothing
+fn end_of_line_size(data: &[u8]) -> usize {
+    match data {
+        [b'\r', b'\n', ..] => 2,
+        [b'\r' | b'\n', ..] => 1,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PDF document with one incremental update, followed by trailing data (see tests/inputs/gen_pdf_incremental.sh)
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/pdf_incremental.bin");
+    const PDF_SIZE: usize = 394;
+    const EOF_SIZE: usize = b"%%EOF\r\n".len();
+
+    #[test]
+    fn incremental_update() {
+        let pdf = &VALID[..PDF_SIZE];
+
+        let header = parse_pdf_header(pdf).unwrap();
+        assert_eq!(header.version_major, 1);
+        assert_eq!(header.version_minor, 7);
+
+        // The document ends at the last %%EOF marker, not the first
+        let trailer = find_pdf_eof(pdf).unwrap();
+        assert_eq!(trailer.eof_count, 2);
+        assert_eq!(trailer.eof_offset, PDF_SIZE - EOF_SIZE);
+        assert_eq!(trailer.size, PDF_SIZE);
+    }
+
+    #[test]
+    fn embedded_pdf() {
+        // The trailing data contains a %%EOF marker of its own, but without a startxref it doesn't end the document
+        let mut file_data = vec![0xFF; 0x100];
+        file_data.extend_from_slice(VALID);
+
+        let signature = pdf_parser(&file_data, 0x100).unwrap();
+        assert_eq!(signature.offset, 0x100);
+        assert_eq!(signature.size, PDF_SIZE);
+        assert_eq!(signature.trailer_offset, Some(0x100 + PDF_SIZE - EOF_SIZE));
+        assert!(!signature.extraction_declined);
+        assert!(signature.description.contains("version 1.7"));
+    }
+
+    #[test]
+    fn back_to_back_pdfs() {
+        // The first document must not extend into the second
+        let mut file_data = VALID[..PDF_SIZE].to_vec();
+        file_data.extend_from_slice(VALID);
+
+        let signature = pdf_parser(&file_data, 0).unwrap();
+        assert_eq!(signature.size, PDF_SIZE);
+    }
+
+    #[test]
+    fn missing_trailer() {
+        let signature = pdf_parser(&VALID[..0x80], 0).unwrap();
+        assert_eq!(signature.size, 0);
+        assert!(signature.extraction_declined);
+    }
 }
@@ -1,5 +1,8 @@
+use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
 use crate::signatures::{CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
 use crate::structures::StructureError;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
 use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
 
 /// Human readable description
@@ -30,9 +33,45 @@ pub fn pe_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Sig
     // Parse the PE header
     if let Ok(pe_header) = parse_pe_header(&file_data[offset..]) {
         result.description = format!(
-            "{}, machine type: {}",
-            result.description, pe_header.machine
+            "{}, machine type: {}, subsystem: {}",
+            result.description, pe_header.machine, pe_header.subsystem
         );
+
+        // The PE file ends with the farthest section's raw data
+        let available_size = file_data.len() - offset;
+
+        if pe_header.size > available_size {
+            result.extraction_declined = true;
+            result.set_truncated(pe_header.size, available_size);
+            return Ok(result);
+        }
+
+        result.size = pe_header.size;
+        result.description = format!("{}, total size: {} bytes", result.description, result.size);
+
+        // Summarize any embedded resources; there is nothing to extract without them
+        let resources = parse_pe_resources(&file_data[offset..], &pe_header).unwrap_or_default();
+
+        if resources.is_empty() {
+            result.extraction_declined = true;
+        } else {
+            let mut type_counts: BTreeMap<&str, usize> = BTreeMap::new();
+            for resource in &resources {
+                *type_counts.entry(&resource.resource_type).or_default() += 1;
+            }
+
+            let type_counts: Vec<String> = type_counts
+                .iter()
+                .map(|(resource_type, count)| format!("{resource_type}: {count}"))
+                .collect();
+
+            result.description = format!(
+                "{}, resources: {}",
+                result.description,
+                type_counts.join(", ")
+            );
+        }
+
         return Ok(result);
     }
 
@@ -40,8 +79,56 @@ pub fn pe_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Sig
 }
 
 /// Stores info about the PE file
+#[derive(Debug, Default, Clone)]
 pub struct PEHeader {
     pub machine: String,
+    pub subsystem: String,
+    /// Total size of the PE file: the end of the headers, or of the farthest section's raw data
+    pub size: usize,
+    /// Section table entries, used to map RVAs to file offsets
+    pub sections: Vec<PESection>,
+    /// RVA and size of the resource directory (the .rsrc section contents), if the image has one
+    pub resource_directory: Option<(usize, usize)>,
+}
+
+/// Describes a PE section
+#[derive(Debug, Default, Clone)]
+pub struct PESection {
+    pub name: String,
+    pub virtual_address: usize,
+    pub virtual_size: usize,
+    pub file_offset: usize,
+    pub file_size: usize,
+}
+
+impl PEHeader {
+    /// Converts a relative virtual address to an offset from the start of the PE file, if it is backed by file data
+    pub fn rva_to_offset(&self, rva: usize) -> Option<usize> {
+        self.sections.iter().find_map(|section| {
+            let section_offset = rva.checked_sub(section.virtual_address)?;
+
+            if section_offset < section.file_size {
+                Some(section.file_offset + section_offset)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Describes a single resource embedded in a PE file
+#[derive(Debug, Default, Clone)]
+pub struct PEResource {
+    /// Resource type, e.g. "RT_ICON"; unknown types are identified by their numeric ID
+    pub resource_type: String,
+    /// Resource name, or its numeric ID
+    pub name: String,
+    /// Language ID
+    pub language: u32,
+    /// Offset of the resource data, relative to the start of the PE file
+    pub offset: usize,
+    /// Size of the resource data
+    pub size: usize,
 }
 
 #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
@@ -83,16 +170,18 @@ struct PEHeaderBytes {
 
 /// Partially parse a PE header
 pub fn parse_pe_header(pe_data: &[u8]) -> Result<PEHeader, StructureError> {
+    const DOS_MAGIC: u16 = 0x5A4D;
     const PE_MAGIC: u32 = 0x00004550;
 
     // Parse the DOS header
     let (dos_header, _) = DOSHeaderBytes::ref_from_prefix(pe_data).map_err(|_| StructureError)?;
-    // Sanity check the reserved header fields; they should all be 0
-    if dos_header
-        .e_res_1
-        .iter()
-        .chain(&dos_header.e_res_2)
-        .all(|&b| b == 0)
+    // Sanity check the DOS magic and the reserved header fields; they should all be 0
+    if dos_header.e_magic == DOS_MAGIC
+        && dos_header
+            .e_res_1
+            .iter()
+            .chain(&dos_header.e_res_2)
+            .all(|&b| b == 0)
     {
         // Start and end offsets of the PE header
         let pe_header_start: usize = dos_header.e_lfanew.get() as usize;
@@ -140,10 +229,491 @@ pub fn parse_pe_header(pe_data: &[u8]) -> Result<PEHeader, StructureError> {
                 }
                 .to_string();
 
-                return Ok(PEHeader { machine });
+                let optional_header_start = pe_header_end;
+                let optional_header_size = pe_header.optional_header_size.get() as usize;
+                let optional_header = pe_data
+                    .get(optional_header_start..optional_header_start + optional_header_size);
+
+                let section_table_start = optional_header_start + optional_header_size;
+                let section_count = pe_header.number_of_sections.get() as usize;
+                let sections = parse_sections(pe_data, section_table_start, section_count);
+
+                let (subsystem, headers_size) =
+                    parse_optional_header_info(optional_header).unwrap_or((0, 0));
+
+                // The headers (including the entire section table) must fit in the file, as must every section's data
+                let section_table_end = section_table_start
+                    + (section_count * std::mem::size_of::<SectionHeaderBytes>());
+                let size = sections
+                    .iter()
+                    .map(|section| section.file_offset + section.file_size)
+                    .fold(section_table_end.max(headers_size), usize::max);
+
+                return Ok(PEHeader {
+                    machine,
+                    subsystem: pe_subsystem_name(subsystem).to_string(),
+                    size,
+                    sections,
+                    resource_directory: parse_resource_data_directory(optional_header),
+                });
             }
         }
     }
 
     Err(StructureError)
 }
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct DataDirectoryBytes {
+    rva: zerocopy::U32<LE>,
+    size: zerocopy::U32<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct SectionHeaderBytes {
+    name: [u8; 8],
+    virtual_size: zerocopy::U32<LE>,
+    virtual_address: zerocopy::U32<LE>,
+    size_of_raw_data: zerocopy::U32<LE>,
+    pointer_to_raw_data: zerocopy::U32<LE>,
+    pointer_to_relocations: zerocopy::U32<LE>,
+    pointer_to_line_numbers: zerocopy::U32<LE>,
+    number_of_relocations: zerocopy::U16<LE>,
+    number_of_line_numbers: zerocopy::U16<LE>,
+    characteristics: zerocopy::U32<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct ResourceDirectoryBytes {
+    characteristics: zerocopy::U32<LE>,
+    timestamp: zerocopy::U32<LE>,
+    major_version: zerocopy::U16<LE>,
+    minor_version: zerocopy::U16<LE>,
+    number_of_named_entries: zerocopy::U16<LE>,
+    number_of_id_entries: zerocopy::U16<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct ResourceDirectoryEntryBytes {
+    name: zerocopy::U32<LE>,
+    offset: zerocopy::U32<LE>,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct ResourceDataEntryBytes {
+    data_rva: zerocopy::U32<LE>,
+    size: zerocopy::U32<LE>,
+    codepage: zerocopy::U32<LE>,
+    reserved: zerocopy::U32<LE>,
+}
+
+/// Parses the section table
+fn parse_sections(pe_data: &[u8], table_offset: usize, section_count: usize) -> Vec<PESection> {
+    let section_header_size = std::mem::size_of::<SectionHeaderBytes>();
+
+    (0..section_count)
+        .map_while(|i| {
+            let section_offset = table_offset + (i * section_header_size);
+            let (section, _) =
+                SectionHeaderBytes::ref_from_prefix(pe_data.get(section_offset..)?).ok()?;
+
+            Some(PESection {
+                name: String::from_utf8_lossy(&section.name)
+                    .trim_end_matches('\0')
+                    .to_string(),
+                virtual_address: section.virtual_address.get() as usize,
+                virtual_size: section.virtual_size.get() as usize,
+                file_offset: section.pointer_to_raw_data.get() as usize,
+                file_size: section.size_of_raw_data.get() as usize,
+            })
+        })
+        .collect()
+}
+
+/// Returns the subsystem and SizeOfHeaders fields from the optional header; these are at the same offsets in PE32 and
+/// PE32+ optional headers
+fn parse_optional_header_info(optional_header: Option<&[u8]>) -> Option<(u16, usize)> {
+    const SIZE_OF_HEADERS_OFFSET: usize = 60;
+    const SUBSYSTEM_OFFSET: usize = 68;
+
+    let optional_header = optional_header?;
+
+    let headers_size = u32::from_le_bytes(
+        optional_header
+            .get(SIZE_OF_HEADERS_OFFSET..SIZE_OF_HEADERS_OFFSET + 4)?
+            .try_into()
+            .unwrap(),
+    ) as usize;
+    let subsystem = u16::from_le_bytes(
+        optional_header
+            .get(SUBSYSTEM_OFFSET..SUBSYSTEM_OFFSET + 2)?
+            .try_into()
+            .unwrap(),
+    );
+
+    Some((subsystem, headers_size))
+}
+
+/// Returns the name of a PE subsystem
+const fn pe_subsystem_name(subsystem: u16) -> &'static str {
+    match subsystem {
+        1 => "Native",
+        2 => "Windows GUI",
+        3 => "Windows console",
+        5 => "OS/2 console",
+        7 => "POSIX console",
+        8 => "Native Windows 9x driver",
+        9 => "Windows CE GUI",
+        10 => "EFI application",
+        11 => "EFI boot service driver",
+        12 => "EFI runtime driver",
+        13 => "EFI ROM",
+        14 => "Xbox",
+        16 => "Windows boot application",
+        _ => "Unknown",
+    }
+}
+
+/// Returns the RVA and size of the resource directory from the optional header's data directories
+fn parse_resource_data_directory(optional_header: Option<&[u8]>) -> Option<(usize, usize)> {
+    const PE32_MAGIC: u16 = 0x10B;
+    const PE32_PLUS_MAGIC: u16 = 0x20B;
+    // Index of the resource directory in the data directories
+    const RESOURCE_DIRECTORY_INDEX: usize = 2;
+
+    let optional_header = optional_header?;
+    let magic = u16::from_le_bytes(optional_header.get(0..2)?.try_into().unwrap());
+
+    // Offset of the NumberOfRvaAndSizes field, which is followed by the data directories
+    let rva_count_offset: usize = match magic {
+        PE32_MAGIC => 92,
+        PE32_PLUS_MAGIC => 108,
+        _ => return None,
+    };
+
+    let rva_count = u32::from_le_bytes(
+        optional_header
+            .get(rva_count_offset..rva_count_offset + 4)?
+            .try_into()
+            .unwrap(),
+    ) as usize;
+
+    if rva_count <= RESOURCE_DIRECTORY_INDEX {
+        return None;
+    }
+
+    let directory_offset = rva_count_offset
+        + 4
+        + (RESOURCE_DIRECTORY_INDEX * std::mem::size_of::<DataDirectoryBytes>());
+    let (directory, _) =
+        DataDirectoryBytes::ref_from_prefix(optional_header.get(directory_offset..)?).ok()?;
+
+    if directory.rva.get() == 0 || directory.size.get() == 0 {
+        return None;
+    }
+
+    Some((directory.rva.get() as usize, directory.size.get() as usize))
+}
+
+/// Walks the type / name / language levels of the resource directory tree, returning all resources with file data
+pub fn parse_pe_resources(
+    pe_data: &[u8],
+    pe_header: &PEHeader,
+) -> Result<Vec<PEResource>, StructureError> {
+    // The resource tree is always exactly three levels deep
+    const TYPE_LEVEL: usize = 0;
+    const NAME_LEVEL: usize = 1;
+    const LANGUAGE_LEVEL: usize = 2;
+    // The high bit of a directory entry's name / offset indicates a name string / subdirectory respectively
+    const HIGH_BIT: u32 = 0x8000_0000;
+    // Sanity limit on the total number of resources
+    const MAX_RESOURCES: usize = 4096;
+    // Sanity limit on the total number of directory entries visited
+    const MAX_ENTRIES: usize = 16384;
+
+    let (resource_rva, _) = pe_header.resource_directory.ok_or(StructureError)?;
+    let resource_offset = pe_header
+        .rva_to_offset(resource_rva)
+        .ok_or(StructureError)?;
+    let resource_data = pe_data.get(resource_offset..).ok_or(StructureError)?;
+
+    let mut resources: Vec<PEResource> = Vec::new();
+    let mut entries_visited: usize = 0;
+
+    // Subdirectories may be referenced more than once; only process each directory once
+    let mut visited_directories: HashSet<usize> = HashSet::new();
+
+    // Stack of (directory offset, tree level, [type, name]) left to process
+    let mut directories: Vec<(usize, usize, [String; 2])> =
+        vec![(0, TYPE_LEVEL, Default::default())];
+
+    'walk: while let Some((directory_offset, level, path)) = directories.pop() {
+        if !visited_directories.insert(directory_offset) {
+            continue;
+        }
+
+        let (directory, _) = ResourceDirectoryBytes::ref_from_prefix(
+            resource_data
+                .get(directory_offset..)
+                .ok_or(StructureError)?,
+        )
+        .map_err(|_| StructureError)?;
+
+        let entry_count = directory.number_of_named_entries.get() as usize
+            + directory.number_of_id_entries.get() as usize;
+        let entries_start = directory_offset + std::mem::size_of::<ResourceDirectoryBytes>();
+
+        for i in 0..entry_count {
+            entries_visited += 1;
+
+            if entries_visited > MAX_ENTRIES {
+                break 'walk;
+            }
+
+            let entry_offset =
+                entries_start + (i * std::mem::size_of::<ResourceDirectoryEntryBytes>());
+            let (entry, _) = ResourceDirectoryEntryBytes::ref_from_prefix(
+                resource_data.get(entry_offset..).ok_or(StructureError)?,
+            )
+            .map_err(|_| StructureError)?;
+
+            let name = entry.name.get();
+            let entry_name = if name & HIGH_BIT != 0 {
+                resource_name(resource_data, (name & !HIGH_BIT) as usize)?
+            } else if level == TYPE_LEVEL {
+                resource_type_name(name)
+            } else {
+                name.to_string()
+            };
+
+            let child_offset = (entry.offset.get() & !HIGH_BIT) as usize;
+            let is_directory = entry.offset.get() & HIGH_BIT != 0;
+
+            match (level, is_directory) {
+                (TYPE_LEVEL, true) => {
+                    directories.push((child_offset, NAME_LEVEL, [entry_name, String::new()]))
+                }
+                (NAME_LEVEL, true) => {
+                    directories.push((child_offset, LANGUAGE_LEVEL, [path[0].clone(), entry_name]))
+                }
+                (LANGUAGE_LEVEL, false) => {
+                    let (data_entry, _) = ResourceDataEntryBytes::ref_from_prefix(
+                        resource_data.get(child_offset..).ok_or(StructureError)?,
+                    )
+                    .map_err(|_| StructureError)?;
+
+                    // Skip resources whose data is not actually in the file
+                    if let Some(offset) =
+                        pe_header.rva_to_offset(data_entry.data_rva.get() as usize)
+                        && pe_data
+                            .get(offset..offset + data_entry.size.get() as usize)
+                            .is_some()
+                    {
+                        resources.push(PEResource {
+                            resource_type: path[0].clone(),
+                            name: path[1].clone(),
+                            language: name,
+                            offset,
+                            size: data_entry.size.get() as usize,
+                        });
+                    }
+                }
+                // Directories any deeper than the language level are not valid
+                _ => return Err(StructureError),
+            }
+
+            if resources.len() > MAX_RESOURCES {
+                return Err(StructureError);
+            }
+        }
+    }
+
+    resources.sort_by_key(|resource| resource.offset);
+    Ok(resources)
+}
+
+/// Reads a length-prefixed UTF-16LE resource name string
+fn resource_name(resource_data: &[u8], offset: usize) -> Result<String, StructureError> {
+    let length_bytes = resource_data
+        .get(offset..offset + 2)
+        .ok_or(StructureError)?;
+    let length = u16::from_le_bytes(length_bytes.try_into().unwrap()) as usize;
+
+    let name_bytes = resource_data
+        .get(offset + 2..offset + 2 + (length * 2))
+        .ok_or(StructureError)?;
+    let name: Vec<u16> = name_bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+
+    Ok(String::from_utf16_lossy(&name))
+}
+
+/// Returns the name of a predefined resource type
+fn resource_type_name(resource_type: u32) -> String {
+    match resource_type {
+        1 => "RT_CURSOR",
+        2 => "RT_BITMAP",
+        3 => "RT_ICON",
+        4 => "RT_MENU",
+        5 => "RT_DIALOG",
+        6 => "RT_STRING",
+        7 => "RT_FONTDIR",
+        8 => "RT_FONT",
+        9 => "RT_ACCELERATOR",
+        10 => "RT_RCDATA",
+        11 => "RT_MESSAGETABLE",
+        12 => "RT_GROUP_CURSOR",
+        14 => "RT_GROUP_ICON",
+        16 => "RT_VERSION",
+        17 => "RT_DLGINCLUDE",
+        19 => "RT_PLUGPLAY",
+        20 => "RT_VXD",
+        21 => "RT_ANICURSOR",
+        22 => "RT_ANIICON",
+        23 => "RT_HTML",
+        24 => "RT_MANIFEST",
+        _ => return resource_type.to_string(),
+    }
+    .to_string()
+}
+
+/// Defines the internal extractor function for PE resources
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::pe::pe_extractor;
+///
+/// match pe_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn pe_extractor() -> Extractor {
+    Extractor {
+        utility: ExtractorType::Internal(extract_pe_resources),
+        ..Default::default()
+    }
+}
+
+/// Carves each resource in the PE resource directory to `<type>/<name>_<language>.bin`
+pub fn extract_pe_resources(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> ExtractionResult {
+    let mut result = ExtractionResult::default();
+
+    let Some(pe_data) = file_data.get(offset..) else {
+        return result;
+    };
+
+    let Ok(resources) =
+        parse_pe_header(pe_data).and_then(|pe_header| parse_pe_resources(pe_data, &pe_header))
+    else {
+        return result;
+    };
+
+    if let Some(output_directory) = output_directory {
+        let chroot = Chroot::new(output_directory);
+        let mut carved_count: usize = 0;
+
+        for resource in resources {
+            // Resource names are arbitrary strings; keep them from introducing path separators
+            let directory_name = resource.resource_type.replace(['/', '\\'], "_");
+            let file_name = format!(
+                "{}_{}.bin",
+                resource.name.replace(['/', '\\'], "_"),
+                resource.language
+            );
+            let file_path = Path::new(&directory_name).join(file_name);
+
+            if chroot.create_directory(&directory_name)
+                && chroot.carve_file(file_path, pe_data, resource.offset, resource.size)
+            {
+                carved_count += 1;
+            }
+        }
+
+        result.success = carved_count > 0;
+    } else {
+        result.success = !resources.is_empty();
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PE32 EFI application whose .rsrc section holds a single RT_MANIFEST resource; see tests/inputs/gen_pe.sh
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/pe.bin");
+
+    const MANIFEST: &[u8] = b"<assembly/>";
+
+    /// Returns the fixture, embedded 0x10 bytes into a blob of padding
+    fn embedded_fixture() -> Vec<u8> {
+        let mut file_data = vec![0xFF; 0x10];
+        file_data.extend(VALID);
+        file_data.extend([0xFF; 0x100]);
+        file_data
+    }
+
+    #[test]
+    fn reports_size_and_subsystem() {
+        let pe_header = parse_pe_header(VALID).unwrap();
+        assert_eq!(pe_header.machine, "Intel x86");
+        assert_eq!(pe_header.subsystem, "EFI application");
+        assert_eq!(pe_header.size, VALID.len());
+
+        let signature = pe_parser(&embedded_fixture(), 0x10).unwrap();
+        assert_eq!(signature.size, VALID.len());
+
+        // Truncated PE files are reported, but not extracted
+        let signature = pe_parser(&VALID[..VALID.len() - 1], 0).unwrap();
+        assert!(signature.extraction_declined);
+        assert_eq!(signature.declared_size, Some(VALID.len()));
+
+        // The PE header offset must lie within the data
+        let mut bad_lfanew = VALID.to_vec();
+        bad_lfanew[0x3C..0x40].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+        assert!(parse_pe_header(&bad_lfanew).is_err());
+    }
+
+    #[test]
+    fn extracts_resources() {
+        let file_data = embedded_fixture();
+
+        let signature = pe_parser(&file_data, 0x10).unwrap();
+        assert!(signature.description.ends_with("resources: RT_MANIFEST: 1"));
+        assert!(!signature.extraction_declined);
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let extraction = extract_pe_resources(&file_data, 0x10, Some(output_dir.path()));
+        assert!(extraction.success);
+
+        let resource =
+            std::fs::read(output_dir.path().join("RT_MANIFEST").join("1_1033.bin")).unwrap();
+        assert_eq!(resource, MANIFEST);
+    }
+}
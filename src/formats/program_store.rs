@@ -1,4 +1,4 @@
-use crate::common::{epoch_to_string, get_cstring};
+use crate::common::{crc16_ccitt, epoch_to_string, get_cstring};
 use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
 use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
 use crate::structures::StructureError;
@@ -151,20 +151,9 @@ const fn parse_compression(ctrl_low: u8) -> Option<Compression> {
     }
 }
 
-// CRC-16/GENIBUS
+// CRC-16/GENIBUS is CRC-16/CCITT-FALSE with the result inverted
 fn crc16_genibus(data: &[u8]) -> u16 {
-    let mut crc: u16 = 0xFFFF;
-    for &byte in data {
-        crc ^= u16::from(byte) << 8;
-        for _ in 0..8 {
-            crc = if crc & 0x8000 != 0 {
-                (crc << 1) ^ 0x1021
-            } else {
-                crc << 1
-            };
-        }
-    }
-    crc ^ 0xFFFF
+    !crc16_ccitt(data)
 }
 
 pub fn parse_program_store_header(data: &[u8]) -> Result<ProgramStoreHeader, StructureError> {
@@ -286,6 +275,8 @@ pub fn parse_program_store_header(data: &[u8]) -> Result<ProgramStoreHeader, Str
 ///
 /// match program_store_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -1,3 +1,4 @@
+use crate::extractors;
 use crate::signatures::{CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
 use crate::structures::StructureError;
 use zerocopy::{BE, FromBytes, Immutable, KnownLayout, Unaligned};
@@ -17,7 +18,7 @@ pub fn qcow_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, S
         ..Default::default()
     };
 
-    if let Ok(qcow_header) = parse_qcow_header(file_data) {
+    if let Ok(qcow_header) = parse_qcow_header(file_data.get(offset..).unwrap_or_default()) {
         result.description = format!(
             "QEMU QCOW Image, version: {}, storage media size: {:#x} bytes, cluster block size: {:#x} bytes, encryption method: {}",
             qcow_header.version,
@@ -25,18 +26,92 @@ pub fn qcow_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, S
             1 << qcow_header.cluster_block_bits,
             qcow_header.encryption_method
         );
+
+        // Images with a backing file only store the clusters that differ from the backing file; qemu-img would open
+        // the backing file by whatever name (or host path) the image specifies, so don't extract these
+        if qcow_header.has_backing_file() {
+            result.extraction_declined = true;
+
+            let backing_file_start = offset + qcow_header.backing_file_offset;
+            let backing_file_end = backing_file_start + qcow_header.backing_file_size;
+
+            let backing_file = file_data
+                .get(backing_file_start..backing_file_end)
+                .ok_or(SignatureError)?;
+
+            result.description = format!(
+                "{}, backing file: {}",
+                result.description,
+                String::from_utf8_lossy(backing_file)
+            );
+        }
+
         return Ok(result);
     };
 
     Err(SignatureError)
 }
 
+/// Describes how to run the qemu-img utility to convert QCOW images to raw disk images
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::qcow::qcow_extractor;
+///
+/// match qcow_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn qcow_extractor() -> extractors::Extractor {
+    extractors::Extractor {
+        utility: extractors::ExtractorType::External("qemu-img".to_string()),
+        extension: "qcow".to_string(),
+        arguments: vec![
+            "convert".to_string(),
+            "-O".to_string(), // Output format
+            "raw".to_string(),
+            extractors::SOURCE_FILE_PLACEHOLDER.to_string(),
+            "disk.img".to_string(),
+        ],
+        exit_codes: vec![0],
+        ..Default::default()
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct QcowHeader {
     pub version: u8,
+    /// Size of the virtual disk, in bytes
     pub storage_media_size: usize,
     pub cluster_block_bits: u8,
     pub encryption_method: String,
+    /// Offset of the backing file name, relative to the start of the image; 0 if there is no backing file
+    pub backing_file_offset: usize,
+    /// Length of the backing file name, in bytes
+    pub backing_file_size: usize,
+    /// Offset of the level 1 table, relative to the start of the image
+    pub level1_table_offset: usize,
+}
+
+impl QcowHeader {
+    /// Returns true if the image is an overlay on top of a backing file
+    pub fn has_backing_file(&self) -> bool {
+        self.backing_file_offset != 0
+    }
 }
 
 #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
@@ -94,7 +169,7 @@ struct QcowHeaderV3 {
     compatible_feature_flags: zerocopy::U64<BE>,
     autoclear_feature_flags: zerocopy::U64<BE>,
     refcount_order: zerocopy::U32<BE>,
-    file_hdr_size: zerocopy::U32<BE>, // 104 or 112
+    file_hdr_size: zerocopy::U32<BE>, // At least 104
 }
 
 pub fn parse_qcow_header(qcow_data: &[u8]) -> Result<QcowHeader, StructureError> {
@@ -132,11 +207,20 @@ fn parse_qcow_header_v1(qcow_data: &[u8]) -> Result<QcowHeader, StructureError>
         return Err(StructureError);
     }
 
+    validate_backing_file(
+        qcow_header.backing_filename_offset.get(),
+        qcow_header.backing_filename_size.get(),
+        qcow_header.cluster_block_bits as u32,
+    )?;
+
     Ok(QcowHeader {
         version: 1,
         storage_media_size: qcow_header.storage_media_size.get() as usize,
         cluster_block_bits: qcow_header.cluster_block_bits,
         encryption_method,
+        backing_file_offset: qcow_header.backing_filename_offset.get() as usize,
+        backing_file_size: qcow_header.backing_filename_size.get() as usize,
+        level1_table_offset: qcow_header.level1_table_offset.get() as usize,
     })
 }
 
@@ -159,11 +243,20 @@ fn parse_qcow_header_v2(qcow_data: &[u8]) -> Result<QcowHeader, StructureError>
         return Err(StructureError);
     }
 
+    validate_backing_file(
+        qcow_header.backing_filename_offset.get(),
+        qcow_header.backing_filename_size.get(),
+        cluster_block_bits,
+    )?;
+
     Ok(QcowHeader {
         version: 2,
         storage_media_size: qcow_header.storage_media_size.get() as usize,
         cluster_block_bits: cluster_block_bits as u8,
         encryption_method,
+        backing_file_offset: qcow_header.backing_filename_offset.get() as usize,
+        backing_file_size: qcow_header.backing_filename_size.get() as usize,
+        level1_table_offset: qcow_header.level1_table_offset.get() as usize,
     })
 }
 
@@ -186,10 +279,96 @@ fn parse_qcow_header_v3(qcow_data: &[u8]) -> Result<QcowHeader, StructureError>
         return Err(StructureError);
     }
 
+    // v3 headers are at least 104 bytes, with any additional fields (e.g., the compression type) padded to a multiple
+    // of 8 bytes, and must fit in the first cluster; refcounts are 1 to 64 bits wide
+    let file_hdr_size = qcow_header.file_hdr_size.get();
+
+    if file_hdr_size < 104
+        || !file_hdr_size.is_multiple_of(8)
+        || file_hdr_size > 1 << cluster_block_bits
+        || qcow_header.refcount_order.get() > 6
+    {
+        return Err(StructureError);
+    }
+
+    validate_backing_file(
+        qcow_header.backing_filename_offset.get(),
+        qcow_header.backing_filename_size.get(),
+        cluster_block_bits,
+    )?;
+
     Ok(QcowHeader {
         version: 3,
         storage_media_size: qcow_header.storage_media_size.get() as usize,
         cluster_block_bits: cluster_block_bits as u8,
         encryption_method,
+        backing_file_offset: qcow_header.backing_filename_offset.get() as usize,
+        backing_file_size: qcow_header.backing_filename_size.get() as usize,
+        level1_table_offset: qcow_header.level1_table_offset.get() as usize,
     })
 }
+
+/// The backing file name is stored in the first cluster, and is no longer than 1023 bytes
+fn validate_backing_file(
+    backing_file_offset: u64,
+    backing_file_size: u32,
+    cluster_block_bits: u32,
+) -> Result<(), StructureError> {
+    const MAX_BACKING_FILE_SIZE: u32 = 1023;
+
+    if backing_file_offset == 0 {
+        return Ok(());
+    }
+
+    if backing_file_size == 0
+        || backing_file_size > MAX_BACKING_FILE_SIZE
+        || backing_file_offset.saturating_add(backing_file_size as u64) > (1 << cluster_block_bits)
+    {
+        return Err(StructureError);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// v3 header of a 1KiB disk with 64KiB clusters, backed by "base.qcow2" (see tests/inputs/gen_qcow_backing_file.sh)
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/qcow_backing_file.bin");
+
+    #[test]
+    fn qcow3_backing_file() {
+        let header = parse_qcow_header(VALID).unwrap();
+        assert_eq!(header.version, 3);
+        assert_eq!(header.storage_media_size, 0x400);
+        assert_eq!(header.cluster_block_bits, 16);
+        assert_eq!(header.level1_table_offset, 0x30000);
+        assert!(header.has_backing_file());
+
+        let mut file_data = vec![0; 0x100];
+        file_data.extend_from_slice(VALID);
+
+        let signature = qcow_parser(&file_data, 0x100).unwrap();
+        assert_eq!(signature.offset, 0x100);
+        assert!(signature.description.contains("backing file: base.qcow2"));
+        assert!(signature.extraction_declined);
+    }
+
+    #[test]
+    fn qcow3_invalid_header_size() {
+        for header_size in [100_u32, 116] {
+            let mut header = VALID.to_vec();
+            header[100..104].copy_from_slice(&header_size.to_be_bytes());
+            assert!(parse_qcow_header(&header).is_err());
+        }
+    }
+
+    #[test]
+    fn qcow3_extended_header_size() {
+        // Newer versions of QEMU add header fields, padding the header to a multiple of 8 bytes
+        let mut header = VALID.to_vec();
+        header[100..104].copy_from_slice(&120_u32.to_be_bytes());
+        assert!(parse_qcow_header(&header).is_ok());
+    }
+}
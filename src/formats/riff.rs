@@ -88,6 +88,8 @@ pub fn parse_riff_header(riff_data: &[u8]) -> Result<RIFFHeader, StructureError>
 ///
 /// match riff_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -120,7 +122,7 @@ pub fn extract_riff_image(
 
     let mut result = ExtractionResult::default();
 
-    if let Ok(riff_header) = parse_riff_header(&file_data[offset..]) {
+    if let Ok(riff_header) = parse_riff_header(file_data.get(offset..).unwrap_or_default()) {
         result.size = Some(riff_header.size);
         result.success = true;
 
@@ -113,6 +113,7 @@ pub struct RomFSFileHeader {
     pub data_offset: usize,
     pub file_type: u32,
     pub executable: bool,
+    pub hardlink: bool,
     pub symlink: bool,
     pub directory: bool,
     pub regular: bool,
@@ -141,6 +142,7 @@ pub fn parse_romfs_file_entry(romfs_data: &[u8]) -> Result<RomFSFileHeader, Stru
     const NEXT_OFFSET_MASK: u32 = 0b11111111_11111111_11111111_11110000;
 
     // We only support extraction of these file types
+    const ROMFS_HARDLINK: u32 = 0;
     const ROMFS_DIRECTORY: u32 = 1;
     const ROMFS_REGULAR_FILE: u32 = 2;
     const ROMFS_SYMLINK: u32 = 3;
@@ -181,6 +183,7 @@ pub fn parse_romfs_file_entry(romfs_data: &[u8]) -> Result<RomFSFileHeader, Stru
             // Set the type of entry that this is
             file_header.fifo = file_header.file_type == ROMFS_FIFO;
             file_header.socket = file_header.file_type == ROMFS_SOCKET;
+            file_header.hardlink = file_header.file_type == ROMFS_HARDLINK;
             file_header.symlink = file_header.file_type == ROMFS_SYMLINK;
             file_header.regular = file_header.file_type == ROMFS_REGULAR_FILE;
             file_header.directory = file_header.file_type == ROMFS_DIRECTORY;
@@ -235,6 +238,7 @@ struct RomFSEntry {
     name: String,
     offset: usize,
     executable: bool,
+    hardlink: bool,
     directory: bool,
     regular: bool,
     block_device: bool,
@@ -258,6 +262,8 @@ struct RomFSEntry {
 ///
 /// match romfs_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -286,7 +292,7 @@ pub fn extract_romfs(
     let mut result = ExtractionResult::default();
 
     // Parse the RomFS header
-    if let Ok(romfs_header) = parse_romfs_header(&file_data[offset..]) {
+    if let Ok(romfs_header) = parse_romfs_header(file_data.get(offset..).unwrap_or_default()) {
         // Calculate start and end offsets of RomFS image
         let romfs_data_start: usize = offset;
         let romfs_data_end: usize = romfs_data_start + romfs_header.image_size;
@@ -371,6 +377,7 @@ fn process_romfs_entries(
                 info: file_header.info,
                 name: file_header.name.clone(),
                 symlink: file_header.symlink,
+                hardlink: file_header.hardlink,
                 regular: file_header.regular,
                 directory: file_header.directory,
                 executable: file_header.executable,
@@ -390,8 +397,32 @@ fn process_romfs_entries(
 
             // Don't do anything special for '.' or '..' directory entries
             if !ignore_file_names.contains(&file_entry.name) {
+                // Hard links point to another file header; extract a copy of that file's data
+                if file_entry.hardlink {
+                    match romfs_data
+                        .get(file_entry.info..)
+                        .map(parse_romfs_file_entry)
+                    {
+                        Some(Ok(target_header)) if target_header.regular => {
+                            file_entry.regular = true;
+                            file_entry.executable = target_header.executable;
+                            file_entry.size = target_header.size;
+                            file_entry.offset = file_entry.info + target_header.data_offset;
+
+                            if (file_entry.offset + file_entry.size) > romfs_data.len() {
+                                warn!("Invalid offset/size specified for file {}", file_entry.name);
+                                return Err(ExtractionError);
+                            }
+                        }
+                        _ => {
+                            warn!(
+                                "Hard link {} does not point to a regular file, skipping",
+                                file_entry.name
+                            );
+                        }
+                    }
                 // Symlinks need their target paths
-                if file_entry.symlink {
+                } else if file_entry.symlink {
                     if let Some(symlink_bytes) =
                         romfs_data.get(file_entry.offset..file_entry.offset + file_entry.size)
                     {
@@ -507,3 +538,44 @@ fn extract_romfs_entries(
     // Return the number of files extracted
     file_count
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A RomFS image holding two files and a hard link to one of them (see tests/inputs/gen_romfs_hardlink.sh)
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/romfs_hardlink.bin");
+
+    #[test]
+    fn hardlink_extraction() {
+        let signature = romfs_parser(VALID, 0).unwrap();
+        assert_eq!(signature.size, VALID.len());
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = extract_romfs(VALID, 0, Some(output_directory.path()));
+        assert!(result.success);
+
+        // Hard links are extracted as copies of their target files
+        let romfs_root = output_directory.path().join("test");
+        assert_eq!(
+            std::fs::read(romfs_root.join("hello.txt")).unwrap(),
+            b"Hello, world!\n"
+        );
+        assert_eq!(
+            std::fs::read(romfs_root.join("goodbye.txt")).unwrap(),
+            b"Goodbye!\n"
+        );
+        assert_eq!(
+            std::fs::read(romfs_root.join("hello_link.txt")).unwrap(),
+            b"Hello, world!\n"
+        );
+        assert_eq!(std::fs::read_dir(&romfs_root).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn header_checksum() {
+        let mut corrupt_image = VALID.to_vec();
+        corrupt_image[12] ^= 1;
+        assert!(romfs_parser(&corrupt_image, 0).is_err());
+    }
+}
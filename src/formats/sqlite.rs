@@ -0,0 +1,272 @@
+use crate::extractors::Extractor;
+use crate::extractors::carve::carve_extractor;
+use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
+use crate::structures::StructureError;
+use zerocopy::{BE, FromBytes, Immutable, KnownLayout, Unaligned};
+
+/// Human readable description
+pub const DESCRIPTION: &str = "SQLite 3 database";
+
+/// SQLite database files always start with these bytes
+pub fn sqlite_magic() -> Vec<Vec<u8>> {
+    vec![b"SQLite format 3\x00".to_vec()]
+}
+
+/// Validates the SQLite database header
+pub fn sqlite_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
+    let sqlite_header = parse_sqlite_header(file_data.get(offset..).unwrap_or_default())
+        .map_err(|_| SignatureError)?;
+
+    let mut result = SignatureResult {
+        offset,
+        description: format!(
+            "{}, page size: {} bytes, text encoding: {}, user version: {}, SQLite version: {}",
+            DESCRIPTION,
+            sqlite_header.page_size,
+            sqlite_header.text_encoding,
+            sqlite_header.user_version,
+            sqlite_header.sqlite_version
+        ),
+        confidence: CONFIDENCE_HIGH,
+        ..Default::default()
+    };
+
+    // Databases written by old versions of SQLite don't keep the database size in the header up to date
+    let Some(page_count) = sqlite_header.page_count else {
+        result.description = format!("{}, database size unknown", result.description);
+        result.extraction_declined = true;
+        return Ok(result);
+    };
+
+    let database_size = page_count * sqlite_header.page_size;
+    let available_data = file_data.len() - offset;
+
+    result.description = format!(
+        "{}, page count: {}, total size: {} bytes",
+        result.description, page_count, database_size
+    );
+
+    if database_size > available_data {
+        result.extraction_declined = true;
+        result.set_truncated(database_size, available_data);
+        return Ok(result);
+    }
+
+    // If this file is just a database, no need to carve it out to yet another file on disk
+    if offset == 0 && database_size == file_data.len() {
+        result.extraction_declined = true;
+    }
+
+    result.size = database_size;
+
+    Ok(result)
+}
+
+/// Stores info about a SQLite database header
+#[derive(Debug, Default, Clone)]
+pub struct SQLiteHeader {
+    pub page_size: usize,
+    /// Database size in pages, if the size recorded in the header is valid
+    pub page_count: Option<usize>,
+    pub text_encoding: String,
+    pub user_version: u32,
+    /// Version of the SQLite library that most recently modified the database, e.g. "3.45.1"
+    pub sqlite_version: String,
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct SQLiteHeaderBytes {
+    magic: [u8; 16],
+    page_size: zerocopy::U16<BE>,
+    write_version: u8,
+    read_version: u8,
+    reserved_space: u8,
+    max_payload_fraction: u8,
+    min_payload_fraction: u8,
+    leaf_payload_fraction: u8,
+    change_counter: zerocopy::U32<BE>,
+    page_count: zerocopy::U32<BE>,
+    first_freelist_page: zerocopy::U32<BE>,
+    freelist_page_count: zerocopy::U32<BE>,
+    schema_cookie: zerocopy::U32<BE>,
+    schema_format: zerocopy::U32<BE>,
+    default_cache_size: zerocopy::U32<BE>,
+    largest_root_page: zerocopy::U32<BE>,
+    text_encoding: zerocopy::U32<BE>,
+    user_version: zerocopy::U32<BE>,
+    incremental_vacuum: zerocopy::U32<BE>,
+    application_id: zerocopy::U32<BE>,
+    reserved: [u8; 20],
+    version_valid_for: zerocopy::U32<BE>,
+    sqlite_version: zerocopy::U32<BE>,
+}
+
+/// Parses a SQLite database header
+///
+/// ```
+/// use binwalk_ng::formats::sqlite::parse_sqlite_header;
+///
+/// let mut header = b"SQLite format 3\x00\x00\x01\x01\x01\x00\x40\x20\x20".to_vec();
+/// header.resize(100, 0);
+///
+/// // A page size of 1 means 65536
+/// let sqlite_header = parse_sqlite_header(&header).unwrap();
+/// assert_eq!(sqlite_header.page_size, 65536);
+/// assert_eq!(sqlite_header.page_count, None);
+///
+/// // Page sizes must be a power of 2
+/// header[16..18].copy_from_slice(b"\x03\x00");
+/// assert!(parse_sqlite_header(&header).is_err());
+/// ```
+pub fn parse_sqlite_header(sqlite_data: &[u8]) -> Result<SQLiteHeader, StructureError> {
+    const MAGIC: &[u8; 16] = b"SQLite format 3\x00";
+    const MIN_PAGE_SIZE: usize = 512;
+    const MAX_PAGE_SIZE: usize = 32768;
+    // A page size value of 1 means a page size of 65536, which does not fit in the 16-bit field
+    const LARGE_PAGE_SIZE_VALUE: u16 = 1;
+    const LARGE_PAGE_SIZE: usize = 65536;
+    // Legacy and WAL journal modes
+    const VALID_FILE_FORMAT_VERSIONS: [u8; 2] = [1, 2];
+    // The payload fractions are fixed
+    const MAX_PAYLOAD_FRACTION: u8 = 64;
+    const MIN_PAYLOAD_FRACTION: u8 = 32;
+    const LEAF_PAYLOAD_FRACTION: u8 = 32;
+    const MAX_SCHEMA_FORMAT: u32 = 4;
+    // Page sizes minus reserved space must be at least this large
+    const MIN_USABLE_PAGE_SIZE: usize = 480;
+
+    let (sqlite_header, _) =
+        SQLiteHeaderBytes::ref_from_prefix(sqlite_data).map_err(|_| StructureError)?;
+
+    let page_size = match sqlite_header.page_size.get() {
+        LARGE_PAGE_SIZE_VALUE => LARGE_PAGE_SIZE,
+        page_size => page_size as usize,
+    };
+
+    if sqlite_header.magic != *MAGIC
+        || !(page_size == LARGE_PAGE_SIZE
+            || (page_size.is_power_of_two()
+                && (MIN_PAGE_SIZE..=MAX_PAGE_SIZE).contains(&page_size)))
+        || !VALID_FILE_FORMAT_VERSIONS.contains(&sqlite_header.write_version)
+        || !VALID_FILE_FORMAT_VERSIONS.contains(&sqlite_header.read_version)
+        || sqlite_header.max_payload_fraction != MAX_PAYLOAD_FRACTION
+        || sqlite_header.min_payload_fraction != MIN_PAYLOAD_FRACTION
+        || sqlite_header.leaf_payload_fraction != LEAF_PAYLOAD_FRACTION
+        || sqlite_header.schema_format.get() > MAX_SCHEMA_FORMAT
+        || page_size - (sqlite_header.reserved_space as usize) < MIN_USABLE_PAGE_SIZE
+    {
+        return Err(StructureError);
+    }
+
+    // A text encoding of 0 means the database is empty, and has no encoding yet
+    let text_encoding = match sqlite_header.text_encoding.get() {
+        0 => "none",
+        1 => "UTF-8",
+        2 => "UTF-16LE",
+        3 => "UTF-16BE",
+        _ => return Err(StructureError),
+    };
+
+    // The database size in the header is only valid if it is non-zero, and the change counter matches version-valid-for
+    let page_count = sqlite_header.page_count.get() as usize;
+    let page_count_valid =
+        page_count != 0 && sqlite_header.change_counter == sqlite_header.version_valid_for.get();
+
+    // SQLite version numbers are encoded as (major * 1000000) + (minor * 1000) + patch
+    let sqlite_version = sqlite_header.sqlite_version.get();
+
+    Ok(SQLiteHeader {
+        page_size,
+        page_count: page_count_valid.then_some(page_count),
+        text_encoding: text_encoding.to_string(),
+        user_version: sqlite_header.user_version.get(),
+        sqlite_version: format!(
+            "{}.{}.{}",
+            sqlite_version / 1000000,
+            (sqlite_version / 1000) % 1000,
+            sqlite_version % 1000
+        ),
+    })
+}
+
+/// Defines the extractor for carving out SQLite databases
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::sqlite::sqlite_extractor;
+///
+/// match sqlite_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(file_name) => println!("Carve extractor OK: {}", file_name),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn sqlite_extractor() -> Extractor {
+    Extractor {
+        do_not_recurse: true,
+        ..carve_extractor("database.sqlite")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two-page database created by SQLite 3.40.1 with a user version of 7, followed by trailing data
+    /// (see tests/inputs/gen_sqlite.sh)
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/sqlite.bin");
+    const DATABASE_SIZE: usize = 2 * 4096;
+
+    #[test]
+    fn real_header() {
+        let header = parse_sqlite_header(VALID).unwrap();
+        assert_eq!(header.page_size, 4096);
+        assert_eq!(header.page_count, Some(2));
+        assert_eq!(header.text_encoding, "UTF-8");
+        assert_eq!(header.user_version, 7);
+        assert_eq!(header.sqlite_version, "3.40.1");
+    }
+
+    #[test]
+    fn embedded_database() {
+        let mut file_data = vec![0xFF; 0x100];
+        file_data.extend_from_slice(VALID);
+
+        let signature = sqlite_parser(&file_data, 0x100).unwrap();
+        assert_eq!(signature.offset, 0x100);
+        assert_eq!(signature.size, DATABASE_SIZE);
+        assert!(!signature.extraction_declined);
+    }
+
+    #[test]
+    fn stale_page_count() {
+        // If the change counter doesn't match version-valid-for, the in-header database size can't be trusted
+        let mut database = VALID[..DATABASE_SIZE].to_vec();
+        database[27] = 4;
+
+        let signature = sqlite_parser(&database, 0).unwrap();
+        assert_eq!(signature.size, 0);
+        assert!(signature.extraction_declined);
+        assert!(signature.description.contains("database size unknown"));
+    }
+
+    #[test]
+    fn truncated_database() {
+        let signature = sqlite_parser(&VALID[..4096], 0).unwrap();
+        assert_eq!(signature.size, 4096);
+        assert_eq!(signature.declared_size, Some(DATABASE_SIZE));
+    }
+}
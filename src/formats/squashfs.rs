@@ -1,13 +1,24 @@
 use crate::common::epoch_to_string;
 use crate::extractors;
+use crate::extractors::Chroot;
+use crate::extractors::squashfs_reader::{SquashFSEntryType, SquashFSReader};
 use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
 use crate::structures::{Endianness, StructureError, dyn_endian};
+use log::{debug, error};
+use std::collections::HashMap;
+use std::io::{BufWriter, Write};
 use std::mem::offset_of;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
 use zerocopy::{FromBytes, Immutable, KnownLayout, Unaligned};
 
 /// Human readable description
 pub const DESCRIPTION: &str = "SquashFS file system";
 
+/// SquashFS major version 4
+const SQUASHFS_V4: u16 = 4;
+
 /// All of the known magic bytes that could indicate the beginning of a SquashFS image
 pub fn squashfs_magic() -> Vec<Vec<u8>> {
     vec![
@@ -23,8 +34,6 @@ pub fn squashfs_magic() -> Vec<Vec<u8>> {
 
 /// Responsible for parsing and validating a suspected SquashFS image header
 pub fn squashfs_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
-    const SQUASHFS_V4: u16 = 4;
-
     let mut result = SignatureResult {
         size: 0,
         offset,
@@ -74,14 +83,11 @@ pub fn squashfs_parser(file_data: &[u8], offset: usize) -> Result<SignatureResul
                 // Make sure the compression type is supported
                 if let Some(compression_type) = parse_compression_type(squashfs_header.compression)
                 {
-                    // Select the appropriate extractor to use
-                    if squashfs_header.endianness == Endianness::Little {
-                        result.preferred_extractor = Some(squashfs_le_extractor());
-                    } else if squashfs_header.major_version == SQUASHFS_V4 {
-                        result.preferred_extractor = Some(squashfs_v4_be_extractor());
-                    } else {
-                        result.preferred_extractor = Some(squashfs_be_extractor());
-                    }
+                    // Picking the extractor probes the installed utilities, so it is deferred until extraction
+                    result.preferred_extractor = Some(extractors::Extractor {
+                        selector: Some(select_image_extractor),
+                        ..candidate_extractors(&squashfs_header).swap_remove(0)
+                    });
 
                     // Format the modified time into something human readable
                     let create_date = epoch_to_string(squashfs_header.timestamp);
@@ -306,6 +312,8 @@ pub fn parse_squashfs_uid_entry(
 ///
 /// match squashfs_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -339,6 +347,8 @@ pub fn squashfs_extractor() -> extractors::Extractor {
 ///
 /// match squashfs_le_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -375,6 +385,8 @@ pub fn squashfs_le_extractor() -> extractors::Extractor {
 ///
 /// match squashfs_be_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -411,6 +423,8 @@ pub fn squashfs_be_extractor() -> extractors::Extractor {
 ///
 /// match squashfs_v4_be_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -434,6 +448,262 @@ pub fn squashfs_v4_be_extractor() -> extractors::Extractor {
     }
 }
 
+/// Describes how to run the squashfs-tools unsquashfs utility to extract little endian SquashFSv4 images
+///
+/// ```
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::squashfs::unsquashfs_extractor;
+///
+/// assert!(matches!(unsquashfs_extractor().utility, ExtractorType::External(cmd) if cmd == "unsquashfs"));
+/// ```
+pub fn unsquashfs_extractor() -> extractors::Extractor {
+    extractors::Extractor {
+        utility: extractors::ExtractorType::External("unsquashfs".to_string()),
+        extension: "sqsh".to_string(),
+        arguments: vec![extractors::SOURCE_FILE_PLACEHOLDER.to_string()],
+        // Exit code may be 0 or 2; 2 indicates running as not root, but otherwise extraction is ok
+        exit_codes: vec![0, 2],
+        ..Default::default()
+    }
+}
+
+/// Describes the built-in SquashFS extractor, used for images that no installed extraction utility can handle
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::squashfs::squashfs_internal_extractor;
+///
+/// match squashfs_internal_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn squashfs_internal_extractor() -> extractors::Extractor {
+    extractors::Extractor {
+        utility: extractors::ExtractorType::Internal(extract_squashfs),
+        ..Default::default()
+    }
+}
+
+/// Internal extractor for little endian SquashFSv4 images compressed with gzip or xz; see
+/// extractors::squashfs_reader. Directories and regular files are extracted, symlinks and special files are skipped.
+pub fn extract_squashfs(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> extractors::ExtractionResult {
+    // Limits the total size of the extracted files, since sparse files take up next to no space in the image
+    const MAX_EXTRACTED_SIZE: usize = 4 * 1024 * 1024 * 1024;
+
+    let mut result = extractors::ExtractionResult::default();
+
+    let Some(squashfs_data) = file_data.get(offset..) else {
+        return result;
+    };
+
+    let Ok(squashfs_header) = parse_squashfs_header(squashfs_data) else {
+        return result;
+    };
+
+    let Ok(reader) = SquashFSReader::new(squashfs_data) else {
+        return result;
+    };
+
+    let Ok(entries) = reader.list() else {
+        return result;
+    };
+
+    let chroot = output_directory.map(Chroot::new);
+    let mut extracted_size: usize = 0;
+
+    for entry in &entries {
+        match entry.entry_type {
+            SquashFSEntryType::Directory => {
+                if let Some(chroot) = &chroot
+                    && !chroot.create_directory(&entry.path)
+                {
+                    return result;
+                }
+            }
+            SquashFSEntryType::File => {
+                extracted_size = extracted_size.saturating_add(entry.size);
+
+                if extracted_size > MAX_EXTRACTED_SIZE {
+                    return result;
+                }
+
+                let extracted = match &chroot {
+                    None => reader.write_file(entry, &mut std::io::sink()).is_ok(),
+                    Some(chroot) => match chroot.create_file_writer(&entry.path) {
+                        None => false,
+                        Some(file) => {
+                            let mut writer = BufWriter::new(file);
+                            reader.write_file(entry, &mut writer).is_ok() && writer.flush().is_ok()
+                        }
+                    },
+                };
+
+                if !extracted {
+                    return result;
+                }
+            }
+            // The reader does not resolve symlink targets or device numbers
+            SquashFSEntryType::Symlink | SquashFSEntryType::Other => (),
+        }
+    }
+
+    result.success = true;
+    result.size = Some(squashfs_header.image_size);
+
+    result
+}
+
+/// Selects the extractor for the SquashFS image at the signature's offset when it is extracted, see select_extractor
+fn select_image_extractor(
+    file_data: &[u8],
+    signature: &SignatureResult,
+) -> Option<extractors::Extractor> {
+    let header = parse_squashfs_header(file_data.get(signature.offset..)?).ok()?;
+    let compression_type = parse_compression_type(header.compression)?;
+
+    Some(select_extractor(&header, compression_type))
+}
+
+/// Returns the external extractors that can handle the image's endianness and version, in order of preference
+fn candidate_extractors(header: &SquashFSHeader) -> Vec<extractors::Extractor> {
+    let mut candidates = vec![];
+
+    if header.endianness == Endianness::Little {
+        candidates.push(squashfs_le_extractor());
+        // Stock squashfs-tools only understands standard little endian v4 images
+        if header.major_version == SQUASHFS_V4 {
+            candidates.push(unsquashfs_extractor());
+        }
+    } else if header.major_version == SQUASHFS_V4 {
+        candidates.push(squashfs_v4_be_extractor());
+    } else {
+        candidates.push(squashfs_be_extractor());
+    }
+
+    candidates
+}
+
+/// Selects the first installed extractor that can handle the image's endianness, version and compression type.
+///
+/// Builds of sasquatch and unsquashfs commonly omit one or more decompressors, which otherwise causes extraction to
+/// fail with little explanation; if no installed extractor supports the image's compression, the built-in extractor is
+/// used if it supports the image, otherwise an error naming the required build option is logged and the default
+/// extractor is returned.
+fn select_extractor(header: &SquashFSHeader, compression_type: &str) -> extractors::Extractor {
+    let mut candidates = candidate_extractors(header);
+
+    // Build option that enables support for this compression type in sasquatch / squashfs-tools
+    let build_option = match compression_type {
+        "gzip" => "GZIP_SUPPORT",
+        "lzma" => "LZMA_XZ_SUPPORT",
+        "lzo" => "LZO_SUPPORT",
+        "xz" => "XZ_SUPPORT",
+        "lz4" => "LZ4_SUPPORT",
+        "zstd" => "ZSTD_SUPPORT",
+        _ => return candidates.swap_remove(0),
+    };
+
+    let mut lacking_support: Vec<String> = vec![];
+
+    for candidate in &candidates {
+        if let extractors::ExtractorType::External(command) = &candidate.utility {
+            match supported_compressors(command) {
+                // Not installed, or its capabilities are unknown
+                None => continue,
+                Some(compressors) if compressors.iter().any(|c| c == compression_type) => {
+                    return candidate.clone();
+                }
+                Some(_) => lacking_support.push(command.clone()),
+            }
+        }
+    }
+
+    // The built-in reader only supports little endian v4 images compressed with gzip or xz
+    if header.endianness == Endianness::Little
+        && header.major_version == SQUASHFS_V4
+        && ["gzip", "xz"].contains(&compression_type)
+    {
+        debug!("No installed SquashFS extractor supports this image, using the built-in extractor");
+        return squashfs_internal_extractor();
+    }
+
+    if !lacking_support.is_empty() {
+        error!(
+            "SquashFS image uses {compression_type} compression, which the installed {} does not support; rebuild it with {build_option}=1 to extract this image",
+            lacking_support.join(" and ")
+        );
+    }
+
+    candidates.swap_remove(0)
+}
+
+/// Returns the decompressors supported by a SquashFS extraction utility, or None if the utility is not installed or
+/// does not report them. Results are cached, so each utility is only run once.
+fn supported_compressors(command: &str) -> Option<Vec<String>> {
+    static SUPPORTED_COMPRESSORS: OnceLock<Mutex<HashMap<String, Option<Vec<String>>>>> =
+        OnceLock::new();
+
+    let mut supported = SUPPORTED_COMPRESSORS
+        .get_or_init(Default::default)
+        .lock()
+        .ok()?;
+
+    supported
+        .entry(command.to_string())
+        .or_insert_with(|| {
+            // Both utilities list their decompressors in their help text, which may be printed to stdout or stderr
+            let output = Command::new(command).arg("-help").output().ok()?;
+            let help_text = [output.stdout, output.stderr].concat();
+            parse_decompressor_list(&String::from_utf8_lossy(&help_text))
+        })
+        .clone()
+}
+
+/// Parses the list of decompressors from the help text of sasquatch or unsquashfs.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::formats::squashfs::parse_decompressor_list;
+///
+/// let help_text = "Usage: unsquashfs [options] filesystem\n\nDecompressors available:\n\tgzip\n\txz\n\tzstd\n";
+///
+/// assert_eq!(parse_decompressor_list(help_text), Some(vec!["gzip".to_string(), "xz".to_string(), "zstd".to_string()]));
+/// assert_eq!(parse_decompressor_list("Usage: unsquashfs"), None);
+/// ```
+pub fn parse_decompressor_list(help_text: &str) -> Option<Vec<String>> {
+    let (_, decompressors) = help_text.split_once("Decompressors available:")?;
+
+    Some(
+        decompressors
+            .lines()
+            // Skip the remainder of the "Decompressors available:" line
+            .skip(1)
+            .take_while(|line| line.starts_with(char::is_whitespace) && !line.trim().is_empty())
+            .filter_map(|line| line.split_whitespace().next())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
 const fn parse_compression_type(compression_type: u16) -> Option<&'static str> {
     Some(match compression_type {
         0 => "unknown",
@@ -446,3 +716,55 @@ const fn parse_compression_type(compression_type: u16) -> Option<&'static str> {
         _ => return None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A little endian v4.0 SquashFS image, padded out to 4 KiB
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/squashfs.bin");
+
+    /// Sizes of each field in a v4.0 superblock
+    const V4_FIELD_SIZES: [usize; 19] = [4, 4, 4, 4, 4, 2, 2, 2, 2, 2, 2, 8, 8, 8, 8, 8, 8, 8, 8];
+
+    /// Returns a copy of the fixture with each superblock field converted to big endian
+    fn big_endian_fixture() -> Vec<u8> {
+        let mut image = VALID.to_vec();
+        let mut field_offset = 0;
+
+        for field_size in V4_FIELD_SIZES {
+            image[field_offset..field_offset + field_size].reverse();
+            field_offset += field_size;
+        }
+
+        image
+    }
+
+    #[test]
+    fn v4_superblock() {
+        let header = parse_squashfs_header(VALID).unwrap();
+        assert_eq!(header.endianness, Endianness::Little);
+        assert_eq!(header.major_version, 4);
+        assert_eq!(header.minor_version, 0);
+        assert_eq!(header.compression, 1);
+        assert_eq!(header.block_size, 0x20000);
+        assert_eq!(header.image_size, 315);
+        assert_eq!(header.inode_count, 2);
+        assert_eq!(header.uid_table_start, 307);
+
+        // The same superblock in big endian byte order
+        let superblock = big_endian_fixture();
+        assert_eq!(&superblock[..4], b"sqsh");
+
+        let header = parse_squashfs_header(&superblock).unwrap();
+        assert_eq!(header.endianness, Endianness::Big);
+        assert_eq!(header.major_version, 4);
+        assert_eq!(header.block_size, 0x20000);
+        assert_eq!(header.image_size, 315);
+
+        // The block size and block log must agree
+        let mut superblock = VALID.to_vec();
+        superblock[22..24].copy_from_slice(&16u16.to_le_bytes());
+        assert!(parse_squashfs_header(&superblock).is_err());
+    }
+}
@@ -70,6 +70,8 @@ pub fn srec_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, S
 ///
 /// match srec_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => panic!("Unexpected external extractor '{}'", cmd),
 /// }
@@ -81,7 +83,8 @@ pub fn srec_extractor() -> extractors::Extractor {
     }
 }
 
-/// Internal extractor for Motorola S-records. Decodes the data records into a binary blob.
+/// Internal extractor for Motorola S-records. Decodes the data records into a binary blob, up to the termination
+/// record or the first record with an invalid checksum.
 pub fn extract_srec(
     file_data: &[u8],
     offset: usize,
@@ -103,15 +106,22 @@ pub fn extract_srec(
         }
     }
     let mut remaining = srec_data;
+    let mut decoded_size: usize = 0;
     while let Ok((record, rest)) = take_srec_record(remaining) {
-        if !record.hex_data.is_empty()
-            && record.ty.is_data()
-            && let Some(f) = &mut file
-        {
-            let res = record.write(f);
-            if res.is_err() {
-                return result;
+        // Stop at the first corrupt record, rather than writing garbage to the output file
+        if !record.checksum_valid() {
+            break;
+        }
+
+        if !record.hex_data.is_empty() && record.ty.is_data() {
+            if let Some(f) = &mut file {
+                let res = record.write(f);
+                if res.is_err() {
+                    return result;
+                }
             }
+
+            decoded_size = decoded_size.max(record.addr as usize + record.hex_data.len());
         }
         remaining = rest;
 
@@ -122,6 +132,7 @@ pub fn extract_srec(
     let consumed = srec_data.len() - remaining.len();
     result.size = Some(consumed);
     result.success = consumed > 0;
+    result.set_compression_ratio(consumed, decoded_size);
 
     result
 }
@@ -330,6 +341,37 @@ mod tests {
         assert_snapshot!(hex::encode(&decoded), @"000102030000000004050607");
     }
 
+    #[test]
+    fn decode_srec_mixed_address_sizes() {
+        // A 16-bit address S1 record and a 32-bit address S3 record, terminated by an S7 record
+        let srec = b"S1050000AABB95\n\
+                      S30700000004CCDD4B\n\
+                      S70500000000FA\n";
+        let dir = tempfile::tempdir().unwrap();
+        let res = extract_srec(srec, 0, Some(dir.path()));
+        assert!(res.success);
+        assert_eq!(res.size, Some(srec.len()));
+        assert_eq!(res.compression_ratio, Some(6.0 / srec.len() as f64));
+
+        let decoded = std::fs::read(dir.path().join("s-record.bin")).unwrap();
+        assert_snapshot!(hex::encode(&decoded), @"aabb0000ccdd");
+    }
+
+    #[test]
+    fn decode_srec_stops_at_invalid_checksum() {
+        // Correct checksum for the S3 record is 0x4B; 0x4C is wrong
+        let srec = b"S1050000AABB95\n\
+                      S30700000004CCDD4C\n\
+                      S70500000000FA\n";
+        let dir = tempfile::tempdir().unwrap();
+        let res = extract_srec(srec, 0, Some(dir.path()));
+        assert!(res.success);
+        assert_eq!(res.size, Some(15));
+
+        let decoded = std::fs::read(dir.path().join("s-record.bin")).unwrap();
+        assert_snapshot!(hex::encode(&decoded), @"aabb");
+    }
+
     #[test]
     fn decode_srec_nonzero_base_with_gap() {
         // Non-zero base address and a gap between records.
@@ -32,7 +32,9 @@ pub fn tarball_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult
     let mut valid_header_count: usize = 0;
 
     // Calculate the actual start of the tarball (header magic does not start at the beginning of a tar entry)
-    let tarball_start_offset = offset - TARBALL_MAGIC_OFFSET;
+    let Some(tarball_start_offset) = offset.checked_sub(TARBALL_MAGIC_OFFSET) else {
+        return Err(SignatureError);
+    };
 
     // Tarball magic bytes do not start at the beginning of the tarball file
     let mut next_header_start = tarball_start_offset;
@@ -75,6 +77,9 @@ pub fn tarball_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult
         }
     }
 
+    // Include the end-of-archive marker, if there is one
+    tarball_total_size += end_of_archive_size(file_data, next_header_start);
+
     // We expect that a tarball should be, at a minimum, one block in size
     if tarball_total_size >= TARBALL_BLOCK_SIZE {
         // Default confidence is medium, if more than just a few tarball headers were found and
@@ -97,6 +102,16 @@ pub fn tarball_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult
     Err(SignatureError)
 }
 
+/// Returns the size of the end-of-archive marker (two all-zero blocks) at the specified offset, or 0 if there is none
+fn end_of_archive_size(file_data: &[u8], offset: usize) -> usize {
+    const END_OF_ARCHIVE_SIZE: usize = TARBALL_BLOCK_SIZE * 2;
+
+    match file_data.get(offset..offset.saturating_add(END_OF_ARCHIVE_SIZE)) {
+        Some(end_blocks) if end_blocks.iter().all(|&b| b == 0) => END_OF_ARCHIVE_SIZE,
+        _ => 0,
+    }
+}
+
 /// Validate a tarball entry checksum
 fn header_checksum_is_valid(header_block: &[u8]) -> bool {
     const TARBALL_CHECKSUM_START: usize = 148;
@@ -147,9 +162,12 @@ fn tarball_entry_size(tarball_entry_data: &[u8]) -> Result<usize, SignatureError
 fn tarball_octal(octal_string: &[u8]) -> usize {
     let mut num: usize = 0;
 
-    for octal_char in octal_string {
-        // ASCII octal values should be ASCII
-        if *octal_char < 0x30 || *octal_char > 0x39 {
+    // Some tar implementations pad numeric fields with leading spaces
+    let leading_spaces = octal_string.iter().take_while(|&&b| b == b' ').count();
+
+    for octal_char in &octal_string[leading_spaces..] {
+        // Numeric fields are ASCII octal, terminated by a space or NULL byte
+        if !(b'0'..=b'7').contains(octal_char) {
             break;
         } else {
             num *= 8;
@@ -287,8 +305,25 @@ fn extract_tarball(
                 }
             }
 
-            // Character/block devices, fifos, sockets, and metadata-only entries:
-            // nothing to carve, but their presence must not fail the extraction.
+            // Device nodes and fifos are recorded as placeholder files, see Chroot.
+            EntryType::Char | EntryType::Block => {
+                let header = entry.header();
+                match (header.device_major(), header.device_minor()) {
+                    (Ok(Some(major)), Ok(Some(minor))) => {
+                        if entry_type.is_character_special() {
+                            chroot.create_character_device(&path, major as usize, minor as usize)
+                        } else {
+                            chroot.create_block_device(&path, major as usize, minor as usize)
+                        }
+                    }
+                    _ => false,
+                }
+            }
+
+            EntryType::Fifo => chroot.create_fifo(&path),
+
+            // Metadata-only entries: nothing to carve, but their presence must not
+            // fail the extraction.
             _ => true,
         };
 
@@ -304,7 +339,7 @@ fn extract_tarball(
 
     if extracted_something {
         result.success = true;
-        result.size = Some(consumed);
+        result.size = Some(consumed + end_of_archive_size(tarball_data, consumed));
     }
 
     result
@@ -358,10 +393,14 @@ mod tests {
     /// The shared test fixture: a deterministic POSIX (ustar) tar archive containing
     /// three files (see tests/inputs/gen_tarball.sh). The `ustar` magic for the first
     /// entry lives at TARBALL_MAGIC_OFFSET (257), i.e. the archive starts at offset 0.
-    const FIXTURE: &[u8] = include_bytes!(concat!(
-        env!("CARGO_MANIFEST_DIR"),
-        "/tests/inputs/tarball.bin"
-    ));
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/tarball.bin");
+
+    /// GNU tar archive of a character device, a block device and a fifo (see tests/inputs/gen_tarball.sh).
+    static VALID_SPECIAL_FILES: &[u8] = include_bytes!("../../tests/inputs/tarball_special.bin");
+
+    /// GNU tar archive of two files, one of which needs a GNU long name ('L') entry (see
+    /// tests/inputs/gen_tarball.sh).
+    static VALID_LONG_NAME: &[u8] = include_bytes!("../../tests/inputs/tarball_long_name.bin");
 
     #[test]
     fn octal_parses_basic_values() {
@@ -377,17 +416,20 @@ mod tests {
         assert_eq!(tarball_octal(b"17\x00rest"), 0o17);
         // A leading terminator yields zero.
         assert_eq!(tarball_octal(b"\x0033"), 0);
+        // Leading space padding is skipped, and 8 / 9 are not octal digits.
+        assert_eq!(tarball_octal(b"   644 "), 0o644);
+        assert_eq!(tarball_octal(b"178"), 0o17);
     }
 
     #[test]
     fn checksum_validates_real_header() {
-        let header = &FIXTURE[0..TARBALL_BLOCK_SIZE];
+        let header = &VALID[0..TARBALL_BLOCK_SIZE];
         assert!(header_checksum_is_valid(header));
     }
 
     #[test]
     fn checksum_rejects_corrupted_header() {
-        let mut header = FIXTURE[0..TARBALL_BLOCK_SIZE].to_vec();
+        let mut header = VALID[0..TARBALL_BLOCK_SIZE].to_vec();
         // Flip a byte in the file name field (outside the checksum field), which must
         // invalidate the stored checksum.
         header[0] ^= 0xFF;
@@ -397,7 +439,7 @@ mod tests {
     #[test]
     fn entry_size_rounds_up_to_block_size() {
         // First entry holds a 27-byte file: one header block + one (partial) data block.
-        let header = &FIXTURE[0..TARBALL_BLOCK_SIZE];
+        let header = &VALID[0..TARBALL_BLOCK_SIZE];
         assert_eq!(tarball_entry_size(header).unwrap(), 2 * TARBALL_BLOCK_SIZE);
     }
 
@@ -409,14 +451,14 @@ mod tests {
 
     #[test]
     fn parser_detects_fixture_archive() {
-        let result = tarball_parser(FIXTURE, TARBALL_MAGIC_OFFSET).unwrap();
+        let result = tarball_parser(VALID, TARBALL_MAGIC_OFFSET).unwrap();
 
         // Archive starts at the very beginning of the file.
         assert_eq!(result.offset, 0);
-        // Reported size covers all six entries' header + data blocks (not the trailing
-        // end-of-archive zero padding): four files (1024 each) plus a directory and a
-        // symlink (one 512-byte header block each).
-        assert_eq!(result.size, 10 * TARBALL_BLOCK_SIZE);
+        // Reported size covers all six entries' header + data blocks plus the two-block
+        // end-of-archive marker (but not any record padding after it): four files (1024
+        // each) plus a directory and a symlink (one 512-byte header block each).
+        assert_eq!(result.size, 12 * TARBALL_BLOCK_SIZE);
         // Six valid headers found; below TARBALL_MIN_EXPECTED_HEADERS, so medium.
         assert_eq!(result.confidence, CONFIDENCE_MEDIUM);
         assert!(result.confidence < CONFIDENCE_HIGH);
@@ -429,4 +471,76 @@ mod tests {
         let zeros = [0u8; 2 * TARBALL_BLOCK_SIZE];
         assert!(tarball_parser(&zeros, TARBALL_MAGIC_OFFSET).is_err());
     }
+
+    /// Device nodes and fifos can't be created without privileges; they must be extracted as
+    /// placeholder files inside the output directory, recording what they would have been.
+    #[test]
+    fn special_files_are_extracted_as_placeholders() {
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = extract_tarball(VALID_SPECIAL_FILES, 0, Some(output_directory.path()));
+        assert!(result.success);
+        // Three header-only entries, plus the end-of-archive marker
+        assert_eq!(result.size, Some(5 * TARBALL_BLOCK_SIZE));
+
+        let root = output_directory.path();
+        assert_eq!(
+            std::fs::read_to_string(root.join("dev/console")).unwrap(),
+            "c 5 1"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("dev/mtdblock3")).unwrap(),
+            "b 31 3"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("dev/initctl")).unwrap(),
+            "fifo"
+        );
+    }
+
+    /// A GNU long name entry is sized and extracted like any other entry; a corrupted header
+    /// checksum ends the archive at the last valid entry.
+    #[test]
+    fn long_name_archive_is_sized_and_extracted() {
+        let long_name = format!("{}/firmware.bin", "long_directory_name".repeat(6));
+
+        // config.txt (header + 1 data block), the long name entry (header + 1 name block), the
+        // second file (header + 2 data blocks), and the two-block end-of-archive marker
+        const ARCHIVE_SIZE: usize = 9 * TARBALL_BLOCK_SIZE;
+
+        // Trailing data is not part of the archive
+        let mut tarball_data = VALID_LONG_NAME[..ARCHIVE_SIZE].to_vec();
+        tarball_data.extend([0xFF; TARBALL_BLOCK_SIZE]);
+
+        let signature = tarball_parser(&tarball_data, TARBALL_MAGIC_OFFSET).unwrap();
+        assert_eq!(signature.offset, 0);
+        assert_eq!(signature.size, ARCHIVE_SIZE);
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = extract_tarball(&tarball_data, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(ARCHIVE_SIZE));
+
+        let root = output_directory.path();
+        assert_eq!(
+            std::fs::read(root.join("config.txt")).unwrap(),
+            b"hostname=ap1\n"
+        );
+        assert_eq!(
+            std::fs::read(root.join(&long_name)).unwrap(),
+            vec![0x5A; 600]
+        );
+
+        // Corrupt the long name entry's header; only config.txt remains valid
+        tarball_data[2 * TARBALL_BLOCK_SIZE] ^= 0xFF;
+
+        let signature = tarball_parser(&tarball_data, TARBALL_MAGIC_OFFSET).unwrap();
+        assert_eq!(signature.size, 2 * TARBALL_BLOCK_SIZE);
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = extract_tarball(&tarball_data, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(2 * TARBALL_BLOCK_SIZE));
+        assert!(output_directory.path().join("config.txt").exists());
+        assert!(!output_directory.path().join(&long_name).exists());
+    }
 }
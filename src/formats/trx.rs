@@ -32,10 +32,24 @@ pub fn trx_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
         // Dry run successful, parse the TRX header and return a useful description
         if let Ok(trx_header) = parse_trx_header(&file_data[offset..]) {
             result.size = trx_total_size;
+            result.children = trx_header
+                .partition_layout()
+                .into_iter()
+                .enumerate()
+                .map(|(i, (partition_offset, partition_size))| SignatureResult {
+                    offset: offset + partition_offset,
+                    size: partition_size,
+                    name: "trx_partition".to_string(),
+                    confidence: CONFIDENCE_HIGH,
+                    description: format!("TRX partition {i}, size: {partition_size} bytes"),
+                    ..Default::default()
+                })
+                .collect();
             result.description = format!(
-                "{}, version {}, partition count: {}, header size: {} bytes, total size: {} bytes",
+                "{}, version {}, flags: {:#X}, partition count: {}, header size: {} bytes, total size: {} bytes",
                 result.description,
                 trx_header.version,
+                trx_header.flags,
                 trx_header.partitions.len(),
                 trx_header.header_size,
                 result.size
@@ -44,6 +58,25 @@ pub fn trx_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
         }
     }
 
+    // A valid header that declares more data than is available is a truncated image
+    if let Ok(trx_header) = parse_trx_header(&file_data[offset..]) {
+        let available_size = file_data.len() - offset;
+
+        if trx_header.total_size > available_size {
+            result.description = format!(
+                "{}, version {}, flags: {:#X}, partition count: {}, header size: {} bytes",
+                result.description,
+                trx_header.version,
+                trx_header.flags,
+                trx_header.partitions.len(),
+                trx_header.header_size
+            );
+            result.extraction_declined = true;
+            result.set_truncated(trx_header.total_size, available_size);
+            return Ok(result);
+        }
+    }
+
     Err(SignatureError)
 }
 
@@ -51,6 +84,7 @@ pub fn trx_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
 #[derive(Debug, Clone, Default)]
 pub struct TRXHeader {
     pub version: u16,
+    pub flags: u16,
     pub checksum: u32,
     pub total_size: usize,
     pub header_size: usize,
@@ -71,8 +105,32 @@ struct TRXHeaderBytes {
     partition4_offset: zerocopy::U32<LE>,
 }
 
+impl TRXHeader {
+    /// Returns the offset (relative to the start of the TRX header) and size of each partition.
+    /// Each partition extends to the start of the next one, or to the end of the TRX image.
+    pub fn partition_layout(&self) -> Vec<(usize, usize)> {
+        self.partitions
+            .iter()
+            .enumerate()
+            .map(|(i, &partition_offset)| {
+                let partition_end = self
+                    .partitions
+                    .get(i + 1)
+                    .copied()
+                    .unwrap_or(self.total_size);
+                (
+                    partition_offset,
+                    partition_end.saturating_sub(partition_offset),
+                )
+            })
+            .collect()
+    }
+}
+
 /// Parse a TRX firmware header
 pub fn parse_trx_header(header_data: &[u8]) -> Result<TRXHeader, StructureError> {
+    const TRX_MAGIC: u32 = 0x30524448;
+
     // TRX comes in two flavors: v1 and v2
     const TRX_VERSION_2: u16 = 2;
 
@@ -84,51 +142,56 @@ pub fn parse_trx_header(header_data: &[u8]) -> Result<TRXHeader, StructureError>
     // Parse the header
     let (trx_header, _) =
         TRXHeaderBytes::ref_from_prefix(header_data).map_err(|_| StructureError)?;
-    // Sanity check partition offsets. Partition offsets may be 0.
-    if trx_header.partition1_offset <= trx_header.total_size
-        && trx_header.partition2_offset <= trx_header.total_size
-        && trx_header.partition3_offset <= trx_header.total_size
+
+    // Sanity check the magic bytes and reported version number
+    if trx_header.magic.get() != TRX_MAGIC || !allowed_versions.contains(&trx_header.version.get())
     {
-        // Sanity check the reported total size
-        if trx_header.total_size.get() as usize > struct_size {
-            // Sanity check the reported version number
-            if allowed_versions.contains(&trx_header.version.get()) {
-                let mut partitions = vec![];
-
-                if trx_header.partition1_offset != 0 {
-                    partitions.push(trx_header.partition1_offset.get() as usize);
-                }
+        return Err(StructureError);
+    }
 
-                if trx_header.partition2_offset != 0 {
-                    partitions.push(trx_header.partition2_offset.get() as usize);
-                }
+    let mut partition_offsets = vec![
+        trx_header.partition1_offset.get() as usize,
+        trx_header.partition2_offset.get() as usize,
+        trx_header.partition3_offset.get() as usize,
+    ];
 
-                if trx_header.partition3_offset != 0 {
-                    partitions.push(trx_header.partition3_offset.get() as usize);
-                }
+    // Only TRXv2 has a fourth partition entry
+    if trx_header.version == TRX_VERSION_2 {
+        partition_offsets.push(trx_header.partition4_offset.get() as usize);
+    } else {
+        // For TRXv1, this means the real structure size is 4 bytes shorter
+        struct_size -= std::mem::size_of::<u32>();
+    }
 
-                // Only TRXv2 has a fourth partition entry
-                if trx_header.version == TRX_VERSION_2 {
-                    if trx_header.partition4_offset != 0 {
-                        partitions.push(trx_header.partition4_offset.get() as usize);
-                    }
-                } else {
-                    // For TRXv1, this means the real structure size is 4 bytes shorter
-                    struct_size -= std::mem::size_of::<u32>();
-                }
+    let total_size = trx_header.total_size.get() as usize;
 
-                return Ok(TRXHeader {
-                    version: trx_header.version.get(),
-                    checksum: trx_header.crc32.get(),
-                    total_size: trx_header.total_size.get() as usize,
-                    header_size: struct_size,
-                    partitions: partitions.clone(),
-                });
-            }
-        }
+    // Sanity check the reported total size
+    if total_size <= struct_size {
+        return Err(StructureError);
+    }
+
+    // Partition offsets may be 0 (unused); all others must lie after the header, within the image, in ascending order
+    let partitions: Vec<usize> = partition_offsets
+        .into_iter()
+        .filter(|partition_offset| *partition_offset != 0)
+        .collect();
+
+    if partitions
+        .iter()
+        .any(|partition_offset| *partition_offset < struct_size || *partition_offset > total_size)
+        || !partitions.is_sorted_by(|a, b| a < b)
+    {
+        return Err(StructureError);
     }
 
-    Err(StructureError)
+    Ok(TRXHeader {
+        version: trx_header.version.get(),
+        flags: trx_header.flags.get(),
+        checksum: trx_header.crc32.get(),
+        total_size,
+        header_size: struct_size,
+        partitions,
+    })
 }
 
 /// Defines the internal TRX extractor
@@ -141,6 +204,8 @@ pub fn parse_trx_header(header_data: &[u8]) -> Result<TRXHeader, StructureError>
 ///
 /// match trx_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -187,23 +252,15 @@ pub fn extract_trx_partitions(
             if let Some(output_directory) = output_directory {
                 let chroot = Chroot::new(output_directory);
 
-                for i in 0..trx_header.partitions.len() {
-                    let next_partition: usize = i + 1;
-                    let this_partition_relative_offset: usize = trx_header.partitions[i];
-                    let this_partition_absolute_offset: usize =
-                        offset + this_partition_relative_offset;
-                    let this_partition_size = if next_partition < trx_header.partitions.len() {
-                        trx_header.partitions[next_partition] - this_partition_relative_offset
-                    } else {
-                        trx_header.total_size - this_partition_relative_offset
-                    };
-
+                for (i, (partition_offset, partition_size)) in
+                    trx_header.partition_layout().into_iter().enumerate()
+                {
                     let this_partition_file_name = format!("partition_{i}.bin");
                     result.success = chroot.carve_file(
                         &this_partition_file_name,
                         file_data,
-                        this_partition_absolute_offset,
-                        this_partition_size,
+                        offset + partition_offset,
+                        partition_size,
                     );
 
                     if !result.success {
@@ -220,3 +277,72 @@ pub fn extract_trx_partitions(
 fn trx_crc32(crc_data: &[u8]) -> u32 {
     crc32(crc_data) ^ 0xFFFFFFFF
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// TRXv1 image with a kernel and a rootfs partition; see tests/inputs/gen_trx.sh
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/trx.bin");
+
+    /// TRXv2 image with the same partitions
+    static VALID_V2: &[u8] = include_bytes!("../../tests/inputs/trx_v2.bin");
+
+    /// Extracts a TRX image and returns the contents of each carved partition
+    fn extract_partitions(image: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = extract_trx_partitions(image, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(image.len()));
+
+        let mut partitions: Vec<(String, Vec<u8>)> = std::fs::read_dir(output_directory.path())
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                (
+                    path.file_name().unwrap().to_string_lossy().to_string(),
+                    std::fs::read(&path).unwrap(),
+                )
+            })
+            .collect();
+        partitions.sort();
+        partitions
+    }
+
+    #[test]
+    fn two_partition_carving() {
+        for (image, version, header_size) in [(VALID, 1, 28), (VALID_V2, 2, 32)] {
+            let header = parse_trx_header(image).unwrap();
+            assert_eq!(header.version, version);
+            assert_eq!(header.header_size, header_size);
+            assert_eq!(header.partitions.len(), 2);
+
+            assert_eq!(
+                extract_partitions(image),
+                [
+                    ("partition_0.bin".to_string(), vec![0xAA; 100]),
+                    ("partition_1.bin".to_string(), vec![0xBB; 60]),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_images() {
+        // A CRC mismatch invalidates the image
+        let mut bad_crc = VALID.to_vec();
+        let last = bad_crc.len() - 1;
+        bad_crc[last] ^= 0xFF;
+        assert!(trx_parser(&bad_crc, 0).is_err());
+
+        // Partitions must be listed in ascending order
+        let mut unordered = VALID.to_vec();
+        unordered[16..20].copy_from_slice(&128u32.to_le_bytes());
+        assert!(parse_trx_header(&unordered).is_err());
+
+        // And must not overlap the header
+        let mut overlapping = VALID.to_vec();
+        overlapping[16..20].copy_from_slice(&8u32.to_le_bytes());
+        assert!(parse_trx_header(&overlapping).is_err());
+    }
+}
@@ -1,9 +1,12 @@
-use crate::common::crc32;
+use crate::common::crc32_seed;
 use crate::extractors;
+use crate::extractors::{Chroot, ExtractionResult};
 use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
 use crate::structures::StructureError;
 use aho_corasick::AhoCorasick;
-use std::collections::HashMap;
+use log::info;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 use zerocopy::{BE, FromBytes, Immutable, KnownLayout, LE, Unaligned};
 
 /// Human readable desciptions
@@ -65,6 +68,20 @@ pub fn ubi_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
                     "{}, version: {}, image size: {} bytes",
                     result.description, ubi_header.version, result.size
                 );
+
+                // Report the size of each volume in the image
+                if let Ok(volumes) = map_ubi_volumes(&file_data[offset..]) {
+                    let volume_info: Vec<String> = volumes
+                        .iter()
+                        .map(|volume| format!("\"{}\" ({} bytes)", volume.name(), volume.size()))
+                        .collect();
+                    result.description = format!(
+                        "{}, volumes: {}",
+                        result.description,
+                        volume_info.join(", ")
+                    );
+                }
+
                 return Ok(result);
             }
         }
@@ -73,57 +90,92 @@ pub fn ubi_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
     Err(SignatureError)
 }
 
+/// A run of this many invalid (neither valid nor erased) physical erase blocks is taken to be the end of a UBI image;
+/// shorter runs are bad blocks, which NAND dumps commonly contain
+const MAX_INVALID_PEB_RUN: usize = 8;
+
 /// Determines the LEB size and returns the size of the UBI image
 fn get_ubi_image_size(ubi_data: &[u8]) -> Result<usize, SignatureError> {
-    let mut leb_size: usize = 0;
-    let mut block_count: usize = 0;
-    let mut best_leb_match_count: usize = 0;
-    let mut previous_volume_offset: usize = 0;
-    let mut possible_leb_sizes: HashMap<usize, usize> = HashMap::new();
+    let (peb_size, block_count) = get_ubi_geometry(ubi_data)?;
+    Ok(block_count * peb_size)
+}
 
-    // Volume magic bytes, version is assumed to be 1
-    let ubi_vol_magic = vec![b"UBI!\x01"];
+/// Determines the physical erase block size (the distance between erase count headers) and the number of erase blocks
+/// in a UBI image.
+///
+/// Erased physical erase blocks (all 0xFF, with no erase count header) and bad physical erase blocks are included in
+/// the count, unless they are at the end of the image.
+fn get_ubi_geometry(ubi_data: &[u8]) -> Result<(usize, usize), SignatureError> {
+    let mut peb_size: usize = 0;
+    let mut best_peb_match_count: usize = 0;
+    let mut previous_ec_offset: Option<usize> = None;
+    let mut possible_peb_sizes: HashMap<usize, usize> = HashMap::new();
+
+    // Erase count header magic bytes, version is assumed to be 1
+    let ubi_ec_magic = vec![b"UBI#\x01"];
 
-    let grep = AhoCorasick::new(ubi_vol_magic).unwrap();
+    let grep = AhoCorasick::new(ubi_ec_magic).unwrap();
 
-    // grep for all volume header magic bytes
+    // grep for all erase count header magic bytes
     for magic_match in grep.find_overlapping_iter(ubi_data) {
         // Offset in the UBI image where this magic match was found
-        let this_volume_offset = magic_match.start();
-
-        // Parse the volume header
-        if parse_ubi_volume_header(&ubi_data[this_volume_offset..]).is_ok() {
-            // Header looks valid, increment the block count
-            block_count += 1;
+        let this_ec_offset = magic_match.start();
 
-            // If there was a previous UBI volume header identified, calculate the leb size as the distance between the two volume header
-            if previous_volume_offset != 0 {
-                let this_leb_size = this_volume_offset - previous_volume_offset;
+        // Parse the erase count header
+        if parse_ubi_ec_header(&ubi_data[this_ec_offset..]).is_ok() {
+            // If there was a previous erase count header identified, calculate the peb size as the distance between the two headers
+            if let Some(previous_ec_offset) = previous_ec_offset {
+                let this_peb_size = this_ec_offset - previous_ec_offset;
 
-                // Keep track of the calculated leb size, and how many times each possible leb size was found
-                *possible_leb_sizes.entry(this_leb_size).or_insert(0) += 1;
+                // Keep track of the calculated peb size, and how many times each possible peb size was found
+                *possible_peb_sizes.entry(this_peb_size).or_insert(0) += 1;
             }
 
-            previous_volume_offset = this_volume_offset;
+            previous_ec_offset = Some(this_ec_offset);
+        }
+    }
+
+    // Pick the most common peb size
+    for (peb_candidate_size, peb_candidate_count) in possible_peb_sizes.iter() {
+        if *peb_candidate_count > best_peb_match_count {
+            peb_size = *peb_candidate_size;
+            best_peb_match_count = *peb_candidate_count;
         }
     }
 
-    // Pick the most common leb size
-    for (leb_candidate_size, leb_candidate_count) in possible_leb_sizes.iter() {
-        if *leb_candidate_count > best_leb_match_count {
-            leb_size = *leb_candidate_size;
-            best_leb_match_count = *leb_candidate_count;
+    if peb_size == 0 {
+        return Err(SignatureError);
+    }
+
+    // Count the physical erase blocks, stopping at a run of blocks that are neither valid nor erased
+    let mut block_count: usize = 0;
+    let mut invalid_peb_run: usize = 0;
+
+    for (block_number, peb_data) in ubi_data.chunks_exact(peb_size).enumerate() {
+        if parse_ubi_ec_header(peb_data).is_ok() {
+            block_count = block_number + 1;
+            invalid_peb_run = 0;
+        } else if !is_erased(peb_data) {
+            invalid_peb_run += 1;
+            if invalid_peb_run >= MAX_INVALID_PEB_RUN {
+                break;
+            }
         }
     }
 
-    // Image size is leb size times the number of blocks
-    if leb_size != 0 && block_count != 0 {
-        return Ok(block_count * leb_size);
+    if block_count != 0 {
+        return Ok((peb_size, block_count));
     }
 
     Err(SignatureError)
 }
 
+/// Returns true if the data is erased flash
+fn is_erased(data: &[u8]) -> bool {
+    const ERASED_FLASH: u8 = 0xFF;
+    data.iter().all(|b| *b == ERASED_FLASH)
+}
+
 /// Stores UBI superblock header info
 #[derive(Debug, Default, Clone)]
 pub struct UbiSuperBlockHeader {
@@ -228,6 +280,9 @@ struct UbiECHeaderBytes {
     header_crc: zerocopy::U32<BE>,
 }
 
+/// Erase count header magic bytes, "UBI#"
+const UBI_EC_HEADER_MAGIC: u32 = 0x55424923;
+
 /// Parse a UBI erase count header
 pub fn parse_ubi_ec_header(ubi_data: &[u8]) -> Result<UbiECHeader, StructureError> {
     let ec_header_size: usize = std::mem::size_of::<UbiECHeaderBytes>();
@@ -238,7 +293,8 @@ pub fn parse_ubi_ec_header(ubi_data: &[u8]) -> Result<UbiECHeader, StructureErro
         UbiECHeaderBytes::ref_from_prefix(ubi_data).map_err(|_| StructureError)?;
 
     // Offsets should be beyond the EC header
-    if ubi_ec_header.data_offset.get() as usize >= ec_header_size
+    if ubi_ec_header.magic.get() == UBI_EC_HEADER_MAGIC
+        && ubi_ec_header.data_offset.get() as usize >= ec_header_size
         && ubi_ec_header.volume_id_header_offset.get() as usize >= ec_header_size
     {
         // Validate the header CRC
@@ -256,9 +312,20 @@ pub fn parse_ubi_ec_header(ubi_data: &[u8]) -> Result<UbiECHeader, StructureErro
     Err(StructureError)
 }
 
-/// Dummy structure indicating a UBI volume header was parsed successfully
+/// Stores info about a UBI volume ID header, which maps a physical erase block to a logical erase block of a volume
 #[derive(Debug, Default, Clone)]
-pub struct UbiVolumeHeader;
+pub struct UbiVolumeHeader {
+    pub volume_id: u32,
+    pub volume_type: u8,
+    /// Logical erase block number of this physical erase block within its volume
+    pub leb_number: usize,
+    /// For static volumes, the number of data bytes in this erase block; 0 for dynamic volumes
+    pub data_size: usize,
+    /// Number of unused bytes at the end of each erase block
+    pub data_padding_size: usize,
+    /// Global write sequence number; the highest number wins if a logical erase block is mapped more than once
+    pub sequence_number: u64,
+}
 
 #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
 #[repr(C, packed)]
@@ -302,20 +369,218 @@ pub fn parse_ubi_volume_header(ubi_data: &[u8]) -> Result<UbiVolumeHeader, Struc
         if let Some(crc_data) = ubi_data.get(0..crc_data_size)
             && ubi_crc(crc_data) == ubi_vol_header.header_crc.get()
         {
-            return Ok(UbiVolumeHeader);
+            return Ok(UbiVolumeHeader {
+                volume_id: ubi_vol_header.volume_id.get(),
+                volume_type: ubi_vol_header.volume_type,
+                leb_number: ubi_vol_header.logical_erase_block_number.get() as usize,
+                data_size: ubi_vol_header.data_size.get() as usize,
+                data_padding_size: ubi_vol_header.data_padding_size.get() as usize,
+                sequence_number: ubi_vol_header.sequence_number.get(),
+            });
         }
     }
 
     Err(StructureError)
 }
 
-/// Calculate a UBI checksum
+/// Volume ID of the layout volume, which holds the volume table
+const UBI_LAYOUT_VOLUME_ID: u32 = 0x7FFFEFFF;
+
+/// Volume IDs at or above this are internal to UBI, and are not user volumes
+const UBI_INTERNAL_VOLUME_START: u32 = 0x7FFFEF00;
+
+/// Volume type of static volumes, whose erase blocks each record how many bytes of data they hold
+const UBI_STATIC_VOLUME: u8 = 2;
+
+/// A UBI volume, as mapped from the volume ID headers of a UBI image's physical erase blocks
+#[derive(Debug, Default, Clone)]
+pub struct UbiVolume<'a> {
+    pub volume_id: u32,
+    /// Volume name, from the volume table in the layout volume; None if unknown
+    pub volume_name: Option<String>,
+    /// Size of each logical erase block
+    leb_size: usize,
+    /// Data of each mapped logical erase block, indexed by logical erase block number
+    lebs: BTreeMap<usize, &'a [u8]>,
+}
+
+impl UbiVolume<'_> {
+    /// The volume name, or a name derived from the volume ID if the volume name is unknown
+    pub fn name(&self) -> String {
+        self.volume_name
+            .clone()
+            .unwrap_or_else(|| format!("volume_{}", self.volume_id))
+    }
+
+    /// Size of the reassembled volume data, up to the end of the last mapped logical erase block
+    pub fn size(&self) -> usize {
+        self.lebs
+            .last_key_value()
+            .map_or(0, |(leb_number, leb_data)| {
+                (leb_number * self.leb_size) + leb_data.len()
+            })
+    }
+
+    /// Reassembles the volume data from its logical erase blocks; unmapped logical erase blocks read as erased flash
+    pub fn data(&self) -> Vec<u8> {
+        const ERASED_FLASH: u8 = 0xFF;
+
+        let mut volume_data: Vec<u8> = Vec::with_capacity(self.size());
+
+        for (leb_number, leb_data) in &self.lebs {
+            volume_data.resize(leb_number * self.leb_size, ERASED_FLASH);
+            volume_data.extend_from_slice(leb_data);
+        }
+
+        volume_data
+    }
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct UbiVolumeTableRecordBytes {
+    reserved_pebs: zerocopy::U32<BE>,
+    alignment: zerocopy::U32<BE>,
+    data_pad: zerocopy::U32<BE>,
+    volume_type: u8,
+    update_marker: u8,
+    name_len: zerocopy::U16<BE>,
+    name: [u8; 128],
+    flags: u8,
+    padding: [u8; 23],
+    crc: zerocopy::U32<BE>,
+}
+
+/// Parse the volume table stored in the layout volume; returns the name of each volume, indexed by volume ID
+fn parse_ubi_volume_table(table_data: &[u8]) -> HashMap<u32, String> {
+    const MAX_VOLUMES: usize = 128;
+
+    let record_size: usize = std::mem::size_of::<UbiVolumeTableRecordBytes>();
+    let crc_data_size: usize = record_size - std::mem::size_of::<u32>();
+
+    let mut volume_names: HashMap<u32, String> = HashMap::new();
+
+    for (volume_id, record_data) in
+        (0..).zip(table_data.chunks_exact(record_size).take(MAX_VOLUMES))
+    {
+        // Unused records have an empty name
+        if let Ok((record, _)) = UbiVolumeTableRecordBytes::ref_from_prefix(record_data)
+            && ubi_crc(&record_data[..crc_data_size]) == record.crc.get()
+            && let Some(name) = record.name.get(..record.name_len.get() as usize)
+            && !name.is_empty()
+        {
+            volume_names.insert(volume_id, String::from_utf8_lossy(name).to_string());
+        }
+    }
+
+    volume_names
+}
+
+/// Maps the user volumes of a UBI image to their logical erase blocks, following the logical-to-physical mapping in
+/// each physical erase block's volume ID header.
+///
+/// Physical erase blocks may be in any order (e.g., due to wear leveling). If a logical erase block is mapped more than
+/// once, the most recently written copy is used. Bad physical erase blocks are skipped. Volumes are sorted by volume ID.
+pub fn map_ubi_volumes(ubi_data: &[u8]) -> Result<Vec<UbiVolume<'_>>, StructureError> {
+    let (peb_size, _) = get_ubi_geometry(ubi_data).map_err(|_| StructureError)?;
+
+    // Logical erase block numbers can't exceed the number of physical erase blocks
+    let max_leb_number = ubi_data.len() / peb_size;
+
+    // Volume ID header and data of each logical erase block, indexed by volume ID, then logical erase block number
+    let mut volume_lebs: BTreeMap<u32, BTreeMap<usize, (UbiVolumeHeader, &[u8])>> = BTreeMap::new();
+    // Size of each volume's logical erase blocks, indexed by volume ID
+    let mut leb_sizes: HashMap<u32, usize> = HashMap::new();
+
+    let mut invalid_peb_run: usize = 0;
+
+    // Every physical erase block in the image starts with an erase count header
+    for peb_data in ubi_data.chunks_exact(peb_size) {
+        let Ok(ec_header) = parse_ubi_ec_header(peb_data) else {
+            // Erased physical erase blocks may be interspersed with valid ones
+            if is_erased(peb_data) {
+                continue;
+            }
+
+            // As may bad physical erase blocks
+            invalid_peb_run += 1;
+            if invalid_peb_run >= MAX_INVALID_PEB_RUN {
+                break;
+            }
+            continue;
+        };
+
+        invalid_peb_run = 0;
+
+        // Erased physical erase blocks have no volume ID header
+        let Some(Ok(vid_header)) = peb_data
+            .get(ec_header.volume_id_offset..)
+            .map(parse_ubi_volume_header)
+        else {
+            continue;
+        };
+
+        let Some(mut leb_data) = peb_size
+            .checked_sub(vid_header.data_padding_size)
+            .and_then(|data_end| peb_data.get(ec_header.data_offset..data_end))
+        else {
+            continue;
+        };
+
+        if vid_header.leb_number >= max_leb_number {
+            continue;
+        }
+
+        leb_sizes.insert(vid_header.volume_id, leb_data.len());
+
+        // Static volume erase blocks record how much of the erase block is used
+        if vid_header.volume_type == UBI_STATIC_VOLUME {
+            let Some(used_data) = leb_data.get(..vid_header.data_size) else {
+                continue;
+            };
+            leb_data = used_data;
+        }
+
+        let lebs = volume_lebs.entry(vid_header.volume_id).or_default();
+
+        if lebs
+            .get(&vid_header.leb_number)
+            .is_none_or(|(mapped_header, _)| {
+                mapped_header.sequence_number < vid_header.sequence_number
+            })
+        {
+            lebs.insert(vid_header.leb_number, (vid_header, leb_data));
+        }
+    }
+
+    // The first logical erase block of the layout volume holds the volume table
+    let volume_names = volume_lebs
+        .get(&UBI_LAYOUT_VOLUME_ID)
+        .and_then(|lebs| lebs.get(&0))
+        .map(|(_, table_data)| parse_ubi_volume_table(table_data))
+        .unwrap_or_default();
+
+    Ok(volume_lebs
+        .into_iter()
+        .filter(|(volume_id, _)| *volume_id < UBI_INTERNAL_VOLUME_START)
+        .map(|(volume_id, lebs)| UbiVolume {
+            volume_id,
+            volume_name: volume_names.get(&volume_id).cloned(),
+            leb_size: leb_sizes[&volume_id],
+            lebs: lebs
+                .into_iter()
+                .map(|(leb_number, (_, leb_data))| (leb_number, leb_data))
+                .collect(),
+        })
+        .collect())
+}
+
+/// Calculate a UBI checksum; this is a CRC32 with no final XOR
 fn ubi_crc(data: &[u8]) -> u32 {
-    const UBI_CRC_INIT: u32 = 0xFFFFFFFF;
-    (!crc32(data)) & UBI_CRC_INIT
+    !crc32_seed(0, data)
 }
 
-/// Describes how to run the ubireader_extract_images utility to extract UBI images
+/// Defines the internal extractor for UBI images, which reassembles each volume in the image to a file
 ///
 /// ```
 /// use std::io::ErrorKind;
@@ -325,6 +590,8 @@ fn ubi_crc(data: &[u8]) -> u32 {
 ///
 /// match ubi_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -339,14 +606,66 @@ fn ubi_crc(data: &[u8]) -> u32 {
 /// ```
 pub fn ubi_extractor() -> extractors::Extractor {
     extractors::Extractor {
-        utility: extractors::ExtractorType::External("ubireader_extract_images".to_string()),
-        extension: "img".to_string(),
-        arguments: vec![extractors::SOURCE_FILE_PLACEHOLDER.to_string()],
-        exit_codes: vec![0],
+        utility: extractors::ExtractorType::Internal(extract_ubi_volumes),
         ..Default::default()
     }
 }
 
+/// Internal extractor for UBI images; writes each volume to `<volume name>.img`, to be further extracted (e.g., as
+/// UBIFS) by recursive analysis
+pub fn extract_ubi_volumes(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> ExtractionResult {
+    const OUTPUT_FILE_EXT: &str = "img";
+
+    let mut result = ExtractionResult::default();
+
+    if let Some(ubi_data) = file_data.get(offset..)
+        && let Ok(image_size) = get_ubi_image_size(ubi_data)
+        && let Ok(volumes) = map_ubi_volumes(ubi_data)
+        && !volumes.is_empty()
+    {
+        result.size = Some(image_size);
+        result.success = true;
+
+        if let Some(output_directory) = output_directory {
+            let chroot = Chroot::new(output_directory);
+
+            for volume in &volumes {
+                // Volume names are user-defined; make sure they are sane file names
+                let file_name: String = volume
+                    .name()
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() || "-_.".contains(c) {
+                            c
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect();
+                let file_name = format!("{}_{file_name}.{OUTPUT_FILE_EXT}", volume.volume_id);
+
+                info!(
+                    "Reassembled UBI volume {} \"{}\": {} bytes",
+                    volume.volume_id,
+                    volume.name(),
+                    volume.size()
+                );
+
+                if !chroot.create_file(&file_name, &volume.data()) {
+                    result.success = false;
+                    break;
+                }
+            }
+        }
+    }
+
+    result
+}
+
 /// Describes how to run the ubireader_extract_files utility to extract UBIFS images
 pub fn ubifs_extractor() -> extractors::Extractor {
     extractors::Extractor {
@@ -357,3 +676,71 @@ pub fn ubifs_extractor() -> extractors::Extractor {
         ..Default::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A UBI image with 1KiB physical erase blocks and a single volume, whose logical erase blocks are out of order
+    /// and interspersed with a stale copy of LEB 0, an erased block and a bad block (see tests/inputs/gen_ubi.sh)
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/ubi.bin");
+
+    const PEB_SIZE: usize = 0x400;
+    const LEB_SIZE: usize = PEB_SIZE - 128;
+
+    #[test]
+    fn ec_header_crc() {
+        let header = parse_ubi_ec_header(VALID).unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!(header.volume_id_offset, 64);
+        assert_eq!(header.data_offset, 128);
+
+        // Corrupt the erase count
+        let mut corrupt_header = VALID[..PEB_SIZE].to_vec();
+        corrupt_header[15] ^= 1;
+        assert!(parse_ubi_ec_header(&corrupt_header).is_err());
+
+        // Corrupt the CRC itself
+        let mut corrupt_header = VALID[..PEB_SIZE].to_vec();
+        corrupt_header[63] ^= 1;
+        assert!(parse_ubi_ec_header(&corrupt_header).is_err());
+    }
+
+    #[test]
+    fn geometry() {
+        // The erased and bad blocks in the middle of the image don't hide the erase blocks that follow them
+        assert_eq!(get_ubi_geometry(VALID).unwrap(), (PEB_SIZE, 8));
+
+        // Trailing erased blocks are not part of the image
+        let mut image = VALID.to_vec();
+        image.extend([0xFF; PEB_SIZE]);
+        assert_eq!(get_ubi_image_size(&image).unwrap(), VALID.len());
+    }
+
+    #[test]
+    fn volume_reassembly() {
+        let volumes = map_ubi_volumes(VALID).unwrap();
+        assert_eq!(volumes.len(), 1);
+        assert_eq!(volumes[0].volume_id, 0);
+        assert_eq!(volumes[0].name(), "rootfs");
+        assert_eq!(volumes[0].size(), LEB_SIZE * 2);
+
+        // The stale copy of LEB 0 is superseded by the one with the higher sequence number
+        assert_eq!(
+            volumes[0].data(),
+            [[b'A'; LEB_SIZE], [b'B'; LEB_SIZE]].concat()
+        );
+    }
+
+    #[test]
+    fn extraction() {
+        let output_directory = tempfile::tempdir().unwrap();
+
+        let result = extract_ubi_volumes(VALID, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(VALID.len()));
+
+        let volume_data = std::fs::read(output_directory.path().join("0_rootfs.img")).unwrap();
+        assert_eq!(volume_data, [[b'A'; LEB_SIZE], [b'B'; LEB_SIZE]].concat());
+    }
+}
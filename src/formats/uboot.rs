@@ -1,4 +1,4 @@
-use crate::common::get_cstring;
+use crate::common::get_cstring_bounded;
 use crate::signatures::{CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
 
 /// Human readable description
@@ -12,6 +12,7 @@ pub fn uboot_magic() -> Vec<Vec<u8>> {
 /// Validates the U-Boot version number magic
 pub fn uboot_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
     const NUMBER_OFFSET: usize = 7;
+    const MAX_VERSION_STRING_LENGTH: usize = 256;
 
     // Successful return value
     let mut result = SignatureResult {
@@ -24,7 +25,10 @@ pub fn uboot_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult,
     if let Some(expected_number_byte) = file_data.get(offset + NUMBER_OFFSET)
         && expected_number_byte.is_ascii_digit()
     {
-        let uboot_version_string = get_cstring(&file_data[offset + NUMBER_OFFSET..]);
+        let uboot_version_string = get_cstring_bounded(
+            &file_data[offset + NUMBER_OFFSET..],
+            MAX_VERSION_STRING_LENGTH,
+        );
 
         if !uboot_version_string.is_empty() {
             result.size = uboot_version_string.len();
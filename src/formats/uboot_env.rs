@@ -0,0 +1,309 @@
+use crate::common::crc32;
+use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
+use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
+use crate::structures::StructureError;
+use std::path::Path;
+
+/// Human readable description
+pub const DESCRIPTION: &str = "U-Boot environment";
+
+/// Size of the CRC32 that starts every environment block
+const CRC_SIZE: usize = 4;
+
+/// Size of the flags byte that follows the CRC32 in redundant environment blocks
+const FLAGS_SIZE: usize = 1;
+
+/// Environment sizes that U-Boot is commonly configured with (CONFIG_ENV_SIZE); the CRC covers the entire block
+const ENV_SIZES: [usize; 9] = [
+    0x400, 0x800, 0x1000, 0x2000, 0x4000, 0x8000, 0x10000, 0x20000, 0x40000,
+];
+
+/// Redundant environment copies live in separate flash sectors, which may be larger than the environment itself
+const MAX_COPY_DISTANCE: usize = 4;
+
+/// The environment is a list of NULL-terminated variables; these are commonly the first one
+pub fn uboot_env_magic() -> Vec<Vec<u8>> {
+    vec![
+        b"arch=".to_vec(),
+        b"baudrate=".to_vec(),
+        b"bootargs=".to_vec(),
+        b"bootcmd=".to_vec(),
+        b"bootdelay=".to_vec(),
+    ]
+}
+
+/// Validates a U-Boot environment block, including redundant copies
+pub fn uboot_env_parser(
+    file_data: &[u8],
+    offset: usize,
+) -> Result<SignatureResult, SignatureError> {
+    // The magic bytes are the start of the environment variables, which follow the CRC and, if redundant, the flags byte
+    for header_size in [CRC_SIZE + FLAGS_SIZE, CRC_SIZE] {
+        if let Some(env_offset) = offset.checked_sub(header_size)
+            && let Ok(env) = parse_uboot_env(file_data, env_offset)
+            && env.header_size == header_size
+        {
+            let active_copy = &env.copies[env.active];
+
+            let layout = if env.copies.len() > 1 {
+                format!(
+                    "redundant, copies: {}, active copy offset: {:#X}, active copy flags: {:#04X}",
+                    env.copies.len(),
+                    active_copy.offset,
+                    active_copy.flags.unwrap_or_default()
+                )
+            } else {
+                "single copy".to_string()
+            };
+
+            let last_copy = env.copies.last().unwrap();
+
+            return Ok(SignatureResult {
+                offset: env_offset,
+                size: last_copy.offset + env.env_size - env_offset,
+                confidence: CONFIDENCE_HIGH,
+                description: format!(
+                    "{DESCRIPTION}, {layout}, environment size: {} bytes, variables: {}",
+                    env.env_size,
+                    active_copy.variables.len()
+                ),
+                ..Default::default()
+            });
+        }
+    }
+
+    Err(SignatureError)
+}
+
+/// A single copy of a U-Boot environment
+#[derive(Debug, Default, Clone)]
+pub struct UBootEnvCopy {
+    /// Offset of this copy in the file data
+    pub offset: usize,
+    /// Flags byte, only present in redundant environments; the active copy is the one most recently written
+    pub flags: Option<u8>,
+    /// Environment variables, as "name=value" strings
+    pub variables: Vec<String>,
+}
+
+/// Describes a U-Boot environment and any redundant copies of it
+#[derive(Debug, Default, Clone)]
+pub struct UBootEnv {
+    /// Size of each copy of the environment, including the header
+    pub env_size: usize,
+    /// Size of each copy's header; 4 for a CRC32, or 5 for a CRC32 plus flags byte (redundant environments)
+    pub header_size: usize,
+    /// All copies with a valid CRC, in file order
+    pub copies: Vec<UBootEnvCopy>,
+    /// Index into `copies` of the active copy
+    pub active: usize,
+}
+
+/// Parses a U-Boot environment starting at `offset`, along with any redundant copy that follows it.
+///
+/// Both the single (CRC32 + data) and redundant (CRC32 + flags + data) layouts are supported; only copies whose CRC is
+/// valid are reported. For redundant environments, the active copy is selected the same way U-Boot selects it.
+pub fn parse_uboot_env(file_data: &[u8], offset: usize) -> Result<UBootEnv, StructureError> {
+    for header_size in [CRC_SIZE + FLAGS_SIZE, CRC_SIZE] {
+        for env_size in ENV_SIZES {
+            let Ok(first_copy) = parse_env_copy(file_data, offset, env_size, header_size) else {
+                continue;
+            };
+
+            let mut env = UBootEnv {
+                env_size,
+                header_size,
+                copies: vec![first_copy],
+                active: 0,
+            };
+
+            // Look for a second, redundant copy in one of the following sectors
+            if header_size == CRC_SIZE + FLAGS_SIZE
+                && let Some(second_copy) = (1..=MAX_COPY_DISTANCE).find_map(|distance| {
+                    parse_env_copy(
+                        file_data,
+                        offset + (env_size * distance),
+                        env_size,
+                        header_size,
+                    )
+                    .ok()
+                })
+            {
+                env.copies.push(second_copy);
+                env.active = active_copy(env.copies[0].flags, env.copies[1].flags);
+            }
+
+            return Ok(env);
+        }
+    }
+
+    Err(StructureError)
+}
+
+/// Parses and validates a single environment copy of the specified size
+fn parse_env_copy(
+    file_data: &[u8],
+    offset: usize,
+    env_size: usize,
+    header_size: usize,
+) -> Result<UBootEnvCopy, StructureError> {
+    let env_data = file_data
+        .get(offset..offset + env_size)
+        .ok_or(StructureError)?;
+
+    let expected_crc = u32::from_le_bytes(env_data[0..CRC_SIZE].try_into().unwrap());
+    let variable_data = &env_data[header_size..];
+
+    if crc32(variable_data) != expected_crc {
+        return Err(StructureError);
+    }
+
+    // Variables are NULL terminated, and the list ends with an empty variable
+    let variables: Vec<String> = variable_data
+        .split(|b| *b == 0)
+        .take_while(|variable| !variable.is_empty())
+        .map(|variable| String::from_utf8_lossy(variable).to_string())
+        .collect();
+
+    if variables.is_empty() || !variables.iter().all(|variable| variable.contains('=')) {
+        return Err(StructureError);
+    }
+
+    Ok(UBootEnvCopy {
+        offset,
+        flags: (header_size > CRC_SIZE).then_some(env_data[CRC_SIZE]),
+        variables,
+    })
+}
+
+/// Returns the index of the active copy of a redundant environment, given the flags of both copies.
+/// The flags are a counter incremented on each save; this mirrors U-Boot's own selection, including counter wrap-around.
+fn active_copy(first_flags: Option<u8>, second_flags: Option<u8>) -> usize {
+    match (
+        first_flags.unwrap_or_default(),
+        second_flags.unwrap_or_default(),
+    ) {
+        (0xFF, 0) => 1,
+        (0, 0xFF) => 0,
+        (first, second) if second > first => 1,
+        _ => 0,
+    }
+}
+
+/// Defines the internal extractor function for U-Boot environments
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::uboot_env::uboot_env_extractor;
+///
+/// match uboot_env_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn uboot_env_extractor() -> Extractor {
+    Extractor {
+        utility: ExtractorType::Internal(extract_uboot_env),
+        ..Default::default()
+    }
+}
+
+/// Writes the variables of the active environment copy to a text file, one "name=value" per line
+pub fn extract_uboot_env(
+    file_data: &[u8],
+    offset: usize,
+    output_directory: Option<&Path>,
+) -> ExtractionResult {
+    const OUTPUT_FILE_NAME: &str = "uboot_env.txt";
+
+    let mut result = ExtractionResult::default();
+
+    if let Ok(env) = parse_uboot_env(file_data, offset) {
+        let last_copy = env.copies.last().unwrap();
+        result.size = Some(last_copy.offset + env.env_size - offset);
+        result.success = true;
+
+        if let Some(output_directory) = output_directory {
+            let mut variables = env.copies[env.active].variables.join("\n");
+            variables.push('\n');
+
+            let chroot = Chroot::new(output_directory);
+            result.success = chroot.create_file(OUTPUT_FILE_NAME, variables.as_bytes());
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENV_SIZE: usize = 0x1000;
+
+    /// Builds a redundant environment copy with the specified flags and variables
+    fn env_copy(flags: u8, variables: &[&str]) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        for variable in variables {
+            data.extend(variable.as_bytes());
+            data.push(0);
+        }
+        data.resize(ENV_SIZE - CRC_SIZE - FLAGS_SIZE, 0);
+
+        let mut env = crc32(&data).to_le_bytes().to_vec();
+        env.push(flags);
+        env.extend(data);
+        env
+    }
+
+    #[test]
+    fn reports_active_redundant_copy() {
+        let mut file_data = vec![0xFF; 0x20];
+        file_data.extend(env_copy(1, &["bootcmd=run old", "bootdelay=3"]));
+        file_data.extend(env_copy(2, &["bootcmd=run new", "bootdelay=1"]));
+
+        let signature = uboot_env_parser(&file_data, 0x20 + CRC_SIZE + FLAGS_SIZE).unwrap();
+        assert_eq!(signature.offset, 0x20);
+        assert_eq!(signature.size, ENV_SIZE * 2);
+        assert!(signature.description.contains("active copy offset: 0x1020"));
+
+        let output_dir = tempfile::tempdir().unwrap();
+        let extraction = extract_uboot_env(&file_data, signature.offset, Some(output_dir.path()));
+        assert!(extraction.success);
+
+        let variables = std::fs::read_to_string(output_dir.path().join("uboot_env.txt")).unwrap();
+        assert_eq!(variables, "bootcmd=run new\nbootdelay=1\n");
+    }
+
+    #[test]
+    fn ignores_copy_with_bad_crc() {
+        let mut stale = env_copy(1, &["bootcmd=run old"]);
+        let mut corrupt = env_copy(2, &["bootcmd=run new"]);
+        corrupt[ENV_SIZE - 1] = 0xAA;
+        stale.extend(corrupt);
+
+        let env = parse_uboot_env(&stale, 0).unwrap();
+        assert_eq!(env.copies.len(), 1);
+        assert_eq!(env.copies[env.active].variables, vec!["bootcmd=run old"]);
+    }
+
+    #[test]
+    fn flag_counter_wraps() {
+        assert_eq!(active_copy(Some(0xFF), Some(0)), 1);
+        assert_eq!(active_copy(Some(0), Some(0xFF)), 0);
+        assert_eq!(active_copy(Some(3), Some(3)), 0);
+    }
+}
@@ -185,6 +185,8 @@ pub fn parse_uefi_capsule_header(uefi_data: &[u8]) -> Result<UEFICapsuleHeader,
 ///
 /// match uefi_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -213,5 +215,6 @@ pub fn uefi_extractor() -> extractors::Extractor {
          * Recursing into this data would result in double extractions for no good reason.
          */
         do_not_recurse: true,
+        ..Default::default()
     }
 }
@@ -1,9 +1,11 @@
-use crate::common::{crc32, epoch_to_string, get_cstring};
+use crate::common::{crc32, epoch_to_string};
 use crate::extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
+use crate::formats::{bzip2, gzip, lz4, lzma, zstd};
 use crate::signatures::{
     CONFIDENCE_HIGH, CONFIDENCE_LOW, CONFIDENCE_MEDIUM, SignatureError, SignatureResult,
 };
 use crate::structures::StructureError;
+use crate::structures::fixed_string::FixedString;
 use std::path::Path;
 use zerocopy::{BE, FromBytes, Immutable, KnownLayout, Unaligned};
 
@@ -57,6 +59,29 @@ pub fn uimage_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult,
                 epoch_to_string(uimage_header.timestamp as u32),
                 uimage_header.name
             );
+            // If the image data was validated, report where the image(s) it contains are located
+            if uimage_size > uimage_header.header_size {
+                let data_start = offset + uimage_header.header_size;
+
+                result.children = uimage_sub_images(
+                    &uimage_header,
+                    &file_data[data_start..data_start + uimage_header.data_size],
+                )
+                .into_iter()
+                .map(|(image_offset, image_size)| SignatureResult {
+                    offset: data_start + image_offset,
+                    size: image_size,
+                    name: "uimage_data".to_string(),
+                    confidence: CONFIDENCE_HIGH,
+                    description: format!(
+                        "uImage data, image type: {}, compression: {}, size: {} bytes",
+                        uimage_header.image_type, uimage_header.compression_type, image_size
+                    ),
+                    ..Default::default()
+                })
+                .collect();
+            }
+
             // If the header CRC is invalid, adjust the reported confidence level and report the checksum mis-match
             if !uimage_header.header_crc_valid {
                 // If the uImage header was otherwise valid and starts at file offset 0 then we're still fairly confident in the result
@@ -73,6 +98,27 @@ pub fn uimage_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult,
         }
     }
 
+    // A valid header whose image data runs past the end of the file is a truncated image
+    if let Ok(uimage_header) = parse_uimage_header(&file_data[offset..])
+        && uimage_header.header_crc_valid
+    {
+        let declared_size = uimage_header.header_size + uimage_header.data_size;
+        let available_size = file_data.len() - offset;
+
+        if declared_size > available_size {
+            result.description = format!(
+                "{}, header size: {} bytes, data size: {} bytes, image name: \"{}\"",
+                result.description,
+                uimage_header.header_size,
+                uimage_header.data_size,
+                uimage_header.name
+            );
+            result.extraction_declined = true;
+            result.set_truncated(declared_size, available_size);
+            return Ok(result);
+        }
+    }
+
     Err(SignatureError)
 }
 
@@ -90,6 +136,10 @@ pub struct UImageHeader {
     pub cpu_type: String,
     pub os_type: String,
     pub image_type: String,
+    pub os_id: u8,
+    pub cpu_id: u8,
+    pub image_type_id: u8,
+    pub compression_id: u8,
     pub header_crc_valid: bool,
 }
 
@@ -107,14 +157,14 @@ struct UImageHeaderBytes {
     cpu_type: u8,
     image_type: u8,
     compression_type: u8,
+    name: FixedString<32>,
 }
 
 /// Pase a uImage header
 pub fn parse_uimage_header(uimage_data: &[u8]) -> Result<UImageHeader, StructureError> {
     const UIMAGE_HEADER_SIZE: usize = 64;
-    const UIMAGE_NAME_OFFSET: usize = 32;
 
-    // Parse the first half of the header
+    // Parse the header
     let (uimage_header, _) =
         UImageHeaderBytes::ref_from_prefix(uimage_data).map_err(|_| StructureError)?;
 
@@ -245,7 +295,7 @@ pub fn parse_uimage_header(uimage_data: &[u8]) -> Result<UImageHeader, Structure
 
     Ok(UImageHeader {
         header_size: UIMAGE_HEADER_SIZE,
-        name: get_cstring(&uimage_data[UIMAGE_NAME_OFFSET..]),
+        name: uimage_header.name.get(),
         data_size: uimage_header.data_size.get() as usize,
         data_checksum: uimage_header.data_crc.get(),
         timestamp: uimage_header.creation_timestamp.get() as usize,
@@ -255,10 +305,60 @@ pub fn parse_uimage_header(uimage_data: &[u8]) -> Result<UImageHeader, Structure
         cpu_type: cpu_type.to_string(),
         os_type: os_type.to_string(),
         image_type: image_type.to_string(),
+        os_id: uimage_header.os_type,
+        cpu_id: uimage_header.cpu_type,
+        image_type_id: uimage_header.image_type,
+        compression_id: uimage_header.compression_type,
         header_crc_valid: uimage_header.header_crc == calculate_uimage_header_checksum(crc_data),
     })
 }
 
+/// Returns the offset (relative to the start of the image data) and size of each image in the uImage data.
+///
+/// Multi-file images start with a NULL-terminated list of big endian image sizes, and each image is padded to a
+/// 4-byte boundary; all other image types contain a single image.
+pub fn uimage_sub_images(uimage_header: &UImageHeader, image_data: &[u8]) -> Vec<(usize, usize)> {
+    const MULTI_FILE_IMAGE_TYPE: &str = "Multi-File Image";
+    const SIZE_FIELD_LEN: usize = 4;
+    const IMAGE_ALIGNMENT: usize = 4;
+
+    if uimage_header.image_type != MULTI_FILE_IMAGE_TYPE {
+        return vec![(0, image_data.len())];
+    }
+
+    let image_sizes: Vec<usize> = image_data
+        .chunks_exact(SIZE_FIELD_LEN)
+        .map(|size| u32::from_be_bytes(size.try_into().unwrap()) as usize)
+        .take_while(|size| *size != 0)
+        .collect();
+
+    let mut images: Vec<(usize, usize)> = Vec::new();
+    let mut image_offset = (image_sizes.len() + 1) * SIZE_FIELD_LEN;
+
+    for image_size in image_sizes {
+        if image_offset + image_size > image_data.len() {
+            break;
+        }
+
+        images.push((image_offset, image_size));
+        image_offset += image_size.next_multiple_of(IMAGE_ALIGNMENT);
+    }
+
+    images
+}
+
+/// Returns the internal decompressor for the uImage data, if the data is compressed and there is one
+fn uimage_decompressor(uimage_header: &UImageHeader) -> Option<Extractor> {
+    match uimage_header.compression_type.as_str() {
+        "gzip" => Some(gzip::gzip_extractor()),
+        "bzip2" => Some(bzip2::bzip2_extractor()),
+        "lzma" => Some(lzma::lzma_extractor()),
+        "lz4" => Some(lz4::lz4_extractor()),
+        "zstd" => Some(zstd::zstd_extractor()),
+        _ => None,
+    }
+}
+
 /// uImage checksum calculator
 fn calculate_uimage_header_checksum(hdr: &[u8]) -> u32 {
     const HEADER_CRC_START: usize = 4;
@@ -281,6 +381,8 @@ fn calculate_uimage_header_checksum(hdr: &[u8]) -> u32 {
 ///
 /// match uimage_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -331,8 +433,17 @@ pub fn extract_uimage(
                 result.size = Some(result.size.unwrap() + uimage_header.data_size);
             }
 
-            // If extraction was requested and the data CRC is valid, carve the uImage data out to a file
+            // If extraction was requested and the data CRC is valid, extract the uImage data
             if data_crc_valid && let Some(output_directory) = output_directory {
+                // Compressed data is handed off to the matching decompressor
+                if let Some(ExtractorType::Internal(decompress)) =
+                    uimage_decompressor(&uimage_header).map(|extractor| extractor.utility)
+                    && decompress(image_data, 0, Some(output_directory)).success
+                {
+                    return result;
+                }
+
+                // Otherwise, carve the uImage data out to a file
                 let chroot = Chroot::new(output_directory);
                 let file_base_name = if uimage_header.name.is_empty() {
                     DEFAULT_OUTPUT_FILE_NAME.to_string()
@@ -349,3 +460,65 @@ pub fn extract_uimage(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// gzip compressed Linux kernel image for ARM; see tests/inputs/gen_uimage.sh
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/uimage.bin");
+
+    #[test]
+    fn valid_header_crc() {
+        let header = parse_uimage_header(VALID).unwrap();
+
+        assert!(header.header_crc_valid);
+        assert_eq!(header.os_id, 5);
+        assert_eq!(header.cpu_id, 2);
+        assert_eq!(header.image_type_id, 2);
+        assert_eq!(header.compression_id, 1);
+        assert_eq!(header.compression_type, "gzip");
+        // Image names that fill the whole field are not NULL terminated
+        assert_eq!(header.name, "Linux-6.6.0 for the ARM platform");
+
+        let signature = uimage_parser(VALID, 0).unwrap();
+        assert_eq!(signature.size, VALID.len());
+        assert_eq!(signature.confidence, CONFIDENCE_HIGH);
+        assert!(!signature.extraction_declined);
+
+        // Compressed data is decompressed, rather than just carved
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = extract_uimage(VALID, 0, Some(output_directory.path()));
+        assert!(result.success);
+
+        let kernel: Vec<u8> = (0..64)
+            .flat_map(|n| format!("This is a Linux kernel, honest (line {n})\n").into_bytes())
+            .collect();
+        assert_eq!(
+            std::fs::read(output_directory.path().join("decompressed.bin")).unwrap(),
+            kernel
+        );
+    }
+
+    #[test]
+    fn corrupted_header_crc() {
+        let mut image = VALID.to_vec();
+        image[10] ^= 0xFF;
+
+        let header = parse_uimage_header(&image).unwrap();
+        assert!(!header.header_crc_valid);
+
+        // The image is still reported, but not extracted
+        let signature = uimage_parser(&image, 0).unwrap();
+        assert!(signature.extraction_declined);
+        assert!(signature.description.ends_with("invalid checksum"));
+
+        // A corrupted data CRC excludes the image data from the reported size
+        let mut image = VALID.to_vec();
+        let last = image.len() - 1;
+        image[last] ^= 0xFF;
+
+        let signature = uimage_parser(&image, 0).unwrap();
+        assert_eq!(signature.size, 64);
+    }
+}
@@ -0,0 +1,518 @@
+use crate::extractors;
+use crate::extractors::identify::identify_only_extractor;
+use crate::signatures::{CONFIDENCE_HIGH, CONFIDENCE_MEDIUM, SignatureError, SignatureResult};
+use crate::structures::StructureError;
+use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
+
+/// Human readable description
+pub const DESCRIPTION: &str = "VMware VMDK disk image";
+
+/// VMDK images are expected to start on a sector boundary
+pub const ALIGNMENT: usize = SECTOR_SIZE;
+
+/// Sizes and offsets in VMDK headers and descriptors are specified in 512 byte sectors
+const SECTOR_SIZE: usize = 512;
+
+/// Magic bytes of a sparse extent header; this is the little endian encoding of 0x564D444B ("VMDK")
+const SPARSE_MAGIC: &[u8; 4] = b"KDMV";
+
+/// The first line of a text descriptor
+const DESCRIPTOR_MAGIC: &[u8; 21] = b"# Disk DescriptorFile";
+
+/// VMDK sparse extents start with a binary header; stand-alone descriptors start with a comment line
+pub fn vmdk_magic() -> Vec<Vec<u8>> {
+    vec![SPARSE_MAGIC.to_vec(), DESCRIPTOR_MAGIC.to_vec()]
+}
+
+/// Validates VMDK sparse extent headers and text descriptors
+pub fn vmdk_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
+    // Successful return value
+    let mut result = SignatureResult {
+        offset,
+        description: DESCRIPTION.to_string(),
+        confidence: CONFIDENCE_MEDIUM,
+        ..Default::default()
+    };
+
+    let vmdk_data = file_data.get(offset..).unwrap_or_default();
+
+    if vmdk_data.starts_with(DESCRIPTOR_MAGIC) {
+        let descriptor = parse_vmdk_descriptor(vmdk_data).map_err(|_| SignatureError)?;
+
+        // The extents are stored in separate files, which won't be available to the extractor
+        result.size = descriptor.size;
+        result.preferred_extractor = Some(identify_only_extractor());
+        result.description = format!(
+            "{} descriptor, create type: {}, extents: {}, capacity: {} bytes",
+            result.description,
+            descriptor.create_type,
+            descriptor.extents.len(),
+            descriptor.capacity()
+        );
+
+        return Ok(result);
+    }
+
+    let header = parse_vmdk_sparse_header(vmdk_data).map_err(|_| SignatureError)?;
+
+    result.description = format!(
+        "{} sparse extent, version: {}, capacity: {} bytes, grain size: {} bytes, overhead: {} bytes",
+        result.description, header.version, header.capacity, header.grain_size, header.overhead
+    );
+
+    // Monolithic sparse images embed their descriptor in the extent
+    if header.descriptor_size > 0
+        && let Some(descriptor_data) = vmdk_data
+            .get(header.descriptor_offset..header.descriptor_offset + header.descriptor_size)
+        && let Ok(descriptor) = parse_vmdk_descriptor(descriptor_data)
+    {
+        result.description = format!(
+            "{}, create type: {}",
+            result.description, descriptor.create_type
+        );
+    }
+
+    if header.compressed {
+        result.description = format!("{}, compressed grains", result.description);
+    }
+
+    // Stream optimized images store the grain directory at the end of the image, so its size can't be determined from
+    // the header; otherwise, the grain tables locate every allocated grain
+    if let Some(grain_directory_offset) = header.grain_directory_offset {
+        let extent_size = sparse_extent_size(vmdk_data, &header, grain_directory_offset)
+            .map_err(|_| SignatureError)?;
+
+        if extent_size > vmdk_data.len() {
+            result.set_truncated(extent_size, vmdk_data.len());
+        } else {
+            result.size = extent_size;
+            result.confidence = CONFIDENCE_HIGH;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Describes how to run the qemu-img utility to convert VMDK images to raw disk images
+///
+/// ```
+/// use std::io::ErrorKind;
+/// use std::process::Command;
+/// use binwalk_ng::extractors::ExtractorType;
+/// use binwalk_ng::formats::vmdk::vmdk_extractor;
+///
+/// match vmdk_extractor().utility {
+///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
+///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
+///     ExtractorType::External(cmd) => {
+///         if let Err(e) = Command::new(&cmd).output() {
+///             if e.kind() == ErrorKind::NotFound {
+///                 panic!("External extractor '{}' not found", cmd);
+///             } else {
+///                 panic!("Failed to execute external extractor '{}': {}", cmd, e);
+///             }
+///         }
+///     }
+/// }
+/// ```
+pub fn vmdk_extractor() -> extractors::Extractor {
+    extractors::Extractor {
+        utility: extractors::ExtractorType::External("qemu-img".to_string()),
+        extension: "vmdk".to_string(),
+        arguments: vec![
+            "convert".to_string(),
+            "-f".to_string(), // Input format
+            "vmdk".to_string(),
+            "-O".to_string(), // Output format
+            "raw".to_string(),
+            extractors::SOURCE_FILE_PLACEHOLDER.to_string(),
+            "disk.img".to_string(),
+        ],
+        exit_codes: vec![0],
+        ..Default::default()
+    }
+}
+
+/// Stores info about a VMDK sparse extent header
+#[derive(Debug, Default, Clone)]
+pub struct VMDKSparseHeader {
+    pub version: u32,
+    /// Size of the virtual disk, in bytes
+    pub capacity: usize,
+    /// Size of each grain (allocation unit), in bytes
+    pub grain_size: usize,
+    /// Offset of the embedded descriptor, relative to the start of the extent
+    pub descriptor_offset: usize,
+    /// Size of the embedded descriptor, in bytes; 0 if there is no embedded descriptor
+    pub descriptor_size: usize,
+    /// Offset of the grain directory, relative to the start of the extent; None if it is stored in the footer
+    pub grain_directory_offset: Option<usize>,
+    /// Number of entries in each grain table
+    pub grain_table_entries: usize,
+    /// Size of the metadata that precedes the first grain, in bytes
+    pub overhead: usize,
+    /// True if grains are deflate compressed (stream optimized images)
+    pub compressed: bool,
+}
+
+// https://web.archive.org/web/20120302211605/http://www.vmware.com/support/developer/vddk/vmdk_50_technote.pdf
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct VMDKSparseHeaderBytes {
+    magic: [u8; 4],
+    version: zerocopy::U32<LE>,
+    flags: zerocopy::U32<LE>,
+    capacity: zerocopy::U64<LE>,
+    grain_size: zerocopy::U64<LE>,
+    descriptor_offset: zerocopy::U64<LE>,
+    descriptor_size: zerocopy::U64<LE>,
+    num_gtes_per_gt: zerocopy::U32<LE>,
+    rgd_offset: zerocopy::U64<LE>,
+    gd_offset: zerocopy::U64<LE>,
+    overhead: zerocopy::U64<LE>,
+    unclean_shutdown: u8,
+    single_end_line_char: u8,
+    non_end_line_char: u8,
+    double_end_line_char1: u8,
+    double_end_line_char2: u8,
+    compress_algorithm: zerocopy::U16<LE>,
+}
+
+/// Parses a VMDK sparse extent header
+pub fn parse_vmdk_sparse_header(vmdk_data: &[u8]) -> Result<VMDKSparseHeader, StructureError> {
+    // Header flag bits
+    const FLAG_VALID_NEWLINE_TEST: u32 = 1 << 0;
+    const FLAG_COMPRESSED_GRAINS: u32 = 1 << 16;
+    // The grain directory offset, if it is specified in the footer
+    const GD_AT_END: u64 = 0xFFFFFFFFFFFFFFFF;
+    // Grain tables always have this many entries
+    const GRAIN_TABLE_ENTRIES: u32 = 512;
+    // Grains are a power of two, and at least 4KiB
+    const MIN_GRAIN_SECTORS: u64 = 8;
+    // The only compression algorithm is deflate
+    const COMPRESSION_DEFLATE: u16 = 1;
+    // Latest known header version
+    const MAX_VERSION: u32 = 3;
+
+    let (header, _) =
+        VMDKSparseHeaderBytes::ref_from_prefix(vmdk_data).map_err(|_| StructureError)?;
+
+    let flags = header.flags.get();
+    let grain_sectors = header.grain_size.get();
+    let overhead_sectors = header.overhead.get();
+
+    if header.magic != *SPARSE_MAGIC
+        || !(1..=MAX_VERSION).contains(&header.version.get())
+        || header.num_gtes_per_gt.get() != GRAIN_TABLE_ENTRIES
+        || header.capacity.get() == 0
+        || grain_sectors < MIN_GRAIN_SECTORS
+        || !grain_sectors.is_power_of_two()
+        || overhead_sectors == 0
+    {
+        return Err(StructureError);
+    }
+
+    // These characters are used to detect files corrupted by text mode FTP transfers
+    if (flags & FLAG_VALID_NEWLINE_TEST) != 0
+        && [
+            header.single_end_line_char,
+            header.non_end_line_char,
+            header.double_end_line_char1,
+            header.double_end_line_char2,
+        ] != *b"\n \r\n"
+    {
+        return Err(StructureError);
+    }
+
+    let compressed = (flags & FLAG_COMPRESSED_GRAINS) != 0;
+
+    if compressed && header.compress_algorithm.get() != COMPRESSION_DEFLATE {
+        return Err(StructureError);
+    }
+
+    // The descriptor and grain directory are all metadata, and are located before the first grain
+    let descriptor_end = header
+        .descriptor_offset
+        .get()
+        .checked_add(header.descriptor_size.get())
+        .ok_or(StructureError)?;
+
+    if descriptor_end > overhead_sectors {
+        return Err(StructureError);
+    }
+
+    let grain_directory_offset = match header.gd_offset.get() {
+        GD_AT_END => None,
+        gd_offset if gd_offset > 0 && gd_offset < overhead_sectors => {
+            Some(sectors_to_bytes(gd_offset)?)
+        }
+        _ => return Err(StructureError),
+    };
+
+    Ok(VMDKSparseHeader {
+        version: header.version.get(),
+        capacity: sectors_to_bytes(header.capacity.get())?,
+        grain_size: sectors_to_bytes(grain_sectors)?,
+        descriptor_offset: sectors_to_bytes(header.descriptor_offset.get())?,
+        descriptor_size: sectors_to_bytes(header.descriptor_size.get())?,
+        grain_directory_offset,
+        grain_table_entries: GRAIN_TABLE_ENTRIES as usize,
+        overhead: sectors_to_bytes(overhead_sectors)?,
+        compressed,
+    })
+}
+
+/// Describes one extent in a VMDK descriptor
+#[derive(Debug, Default, Clone)]
+pub struct VMDKExtent {
+    /// One of RW, RDONLY or NOACCESS
+    pub access: String,
+    /// Size of the extent, in bytes
+    pub size: usize,
+    /// Extent type, e.g. SPARSE or FLAT
+    pub extent_type: String,
+    /// Name of the file containing the extent data; ZERO extents have no file
+    pub file_name: Option<String>,
+}
+
+/// Stores info about a VMDK text descriptor
+#[derive(Debug, Default, Clone)]
+pub struct VMDKDescriptor {
+    /// Disk layout, e.g. monolithicSparse or twoGbMaxExtentFlat
+    pub create_type: String,
+    pub extents: Vec<VMDKExtent>,
+    /// Size of the descriptor text, in bytes
+    pub size: usize,
+}
+
+impl VMDKDescriptor {
+    /// Returns the size of the virtual disk, in bytes
+    pub fn capacity(&self) -> usize {
+        self.extents.iter().map(|extent| extent.size).sum()
+    }
+}
+
+/// Parses a VMDK text descriptor; the descriptor ends at the first NULL byte or non-text character
+///
+/// ```
+/// use binwalk_ng::formats::vmdk::parse_vmdk_descriptor;
+///
+/// let descriptor_text = b"# Disk DescriptorFile\nversion=1\ncreateType=\"vmfs\"\n\n# Extent description\nRW 2048 VMFS \"disk-flat.vmdk\"\n\x00\x00";
+///
+/// let descriptor = parse_vmdk_descriptor(descriptor_text).unwrap();
+/// assert_eq!(descriptor.create_type, "vmfs");
+/// assert_eq!(descriptor.extents.len(), 1);
+/// assert_eq!(descriptor.extents[0].file_name.as_deref(), Some("disk-flat.vmdk"));
+/// assert_eq!(descriptor.capacity(), 2048 * 512);
+/// assert_eq!(descriptor.size, descriptor_text.len() - 2);
+/// ```
+pub fn parse_vmdk_descriptor(descriptor_data: &[u8]) -> Result<VMDKDescriptor, StructureError> {
+    // Descriptors are small; don't go looking through huge amounts of text
+    const MAX_DESCRIPTOR_SIZE: usize = 64 * 1024;
+    // Valid extent access modes
+    const EXTENT_ACCESS: [&str; 3] = ["RW", "RDONLY", "NOACCESS"];
+
+    if !descriptor_data.starts_with(DESCRIPTOR_MAGIC) {
+        return Err(StructureError);
+    }
+
+    let text_size = descriptor_data
+        .iter()
+        .take(MAX_DESCRIPTOR_SIZE)
+        .take_while(|&&byte| byte.is_ascii_graphic() || byte.is_ascii_whitespace())
+        .count();
+
+    let text = std::str::from_utf8(&descriptor_data[..text_size]).map_err(|_| StructureError)?;
+
+    let mut descriptor = VMDKDescriptor {
+        size: text_size,
+        ..Default::default()
+    };
+
+    for line in text.lines().map(str::trim) {
+        if let Some(create_type) = line.strip_prefix("createType=") {
+            descriptor.create_type = create_type.trim_matches('"').to_string();
+            continue;
+        }
+
+        // Extent lines look like: RW 4192256 SPARSE "disk-s001.vmdk" [offset]
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        if fields.len() >= 3 && EXTENT_ACCESS.contains(&fields[0]) {
+            let sectors: u64 = fields[1].parse().map_err(|_| StructureError)?;
+
+            let file_name = line
+                .split_once('"')
+                .and_then(|(_, remainder)| remainder.split_once('"'))
+                .map(|(file_name, _)| file_name.to_string());
+
+            descriptor.extents.push(VMDKExtent {
+                access: fields[0].to_string(),
+                size: sectors_to_bytes(sectors)?,
+                extent_type: fields[2].to_string(),
+                file_name,
+            });
+        }
+    }
+
+    if descriptor.create_type.is_empty() || descriptor.extents.is_empty() {
+        return Err(StructureError);
+    }
+
+    Ok(descriptor)
+}
+
+/// Returns the size of a sparse extent: the end of the last grain or grain table, or the end of the metadata,
+/// whichever is greater
+fn sparse_extent_size(
+    vmdk_data: &[u8],
+    header: &VMDKSparseHeader,
+    grain_directory_offset: usize,
+) -> Result<usize, StructureError> {
+    // Grain directory and grain table entries are 32 bit sector offsets
+    const ENTRY_SIZE: usize = std::mem::size_of::<u32>();
+
+    let grain_count = header.capacity.div_ceil(header.grain_size);
+    let grain_table_count = grain_count.div_ceil(header.grain_table_entries);
+    let grain_table_size = header.grain_table_entries * ENTRY_SIZE;
+
+    // The grain directory is part of the metadata that precedes the first grain
+    let grain_directory_end = grain_table_count
+        .checked_mul(ENTRY_SIZE)
+        .and_then(|size| size.checked_add(grain_directory_offset))
+        .ok_or(StructureError)?;
+
+    if grain_directory_end > header.overhead {
+        return Err(StructureError);
+    }
+
+    let mut extent_size = header.overhead;
+
+    // If the grain directory isn't available, the image is truncated
+    let Some(grain_directory) = vmdk_data.get(grain_directory_offset..grain_directory_end) else {
+        return Ok(extent_size);
+    };
+
+    for grain_table_entry in grain_directory.chunks_exact(ENTRY_SIZE) {
+        let grain_table_offset = sector_entry_to_bytes(grain_table_entry);
+
+        // Grain tables are only allocated once one of their grains is written
+        if grain_table_offset == 0 {
+            continue;
+        }
+
+        extent_size = extent_size.max(grain_table_offset + grain_table_size);
+
+        // If the grain table isn't available, the image is truncated
+        let Some(grain_table) =
+            vmdk_data.get(grain_table_offset..grain_table_offset + grain_table_size)
+        else {
+            continue;
+        };
+
+        for grain_entry in grain_table.chunks_exact(ENTRY_SIZE) {
+            let grain_offset = sector_entry_to_bytes(grain_entry);
+
+            // Entries of 0 and 1 are unallocated and zeroed grains, respectively
+            if grain_offset > SECTOR_SIZE {
+                extent_size = extent_size.max(grain_offset + header.grain_size);
+            }
+        }
+    }
+
+    Ok(extent_size)
+}
+
+/// Converts a little endian 32 bit sector offset to a byte offset
+fn sector_entry_to_bytes(entry: &[u8]) -> usize {
+    u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]) as usize * SECTOR_SIZE
+}
+
+/// Converts a size or offset in sectors to bytes
+fn sectors_to_bytes(sectors: u64) -> Result<usize, StructureError> {
+    usize::try_from(sectors)
+        .ok()
+        .and_then(|sectors| sectors.checked_mul(SECTOR_SIZE))
+        .ok_or(StructureError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1MiB monolithic sparse extent with 64KiB grains, two of which are allocated (see tests/inputs/gen_vmdk.sh)
+    static VALID_SPARSE: &[u8] = include_bytes!("../../tests/inputs/vmdk.bin");
+    /// Text descriptor of a two-extent twoGbMaxExtentSparse disk (see tests/inputs/gen_vmdk.sh)
+    static VALID_DESCRIPTOR: &[u8] = include_bytes!("../../tests/inputs/vmdk_descriptor.bin");
+    const GRAIN_SECTORS: usize = 128;
+    const OVERHEAD_SECTORS: usize = 128;
+    const EXTENT_SIZE: usize = (256 + GRAIN_SECTORS) * SECTOR_SIZE;
+
+    #[test]
+    fn sparse_header() {
+        let header = parse_vmdk_sparse_header(VALID_SPARSE).unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!(header.capacity, 2048 * SECTOR_SIZE);
+        assert_eq!(header.grain_size, GRAIN_SECTORS * SECTOR_SIZE);
+        assert_eq!(header.descriptor_offset, SECTOR_SIZE);
+        assert_eq!(header.descriptor_size, 20 * SECTOR_SIZE);
+        assert_eq!(header.grain_directory_offset, Some(26 * SECTOR_SIZE));
+        assert_eq!(header.overhead, OVERHEAD_SECTORS * SECTOR_SIZE);
+        assert!(!header.compressed);
+    }
+
+    #[test]
+    fn embedded_sparse_extent() {
+        let mut file_data = vec![0; 0x400];
+        file_data.extend_from_slice(VALID_SPARSE);
+        file_data.extend([0xFF; 0x400]);
+
+        let signature = vmdk_parser(&file_data, 0x400).unwrap();
+        assert_eq!(signature.offset, 0x400);
+        assert_eq!(signature.size, EXTENT_SIZE);
+        assert!(signature.description.contains("capacity: 1048576 bytes"));
+        assert!(
+            signature
+                .description
+                .contains("create type: monolithicSparse")
+        );
+    }
+
+    #[test]
+    fn truncated_sparse_extent() {
+        let signature = vmdk_parser(&VALID_SPARSE[..200 * SECTOR_SIZE], 0).unwrap();
+        assert_eq!(signature.size, 200 * SECTOR_SIZE);
+        assert_eq!(signature.declared_size, Some(EXTENT_SIZE));
+    }
+
+    #[test]
+    fn corrupted_newline_test() {
+        // Text mode transfers convert "\r\n" to "\n"
+        let mut extent = VALID_SPARSE.to_vec();
+        extent[73..77].copy_from_slice(b"\n \n\n");
+        assert!(parse_vmdk_sparse_header(&extent).is_err());
+    }
+
+    #[test]
+    fn invalid_grain_size() {
+        let mut extent = VALID_SPARSE.to_vec();
+        extent[20..28].copy_from_slice(&100_u64.to_le_bytes());
+        assert!(parse_vmdk_sparse_header(&extent).is_err());
+    }
+
+    #[test]
+    fn descriptor_file() {
+        let mut file_data = VALID_DESCRIPTOR.to_vec();
+        file_data.extend([0; 0x100]);
+
+        let signature = vmdk_parser(&file_data, 0).unwrap();
+        assert_eq!(signature.size, VALID_DESCRIPTOR.len());
+        assert!(
+            signature
+                .description
+                .contains("create type: twoGbMaxExtentSparse, extents: 2")
+        );
+    }
+}
@@ -1,5 +1,4 @@
 use crate::common::{crc32, is_offset_safe};
-use crate::formats::lzma::lzma_decompress;
 use crate::formats::sevenzip::sevenzip_extractor;
 use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
 use crate::structures::StructureError;
@@ -25,55 +24,62 @@ pub fn xz_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Sig
 
     let mut next_offset = offset;
     let mut previous_offset = None;
-    let mut stream_header_count = 0;
-    let available_data = file_data.len() - offset;
+    let mut stream_count = 0;
+    let available_data = file_data.len();
 
     // XZ streams can be concatenated together, need to process them all to determine the size of an XZ file
     while is_offset_safe(available_data, next_offset, previous_offset) {
-        // Parse the next XZ header to validate the header CRC
-        match parse_xz_header(&file_data[next_offset..]) {
-            Err(_) => break,
-            Ok(_) => {
-                // Header is valid
-                stream_header_count += 1;
-
-                // Do an extraction dry-run to make sure the data decompresses correctly
-                let dry_run = lzma_decompress(file_data, next_offset, None);
-
-                // If dry run was a success, update the offset and size fields
-                if dry_run.success
-                    && let Some(size) = dry_run.size
-                {
-                    previous_offset = Some(next_offset);
-                    next_offset += size;
-                    result.size += size;
-                // Else, report that the data is malformed and stop processing XZ streams
-                } else {
-                    // 7z may be able to at least partially extract malformed data streams
-                    result.preferred_extractor = Some(sevenzip_extractor());
-                    result.description = format!(
-                        "{}, valid header with malformed data stream",
-                        result.description
-                    );
-                    break;
+        let xz_data = &file_data[next_offset..];
+
+        // Each stream must start with a valid header
+        if parse_xz_header(xz_data).is_err() {
+            break;
+        }
+
+        match xz_stream_size(xz_data) {
+            Ok(stream_size) => {
+                stream_count += 1;
+
+                // Stream padding between streams is only part of the file if another stream follows it
+                result.size = next_offset + stream_size - offset;
+                previous_offset = Some(next_offset);
+                next_offset += stream_size + xz_stream_padding_size(&xz_data[stream_size..]);
+            }
+            // Else, report that the data is malformed and stop processing XZ streams
+            Err(_) => {
+                // 7z may be able to at least partially extract malformed data streams
+                result.preferred_extractor = Some(sevenzip_extractor());
+                result.description = format!(
+                    "{}, valid header with missing or malformed stream footer",
+                    result.description
+                );
+
+                // The first stream header is valid, but its size is unknown
+                if stream_count == 0 {
+                    return Ok(result);
                 }
+
+                break;
             }
         }
     }
 
-    // Return success if at least one valid XZ stream header was found
-    if stream_header_count > 0 {
-        // Only report the total size if we were able to determine the total size
-        if result.size > 0 {
-            result.description =
-                format!("{}, total size: {} bytes", result.description, result.size);
-        }
+    // Return success if at least one complete XZ stream was found
+    if stream_count > 0 {
+        result.description = format!(
+            "{}, stream count: {}, total size: {} bytes",
+            result.description, stream_count, result.size
+        );
         return Ok(result);
     }
 
     Err(SignatureError)
 }
 
+/// Size of both the XZ stream header and stream footer
+const XZ_HEADER_SIZE: usize = 12;
+const XZ_FOOTER_SIZE: usize = 12;
+
 #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
 #[repr(C, packed)]
 struct XZHeader {
@@ -85,17 +91,175 @@ struct XZHeader {
 
 /// Parse and validate an XZ header, returns the header size
 pub fn parse_xz_header(xz_data: &[u8]) -> Result<usize, StructureError> {
+    parse_xz_stream_flags(xz_data).map(|_| XZ_HEADER_SIZE)
+}
+
+/// Parse and validate an XZ header, returns the stream flags
+fn parse_xz_stream_flags(xz_data: &[u8]) -> Result<u16, StructureError> {
     const XZ_CRC_END: usize = 8;
     const XZ_CRC_START: usize = 6;
-    const XZ_HEADER_SIZE: usize = 12;
 
     let (xz_header, _) = XZHeader::ref_from_prefix(xz_data).map_err(|_| StructureError)?;
 
     if let Some(crc_data) = xz_data.get(XZ_CRC_START..XZ_CRC_END)
         && xz_header.header_crc == crc32(crc_data)
     {
-        return Ok(XZ_HEADER_SIZE);
+        return Ok(xz_header.flags.get());
+    }
+
+    Err(StructureError)
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct XZFooter {
+    footer_crc: zerocopy::U32<LE>,
+    backward_size: zerocopy::U32<LE>,
+    flags: zerocopy::U16<LE>,
+    magic: [u8; 2],
+}
+
+/// Stores info about an XZ stream footer
+#[derive(Debug, Default, Clone)]
+pub struct XZStreamFooter {
+    /// Size of the stream index, which immediately precedes the footer
+    pub index_size: usize,
+    pub flags: u16,
+}
+
+/// Parse and validate an XZ stream footer
+pub fn parse_xz_footer(footer_data: &[u8]) -> Result<XZStreamFooter, StructureError> {
+    const FOOTER_MAGIC: &[u8; 2] = b"YZ";
+    const XZ_CRC_START: usize = 4;
+    const XZ_CRC_END: usize = 10;
+
+    let (xz_footer, _) = XZFooter::ref_from_prefix(footer_data).map_err(|_| StructureError)?;
+
+    // The CRC covers the backward size and stream flags
+    if xz_footer.magic == *FOOTER_MAGIC
+        && let Some(crc_data) = footer_data.get(XZ_CRC_START..XZ_CRC_END)
+        && xz_footer.footer_crc == crc32(crc_data)
+    {
+        return Ok(XZStreamFooter {
+            // Backward size is stored in multiples of 4 bytes, minus 1
+            index_size: (xz_footer.backward_size.get() as usize + 1) * 4,
+            flags: xz_footer.flags.get(),
+        });
     }
 
     Err(StructureError)
 }
+
+/// Locates the footer of the XZ stream at the start of xz_data, returns the total size of the stream
+///
+/// XZ streams are always a multiple of 4 bytes in size, so candidate footers are 4-byte aligned. A candidate
+/// is valid if its CRC is correct, its stream flags match those in the stream header, and its backward size
+/// points to a valid stream index.
+pub fn xz_stream_size(xz_data: &[u8]) -> Result<usize, StructureError> {
+    const INDEX_INDICATOR: u8 = 0;
+    const INDEX_CRC_SIZE: usize = 4;
+
+    let stream_flags = parse_xz_stream_flags(xz_data)?;
+
+    let mut footer_start = XZ_HEADER_SIZE;
+
+    while let Some(footer_data) = xz_data.get(footer_start..footer_start + XZ_FOOTER_SIZE) {
+        if let Ok(footer) = parse_xz_footer(footer_data)
+            && footer.flags == stream_flags
+            && let Some(index_start) = footer_start.checked_sub(footer.index_size)
+            && index_start >= XZ_HEADER_SIZE
+        {
+            let index = &xz_data[index_start..footer_start];
+            let (index_data, index_crc) = index.split_at(index.len() - INDEX_CRC_SIZE);
+
+            if index_data.first() == Some(&INDEX_INDICATOR)
+                && crc32(index_data).to_le_bytes() == index_crc
+            {
+                return Ok(footer_start + XZ_FOOTER_SIZE);
+            }
+        }
+
+        footer_start += 4;
+    }
+
+    Err(StructureError)
+}
+
+/// Returns the size of any stream padding (null bytes, in multiples of 4) at the start of xz_data
+fn xz_stream_padding_size(xz_data: &[u8]) -> usize {
+    let null_bytes = xz_data.iter().take_while(|&&b| b == 0).count();
+    null_bytes - (null_bytes % 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::lzma::lzma_decompress;
+
+    /// A single XZ stream (see tests/inputs/gen_xz.sh).
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/xz.bin");
+
+    /// The data compressed in VALID
+    fn payload() -> Vec<u8> {
+        (0..10000u32)
+            .flat_map(|n| (n % 251).to_le_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn single_stream() {
+        assert_eq!(xz_stream_size(VALID).unwrap(), VALID.len());
+
+        // The footer's backward size locates the stream index
+        let footer = parse_xz_footer(&VALID[VALID.len() - XZ_FOOTER_SIZE..]).unwrap();
+        assert_eq!(VALID[VALID.len() - XZ_FOOTER_SIZE - footer.index_size], 0);
+
+        // Leading and trailing data is not part of the stream
+        let mut file_data = vec![0xAB; 16];
+        file_data.extend(VALID);
+        file_data.extend([0xCD; 100]);
+
+        let signature = xz_parser(&file_data, 16).unwrap();
+        assert_eq!(signature.offset, 16);
+        assert_eq!(signature.size, VALID.len());
+        assert!(signature.preferred_extractor.is_none());
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = lzma_decompress(&file_data, 16, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(VALID.len()));
+        assert_eq!(
+            std::fs::read(output_directory.path().join("decompressed.bin")).unwrap(),
+            payload()
+        );
+    }
+
+    #[test]
+    fn concatenated_streams() {
+        // Streams may be separated by null stream padding, in multiples of 4 bytes
+        let mut file_data = VALID.to_vec();
+        file_data.extend([0; 8]);
+        file_data.extend(VALID);
+        file_data.extend([0; 4]);
+
+        let signature = xz_parser(&file_data, 0).unwrap();
+        assert_eq!(signature.size, VALID.len() + 8 + VALID.len());
+        assert!(signature.description.contains("stream count: 2"));
+    }
+
+    #[test]
+    fn corrupted_footer() {
+        let mut stream = VALID.to_vec();
+
+        // Footer stream flags no longer match the header; CRC is also now invalid
+        let flags_offset = stream.len() - 4;
+        stream[flags_offset] ^= 1;
+
+        assert!(xz_stream_size(&stream).is_err());
+
+        // The stream header is still valid, so the signature is reported, without a size
+        let signature = xz_parser(&stream, 0).unwrap();
+        assert_eq!(signature.size, 0);
+        assert!(signature.preferred_extractor.is_some());
+    }
+}
@@ -31,6 +31,7 @@ pub fn zip_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
         match find_zip_eof(file_data, offset) {
             Ok(zip_info) => {
                 result.size = zip_info.eof - offset;
+                result.trailer_offset = Some(zip_info.eocd_offset);
                 result.description = format!(
                     "{}, version: {}.{}, file count: {}, total size: {} bytes",
                     result.description,
@@ -83,6 +84,7 @@ pub fn zip_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, Si
 
 pub struct ZipEOCDInfo {
     pub eof: usize,
+    pub eocd_offset: usize,
     pub file_count: usize,
 }
 
@@ -105,6 +107,7 @@ pub fn find_zip_eof(file_data: &[u8], offset: usize) -> Result<ZipEOCDInfo, Sign
         {
             return Ok(ZipEOCDInfo {
                 eof: eocd_start + eocd_header.size,
+                eocd_offset: eocd_start,
                 file_count: eocd_header.file_count,
             });
         }
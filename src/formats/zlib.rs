@@ -1,6 +1,6 @@
 use crate::extractors::inflate;
 use crate::extractors::{ExtractionResult, Extractor, ExtractorType};
-use crate::signatures::{CONFIDENCE_HIGH, SignatureError, SignatureResult};
+use crate::signatures::{CONFIDENCE_HIGH, CONFIDENCE_LOW, SignatureError, SignatureResult};
 use std::path::Path;
 
 /// Human readable description
@@ -16,6 +16,9 @@ pub fn zlib_magic() -> Vec<Vec<u8>> {
 }
 
 /// Validate a zlib signature
+///
+/// Zlib data whose deflate stream is valid, but whose trailing Adler-32 checksum is missing or wrong (e.g., a truncated
+/// stream), is reported with low confidence and the reason recorded in SignatureResult.trailer_error.
 pub fn zlib_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
     let mut result = SignatureResult {
         offset,
@@ -24,21 +27,50 @@ pub fn zlib_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, S
         ..Default::default()
     };
 
-    // Decompress the zlib; no output directory specified, dry run only.
-    let decompression_dry_run = zlib_decompress(file_data, offset, None);
+    // Inflate the deflate data that follows the zlib header; no output directory specified, dry run only.
+    let dry_run = inflate::inflate_decompressor(file_data, offset + HEADER_SIZE, None);
 
-    // If the decompression dry run was a success, this signature is almost certianly valid
-    if decompression_dry_run.success
-        && let Some(zlib_file_size) = decompression_dry_run.size
-    {
-        result.size = zlib_file_size;
-        result.description = format!("{}, total size: {} bytes", result.description, result.size);
-        return Ok(result);
+    if !dry_run.success {
+        return Err(SignatureError);
     }
 
-    Err(SignatureError)
+    // The checksum immediately follows the deflate data
+    let checksum_start = offset + HEADER_SIZE + dry_run.size;
+    let checksum_end = checksum_start + CHECKSUM_SIZE;
+
+    match file_data.get(checksum_start..checksum_end) {
+        None => {
+            // Truncated stream; the zlib data ends at EOF
+            result.size = file_data.len() - offset;
+            result.trailer_error = Some("Adler-32 checksum is missing".to_string());
+        }
+        Some(adler32_checksum_bytes) => {
+            result.size = checksum_end - offset;
+
+            let reported_checksum = u32::from_be_bytes(adler32_checksum_bytes.try_into().unwrap());
+
+            if reported_checksum != dry_run.adler32 {
+                result.trailer_error = Some(format!(
+                    "Adler-32 checksum mismatch (expected {:#010X}, found {:#010X})",
+                    dry_run.adler32, reported_checksum
+                ));
+            }
+        }
+    }
+
+    result.description = format!("{}, total size: {} bytes", result.description, result.size);
+
+    if let Some(trailer_error) = &result.trailer_error {
+        result.confidence = CONFIDENCE_LOW;
+        result.description = format!("{}, invalid trailer: {}", result.description, trailer_error);
+    }
+
+    Ok(result)
 }
 
+/// Size of the zlib header that precedes the deflate data stream
+const HEADER_SIZE: usize = 2;
+
 /// Size of the checksum that follows the ZLIB deflate data stream
 pub const CHECKSUM_SIZE: usize = 4;
 
@@ -52,6 +84,8 @@ pub const CHECKSUM_SIZE: usize = 4;
 ///
 /// match zlib_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -77,9 +111,6 @@ pub fn zlib_decompress(
     offset: usize,
     output_directory: Option<&Path>,
 ) -> ExtractionResult {
-    // Size of the zlib header
-    const HEADER_SIZE: usize = 2;
-
     let mut exresult = ExtractionResult::default();
 
     // Do the decompression, ignoring the ZLIB header
@@ -100,6 +131,8 @@ pub fn zlib_decompress(
             if reported_checksum == inflate_result.adler32 {
                 exresult.success = true;
                 exresult.size = Some(HEADER_SIZE + inflate_result.size + CHECKSUM_SIZE);
+                exresult
+                    .set_compression_ratio(inflate_result.size, inflate_result.decompressed_size);
             }
         }
     }
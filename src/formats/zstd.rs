@@ -12,19 +12,20 @@ use zerocopy::{FromBytes, Immutable, KnownLayout, LE, Unaligned};
 /// Human readable description
 pub const DESCRIPTION: &str = "ZSTD compressed data";
 
-/// ZSTD magic bytes
+/// ZSTD magic bytes; also matches skippable frames, which may precede ZSTD frames
 pub fn zstd_magic() -> Vec<Vec<u8>> {
-    vec![b"\x28\xb5\x2f\xfd".to_vec()]
+    let mut magics = vec![b"\x28\xb5\x2f\xfd".to_vec()];
+
+    // Skippable frame magics are 0x184D2A50 - 0x184D2A5F
+    for n in 0..=0x0F {
+        magics.push(vec![0x50 | n, 0x2A, 0x4D, 0x18]);
+    }
+
+    magics
 }
 
 /// Validate a ZSTD signature
 pub fn zstd_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
-    // Size of checksum value at EOF
-    const EOF_CHECKSUM_SIZE: usize = 4;
-
-    // More or less arbitrarily chosen
-    const MIN_BLOCK_COUNT: usize = 2;
-
     let mut result = SignatureResult {
         offset,
         description: DESCRIPTION.to_string(),
@@ -33,93 +34,159 @@ pub fn zstd_parser(file_data: &[u8], offset: usize) -> Result<SignatureResult, S
     };
 
     let available_data = file_data.len();
+    let mut next_frame_start = offset;
+    let mut previous_frame_start = None;
+    let mut frames: Vec<ZSTDFrame> = Vec::new();
+
+    // ZSTD frames and skippable frames can be concatenated together; process them all to determine the total size
+    while is_offset_safe(available_data, next_frame_start, previous_frame_start) {
+        let frame_data = &file_data[next_frame_start..];
+
+        let frame_size = if let Ok(skippable_frame_size) = parse_skippable_frame(frame_data) {
+            skippable_frame_size
+        } else if let Ok(frame) = parse_zstd_frame(frame_data) {
+            let frame_size = frame.size;
+            frames.push(frame);
+            frame_size
+        } else {
+            break;
+        };
 
-    // Parse the ZSTD header; this should be safe as the ZSTD magic bytes wouldn't have matched at this offset if nothing was there...
-    if let Ok(zstd_header) = parse_zstd_header(&file_data[offset..]) {
-        /*
-         * The first block header starts immediately after the ZSTD header, BUT there may be optional header fields present.
-         * Must parse the frame header descriptor bit fields to determine total size of the header.
-         */
-        let mut next_block_header_start = offset + zstd_header.fixed_header_size;
-        let mut previous_block_header_start = None;
-
-        // If single segment flag is not set, then window descriptor byte is present in the header
-        if !zstd_header.single_segment_flag {
-            next_block_header_start += 1;
-        }
+        previous_frame_start = Some(next_frame_start);
+        next_frame_start += frame_size;
+        result.size = next_frame_start - offset;
+    }
 
-        // If the dictionary ID flag is non-zero, its value represents the size of the dictionary ID field; else, this field does not exist
-        next_block_header_start += match zstd_header.dictionary_id_flag {
-            1 => 1,
-            2 => 2,
-            3 => 4,
-            _ => 0,
-        };
+    // Skippable frames on their own are not interesting
+    let first_frame = frames.first().ok_or(SignatureError)?;
 
-        /*
-         * If the frame content flag is 0 and the single segment flag is set, then the frame content header field is 1 byte in length;
-         * else, the frame content flag indicates the size of the grame content header field.
-         */
-        next_block_header_start += match zstd_header.frame_content_flag {
-            0 if zstd_header.single_segment_flag => 1,
-            1 => 2,
-            2 => 4,
-            3 => 8,
-            _ => 0,
-        };
+    if let Some(content_size) = first_frame.header.content_size {
+        result.description = format!(
+            "{}, decompressed size: {} bytes",
+            result.description, content_size
+        );
+    }
 
-        // Keep a count of how many blocks we've processed
-        let mut block_count: usize = 0;
-
-        // We now know where the first block header starts, loop through all the blocks to determine where the ZSTD data ends
-        while is_offset_safe(
-            available_data,
-            next_block_header_start,
-            previous_block_header_start,
-        ) {
-            // Parse the block header
-            match parse_block_header(&file_data[next_block_header_start..]) {
-                Err(_) => {
-                    break;
-                }
+    if let Some(dictionary_id) = first_frame.header.dictionary_id {
+        result.description = format!(
+            "{}, dictionary ID: {:#X}",
+            result.description, dictionary_id
+        );
+    }
 
-                Ok(block_header) => {
-                    // Block header looks valid, increment block counter
-                    block_count += 1;
-
-                    // The next block header should start at the end of this block; note that the reported block size does not include the size of the block header
-                    previous_block_header_start = Some(next_block_header_start);
-                    next_block_header_start += block_header.header_size + block_header.block_size;
-
-                    // Was this the last block?
-                    if block_header.last_block {
-                        // Update the total size, which is the difference between the end of the last block and the start of the ZSTD header
-                        result.size = next_block_header_start - offset;
-
-                        // If a checksum is included at the end of the block stream, add the checksum size to the total size
-                        if zstd_header.content_checksum_present {
-                            result.size += EOF_CHECKSUM_SIZE;
-                        }
-
-                        // Make sure we've processed more than one block; if so, return Ok, else break and return Err below
-                        if block_count >= MIN_BLOCK_COUNT {
-                            result.description = format!(
-                                "{}, total size: {} bytes",
-                                result.description, result.size
-                            );
-                            return Ok(result);
-                        } else {
-                            break;
-                        }
-                    }
-                }
+    if frames.len() > 1 {
+        result.description = format!("{}, frame count: {}", result.description, frames.len());
+    }
+
+    result.description = format!("{}, total size: {} bytes", result.description, result.size);
+
+    Ok(result)
+}
+
+/// Stores info about a ZSTD frame
+#[derive(Debug, Default, Clone)]
+pub struct ZSTDFrame {
+    pub header: ZSTDHeader,
+    pub block_count: usize,
+    /// Total size of the frame, including the frame header and checksum
+    pub size: usize,
+}
+
+/// Parse a ZSTD frame, walking its blocks to determine the size of the frame
+pub fn parse_zstd_frame(zstd_data: &[u8]) -> Result<ZSTDFrame, StructureError> {
+    // Size of checksum value at EOF
+    const EOF_CHECKSUM_SIZE: usize = 4;
+
+    // More or less arbitrarily chosen
+    const MIN_BLOCK_COUNT: usize = 2;
+
+    let header = parse_zstd_header(zstd_data)?;
+
+    let available_data = zstd_data.len();
+    let mut block_count: usize = 0;
+
+    // The first block header starts immediately after the frame header
+    let mut next_block_header_start = header.header_size;
+    let mut previous_block_header_start = None;
+
+    // Loop through all the blocks to determine where the ZSTD frame ends
+    while is_offset_safe(
+        available_data,
+        next_block_header_start,
+        previous_block_header_start,
+    ) {
+        let block_header = parse_block_header(&zstd_data[next_block_header_start..])?;
+
+        // Block header looks valid, increment block counter
+        block_count += 1;
+
+        // The next block header should start at the end of this block; note that the reported block size does not include the size of the block header
+        previous_block_header_start = Some(next_block_header_start);
+        next_block_header_start += block_header.header_size + block_header.block_size;
+
+        if block_header.last_block {
+            let mut frame = ZSTDFrame {
+                header,
+                block_count,
+                size: next_block_header_start,
+            };
+
+            // If a checksum is included at the end of the block stream, add the checksum size to the total size
+            if frame.header.content_checksum_present {
+                frame.size += EOF_CHECKSUM_SIZE;
             }
+
+            let frame_data = zstd_data.get(..frame.size).ok_or(StructureError)?;
+
+            /*
+             * Frames with only a single block of data are more prone to false positives.
+             * But, as a single block can decompress to no more than ZSTD_MAX_BLOCK_SIZE bytes,
+             * they're cheap to validate by decompressing them.
+             */
+            if block_count < MIN_BLOCK_COUNT
+                && zstd::bulk::decompress(frame_data, ZSTD_MAX_BLOCK_SIZE).is_err()
+            {
+                break;
+            }
+
+            return Ok(frame);
+        }
+    }
+
+    Err(StructureError)
+}
+
+/// Parse a skippable frame, returns the total size of the frame
+pub fn parse_skippable_frame(frame_data: &[u8]) -> Result<usize, StructureError> {
+    const SKIPPABLE_MAGIC_MASK: u32 = 0xFFFFFFF0;
+    const SKIPPABLE_MAGIC: u32 = 0x184D2A50;
+
+    let (frame_header, _) =
+        SkippableFrameHeader::ref_from_prefix(frame_data).map_err(|_| StructureError)?;
+
+    if frame_header.magic.get() & SKIPPABLE_MAGIC_MASK == SKIPPABLE_MAGIC {
+        let frame_size =
+            std::mem::size_of::<SkippableFrameHeader>() + frame_header.frame_size.get() as usize;
+
+        // The user data must be present
+        if frame_size <= frame_data.len() {
+            return Ok(frame_size);
         }
     }
 
-    Err(SignatureError)
+    Err(StructureError)
+}
+
+#[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(C, packed)]
+struct SkippableFrameHeader {
+    magic: zerocopy::U32<LE>,
+    frame_size: zerocopy::U32<LE>,
 }
 
+/// Maximum size of a ZSTD block, compressed or decompressed
+const ZSTD_MAX_BLOCK_SIZE: usize = 128 * 1024;
+
 /// Stores info about a ZSTD file header
 #[derive(Debug, Default, Clone)]
 pub struct ZSTDHeader {
@@ -128,6 +195,12 @@ pub struct ZSTDHeader {
     pub content_checksum_present: bool,
     pub single_segment_flag: bool,
     pub frame_content_flag: u8,
+    /// Size of the frame header, including all optional fields
+    pub header_size: usize,
+    /// Not specified for single segment frames, where the window size is the content size
+    pub window_size: Option<u64>,
+    pub dictionary_id: Option<u32>,
+    pub content_size: Option<u64>,
 }
 
 #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
@@ -146,6 +219,7 @@ pub fn parse_zstd_header(zstd_data: &[u8]) -> Result<ZSTDHeader, StructureError>
     const SINGLE_SEGMENT_MASK: u8 = 0b100000;
     const FRAME_CONTENT_MASK: u8 = 0b11000000;
     const FRAME_CONTENT_SHIFT: u8 = 6;
+    const ZSTD_MAGIC: &[u8; 4] = b"\x28\xb5\x2f\xfd";
 
     let mut zstd_info = ZSTDHeader {
         fixed_header_size: std::mem::size_of::<ZstdHeaderBytes>(),
@@ -157,26 +231,102 @@ pub fn parse_zstd_header(zstd_data: &[u8]) -> Result<ZSTDHeader, StructureError>
         ZstdHeaderBytes::ref_from_prefix(zstd_data).map_err(|_| StructureError)?;
 
     // Unused bits should be unused
-    if (zstd_header.frame_header_descriptor & FRAME_UNUSED_BITS_MASK) == 0 {
-        // Indicates if a dictionary ID field is present, and if so, how big it is
-        zstd_info.dictionary_id_flag = zstd_header.frame_header_descriptor & DICTIONARY_ID_MASK;
+    if zstd_header.magic != *ZSTD_MAGIC
+        || (zstd_header.frame_header_descriptor & FRAME_UNUSED_BITS_MASK) != 0
+    {
+        return Err(StructureError);
+    }
 
-        // Indicates if there is a 4-byte checksum present at the end of the compressed block stream
-        zstd_info.content_checksum_present =
-            (zstd_header.frame_header_descriptor & CONTENT_CHECKSUM_MASK) != 0;
+    // Indicates if a dictionary ID field is present, and if so, how big it is
+    zstd_info.dictionary_id_flag = zstd_header.frame_header_descriptor & DICTIONARY_ID_MASK;
 
-        // If this flag is set, then the window descriptor byte is not present
-        zstd_info.single_segment_flag =
-            (zstd_header.frame_header_descriptor & SINGLE_SEGMENT_MASK) != 0;
+    // Indicates if there is a 4-byte checksum present at the end of the compressed block stream
+    zstd_info.content_checksum_present =
+        (zstd_header.frame_header_descriptor & CONTENT_CHECKSUM_MASK) != 0;
 
-        // Indicates if the frame content field is present, and if so, how big it is
-        zstd_info.frame_content_flag =
-            (zstd_header.frame_header_descriptor & FRAME_CONTENT_MASK) >> FRAME_CONTENT_SHIFT;
+    // If this flag is set, then the window descriptor byte is not present
+    zstd_info.single_segment_flag =
+        (zstd_header.frame_header_descriptor & SINGLE_SEGMENT_MASK) != 0;
 
-        return Ok(zstd_info);
+    // Indicates if the frame content field is present, and if so, how big it is
+    zstd_info.frame_content_flag =
+        (zstd_header.frame_header_descriptor & FRAME_CONTENT_MASK) >> FRAME_CONTENT_SHIFT;
+
+    /*
+     * The optional header fields immediately follow the frame header descriptor, in order:
+     * window descriptor, dictionary ID, and frame content size.
+     */
+    let mut next_field_start = zstd_info.fixed_header_size;
+
+    if !zstd_info.single_segment_flag {
+        let window_descriptor = *zstd_data.get(next_field_start).ok_or(StructureError)?;
+        zstd_info.window_size = Some(zstd_window_size(window_descriptor));
+        next_field_start += 1;
     }
 
-    Err(StructureError)
+    // If the dictionary ID flag is non-zero, its value represents the size of the dictionary ID field; else, this field does not exist
+    let dictionary_id_size: usize = match zstd_info.dictionary_id_flag {
+        1 => 1,
+        2 => 2,
+        3 => 4,
+        _ => 0,
+    };
+
+    // A dictionary ID of 0 means that no specific dictionary is required
+    let dictionary_id = zstd_le_field(zstd_data, next_field_start, dictionary_id_size)?;
+    if dictionary_id != 0 {
+        zstd_info.dictionary_id = Some(dictionary_id as u32);
+    }
+    next_field_start += dictionary_id_size;
+
+    /*
+     * If the frame content flag is 0 and the single segment flag is set, then the frame content header field is 1 byte in length;
+     * else, the frame content flag indicates the size of the frame content header field.
+     */
+    let content_size_size: usize = match zstd_info.frame_content_flag {
+        0 if zstd_info.single_segment_flag => 1,
+        1 => 2,
+        2 => 4,
+        3 => 8,
+        _ => 0,
+    };
+
+    if content_size_size > 0 {
+        let content_size = zstd_le_field(zstd_data, next_field_start, content_size_size)?;
+
+        // The 2-byte field is offset by 256
+        zstd_info.content_size = if content_size_size == 2 {
+            Some(content_size + 256)
+        } else {
+            Some(content_size)
+        };
+    }
+    next_field_start += content_size_size;
+
+    zstd_info.header_size = next_field_start;
+
+    Ok(zstd_info)
+}
+
+/// Decodes a window descriptor byte into the window size
+fn zstd_window_size(window_descriptor: u8) -> u64 {
+    const MIN_WINDOW_LOG: u64 = 10;
+
+    let exponent = (window_descriptor >> 3) as u64;
+    let mantissa = (window_descriptor & 0b111) as u64;
+
+    let window_base: u64 = 1 << (MIN_WINDOW_LOG + exponent);
+    window_base + ((window_base / 8) * mantissa)
+}
+
+/// Reads a little endian field of the specified size (0 - 8 bytes)
+fn zstd_le_field(zstd_data: &[u8], offset: usize, size: usize) -> Result<u64, StructureError> {
+    let field_bytes = zstd_data.get(offset..offset + size).ok_or(StructureError)?;
+
+    let mut value_bytes = [0; 8];
+    value_bytes[..size].copy_from_slice(field_bytes);
+
+    Ok(u64::from_le_bytes(value_bytes))
 }
 
 #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
@@ -220,6 +370,9 @@ pub fn parse_block_header(block_data: &[u8]) -> Result<ZSTDBlockHeader, Structur
     block_info.block_type = (info_bits & ZSTD_BLOCK_TYPE_MASK) >> ZSTD_BLOCK_TYPE_SHIFT;
     block_info.block_size = ((info_bits & ZSTD_BLOCK_SIZE_MASK) >> ZSTD_BLOCK_SIZE_SHIFT) as usize;
 
+    // For RLE blocks, this is the decompressed size
+    let raw_block_size = block_info.block_size;
+
     /*
      * An RLE block consists of a single byte of raw block data, which when decompressed must be repeased block_size times.
      * We're not decompressing, just want to know the size of the raw data so we can check the next block header.
@@ -230,8 +383,8 @@ pub fn parse_block_header(block_data: &[u8]) -> Result<ZSTDBlockHeader, Structur
         block_info.block_size = 1;
     }
 
-    // Block type is invalid if set to the reserved block type
-    if block_info.block_type != ZSTD_RESERVED_BLOCK_TYPE {
+    // Block type is invalid if set to the reserved block type, and blocks can't exceed the maximum block size
+    if block_info.block_type != ZSTD_RESERVED_BLOCK_TYPE && raw_block_size <= ZSTD_MAX_BLOCK_SIZE {
         return Ok(block_info);
     }
 
@@ -248,6 +401,8 @@ pub fn parse_block_header(block_data: &[u8]) -> Result<ZSTDBlockHeader, Structur
 ///
 /// match zstd_extractor().utility {
 ///     ExtractorType::None => panic!("Invalid extractor type of None"),
+///     ExtractorType::IdentifyOnly => panic!("Invalid extractor type of IdentifyOnly"),
+///     ExtractorType::Carve(_) => panic!("Invalid extractor type of Carve"),
 ///     ExtractorType::Internal(func) => println!("Internal extractor OK: {:?}", func),
 ///     ExtractorType::External(cmd) => {
 ///         if let Err(e) = Command::new(&cmd).output() {
@@ -296,6 +451,7 @@ fn zstd_decompress(
                     result.success = true;
                     let remaining = decoder.finish();
                     result.size = Some(data.len() - remaining.len());
+                    result.set_compression_ratio(data.len() - remaining.len(), decompressed.len());
                     if let Some(output_directory) = output_directory {
                         let chroot = Chroot::new(output_directory);
                         result.success = chroot.create_file(OUTPUT_FILE_NAME, &decompressed);
@@ -309,3 +465,60 @@ fn zstd_decompress(
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A skippable frame followed by a single block ZSTD frame (see tests/inputs/gen_zstd_skippable.sh).
+    static VALID: &[u8] = include_bytes!("../../tests/inputs/zstd_skippable.bin");
+
+    /// Size of the skippable frame at the start of VALID
+    const SKIPPABLE_FRAME_SIZE: usize = 12;
+
+    #[test]
+    fn small_frame() {
+        let data = b"A small ZSTD frame, which fits in a single block".repeat(4);
+        let frame = &VALID[SKIPPABLE_FRAME_SIZE..];
+
+        assert_eq!(parse_skippable_frame(VALID).unwrap(), SKIPPABLE_FRAME_SIZE);
+
+        let header = parse_zstd_header(frame).unwrap();
+        assert_eq!(header.content_size, Some(data.len() as u64));
+        assert_eq!(header.dictionary_id, None);
+        assert!(header.content_checksum_present);
+
+        // Trailing data is not part of the ZSTD data
+        let mut file_data = VALID.to_vec();
+        file_data.extend([0xFF; 32]);
+
+        let signature = zstd_parser(&file_data, 0).unwrap();
+        assert_eq!(signature.size, VALID.len());
+        assert_eq!(
+            signature.description,
+            format!(
+                "ZSTD compressed data, decompressed size: {} bytes, total size: {} bytes",
+                data.len(),
+                VALID.len()
+            )
+        );
+
+        let output_directory = tempfile::tempdir().unwrap();
+        let result = zstd_decompress(&file_data, 0, Some(output_directory.path()));
+        assert!(result.success);
+        assert_eq!(result.size, Some(VALID.len()));
+        assert_eq!(
+            std::fs::read(output_directory.path().join("decompressed.bin")).unwrap(),
+            data
+        );
+
+        // Skippable frames on their own are not reported
+        assert!(zstd_parser(&VALID[..SKIPPABLE_FRAME_SIZE], 0).is_err());
+
+        // A single block frame with a bad content checksum fails to decompress, so is not reported
+        let mut corrupted = frame.to_vec();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0xFF;
+        assert!(zstd_parser(&corrupted, 0).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! Detection of index tables at the start of "fat" firmware images that bundle sub-images for multiple devices (e.g.,
+//! one per SoC), each table entry giving the offset, size and model name of a sub-image.
+
+use serde::{Deserialize, Serialize};
+
+/// Index tables must have at least this many entries
+const MIN_ENTRIES: usize = 2;
+
+/// Index tables are assumed to have no more than this many entries
+const MAX_ENTRIES: usize = 64;
+
+/// Describes the layout of an index table and its entries. Offset and size fields are 32-bit integers; model names are
+/// NULL-padded ASCII strings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexTableLayout {
+    /// Name of the layout, reported in IndexTable.layout
+    pub name: String,
+    /// File offset of the first table entry
+    pub table_offset: usize,
+    /// Size of each table entry, in bytes
+    pub entry_size: usize,
+    /// Offset of the sub-image offset field within each entry
+    pub offset_field: usize,
+    /// Offset of the sub-image size field within each entry
+    pub size_field: usize,
+    /// Offset of the model name field within each entry
+    pub model_field: usize,
+    /// Size of the model name field, in bytes
+    pub model_size: usize,
+    /// True if the offset and size fields are big endian
+    pub big_endian: bool,
+}
+
+impl IndexTableLayout {
+    /// Reads the 32-bit integer at `field` in an entry
+    fn read_u32(&self, entry: &[u8], field: usize) -> Option<usize> {
+        let bytes: [u8; 4] = entry.get(field..field + 4)?.try_into().ok()?;
+
+        let value = if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        };
+
+        Some(value as usize)
+    }
+
+    /// Parses a table entry; returns None if it is not a plausible entry
+    fn parse_entry(&self, entry: &[u8]) -> Option<IndexEntry> {
+        let model_field = entry.get(self.model_field..self.model_field + self.model_size)?;
+
+        // The model name must be printable, with only NULL padding after it
+        let model_length = model_field
+            .iter()
+            .position(|b| *b == 0)
+            .unwrap_or(model_field.len());
+
+        if model_length == 0
+            || !model_field[..model_length]
+                .iter()
+                .all(|b| b.is_ascii_graphic() || *b == b' ')
+            || model_field[model_length..].iter().any(|b| *b != 0)
+        {
+            return None;
+        }
+
+        Some(IndexEntry {
+            offset: self.read_u32(entry, self.offset_field)?,
+            size: self.read_u32(entry, self.size_field)?,
+            model: String::from_utf8_lossy(&model_field[..model_length]).to_string(),
+        })
+    }
+}
+
+/// Returns the built-in index table layouts: a table at the start of the file with 16 or 32 byte model names either
+/// before or after the offset and size fields, in either endianness.
+pub fn default_layouts() -> Vec<IndexTableLayout> {
+    let mut layouts = Vec::new();
+
+    for big_endian in [false, true] {
+        let endianness = if big_endian { "be" } else { "le" };
+
+        for model_size in [16, 32] {
+            layouts.push(IndexTableLayout {
+                name: format!("offset_size_model{model_size}_{endianness}"),
+                table_offset: 0,
+                entry_size: 8 + model_size,
+                offset_field: 0,
+                size_field: 4,
+                model_field: 8,
+                model_size,
+                big_endian,
+            });
+
+            layouts.push(IndexTableLayout {
+                name: format!("model{model_size}_offset_size_{endianness}"),
+                table_offset: 0,
+                entry_size: model_size + 8,
+                offset_field: model_size,
+                size_field: model_size + 4,
+                model_field: 0,
+                model_size,
+                big_endian,
+            });
+        }
+    }
+
+    layouts
+}
+
+/// A sub-image listed in an index table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// File offset of the sub-image
+    pub offset: usize,
+    /// Size of the sub-image, in bytes
+    pub size: usize,
+    /// Model name of the device the sub-image is for
+    pub model: String,
+}
+
+/// Describes an index table found in the file data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexTable {
+    /// Name of the matching IndexTableLayout
+    pub layout: String,
+    /// File offset of the table
+    pub offset: usize,
+    /// Size of the table, not including any terminating entry
+    pub size: usize,
+    /// The sub-images listed in the table, in table order
+    pub entries: Vec<IndexEntry>,
+}
+
+/// Looks for an index table matching any of the given layouts; if more than one layout matches, the table with the most
+/// entries is returned.
+///
+/// A table is only considered plausible if it has at least two entries with printable model names, all of which point
+/// to non-overlapping sub-images that lie between the end of the table and the end of the file. The table ends at the
+/// first entry that is not plausible (e.g., an all-zero terminating entry).
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::index_table::{default_layouts, find_index_table};
+///
+/// let mut file_data: Vec<u8> = Vec::new();
+///
+/// for (offset, size, model) in [(0x40_u32, 0x10_u32, b"SOC-A"), (0x50, 0x20, b"SOC-B")] {
+///     file_data.extend(offset.to_le_bytes());
+///     file_data.extend(size.to_le_bytes());
+///     file_data.extend(model);
+///     file_data.extend([0; 11]);
+/// }
+/// file_data.resize(0x70, 0);
+///
+/// let table = find_index_table(&file_data, &default_layouts()).unwrap();
+///
+/// assert_eq!(table.layout, "offset_size_model16_le");
+/// assert_eq!(table.entries.len(), 2);
+/// assert_eq!(table.entries[1].offset, 0x50);
+/// assert_eq!(table.entries[1].model, "SOC-B");
+///
+/// // Entries must not point past the end of the file
+/// assert_eq!(find_index_table(&file_data[..0x60], &default_layouts()), None);
+/// ```
+pub fn find_index_table(file_data: &[u8], layouts: &[IndexTableLayout]) -> Option<IndexTable> {
+    layouts
+        .iter()
+        .filter_map(|layout| parse_index_table(file_data, layout))
+        .max_by_key(|table| table.entries.len())
+}
+
+/// Parses and validates an index table with the specified layout
+fn parse_index_table(file_data: &[u8], layout: &IndexTableLayout) -> Option<IndexTable> {
+    if layout.entry_size == 0 {
+        return None;
+    }
+
+    let entries: Vec<IndexEntry> = file_data
+        .get(layout.table_offset..)?
+        .chunks_exact(layout.entry_size)
+        .take(MAX_ENTRIES)
+        .map_while(|entry| layout.parse_entry(entry))
+        .collect();
+
+    if entries.len() < MIN_ENTRIES {
+        return None;
+    }
+
+    let table_end = layout.table_offset + (entries.len() * layout.entry_size);
+
+    // Sub-images must lie between the end of the table and EOF
+    if entries.iter().any(|entry| {
+        entry.size == 0
+            || entry.offset < table_end
+            || entry
+                .offset
+                .checked_add(entry.size)
+                .is_none_or(|entry_end| entry_end > file_data.len())
+    }) {
+        return None;
+    }
+
+    // Sub-images must not overlap
+    let mut sorted_entries: Vec<&IndexEntry> = entries.iter().collect();
+    sorted_entries.sort_by_key(|entry| entry.offset);
+
+    if sorted_entries
+        .windows(2)
+        .any(|pair| pair[0].offset + pair[0].size > pair[1].offset)
+    {
+        return None;
+    }
+
+    Some(IndexTable {
+        layout: layout.name.clone(),
+        offset: layout.table_offset,
+        size: table_end - layout.table_offset,
+        entries,
+    })
+}
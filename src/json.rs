@@ -11,6 +11,7 @@ use crate::display;
 #[cfg(feature = "entropy-plot")]
 use crate::entropy::FileEntropy;
 use binwalk_ng::AnalysisResults;
+use binwalk_ng::profile::SignatureProfile;
 
 const STDOUT: &str = "-";
 const JSON_LIST_START: &str = "[\n";
@@ -21,13 +22,29 @@ const JSON_LIST_SEP: &str = ",\n";
 pub enum JSONType {
     #[cfg(feature = "entropy-plot")]
     Entropy(FileEntropy),
-    Analysis(AnalysisResults),
+    Analysis(Box<AnalysisResults>),
+    Profile(Vec<SignatureProfile>),
 }
 
-#[derive(Debug, Default, Clone)]
+impl JSONType {
+    /// The file these results describe; None for results that span all analyzed files
+    fn file_path(&self) -> Option<&Path> {
+        match self {
+            #[cfg(feature = "entropy-plot")]
+            Self::Entropy(entropy) => Some(&entropy.file),
+            Self::Analysis(analysis) => Some(&analysis.file_path),
+            Self::Profile(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
 pub struct JsonLogger {
     pub json_file: Option<PathBuf>,
     pub json_file_initialized: bool,
+    /// If true, results are held until close() and then written sorted by file path, rather than in completion order
+    pub sorted: bool,
+    pending: Vec<JSONType>,
 }
 
 impl JsonLogger {
@@ -41,11 +58,29 @@ impl JsonLogger {
         new_instance
     }
 
-    pub fn close(&self) {
+    pub fn close(&mut self) {
+        let mut pending = std::mem::take(&mut self.pending);
+        // Results that are not specific to a file go last
+        pending.sort_by(|a, b| {
+            (a.file_path().is_none(), a.file_path()).cmp(&(b.file_path().is_none(), b.file_path()))
+        });
+
+        for results in pending {
+            self.write_results(results);
+        }
+
         self.write_json(JSON_LIST_END);
     }
 
     pub fn log(&mut self, results: JSONType) {
+        if self.sorted {
+            self.pending.push(results);
+        } else {
+            self.write_results(results);
+        }
+    }
+
+    fn write_results(&mut self, results: JSONType) {
         // Convert analysis results to JSON
         match serde_json::to_string_pretty(&results) {
             Err(e) => error!("Failed to convert analysis results to JSON: {e}"),
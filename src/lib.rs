@@ -18,9 +18,20 @@
 //! ```
 mod binwalk_ng;
 pub mod common;
+pub mod encoded;
 pub mod extractors;
 pub mod formats;
+pub mod index_table;
 pub mod magic;
+pub mod magic_file;
+pub mod padding;
+pub mod prelude;
+pub mod profile;
 pub mod signatures;
+pub mod strings;
 pub mod structures;
+pub mod warnings;
 pub use binwalk_ng::{AnalysisResults, Binwalk, BinwalkError};
+pub use extractors::{Chroot, ExtractionResult, Extractor, ExtractorType};
+pub use signatures::{Signature, SignatureResult};
+pub use structures::StructureError;
@@ -10,6 +10,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "gzip".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::gzip::gzip_magic(),
             parser: formats::gzip::gzip_parser,
@@ -21,6 +22,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "deb".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::deb::deb_magic(),
             parser: formats::deb::deb_parser,
@@ -32,6 +34,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "7zip".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::sevenzip::sevenzip_magic(),
             parser: formats::sevenzip::sevenzip_parser,
@@ -43,6 +46,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "xz".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::xz::xz_magic(),
             parser: formats::xz::xz_parser,
@@ -54,6 +58,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "tarball".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::tarball::tarball_magic(),
             parser: formats::tarball::tarball_parser,
@@ -65,6 +70,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "squashfs".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::squashfs::squashfs_magic(),
             parser: formats::squashfs::squashfs_parser,
@@ -76,6 +82,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "dlob".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::dlob::dlob_magic(),
             parser: formats::dlob::dlob_parser,
@@ -87,6 +94,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "lzma".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::lzma::lzma_magic(),
             parser: formats::lzma::lzma_parser,
@@ -94,11 +102,24 @@ pub fn patterns() -> Vec<signatures::Signature> {
             //extractor: Some(formats::sevenzip::sevenzip_extractor()),
             extractor: Some(formats::lzma::lzma_extractor()),
         },
+        // lzip
+        signatures::Signature {
+            name: "lzip".to_string(),
+            short: false,
+            magic_offset: 0,
+            magic_alignment: None,
+            always_display: false,
+            magic: formats::lzip::lzip_magic(),
+            parser: formats::lzip::lzip_parser,
+            description: formats::lzip::DESCRIPTION.to_string(),
+            extractor: Some(formats::lzip::lzip_extractor()),
+        },
         // Fritz!Box EVA kernel image (TI record format, LZMA-compressed)
         signatures::Signature {
             name: "eva".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::eva::eva_magic(),
             parser: formats::eva::eva_parser,
@@ -110,6 +131,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "bmp".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::bmp::bmp_magic(),
             parser: formats::bmp::bmp_parser,
@@ -121,6 +143,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "bzip2".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::bzip2::bzip2_magic(),
             parser: formats::bzip2::bzip2_parser,
@@ -132,6 +155,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "uimage".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::uimage::uimage_magic(),
             parser: formats::uimage::uimage_parser,
@@ -143,6 +167,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "packimg".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::packimg::packimg_magic(),
             parser: formats::packimg::packimg_parser,
@@ -154,6 +179,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "crc32".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::hashes::crc32_magic(),
             parser: formats::hashes::crc32_parser,
@@ -165,6 +191,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "sha256".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::hashes::sha256_magic(),
             parser: formats::hashes::sha256_parser,
@@ -176,17 +203,19 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "cpio".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::cpio::cpio_magic(),
             parser: formats::cpio::cpio_parser,
             description: formats::cpio::DESCRIPTION.to_string(),
-            extractor: Some(formats::sevenzip::sevenzip_extractor()),
+            extractor: Some(formats::cpio::cpio_extractor()),
         },
         // iso9660 primary volume
         signatures::Signature {
             name: "iso9660".to_string(),
             short: false,
-            magic_offset: 0,
+            magic_offset: formats::iso9660::MAGIC_OFFSET,
+            magic_alignment: None,
             always_display: false,
             magic: formats::iso9660::iso_magic(),
             parser: formats::iso9660::iso_parser,
@@ -198,6 +227,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "linux_kernel".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::linux::linux_kernel_version_magic(),
             parser: formats::linux::linux_kernel_version_parser,
@@ -209,6 +239,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "linux_boot_image".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::linux::linux_boot_image_magic(),
             parser: formats::linux::linux_boot_image_parser,
@@ -220,6 +251,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "linux_arm_zimage".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::linux::linux_arm_zimage_magic(),
             parser: formats::linux::linux_arm_zimage_parser,
@@ -231,6 +263,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "zstd".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::zstd::zstd_magic(),
             parser: formats::zstd::zstd_parser,
@@ -242,6 +275,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "zip".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::zip::zip_magic(),
             parser: formats::zip::zip_parser,
@@ -253,6 +287,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "pchrom".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::pchrom::pch_rom_magic(),
             parser: formats::pchrom::pch_rom_parser,
@@ -264,6 +299,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "uefi_pi_volume".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::uefi::uefi_volume_magic(),
             parser: formats::uefi::uefi_volume_parser,
@@ -275,6 +311,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "uefi_capsule".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::uefi::uefi_capsule_magic(),
             parser: formats::uefi::uefi_capsule_parser,
@@ -286,28 +323,55 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "pdf".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::pdf::pdf_magic(),
             parser: formats::pdf::pdf_parser,
             description: formats::pdf::DESCRIPTION.to_string(),
-            extractor: None,
+            extractor: Some(formats::pdf::pdf_extractor()),
         },
         // ELF
         signatures::Signature {
             name: "elf".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::elf::elf_magic(),
             parser: formats::elf::elf_parser,
             description: formats::elf::DESCRIPTION.to_string(),
             extractor: None,
         },
+        // Mach-O
+        signatures::Signature {
+            name: "macho".to_string(),
+            short: false,
+            magic_offset: 0,
+            magic_alignment: None,
+            always_display: false,
+            magic: formats::macho::macho_magic(),
+            parser: formats::macho::macho_parser,
+            description: formats::macho::DESCRIPTION.to_string(),
+            extractor: None,
+        },
+        // Mach-O universal binary
+        signatures::Signature {
+            name: "macho_fat".to_string(),
+            short: false,
+            magic_offset: 0,
+            magic_alignment: None,
+            always_display: false,
+            magic: formats::macho::macho_fat_magic(),
+            parser: formats::macho::macho_fat_parser,
+            description: formats::macho::FAT_DESCRIPTION.to_string(),
+            extractor: Some(formats::macho::macho_fat_extractor()),
+        },
         // CramFS
         signatures::Signature {
             name: "cramfs".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::cramfs::cramfs_magic(),
             parser: formats::cramfs::cramfs_parser,
@@ -320,6 +384,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "qnx_ifs".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::qnx::qnx_ifs_magic(),
             parser: formats::qnx::qnx_ifs_parser,
@@ -331,6 +396,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "romfs".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::romfs::romfs_magic(),
             parser: formats::romfs::romfs_parser,
@@ -341,7 +407,8 @@ pub fn patterns() -> Vec<signatures::Signature> {
         signatures::Signature {
             name: "ext".to_string(),
             short: false,
-            magic_offset: 0,
+            magic_offset: formats::ext::MAGIC_OFFSET,
+            magic_alignment: None,
             always_display: false,
             magic: formats::ext::ext_magic(),
             parser: formats::ext::ext_parser,
@@ -353,6 +420,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "cab".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::cab::cab_magic(),
             parser: formats::cab::cab_parser,
@@ -364,6 +432,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "jffs2".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::jffs2::jffs2_magic(),
             parser: formats::jffs2::jffs2_parser,
@@ -375,6 +444,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "yaffs".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::yaffs::yaffs_magic(),
             parser: formats::yaffs::yaffs_parser,
@@ -386,6 +456,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "lz4".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::lz4::lz4_magic(),
             parser: formats::lz4::lz4_parser,
@@ -397,6 +468,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "lzop".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::lzop::lzop_magic(),
             parser: formats::lzop::lzop_parser,
@@ -408,11 +480,12 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "pe".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::pe::pe_magic(),
             parser: formats::pe::pe_parser,
             description: formats::pe::DESCRIPTION.to_string(),
-            extractor: None,
+            extractor: Some(formats::pe::pe_extractor()),
         },
         // zlib
         signatures::Signature {
@@ -420,6 +493,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             // The magic bytes for this signature are only 2 bytes, only match on the beginning of a file
             short: true,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::zlib::zlib_magic(),
             parser: formats::zlib::zlib_parser,
@@ -432,6 +506,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             // The magic bytes for this signature are only 2 bytes, only match on the beginning of a file
             short: true,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::gpg::gpg_signed_magic(),
             parser: formats::gpg::gpg_signed_parser,
@@ -443,6 +518,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "pem_certificate".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::pem::pem_certificate_magic(),
             parser: formats::pem::pem_parser,
@@ -454,6 +530,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "pem_public_key".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::pem::pem_public_key_magic(),
             parser: formats::pem::pem_parser,
@@ -465,6 +542,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "pem_private_key".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::pem::pem_private_key_magic(),
             parser: formats::pem::pem_parser,
@@ -476,6 +554,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "chk".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::chk::chk_magic(),
             parser: formats::chk::chk_parser,
@@ -487,6 +566,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "trx".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::trx::trx_magic(),
             parser: formats::trx::trx_parser,
@@ -498,6 +578,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "srecord".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::srec::srec_magic(),
             parser: formats::srec::srec_parser,
@@ -509,17 +590,31 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "srecord_generic".to_string(),
             short: true,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::srec::srec_short_magic(),
             parser: formats::srec::srec_parser,
             description: formats::srec::SREC_SHORT_DESCRIPTION.to_string(),
             extractor: Some(formats::srec::srec_extractor()),
         },
+        // Intel HEX
+        signatures::Signature {
+            name: "ihex".to_string(),
+            short: false,
+            magic_offset: 0,
+            magic_alignment: None,
+            always_display: false,
+            magic: formats::ihex::ihex_magic(),
+            parser: formats::ihex::ihex_parser,
+            description: formats::ihex::DESCRIPTION.to_string(),
+            extractor: Some(formats::ihex::ihex_extractor()),
+        },
         // Android sparse
         signatures::Signature {
             name: "android_sparse".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::androidsparse::android_sparse_magic(),
             parser: formats::androidsparse::android_sparse_parser,
@@ -531,6 +626,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "dtb".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::dtb::dtb_magic(),
             parser: formats::dtb::dtb_parser,
@@ -542,6 +638,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "ubi".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::ubi::ubi_magic(),
             parser: formats::ubi::ubi_parser,
@@ -553,6 +650,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "ubifs".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::ubi::ubifs_magic(),
             parser: formats::ubi::ubifs_parser,
@@ -564,6 +662,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "cfe".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::cfe::cfe_magic(),
             parser: formats::cfe::cfe_parser,
@@ -575,6 +674,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "seama".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::seama::seama_magic(),
             parser: formats::seama::seama_parser,
@@ -586,17 +686,19 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "compressd".to_string(),
             short: true,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::compressd::compressd_magic(),
             parser: formats::compressd::compressd_parser,
             description: formats::compressd::DESCRIPTION.to_string(),
-            extractor: Some(formats::sevenzip::sevenzip_extractor()),
+            extractor: Some(formats::compressd::compressd_extractor()),
         },
         // rar archive
         signatures::Signature {
             name: "rar".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::rar::rar_magic(),
             parser: formats::rar::rar_parser,
@@ -608,6 +710,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "png".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::png::png_magic(),
             parser: formats::png::png_parser,
@@ -619,6 +722,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "jpeg".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::jpeg::jpeg_magic(),
             parser: formats::jpeg::jpeg_parser,
@@ -630,6 +734,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "arcadyan".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::arcadyan::obfuscated_lzma_magic(),
             parser: formats::arcadyan::obfuscated_lzma_parser,
@@ -641,6 +746,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "copyright".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::copyright::copyright_magic(),
             parser: formats::copyright::copyright_parser,
@@ -652,6 +758,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "wind_kernel".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::vxworks::wind_kernel_magic(),
             parser: formats::vxworks::wind_kernel_parser,
@@ -663,6 +770,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "vxworks_symtab".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::vxworks::symbol_table_magic(),
             parser: formats::vxworks::symbol_table_parser,
@@ -674,6 +782,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "ecos".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::ecos::exception_handler_magic(),
             parser: formats::ecos::exception_handler_parser,
@@ -685,6 +794,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "dmg".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::dmg::dmg_magic(),
             parser: formats::dmg::dmg_parser,
@@ -696,6 +806,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "riff".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::riff::riff_magic(),
             parser: formats::riff::riff_parser,
@@ -707,6 +818,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "openssl".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::openssl::openssl_crypt_magic(),
             parser: formats::openssl::openssl_crypt_parser,
@@ -718,6 +830,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "lzfse".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::lzfse::lzfse_magic(),
             parser: formats::lzfse::lzfse_parser,
@@ -729,6 +842,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "mbr".to_string(),
             short: true,
             magic_offset: formats::mbr::MAGIC_OFFSET,
+            magic_alignment: None,
             always_display: true,
             magic: formats::mbr::mbr_magic(),
             parser: formats::mbr::mbr_parser,
@@ -740,6 +854,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "tplink".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::tplink::tplink_magic(),
             parser: formats::tplink::tplink_parser,
@@ -751,6 +866,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "pjl".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::pjl::pjl_magic(),
             parser: formats::pjl::pjl_parser,
@@ -762,6 +878,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "jboot_arm".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::jboot::jboot_arm_magic(),
             parser: formats::jboot::jboot_arm_parser,
@@ -773,6 +890,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "jboot_stag".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::jboot::jboot_stag_magic(),
             parser: formats::jboot::jboot_stag_parser,
@@ -784,6 +902,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "jboot_sch2".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::jboot::jboot_sch2_magic(),
             parser: formats::jboot::jboot_sch2_parser,
@@ -795,6 +914,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "pcapng".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::pcap::pcapng_magic(),
             parser: formats::pcap::pcapng_parser,
@@ -806,6 +926,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "rsa".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::rsa::rsa_magic(),
             parser: formats::rsa::rsa_parser,
@@ -817,6 +938,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "gif".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::gif::gif_magic(),
             parser: formats::gif::gif_parser,
@@ -828,6 +950,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "svg".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::svg::svg_magic(),
             parser: formats::svg::svg_parser,
@@ -839,6 +962,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "linux_arm64_boot_image".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::linux::linux_arm64_boot_image_magic(),
             parser: formats::linux::linux_arm64_boot_image_parser,
@@ -850,28 +974,43 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "fat".to_string(),
             short: true,
             magic_offset: formats::fat::MAGIC_OFFSET,
+            magic_alignment: None,
             always_display: false,
             magic: formats::fat::fat_magic(),
             parser: formats::fat::fat_parser,
             description: formats::fat::DESCRIPTION.to_string(),
-            extractor: Some(extractors::tsk::tsk_extractor()),
+            extractor: Some(formats::fat::fat_extractor()),
         },
         // EFI GPT
         signatures::Signature {
             name: "efigpt".to_string(),
             short: false,
-            magic_offset: 0,
+            magic_offset: formats::efigpt::MAGIC_OFFSET,
+            magic_alignment: None,
             always_display: false,
             magic: formats::efigpt::efigpt_magic(),
             parser: formats::efigpt::efigpt_parser,
             description: formats::efigpt::DESCRIPTION.to_string(),
-            extractor: Some(formats::sevenzip::sevenzip_extractor()),
+            extractor: Some(formats::efigpt::efigpt_extractor()),
+        },
+        // EFI GPT, 4096 byte sectors
+        signatures::Signature {
+            name: "efigpt_4k".to_string(),
+            short: false,
+            magic_offset: formats::efigpt::MAGIC_OFFSET_4K,
+            magic_alignment: None,
+            always_display: false,
+            magic: formats::efigpt::efigpt_4k_magic(),
+            parser: formats::efigpt::efigpt_4k_parser,
+            description: formats::efigpt::DESCRIPTION.to_string(),
+            extractor: Some(formats::efigpt::efigpt_extractor()),
         },
         // RTK firmware header
         signatures::Signature {
             name: "rtk".to_string(),
             short: true,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::rtk::rtk_magic(),
             parser: formats::rtk::rtk_parser,
@@ -883,6 +1022,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "aes_sbox".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::aes::aes_sbox_magic(),
             parser: formats::aes::aes_sbox_parser,
@@ -894,6 +1034,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "aes_forward_table".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::aes::aes_forward_table_magic(),
             parser: formats::aes::aes_forward_table_parser,
@@ -905,6 +1046,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "aes_reverse_table".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::aes::aes_reverse_table_magic(),
             parser: formats::aes::aes_reverse_table_parser,
@@ -916,6 +1058,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "aes_rcon".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::aes::aes_rcon_magic(),
             parser: formats::aes::aes_rcon_parser,
@@ -927,6 +1070,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "aes_acceleration_table".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::aes::aes_acceleration_table_magic(),
             parser: formats::aes::aes_acceleration_table_parser,
@@ -938,17 +1082,19 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "luks".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::luks::luks_magic(),
             parser: formats::luks::luks_parser,
             description: formats::luks::DESCRIPTION.to_string(),
-            extractor: None,
+            extractor: Some(extractors::identify::identify_only_extractor()),
         },
         // TP-Link RTOS
         signatures::Signature {
             name: "tplink_rtos".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::tplink::tplink_rtos_magic(),
             parser: formats::tplink::tplink_rtos_parser,
@@ -960,6 +1106,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "binhdr".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::binhdr::bin_hdr_magic(),
             parser: formats::binhdr::bin_hdr_parser,
@@ -971,6 +1118,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "autel".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::autel::autel_magic(),
             parser: formats::autel::autel_parser,
@@ -982,6 +1130,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "ntfs".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::ntfs::ntfs_magic(),
             parser: formats::ntfs::ntfs_parser,
@@ -993,6 +1142,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "apfs".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::apfs::apfs_magic(),
             parser: formats::apfs::apfs_parser,
@@ -1004,6 +1154,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "btrfs".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::btrfs::btrfs_magic(),
             parser: formats::btrfs::btrfs_parser,
@@ -1015,6 +1166,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "wince".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::wince::wince_magic(),
             parser: formats::wince::wince_parser,
@@ -1026,6 +1178,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "dahua_zip".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::dahua_zip::dahua_zip_magic(),
             parser: formats::dahua_zip::dahua_zip_parser,
@@ -1037,6 +1190,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "mh01".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::mh01::mh01_magic(),
             parser: formats::mh01::mh01_parser,
@@ -1048,6 +1202,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "csman".to_string(),
             short: true,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::csman::csman_magic(),
             parser: formats::csman::csman_parser,
@@ -1059,6 +1214,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "dxbc".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::dxbc::dxbc_magic(),
             parser: formats::dxbc::dxbc_parser,
@@ -1070,6 +1226,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "dlink_tlv".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::dlink_tlv::dlink_tlv_magic(),
             parser: formats::dlink_tlv::dlink_tlv_parser,
@@ -1081,6 +1238,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "dlink_fw".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::dlink_fw::dlink_fw_magic(),
             parser: formats::dlink_fw::dlink_fw_parser,
@@ -1092,6 +1250,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "dlke".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::dlke::dlke_magic(),
             parser: formats::dlke::dlke_parser,
@@ -1103,6 +1262,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "shrs".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::shrs::shrs_magic(),
             parser: formats::shrs::shrs_parser,
@@ -1114,6 +1274,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "pkcs_der_hash".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::pkcs_der::der_hash_magic(),
             parser: formats::pkcs_der::der_hash_parser,
@@ -1125,6 +1286,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "logfs".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::logfs::logfs_magic(),
             parser: formats::logfs::logfs_parser,
@@ -1136,6 +1298,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "encrpted_img".to_string(),
             short: true,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::encrpted_img::encrpted_img_magic(),
             parser: formats::encrpted_img::encrpted_img_parser,
@@ -1147,28 +1310,43 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "android_bootimg".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::android_bootimg::android_bootimg_magic(),
             parser: formats::android_bootimg::android_bootimg_parser,
             description: formats::android_bootimg::DESCRIPTION.to_string(),
-            extractor: None,
+            extractor: Some(formats::android_bootimg::android_bootimg_extractor()),
         },
         // uboot
         signatures::Signature {
             name: "uboot".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::uboot::uboot_magic(),
             parser: formats::uboot::uboot_parser,
             description: formats::uboot::DESCRIPTION.to_string(),
             extractor: None,
         },
+        // uboot environment
+        signatures::Signature {
+            name: "uboot_env".to_string(),
+            short: false,
+            magic_offset: 0,
+            magic_alignment: None,
+            always_display: false,
+            magic: formats::uboot_env::uboot_env_magic(),
+            parser: formats::uboot_env::uboot_env_parser,
+            description: formats::uboot_env::DESCRIPTION.to_string(),
+            extractor: Some(formats::uboot_env::uboot_env_extractor()),
+        },
         // dms firmware
         signatures::Signature {
             name: "dms".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::dms::dms_magic(),
             parser: formats::dms::dms_parser,
@@ -1180,6 +1358,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "dkbs".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::dkbs::dkbs_magic(),
             parser: formats::dkbs::dkbs_parser,
@@ -1191,6 +1370,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "encfw".to_string(),
             short: true,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::encfw::encfw_magic(),
             parser: formats::encfw::encfw_parser,
@@ -1202,6 +1382,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "matter_ota".to_string(),
             short: true,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::matter_ota::matter_ota_magic(),
             parser: formats::matter_ota::matter_ota_parser,
@@ -1213,28 +1394,31 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "dpapi".to_string(),
             short: true,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::dpapi::dpapi_magic(),
             parser: formats::dpapi::dpapi_parser,
             description: formats::dpapi::DESCRIPTION.to_string(),
-            extractor: None,
+            extractor: Some(extractors::identify::identify_only_extractor()),
         },
         // QEMU QCOW image
         signatures::Signature {
             name: "qcow".to_string(),
             short: true,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: true,
             magic: formats::qcow::qcow_magic(),
             parser: formats::qcow::qcow_parser,
             description: formats::qcow::DESCRIPTION.to_string(),
-            extractor: None,
+            extractor: Some(formats::qcow::qcow_extractor()),
         },
         // ARJ archive
         signatures::Signature {
             name: "arj".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::arj::arj_magic(),
             parser: formats::arj::arj_parser,
@@ -1246,6 +1430,7 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "md5".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::hashes::md5_magic(),
             parser: formats::hashes::md5_parser,
@@ -1257,12 +1442,49 @@ pub fn patterns() -> Vec<signatures::Signature> {
             name: "program_store".to_string(),
             short: false,
             magic_offset: 0,
+            magic_alignment: None,
             always_display: false,
             magic: formats::program_store::program_store_magic(),
             parser: formats::program_store::program_store_parser,
             description: formats::program_store::DESCRIPTION.to_string(),
             extractor: Some(formats::program_store::program_store_extractor()),
         },
+        // SQLite database
+        signatures::Signature {
+            name: "sqlite".to_string(),
+            short: false,
+            magic_offset: 0,
+            magic_alignment: None,
+            always_display: false,
+            magic: formats::sqlite::sqlite_magic(),
+            parser: formats::sqlite::sqlite_parser,
+            description: formats::sqlite::DESCRIPTION.to_string(),
+            extractor: Some(formats::sqlite::sqlite_extractor()),
+        },
+        // OLE2 compound file
+        signatures::Signature {
+            name: "olecf".to_string(),
+            short: false,
+            magic_offset: 0,
+            magic_alignment: None,
+            always_display: false,
+            magic: formats::olecf::olecf_magic(),
+            parser: formats::olecf::olecf_parser,
+            description: formats::olecf::DESCRIPTION.to_string(),
+            extractor: Some(formats::olecf::olecf_extractor()),
+        },
+        // VMware VMDK disk image
+        signatures::Signature {
+            name: "vmdk".to_string(),
+            short: false,
+            magic_offset: 0,
+            magic_alignment: Some(formats::vmdk::ALIGNMENT),
+            always_display: false,
+            magic: formats::vmdk::vmdk_magic(),
+            parser: formats::vmdk::vmdk_parser,
+            description: formats::vmdk::DESCRIPTION.to_string(),
+            extractor: Some(formats::vmdk::vmdk_extractor()),
+        },
     ];
 
     binary_signatures
@@ -0,0 +1,219 @@
+//! Loading of user-defined signatures from a JSON magic file, so that new file types can be identified (and extracted
+//! with an external utility) without recompiling.
+//!
+//! A magic file is a JSON list of signature definitions:
+//!
+//! ```json
+//! [
+//!     {
+//!         "name": "foobar",
+//!         "description": "FooBar firmware header",
+//!         "magic": "46 4F 4F 42",
+//!         "offset": 0,
+//!         "endianness": "big",
+//!         "category": "firmware",
+//!         "extractor": { "command": "unfoobar", "arguments": ["-x", "%e"], "extension": "bin", "exit_codes": [0] }
+//!     }
+//! ]
+//! ```
+//!
+//! Only `name`, `description` and `magic` are required:
+//!
+//! - `magic` is a hex string; whitespace and a leading `0x` are ignored
+//! - `offset`, if set, is the only file offset at which the magic bytes are matched; otherwise they are searched for
+//!   throughout the file
+//! - `endianness` is one of `big` (the default; the magic bytes are matched as written), `little` (the magic bytes are
+//!   byte-swapped) or `both`
+//! - `category` is appended to the description, for display
+//! - `extractor` specifies an external extraction utility; `arguments` defaults to `["%e"]`, the carved file name
+
+use crate::BinwalkError;
+use crate::extractors::{Extractor, ExtractorType, SOURCE_FILE_PLACEHOLDER};
+use crate::magic;
+use crate::signatures::{Signature, SignatureError, SignatureResult};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Byte order of a user-defined magic value
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Endianness {
+    #[default]
+    Big,
+    Little,
+    Both,
+}
+
+/// External extractor definition, as it appears in a magic file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExtractorDefinition {
+    command: String,
+    #[serde(default = "default_arguments")]
+    arguments: Vec<String>,
+    #[serde(default)]
+    extension: String,
+    #[serde(default)]
+    exit_codes: Vec<i32>,
+}
+
+/// Signature definition, as it appears in a magic file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SignatureDefinition {
+    name: String,
+    description: String,
+    magic: String,
+    offset: Option<usize>,
+    #[serde(default)]
+    endianness: Endianness,
+    category: Option<String>,
+    extractor: Option<ExtractorDefinition>,
+}
+
+/// By default, external extractors are just passed the path to the carved file
+fn default_arguments() -> Vec<String> {
+    vec![SOURCE_FILE_PLACEHOLDER.to_string()]
+}
+
+/// Parser for all user-defined signatures, which are validated by their magic bytes alone.
+///
+/// Results are reported with low confidence and, as no size is known, extend to the next signature or EOF.
+/// The description is filled in from the signature definition.
+pub fn magic_file_parser(
+    _file_data: &[u8],
+    offset: usize,
+) -> Result<SignatureResult, SignatureError> {
+    Ok(SignatureResult {
+        offset,
+        ..Default::default()
+    })
+}
+
+/// Loads the user-defined signatures in a JSON magic file; see the module documentation for the file format.
+///
+/// The returned signatures can be passed to `Binwalk::configure`. Signature names must not contain whitespace, and
+/// must not be the same as any built-in or previously defined signature name.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::magic_file::load_magic_file;
+///
+/// # let temp_dir = tempfile::tempdir().unwrap();
+/// # let magic_file = temp_dir.path().join("magic.json");
+/// std::fs::write(&magic_file, r#"[
+///     {"name": "foobar", "description": "FooBar image", "magic": "0x464F4F42", "endianness": "both"}
+/// ]"#).unwrap();
+///
+/// let signatures = load_magic_file(&magic_file).unwrap();
+///
+/// assert_eq!(signatures.len(), 1);
+/// assert_eq!(signatures[0].magic, vec![b"FOOB".to_vec(), b"BOOF".to_vec()]);
+/// ```
+pub fn load_magic_file(path: impl AsRef<Path>) -> Result<Vec<Signature>, BinwalkError> {
+    let path = path.as_ref();
+
+    let file_contents = std::fs::read_to_string(path).map_err(|e| {
+        BinwalkError::new(&format!(
+            "Failed to read magic file '{}': {e}",
+            path.display()
+        ))
+    })?;
+
+    let definitions: Vec<SignatureDefinition> = serde_json::from_str(&file_contents)
+        .map_err(|e| BinwalkError::new(&format!("Invalid magic file '{}': {e}", path.display())))?;
+
+    let mut signature_names: HashSet<String> = magic::patterns()
+        .into_iter()
+        .map(|signature| signature.name.to_lowercase())
+        .collect();
+
+    let mut signatures: Vec<Signature> = Vec::new();
+
+    for definition in definitions {
+        let signature = signature_from_definition(definition).map_err(|e| {
+            BinwalkError::new(&format!("Invalid magic file '{}': {e}", path.display()))
+        })?;
+
+        // Signature names are used as lookup keys, and are matched case insensitively by include / exclude filters
+        if !signature_names.insert(signature.name.to_lowercase()) {
+            return Err(BinwalkError::new(&format!(
+                "Invalid magic file '{}': signature name '{}' is already defined",
+                path.display(),
+                signature.name
+            )));
+        }
+
+        signatures.push(signature);
+    }
+
+    Ok(signatures)
+}
+
+/// Converts a signature definition into a Signature, validating its fields
+fn signature_from_definition(definition: SignatureDefinition) -> Result<Signature, String> {
+    if definition.name.is_empty() || definition.name.contains(char::is_whitespace) {
+        return Err(format!(
+            "signature name '{}' must be non-empty and contain no whitespace",
+            definition.name
+        ));
+    }
+
+    let magic_bytes = parse_hex(&definition.magic)
+        .ok_or_else(|| format!("signature '{}' has invalid hex magic", definition.name))?;
+
+    let swapped_magic_bytes: Vec<u8> = magic_bytes.iter().rev().copied().collect();
+
+    let mut magic = match definition.endianness {
+        Endianness::Big => vec![magic_bytes],
+        Endianness::Little => vec![swapped_magic_bytes],
+        Endianness::Both => vec![magic_bytes, swapped_magic_bytes],
+    };
+    magic.dedup();
+
+    let description = match definition.category {
+        Some(category) => format!("{} ({category})", definition.description),
+        None => definition.description,
+    };
+
+    let extractor = definition.extractor.map(|extractor| Extractor {
+        utility: ExtractorType::External(extractor.command),
+        extension: extractor.extension,
+        arguments: extractor.arguments,
+        exit_codes: extractor.exit_codes,
+        ..Default::default()
+    });
+
+    Ok(Signature {
+        name: definition.name,
+        // Short signatures are only matched at a fixed offset from the start of the file
+        short: definition.offset.is_some(),
+        magic,
+        magic_offset: definition.offset.unwrap_or_default(),
+        magic_alignment: None,
+        description,
+        always_display: false,
+        parser: magic_file_parser,
+        extractor,
+    })
+}
+
+/// Parses a hex string, ignoring whitespace and an optional leading "0x"; returns None if the string is empty or invalid
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex: String = hex.chars().filter(|c| !c.is_whitespace()).collect();
+    let hex = hex
+        .strip_prefix("0x")
+        .or_else(|| hex.strip_prefix("0X"))
+        .unwrap_or(&hex);
+
+    if hex.is_empty() || !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
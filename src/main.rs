@@ -1,7 +1,9 @@
 use binwalk_ng::extractors::Chroot;
-use binwalk_ng::{AnalysisResults, common, extractors};
+use binwalk_ng::profile::Profiler;
+use binwalk_ng::warnings::{Warning, WarningCode};
+use binwalk_ng::{AnalysisResults, common, strings};
 use clap::Parser;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use rayon::ThreadPool;
 use std::collections::VecDeque;
 use std::panic;
@@ -9,12 +11,13 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::process;
 use std::process::ExitCode;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time;
 
+mod bundle;
 mod cli_parser;
 mod display;
 #[cfg(feature = "entropy-plot")]
@@ -22,9 +25,6 @@ mod entropy;
 mod json;
 
 fn main() -> ExitCode {
-    // Only use one thread if unable to auto-detect available core info
-    const DEFAULT_WORKER_COUNT: usize = 1;
-
     // Number of seconds to wait before printing debug progress info
     const PROGRESS_INTERVAL: u64 = 30;
 
@@ -32,11 +32,18 @@ fn main() -> ExitCode {
     // be deleted at the end of extraction.
     const BINWALK_RM_SYMLINK: &str = "BINWALK_RM_EXTRACTION_SYMLINK";
 
+    // Comma separated list of NAME=PATH external extractor path overrides; --extractor-path takes precedence
+    const BINWALK_EXTRACTOR_PATH: &str = "BINWALK_EXTRACTOR_PATH";
+
+    // If this env var is set, output is made reproducible as if --reproducible were specified, and file timestamps
+    // are pinned to its value; see https://reproducible-builds.org/specs/source-date-epoch/
+    const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
     // Output directory for extracted files
     let mut output_directory: Option<PathBuf> = None;
 
     /*
-     * Queue of files waiting to be analyzed.
+     * Queue of files waiting to be analyzed, along with their recursion depth (0 for the target file itself).
      * Grows when matryoshka mode discovers nested files in extraction results.
      */
     let mut target_files = VecDeque::new();
@@ -63,15 +70,63 @@ fn main() -> ExitCode {
         .init();
 
     // Process command line arguments
-    let cli_args = cli_parser::CliArgs::parse();
+    let mut cli_args = cli_parser::CliArgs::parse();
+
+    display::configure_color(cli_args.no_color);
+
+    // Carving is still honored, but no extractor of any type may be invoked
+    if cli_args.no_extract_known {
+        cli_args.extract = false;
+    }
+
+    // Load any user-defined signatures; later magic files may not redefine signatures from earlier ones
+    let mut user_signatures: Vec<binwalk_ng::Signature> = vec![];
+    for magic_file in &cli_args.magic {
+        match binwalk_ng::magic_file::load_magic_file(magic_file) {
+            Ok(signatures) => {
+                if let Some(signature) = signatures.iter().find(|signature| {
+                    user_signatures
+                        .iter()
+                        .any(|loaded| loaded.name.eq_ignore_ascii_case(&signature.name))
+                }) {
+                    error!(
+                        "Signature name '{}' in magic file '{}' is already defined",
+                        signature.name,
+                        magic_file.display()
+                    );
+                    return ExitCode::FAILURE;
+                }
+                user_signatures.extend(signatures);
+            }
+            Err(e) => {
+                error!("{}", e.message);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
 
     // If --list was specified, just display a list of signatures and return
     if cli_args.list {
-        display::print_signature_list(cli_args.quiet, &binwalk_ng::magic::patterns());
+        let mut signatures = binwalk_ng::magic::patterns();
+        signatures.extend(user_signatures);
+        display::print_signature_list(cli_args.quiet, &signatures);
         return ExitCode::SUCCESS;
     }
 
+    // Timestamp to pin output file times to, if reproducible output was requested
+    let reproducible_time = match std::env::var(SOURCE_DATE_EPOCH) {
+        Ok(value) => match value.trim().parse::<u64>() {
+            Ok(epoch) => Some(time::UNIX_EPOCH + time::Duration::from_secs(epoch)),
+            Err(e) => {
+                error!("Invalid {SOURCE_DATE_EPOCH} value '{value}': {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        Err(_) => cli_args.reproducible.then_some(time::UNIX_EPOCH),
+    };
+
     let mut json_logger = json::JsonLogger::new(cli_args.log.as_deref());
+    json_logger.sorted = reproducible_time.is_some();
 
     if cli_args.entropy {
         #[cfg(not(feature = "entropy-plot"))]
@@ -109,12 +164,12 @@ fn main() -> ExitCode {
     }
 
     // Initialize binwalk
-    let binwalker = match binwalk_ng::Binwalk::configure(
+    let mut binwalker = match binwalk_ng::Binwalk::configure(
         cli_args.file_name.as_deref(),
         output_directory.as_deref(),
         cli_args.include,
         cli_args.exclude,
-        None,
+        Some(user_signatures),
         cli_args.search_all,
     ) {
         Err(e) => {
@@ -124,18 +179,49 @@ fn main() -> ExitCode {
         Ok(bw) => bw,
     };
 
-    // If the user specified --threads, honor that request; else, auto-detect available parallelism
-    let available_workers = cli_args.threads.unwrap_or_else(|| {
-        // Get CPU core info
-        match thread::available_parallelism() {
-            // In case of error use the default
-            Err(e) => {
-                error!("Failed to retrieve CPU core info: {e}");
-                DEFAULT_WORKER_COUNT
+    // Collect external extractor path overrides from the environment, then from the command line
+    let mut extractor_paths: Vec<(String, PathBuf)> = vec![];
+    if let Ok(env_paths) = std::env::var(BINWALK_EXTRACTOR_PATH) {
+        for entry in env_paths.split(',').filter(|entry| !entry.is_empty()) {
+            match cli_parser::parse_extractor_path(entry) {
+                Ok(extractor_path) => extractor_paths.push(extractor_path),
+                Err(e) => {
+                    error!("Invalid {BINWALK_EXTRACTOR_PATH} entry: {e}");
+                    return ExitCode::FAILURE;
+                }
             }
-            Ok(coreinfo) => coreinfo.get(),
         }
-    });
+    }
+    extractor_paths.extend(cli_args.extractor_path);
+
+    for (name, path) in extractor_paths {
+        if let Err(e) = binwalker.set_extractor_path(&name, &path) {
+            error!("{}", e.message);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    binwalker.verify_signatures = cli_args.verify;
+    binwalker.strings_min_length = cli_args.strings;
+    binwalker.padding_min_length = cli_args.padding;
+    binwalker.encoded_min_length = cli_args.decode;
+    binwalker.min_confidence = cli_args.min_confidence;
+    binwalker.reproducible = reproducible_time.is_some();
+    binwalker.decompress_input = cli_args.decompress_input;
+    if cli_args.index_table {
+        binwalker.index_table_layouts = binwalk_ng::index_table::default_layouts();
+    }
+    binwalker.max_results = if cli_args.first_match {
+        Some(1)
+    } else {
+        cli_args.max_results
+    };
+    binwalker.extractor_timeout = cli_args.extractor_timeout.map(time::Duration::from_secs);
+    if cli_args.profile {
+        binwalker.profiler = Some(Arc::new(Profiler::default()));
+    }
+
+    let available_workers = worker_count(cli_args.threads);
 
     // Initialize thread pool
     debug!("Initializing thread pool with {available_workers} workers");
@@ -169,13 +255,37 @@ fn main() -> ExitCode {
     );
 
     // Queue the initial file path
-    target_files.push_back(binwalker.base_target_file.clone());
+    target_files.push_back((binwalker.base_target_file.clone(), 0));
+
+    let carve_mode = match (cli_args.carve, cli_args.sparse) {
+        (false, _) => CarveMode::Off,
+        (true, false) => CarveMode::Dense,
+        (true, true) => CarveMode::Sparse,
+    };
+
+    let carving = CarveOptions {
+        mode: carve_mode,
+        bundle: match &cli_args.carve_bundle {
+            None => None,
+            Some(bundle_file) => match bundle::CarveBundle::create(bundle_file) {
+                Ok(carve_bundle) => Some(Arc::new(Mutex::new(carve_bundle))),
+                Err(e) => {
+                    error!(
+                        "Failed to create carve bundle '{}': {e}",
+                        bundle_file.display()
+                    );
+                    return ExitCode::FAILURE;
+                }
+            },
+        },
+    };
 
     let flags = AnalysisFlags {
         verbose: cli_args.verbose,
         quiet: cli_args.quiet,
         do_extract: cli_args.extract,
         matryoshka: cli_args.matryoshka,
+        max_depth: cli_args.max_depth,
     };
 
     /*
@@ -184,13 +294,14 @@ fn main() -> ExitCode {
      */
     loop {
         // Drain any queued files into the thread pool
-        while let Some(target_file) = target_files.pop_front() {
+        while let Some((target_file, depth)) = target_files.pop_front() {
             spawn_worker(
                 &workers,
                 binwalker.clone(),
                 target_file,
+                depth,
                 cli_args.extract,
-                cli_args.carve,
+                carving.clone(),
                 worker_tx.clone(),
                 pending.clone(),
             );
@@ -214,9 +325,10 @@ fn main() -> ExitCode {
         }
 
         // Drain all available results from the channel
-        while let Ok(results) = worker_rx.try_recv() {
+        while let Ok((results, depth)) = worker_rx.try_recv() {
             process_analysis_results(
                 results,
+                depth,
                 &mut file_count,
                 &mut json_logger,
                 flags,
@@ -227,9 +339,10 @@ fn main() -> ExitCode {
         // Exit only when no work remains and the channel is truly empty
         if pending.load(Ordering::Acquire) == 0 && target_files.is_empty() {
             match worker_rx.try_recv() {
-                Ok(results) => {
+                Ok((results, depth)) => {
                     process_analysis_results(
                         results,
+                        depth,
                         &mut file_count,
                         &mut json_logger,
                         flags,
@@ -241,8 +354,20 @@ fn main() -> ExitCode {
         }
     }
 
+    // Report which signatures took the most time
+    if let Some(profiler) = &binwalker.profiler {
+        let report = profiler.report();
+        display::print_profile(cli_args.quiet, &report);
+        json_logger.log(json::JSONType::Profile(report));
+    }
+
     json_logger.close();
 
+    // All workers are done, write out the carve bundle index
+    if let Some(carve_bundle) = &carving.bundle {
+        carve_bundle.lock().unwrap().close();
+    }
+
     // If BINWALK_RM_SYMLINK env var was set, delete the base_target_file symlink
     if (cli_args.carve || cli_args.extract)
         && std::env::var(BINWALK_RM_SYMLINK).is_ok()
@@ -255,6 +380,13 @@ fn main() -> ExitCode {
         );
     }
 
+    // Replace wall-clock timestamps of extracted files with the reproducible timestamp
+    if let Some(reproducible_time) = reproducible_time
+        && (cli_args.carve || cli_args.extract)
+    {
+        clamp_file_times(&binwalker.base_output_directory, reproducible_time);
+    }
+
     // All done, show some basic statistics
     display::print_stats(
         cli_args.quiet,
@@ -267,6 +399,66 @@ fn main() -> ExitCode {
     ExitCode::SUCCESS
 }
 
+/// Returns the number of worker threads to use: the number requested with --threads, else the number of available CPU cores.
+///
+/// Requests for far more threads than there are cores are clamped, as they only add scheduling overhead.
+fn worker_count(requested: Option<usize>) -> usize {
+    // Only use one thread if unable to auto-detect available core info
+    const DEFAULT_WORKER_COUNT: usize = 1;
+    // Allow some oversubscription, as workers spend time waiting on external extractors
+    const MAX_WORKERS_PER_CORE: usize = 4;
+
+    let available_cores = match thread::available_parallelism() {
+        Ok(coreinfo) => coreinfo.get(),
+        Err(e) => {
+            error!("Failed to retrieve CPU core info: {e}");
+            DEFAULT_WORKER_COUNT
+        }
+    };
+
+    let max_workers = available_cores * MAX_WORKERS_PER_CORE;
+
+    match requested {
+        None => available_cores,
+        Some(threads) if threads > max_workers => {
+            warn!(
+                "Requested {threads} threads, but only {available_cores} CPU cores are available; using {max_workers} threads"
+            );
+            max_workers
+        }
+        Some(threads) => threads,
+    }
+}
+
+/// Recursively sets the modification time of any file or directory newer than `max_time` to `max_time`.
+/// Symbolic links are not followed, and their own timestamps are left as-is.
+fn clamp_file_times(path: &Path, max_time: time::SystemTime) {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return;
+    };
+
+    if metadata.is_symlink() {
+        return;
+    }
+
+    if metadata.is_dir()
+        && let Ok(entries) = std::fs::read_dir(path)
+    {
+        for entry in entries.flatten() {
+            clamp_file_times(&entry.path(), max_time);
+        }
+    }
+
+    // Directories are updated last, after their contents have been modified
+    if metadata
+        .modified()
+        .is_ok_and(|modified| modified > max_time)
+        && let Err(e) = std::fs::File::open(path).and_then(|file| file.set_modified(max_time))
+    {
+        debug!("Failed to set timestamp of {}: {e}", path.display());
+    }
+}
+
 /// Returns true if the specified results should be displayed to screen
 fn should_display(results: &AnalysisResults, file_count: usize, verbose: bool) -> bool {
     /*
@@ -287,43 +479,102 @@ fn should_display(results: &AnalysisResults, file_count: usize, verbose: bool) -
     false
 }
 
+/// How, if at all, analyzed file data is carved to disk
+#[derive(Clone, Copy, PartialEq)]
+enum CarveMode {
+    Off,
+    Dense,
+    Sparse,
+}
+
+/// Where analyzed file data is carved to: separate files on disk, a single bundle file, or both
+#[derive(Clone)]
+struct CarveOptions {
+    mode: CarveMode,
+    bundle: Option<Arc<Mutex<bundle::CarveBundle>>>,
+}
+
 #[derive(Clone, Copy)]
 struct AnalysisFlags {
     verbose: bool,
     quiet: bool,
     do_extract: bool,
     matryoshka: bool,
+    /// Maximum matryoshka recursion depth; None for unlimited
+    max_depth: Option<usize>,
 }
 
 /// Process analysis results from a worker: log, display, and queue nested files.
+/// `depth` is the recursion depth of the analyzed file.
 fn process_analysis_results(
-    results: AnalysisResults,
+    mut results: AnalysisResults,
+    depth: usize,
     file_count: &mut usize,
     json_logger: &mut json::JsonLogger,
     flags: AnalysisFlags,
-    target_files: &mut VecDeque<PathBuf>,
+    target_files: &mut VecDeque<(PathBuf, usize)>,
 ) {
     *file_count += 1;
-    json_logger.log(json::JSONType::Analysis(results.clone()));
 
-    if results.file_map.is_empty() {
+    let depth_limit_reached =
+        flags.matryoshka && flags.max_depth.is_some_and(|max_depth| depth >= max_depth);
+
+    if depth_limit_reached {
+        add_depth_limit_warnings(&mut results);
+    }
+
+    json_logger.log(json::JSONType::Analysis(Box::new(results.clone())));
+
+    if results.file_map.is_empty()
+        && results.strings.is_empty()
+        && results.padding.is_empty()
+        && results.encoded.is_empty()
+        && results.index_table.is_none()
+    {
         debug!("Found no results for file {}", results.file_path.display());
         return;
     }
 
     if should_display(&results, *file_count, flags.verbose) {
-        display::print_analysis_results(flags.quiet, flags.do_extract, &results);
+        display::print_analysis_results(flags.quiet, flags.verbose, flags.do_extract, &results);
     }
 
     if flags.matryoshka {
+        if depth_limit_reached {
+            info!(
+                "Maximum recursion depth reached, not scanning files extracted from {}",
+                results.file_path.display()
+            );
+            return;
+        }
+
         for r in results
             .extractions
             .into_values()
             .filter(|r| !r.do_not_recurse)
         {
-            let files = extractors::get_extracted_files(&r.output_directory);
-            debug!("Queuing {} files for analysis", files.len());
-            target_files.extend(files);
+            debug!("Queuing {} files for analysis", r.extracted_files.len());
+            target_files.extend(r.extracted_files.into_iter().map(|file| (file, depth + 1)));
+        }
+    }
+}
+
+/// Records a warning for each extraction whose extracted files will not be analyzed, as the maximum recursion depth
+/// has been reached
+fn add_depth_limit_warnings(results: &mut AnalysisResults) {
+    for signature in &results.file_map {
+        if let Some(extraction) = results.extractions.get(&signature.id)
+            && !extraction.do_not_recurse
+            && !extraction.extracted_files.is_empty()
+        {
+            results.warnings.push(Warning::for_signature(
+                WarningCode::DepthLimitReached,
+                signature,
+                format!(
+                    "Maximum recursion depth reached, not analyzing {} extracted files",
+                    extraction.extracted_files.len()
+                ),
+            ));
         }
     }
 }
@@ -333,34 +584,45 @@ fn spawn_worker(
     pool: &ThreadPool,
     bw: binwalk_ng::Binwalk,
     target_file: impl AsRef<Path>,
+    depth: usize,
     do_extraction: bool,
-    do_carve: bool,
-    worker_tx: mpsc::Sender<AnalysisResults>,
+    carving: CarveOptions,
+    worker_tx: mpsc::Sender<(AnalysisResults, usize)>,
     pending: Arc<AtomicUsize>,
 ) {
     let target_file = target_file.as_ref().to_path_buf();
     pending.fetch_add(1, Ordering::Release);
     pool.spawn(move || {
-        // Read in file data
-        let file_data = common::read_file(&target_file).unwrap_or_else(|_| {
+        // Read in file data; only the base target file is ever decompressed, not extracted files
+        let decompress = bw.decompress_input && target_file == bw.base_target_file;
+        let file_data = common::read_input(&target_file, decompress).unwrap_or_else(|_| {
             error!("Failed to read {} data", target_file.display());
-            b"".to_vec()
+            common::InputData::default()
         });
 
         // Analyze target file, with extraction, if specified
         let results = bw.analyze_buf(&file_data, &target_file, do_extraction);
 
         // If data carving was requested as part of extraction, carve analysis results to disk
-        if do_carve {
-            let carve_count = carve_file_map(&file_data, &results);
+        if carving.mode != CarveMode::Off {
+            let carve_count = carve_file_map(&file_data, &results, carving.mode)
+                + carve_index_table(&file_data, &results, carving.mode);
             info!(
                 "Carved {carve_count} data blocks to disk from {}",
                 target_file.display()
             );
         }
 
+        if let Some(carve_bundle) = &carving.bundle {
+            let carve_count = carve_file_map_to_bundle(&file_data, &results, carve_bundle);
+            info!(
+                "Added {carve_count} data blocks to the carve bundle from {}",
+                target_file.display()
+            );
+        }
+
         // Report file results back to main thread
-        if let Err(e) = worker_tx.send(results) {
+        if let Err(e) = worker_tx.send((results, depth)) {
             error!(
                 "Worker thread for {} failed to send results back to main thread: {e}",
                 target_file.display()
@@ -371,59 +633,170 @@ fn spawn_worker(
     });
 }
 
-/// Carve signatures identified during analysis to separate files on disk.
-/// Returns the number of carved files created.
-/// Note that unknown blocks of file data are also carved to disk, so the number of files
-/// created may be larger than the number of results defined in results.file_map.
-fn carve_file_map(file_data: &[u8], results: &binwalk_ng::AnalysisResults) -> usize {
-    let mut carve_count: usize = 0;
-    let mut last_known_offset: usize = 0;
-    let mut unknown_bytes: Vec<(usize, usize)> = Vec::new();
+/// A region of analyzed file data to be carved
+struct CarveRegion<'a> {
+    /// Signature name, or "unknown" / "padding" for unidentified data
+    name: &'a str,
+    /// Signature description; empty for unidentified data
+    description: &'a str,
+    offset: usize,
+    size: usize,
+}
 
+/// Splits the analyzed file data into regions for carving: one for each identified signature, and one for each block of
+/// unknown data in between, with any padding within the unknown data split out separately so that the real unknown data
+/// stands out. Regions are sorted by offset; no regions are returned if no signatures were identified.
+fn carve_regions(file_size: usize, results: &binwalk_ng::AnalysisResults) -> Vec<CarveRegion<'_>> {
     // No results, don't do anything
-    if !results.file_map.is_empty() {
-        // Loop through all identified signatures in the file
-        for signature_result in &results.file_map {
-            // If there is data between the last signature and this signature, it is some chunk of unknown data
-            if signature_result.offset > last_known_offset {
-                unknown_bytes.push((
-                    last_known_offset,
-                    signature_result.offset - last_known_offset,
-                ));
-            }
-
-            // Carve this signature's data to disk
-            if carve_file_data_to_disk(
-                &results.file_path,
-                file_data,
-                &signature_result.name,
-                signature_result.offset,
-                signature_result.size,
-            ) {
-                carve_count += 1;
-            }
+    if results.file_map.is_empty() {
+        return Vec::new();
+    }
 
-            // Update the last known offset to the end of this signature's data
-            last_known_offset = signature_result.offset + signature_result.size;
-        }
+    let mut regions: Vec<CarveRegion> = results
+        .file_map
+        .iter()
+        .map(|signature_result| CarveRegion {
+            name: &signature_result.name,
+            description: &signature_result.description,
+            offset: signature_result.offset,
+            size: signature_result.size,
+        })
+        .collect();
 
-        // Calculate the size of any remaining data from the end of the last signature to EOF
-        let remaining_data = file_data.len() - last_known_offset;
+    for (offset, size) in strings::unknown_regions(&results.file_map, file_size) {
+        let end = offset + size;
+        let mut next_offset = offset;
 
-        // Add any remaining unknown data to the unknown_bytes list
-        if remaining_data > 0 {
-            unknown_bytes.push((last_known_offset, remaining_data));
+        for padding in results
+            .padding
+            .iter()
+            .filter(|padding| padding.offset >= offset && padding.offset < end)
+        {
+            if padding.offset > next_offset {
+                regions.push(CarveRegion {
+                    name: "unknown",
+                    description: "",
+                    offset: next_offset,
+                    size: padding.offset - next_offset,
+                });
+            }
+            regions.push(CarveRegion {
+                name: "padding",
+                description: "",
+                offset: padding.offset,
+                size: padding.size,
+            });
+            next_offset = padding.offset + padding.size;
         }
 
-        // All known signature data has been carved to disk, now carve any unknown blocks of data to disk
-        for (offset, size) in unknown_bytes {
-            if carve_file_data_to_disk(&results.file_path, file_data, "unknown", offset, size) {
-                carve_count += 1;
-            }
+        if end > next_offset {
+            regions.push(CarveRegion {
+                name: "unknown",
+                description: "",
+                offset: next_offset,
+                size: end - next_offset,
+            });
         }
     }
 
-    carve_count
+    regions.sort_by_key(|region| region.offset);
+    regions
+}
+
+/// Carve signatures identified during analysis to separate files on disk.
+/// Returns the number of carved files created.
+/// Note that unknown blocks of file data are also carved to disk, so the number of files
+/// created may be larger than the number of results defined in results.file_map.
+fn carve_file_map(
+    file_data: &[u8],
+    results: &binwalk_ng::AnalysisResults,
+    carve_mode: CarveMode,
+) -> usize {
+    carve_regions(file_data.len(), results)
+        .into_iter()
+        .filter(|region| {
+            carve_file_data_to_disk(
+                &results.file_path,
+                file_data,
+                region.name,
+                region.offset,
+                region.size,
+                carve_mode,
+            )
+        })
+        .count()
+}
+
+/// Carves each sub-image listed in an index table found during analysis to disk.
+/// Returns the number of sub-images carved.
+fn carve_index_table(
+    file_data: &[u8],
+    results: &binwalk_ng::AnalysisResults,
+    carve_mode: CarveMode,
+) -> usize {
+    let Some(index_table) = &results.index_table else {
+        return 0;
+    };
+
+    index_table
+        .entries
+        .iter()
+        .filter(|entry| {
+            // Model names come from the file data; make sure they are sane file names
+            let model: String = entry
+                .model
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+
+            carve_file_data_to_disk(
+                &results.file_path,
+                file_data,
+                &format!("index_{model}"),
+                entry.offset,
+                entry.size,
+                carve_mode,
+            )
+        })
+        .count()
+}
+
+/// Append signatures identified during analysis, and the unknown data between them, to a carve bundle.
+/// Returns the number of regions appended.
+fn carve_file_map_to_bundle(
+    file_data: &[u8],
+    results: &binwalk_ng::AnalysisResults,
+    carve_bundle: &Mutex<bundle::CarveBundle>,
+) -> usize {
+    let regions = carve_regions(file_data.len(), results);
+
+    // Keep this file's regions together in the bundle
+    let mut carve_bundle = carve_bundle.lock().unwrap();
+
+    regions
+        .into_iter()
+        .filter(|region| {
+            match carve_bundle.append(
+                &results.file_path,
+                file_data,
+                region.offset,
+                region.size,
+                region.name,
+                region.description,
+            ) {
+                Ok(()) => true,
+                Err(e) => {
+                    error!(
+                        "Failed to add {} [{:#X}..{:#X}] to carve bundle: {e}",
+                        results.file_path.display(),
+                        region.offset,
+                        region.offset + region.size,
+                    );
+                    false
+                }
+            }
+        })
+        .count()
 }
 
 /// Carves a block of file data to a new file on disk
@@ -433,6 +806,7 @@ fn carve_file_data_to_disk(
     name: &str,
     offset: usize,
     size: usize,
+    carve_mode: CarveMode,
 ) -> bool {
     let chroot = Chroot::default();
 
@@ -445,7 +819,13 @@ fn carve_file_data_to_disk(
     debug!("Carving {carved_file_path}");
 
     // Carve the data to disk
-    if !chroot.carve_file(&carved_file_path, file_data, offset, size) {
+    let carved = if carve_mode == CarveMode::Sparse {
+        chroot.carve_sparse_file(&carved_file_path, file_data, offset, size)
+    } else {
+        chroot.carve_file(&carved_file_path, file_data, offset, size)
+    };
+
+    if !carved {
         error!(
             "Failed to carve {} [{:#X}..{:#X}] to disk",
             carved_file_path,
@@ -0,0 +1,73 @@
+//! Detection of padding / filler regions (e.g., erased flash) in unidentified data.
+
+use crate::signatures::SignatureResult;
+use crate::strings::unknown_regions;
+use serde::{Deserialize, Serialize};
+
+/// Describes a run of a single repeated byte found in the file data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaddingResult {
+    /// File/data offset where the padding starts
+    pub offset: usize,
+    /// Length of the padding, in bytes
+    pub size: usize,
+    /// The byte value that the padding consists of (typically 0xFF or 0x00)
+    pub fill_byte: u8,
+}
+
+/// Find all runs of at least `min_length` identical bytes in the unknown regions of the file data.
+/// Results are sorted by offset.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::padding::find_padding;
+/// use binwalk_ng::signatures::SignatureResult;
+///
+/// let mut file_data = b"ABCD".to_vec();
+/// file_data.extend([0xFF; 32]);
+/// file_data.extend(b"EFGH");
+/// file_data.extend([0; 8]);
+///
+/// let padding = find_padding(&file_data, &[], 16);
+///
+/// assert_eq!(padding.len(), 1);
+/// assert_eq!(padding[0].offset, 4);
+/// assert_eq!(padding[0].size, 32);
+/// assert_eq!(padding[0].fill_byte, 0xFF);
+///
+/// // Padding inside identified data is not reported
+/// let file_map = vec![SignatureResult { offset: 0, size: 20, ..Default::default() }];
+///
+/// assert_eq!(find_padding(&file_data, &file_map, 16).len(), 1);
+/// assert_eq!(find_padding(&file_data, &file_map, 16)[0].offset, 20);
+/// ```
+pub fn find_padding(
+    file_data: &[u8],
+    file_map: &[SignatureResult],
+    min_length: usize,
+) -> Vec<PaddingResult> {
+    let mut padding: Vec<PaddingResult> = Vec::new();
+
+    for (offset, size) in unknown_regions(file_map, file_data.len()) {
+        let Some(region) = file_data.get(offset..offset + size) else {
+            continue;
+        };
+
+        // chunk_by yields each run of identical bytes
+        let mut run_offset = offset;
+
+        for run in region.chunk_by(|a, b| a == b) {
+            if run.len() >= min_length {
+                padding.push(PaddingResult {
+                    offset: run_offset,
+                    size: run.len(),
+                    fill_byte: run[0],
+                });
+            }
+            run_offset += run.len();
+        }
+    }
+
+    padding
+}
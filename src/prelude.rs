@@ -0,0 +1,27 @@
+//! Convenience re-exports of the stable public API.
+//!
+//! Downstream code should prefer importing from here (or from the crate root) over reaching into
+//! individual `formats` or `extractors` modules, whose layout may change between releases.
+//!
+//! ## Example
+//!
+//! ```
+//! use binwalk_ng::prelude::*;
+//!
+//! let binwalker = Binwalk::new();
+//! let file_data = std::fs::read("tests/inputs/gzip.bin").expect("Failed to read from file");
+//!
+//! let file_map: Vec<SignatureResult> = binwalker.scan(&file_data);
+//! let results: AnalysisResults = binwalker.analyze_buf(&file_data, "gzip.bin", false);
+//!
+//! assert_eq!(file_map.len(), results.file_map.len());
+//! ```
+pub use crate::binwalk_ng::{AnalysisResults, Binwalk, BinwalkError};
+pub use crate::extractors::{
+    Chroot, ExtractionError, ExtractionResult, Extractor, ExtractorType, InternalExtractor,
+};
+pub use crate::signatures::{
+    CONFIDENCE_HIGH, CONFIDENCE_LOW, CONFIDENCE_MEDIUM, Signature, SignatureError, SignatureParser,
+    SignatureResult,
+};
+pub use crate::structures::{Endianness, StructureError};
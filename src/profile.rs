@@ -0,0 +1,106 @@
+//! Per-signature timing of signature validation and extraction, for finding slow parsers.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Time spent on a single signature type, accumulated over all files analyzed
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureProfile {
+    /// The signature name, as defined in Signature.name
+    pub name: String,
+    /// Number of times the signature parser was invoked to validate a magic match
+    pub validations: usize,
+    /// Total time spent in the signature parser, in microseconds
+    pub validation_time_us: u64,
+    /// Number of times an extractor was run for this signature
+    pub extractions: usize,
+    /// Total time spent running extractors for this signature, in microseconds
+    pub extraction_time_us: u64,
+}
+
+impl SignatureProfile {
+    /// Total time spent on this signature, in microseconds
+    pub const fn total_time_us(&self) -> u64 {
+        self.validation_time_us + self.extraction_time_us
+    }
+}
+
+/// Accumulates per-signature timings; shared between all worker threads, see Binwalk::profiler
+#[derive(Debug, Default)]
+pub struct Profiler {
+    profiles: Mutex<HashMap<String, SignatureProfile>>,
+}
+
+impl Profiler {
+    /// Runs a signature parser, adding the time it took to the signature's validation time
+    pub fn time_validation<T>(&self, signature_name: &str, parser: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = parser();
+        self.update(signature_name, |profile| {
+            profile.validations += 1;
+            profile.validation_time_us += duration_us(start.elapsed());
+        });
+        result
+    }
+
+    /// Runs an extractor, adding the time it took to the signature's extraction time
+    pub fn time_extraction<T>(&self, signature_name: &str, extractor: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = extractor();
+        self.update(signature_name, |profile| {
+            profile.extractions += 1;
+            profile.extraction_time_us += duration_us(start.elapsed());
+        });
+        result
+    }
+
+    fn update(&self, signature_name: &str, update: impl FnOnce(&mut SignatureProfile)) {
+        update(
+            self.profiles
+                .lock()
+                .unwrap()
+                .entry(signature_name.to_string())
+                .or_insert_with(|| SignatureProfile {
+                    name: signature_name.to_string(),
+                    ..Default::default()
+                }),
+        );
+    }
+
+    /// Returns the accumulated timings, slowest signature first
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use binwalk_ng::profile::Profiler;
+    ///
+    /// let profiler = Profiler::default();
+    ///
+    /// profiler.time_validation("fast", || ());
+    /// profiler.time_validation("slow", || std::thread::sleep(std::time::Duration::from_millis(2)));
+    /// profiler.time_extraction("slow", || ());
+    ///
+    /// let report = profiler.report();
+    ///
+    /// assert_eq!(report.len(), 2);
+    /// assert_eq!(report[0].name, "slow");
+    /// assert_eq!(report[0].validations, 1);
+    /// assert_eq!(report[0].extractions, 1);
+    /// ```
+    pub fn report(&self) -> Vec<SignatureProfile> {
+        let mut report: Vec<SignatureProfile> =
+            self.profiles.lock().unwrap().values().cloned().collect();
+        report.sort_by(|a, b| {
+            b.total_time_us()
+                .cmp(&a.total_time_us())
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        report
+    }
+}
+
+fn duration_us(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
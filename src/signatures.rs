@@ -24,8 +24,10 @@
 //!     name: "foobar".to_string(),
 //!     // Set to true for signatures with very short magic bytes; they will only be matched at file offset 0
 //!     short: false,
-//!     // Offset from the start of the file to the "magic" bytes; only really relevant for short signatures
+//!     // Offset from the start of the file to the "magic" bytes; only really relevant for short or aligned signatures
 //!     magic_offset: 0,
+//!     // Set to Some(block size) if the signature data always starts on a block boundary; other magic matches are skipped
+//!     magic_alignment: None,
 //!     // Most signatures will want to set this to false and let the code in main.rs determine if/when to display
 //!     always_display: false,
 //!     // The magic bytes associated with this signature; there may be more than one set of magic bytes per signature
@@ -138,8 +140,10 @@ pub type SignatureParser = fn(&[u8], usize) -> Result<SignatureResult, Signature
 /// The SignatureResult struct is returned by all SignatureParser functions upon success.
 ///
 /// The `id`, `name`, and `always_display` fields are automatically populated after being returned by a SignatureParser function, and need not be set by the SignatureParser function.
+/// For `children` results, only `id` and `parent_id` are populated; the parser must set each child's `name`.
 ///
-/// At the very least, SignatureParser functions should define the `offset` and `description` fields.
+/// At the very least, SignatureParser functions should define the `offset` and `description` fields; an empty `description`
+/// is replaced with the signature's `Signature.description`.
 ///
 /// ## Additional Notes
 ///
@@ -166,11 +170,65 @@ pub struct SignatureResult {
     pub always_display: bool,
     /// Set to true to disable extraction for this particular signature result (default: false)
     pub extraction_declined: bool,
+    /// Offset of the first image in a run of back-to-back images that this one belongs to (e.g., the first bank of an A/B
+    /// dual-bank firmware layout); None if this signature does not directly follow another image. Auto-populated.
+    pub sibling_offset: Option<usize>,
+    /// For end-anchored formats (e.g., a ZIP end-of-central-directory record), the offset of the trailer that bounds
+    /// the signature data; None if the format has no trailer, or the parser does not report it
+    pub trailer_offset: Option<usize>,
+    /// Why the trailer (e.g., a trailing checksum or size field) failed validation, for signatures that are reported with
+    /// low confidence because their header and data are valid but their trailer is missing or wrong; None otherwise
+    pub trailer_error: Option<String>,
+    /// For truncated data (e.g., a partial download), the total size declared in the signature's header; `size` is then
+    /// the size of the data actually available. None if the data is not known to be truncated.
+    pub declared_size: Option<usize>,
+    /// Name of the extractor that handles this signature, as reported in ExtractionResult.extractor: either
+    /// `<signature name>_built_in` or the external extraction command; None if it is not extractable. Auto-populated,
+    /// whether or not extraction is performed.
+    pub extractor: Option<String>,
+    /// Sub-images of a container format (e.g., the partitions of a TRX image), as located by the container's parser.
+    /// These are reported without extraction, and are not themselves entries in the file map.
+    pub children: Vec<Self>,
+    /// For entries in another result's `children`, the `id` of that parent result; None otherwise. Auto-populated.
+    pub parent_id: Option<String>,
     /// Signatures may specify a preferred extractor, which overrides the default extractor specified in the Signature.extractor definition
     #[serde(skip_deserializing, skip_serializing)]
     pub preferred_extractor: Option<extractors::Extractor>,
 }
 
+impl SignatureResult {
+    /// Reports this signature's data as truncated: its header declares `declared_size` bytes of data, but only
+    /// `available_size` bytes remain in the file. The result is sized to the available data and given low confidence.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use binwalk_ng::signatures::{CONFIDENCE_HIGH, CONFIDENCE_LOW, SignatureResult};
+    ///
+    /// let mut result = SignatureResult {
+    ///     description: "TRX firmware image".to_string(),
+    ///     confidence: CONFIDENCE_HIGH,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// result.set_truncated(4096, 1000);
+    ///
+    /// assert_eq!(result.size, 1000);
+    /// assert_eq!(result.declared_size, Some(4096));
+    /// assert_eq!(result.confidence, CONFIDENCE_LOW);
+    /// assert_eq!(result.description, "TRX firmware image, truncated: declared 4096 bytes, available 1000 bytes");
+    /// ```
+    pub fn set_truncated(&mut self, declared_size: usize, available_size: usize) {
+        self.size = available_size;
+        self.declared_size = Some(declared_size);
+        self.confidence = CONFIDENCE_LOW;
+        self.description = format!(
+            "{}, truncated: declared {declared_size} bytes, available {available_size} bytes",
+            self.description
+        );
+    }
+}
+
 /// Defines a file signature to search for, and how to extract that file type
 #[derive(Debug, Clone)]
 pub struct Signature {
@@ -180,8 +238,11 @@ pub struct Signature {
     pub short: bool,
     /// List of magic byte patterns associated with this signature
     pub magic: Vec<Vec<u8>>,
-    /// Offset of magic bytes from the beginning of the file; only relevant for short signatures
+    /// Offset of magic bytes from the beginning of the file (short signatures) or signature data (aligned signatures)
     pub magic_offset: usize,
+    /// If set, magic matches are only validated where the signature data would start (i.e., the magic match offset minus
+    /// magic_offset) on a multiple of this many bytes; useful for formats that only ever start on a sector boundary
+    pub magic_alignment: Option<usize>,
     /// Human readable description of this signature
     pub description: String,
     /// If true, will always display files that contain this signature, even during recursive extraction
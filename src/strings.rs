@@ -0,0 +1,222 @@
+//! Extraction of printable strings from unidentified data, similar to strings(1).
+
+use crate::common::is_printable_ascii;
+use crate::signatures::SignatureResult;
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of strings reported per file, to avoid dumping megabytes of output for large blobs
+pub const MAX_STRINGS: usize = 10_000;
+
+/// Character encoding of a string result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StringEncoding {
+    Ascii,
+    Utf16Le,
+}
+
+/// Describes a printable string found in the file data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StringResult {
+    /// File/data offset where this string starts
+    pub offset: usize,
+    /// Encoding of the string in the file data
+    pub encoding: StringEncoding,
+    /// The string itself
+    pub value: String,
+}
+
+/// Returns the (offset, size) of each region of data not claimed by any signature in the file map.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::signatures::SignatureResult;
+/// use binwalk_ng::strings::unknown_regions;
+///
+/// let file_map = vec![SignatureResult { offset: 16, size: 32, ..Default::default() }];
+///
+/// assert_eq!(unknown_regions(&file_map, 64), vec![(0, 16), (48, 16)]);
+/// assert_eq!(unknown_regions(&[], 64), vec![(0, 64)]);
+/// ```
+pub fn unknown_regions(file_map: &[SignatureResult], file_size: usize) -> Vec<(usize, usize)> {
+    let mut regions: Vec<(usize, usize)> = Vec::new();
+    let mut last_known_offset: usize = 0;
+
+    for signature in file_map {
+        if signature.offset > last_known_offset {
+            regions.push((last_known_offset, signature.offset - last_known_offset));
+        }
+
+        last_known_offset = last_known_offset.max(signature.offset + signature.size);
+    }
+
+    if file_size > last_known_offset {
+        regions.push((last_known_offset, file_size - last_known_offset));
+    }
+
+    regions
+}
+
+/// Find all ASCII and UTF-16LE strings of at least `min_length` characters in the unknown regions of the file data.
+/// At most MAX_STRINGS strings are returned; results are sorted by offset.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::strings::{StringEncoding, find_strings};
+///
+/// let file_data = b"\x00\xFFhello\x00\x01w\x00o\x00r\x00l\x00d\x00\xFFhi\x00";
+///
+/// let strings = find_strings(file_data, &[], 4);
+///
+/// assert_eq!(strings.len(), 2);
+/// assert_eq!(strings[0].offset, 2);
+/// assert_eq!(strings[0].value, "hello");
+/// assert_eq!(strings[1].offset, 9);
+/// assert_eq!(strings[1].encoding, StringEncoding::Utf16Le);
+/// assert_eq!(strings[1].value, "world");
+/// ```
+pub fn find_strings(
+    file_data: &[u8],
+    file_map: &[SignatureResult],
+    min_length: usize,
+) -> Vec<StringResult> {
+    let mut strings: Vec<StringResult> = Vec::new();
+
+    // Regions are in offset order, so once MAX_STRINGS strings have been found, the rest can be skipped
+    for (offset, size) in unknown_regions(file_map, file_data.len()) {
+        let remaining = MAX_STRINGS - strings.len();
+
+        if remaining == 0 {
+            break;
+        }
+
+        if let Some(region) = file_data.get(offset..offset + size) {
+            let mut region_strings = ascii_strings(region, offset, min_length, remaining);
+            region_strings.extend(utf16le_strings(region, offset, min_length, remaining));
+
+            region_strings.sort_by_key(|string| string.offset);
+            region_strings.truncate(remaining);
+            strings.extend(region_strings);
+        }
+    }
+
+    strings
+}
+
+/// Find all runs of at least `min_length` printable ASCII characters in the data, like strings(1). Returns the offset
+/// and text of each run, in offset order.
+///
+/// ## Example
+///
+/// ```
+/// use binwalk_ng::strings::extract_strings;
+///
+/// let data = b"\x7FELF\x02\x01U-Boot 2023.04\x00\x00v1.2\x01build 42\nok\xFF";
+///
+/// let strings = extract_strings(data, 4);
+///
+/// // Runs separated by a single non-printable byte are reported separately
+/// assert_eq!(strings.len(), 3);
+/// assert_eq!(strings[0], (6, "U-Boot 2023.04".to_string()));
+/// assert_eq!(strings[1], (22, "v1.2".to_string()));
+/// assert_eq!(strings[2], (27, "build 42\nok".to_string()));
+/// assert!(extract_strings(data, 64).is_empty());
+/// ```
+pub fn extract_strings(data: &[u8], min_length: usize) -> Vec<(usize, String)> {
+    ascii_strings(data, 0, min_length, usize::MAX)
+        .into_iter()
+        .map(|string| (string.offset, string.value))
+        .collect()
+}
+
+/// Find the first `limit` runs of printable ASCII characters
+fn ascii_strings(
+    data: &[u8],
+    base_offset: usize,
+    min_length: usize,
+    limit: usize,
+) -> Vec<StringResult> {
+    printable_runs(
+        data,
+        base_offset,
+        min_length,
+        limit,
+        StringEncoding::Ascii,
+        1,
+        |unit| is_printable_ascii(unit[0], true),
+    )
+}
+
+/// Find the first `limit` runs of UTF-16LE encoded printable ASCII characters, at both even and odd alignments
+fn utf16le_strings(
+    data: &[u8],
+    base_offset: usize,
+    min_length: usize,
+    limit: usize,
+) -> Vec<StringResult> {
+    printable_runs(
+        data,
+        base_offset,
+        min_length,
+        limit,
+        StringEncoding::Utf16Le,
+        2,
+        is_printable_utf16le,
+    )
+}
+
+/// Returns true if the provided bytes are a single UTF-16LE code unit encoding a printable ASCII character
+fn is_printable_utf16le(code_unit: &[u8]) -> bool {
+    matches!(code_unit, [b, 0] if is_printable_ascii(*b, true))
+}
+
+/// Find runs of at least `min_length` printable characters, each encoded as `unit_size` bytes, at every possible alignment.
+/// The first byte of each unit is taken as the ASCII character value. At most `limit` runs are found per alignment.
+fn printable_runs(
+    data: &[u8],
+    base_offset: usize,
+    min_length: usize,
+    limit: usize,
+    encoding: StringEncoding,
+    unit_size: usize,
+    is_printable: fn(&[u8]) -> bool,
+) -> Vec<StringResult> {
+    let mut strings: Vec<StringResult> = Vec::new();
+
+    for alignment in 0..unit_size.min(data.len()) {
+        let alignment_start = strings.len();
+        let mut run_start: Option<usize> = None;
+        let mut value = String::new();
+
+        // A trailing None terminates any run that extends to the end of the data
+        let units = data[alignment..].chunks(unit_size).map(Some);
+
+        for (i, unit) in units.chain(std::iter::once(None)).enumerate() {
+            match unit {
+                Some(unit) if is_printable(unit) => {
+                    run_start.get_or_insert(alignment + (i * unit_size));
+                    value.push(char::from(unit[0]));
+                }
+                _ => {
+                    if let Some(start) = run_start.take()
+                        && value.len() >= min_length
+                    {
+                        strings.push(StringResult {
+                            offset: base_offset + start,
+                            encoding,
+                            value: value.clone(),
+                        });
+                    }
+                    value.clear();
+
+                    if strings.len() - alignment_start >= limit {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    strings
+}
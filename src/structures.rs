@@ -1,10 +1,16 @@
 pub mod dyn_endian;
+pub mod fixed_string;
 
 use std::fmt;
 
 /*
- * Note that all values returned by the parse() function are of type usize; this is a concious decision.
- * Returning usize types makes the calling code much cleaner, but that means that u64 fields won't fit into the return value on 32-bit systems.
+ * Structures are parsed by declaring a #[repr(C, packed)] struct of typed fields and mapping it onto the data with
+ * zerocopy (see dyn_endian for fields whose endianness is only known at runtime). Field access is therefore checked
+ * at compile time; there is no dynamic, string-keyed parse() interface. Fixed-size, NULL-padded string fields are
+ * declared as fixed_string::FixedString.
+ *
+ * Parsed header fields are converted to usize freely (e.g. `header.size.get() as usize`); this is a concious decision.
+ * It makes the calling code much cleaner, but that means that u64 fields won't fit on 32-bit systems.
  * Thus, only 64-bit systems are supported. This requirement is enforced here.
  */
 #[cfg(not(target_pointer_width = "64"))]
@@ -34,4 +34,112 @@ dyn_endian_ty! {
     pub struct U16(u16);
     pub struct U32(u32);
     pub struct U64(u64);
+    pub struct I16(i16);
+    pub struct I32(i32);
+    pub struct I64(i64);
+}
+
+// Odd width integers (e.g., 40-bit counters) are stored in the low bytes of the next largest native type
+macro_rules! dyn_endian_odd_width_ty {
+    ($($vis:vis struct $name:ident($width:literal, $underlying:ty));* $(;)?) => {
+        $(
+        #[derive(Copy, Clone, PartialEq, Eq, zerocopy::FromBytes, zerocopy::KnownLayout, zerocopy::Unaligned, zerocopy::Immutable)]
+        #[repr(transparent)]
+        $vis struct $name([u8; $width]);
+
+        impl $name {
+            const UNUSED_BYTES: usize = size_of::<$underlying>() - $width;
+
+            #[inline]
+            #[allow(unused)]
+            $vis fn new(value: $underlying, endianness: Endianness) -> Self {
+                let mut bytes = [0; $width];
+
+                match endianness {
+                    Endianness::Little => bytes.copy_from_slice(&value.to_le_bytes()[..$width]),
+                    Endianness::Big => bytes.copy_from_slice(&value.to_be_bytes()[Self::UNUSED_BYTES..]),
+                }
+
+                Self(bytes)
+            }
+
+            #[inline]
+            #[allow(unused)]
+            $vis fn get(&self, endianness: Endianness) -> $underlying {
+                let mut bytes = [0; size_of::<$underlying>()];
+
+                match endianness {
+                    Endianness::Little => {
+                        bytes[..$width].copy_from_slice(&self.0);
+                        <$underlying>::from_le_bytes(bytes)
+                    }
+                    Endianness::Big => {
+                        bytes[Self::UNUSED_BYTES..].copy_from_slice(&self.0);
+                        <$underlying>::from_be_bytes(bytes)
+                    }
+                }
+            }
+        }
+        )*
+    };
+}
+
+dyn_endian_odd_width_ty! {
+    pub struct U40(5, u64);
+    pub struct U48(6, u64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zerocopy::FromBytes;
+
+    #[test]
+    fn signed_fields_are_sign_extended() {
+        let data: [u8; 8] = [0xFF, 0xFF, 0xFF, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF];
+
+        // Single byte fields have no endianness, and are declared as plain i8s
+        assert_eq!(i8::read_from_bytes(&data[..1]).unwrap(), -1);
+
+        let (field, _) = I16::read_from_prefix(&data).unwrap();
+        assert_eq!(field.get(Endianness::Little), -1);
+
+        let (field, _) = I32::read_from_prefix(&data).unwrap();
+        assert_eq!(field.get(Endianness::Big), -2);
+        assert_eq!(field.get(Endianness::Little), -0x01000001);
+
+        let field = I64::read_from_bytes(&data).unwrap();
+        assert_eq!(field.get(Endianness::Little), -0x01000001);
+        assert_eq!(I64::new(-42, Endianness::Big).get(Endianness::Big), -42);
+    }
+
+    #[test]
+    fn odd_width_fields() {
+        let data: [u8; 6] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+
+        let (field, _) = U40::read_from_prefix(&data).unwrap();
+        assert_eq!(field.get(Endianness::Little), 0x05_0403_0201);
+        assert_eq!(field.get(Endianness::Big), 0x01_0203_0405);
+
+        let field = U48::read_from_bytes(&data).unwrap();
+        assert_eq!(field.get(Endianness::Little), 0x0605_0403_0201);
+        assert_eq!(field.get(Endianness::Big), 0x0102_0304_0506);
+
+        for endianness in [Endianness::Little, Endianness::Big] {
+            assert_eq!(
+                U40::new(0xFF_0000_0001, endianness).get(endianness),
+                0xFF_0000_0001
+            );
+            assert_eq!(
+                U48::new(0xFFFF_0000_0001, endianness).get(endianness),
+                0xFFFF_0000_0001
+            );
+        }
+
+        // Bits that don't fit in the field are discarded
+        assert_eq!(
+            U40::new(u64::MAX, Endianness::Big).get(Endianness::Big),
+            0xFF_FFFF_FFFF
+        );
+    }
 }
@@ -0,0 +1,39 @@
+use crate::common::get_cstring;
+use zerocopy::{FromBytes, Immutable, KnownLayout, Unaligned};
+
+/// A fixed-size, NULL-padded string field, such as a product name or version string embedded in a header
+#[derive(Copy, Clone, PartialEq, Eq, FromBytes, KnownLayout, Unaligned, Immutable)]
+#[repr(transparent)]
+pub struct FixedString<const N: usize>([u8; N]);
+
+impl<const N: usize> FixedString<N> {
+    /// Returns the string up to the first NULL byte, or the whole field if it is not NULL terminated
+    pub fn get(&self) -> String {
+        get_cstring(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zerocopy::BE;
+
+    #[derive(FromBytes, KnownLayout, Unaligned, Immutable)]
+    #[repr(C, packed)]
+    struct HeaderBytes {
+        name: FixedString<16>,
+        version: FixedString<4>,
+        size: zerocopy::U32<BE>,
+    }
+
+    #[test]
+    fn string_fields_are_null_trimmed() {
+        let data = b"WRT54G\0\0\0\0\0\0\0\0\0\0v1.2\x00\x00\x10\x00";
+
+        let header = HeaderBytes::ref_from_bytes(data).unwrap();
+
+        assert_eq!(header.name.get(), "WRT54G");
+        assert_eq!(header.version.get(), "v1.2");
+        assert_eq!(header.size.get(), 0x1000);
+    }
+}
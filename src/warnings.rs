@@ -0,0 +1,78 @@
+//! Non-fatal problems encountered during analysis, collected per file so that library users and JSON consumers can
+//! surface them without parsing log output.
+
+use crate::signatures::SignatureResult;
+use serde::{Deserialize, Serialize};
+
+/// The kind of problem a warning describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WarningCode {
+    /// A signature's trailer (e.g., a trailing checksum or size) is missing or wrong, see SignatureResult.trailer_error
+    InvalidTrailer,
+    /// A signature's header declares more data than is available (e.g., a partial download), see
+    /// SignatureResult.declared_size
+    Truncated,
+    /// A signature failed its test extraction, see Binwalk::verify
+    VerificationFailed,
+    /// Extraction of a signature failed
+    ExtractionFailed,
+    /// Only some of the results of an analysis pass were reported, e.g. strings::MAX_STRINGS or Binwalk::max_results
+    ResultsTruncated,
+    /// Files extracted from a signature were not analyzed because the maximum recursion depth was reached
+    DepthLimitReached,
+}
+
+/// Describes a non-fatal problem encountered during analysis
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Warning {
+    pub code: WarningCode,
+    /// Human readable description of the problem
+    pub message: String,
+    /// File/data offset the warning pertains to, if any
+    pub offset: Option<usize>,
+    /// ID of the signature result the warning pertains to, if any
+    pub signature_id: Option<String>,
+    /// Name of the signature the warning pertains to, if any
+    pub signature_name: Option<String>,
+}
+
+impl Warning {
+    /// A warning that does not pertain to any particular signature result
+    pub fn new(code: WarningCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            offset: None,
+            signature_id: None,
+            signature_name: None,
+        }
+    }
+
+    /// A warning about a signature result
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use binwalk_ng::signatures::SignatureResult;
+    /// use binwalk_ng::warnings::{Warning, WarningCode};
+    ///
+    /// let signature = SignatureResult { offset: 0x100, name: "gzip".to_string(), ..Default::default() };
+    /// let warning = Warning::for_signature(WarningCode::ExtractionFailed, &signature, "extraction failed");
+    ///
+    /// assert_eq!(warning.offset, Some(0x100));
+    /// assert_eq!(warning.signature_name.as_deref(), Some("gzip"));
+    /// ```
+    pub fn for_signature(
+        code: WarningCode,
+        signature: &SignatureResult,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            offset: Some(signature.offset),
+            signature_id: Some(signature.id.clone()),
+            signature_name: Some(signature.name.clone()),
+        }
+    }
+}
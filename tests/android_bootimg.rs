@@ -0,0 +1,15 @@
+mod common;
+
+#[test]
+fn integration_test() {
+    const SIGNATURE_TYPE: &str = "android_bootimg";
+    const INPUT_FILE_NAME: &str = "android_bootimg.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
+#[test]
+fn integration_test_v3() {
+    const SIGNATURE_TYPE: &str = "android_bootimg";
+    const INPUT_FILE_NAME: &str = "android_bootimg_v3.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
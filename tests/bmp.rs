@@ -15,3 +15,34 @@ fn integration_test() {
         expected_extraction_offsets,
     );
 }
+
+#[test]
+fn truncated_test() {
+    let image =
+        std::fs::read(std::path::Path::new("tests").join("inputs").join("bmp.bin")).unwrap();
+
+    // Cut the first BMP image short
+    const BMP_OFFSET: usize = 0xB7F94;
+    let truncated = &image[..BMP_OFFSET + 0x200];
+
+    let binwalker =
+        binwalk_ng::Binwalk::configure(None, None, vec!["bmp".to_string()], vec![], None, false)
+            .expect("Binwalk initialization failed");
+
+    let analysis = binwalker.analyze_buf(truncated, "truncated.bin", false);
+    assert_eq!(analysis.file_map.len(), 1);
+    assert_eq!(analysis.file_map[0].offset, BMP_OFFSET);
+    assert_eq!(analysis.file_map[0].size, 0x200);
+    assert!(analysis.file_map[0].declared_size.unwrap() > 0x200);
+    assert!(analysis.file_map[0].extraction_declined);
+    assert_eq!(
+        analysis.file_map[0].confidence,
+        binwalk_ng::signatures::CONFIDENCE_LOW
+    );
+
+    assert_eq!(analysis.warnings.len(), 1);
+    assert_eq!(
+        analysis.warnings[0].code,
+        binwalk_ng::warnings::WarningCode::Truncated
+    );
+}
@@ -6,3 +6,10 @@ fn integration_test() {
     const INPUT_FILE_NAME: &str = "bzip2.bin";
     common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
 }
+
+#[test]
+fn integration_test_trailing_junk() {
+    const SIGNATURE_TYPE: &str = "bzip2";
+    const INPUT_FILE_NAME: &str = "bzip2_trailing_junk.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
@@ -39,6 +39,8 @@ pub fn assert_results_ok(
     );
     insta::assert_yaml_snapshot!(format!("{base}_file_map"), results.file_map, {
         "[].id" => "[uuid]",
+        "[].children[].id" => "[uuid]",
+        "[].children[].parent_id" => "[uuid]",
     });
 
     let ordered_extractions: Vec<Option<&ExtractionResult>> = results
@@ -48,6 +50,7 @@ pub fn assert_results_ok(
         .collect();
     insta::assert_yaml_snapshot!(format!("{base}_ordered_extractions"), ordered_extractions, {
         "[].output_directory" => "[output_directory]",
+        "[].extracted_files" => "[extracted_files]",
     });
 
     // Assert that the number of signature results and extractions match the expected results
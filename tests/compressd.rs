@@ -0,0 +1,15 @@
+mod common;
+
+#[test]
+fn integration_test() {
+    const SIGNATURE_TYPE: &str = "compressd";
+    const INPUT_FILE_NAME: &str = "compressd.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
+#[test]
+fn integration_test_clear_code() {
+    const SIGNATURE_TYPE: &str = "compressd";
+    const INPUT_FILE_NAME: &str = "compressd_clear.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
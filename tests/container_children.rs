@@ -0,0 +1,40 @@
+use binwalk_ng::Binwalk;
+
+#[test]
+fn trx_partitions_are_reported() {
+    // TRXv1 image with a 100 byte kernel and a 60 byte rootfs partition (see tests/inputs/gen_trx.sh)
+    let image = std::fs::read("tests/inputs/trx.bin").unwrap();
+
+    let results = Binwalk::new().scan(&image);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].name, "trx");
+
+    let children = &results[0].children;
+    assert_eq!(children.len(), 2);
+    assert_eq!((children[0].offset, children[0].size), (28, 100));
+    assert_eq!((children[1].offset, children[1].size), (128, 60));
+
+    for child in children {
+        assert_eq!(child.parent_id.as_ref(), Some(&results[0].id));
+    }
+}
+
+#[test]
+fn multi_file_uimage_images_are_reported() {
+    // Multi-file uImage holding a 10 byte and a 20 byte image (see tests/inputs/gen_uimage.sh)
+    let mut file_data = vec![0; 16];
+    file_data.extend(std::fs::read("tests/inputs/uimage_multi.bin").unwrap());
+
+    let results = Binwalk::new().scan(&file_data);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].name, "uimage");
+
+    // Image data starts after the 64-byte header and the 3-entry size table; images are 4-byte aligned
+    let children = &results[0].children;
+    assert_eq!(children.len(), 2);
+    assert_eq!((children[0].offset, children[0].size), (16 + 64 + 12, 10));
+    assert_eq!(
+        (children[1].offset, children[1].size),
+        (16 + 64 + 12 + 12, 20)
+    );
+}
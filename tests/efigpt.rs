@@ -0,0 +1,15 @@
+mod common;
+
+#[test]
+fn integration_test() {
+    const SIGNATURE_TYPE: &str = "efigpt";
+    const INPUT_FILE_NAME: &str = "efigpt.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
+#[test]
+fn integration_test_4k() {
+    const SIGNATURE_TYPE: &str = "efigpt_4k";
+    const INPUT_FILE_NAME: &str = "efigpt_4k.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
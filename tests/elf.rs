@@ -0,0 +1,17 @@
+mod common;
+
+#[test]
+fn integration_test() {
+    const SIGNATURE_TYPE: &str = "elf";
+    const INPUT_FILE_NAME: &str = "elf.bin";
+
+    let expected_signature_offsets: Vec<usize> = vec![0x20];
+    let expected_extraction_offsets: Vec<usize> = vec![];
+
+    let results = common::run_binwalk(SIGNATURE_TYPE, INPUT_FILE_NAME);
+    common::assert_results_ok(
+        results,
+        expected_signature_offsets,
+        expected_extraction_offsets,
+    );
+}
@@ -0,0 +1,47 @@
+use binwalk_ng::Binwalk;
+use binwalk_ng::encoded::BlobEncoding;
+
+#[test]
+fn encoded_gzip_is_identified() {
+    // Offsets of the base64 and hex encoded copies of gzip.bin (see tests/inputs/gen_encoded.sh)
+    const BASE64_OFFSET: usize = 18;
+    const HEX_OFFSET: usize = 177;
+
+    let gzip_data = std::fs::read("tests/inputs/gzip.bin").unwrap();
+    let file_data = std::fs::read("tests/inputs/encoded.bin").unwrap();
+
+    let mut binwalker = Binwalk::new();
+    binwalker.encoded_min_length = Some(64);
+
+    let results = binwalker.analyze_buf(&file_data, "config.txt", false);
+
+    assert_eq!(results.encoded.len(), 2);
+
+    assert_eq!(results.encoded[0].offset, BASE64_OFFSET);
+    assert_eq!(results.encoded[0].encoding, BlobEncoding::Base64);
+    assert_eq!(results.encoded[0].decoded_size, gzip_data.len());
+    assert_eq!(results.encoded[0].file_map.len(), 1);
+    assert_eq!(results.encoded[0].file_map[0].name, "gzip");
+
+    assert_eq!(results.encoded[1].offset, HEX_OFFSET);
+    assert_eq!(results.encoded[1].encoding, BlobEncoding::Hex);
+    assert_eq!(results.encoded[1].file_map[0].name, "gzip");
+}
+
+#[test]
+fn invalid_encodings_are_ignored() {
+    let mut binwalker = Binwalk::new();
+    binwalker.encoded_min_length = Some(16);
+
+    // Odd number of hex digits, and a base64 length that is not a multiple of 4
+    let file_data =
+        b"1f8b08000000000000031f8b0800000000000003a ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghi";
+
+    assert!(binwalk_ng::encoded::find_encoded_blobs(file_data, &[], 16).is_empty());
+    assert!(
+        binwalker
+            .analyze_buf(file_data, "config.txt", false)
+            .encoded
+            .is_empty()
+    );
+}
@@ -0,0 +1,111 @@
+#![cfg(unix)]
+
+use std::time::{Duration, Instant};
+
+use binwalk_ng::extractors::{self, ExtractionResult, Extractor, ExtractorType};
+use binwalk_ng::signatures::SignatureResult;
+
+/// Runs an external extractor against some dummy data
+fn run_external_extractor(extractor: Extractor) -> ExtractionResult {
+    let work_directory = tempfile::tempdir().unwrap();
+    let file_path = work_directory.path().join("firmware.bin");
+    let file_data = vec![0xAA; 64];
+    std::fs::write(&file_path, &file_data).unwrap();
+
+    let signature = SignatureResult {
+        name: "dummy".to_string(),
+        offset: 0,
+        size: file_data.len(),
+        ..Default::default()
+    };
+
+    extractors::execute(&file_data, &file_path, &signature, &Some(extractor))
+}
+
+/// Shell command extractor
+fn sh_extractor(script: &str) -> Extractor {
+    Extractor {
+        utility: ExtractorType::External("sh".to_string()),
+        arguments: vec!["-c".to_string(), script.to_string()],
+        ..Default::default()
+    }
+}
+
+/// An external extractor that outlives its timeout is killed, and its extraction fails
+#[test]
+fn hung_external_extractor_is_killed() {
+    let extractor = Extractor {
+        utility: ExtractorType::External("sleep".to_string()),
+        arguments: vec!["30".to_string()],
+        timeout: Some(Duration::from_millis(200)),
+        ..Default::default()
+    };
+
+    let start = Instant::now();
+    let result = run_external_extractor(extractor);
+
+    assert!(!result.success);
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+/// The output of a failed external extractor is kept, to show why it failed
+#[test]
+fn failed_external_extractor_output_is_captured() {
+    let result = run_external_extractor(sh_extractor(
+        "echo 'reading archive'; echo 'bad archive header' >&2; exit 3",
+    ));
+
+    assert!(!result.success);
+    assert_eq!(
+        result.extractor_output.as_deref(),
+        Some("reading archive\nbad archive header")
+    );
+}
+
+/// Overly long lines of external extractor output are truncated
+#[test]
+fn long_external_extractor_output_is_truncated() {
+    let result = run_external_extractor(sh_extractor(
+        "head -c 100000 /dev/zero | tr '\\0' x; echo; echo 'bad archive header'; exit 3",
+    ));
+
+    let long_line = "x".repeat(extractors::EXTRACTOR_OUTPUT_LINE_SIZE);
+    assert!(!result.success);
+    assert_eq!(
+        result.extractor_output,
+        Some(format!("{long_line}\nbad archive header"))
+    );
+}
+
+/// An external extractor that exits cleanly succeeds
+#[test]
+fn clean_exit_succeeds() {
+    let result = run_external_extractor(sh_extractor("echo data > extracted.bin"));
+
+    assert!(result.success);
+}
+
+/// An external extractor that exits nonzero fails, even if it extracted something before erroring out
+#[test]
+fn nonzero_exit_fails() {
+    let result = run_external_extractor(sh_extractor("echo data > extracted.bin; exit 1"));
+
+    assert!(!result.success);
+}
+
+/// Nonzero exit codes are accepted from external extractors that are expected to return them
+#[test]
+fn allowed_nonzero_exit_succeeds() {
+    let mut extractor = sh_extractor("echo data > extracted.bin; exit 2");
+    extractor.exit_codes = vec![0, 2];
+    assert!(run_external_extractor(extractor).success);
+
+    let mut extractor = sh_extractor("echo data > extracted.bin; exit 1");
+    extractor.allow_nonzero_exit = true;
+    assert!(run_external_extractor(extractor).success);
+
+    // An extractor killed by a signal did not exit, so it still fails
+    let mut extractor = sh_extractor("echo data > extracted.bin; kill -9 $$");
+    extractor.allow_nonzero_exit = true;
+    assert!(!run_external_extractor(extractor).success);
+}
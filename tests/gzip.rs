@@ -6,3 +6,174 @@ fn integration_test() {
     const INPUT_FILE_NAME: &str = "gzip.bin";
     common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
 }
+
+#[test]
+fn integration_test_multi_member() {
+    const SIGNATURE_TYPE: &str = "gzip";
+    const INPUT_FILE_NAME: &str = "gzip_multi_member.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
+#[test]
+fn integration_test_truncated() {
+    const SIGNATURE_TYPE: &str = "gzip";
+    const INPUT_FILE_NAME: &str = "gzip_truncated.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
+#[test]
+fn integration_test_bad_crc() {
+    const SIGNATURE_TYPE: &str = "gzip";
+    const INPUT_FILE_NAME: &str = "gzip_bad_crc.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
+#[test]
+fn concatenated_members_test() {
+    let image = std::fs::read(
+        std::path::Path::new("tests")
+            .join("inputs")
+            .join("gzip.bin"),
+    )
+    .unwrap();
+    let concatenated = [image.as_slice(), image.as_slice()].concat();
+
+    let binwalker = binwalk_ng::Binwalk::configure(None, None, vec![], vec![], None, false)
+        .expect("Binwalk initialization failed");
+    let results = binwalker.scan(&concatenated);
+
+    // Like gunzip, concatenated members are treated as a single gzip file
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].size, concatenated.len());
+    assert_eq!(results[0].trailer_error, None);
+}
+
+#[test]
+fn damaged_trailer_test() {
+    let bad_crc = std::fs::read(
+        std::path::Path::new("tests")
+            .join("inputs")
+            .join("gzip_bad_crc.bin"),
+    )
+    .unwrap();
+    let image = std::fs::read(
+        std::path::Path::new("tests")
+            .join("inputs")
+            .join("gzip.bin"),
+    )
+    .unwrap();
+
+    let mut binwalker = binwalk_ng::Binwalk::configure(None, None, vec![], vec![], None, false)
+        .expect("Binwalk initialization failed");
+
+    // The damaged trailer is reported as an analysis warning
+    let analysis = binwalker.analyze_buf(&bad_crc, "gzip_bad_crc.bin", false);
+    assert_eq!(analysis.warnings.len(), 1);
+    assert_eq!(
+        analysis.warnings[0].code,
+        binwalk_ng::warnings::WarningCode::InvalidTrailer
+    );
+    assert_eq!(analysis.warnings[0].offset, Some(0));
+    assert_eq!(
+        analysis.warnings[0].signature_id.as_ref(),
+        Some(&analysis.file_map[0].id)
+    );
+
+    // Low confidence matches are hidden when a higher minimum confidence is requested
+    binwalker.min_confidence = binwalk_ng::signatures::CONFIDENCE_MEDIUM;
+    assert!(binwalker.scan(&bad_crc).is_empty());
+    assert_eq!(binwalker.scan(&image).len(), 1);
+}
+
+#[test]
+fn reproducible_ids_test() {
+    let image = std::fs::read(
+        std::path::Path::new("tests")
+            .join("inputs")
+            .join("gzip.bin"),
+    )
+    .unwrap();
+
+    let mut binwalker = binwalk_ng::Binwalk::configure(None, None, vec![], vec![], None, false)
+        .expect("Binwalk initialization failed");
+    binwalker.reproducible = true;
+
+    assert_eq!(binwalker.scan(&image)[0].id, binwalker.scan(&image)[0].id);
+}
+
+#[test]
+fn decompress_input_test() {
+    use std::io::Write;
+
+    let image = std::fs::read(
+        std::path::Path::new("tests")
+            .join("inputs")
+            .join("gzip.bin"),
+    )
+    .unwrap();
+
+    // Wrap the gzip image in an outer xz stream
+    let mut encoder = liblzma::write::XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(&image).unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let target_file = temp_dir.path().join("gzip.bin.xz");
+    std::fs::write(&target_file, encoder.finish().unwrap()).unwrap();
+
+    let mut binwalker =
+        binwalk_ng::Binwalk::configure(Some(&target_file), None, vec![], vec![], None, false)
+            .expect("Binwalk initialization failed");
+
+    let analysis = binwalker.analyze(&binwalker.base_target_file, false);
+    assert_eq!(analysis.file_map.len(), 1);
+    assert_eq!(analysis.file_map[0].name, "xz");
+
+    // Offsets are relative to the decompressed data
+    binwalker.decompress_input = true;
+    let analysis = binwalker.analyze(&binwalker.base_target_file, false);
+    assert_eq!(analysis.file_map.len(), 1);
+    assert_eq!(analysis.file_map[0].name, "gzip");
+    assert_eq!(analysis.file_map[0].offset, 0);
+    assert_eq!(analysis.file_map[0].size, image.len());
+}
+
+#[test]
+fn max_results_test() {
+    let image = std::fs::read(
+        std::path::Path::new("tests")
+            .join("inputs")
+            .join("gzip.bin"),
+    )
+    .unwrap();
+    // Padding between the images keeps them from being treated as members of a single gzip file
+    let padding = [0xFF; 16];
+    let triple_bank = [
+        image.as_slice(),
+        &padding,
+        image.as_slice(),
+        &padding,
+        image.as_slice(),
+    ]
+    .concat();
+
+    let mut binwalker = binwalk_ng::Binwalk::configure(None, None, vec![], vec![], None, false)
+        .expect("Binwalk initialization failed");
+    binwalker.max_results = Some(2);
+
+    let analysis = binwalker.analyze_buf(&triple_bank, "triple_bank.bin", false);
+    assert_eq!(analysis.file_map.len(), 2);
+    assert_eq!(analysis.file_map[1].offset, image.len() + padding.len());
+    assert_eq!(analysis.warnings.len(), 1);
+    assert_eq!(
+        analysis.warnings[0].code,
+        binwalk_ng::warnings::WarningCode::ResultsTruncated
+    );
+
+    binwalker.max_results = Some(1);
+    assert_eq!(binwalker.scan(&triple_bank).len(), 1);
+
+    // Nothing is truncated if there are no more results than the maximum
+    binwalker.max_results = Some(3);
+    let analysis = binwalker.analyze_buf(&triple_bank, "triple_bank.bin", false);
+    assert_eq!(analysis.file_map.len(), 3);
+    assert!(analysis.warnings.is_empty());
+}
@@ -0,0 +1,8 @@
+mod common;
+
+#[test]
+fn integration_test() {
+    const SIGNATURE_TYPE: &str = "ihex";
+    const INPUT_FILE_NAME: &str = "ihex.hex";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
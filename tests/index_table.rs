@@ -0,0 +1,71 @@
+use binwalk_ng::Binwalk;
+use binwalk_ng::index_table::default_layouts;
+
+/// Fat firmware image: a big endian index table with 32 byte model names preceding the offset and size fields,
+/// followed by an all-zero terminating entry and three gzip sub-images (see tests/inputs/gen_index_table.sh)
+const INPUT_FILE_NAME: &str = "tests/inputs/index_table.bin";
+
+/// Size of each sub-image, a copy of tests/inputs/gzip.bin
+const SUB_IMAGE_SIZE: usize = 109;
+
+#[test]
+fn index_table_sub_images_are_reported() {
+    let file_data = std::fs::read(INPUT_FILE_NAME).unwrap();
+
+    let mut binwalker = Binwalk::new();
+    binwalker.index_table_layouts = default_layouts();
+
+    let results = binwalker.analyze_buf(&file_data, "fat.bin", false);
+    let index_table = results.index_table.unwrap();
+
+    assert_eq!(index_table.layout, "model32_offset_size_be");
+    assert_eq!(index_table.offset, 0);
+    assert_eq!(index_table.size, 120);
+
+    let models: Vec<&str> = index_table
+        .entries
+        .iter()
+        .map(|entry| entry.model.as_str())
+        .collect();
+    assert_eq!(models, ["MT7621", "IPQ4019", "BCM4708"]);
+
+    // Each sub-image is also identified by the signature scan
+    for entry in &index_table.entries {
+        assert_eq!(entry.size, SUB_IMAGE_SIZE);
+        assert!(
+            results
+                .file_map
+                .iter()
+                .any(|result| result.offset == entry.offset && result.name == "gzip")
+        );
+    }
+}
+
+#[test]
+fn implausible_index_tables_are_ignored() {
+    let mut file_data = std::fs::read(INPUT_FILE_NAME).unwrap();
+
+    // Make the second entry overlap the first
+    let first_offset = u32::from_be_bytes(file_data[32..36].try_into().unwrap());
+    file_data[72..76].copy_from_slice(&(first_offset + 32).to_be_bytes());
+
+    let mut binwalker = Binwalk::new();
+    binwalker.index_table_layouts = default_layouts();
+
+    assert_eq!(
+        binwalker
+            .analyze_buf(&file_data, "fat.bin", false)
+            .index_table,
+        None
+    );
+
+    // No index table is looked for unless layouts are configured
+    let file_data = std::fs::read(INPUT_FILE_NAME).unwrap();
+
+    assert_eq!(
+        Binwalk::new()
+            .analyze_buf(&file_data, "fat.bin", false)
+            .index_table,
+        None
+    );
+}
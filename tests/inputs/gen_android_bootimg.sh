@@ -0,0 +1,58 @@
+#!/bin/bash
+#
+# Generates the Android boot images used by tests/android_bootimg.rs and the unit tests in
+# src/formats/android_bootimg.rs:
+#
+#   android_bootimg.bin     header version 2, 2048 byte pages: a 100 byte kernel, a 50 byte ramdisk and a 30 byte DTB
+#   android_bootimg_v3.bin  header version 3, 4096 byte pages: a 5000 byte kernel and a 200 byte ramdisk
+#
+# Each section is filled with its initial (K, R or D), and both images report Android 11.0.0, patch level 2021-03.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+OS_VERSION = (11 << 25) | (21 << 4) | 3
+
+
+def pad(data, page_size):
+    return data + bytes(-len(data) % page_size)
+
+
+def bootimg_v2():
+    page_size = 2048
+
+    header = b"ANDROID!" + struct.pack(
+        "<10I",
+        100,         # kernel size
+        0x10008000,  # kernel load address
+        50,          # ramdisk size
+        0x11000000,  # ramdisk load address
+        0,           # second size
+        0x10F00000,  # second load address
+        0x10000100,  # tags address
+        page_size,
+        2,           # header version
+        OS_VERSION,
+    )
+    header = header.ljust(1632, b"\x00")  # name, cmdline, id, extra_cmdline
+    header += struct.pack("<IQIIQ", 0, 0, 1660, 30, 0x11F00000)  # recovery dtbo, header size, dtb
+
+    return b"".join(pad(section, page_size) for section in [header, b"K" * 100, b"R" * 50, b"D" * 30])
+
+
+def bootimg_v3():
+    page_size = 4096
+
+    header = b"ANDROID!" + struct.pack("<4I", 5000, 200, OS_VERSION, 1580) + bytes(16) + struct.pack("<I", 3)
+
+    return b"".join(pad(section, page_size) for section in [header, b"K" * 5000, b"R" * 200])
+
+
+with open("android_bootimg.bin", "wb") as f:
+    f.write(bootimg_v2())
+
+with open("android_bootimg_v3.bin", "wb") as f:
+    f.write(bootimg_v3())
+PY
@@ -0,0 +1,17 @@
+#!/bin/bash
+#
+# Generates the Autel obfuscated firmware fixture used by tests/autel.rs and the unit tests in src/formats/autel.rs:
+# a 32 byte ECC header followed by 300 bytes of encoded data (one full 256 byte block and a partial block).
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+encoded_data = bytes(n & 0xFF for n in range(300))
+
+header = b"ECC0101\x00" + struct.pack("<II", len(encoded_data), 0x20) + b"Copyright Autel\x00"
+
+with open("autel.bin", "wb") as f:
+    f.write(header + encoded_data)
+PY
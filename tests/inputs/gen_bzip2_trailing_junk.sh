@@ -0,0 +1,22 @@
+#!/bin/bash
+#
+# Generates the bzip2 fixture used by tests/bzip2.rs and the unit tests in src/formats/bzip2.rs.
+#
+# The bzip2 stream at the start of bzip2.bin, followed by trailing junk that starts out looking like another
+# bzip2 stream.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import bz2
+
+with open("bzip2.bin", "rb") as f:
+    data = f.read()
+
+decompressor = bz2.BZ2Decompressor()
+decompressor.decompress(data)
+stream = data[: len(data) - len(decompressor.unused_data)]
+
+with open("bzip2_trailing_junk.bin", "wb") as f:
+    f.write(stream + b"BZh9junk junk junk")
+PY
@@ -0,0 +1,41 @@
+#!/bin/bash
+#
+# Generates the cabinet fixture used by tests/cab.rs and the unit tests in src/formats/cab.rs.
+#
+# The cabinet has one uncompressed folder, holding a single data block and a single file: a.txt, "hello\n".
+# The data block has no checksum.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+HEADER_SIZE = 36
+FOLDER_SIZE = 8
+FILE_ENTRY_SIZE = 22
+FILE_DATA = b"hello\n"
+COMPRESSION_NONE = 0
+
+first_file_offset = HEADER_SIZE + FOLDER_SIZE
+data_offset = first_file_offset + FILE_ENTRY_SIZE
+cabinet_size = data_offset + 8 + len(FILE_DATA)
+
+# CFHEADER: version 1.3, one folder, one file, no flags
+cab = b"MSCF\x00\x00\x00\x00"
+cab += struct.pack("<IIII", cabinet_size, 0, first_file_offset, 0)
+cab += struct.pack("<BBHHHHH", 3, 1, 1, 1, 0, 0, 0)
+
+# CFFOLDER
+cab += struct.pack("<IHH", data_offset, 1, COMPRESSION_NONE)
+
+# CFFILE
+cab += struct.pack("<IIHHHH", len(FILE_DATA), 0, 0, 0, 0, 0) + b"a.txt\x00"
+
+# CFDATA
+cab += struct.pack("<IHH", 0, len(FILE_DATA), len(FILE_DATA)) + FILE_DATA
+
+assert len(cab) == cabinet_size
+
+with open("cab.bin", "wb") as f:
+    f.write(cab)
+PY
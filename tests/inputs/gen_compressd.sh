@@ -0,0 +1,98 @@
+#!/bin/bash
+#
+# Generates the compress'd (.Z) fixtures used by tests/compressd.rs and the unit tests in src/formats/compressd.rs:
+#
+#   compressd.bin        16-bit maximum code size; code widths grow from 9 to 11 bits
+#   compressd_clear.bin  10-bit maximum code size; a CLEAR code is emitted each time the table fills
+#
+# The ncompress `compress` utility isn't commonly installed, so this uses an equivalent LZW
+# encoder (including ncompress's padding of the code stream whenever the code width changes),
+# and verifies the output with `gzip -dc`, which is also able to decompress .Z files.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+INIT_BITS = 9
+CLEAR = 256
+
+
+def compress(data, max_bits, emit_clear):
+    header = bytes([0x1F, 0x9D, 0x80 | max_bits])
+    body = bytearray()
+    state = {"acc": 0, "nacc": 0, "bitpos": 0, "segment_start": 0}
+
+    def put_bits(value, count):
+        state["acc"] |= value << state["nacc"]
+        state["nacc"] += count
+        state["bitpos"] += count
+        while state["nacc"] >= 8:
+            body.append(state["acc"] & 0xFF)
+            state["acc"] >>= 8
+            state["nacc"] -= 8
+
+    # Codes are written in groups of n_bits bytes; changing the code width pads out the current group
+    def align(n_bits):
+        group_bits = n_bits * 8
+        used = state["bitpos"] - state["segment_start"]
+        if used % group_bits:
+            put_bits(0, group_bits - (used % group_bits))
+        state["segment_start"] = state["bitpos"]
+
+    max_max_code = 1 << max_bits
+    n_bits = INIT_BITS
+    max_code = (1 << n_bits) - 1
+    free_ent = 257
+    table = {}
+
+    prefix = data[0]
+    for c in data[1:]:
+        if (prefix, c) in table:
+            prefix = table[(prefix, c)]
+            continue
+
+        put_bits(prefix, n_bits)
+        if free_ent > max_code and n_bits < max_bits:
+            align(n_bits)
+            n_bits += 1
+            max_code = max_max_code if n_bits == max_bits else (1 << n_bits) - 1
+
+        if free_ent < max_max_code:
+            table[(prefix, c)] = free_ent
+            free_ent += 1
+        elif emit_clear:
+            put_bits(CLEAR, n_bits)
+            align(n_bits)
+            n_bits = INIT_BITS
+            max_code = (1 << n_bits) - 1
+            free_ent = 257
+            table.clear()
+
+        prefix = c
+
+    put_bits(prefix, n_bits)
+    if state["nacc"]:
+        body.append(state["acc"] & 0xFF)
+
+    return header + bytes(body)
+
+
+# Keep in sync with src/formats/compressd.rs
+data = "".join(
+    f"Line {n}: the quick brown fox jumps over the lazy dog {n * n}\n" for n in range(200)
+).encode()
+
+with open("compressd.bin", "wb") as f:
+    f.write(compress(data, 16, False))
+
+with open("compressd_clear.bin", "wb") as f:
+    f.write(compress(data, 10, True))
+
+with open("compressd.txt", "wb") as f:
+    f.write(data)
+PY
+
+for fixture in compressd.bin compressd_clear.bin; do
+    gzip -dc < "$fixture" | cmp - compressd.txt || exit 1
+done
+
+rm -f compressd.txt
@@ -0,0 +1,61 @@
+#!/bin/bash
+#
+# Generates cpio.bin, the newc CPIO archive used by tests/cpio.rs and the unit tests in src/formats/cpio.rs:
+#
+#   etc            directory
+#   etc/hostname   regular file, "router\n"
+#   hostname       symlink to etc/hostname
+#   bin/sh         hard link to bin/busybox (carries no data)
+#   bin/busybox    regular file, mode 0755, "\x7FELF"
+#   TRAILER!!!     end of archive
+#
+# Headers and data are each padded to 4-byte boundaries; the archive is 752 bytes.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+archive = bytearray()
+
+
+def pad(data):
+    data.extend(b"\x00" * (-len(data) % 4))
+
+
+def append_entry(inode, mode, link_count, name, data):
+    name = name.encode() + b"\x00"
+    fields = [
+        inode,
+        mode,
+        0,  # uid
+        0,  # gid
+        link_count,
+        0,  # mtime
+        len(data),
+        0,  # dev major
+        0,  # dev minor
+        0,  # rdev major
+        0,  # rdev minor
+        len(name),
+        0,  # check
+    ]
+
+    archive.extend(b"070701")
+    for field in fields:
+        archive.extend(b"%08X" % field)
+    archive.extend(name)
+    pad(archive)
+    archive.extend(data)
+    pad(archive)
+
+
+append_entry(1, 0o040755, 2, "etc", b"")
+append_entry(2, 0o100644, 1, "etc/hostname", b"router\n")
+append_entry(3, 0o120777, 1, "hostname", b"etc/hostname")
+# Only the last hard link carries the file data
+append_entry(4, 0o100755, 2, "bin/sh", b"")
+append_entry(4, 0o100755, 2, "bin/busybox", b"\x7FELF")
+append_entry(0, 0, 1, "TRAILER!!!", b"")
+
+with open("cpio.bin", "wb") as f:
+    f.write(archive)
+PY
@@ -0,0 +1,71 @@
+#!/bin/bash
+#
+# Generates the device tree blob used by tests/dtb.rs and the unit tests in src/formats/dtb.rs.
+# The blob describes this tree:
+#
+#   / {
+#       model = "binwalk-test";
+#       chosen {
+#           bootargs = "console=ttyS0";
+#       };
+#   };
+#
+# The header is followed by an empty memory reservation block, the structure block, and the strings block.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+FDT_BEGIN_NODE = 1
+FDT_END_NODE = 2
+FDT_PROP = 3
+FDT_END = 9
+
+HEADER_SIZE = 40
+MEM_RESERVATION_SIZE = 16
+
+strings = b"model\0bootargs\0"
+
+
+def aligned(data):
+    return data + b"\0" * (-len(data) % 4)
+
+
+def begin_node(name):
+    return struct.pack(">I", FDT_BEGIN_NODE) + aligned(name + b"\0")
+
+
+def prop(name, value):
+    return struct.pack(">III", FDT_PROP, len(value), strings.index(name + b"\0")) + aligned(value)
+
+
+dt_struct = (
+    begin_node(b"")
+    + prop(b"model", b"binwalk-test\0")
+    + begin_node(b"chosen")
+    + prop(b"bootargs", b"console=ttyS0\0")
+    + struct.pack(">III", FDT_END_NODE, FDT_END_NODE, FDT_END)
+)
+
+struct_offset = HEADER_SIZE + MEM_RESERVATION_SIZE
+strings_offset = struct_offset + len(dt_struct)
+total_size = strings_offset + len(strings)
+
+header = struct.pack(
+    ">10I",
+    0xD00DFEED,
+    total_size,
+    struct_offset,
+    strings_offset,
+    HEADER_SIZE,
+    17,
+    16,
+    0,
+    len(strings),
+    len(dt_struct),
+)
+
+with open("dtb.bin", "wb") as f:
+    f.write(header + b"\0" * MEM_RESERVATION_SIZE + dt_struct + strings)
+PY
@@ -0,0 +1,76 @@
+#!/bin/bash
+#
+# Generates the EFI GPT disk images used by tests/efigpt.rs and the unit tests in src/formats/efigpt.rs:
+#
+#   efigpt.bin     512 byte sectors
+#   efigpt_4k.bin  4096 byte sectors
+#
+# Both are eight sectors long: a protective MBR, the GPT header, one sector of four partition entries, an unused
+# sector, and two Linux file system partitions, "part0" (LBAs 4-5, filled with 0xAA) and "part1" (LBAs 6-7, filled
+# with 0xBB). The GUIDs are pinned, so the images are reproducible.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+import uuid
+import zlib
+
+SECTOR_COUNT = 8
+PARTITION_ENTRY_COUNT = 4
+PARTITION_ENTRY_SIZE = 128
+HEADER_SIZE = 92
+
+LINUX_FILESYSTEM = uuid.UUID("0FC63DAF-8483-4772-8E79-3D69D8477DE4")
+DISK_GUID = uuid.UUID("01234567-89AB-CDEF-0123-456789ABCDEF")
+
+PARTITIONS = [
+    ("part0", 4, 5, 0xAA),
+    ("part1", 6, 7, 0xBB),
+]
+
+
+def protective_mbr():
+    mbr = bytearray(512)
+    # A single partition of type 0xEE covers the entire disk
+    mbr[0x1BE:0x1CE] = struct.pack("<B3sB3sII", 0, b"\x00\x02\x00", 0xEE, b"\xFF\xFF\xFF", 1, SECTOR_COUNT - 1)
+    mbr[0x1FE:0x200] = b"\x55\xAA"
+    return bytes(mbr)
+
+
+def gpt_image(sector_size):
+    image = bytearray(sector_size * SECTOR_COUNT)
+    image[:512] = protective_mbr()
+
+    entries = bytearray(PARTITION_ENTRY_COUNT * PARTITION_ENTRY_SIZE)
+    for i, (name, start_lba, end_lba, fill) in enumerate(PARTITIONS):
+        partition_guid = uuid.UUID(int=DISK_GUID.int + i + 1)
+        entries[i * PARTITION_ENTRY_SIZE:(i + 1) * PARTITION_ENTRY_SIZE] = (
+            LINUX_FILESYSTEM.bytes_le
+            + partition_guid.bytes_le
+            + struct.pack("<QQQ", start_lba, end_lba, 0)
+            + name.encode("utf-16-le").ljust(72, b"\x00")
+        )
+        image[start_lba * sector_size:(end_lba + 1) * sector_size] = bytes([fill]) * (sector_size * 2)
+
+    image[sector_size * 2:sector_size * 2 + len(entries)] = entries
+
+    header = bytearray(
+        b"EFI PART"
+        + struct.pack("<IIII", 0x00010000, HEADER_SIZE, 0, 0)
+        + struct.pack("<QQQQ", 1, SECTOR_COUNT - 1, 4, SECTOR_COUNT - 1)
+        + DISK_GUID.bytes_le
+        + struct.pack("<QIII", 2, PARTITION_ENTRY_COUNT, PARTITION_ENTRY_SIZE, zlib.crc32(entries))
+    )
+    header[16:20] = struct.pack("<I", zlib.crc32(header))
+    image[sector_size:sector_size + HEADER_SIZE] = header
+
+    return bytes(image)
+
+
+with open("efigpt.bin", "wb") as f:
+    f.write(gpt_image(512))
+
+with open("efigpt_4k.bin", "wb") as f:
+    f.write(gpt_image(4096))
+PY
@@ -0,0 +1,58 @@
+#!/bin/bash
+#
+# Generates the ELF sample used by tests/elf.rs and the unit tests in src/formats/elf.rs: a 64-bit little endian
+# x86-64 executable, embedded 0x20 bytes into a blob of 0xFF padding. The ELF has a single loadable segment and
+# four sections:
+#
+#   0x000  ELF header, followed by the program header table
+#   0x100  .text
+#   0x120  .shstrtab (.bss also starts here, but occupies no file space)
+#   0x140  section header table (NULL, .text, .bss, .shstrtab), ending at 0x240
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+SECTION_HEADERS_OFFSET = 0x140
+SECTION_COUNT = 4
+
+
+def section_header(section_type, offset, size):
+    return struct.pack("<IIQQQQ", 0, section_type, 0, 0, offset, size).ljust(64, b"\0")
+
+
+elf = b"\x7FELF\x02\x01\x01\x00".ljust(16, b"\0")
+elf += struct.pack(
+    "<HHIQQQIHHHHHH",
+    2,  # executable
+    0x3E,  # x86-64
+    1,  # version
+    0x400100,  # entry point
+    64,  # program header offset
+    SECTION_HEADERS_OFFSET,
+    0,  # flags
+    64,  # ELF header size
+    56,  # program header entry size
+    1,  # program header count
+    64,  # section header entry size
+    SECTION_COUNT,
+    3,  # section name string table index
+)
+
+# A single loadable segment, covering the ELF header through the end of .text
+elf += struct.pack("<IIQQQQQQ", 1, 5, 0, 0x400000, 0x400000, 0x120, 0x120, 0x1000)
+
+elf = elf.ljust(0x100, b"\0")
+elf += b"\xC3" * 0x20
+elf += b"\0.text\0.bss\0.shstrtab\0"
+elf = elf.ljust(SECTION_HEADERS_OFFSET, b"\0")
+
+elf += section_header(0, 0, 0)
+elf += section_header(1, 0x100, 0x20)
+elf += section_header(8, 0x120, 0x1000)
+elf += section_header(3, 0x120, 0x16)
+
+with open("elf.bin", "wb") as f:
+    f.write(b"\xFF" * 0x20 + elf + b"\xFF" * 0x400)
+PY
@@ -0,0 +1,19 @@
+#!/bin/bash
+#
+# Generates the configuration dump used by tests/encoded.rs: an INI style text file holding gzip.bin twice, once as
+# line-wrapped base64 (76 characters per line, as found in PEM files and configuration dumps), and once as hex.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import base64
+import textwrap
+
+with open("gzip.bin", "rb") as f:
+    gzip_data = f.read()
+
+wrapped = "\n".join(textwrap.wrap(base64.b64encode(gzip_data).decode(), 76))
+
+with open("encoded.bin", "w") as f:
+    f.write(f"[firmware]\nimage=\n{wrapped}\nchecksum={gzip_data.hex()}\n")
+PY
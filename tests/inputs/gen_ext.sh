@@ -0,0 +1,21 @@
+#!/bin/bash
+#
+# Generates the ext2 file system image used by tests/ext.rs and the unit tests in src/formats/ext.rs: 64 blocks of
+# 1KiB, 16 inodes, a volume name of "rootfs", and a single file, hello.txt.
+#
+# The UUID, hash seed and all timestamps are pinned, so the image is reproducible.
+
+cd "$(dirname "$0")" || exit 1
+
+root=$(mktemp -d)
+trap 'rm -rf "$root"' EXIT
+
+printf 'Hello, ext2!\n' > "$root/hello.txt"
+touch -d @1700000000 "$root/hello.txt" "$root"
+
+rm -f ext.bin
+truncate -s 64K ext.bin
+E2FSPROGS_FAKE_TIME=1700000000 mke2fs -q -F -t ext2 -b 1024 -N 16 -m 5 -L rootfs \
+    -U 01234567-89ab-cdef-0123-456789abcdef \
+    -E hash_seed=01234567-89ab-cdef-0123-456789abcdef,root_owner=0:0 \
+    -d "$root" ext.bin 64 || exit $?
@@ -0,0 +1,108 @@
+#!/bin/bash
+#
+# Generates the FAT16 fixture used by tests/fat.rs and the unit tests in src/formats/fat.rs.
+#
+# The image has 4200 512-byte sectors, one sector per cluster, and contains:
+#
+#   A long file name.txt  700 bytes of (n % 251), in clusters 3 and 5 (not contiguous); short name ALONGF~1.TXT
+#   SUBDIR/INNER.TXT      "inner\n", in cluster 4; SUBDIR is in cluster 2
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+SECTOR_SIZE = 512
+TOTAL_SECTORS = 4200
+RESERVED_SECTORS = 1
+FAT_SECTORS = 17
+ROOT_ENTRIES = 512
+ROOT_DIRECTORY_OFFSET = (RESERVED_SECTORS + 2 * FAT_SECTORS) * SECTOR_SIZE
+DATA_OFFSET = ROOT_DIRECTORY_OFFSET + ROOT_ENTRIES * 32
+
+LONG_NAME = "A long file name.txt"
+SHORT_NAME = b"ALONGF~1TXT"
+
+FILE_DATA = bytes(n % 251 for n in range(700))
+
+
+def boot_sector():
+    boot_sector = b"\xEB\x3C\x90MSDOS5.0"
+    boot_sector += struct.pack(
+        "<HBHBHHBH",
+        SECTOR_SIZE,
+        1,  # sectors per cluster
+        RESERVED_SECTORS,
+        2,  # FAT count
+        ROOT_ENTRIES,
+        TOTAL_SECTORS,
+        0xF8,  # media descriptor
+        FAT_SECTORS,
+    )
+    return boot_sector.ljust(SECTOR_SIZE - 2, b"\x00") + b"\x55\xAA"
+
+
+def directory_entry(name, attributes, cluster, size):
+    return name + bytes([attributes]) + b"\x00" * 14 + struct.pack("<HI", cluster, size)
+
+
+def long_name_entries():
+    """Long file name entries for LONG_NAME, in the order they are stored on disk"""
+    checksum = 0
+    for byte in SHORT_NAME:
+        checksum = ((checksum >> 1) | (checksum << 7)) & 0xFF
+        checksum = (checksum + byte) & 0xFF
+
+    # The name is NULL terminated, then padded with 0xFFFF
+    name = LONG_NAME.encode("utf-16-le") + b"\x00\x00"
+    name = name.ljust(26 * 2, b"\xFF")
+
+    entries = []
+    for index in range(2):
+        chars = name[index * 26 : (index + 1) * 26]
+        order = index + 1
+        if index == 1:
+            order |= 0x40
+        entries.append(
+            bytes([order]) + chars[:10] + bytes([0x0F, 0, checksum]) + chars[10:22] + b"\x00\x00" + chars[22:]
+        )
+
+    return b"".join(reversed(entries))
+
+
+def cluster_offset(cluster):
+    return DATA_OFFSET + (cluster - 2) * SECTOR_SIZE
+
+
+image = bytearray(TOTAL_SECTORS * SECTOR_SIZE)
+
+
+def write(offset, data):
+    image[offset : offset + len(data)] = data
+
+
+write(0, boot_sector())
+
+# Sub-directory in cluster 2, inner file in cluster 4, long-named file in clusters 3 and 5
+fat = struct.pack("<6H", 0xFFF8, 0xFFFF, 0xFFFF, 5, 0xFFFF, 0xFFFF)
+for fat_sector in (RESERVED_SECTORS, RESERVED_SECTORS + FAT_SECTORS):
+    write(fat_sector * SECTOR_SIZE, fat)
+
+root = directory_entry(b"TEST_VOLUME", 0x08, 0, 0)
+root += long_name_entries()
+root += directory_entry(SHORT_NAME, 0x20, 3, len(FILE_DATA))
+root += directory_entry(b"SUBDIR     ", 0x10, 2, 0)
+write(ROOT_DIRECTORY_OFFSET, root)
+
+subdir = directory_entry(b".          ", 0x10, 2, 0)
+subdir += directory_entry(b"..         ", 0x10, 0, 0)
+subdir += directory_entry(b"INNER   TXT", 0x20, 4, 6)
+write(cluster_offset(2), subdir)
+
+write(cluster_offset(4), b"inner\n")
+write(cluster_offset(3), FILE_DATA[:SECTOR_SIZE])
+write(cluster_offset(5), FILE_DATA[SECTOR_SIZE:])
+
+with open("fat16.bin", "wb") as f:
+    f.write(image)
+PY
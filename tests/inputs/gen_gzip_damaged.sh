@@ -0,0 +1,25 @@
+#!/bin/bash
+#
+# Generates the damaged gzip fixtures used by tests/gzip.rs and the unit tests in src/formats/gzip.rs, from gzip.bin:
+#
+#   gzip_truncated.bin: gzip.bin without its trailing CRC and ISIZE fields, as if the stream were cut short
+#   gzip_bad_crc.bin:   gzip.bin with the low byte of its CRC field inverted
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+# Size of the CRC and ISIZE fields at the end of a gzip member
+TRAILER_SIZE = 8
+
+with open("gzip.bin", "rb") as f:
+    data = f.read()
+
+with open("gzip_truncated.bin", "wb") as f:
+    f.write(data[:-TRAILER_SIZE])
+
+bad_crc = bytearray(data)
+bad_crc[-TRAILER_SIZE] ^= 0xFF
+
+with open("gzip_bad_crc.bin", "wb") as f:
+    f.write(bad_crc)
+PY
@@ -0,0 +1,25 @@
+#!/bin/bash
+#
+# Generates the multi-member gzip fixture used by tests/gzip.rs and the unit tests in src/formats/gzip.rs.
+#
+# Equivalent to `cat first.gz second.gz`: two gzip members, with no original file names and a timestamp of 0,
+# which gunzip decompresses to "first member\nsecond member\n".
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import gzip
+import io
+
+
+def gzip_member(data):
+    member = io.BytesIO()
+    with gzip.GzipFile(filename="", mode="wb", fileobj=member, mtime=0) as f:
+        f.write(data)
+    return member.getvalue()
+
+
+with open("gzip_multi_member.bin", "wb") as f:
+    f.write(gzip_member(b"first member\n"))
+    f.write(gzip_member(b"second member\n"))
+PY
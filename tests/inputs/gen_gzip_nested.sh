@@ -0,0 +1,19 @@
+#!/bin/bash
+#
+# Generates the nested gzip fixture used by tests/max_depth.rs: some uncompressed data, gzip compressed six times
+# over, so that each level of extraction yields another gzip stream.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import gzip
+
+LEVELS = 6
+
+data = b"This innermost data is not compressed"
+for _ in range(LEVELS):
+    data = gzip.compress(data, mtime=0)
+
+with open("gzip_nested.bin", "wb") as f:
+    f.write(data)
+PY
@@ -0,0 +1,29 @@
+#!/bin/bash
+#
+# Generates the fat firmware image used by tests/index_table.rs.
+#
+# A big endian index table of 40 byte entries (a 32 byte model name, then the offset and size of the model's
+# sub-image), terminated by an all-zero entry, followed by three sub-images; each sub-image is a copy of gzip.bin.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+ENTRY_SIZE = 40
+MODELS = [b"MT7621", b"IPQ4019", b"BCM4708"]
+
+with open("gzip.bin", "rb") as f:
+    sub_image = f.read()
+
+data_offset = (len(MODELS) + 1) * ENTRY_SIZE
+table = b""
+data = b""
+
+for model in MODELS:
+    table += model.ljust(32, b"\x00") + struct.pack(">II", data_offset + len(data), len(sub_image))
+    data += sub_image
+
+with open("index_table.bin", "wb") as f:
+    f.write(table.ljust(data_offset, b"\x00") + data)
+PY
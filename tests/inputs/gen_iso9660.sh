@@ -0,0 +1,109 @@
+#!/bin/bash
+#
+# Generates the ISO9660 fixtures used by tests/iso9660.rs and the unit tests in src/formats/iso9660.rs:
+#
+#   iso9660.bin             README.TXT;1, with no extensions
+#   iso9660_rock_ridge.bin  As above, plus a Rock Ridge name: "readme with a long name.txt"
+#   iso9660_joliet.bin      As above, plus a Joliet supplementary volume naming it "Joliet README.txt;1"
+#
+# Each image is 22 2048-byte blocks: the system area, the volume descriptors starting at block 16, the root
+# directories, and the file data in block 21.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+BLOCK_SIZE = 2048
+PVD_BLOCK = 16
+SVD_BLOCK = 17
+TERMINATOR_BLOCK = 18
+ROOT_BLOCK = 19
+JOLIET_ROOT_BLOCK = 20
+FILE_BLOCK = 21
+VOLUME_SIZE = 22
+
+FILE_DATA = b"ISO9660 test file contents\n"
+
+
+def both_endian_u16(value):
+    return struct.pack("<H", value) + struct.pack(">H", value)
+
+
+def both_endian_u32(value):
+    return struct.pack("<I", value) + struct.pack(">I", value)
+
+
+def pad_to_even(data):
+    return data + b"\x00" * (len(data) % 2)
+
+
+def directory_record(extent, size, directory, identifier, system_use=b""):
+    record = b"\x00\x00" + both_endian_u32(extent) + both_endian_u32(size) + b"\x00" * 7
+    record += bytes([0x02 if directory else 0]) + b"\x00\x00" + both_endian_u16(1)
+    record += bytes([len(identifier)]) + identifier
+    record = pad_to_even(pad_to_even(record) + system_use)
+    return bytes([len(record)]) + record[1:]
+
+
+def volume_descriptor(descriptor_type, escape_sequences, root_block):
+    descriptor = bytes([descriptor_type]) + b"CD001\x01\x00"
+    descriptor += b" " * 32 + b"TEST_VOLUME".ljust(32) + b"\x00" * 8
+    descriptor += both_endian_u32(VOLUME_SIZE) + escape_sequences
+    descriptor = descriptor.ljust(120, b"\x00")
+    descriptor += both_endian_u16(1) + both_endian_u16(1) + both_endian_u16(BLOCK_SIZE)
+    descriptor += both_endian_u32(0) + b"\x00" * 16
+    return descriptor + directory_record(root_block, BLOCK_SIZE, True, b"\x00")
+
+
+def build_iso(rock_ridge_name=None, joliet_name=None):
+    iso = bytearray(VOLUME_SIZE * BLOCK_SIZE)
+
+    def write(block, data):
+        start = block * BLOCK_SIZE
+        iso[start : start + len(data)] = data
+
+    write(PVD_BLOCK, volume_descriptor(1, b"", ROOT_BLOCK))
+
+    if joliet_name is None:
+        write(SVD_BLOCK, b"\xFFCD001\x01")
+    else:
+        write(SVD_BLOCK, volume_descriptor(2, b"%/E", JOLIET_ROOT_BLOCK))
+        write(TERMINATOR_BLOCK, b"\xFFCD001\x01")
+
+    # The SUSP "SP" entry in the root "." record identifies Rock Ridge
+    if rock_ridge_name is None:
+        dot_system_use, file_system_use = b"", b""
+    else:
+        name = rock_ridge_name.encode()
+        dot_system_use = b"SP\x07\x01\xBE\xEF\x00"
+        file_system_use = b"NM" + bytes([5 + len(name), 1, 0]) + name
+
+    root = directory_record(ROOT_BLOCK, BLOCK_SIZE, True, b"\x00", dot_system_use)
+    root += directory_record(ROOT_BLOCK, BLOCK_SIZE, True, b"\x01")
+    root += directory_record(FILE_BLOCK, len(FILE_DATA), False, b"README.TXT;1", file_system_use)
+    write(ROOT_BLOCK, root)
+
+    if joliet_name is not None:
+        joliet_root = directory_record(JOLIET_ROOT_BLOCK, BLOCK_SIZE, True, b"\x00")
+        joliet_root += directory_record(JOLIET_ROOT_BLOCK, BLOCK_SIZE, True, b"\x01")
+        joliet_root += directory_record(
+            FILE_BLOCK, len(FILE_DATA), False, joliet_name.encode("utf-16-be")
+        )
+        write(JOLIET_ROOT_BLOCK, joliet_root)
+
+    write(FILE_BLOCK, FILE_DATA)
+
+    return bytes(iso)
+
+
+fixtures = {
+    "iso9660.bin": build_iso(),
+    "iso9660_rock_ridge.bin": build_iso(rock_ridge_name="readme with a long name.txt"),
+    "iso9660_joliet.bin": build_iso(joliet_name="Joliet README.txt;1"),
+}
+
+for file_name, iso in fixtures.items():
+    with open(file_name, "wb") as f:
+        f.write(iso)
+PY
@@ -0,0 +1,61 @@
+#!/bin/bash
+#
+# Generates the big endian JFFS2 image used by tests/jffs2.rs and the unit tests in src/formats/jffs2.rs.
+#
+# mkfs.jffs2 isn't commonly installed, so the nodes are built here. The image has two 1KiB erase blocks, each
+# starting with a clean marker and holding one uncompressed file in the root directory; the remainder of the first
+# erase block is erased flash:
+#
+#   0x000  clean marker
+#   0x00C  directory entry "hello.txt" (inode 2)
+#   0x040  inode 2, "Hello, JFFS2!\n"
+#   0x400  clean marker
+#   0x40C  directory entry "goodbye.txt" (inode 3)
+#   0x440  inode 3, "Goodbye!\n"
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+import zlib
+
+ERASE_BLOCK_SIZE = 0x400
+MTIME = 1700000000
+
+
+# JFFS2 checksums are CRC32s with a seed of 0 and no final XOR
+def jffs2_crc(data):
+    return ~zlib.crc32(data, 0xFFFFFFFF) & 0xFFFFFFFF
+
+
+def node(node_type, body):
+    header = struct.pack(">HHI", 0x1985, node_type, 12 + len(body))
+    data = header + struct.pack(">I", jffs2_crc(header)) + body
+    return data + bytes(-len(data) % 4)
+
+
+def clean_marker():
+    return node(0x2003, b"")
+
+
+def dirent(ino, name):
+    fields = struct.pack(">IIIIBBH", 1, 1, ino, MTIME, len(name), 8, 0)
+    header = struct.pack(">HHI", 0x1985, 0xE001, 12 + len(fields) + 8 + len(name))
+    node_crc = jffs2_crc(header + struct.pack(">I", jffs2_crc(header)) + fields)
+    return node(0xE001, fields + struct.pack(">II", node_crc, jffs2_crc(name)) + name)
+
+
+def inode(ino, data):
+    fields = struct.pack(">IIIHHIIIIIIIBBH", ino, 1, 0o100644, 0, 0, len(data), MTIME, MTIME, MTIME, 0,
+                         len(data), len(data), 0, 0, 0)
+    header = struct.pack(">HHI", 0x1985, 0xE002, 12 + len(fields) + 8 + len(data))
+    node_crc = jffs2_crc(header + struct.pack(">I", jffs2_crc(header)) + fields)
+    return node(0xE002, fields + struct.pack(">II", jffs2_crc(data), node_crc) + data)
+
+
+first_block = clean_marker() + dirent(2, b"hello.txt") + inode(2, b"Hello, JFFS2!\n")
+second_block = clean_marker() + dirent(3, b"goodbye.txt") + inode(3, b"Goodbye!\n")
+
+with open("jffs2.bin", "wb") as f:
+    f.write(first_block.ljust(ERASE_BLOCK_SIZE, b"\xFF") + second_block)
+PY
@@ -0,0 +1,96 @@
+#!/bin/bash
+#
+# Generates the LUKS fixtures used by tests/luks.rs and the unit tests in src/formats/luks.rs.
+#
+# luks1.bin: a LUKS1 header (aes-xts-plain64, 512-bit volume key, sha256) with key slot 0 enabled, the key material
+# area, and the encrypted payload at sector 4096 (0x200000), of which the first 4096 bytes are included.
+#
+# luks2.bin: the primary and secondary LUKS2 headers (16 KiB each, binary header + JSON metadata) of a volume whose
+# aes-xts-plain64 data segment starts at 16 MiB; the key slot area and payload are not included, so the volume is
+# truncated.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import json
+import struct
+
+SECTOR_SIZE = 512
+UUID = b"0b1c9a7e-5f2d-4c8e-9a61-3d7f2e4b8c05"
+
+# LUKS1
+KEY_BYTES = 64
+STRIPES = 4000
+PAYLOAD_SECTOR = 4096
+KEY_SLOT_ACTIVE = 0x00AC71F3
+KEY_SLOT_DISABLED = 0x0000DEAD
+
+luks1 = b"LUKS\xBA\xBE" + struct.pack(">H", 1)
+luks1 += b"aes".ljust(32, b"\x00") + b"xts-plain64".ljust(32, b"\x00") + b"sha256".ljust(32, b"\x00")
+luks1 += struct.pack(">II", PAYLOAD_SECTOR, KEY_BYTES)
+# Master key digest, digest salt and digest iterations
+luks1 += bytes(range(20)) + bytes(range(32, 64)) + struct.pack(">I", 100000)
+luks1 += UUID.ljust(40, b"\x00")
+
+# Key slots: state, iterations, salt, key material sector and stripe count; each slot's key material is 4 KiB aligned
+key_material_sectors = (KEY_BYTES * STRIPES + 4095) // 4096 * 8
+for slot in range(8):
+    key_material_sector = 8 + slot * key_material_sectors
+    if slot == 0:
+        luks1 += struct.pack(">II", KEY_SLOT_ACTIVE, 2000000) + bytes(range(64, 96))
+    else:
+        luks1 += struct.pack(">II", KEY_SLOT_DISABLED, 0) + bytes(32)
+    luks1 += struct.pack(">II", key_material_sector, STRIPES)
+
+luks1 = luks1.ljust(PAYLOAD_SECTOR * SECTOR_SIZE, b"\x00")
+luks1 += b"\xA5" * 4096
+
+with open("luks1.bin", "wb") as f:
+    f.write(luks1)
+
+# LUKS2
+HEADER_SIZE = 0x4000
+BINARY_HEADER_SIZE = 0x1000
+
+metadata = {
+    "keyslots": {
+        "0": {
+            "type": "luks2",
+            "key_size": KEY_BYTES,
+            "area": {
+                "type": "raw",
+                "offset": "32768",
+                "size": "258048",
+                "encryption": "aes-xts-plain64",
+                "key_size": KEY_BYTES,
+            },
+        }
+    },
+    "segments": {
+        "0": {
+            "type": "crypt",
+            "offset": "16777216",
+            "size": "dynamic",
+            "iv_tweak": "0",
+            "encryption": "aes-xts-plain64",
+            "sector_size": 512,
+        }
+    },
+    "digests": {},
+    "config": {"json_size": "12288", "keyslots_size": "16744448"},
+}
+json_area = json.dumps(metadata, separators=(",", ":")).encode().ljust(HEADER_SIZE - BINARY_HEADER_SIZE, b"\x00")
+
+
+def luks2_header(magic, header_offset):
+    # Magic, version, header size, sequence ID, label
+    header = magic + struct.pack(">HQQ", 2, HEADER_SIZE, 1) + bytes(48)
+    # Checksum algorithm, salt, UUID, subsystem, header offset
+    header += b"sha256".ljust(32, b"\x00") + bytes(64) + UUID.ljust(40, b"\x00") + bytes(48)
+    header += struct.pack(">Q", header_offset)
+    return header.ljust(BINARY_HEADER_SIZE, b"\x00") + json_area
+
+
+with open("luks2.bin", "wb") as f:
+    f.write(luks2_header(b"LUKS\xBA\xBE", 0) + luks2_header(b"SKUL\xBA\xBE", HEADER_SIZE))
+PY
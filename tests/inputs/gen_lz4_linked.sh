@@ -0,0 +1,20 @@
+#!/bin/bash
+#
+# Generates the LZ4 fixture used by tests/lz4.rs and the unit tests in src/formats/lz4.rs.
+#
+# A single LZ4 frame of 300000 bytes of data, spanning several 64KB linked blocks, with the content size in the
+# frame header and a content checksum.
+
+cd "$(dirname "$0")" || exit 1
+
+work_dir="$(mktemp -d)" || exit 1
+trap 'rm -rf "$work_dir"' EXIT
+
+python3 - "$work_dir/data" <<'PY'
+import sys
+
+with open(sys.argv[1], "wb") as f:
+    f.write(bytes((n % 97) ^ ((n // 1000) & 0xFF) for n in range(300000)))
+PY
+
+lz4 -q -B4 -BD --content-size -c "$work_dir/data" > lz4_linked.bin
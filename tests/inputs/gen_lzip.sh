@@ -0,0 +1,37 @@
+#!/bin/bash
+#
+# Generates the lzip fixture used by tests/lzip.rs and the unit tests in src/formats/lzip.rs.
+#
+# Equivalent to `cat first.lz second.lz`: two lzip members with a 64KB dictionary, which decompress to
+# "first member\nsecond member\n". Each member is an lzip header, an LZMA stream terminated by an end of
+# stream marker, and a footer holding the CRC32 and size of the data, and the size of the member.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import lzma
+import struct
+import zlib
+
+# Size of the LZMA-alone header: properties, dictionary size, and uncompressed size
+LZMA_ALONE_HEADER_SIZE = 13
+LZIP_FOOTER_SIZE = 20
+
+
+def lzip_member(data):
+    # The LZMA-alone encoder terminates the stream with an end of stream marker, as lzip requires
+    lzma_alone = lzma.compress(
+        data,
+        format=lzma.FORMAT_ALONE,
+        filters=[{"id": lzma.FILTER_LZMA1, "preset": 6, "dict_size": 1 << 16}],
+    )
+
+    # Coded dictionary size 0x10: 2^16 bytes
+    member = b"LZIP\x01\x10" + lzma_alone[LZMA_ALONE_HEADER_SIZE:]
+    return member + struct.pack("<IQQ", zlib.crc32(data), len(data), len(member) + LZIP_FOOTER_SIZE)
+
+
+with open("lzip.bin", "wb") as f:
+    f.write(lzip_member(b"first member\n"))
+    f.write(lzip_member(b"second member\n"))
+PY
@@ -0,0 +1,19 @@
+#!/bin/bash
+#
+# Generates the LZMA (.lzma, or "LZMA alone") fixture used by tests/lzma.rs and the unit tests in src/formats/lzma.rs.
+#
+# The stream is compressed with the default preset (properties 0x5D, 8MiB dictionary), and, like the output of
+# `lzma` when reading from a pipe, has an unknown decompressed size and an end of stream marker.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import lzma
+
+data = "".join(
+    f"Line {n}: the quick brown fox jumps over the lazy dog {n * n}\n" for n in range(200)
+).encode()
+
+with open("lzma.bin", "wb") as f:
+    f.write(lzma.compress(data, format=lzma.FORMAT_ALONE))
+PY
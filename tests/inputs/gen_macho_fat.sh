@@ -0,0 +1,41 @@
+#!/bin/bash
+#
+# Generates the fat Mach-O binary used by tests/macho.rs and the unit tests in src/formats/macho.rs.
+# The fat header lists two slices, each aligned to a 4KB boundary:
+#
+#   0x1000  x86_64 slice
+#   0x2000  ARM64 slice
+#
+# Each slice is a thin, little endian, 64-bit Mach-O executable header with a single LC_BUILD_VERSION
+# load command.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+CPU_TYPE_X86_64 = 0x01000007
+CPU_TYPE_ARM64 = 0x0100000C
+MH_EXECUTE = 2
+LC_BUILD_VERSION = 0x32
+ALIGNMENT = 12
+
+
+def thin_macho_64(cpu_type):
+    load_command = struct.pack("<II", LC_BUILD_VERSION, 24) + b"\0" * 16
+    header = b"\xCF\xFA\xED\xFE" + struct.pack("<7I", cpu_type, 0, MH_EXECUTE, 1, len(load_command), 0, 0)
+    return header + load_command
+
+
+slices = [(CPU_TYPE_X86_64, thin_macho_64(CPU_TYPE_X86_64)), (CPU_TYPE_ARM64, thin_macho_64(CPU_TYPE_ARM64))]
+
+fat = b"\xCA\xFE\xBA\xBE" + struct.pack(">I", len(slices))
+for i, (cpu_type, data) in enumerate(slices):
+    fat += struct.pack(">5I", cpu_type, 0, (i + 1) << ALIGNMENT, len(data), ALIGNMENT)
+
+for i, (_, data) in enumerate(slices):
+    fat = fat.ljust((i + 1) << ALIGNMENT, b"\0") + data
+
+with open("macho_fat.bin", "wb") as f:
+    f.write(fat)
+PY
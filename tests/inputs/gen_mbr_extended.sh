@@ -0,0 +1,47 @@
+#!/bin/bash
+#
+# Generates the MBR disk image with an extended partition used by tests/mbr.rs and the unit tests in
+# src/formats/mbr.rs. The image is 12 sectors long:
+#
+#   LBA 0      MBR: a Linux partition at LBA 2, and an extended partition at LBA 4
+#   LBA 2-3    Linux partition
+#   LBA 4      first EBR: a logical partition at LBA 5, and a link to the next EBR at LBA 7
+#   LBA 5-6    Linux logical partition
+#   LBA 7      second EBR: a logical partition at LBA 8, and no further links
+#   LBA 8-11   FAT32 logical partition
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+SECTOR_SIZE = 512
+
+image = bytearray(12 * SECTOR_SIZE)
+
+
+# Partition entry LBAs in an EBR are relative to the extended partition (links) or to the EBR (logical partitions)
+def write_entry(sector, index, os_type, lba, count):
+    entry_offset = sector * SECTOR_SIZE + 446 + index * 16
+    image[entry_offset + 4] = os_type
+    image[entry_offset + 8:entry_offset + 16] = struct.pack("<II", lba, count)
+
+
+def write_boot_signature(sector):
+    image[sector * SECTOR_SIZE + 510:sector * SECTOR_SIZE + 512] = b"\x55\xAA"
+
+
+write_entry(0, 0, 0x83, 2, 2)
+write_entry(0, 1, 0x05, 4, 8)
+write_boot_signature(0)
+
+write_entry(4, 0, 0x83, 1, 2)
+write_entry(4, 1, 0x05, 3, 5)
+write_boot_signature(4)
+
+write_entry(7, 0, 0x0B, 1, 4)
+write_boot_signature(7)
+
+with open("mbr_extended.bin", "wb") as f:
+    f.write(image)
+PY
@@ -0,0 +1,43 @@
+#!/bin/bash
+#
+# Generates the OLE2 compound file fixture used by tests/olecf.rs and the unit tests in src/formats/olecf.rs.
+#
+# A minimal version 3 compound file (512-byte sectors): the header, one FAT sector in sector 0, and one directory
+# sector in sector 1 holding the root storage entry, with the MSI installer CLSID
+# {000C1084-0000-0000-C000-000000000046}. The 1536-byte compound file is followed by 768 bytes of trailing data so
+# that it is carved out of the file.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+SECTOR_SIZE = 512
+FAT_SECTOR = 0xFFFFFFFD
+END_OF_CHAIN = 0xFFFFFFFE
+FREE_SECTOR = 0xFFFFFFFF
+MSI_CLSID = bytes.fromhex("84100C000000000000C0000000000046")
+
+header = b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1" + bytes(16)
+# Minor version, major version, byte order, sector shift, mini sector shift, reserved
+header += struct.pack("<HHHHH6x", 0x3E, 3, 0xFFFE, 9, 6)
+# Directory sector count, FAT sector count, first directory sector, transaction signature
+header += struct.pack("<IIII", 0, 1, 1, 0)
+# Mini stream cutoff, mini FAT start and count, DIFAT start and count
+header += struct.pack("<IIIII", 4096, END_OF_CHAIN, 0, END_OF_CHAIN, 0)
+# The FAT is in sector 0
+header += struct.pack("<I", 0)
+header = header.ljust(SECTOR_SIZE, b"\xFF")
+
+# FAT: sector 0 is the FAT, sector 1 is the directory
+fat = struct.pack("<II", FAT_SECTOR, END_OF_CHAIN).ljust(SECTOR_SIZE, b"\xFF")
+
+# Root storage directory entry
+directory = "Root Entry\0".encode("utf-16-le").ljust(64, b"\x00")
+directory += struct.pack("<HBBIII", 22, 5, 1, FREE_SECTOR, FREE_SECTOR, FREE_SECTOR)
+directory += MSI_CLSID
+directory = directory.ljust(SECTOR_SIZE, b"\x00")
+
+with open("olecf.bin", "wb") as f:
+    f.write(header + fat + directory + b"\x41" * 0x300)
+PY
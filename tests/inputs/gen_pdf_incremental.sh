@@ -0,0 +1,37 @@
+#!/bin/bash
+#
+# Generates the PDF fixture used by tests/pdf.rs and the unit tests in src/formats/pdf.rs.
+#
+# An empty PDF 1.7 document with one incremental update that replaces its catalog, so that it has two xref sections,
+# two trailers and two %%EOF markers. The document is followed by trailing data containing a stray %%EOF marker
+# without a startxref, which must not be mistaken for the end of the document.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+pdf = b"%PDF-1.7\r\n%\xE2\xE3\xCF\xD3\r\n"
+
+catalog_offset = len(pdf)
+pdf += b"1 0 obj\r\n<</Type/Catalog/Pages 2 0 R>>\r\nendobj\r\n"
+pages_offset = len(pdf)
+pdf += b"2 0 obj\r\n<</Type/Pages/Kids[]/Count 0>>\r\nendobj\r\n"
+
+xref_offset = len(pdf)
+pdf += (
+    f"xref\r\n0 3\r\n0000000000 65535 f\r\n{catalog_offset:010} 00000 n\r\n{pages_offset:010} 00000 n\r\n"
+    f"trailer\r\n<</Size 3/Root 1 0 R>>\r\nstartxref\r\n{xref_offset}\r\n%%EOF\r\n"
+).encode()
+
+# The update replaces the catalog object
+updated_catalog_offset = len(pdf)
+pdf += b"1 0 obj\r\n<</Type/Catalog/Pages 2 0 R/Lang(en)>>\r\nendobj\r\n"
+
+update_xref_offset = len(pdf)
+pdf += (
+    f"xref\r\n1 1\r\n{updated_catalog_offset:010} 00000 n\r\n"
+    f"trailer\r\n<</Size 3/Root 1 0 R/Prev {xref_offset}>>\r\nstartxref\r\n{update_xref_offset}\r\n%%EOF\r\n"
+).encode()
+
+with open("pdf_incremental.bin", "wb") as f:
+    f.write(pdf + b"\x00\x00%%EOF\x00\x00")
+PY
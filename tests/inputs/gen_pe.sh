@@ -0,0 +1,53 @@
+#!/bin/bash
+#
+# Generates the PE sample used by tests/pe.rs and the unit tests in src/formats/pe.rs: a minimal PE32 EFI application
+# for x86, with a single .rsrc section holding one RT_MANIFEST resource:
+#
+#   0x000  DOS header
+#   0x040  PE header, optional header (16 data directories), and the .rsrc section header
+#   0x200  .rsrc: type, name and language directories, a data entry, and the manifest data at 0x258
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+PE_HEADER = 0x40
+OPTIONAL_HEADER = PE_HEADER + 24
+OPTIONAL_HEADER_SIZE = 224
+SECTION_TABLE = OPTIONAL_HEADER + OPTIONAL_HEADER_SIZE
+RSRC_OFFSET = 0x200
+RSRC_RVA = 0x1000
+
+RT_MANIFEST = 24
+LANGUAGE_EN_US = 1033
+EFI_APPLICATION = 10
+
+manifest = b"<assembly/>"
+
+# Resource tree: one type directory, one name directory, one language directory, one data entry
+rsrc = b""
+for entry_id, child_offset in [(RT_MANIFEST, 0x80000018), (1, 0x80000030), (LANGUAGE_EN_US, 0x48)]:
+    rsrc += b"\0" * 12 + struct.pack("<HHII", 0, 1, entry_id, child_offset)
+rsrc += struct.pack("<IIII", RSRC_RVA + 0x58, len(manifest), 0, 0)
+rsrc += manifest
+
+image = bytearray(RSRC_OFFSET)
+
+# DOS header and PE header
+image[0:2] = b"MZ"
+image[0x3C:0x40] = struct.pack("<I", PE_HEADER)
+image[PE_HEADER:PE_HEADER + 24] = b"PE\0\0" + struct.pack("<HHIIIHH", 0x14C, 1, 0, 0, 0, OPTIONAL_HEADER_SIZE, 0)
+
+# Optional header: PE32 magic, subsystem, and the resource data directory
+image[OPTIONAL_HEADER:OPTIONAL_HEADER + 2] = struct.pack("<H", 0x10B)
+image[OPTIONAL_HEADER + 68:OPTIONAL_HEADER + 70] = struct.pack("<H", EFI_APPLICATION)
+image[OPTIONAL_HEADER + 92:OPTIONAL_HEADER + 96] = struct.pack("<I", 16)
+image[OPTIONAL_HEADER + 112:OPTIONAL_HEADER + 120] = struct.pack("<II", RSRC_RVA, len(rsrc))
+
+# .rsrc section header
+image[SECTION_TABLE:SECTION_TABLE + 24] = b".rsrc\0\0\0" + struct.pack("<IIII", len(rsrc), RSRC_RVA, len(rsrc), RSRC_OFFSET)
+
+with open("pe.bin", "wb") as f:
+    f.write(bytes(image) + rsrc)
+PY
@@ -0,0 +1,23 @@
+#!/bin/bash
+#
+# Generates the QCOW fixture with a backing file used by tests/qcow.rs and the unit tests in src/formats/qcow.rs.
+#
+# The v3 image header in qcow.bin, which ends with its header extensions at offset 0x200, with the backing file name
+# "base.qcow2" stored right after the header extensions, as qemu-img does for overlay images.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+BACKING_FILE = b"base.qcow2"
+
+with open("qcow.bin", "rb") as f:
+    qcow = bytearray(f.read())
+
+backing_file_offset = len(qcow)
+qcow[8:20] = struct.pack(">QI", backing_file_offset, len(BACKING_FILE))
+
+with open("qcow_backing_file.bin", "wb") as f:
+    f.write(qcow + BACKING_FILE)
+PY
@@ -0,0 +1,60 @@
+#!/bin/bash
+#
+# Generates the RomFS image with a hard link used by tests/romfs.rs and the unit tests in src/formats/romfs.rs.
+#
+# genromfs isn't commonly installed, and doesn't emit hard links, so the image is built here. It is 1KiB, with a
+# volume name of "test", and its root directory holds:
+#
+#   hello.txt       "Hello, world!\n"
+#   goodbye.txt     "Goodbye!\n"
+#   hello_link.txt  a hard link to hello.txt
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+IMAGE_SIZE = 1024
+
+ROOT_DIRECTORY_OFFSET = 32
+HELLO_OFFSET = 96
+
+HARDLINK = 0
+DIRECTORY = 1
+REGULAR_FILE = 2
+
+
+def pad(data):
+    return data + bytes(-len(data) % 16)
+
+
+entries = [
+    (b".", DIRECTORY, ROOT_DIRECTORY_OFFSET, b""),
+    (b"..", HARDLINK, ROOT_DIRECTORY_OFFSET, b""),
+    (b"hello.txt", REGULAR_FILE, 0, b"Hello, world!\n"),
+    (b"goodbye.txt", REGULAR_FILE, 0, b"Goodbye!\n"),
+    (b"hello_link.txt", HARDLINK, HELLO_OFFSET, b""),
+]
+
+image = pad(b"-rom1fs-" + struct.pack(">II", IMAGE_SIZE, 0) + b"test\x00")
+
+for index, (name, file_type, info, data) in enumerate(entries):
+    entry_name = pad(name + b"\x00")
+    entry_data = pad(data)
+
+    if index == len(entries) - 1:
+        next_header_offset = 0
+    else:
+        next_header_offset = len(image) + 16 + len(entry_name) + len(entry_data)
+
+    image += struct.pack(">IIII", next_header_offset | file_type, info, len(data), 0) + entry_name + entry_data
+
+image = bytearray(image.ljust(IMAGE_SIZE, b"\x00"))
+
+# The first 512 bytes sum to 0
+checksum = -sum(struct.unpack(">128I", image[:512])) & 0xFFFFFFFF
+image[12:16] = struct.pack(">I", checksum)
+
+with open("romfs_hardlink.bin", "wb") as f:
+    f.write(image)
+PY
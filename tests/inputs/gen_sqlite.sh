@@ -0,0 +1,33 @@
+#!/bin/bash
+#
+# Generates the SQLite fixture used by tests/sqlite.rs and the unit tests in src/formats/sqlite.rs.
+#
+# A two-page database (4096-byte pages, user version 7) holding a single table, followed by 256 bytes of trailing
+# data so that the database is carved out of the file. The SQLite library that writes the database (3.40.1 for the
+# committed fixture) is recorded in its header.
+
+cd "$(dirname "$0")" || exit 1
+
+work_dir="$(mktemp -d)" || exit 1
+trap 'rm -rf "$work_dir"' EXIT
+
+python3 - "$work_dir/database.sqlite" <<'PY'
+import sqlite3
+import sys
+
+database_path = sys.argv[1]
+
+connection = sqlite3.connect(database_path)
+connection.execute("PRAGMA page_size = 4096")
+connection.execute("PRAGMA user_version = 7")
+connection.execute("CREATE TABLE settings (name TEXT, value TEXT)")
+connection.execute("INSERT INTO settings VALUES ('hostname', 'router')")
+connection.commit()
+connection.close()
+
+with open(database_path, "rb") as f:
+    database = f.read()
+
+with open("sqlite.bin", "wb") as f:
+    f.write(database + b"\xFF" * 256)
+PY
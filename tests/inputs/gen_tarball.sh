@@ -1,6 +1,10 @@
 #!/bin/bash
 #
-# Generates a deterministic POSIX (ustar) tar archive used by tests/tarball.rs.
+# Generates the deterministic tar archives used by tests/tarball.rs:
+#
+#   tarball.bin          POSIX (ustar) archive of regular files, a directory and a symlink
+#   tarball_special.bin  GNU archive of a character device, a block device and a fifo
+#   tarball_long_name.bin  GNU archive of two files, one of which needs a GNU long name ('L') entry
 #
 # The archive layout and file contents are pinned here and asserted in the test,
 # so the extractor (currently external `tar`, soon to be replaced) can be verified
@@ -53,4 +57,35 @@ with tarfile.open("tarball.bin", "w", format=tarfile.USTAR_FORMAT) as tar:
     link.mode = 0o777
     link.linkname = "hello.txt"
     tar.addfile(link)
+
+# Device nodes and fifos (which can't be created without privileges, so are extracted
+# as placeholder files); (name, type, major, minor)
+special_files = [
+    ("dev/console",   tarfile.CHRTYPE, 5,  1),
+    ("dev/mtdblock3", tarfile.BLKTYPE, 31, 3),
+    ("dev/initctl",   tarfile.FIFOTYPE, 0, 0),
+]
+
+with tarfile.open("tarball_special.bin", "w", format=tarfile.GNU_FORMAT) as tar:
+    for name, entry_type, major, minor in special_files:
+        info = reset(tarfile.TarInfo(name))
+        info.type = entry_type
+        info.mode = 0o600
+        info.devmajor = major
+        info.devminor = minor
+        tar.addfile(info)
+
+# A path longer than the 100-byte name field, which GNU tar stores in a ././@LongLink entry
+long_name = "long_directory_name" * 6 + "/firmware.bin"
+long_name_files = [
+    ("config.txt", b"hostname=ap1\n"),
+    (long_name,    b"\x5A" * 600),
+]
+
+with tarfile.open("tarball_long_name.bin", "w", format=tarfile.GNU_FORMAT) as tar:
+    for name, data in long_name_files:
+        info = reset(tarfile.TarInfo(name))
+        info.mode = 0o644
+        info.size = len(data)
+        tar.addfile(info, io.BytesIO(data))
 PY
@@ -0,0 +1,39 @@
+#!/bin/bash
+#
+# Generates the TRX firmware images used by tests/trx.rs and the unit tests in src/formats/trx.rs:
+#
+#   trx.bin     TRXv1 image (28 byte header), with a 100 byte kernel and a 60 byte rootfs partition
+#   trx_v2.bin  TRXv2 image (32 byte header), with the same partitions
+#
+# The header CRC covers everything after the CRC field, and is stored without the final XOR.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+import zlib
+
+partitions = [b"\xAA" * 100, b"\xBB" * 60]
+
+
+def trx_image(version):
+    offset_count = 4 if version == 2 else 3
+    header_size = 16 + offset_count * 4
+
+    offsets = [0] * offset_count
+    body = b""
+    for i, partition in enumerate(partitions):
+        offsets[i] = header_size + len(body)
+        body += partition
+
+    crc_data = struct.pack("<HH%dI" % offset_count, 0, version, *offsets) + body
+    crc = zlib.crc32(crc_data) ^ 0xFFFFFFFF
+    return b"HDR0" + struct.pack("<II", len(crc_data) + 12, crc) + crc_data
+
+
+with open("trx.bin", "wb") as f:
+    f.write(trx_image(1))
+
+with open("trx_v2.bin", "wb") as f:
+    f.write(trx_image(2))
+PY
@@ -0,0 +1,70 @@
+#!/bin/bash
+#
+# Generates the UBI image used by tests/ubi.rs and the unit tests in src/formats/ubi.rs.
+#
+# The image has 1KiB physical erase blocks and a single dynamic volume, "rootfs", whose two logical erase blocks
+# ("A" * 896 and "B" * 896) are stored out of order. It also contains a stale copy of LEB 0, an erased block, a bad
+# block and two free blocks:
+#
+#   PEB 0  layout volume
+#   PEB 1  rootfs LEB 1, sequence number 4
+#   PEB 2  rootfs LEB 0, sequence number 2 (stale)
+#   PEB 3  erased
+#   PEB 4  rootfs LEB 0, sequence number 3
+#   PEB 5  bad (all zeros)
+#   PEB 6  free
+#   PEB 7  free
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+import zlib
+
+PEB_SIZE = 0x400
+VID_HEADER_OFFSET = 64
+DATA_OFFSET = 128
+LEB_SIZE = PEB_SIZE - DATA_OFFSET
+LAYOUT_VOLUME_ID = 0x7FFFEFFF
+
+
+# UBI checksums are CRC32s with no final XOR
+def ubi_crc(data):
+    return struct.pack(">I", ~zlib.crc32(data) & 0xFFFFFFFF)
+
+
+def ec_header():
+    header = b"UBI#\x01\x00\x00\x00" + struct.pack(">QII", 1, VID_HEADER_OFFSET, DATA_OFFSET) + bytes(36)
+    return header + ubi_crc(header)
+
+
+def free_peb():
+    return ec_header().ljust(PEB_SIZE, b"\xFF")
+
+
+def peb(volume_id, leb_number, sequence_number, leb_data):
+    vid_header = b"UBI!\x01\x01\x00\x00" + struct.pack(">II", volume_id, leb_number) + bytes(24)
+    vid_header += struct.pack(">Q", sequence_number) + bytes(12)
+    vid_header += ubi_crc(vid_header)
+    return (ec_header() + vid_header + leb_data).ljust(PEB_SIZE, b"\xFF")
+
+
+def volume_table(name):
+    record = bytes(12) + bytes([1, 0]) + struct.pack(">H", len(name)) + name.ljust(128, b"\x00") + bytes(24)
+    return record + ubi_crc(record)
+
+
+image = b"".join([
+    peb(LAYOUT_VOLUME_ID, 0, 1, volume_table(b"rootfs")),
+    peb(0, 1, 4, b"B" * LEB_SIZE),
+    peb(0, 0, 2, b"X" * LEB_SIZE),
+    b"\xFF" * PEB_SIZE,
+    peb(0, 0, 3, b"A" * LEB_SIZE),
+    bytes(PEB_SIZE),
+    free_peb(),
+    free_peb(),
+])
+
+with open("ubi.bin", "wb") as f:
+    f.write(image)
+PY
@@ -0,0 +1,58 @@
+#!/bin/bash
+#
+# Generates the uImages used by tests/uimage.rs, tests/container_children.rs and the unit tests in
+# src/formats/uimage.rs:
+#
+#   uimage.bin        a gzip compressed Linux kernel image for ARM, whose 32 character image name fills the
+#                     whole name field, and so is not NULL terminated
+#   uimage_multi.bin  an uncompressed multi-file image for ARM, holding a 10 byte and a 20 byte image; the
+#                     images follow a NULL terminated table of image sizes, and are 4 byte aligned
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import gzip
+import struct
+import zlib
+
+KERNEL_IMAGE = 2
+MULTI_FILE_IMAGE = 4
+NO_COMPRESSION = 0
+GZIP_COMPRESSION = 1
+
+
+def uimage(data, image_type, compression, name):
+    def header(header_crc):
+        return struct.pack(
+            ">7I4B32s",
+            0x27051956,
+            header_crc,
+            1700000000,
+            len(data),
+            0x80008000,
+            0x80008000,
+            zlib.crc32(data),
+            5,  # Linux
+            2,  # ARM
+            image_type,
+            compression,
+            name,
+        )
+
+    return header(zlib.crc32(header(0))) + data
+
+
+kernel = b"".join(b"This is a Linux kernel, honest (line %d)\n" % n for n in range(64))
+data = gzip.compress(kernel, compresslevel=9, mtime=0)
+
+with open("uimage.bin", "wb") as f:
+    f.write(uimage(data, KERNEL_IMAGE, GZIP_COMPRESSION, b"Linux-6.6.0 for the ARM platform"))
+
+images = [b"\xAA" * 10, b"\xBB" * 20]
+data = struct.pack(">%dI" % (len(images) + 1), *[len(image) for image in images], 0)
+for image in images:
+    data += image + b"\x00" * (-len(image) % 4)
+
+with open("uimage_multi.bin", "wb") as f:
+    f.write(uimage(data, MULTI_FILE_IMAGE, NO_COMPRESSION, b""))
+PY
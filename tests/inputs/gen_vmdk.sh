@@ -0,0 +1,95 @@
+#!/bin/bash
+#
+# Generates the VMDK fixtures used by tests/vmdk.rs and the unit tests in src/formats/vmdk.rs.
+#
+# vmdk.bin: a 1 MiB monolithic sparse extent with 64 KiB grains, laid out the way qemu-img creates them: the header,
+# the embedded descriptor at sector 1, the redundant grain directory and grain table at sectors 21 and 22, the grain
+# directory and grain table at sectors 26 and 27, and 128 sectors of overhead. Grains 0 and 3 are allocated, at
+# sectors 128 and 256.
+#
+# vmdk_descriptor.bin: the text descriptor of a 2 GiB twoGbMaxExtentSparse disk, whose two extents are stored in
+# separate files.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import struct
+
+SECTOR_SIZE = 512
+CAPACITY_SECTORS = 2048
+GRAIN_SECTORS = 128
+GRAIN_TABLE_ENTRIES = 512
+OVERHEAD_SECTORS = 128
+# Header flags: valid newline test, redundant grain table
+FLAGS = 0x3
+
+DESCRIPTOR = b"""# Disk DescriptorFile
+version=1
+CID=52f6b4ab
+parentCID=ffffffff
+createType="monolithicSparse"
+
+# Extent description
+RW 2048 SPARSE "disk.vmdk"
+
+# The Disk Data Base
+#DDB
+
+ddb.virtualHWVersion = "4"
+ddb.geometry.cylinders = "2"
+ddb.geometry.heads = "16"
+ddb.geometry.sectors = "63"
+ddb.adapterType = "ide"
+"""
+
+
+def sector(number):
+    return number * SECTOR_SIZE
+
+
+# Grain 0 is at sector 128 and grain 3 is at sector 256; all other grains are unallocated
+grain_table = struct.pack("<4I", 128, 0, 0, 256)
+
+# Magic, version, flags, capacity, grain size, descriptor offset and size, grain table entries, redundant grain
+# directory offset, grain directory offset, overhead
+header = b"KDMV" + struct.pack(
+    "<IIQQQQIQQQ", 1, FLAGS, CAPACITY_SECTORS, GRAIN_SECTORS, 1, 20, GRAIN_TABLE_ENTRIES, 21, 26, OVERHEAD_SECTORS
+)
+# Unclean shutdown, newline test characters, compression algorithm
+header += b"\x00\n \r\n" + struct.pack("<H", 0)
+
+extent = header.ljust(sector(1), b"\x00")
+extent += DESCRIPTOR.ljust(sector(20), b"\x00")
+extent += struct.pack("<I", 22).ljust(sector(1), b"\x00")
+extent += grain_table.ljust(sector(4), b"\x00")
+extent += struct.pack("<I", 27).ljust(sector(1), b"\x00")
+extent += grain_table.ljust(sector(4), b"\x00")
+extent = extent.ljust(sector(OVERHEAD_SECTORS), b"\x00")
+extent += b"\xAA" * sector(GRAIN_SECTORS)
+extent += b"\x00" * sector(256 - OVERHEAD_SECTORS - GRAIN_SECTORS)
+extent += b"\x55" * sector(GRAIN_SECTORS)
+
+with open("vmdk.bin", "wb") as f:
+    f.write(extent)
+
+with open("vmdk_descriptor.bin", "wb") as f:
+    f.write(b"""# Disk DescriptorFile
+version=1
+CID=7a3e91c2
+parentCID=ffffffff
+createType="twoGbMaxExtentSparse"
+
+# Extent description
+RW 4192256 SPARSE "disk-s001.vmdk"
+RW 2048 SPARSE "disk-s002.vmdk"
+
+# The Disk Data Base
+#DDB
+
+ddb.virtualHWVersion = "4"
+ddb.geometry.cylinders = "4161"
+ddb.geometry.heads = "16"
+ddb.geometry.sectors = "63"
+ddb.adapterType = "ide"
+""")
+PY
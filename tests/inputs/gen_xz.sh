@@ -0,0 +1,17 @@
+#!/bin/bash
+#
+# Generates the XZ fixture used by tests/xz.rs and the unit tests in src/formats/xz.rs.
+#
+# The file is a single XZ stream (default preset, CRC64 check) with no stream padding.
+
+cd "$(dirname "$0")" || exit 1
+
+python3 - <<'PY'
+import lzma
+import struct
+
+data = b"".join(struct.pack("<I", n % 251) for n in range(10000))
+
+with open("xz.bin", "wb") as f:
+    f.write(lzma.compress(data, format=lzma.FORMAT_XZ))
+PY
@@ -0,0 +1,23 @@
+#!/bin/bash
+#
+# Generates the ZSTD fixture used by tests/zstd.rs and the unit tests in src/formats/zstd.rs.
+#
+# A 12-byte skippable frame (magic 0x184D2A5A, 4 bytes of user data) is followed by a single ZSTD frame that fits
+# in one block, with the decompressed size in its frame header and a content checksum.
+
+cd "$(dirname "$0")" || exit 1
+
+work_dir="$(mktemp -d)" || exit 1
+trap 'rm -rf "$work_dir"' EXIT
+
+python3 - "$work_dir/data" <<'PY'
+import sys
+
+with open(sys.argv[1], "wb") as f:
+    f.write(b"A small ZSTD frame, which fits in a single block" * 4)
+PY
+
+{
+    printf '\x5A\x2A\x4D\x18\x04\x00\x00\x00meta'
+    zstd -3 --check -q -c "$work_dir/data"
+} > zstd_skippable.bin
@@ -0,0 +1,22 @@
+mod common;
+
+#[test]
+fn integration_test() {
+    const SIGNATURE_TYPE: &str = "iso9660";
+    const INPUT_FILE_NAME: &str = "iso9660.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
+#[test]
+fn integration_test_rock_ridge() {
+    const SIGNATURE_TYPE: &str = "iso9660";
+    const INPUT_FILE_NAME: &str = "iso9660_rock_ridge.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
+#[test]
+fn integration_test_joliet() {
+    const SIGNATURE_TYPE: &str = "iso9660";
+    const INPUT_FILE_NAME: &str = "iso9660_joliet.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
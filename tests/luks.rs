@@ -0,0 +1,15 @@
+mod common;
+
+#[test]
+fn integration_test_luks1() {
+    const SIGNATURE_TYPE: &str = "luks";
+    const INPUT_FILE_NAME: &str = "luks1.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
+#[test]
+fn integration_test_luks2() {
+    const SIGNATURE_TYPE: &str = "luks";
+    const INPUT_FILE_NAME: &str = "luks2.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
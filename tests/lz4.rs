@@ -11,3 +11,10 @@ fn integration_test() {
 fn trailing_data() {
     common::trailing_data_test("lz4", "lz4.bin");
 }
+
+#[test]
+fn integration_test_linked_blocks() {
+    const SIGNATURE_TYPE: &str = "lz4";
+    const INPUT_FILE_NAME: &str = "lz4_linked.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
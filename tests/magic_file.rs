@@ -0,0 +1,77 @@
+use binwalk_ng::Binwalk;
+use binwalk_ng::magic_file::load_magic_file;
+
+const MAGIC_FILE: &str = r#"[
+    {"name": "foobar", "description": "FooBar image", "magic": "46 4F 4F 42", "category": "firmware"},
+    {"name": "fixedhdr", "description": "Fixed header", "magic": "0xCAFE", "offset": 4, "endianness": "little"}
+]"#;
+
+fn load_signatures() -> Vec<binwalk_ng::Signature> {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let magic_file = temp_dir.path().join("magic.json");
+    std::fs::write(&magic_file, MAGIC_FILE).unwrap();
+    load_magic_file(&magic_file).unwrap()
+}
+
+#[test]
+fn user_signatures_are_matched() {
+    let binwalker =
+        Binwalk::configure(None, None, vec![], vec![], Some(load_signatures()), false).unwrap();
+
+    let mut file_data = b"\x00\x00\x00\x00\xFE\xCA".to_vec();
+    file_data.extend([0; 0x20]);
+    file_data.extend(b"FOOB");
+    file_data.extend([0; 0x20]);
+
+    let results = binwalker.scan(&file_data);
+    assert_eq!(results.len(), 2);
+
+    assert_eq!(results[0].name, "fixedhdr");
+    assert_eq!(results[0].offset, 4);
+    assert_eq!(results[0].description, "Fixed header");
+
+    assert_eq!(results[1].name, "foobar");
+    assert_eq!(results[1].offset, 0x26);
+    assert_eq!(results[1].description, "FooBar image (firmware)");
+
+    // Fixed offset signatures are not matched anywhere else
+    let results = binwalker.scan(&[0, 0xFE, 0xCA, 0, 0, 0, 0, 0]);
+    assert!(results.is_empty());
+}
+
+#[test]
+fn user_signatures_can_be_excluded() {
+    let binwalker = Binwalk::configure(
+        None,
+        None,
+        vec![],
+        vec!["FOOBAR".to_string()],
+        Some(load_signatures()),
+        false,
+    )
+    .unwrap();
+
+    assert!(binwalker.scan(b"\x00FOOB\x00\x00\x00\x00").is_empty());
+}
+
+#[test]
+fn invalid_magic_files_are_rejected() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let magic_file = temp_dir.path().join("magic.json");
+
+    for contents in [
+        // Not a list
+        r#"{"name": "foobar"}"#,
+        // Odd number of hex digits
+        r#"[{"name": "foobar", "description": "FooBar", "magic": "464F4"}]"#,
+        // Name contains whitespace
+        r#"[{"name": "foo bar", "description": "FooBar", "magic": "464F"}]"#,
+        // Name collides with a built-in signature
+        r#"[{"name": "gzip", "description": "FooBar", "magic": "464F"}]"#,
+        // Unknown field
+        r#"[{"name": "foobar", "description": "FooBar", "magic": "464F", "size": 4}]"#,
+    ] {
+        std::fs::write(&magic_file, contents).unwrap();
+        assert!(load_magic_file(&magic_file).is_err(), "{contents}");
+    }
+}
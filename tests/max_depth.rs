@@ -0,0 +1,69 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Runs binwalk in matryoshka mode against the specified file, with any additional arguments,
+/// and returns the number of files that were analyzed, and the number of depth limit warnings
+fn analyzed_file_count(file_path: &Path, extra_args: &[&str]) -> (usize, usize) {
+    let work_directory = file_path.parent().unwrap();
+    let log_file = work_directory.join("log.json");
+    let output_directory = work_directory.join("extractions");
+
+    let status = Command::new(env!("CARGO_BIN_EXE_binwalk"))
+        .arg("-q")
+        .arg("-M")
+        .arg("-e")
+        .arg("-d")
+        .arg(&output_directory)
+        .arg("-l")
+        .arg(&log_file)
+        .args(extra_args)
+        .arg(file_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let log: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&log_file).unwrap()).unwrap();
+
+    std::fs::remove_file(&log_file).unwrap();
+    std::fs::remove_dir_all(&output_directory).unwrap();
+
+    let analyses: Vec<&serde_json::Value> = log
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|entry| entry.get("Analysis"))
+        .collect();
+
+    let depth_limit_warnings = analyses
+        .iter()
+        .flat_map(|analysis| analysis["warnings"].as_array().unwrap())
+        .filter(|warning| warning["code"] == "DepthLimitReached")
+        .count();
+
+    (analyses.len(), depth_limit_warnings)
+}
+
+#[test]
+fn recursion_stops_at_max_depth() {
+    let work_directory = tempfile::tempdir().unwrap();
+    let file_path = work_directory.path().join("nested.bin");
+
+    // Six nested levels of gzip compressed data (see tests/inputs/gen_gzip_nested.sh)
+    std::fs::copy("tests/inputs/gzip_nested.bin", &file_path).unwrap();
+
+    // Unbounded, the target file and all six levels of nested data are analyzed
+    assert_eq!(analyzed_file_count(&file_path, &[]), (7, 0));
+
+    // The target file, plus three levels of nested data; the data extracted from the third level is not analyzed
+    assert_eq!(
+        analyzed_file_count(&file_path, &["--max-depth", "3"]),
+        (4, 1)
+    );
+
+    // No recursion at all
+    assert_eq!(
+        analyzed_file_count(&file_path, &["--max-depth", "0"]),
+        (1, 1)
+    );
+}
@@ -6,3 +6,10 @@ fn integration_test() {
     const INPUT_FILE_NAME: &str = "mbr.bin";
     common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
 }
+
+#[test]
+fn integration_test_extended() {
+    const SIGNATURE_TYPE: &str = "mbr";
+    const INPUT_FILE_NAME: &str = "mbr_extended.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
@@ -0,0 +1,36 @@
+use binwalk_ng::extractors::{Extractor, ExtractorType};
+use binwalk_ng::formats;
+
+/// Runs an internal extractor's dry run at an offset past the end of the data
+fn extract_out_of_range(extractor: Extractor) -> bool {
+    let ExtractorType::Internal(extract) = extractor.utility else {
+        panic!("not an internal extractor");
+    };
+
+    let file_data = vec![0; 0x200];
+    extract(&file_data, file_data.len() + 1, None).success
+}
+
+/// Internal extractors must fail cleanly, rather than panic, when given an offset beyond the end of the data
+#[test]
+fn extractors_reject_out_of_range_offsets() {
+    let extractors = [
+        formats::arcadyan::obfuscated_lzma_extractor(),
+        formats::autel::autel_extractor(),
+        formats::riff::riff_extractor(),
+        formats::romfs::romfs_extractor(),
+        formats::gif::gif_extractor(),
+        formats::gzip::gzip_extractor(),
+        formats::dtb::dtb_extractor(),
+        formats::androidsparse::android_sparse_extractor(),
+        formats::jpeg::jpeg_extractor(),
+        formats::zstd::zstd_extractor(),
+        formats::lz4::lz4_extractor(),
+        formats::bzip2::bzip2_extractor(),
+        formats::compressd::compressd_extractor(),
+    ];
+
+    for extractor in extractors {
+        assert!(!extract_out_of_range(extractor));
+    }
+}
@@ -15,3 +15,10 @@ fn integration_test() {
         expected_extraction_offsets,
     );
 }
+
+#[test]
+fn integration_test_incremental_update() {
+    const SIGNATURE_TYPE: &str = "pdf";
+    const INPUT_FILE_NAME: &str = "pdf_incremental.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
@@ -5,6 +5,23 @@ fn integration_test() {
     const SIGNATURE_TYPE: &str = "qcow";
     const INPUT_FILE_NAME: &str = "qcow.bin";
 
+    let expected_signature_offsets: Vec<usize> = vec![0];
+    let expected_extraction_offsets: Vec<usize> = vec![0];
+
+    let results = common::run_binwalk(SIGNATURE_TYPE, INPUT_FILE_NAME);
+    common::assert_results_ok(
+        results,
+        expected_signature_offsets,
+        expected_extraction_offsets,
+    );
+}
+
+#[test]
+fn integration_test_backing_file() {
+    const SIGNATURE_TYPE: &str = "qcow";
+    const INPUT_FILE_NAME: &str = "qcow_backing_file.bin";
+
+    // Images with a backing file are identified, but not extracted
     let expected_signature_offsets: Vec<usize> = vec![0];
     let expected_extraction_offsets: Vec<usize> = vec![];
 
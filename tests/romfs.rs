@@ -6,3 +6,10 @@ fn integration_test() {
     const INPUT_FILE_NAME: &str = "romfs.bin";
     common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
 }
+
+#[test]
+fn integration_test_hardlink() {
+    const SIGNATURE_TYPE: &str = "romfs";
+    const INPUT_FILE_NAME: &str = "romfs_hardlink.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
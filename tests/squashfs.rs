@@ -6,3 +6,58 @@ fn integration_test() {
     const INPUT_FILE_NAME: &str = "squashfs.bin";
     common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
 }
+
+#[test]
+fn dual_bank_test() {
+    let image = std::fs::read(
+        std::path::Path::new("tests")
+            .join("inputs")
+            .join("squashfs.bin"),
+    )
+    .unwrap();
+
+    let binwalker = binwalk_ng::Binwalk::configure(None, None, vec![], vec![], None, false)
+        .expect("Binwalk initialization failed");
+
+    // The image is padded out past the end of the file system
+    let image_size = binwalker.scan(&image)[0].size;
+    let image = &image[..image_size];
+    let dual_bank = [image, image].concat();
+
+    let results = binwalker.scan(&dual_bank);
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].sibling_offset, None);
+    assert_eq!(results[1].offset, image.len());
+    assert_eq!(results[1].sibling_offset, Some(0));
+}
+
+#[test]
+fn different_types_are_not_siblings_test() {
+    let image = std::fs::read(
+        std::path::Path::new("tests")
+            .join("inputs")
+            .join("squashfs.bin"),
+    )
+    .unwrap();
+    let gzip_image = std::fs::read(
+        std::path::Path::new("tests")
+            .join("inputs")
+            .join("gzip.bin"),
+    )
+    .unwrap();
+
+    let binwalker = binwalk_ng::Binwalk::configure(None, None, vec![], vec![], None, false)
+        .expect("Binwalk initialization failed");
+
+    // A file system immediately followed by unrelated compressed data is not a dual-bank layout
+    let image_size = binwalker.scan(&image)[0].size;
+    let file_data = [&image[..image_size], gzip_image.as_slice()].concat();
+
+    let results = binwalker.scan(&file_data);
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[1].name, "gzip");
+    assert_eq!(results[1].offset, image_size);
+    assert_eq!(results[1].sibling_offset, None);
+}
@@ -13,3 +13,10 @@ fn integration_test_s6() {
     const INPUT_FILE_NAME: &str = "srec_s6.hex";
     common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
 }
+
+#[test]
+fn integration_test_bad_checksum() {
+    const SIGNATURE_TYPE: &str = "srecord";
+    const INPUT_FILE_NAME: &str = "srec_bad_checksum.hex";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
@@ -14,6 +14,22 @@ fn integration_test() {
     common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
 }
 
+/// As above, for a GNU archive of device nodes and fifos.
+#[test]
+fn integration_test_special_files() {
+    const SIGNATURE_TYPE: &str = "tarball";
+    const INPUT_FILE_NAME: &str = "tarball_special.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
+/// As above, for a GNU archive with a long name ('L') entry.
+#[test]
+fn integration_test_long_name() {
+    const SIGNATURE_TYPE: &str = "tarball";
+    const INPUT_FILE_NAME: &str = "tarball_long_name.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
 /// End-to-end extraction test that pins the extracted file tree *and* its contents.
 ///
 /// This is the regression guard for swapping out the external `tar` extractor: it
@@ -0,0 +1,65 @@
+use binwalk_ng::Binwalk;
+use binwalk_ng::magic::patterns;
+
+/// Run Binwalk, with extraction, against a temporary file containing the provided data
+fn assert_nothing_found(data: &[u8], full_search: bool) {
+    let mut tmp = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut tmp, data).unwrap();
+
+    let output_directory = tempfile::tempdir().unwrap();
+    let binwalker = Binwalk::configure(
+        Some(tmp.path()),
+        Some(output_directory.as_ref()),
+        vec![],
+        vec![],
+        None,
+        full_search,
+    )
+    .expect("Binwalk initialization failed");
+
+    let results = binwalker.analyze(&binwalker.base_target_file, true);
+
+    assert!(results.file_map.is_empty(), "expected no signature results");
+    assert!(results.extractions.is_empty(), "expected no extractions");
+}
+
+#[test]
+fn empty_file() {
+    assert_nothing_found(&[], false);
+    assert_nothing_found(&[], true);
+}
+
+#[test]
+fn single_byte_file() {
+    for byte in [0x00, 0x1F, 0x7F, 0xFF] {
+        assert_nothing_found(&[byte], false);
+        assert_nothing_found(&[byte], true);
+    }
+}
+
+#[test]
+fn few_byte_files() {
+    assert_nothing_found(b"\x1F\x8B", true);
+    assert_nothing_found(b"\x1F\x8B\x08\x00", true);
+    assert_nothing_found(b"PK\x03\x04", true);
+    assert_nothing_found(b"ustar\x00", true);
+}
+
+#[test]
+fn truncated_magic_bytes() {
+    let binwalker = Binwalk::configure(None, None, vec![], vec![], None, true)
+        .expect("Binwalk initialization failed");
+
+    // Magic bytes at (or very near) the start of the data, with nothing after them, must not panic any parser
+    for signature in patterns() {
+        for magic in &signature.magic {
+            for prefix_len in 0..2 {
+                let mut data = vec![0; prefix_len];
+                data.extend_from_slice(magic);
+                binwalker.scan(&data);
+                data.push(0);
+                binwalker.scan(&data);
+            }
+        }
+    }
+}
@@ -0,0 +1,15 @@
+mod common;
+
+#[test]
+fn integration_test() {
+    const SIGNATURE_TYPE: &str = "trx";
+    const INPUT_FILE_NAME: &str = "trx.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
+#[test]
+fn integration_test_v2() {
+    const SIGNATURE_TYPE: &str = "trx";
+    const INPUT_FILE_NAME: &str = "trx_v2.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
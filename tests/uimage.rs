@@ -0,0 +1,8 @@
+mod common;
+
+#[test]
+fn integration_test() {
+    const SIGNATURE_TYPE: &str = "uimage";
+    const INPUT_FILE_NAME: &str = "uimage.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
@@ -0,0 +1,74 @@
+use binwalk_ng::Binwalk;
+use binwalk_ng::extractors::{ExtractionResult, Extractor, ExtractorType};
+use binwalk_ng::signatures::{
+    CONFIDENCE_HIGH, CONFIDENCE_LOW, Signature, SignatureError, SignatureResult,
+};
+
+/// Claims every magic match is 8 bytes in size, without validating anything
+fn speculative_parser(_file_data: &[u8], offset: usize) -> Result<SignatureResult, SignatureError> {
+    Ok(SignatureResult {
+        offset,
+        size: 8,
+        description: "Speculative data".to_string(),
+        confidence: CONFIDENCE_HIGH,
+        ..Default::default()
+    })
+}
+
+/// Accepts data only if the byte following the magic is zero, and reports a size of 4 bytes
+fn strict_extractor(
+    file_data: &[u8],
+    offset: usize,
+    _output_directory: Option<&std::path::Path>,
+) -> ExtractionResult {
+    ExtractionResult {
+        success: file_data.get(offset + 2) == Some(&0),
+        size: Some(4),
+        ..Default::default()
+    }
+}
+
+fn speculative_signature() -> Signature {
+    Signature {
+        name: "speculative".to_string(),
+        short: false,
+        magic_offset: 0,
+        magic_alignment: None,
+        always_display: false,
+        magic: vec![b"\xF0\x0D".to_vec()],
+        parser: speculative_parser,
+        description: "Speculative data".to_string(),
+        extractor: Some(Extractor {
+            utility: ExtractorType::Internal(strict_extractor),
+            ..Default::default()
+        }),
+    }
+}
+
+#[test]
+fn verify_adjusts_size_and_confidence() {
+    let file_data = b"\xF0\x0D\x00\x00\x00\x00\x00\x00\xF0\x0D\xFF\xFF\xFF\xFF\xFF\xFF";
+
+    let mut binwalker = Binwalk::configure(
+        None,
+        None,
+        vec!["speculative".to_string()],
+        vec![],
+        Some(vec![speculative_signature()]),
+        false,
+    )
+    .expect("Binwalk initialization failed");
+    binwalker.verify_signatures = true;
+
+    let results = binwalker.analyze_buf(file_data, "verify.bin", false);
+
+    assert_eq!(results.file_map.len(), 2);
+
+    // First match passes verification; its size is refined
+    assert_eq!(results.file_map[0].size, 4);
+    assert_eq!(results.file_map[0].confidence, CONFIDENCE_HIGH);
+
+    // Second match fails verification; its confidence is downgraded
+    assert_eq!(results.file_map[1].size, 8);
+    assert_eq!(results.file_map[1].confidence, CONFIDENCE_LOW);
+}
@@ -0,0 +1,15 @@
+mod common;
+
+#[test]
+fn integration_test() {
+    const SIGNATURE_TYPE: &str = "vmdk";
+    const INPUT_FILE_NAME: &str = "vmdk.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
+
+#[test]
+fn integration_test_descriptor() {
+    const SIGNATURE_TYPE: &str = "vmdk";
+    const INPUT_FILE_NAME: &str = "vmdk_descriptor.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}
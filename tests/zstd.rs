@@ -11,3 +11,10 @@ fn integration_test() {
 fn trailing_data() {
     common::trailing_data_test("zstd", "zstd.bin");
 }
+
+#[test]
+fn integration_test_skippable_frame() {
+    const SIGNATURE_TYPE: &str = "zstd";
+    const INPUT_FILE_NAME: &str = "zstd_skippable.bin";
+    common::integration_test(SIGNATURE_TYPE, INPUT_FILE_NAME);
+}